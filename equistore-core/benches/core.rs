@@ -0,0 +1,206 @@
+//! Benchmarks of the core operations, run them with
+//! `cargo bench --features benchmarks`.
+//!
+//! The sizes used here are representative of a dataset of a few hundred
+//! structures with a handful of atomic species, where each key corresponds to
+//! a pair of species.
+
+#![allow(clippy::needless_return)]
+
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId, black_box};
+
+use equistore_core::internals::{Labels, LabelsBuilder, TensorBlock, TensorMap, TestArray};
+use equistore_core::internals::{save, load};
+
+/// Number of structures in the generated tensor maps
+const N_STRUCTURES: usize = 200;
+/// Number of atoms in each structure
+const N_ATOMS: usize = 20;
+/// Number of atomic species, the tensor maps contain one block for each pair
+/// of species
+const N_SPECIES: usize = 4;
+/// Number of properties in each block
+const N_PROPERTIES: usize = 16;
+
+/// Create `Labels` with the `["structure", "center"]` names and `n_structures`
+/// structures containing `n_atoms` atoms each
+fn samples(n_structures: usize, n_atoms: usize) -> Labels {
+    let mut builder = LabelsBuilder::new(vec!["structure", "center"]);
+    builder.reserve(n_structures * n_atoms);
+    for structure in 0..n_structures {
+        for center in 0..n_atoms {
+            builder.add(&[structure, center]).expect("invalid labels");
+        }
+    }
+    return builder.finish();
+}
+
+/// Create `Labels` with a single `name` and `count` entries
+fn range(name: &str, count: usize) -> Labels {
+    let mut builder = LabelsBuilder::new(vec![name]);
+    for i in 0..count {
+        builder.add(&[i]).expect("invalid labels");
+    }
+    return builder.finish();
+}
+
+/// Create a `TensorMap` with one block for each pair of species, each block
+/// containing one component and gradients with respect to positions
+fn tensor() -> TensorMap {
+    let mut keys = LabelsBuilder::new(vec!["species_center", "species_neighbor"]);
+    for species_center in 0..N_SPECIES {
+        for species_neighbor in 0..N_SPECIES {
+            keys.add(&[species_center, species_neighbor]).expect("invalid labels");
+        }
+    }
+    let keys = keys.finish();
+
+    let n_structures = N_STRUCTURES / N_SPECIES;
+    let samples = Arc::new(samples(n_structures, N_ATOMS));
+    let components = vec![Arc::new(range("m", 3))];
+    let properties = Arc::new(range("n", N_PROPERTIES));
+
+    let mut gradient_samples = LabelsBuilder::new(vec!["sample", "structure", "atom"]);
+    for (sample, entry) in samples.iter().enumerate() {
+        gradient_samples.add(&[sample, entry[0].usize(), entry[1].usize()]).expect("invalid labels");
+    }
+    let gradient_samples = Arc::new(gradient_samples.finish());
+
+    let mut blocks = Vec::new();
+    for _ in 0..keys.count() {
+        let mut block = TensorBlock::new(
+            TestArray::new(vec![samples.count(), 3, N_PROPERTIES]),
+            Arc::clone(&samples),
+            components.clone(),
+            Arc::clone(&properties),
+        ).expect("invalid block");
+
+        block.add_gradient(
+            "positions",
+            TestArray::new(vec![gradient_samples.count(), 3, 3, N_PROPERTIES]),
+            Arc::clone(&gradient_samples),
+            vec![Arc::new(range("direction", 3)), Arc::new(range("m", 3))],
+        ).expect("invalid gradient");
+
+        blocks.push(block);
+    }
+
+    return TensorMap::new(keys, blocks).expect("invalid tensor map");
+}
+
+/// Benchmark the construction of `Labels` and lookup of entries
+fn labels(c: &mut Criterion) {
+    let mut group = c.benchmark_group("labels");
+    for n_structures in [10, 100, 1000] {
+        let count = n_structures * N_ATOMS;
+        group.bench_with_input(BenchmarkId::new("construction", count), &n_structures, |b, &n_structures| {
+            b.iter(|| samples(black_box(n_structures), N_ATOMS));
+        });
+
+        let labels = samples(n_structures, N_ATOMS);
+        let entries = labels.iter().map(|entry| entry.to_vec()).collect::<Vec<_>>();
+        group.bench_with_input(BenchmarkId::new("position", count), &labels, |b, labels| {
+            b.iter(|| {
+                for entry in &entries {
+                    black_box(labels.position(entry));
+                }
+            });
+        });
+
+        let mut frozen = labels.clone();
+        frozen.freeze();
+        group.bench_with_input(BenchmarkId::new("position_frozen", count), &frozen, |b, labels| {
+            b.iter(|| {
+                for entry in &entries {
+                    black_box(labels.position(entry));
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark moving keys to samples and properties
+fn keys_to(c: &mut Criterion) {
+    let tensor = tensor();
+    let species_center = LabelsBuilder::new(vec!["species_center"]).finish();
+    let species_neighbor = LabelsBuilder::new(vec!["species_neighbor"]).finish();
+
+    let mut group = c.benchmark_group("tensor");
+    group.bench_function("keys_to_samples", |b| {
+        b.iter(|| tensor.keys_to_samples(&species_center, true).expect("keys_to_samples failed"));
+    });
+
+    group.bench_function("keys_to_properties", |b| {
+        b.iter(|| tensor.keys_to_properties(&species_neighbor, true).expect("keys_to_properties failed"));
+    });
+    group.finish();
+}
+
+/// Benchmark joining multiple tensor maps along samples and properties, as
+/// done by `equistore.join` in Python: the blocks of all tensor maps are put
+/// in a single tensor map with an additional `"tensor"` key dimension, which
+/// is then moved to the samples or properties.
+fn join(c: &mut Criterion) {
+    let tensor = tensor();
+
+    let mut group = c.benchmark_group("join");
+    for n_tensors in [2, 8] {
+        let mut keys = LabelsBuilder::new(vec!["tensor", "species_center", "species_neighbor"]);
+        let mut blocks = Vec::new();
+        for i in 0..n_tensors {
+            for (key, block) in tensor.keys().iter().zip(tensor.blocks()) {
+                keys.add(&[i, key[0].usize(), key[1].usize()]).expect("invalid labels");
+                blocks.push(block.try_clone().expect("failed to clone block"));
+            }
+        }
+        let to_join = TensorMap::new(keys.finish(), blocks).expect("invalid tensor map");
+        let tensor_dimension = LabelsBuilder::new(vec!["tensor"]).finish();
+
+        group.bench_with_input(BenchmarkId::new("samples", n_tensors), &to_join, |b, to_join| {
+            b.iter(|| to_join.keys_to_samples(&tensor_dimension, true).expect("keys_to_samples failed"));
+        });
+
+        group.bench_with_input(BenchmarkId::new("properties", n_tensors), &to_join, |b, to_join| {
+            b.iter(|| to_join.keys_to_properties(&tensor_dimension, true).expect("keys_to_properties failed"));
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark saving and loading `TensorMap` to and from in-memory buffers
+fn serialization(c: &mut Criterion) {
+    let tensor = tensor();
+
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    save(&mut buffer, &tensor).expect("failed to save");
+    let buffer = buffer.into_inner();
+
+    let mut group = c.benchmark_group("serialization");
+    group.bench_function("save", |b| {
+        b.iter(|| {
+            let mut output = std::io::Cursor::new(Vec::with_capacity(buffer.len()));
+            save(&mut output, &tensor).expect("failed to save");
+            output
+        });
+    });
+
+    group.bench_function("load", |b| {
+        b.iter(|| {
+            let reader = std::io::Cursor::new(&buffer);
+            load(reader, |shape| Ok(TestArray::new(shape))).expect("failed to load")
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    labels,
+    keys_to,
+    join,
+    serialization,
+);
+criterion_main!(benches);
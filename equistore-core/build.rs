@@ -1,6 +1,9 @@
 use std::path::PathBuf;
 
 fn main() {
+    // `cargo fuzz` sets this cfg to enable the entry points in `src/fuzzing.rs`
+    println!("cargo:rustc-check-cfg=cfg(fuzzing)");
+
     let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
 
     let generated_comment = "\
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    equistore_core::fuzzing::load(data);
+    equistore_core::fuzzing::load_first_block(data);
+});
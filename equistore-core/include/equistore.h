@@ -13,6 +13,28 @@
 #include <stdint.h>
 #include <stdlib.h>
 
+/**
+ * Major version of the equistore library corresponding to this header
+ */
+#define EQS_VERSION_MAJOR 0
+
+/**
+ * Minor version of the equistore library corresponding to this header
+ */
+#define EQS_VERSION_MINOR 1
+
+/**
+ * Patch version of the equistore library corresponding to this header
+ */
+#define EQS_VERSION_PATCH 0
+
+/**
+ * Version of the C ABI of equistore corresponding to this header. This number
+ * is incremented every time a backward-incompatible change is made to the
+ * functions or types in the C API.
+ */
+#define EQS_ABI_VERSION 1
+
 /**
  * Status code used when a function succeeded
  */
@@ -34,6 +56,16 @@
  */
 #define EQS_SERIALIZATION_ERROR 3
 
+/**
+ * Status code used when memory allocation failed
+ */
+#define EQS_OUT_OF_MEMORY_ERROR 4
+
+/**
+ * Status code used when an operation was cancelled through `eqs_progress_t`
+ */
+#define EQS_CANCELLED_ERROR 5
+
 /**
  * Status code used when a memory buffer is too small to fit the requested data
  */
@@ -55,6 +87,16 @@
  */
 typedef struct eqs_block_t eqs_block_t;
 
+/**
+ * Opaque type representing a lazily evaluated chain of element-wise
+ * operations on a `eqs_tensormap_t`.
+ *
+ * The lazy tensor map only keeps pointers to the tensor maps used as source
+ * and in binary operations, these tensor maps must stay alive (and must not
+ * be modified) until the lazy tensor map is freed.
+ */
+typedef struct eqs_lazy_tensormap_t eqs_lazy_tensormap_t;
+
 /**
  * Opaque type representing a `TensorMap`.
  */
@@ -70,6 +112,13 @@ typedef struct eqs_tensormap_t eqs_tensormap_t;
  */
 typedef int32_t eqs_status_t;
 
+/**
+ * Function pointer called by equistore to report non-fatal warnings, with
+ * the `message` of the warning as a NULL-terminated UTF-8 string. The message
+ * is only valid during the call to this function.
+ */
+typedef void (*eqs_warning_callback_t)(void *user_data, const char *message);
+
 /**
  * A set of labels used to carry metadata associated with a tensor map.
  *
@@ -104,7 +153,9 @@ typedef struct eqs_labels_t {
    */
   uintptr_t size;
   /**
-   * Number entries in the set of labels
+   * Number entries in the set of labels. Labels without dimensions
+   * (`size == 0`) can contain at most one (empty) entry, in which case
+   * `names` and `values` can be NULL.
    */
   uintptr_t count;
 } eqs_labels_t;
@@ -143,6 +194,11 @@ typedef struct eqs_sample_mapping_t {
  * **WARNING**: all function implementations **MUST** be thread-safe, and can
  * be called from multiple threads at the same time. The `eqs_array_t` itself
  * might be moved from one thread to another.
+ *
+ * **ABI NOTE**: the `strides` field was added after equistore 0.1.0, growing
+ * this struct from 80 to 88 bytes on 64-bit platforms. Code creating
+ * `eqs_array_t` must be compiled against the same version of this header as
+ * the library it is linked to, and initialize all the fields.
  */
 typedef struct eqs_array_t {
   /**
@@ -161,8 +217,8 @@ typedef struct eqs_array_t {
    * Get a pointer to the underlying data storage.
    *
    * This function is allowed to fail if the data is not accessible in RAM,
-   * not stored as 64-bit floating point values, or not stored as a
-   * C-contiguous array.
+   * or not stored as 64-bit floating point values. If the data is not
+   * stored as a C-contiguous array, the `strides` function must be set.
    */
   eqs_status_t (*data)(void *array, double **data);
   /**
@@ -189,7 +245,10 @@ typedef struct eqs_array_t {
    *
    * The new array should be filled with zeros.
    */
-  eqs_status_t (*create)(const void *array, const uintptr_t *shape, uintptr_t shape_count, struct eqs_array_t *new_array);
+  eqs_status_t (*create)(const void *array,
+                         const uintptr_t *shape,
+                         uintptr_t shape_count,
+                         struct eqs_array_t *new_array);
   /**
    * Make a copy of this `array` and return the new array in `new_array`.
    *
@@ -215,9 +274,96 @@ typedef struct eqs_array_t {
    * `array[samples[i].output, ..., property_start:property_end]` for `i` up
    * to `samples_count`. All indexes are 0-based.
    */
-  eqs_status_t (*move_samples_from)(void *output, const void *input, const struct eqs_sample_mapping_t *samples, uintptr_t samples_count, uintptr_t property_start, uintptr_t property_end);
+  eqs_status_t (*move_samples_from)(void *output,
+                                    const void *input,
+                                    const struct eqs_sample_mapping_t *samples,
+                                    uintptr_t samples_count,
+                                    uintptr_t property_start,
+                                    uintptr_t property_end);
+  /**
+   * Get the strides of the array managed by this `eqs_array_t` in the
+   * `*strides` pointer, and the number of dimensions (size of the
+   * `*strides` array) in `*strides_count`.
+   *
+   * Strides are given in number of elements: the entry at `[i, j, ...]` is
+   * stored at `data[i * strides[0] + j * strides[1] + ...]`, where `data` is
+   * the pointer returned by `eqs_array_t.data`.
+   *
+   * This function can be set to `NULL` if the array is always stored as a
+   * C-contiguous array. Arrays created with `eqs_array_t.create` or
+   * `eqs_array_t.copy` are expected to be C-contiguous.
+   */
+  eqs_status_t (*strides)(const void *array, const uintptr_t **strides, uintptr_t *strides_count);
 } eqs_array_t;
 
+/**
+ * Function pointer called by `eqs_tensormap_foreach_block` for each block
+ * of a tensor map.
+ *
+ * The callback gets the `index` of the block, the values of the corresponding
+ * `key` (containing one value for each dimension of the keys), and a pointer
+ * to the `block`. The block memory is managed by the tensor map, and follows
+ * the same rules as blocks obtained with `eqs_tensormap_block_by_id`. If the
+ * callback returns a non-zero `eqs_status_t`, the iteration stops.
+ */
+typedef eqs_status_t (*eqs_block_callback_t)(void *user_data,
+                                             uintptr_t index,
+                                             const int32_t *key,
+                                             struct eqs_block_t *block);
+
+/**
+ * `eqs_progress_t` reports the progress of long-running operations
+ * (serialization and keys restructuring), as the number of blocks processed
+ * so far out of the total number of blocks; and allows to cancel these
+ * operations.
+ *
+ * This can be used to display progress bars while these operations run, and
+ * to abort them cleanly from user interfaces.
+ */
+typedef struct eqs_progress_t {
+  /**
+   * User-provided data should be stored here, it will be passed as the
+   * first parameter to `callback` and `cancelled`.
+   */
+  void *user_data;
+  /**
+   * Function called after each block is processed, with `done` blocks
+   * processed so far out of `total`. If this is `NULL`, progress is not
+   * reported.
+   */
+  void (*callback)(void *user_data, uintptr_t done, uintptr_t total);
+  /**
+   * Function called before each block is processed. If it returns `true`,
+   * the operation stops and returns `EQS_CANCELLED_ERROR`. If this is
+   * `NULL`, the operation can not be cancelled.
+   */
+  bool (*cancelled)(void *user_data);
+} eqs_progress_t;
+
+/**
+ * Estimation of the memory used by some part of a tensor map, in bytes, for
+ * use with `eqs_tensormap_memory_usage`.
+ */
+typedef struct eqs_memory_usage_t {
+  /**
+   * memory used by the values data arrays, assuming they contain 64-bit
+   * floating point values
+   */
+  uintptr_t data;
+  /**
+   * memory used to store the values (and names) of the labels
+   */
+  uintptr_t labels_values;
+  /**
+   * memory used by the hash maps to lookup the position of labels entries
+   */
+  uintptr_t labels_positions;
+  /**
+   * memory used by the gradients, including both data and labels
+   */
+  uintptr_t gradients;
+} eqs_memory_usage_t;
+
 /**
  * Function pointer to create a new `eqs_array_t` when de-serializing tensor
  * maps.
@@ -229,8 +375,111 @@ typedef struct eqs_array_t {
  * The newly created array should contains 64-bit floating points (`double`)
  * data, and live on CPU, since equistore will use `eqs_array_t.data` to get
  * the data pointer and write to it.
+ *
+ * This can be set to `NULL`, in which case the data is loaded in arrays
+ * managed by equistore, which only support access through `eqs_array_t`
+ * functions. This is useful when providing a callback is impractical, e.g.
+ * when using equistore compiled to WebAssembly.
+ */
+typedef eqs_status_t (*eqs_create_array_callback_t)(const uintptr_t *shape,
+                                                    uintptr_t shape_count,
+                                                    struct eqs_array_t *array);
+
+/**
+ * Options controlling how tensor maps are saved, for use with
+ * `eqs_tensormap_save_with_options`.
+ */
+typedef struct eqs_save_options_t {
+  /**
+   * Compress all the labels with zstd, using a dictionary trained on the
+   * labels of the tensor map and shared between all of them. This can
+   * significantly reduce the size of files containing many small blocks,
+   * but the resulting files can not be loaded with numpy.
+   */
+  bool labels_dictionary;
+  /**
+   * Round the values and gradients data to keep only this number of bits
+   * in the mantissa of the 64-bit floating point values (up to 52), and
+   * store them in zstd-compressed `<path>.npy.zst` files. The relative
+   * error introduced by the rounding is at most `2^-(mantissa_bits + 1)`.
+   * Setting this to 0 keeps the data unchanged.
+   */
+  uint32_t mantissa_bits;
+} eqs_save_options_t;
+
+/**
+ * Limits on the size of the data created when loading tensor maps, for use
+ * with `eqs_tensormap_load_with_limits` and
+ * `eqs_tensormap_load_buffer_with_limits`.
+ *
+ * The sizes of the arrays and labels are read from the serialized data, and
+ * can be arbitrarily large in corrupted or malicious files. Data over these
+ * limits is refused before allocating memory for it. Setting any of the
+ * fields to 0 disables the corresponding limit.
+ */
+typedef struct eqs_load_limits_t {
+  /**
+   * Maximal number of entries in any of the labels (keys, samples,
+   * components or properties)
+   */
+  uintptr_t max_labels_entries;
+  /**
+   * Maximal size in bytes of any array (values, gradients or strings)
+   */
+  uintptr_t max_array_bytes;
+  /**
+   * Maximal number of blocks in the tensor map
+   */
+  uintptr_t max_blocks;
+} eqs_load_limits_t;
+
+/**
+ * Function pointer to grow in-memory buffers for `eqs_tensormap_save_buffer`.
+ *
+ * This function takes an existing pointer in `ptr` and a new length in
+ * `new_size`, and grows the allocation. If `ptr` is `NULL`, it should create a
+ * new allocation. If it is unable to allocate memory, it should return a
+ * `NULL` pointer. This follows the API of the standard C function `realloc`,
+ * with an additional parameter `user_data` that can be used to hold custom
+ * data. In particular, the new allocation must start with the data from the
+ * old one.
+ *
+ * This function will be called multiple times while serializing data, every
+ * time the buffer needs to grow.
+ */
+typedef uint8_t *(*eqs_realloc_buffer_t)(void *user_data, uint8_t *ptr, uintptr_t new_size);
+
+/**
+ * `eqs_read_at_t` provides random access to serialized tensor map data, for
+ * use with `eqs_tensormap_load_read_at`.
+ *
+ * This can be implemented on top of remote storage (HTTP servers, object
+ * stores, *etc.*), where each call to `read_at` is a request for a range of
+ * bytes. Only the parts of the data needed to load the requested blocks are
+ * read.
  */
-typedef eqs_status_t (*eqs_create_array_callback_t)(const uintptr_t *shape, uintptr_t shape_count, struct eqs_array_t *array);
+typedef struct eqs_read_at_t {
+  /**
+   * User-provided data should be stored here, it will be passed as the
+   * first parameter to `read_at`.
+   */
+  void *user_data;
+  /**
+   * Total size in bytes of the serialized data
+   */
+  uint64_t size;
+  /**
+   * Read up to `count` bytes starting at `offset` in the serialized data
+   * into `buffer`, and store the number of bytes actually read in `*read`.
+   * `*read` should only be set to 0 when `offset` is at or after the end of
+   * the data.
+   */
+  eqs_status_t (*read_at)(void *user_data,
+                          uint64_t offset,
+                          uint8_t *buffer,
+                          uintptr_t count,
+                          uintptr_t *read);
+} eqs_read_at_t;
 
 #ifdef __cplusplus
 extern "C" {
@@ -246,13 +495,42 @@ extern "C" {
  */
 void eqs_disable_panic_printing(void);
 
+/**
+ * Set the function to call when equistore emits a non-fatal warning,
+ * replacing any previously registered callback. The warnings are ignored by
+ * default, or if `callback` is `NULL`.
+ *
+ * The callback can be called from any thread, and should be thread-safe.
+ *
+ * @param callback function to call with each warning message, or `NULL`
+ * @param user_data custom data for the `callback`. This will be passed as
+ *                  the first argument to `callback` as-is.
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_set_warning_callback(eqs_warning_callback_t callback, void *user_data);
+
 /**
  * Get the version of the core equistore library as a string.
  *
- * This version should follow the `<major>.<minor>.<patch>[-<dev>]` format.
+ * This version should follow the `<major>.<minor>.<patch>[-<dev>]` format,
+ * where `<major>`, `<minor>` and `<patch>` are the values of
+ * `EQS_VERSION_MAJOR`, `EQS_VERSION_MINOR` and `EQS_VERSION_PATCH` in the
+ * header corresponding to this library.
  */
 const char *eqs_version(void);
 
+/**
+ * Get the version of the C ABI implemented by the core equistore library.
+ *
+ * Code loading equistore dynamically (e.g. plugins) should check that this
+ * is equal to the `EQS_ABI_VERSION` of the header they were compiled with
+ * before calling any other function.
+ */
+uint32_t eqs_abi_version(void);
+
 /**
  * Get the last error message that was created on the current thread.
  *
@@ -280,6 +558,59 @@ eqs_status_t eqs_labels_position(struct eqs_labels_t labels,
                                  uintptr_t values_count,
                                  int64_t *result);
 
+/**
+ * Get a description of the entries in `labels` as a numpy structured dtype,
+ * allowing to view the labels values as an array of records with named
+ * fields without copying them. This operation is only available if the labels
+ * correspond to a set of Rust Labels (i.e. `labels.internal_ptr_` is not
+ * NULL).
+ *
+ * The dtype is written to `buffer` in the format used by numpy's
+ * `__array_interface__["descr"]`, e.g. `[('structure', '<i4'), ('center',
+ * '<i4')]`, with one native-endian 32-bit integer field per dimension.
+ *
+ * @param labels set of labels with an associated Rust data structure
+ * @param buffer buffer to be filled with the dtype description, as an UTF-8
+ *               encoded, NULL-terminated string
+ * @param buffer_size size of the buffer
+ * @param values will be set to a pointer to the first entry in the labels, or
+ *               NULL if the labels are empty. Entries are packed one after the
+ *               other, each one taking `4 * labels.size` bytes.
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_labels_structured_dtype(struct eqs_labels_t labels,
+                                         char *buffer,
+                                         uintptr_t buffer_size,
+                                         const int32_t **values);
+
+/**
+ * Get the positions of multiple entries in the given set of `labels`, doing
+ * all the lookups in a single call. This operation is only available if the
+ * labels correspond to a set of Rust Labels (i.e. `labels.internal_ptr_` is
+ * not NULL).
+ *
+ * @param labels set of labels with an associated Rust data structure
+ * @param queries pointer to the first element of a 2D row-major array
+ *                containing the entries to lookup. Each row should contain
+ *                `labels.size` elements, and there are `queries_count` rows
+ *                in total.
+ * @param queries_count number of entries to lookup
+ * @param results pointer to an array of size `queries_count`, which will be
+ *                filled with the position of each entry in the labels, or -1
+ *                if the entry was not found
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_labels_positions(struct eqs_labels_t labels,
+                                  const int32_t *queries,
+                                  uintptr_t queries_count,
+                                  int64_t *results);
+
 /**
  * Finish the creation of `eqs_labels_t` by associating it to Rust-owned
  * labels.
@@ -287,6 +618,10 @@ eqs_status_t eqs_labels_position(struct eqs_labels_t labels,
  * This allows using the `eqs_labels_positions` and `eqs_labels_clone`
  * functions on the `eqs_labels_t`.
  *
+ * All the `labels.count` entries in `labels.values` are validated (checking
+ * the names and that there are no duplicated entries) and copied in a single
+ * operation, so this function can be used to create large labels efficiently.
+ *
  * This function allocates memory which must be released `eqs_labels_free` when
  * you don't need it anymore.
  *
@@ -353,6 +688,52 @@ eqs_status_t eqs_register_data_origin(const char *name, eqs_data_origin_t *origi
  */
 eqs_status_t eqs_get_data_origin(eqs_data_origin_t origin, char *buffer, uintptr_t buffer_size);
 
+/**
+ * Register a new array backend with the given `name`, using the functions in
+ * `vtable` to manipulate the arrays. This also registers a data origin with
+ * the same `name`, which is returned in `origin`.
+ *
+ * Arrays using the registered functions can then be created with
+ * `eqs_array_from_backend`, giving only the pointer to the backend-specific
+ * data. This allows foreign runtimes to provide their own storage, which will
+ * be used by all operations in equistore.
+ *
+ * Calling this function multiple times with the same name replaces the
+ * previously registered functions.
+ *
+ * @param name name of the backend as an UTF-8 encoded NULL-terminated string
+ * @param vtable array containing the functions of the backend. The `ptr` of
+ *               this array is ignored, and all functions except `destroy`
+ *               must be set.
+ * @param origin pointer to an `eqs_data_origin_t` where the origin associated
+ *               with this backend will be stored
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_register_array_backend(const char *name,
+                                        const struct eqs_array_t *vtable,
+                                        eqs_data_origin_t *origin);
+
+/**
+ * Create a new array managing `ptr`, using the functions registered for
+ * `origin` with `eqs_register_array_backend`.
+ *
+ * The new array takes ownership of `ptr`, and will release it with the
+ * `destroy` function of the backend. If this function fails, `ptr` is not
+ * released.
+ *
+ * @param origin data origin of a registered array backend
+ * @param ptr backend-specific data for the new array
+ * @param array pointer to an `eqs_array_t` that will be set to the new array
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_array_from_backend(eqs_data_origin_t origin, void *ptr, struct eqs_array_t *array);
+
 /**
  * Create a new `eqs_block_t` with the given `data` and `samples`, `components`
  * and `properties` labels.
@@ -437,6 +818,10 @@ eqs_status_t eqs_block_labels(const struct eqs_block_t *block,
  * The `values_gradients` parameter controls whether this function looks up
  * labels for `"values"` or one of the gradients in this block.
  *
+ * Arrays can be shared between multiple blocks after a copy, in which case
+ * this function will make a separate copy of the array for this block, since
+ * the caller can modify the data through the returned handle.
+ *
  * @param block pointer to an existing block
  * @param values_gradients either `"values"` or the name of gradients to lookup
  * @param data pointer to an empty `eqs_array_t` that will be set to the
@@ -477,6 +862,27 @@ eqs_status_t eqs_block_add_gradient(struct eqs_block_t *block,
                                     const struct eqs_labels_t *components,
                                     uintptr_t components_count);
 
+/**
+ * Register the convention for gradients with respect to `parameter`. All
+ * gradients with respect to this parameter added afterward with
+ * `eqs_block_add_gradient` must start with the given `components`, followed by
+ * the components of the values. Registering the same parameter multiple times
+ * replaces the previous convention.
+ *
+ * @param parameter name of the gradient parameter as a NULL-terminated UTF-8
+ *                  string (e.g. `"strain"`)
+ * @param components array of component labels that gradients with respect to
+ *                   this parameter must have before the values components
+ * @param components_count number of entries in the `components` array
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_register_gradient_parameter(const char *parameter,
+                                             const struct eqs_labels_t *components,
+                                             uintptr_t components_count);
+
 /**
  * Get a list of all gradients defined in this `block` in the `parameters` array.
  *
@@ -494,6 +900,136 @@ eqs_status_t eqs_block_gradients_list(const struct eqs_block_t *block,
                                       const char *const **parameters,
                                       uintptr_t *parameters_count);
 
+/**
+ * Get the physical unit of the values in this `block` (e.g. `"eV"`).
+ *
+ * @param block pointer to an existing block
+ * @param unit will be set to a NULL-terminated UTF-8 string containing the
+ *             unit, or to `NULL` if the unit of this block is not set. The
+ *             string is owned by the block, and only valid until the unit
+ *             of this block is changed or the block is freed.
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_block_unit(const struct eqs_block_t *block, const char **unit);
+
+/**
+ * Set the physical unit of the values in this `block`.
+ *
+ * The unit is preserved by copies and serialization, and used to check the
+ * consistency of arithmetic operations.
+ *
+ * @param block pointer to an existing block
+ * @param unit NULL-terminated UTF-8 string containing the new unit, or `NULL`
+ *             to remove the unit of this block
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_block_set_unit(struct eqs_block_t *block, const char *unit);
+
+/**
+ * Get the physical quantity represented by the values in this `block` (e.g.
+ * `"energy"`).
+ *
+ * @param block pointer to an existing block
+ * @param quantity will be set to a NULL-terminated UTF-8 string containing
+ *                 the quantity, or to `NULL` if the quantity of this block is
+ *                 not set. The string is owned by the block, and only valid
+ *                 until the quantity of this block is changed or the block
+ *                 is freed.
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_block_quantity(const struct eqs_block_t *block, const char **quantity);
+
+/**
+ * Set the physical quantity represented by the values in this `block`.
+ *
+ * The quantity is preserved by copies and serialization, and used to check
+ * the consistency of arithmetic operations.
+ *
+ * @param block pointer to an existing block
+ * @param quantity NULL-terminated UTF-8 string containing the new quantity,
+ *                 or `NULL` to remove the quantity of this block
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_block_set_quantity(struct eqs_block_t *block, const char *quantity);
+
+/**
+ * Set all the values in this `block` to `value`. The gradients are left
+ * unchanged.
+ *
+ * @param block pointer to an existing block
+ * @param value new value for all entries in the block values
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_block_fill(struct eqs_block_t *block, double value);
+
+/**
+ * Copy the values and gradients data from `other` into this `block`.
+ *
+ * Both blocks must have the same samples, components and properties labels,
+ * and the same set of gradients with the same labels.
+ *
+ * @param block pointer to an existing block, which will be modified
+ * @param other pointer to an existing block containing the data to copy
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_block_copy_from(struct eqs_block_t *block, const struct eqs_block_t *other);
+
+/**
+ * Swap the samples and properties of this `block`, transposing the values
+ * data. The components stay in the same order.
+ *
+ * Blocks with gradients can not be transposed, since the gradient samples
+ * refer to the samples of the values.
+ *
+ * @param block pointer to an existing block, which will be modified
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_block_transpose(struct eqs_block_t *block);
+
+/**
+ * Set the values of this `block` for the samples at the given positions to
+ * `data`. The gradients are left unchanged.
+ *
+ * @param block pointer to an existing block
+ * @param samples positions of the samples to modify in the block
+ * @param samples_count number of entries in the `samples` array
+ * @param data new values for the selected samples, in row-major order. This
+ *             must contain `samples_count` times the number of values for a
+ *             single sample (i.e. the product of the size of all components
+ *             and properties).
+ * @param data_count number of entries in the `data` array
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_block_assign_rows(struct eqs_block_t *block,
+                                   const uintptr_t *samples,
+                                   uintptr_t samples_count,
+                                   const double *data,
+                                   uintptr_t data_count);
+
 /**
  * Create a new `eqs_tensormap_t` with the given `keys` and `blocks`.
  * `blocks_count` must be set to the number of entries in the blocks array.
@@ -544,6 +1080,31 @@ eqs_status_t eqs_tensormap_free(struct eqs_tensormap_t *tensor);
  */
 struct eqs_tensormap_t *eqs_tensormap_copy(const struct eqs_tensormap_t *tensor);
 
+/**
+ * Create a new `eqs_tensormap_t` with the same keys, labels and gradients as
+ * `tensor`, using the given `arrays` as the values of the blocks. This can be
+ * used to re-create the same tensor map structure around new data without
+ * copying any of the metadata.
+ *
+ * The new tensor map takes ownership of all the arrays, which will be
+ * released with `array.destroy(array.ptr)` when they are no longer needed,
+ * including if this function fails. The memory allocated by this function
+ * should be released using `eqs_tensormap_free`.
+ *
+ * @param tensor existing tensor map
+ * @param arrays arrays containing the new values, one for each block of the
+ *               tensor map, in the same order as the blocks. Each array must
+ *               have the same shape as the corresponding block values.
+ * @param arrays_count number of elements in the `arrays` array
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer
+ *          in case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_tensormap_with_values(const struct eqs_tensormap_t *tensor,
+                                                  struct eqs_array_t *arrays,
+                                                  uintptr_t arrays_count);
+
 /**
  * Get the keys for the given `tensor` map.
  *
@@ -573,12 +1134,35 @@ eqs_status_t eqs_tensormap_keys(const struct eqs_tensormap_t *tensor, struct eqs
  *
  * @returns The status code of this operation. If the status is not
  *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
- *          error message.
+ *          error message. If `index` is out of bounds, this returns
+ *          `EQS_INVALID_PARAMETER_ERROR`.
  */
 eqs_status_t eqs_tensormap_block_by_id(struct eqs_tensormap_t *tensor,
                                        struct eqs_block_t **block,
                                        uintptr_t index);
 
+/**
+ * Call `callback` for each block in this `tensor` map, in the same order as
+ * the keys. This gives access to all blocks with a single function call,
+ * instead of calling `eqs_tensormap_keys` and `eqs_tensormap_block_by_id`
+ * repeatedly.
+ *
+ * The tensor map must not be modified by `callback`.
+ *
+ * @param tensor pointer to an existing tensor map
+ * @param callback function to call for each block
+ * @param user_data custom data for the `callback`. This will be passed as
+ *                  the first argument to `callback` as-is.
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message. If `callback` returns a non-zero status, this
+ *          function returns the same status.
+ */
+eqs_status_t eqs_tensormap_foreach_block(struct eqs_tensormap_t *tensor,
+                                         eqs_block_callback_t callback,
+                                         void *user_data);
+
 /**
  * Get indices of the blocks in this `tensor` corresponding to the given
  * `selection`. The `selection` should have a subset of the names/dimensions of
@@ -606,22 +1190,91 @@ eqs_status_t eqs_tensormap_blocks_matching(const struct eqs_tensormap_t *tensor,
                                            struct eqs_labels_t selection);
 
 /**
- * Merge blocks with the same value for selected keys dimensions along the
- * property axis.
+ * Get a view over the blocks in this `tensor` matching the given `selection`,
+ * using the same rules as `eqs_tensormap_blocks_matching`. The blocks are not
+ * copied, making this a cheap way to iterate over subsets of a large tensor
+ * map.
+ *
+ * The keys of the selected blocks are stored in `keys`, which contain all the
+ * dimensions of the tensor map keys. This function allocates memory for
+ * `keys` which must be released with `eqs_labels_free` when you don't need it
+ * anymore.
+ *
+ * When calling this function, `*count` should contain the number of entries
+ * in `blocks`, which must be the same as the number of blocks in the tensor
+ * map. When the function returns successfully, `*count` will contain the
+ * number of blocks matching the selection, i.e. how many pointers were
+ * written to `blocks`. These blocks follow the same rules as blocks obtained
+ * with `eqs_tensormap_block_by_id`: their memory is managed by the tensor map
+ * and they should not be freed.
  *
- * The dimensions (names) of `keys_to_move` will be moved from the keys to
- * the property labels, and blocks with the same remaining keys dimensions
- * will be merged together along the property axis.
+ * @param tensor pointer to an existing tensor map
+ * @param selection labels with a single entry describing which blocks are
+ *                  requested
+ * @param keys pointer to be filled with the keys of the selected blocks
+ * @param blocks array to be filled with pointers to the selected blocks
+ * @param count number of entries in `blocks`
  *
- * If `keys_to_move` does not contains any entries (`keys_to_move.count
- * == 0`), then the new property labels will contain entries corresponding
- * to the merged blocks only. For example, merging a block with key `a=0`
- * and properties `p=1, 2` with a block with key `a=2` and properties `p=1,
- * 3` will produce a block with properties `a, p = (0, 1), (0, 2), (2, 1),
- * (2, 3)`.
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_view(struct eqs_tensormap_t *tensor,
+                                struct eqs_labels_t selection,
+                                struct eqs_labels_t *keys,
+                                struct eqs_block_t **blocks,
+                                uintptr_t *count);
+
+/**
+ * Compress in memory the data of all blocks in this `tensor` matching the
+ * given `selection`, using the same rules as `eqs_tensormap_blocks_matching`.
  *
- * If `keys_to_move` contains entries, then the property labels must be the
- * same for all the merged blocks. In that case, the merged property labels
+ * The data of compressed blocks is stored with zstd, and is decompressed the
+ * first time it is accessed through `eqs_array_t.data`. Compressed arrays use
+ * a separate data origin, use `eqs_tensormap_decompress_blocks` to convert
+ * them back to the original array type.
+ *
+ * @param tensor pointer to an existing tensor map
+ * @param selection labels with a single entry describing which blocks should
+ *                  be compressed
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_compress_blocks(struct eqs_tensormap_t *tensor,
+                                           struct eqs_labels_t selection);
+
+/**
+ * Convert the data of all blocks compressed with
+ * `eqs_tensormap_compress_blocks` in this `tensor` back to the original array
+ * type.
+ *
+ * @param tensor pointer to an existing tensor map
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_decompress_blocks(struct eqs_tensormap_t *tensor);
+
+/**
+ * Merge blocks with the same value for selected keys dimensions along the
+ * property axis.
+ *
+ * The dimensions (names) of `keys_to_move` will be moved from the keys to
+ * the property labels, and blocks with the same remaining keys dimensions
+ * will be merged together along the property axis.
+ *
+ * If `keys_to_move` does not contains any entries (`keys_to_move.count
+ * == 0`), then the new property labels will contain entries corresponding
+ * to the merged blocks only. For example, merging a block with key `a=0`
+ * and properties `p=1, 2` with a block with key `a=2` and properties `p=1,
+ * 3` will produce a block with properties `a, p = (0, 1), (0, 2), (2, 1),
+ * (2, 3)`.
+ *
+ * If `keys_to_move` contains entries, then the property labels must be the
+ * same for all the merged blocks. In that case, the merged property labels
  * will contains each of the entries of `keys_to_move` and then the current
  * property labels. For example, using `a=2, 3` in `keys_to_move`, and
  * blocks with properties `p=1, 2` will result in `a, p = (2, 1), (2, 2),
@@ -648,6 +1301,26 @@ struct eqs_tensormap_t *eqs_tensormap_keys_to_properties(const struct eqs_tensor
                                                          struct eqs_labels_t keys_to_move,
                                                          bool sort_samples);
 
+/**
+ * Same as `eqs_tensormap_keys_to_properties`, reporting the number of new blocks
+ * created so far with `progress`.
+ *
+ * @param tensor pointer to an existing tensor map
+ * @param keys_to_move description of the keys to move
+ * @param sort_samples whether to sort the samples lexicographically after
+ *                     merging blocks
+ * @param progress callbacks used to report the progress of this operation
+ *                 and to cancel it
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_tensormap_keys_to_properties_with_progress(const struct eqs_tensormap_t *tensor,
+                                                                       struct eqs_labels_t keys_to_move,
+                                                                       bool sort_samples,
+                                                                       struct eqs_progress_t progress);
+
 /**
  * Move the given dimensions from the component labels to the property labels
  * for each block in this tensor map.
@@ -701,6 +1374,407 @@ struct eqs_tensormap_t *eqs_tensormap_keys_to_samples(const struct eqs_tensormap
                                                       struct eqs_labels_t keys_to_move,
                                                       bool sort_samples);
 
+/**
+ * Same as `eqs_tensormap_keys_to_samples`, reporting the number of new blocks
+ * created so far with `progress`.
+ *
+ * @param tensor pointer to an existing tensor map
+ * @param keys_to_move description of the keys to move
+ * @param sort_samples whether to sort the samples lexicographically after
+ *                     merging blocks
+ * @param progress callbacks used to report the progress of this operation
+ *                 and to cancel it
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_tensormap_keys_to_samples_with_progress(const struct eqs_tensormap_t *tensor,
+                                                                    struct eqs_labels_t keys_to_move,
+                                                                    bool sort_samples,
+                                                                    struct eqs_progress_t progress);
+
+/**
+ * Get a canonical version of this `tensor` map, where the keys are sorted in
+ * lexicographic order, and the samples and properties of each block (and the
+ * samples of each gradient) are sorted as well. The data arrays are re-ordered
+ * accordingly.
+ *
+ * Two tensor maps containing the same data in different order will give the
+ * same result after canonicalization, and will be saved to the same bytes by
+ * `eqs_tensormap_save`. Components are not re-ordered.
+ *
+ * The result is a new tensor map, which should be freed with `eqs_tensormap_free`.
+ *
+ * @param tensor pointer to an existing tensor map
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_tensormap_canonicalize(const struct eqs_tensormap_t *tensor);
+
+/**
+ * Get a new tensor map where the blocks of `tensor` are sorted according to
+ * the values of the key dimensions in `names`.
+ *
+ * The first dimension in `names` has the highest priority, and later
+ * dimensions are only used to break ties. Blocks with the same values for all
+ * the dimensions in `names` keep their relative order. If `descending` is
+ * `true`, the blocks are sorted in descending order instead.
+ *
+ * The permutation applied to the blocks is stored in `permutation`, which
+ * must contain space for as many entries as there are keys in the tensor map:
+ * the block at index `i` in the new tensor map corresponds to the block at
+ * index `permutation[i]` in `tensor`.
+ *
+ * The result is a new tensor map, which should be freed with `eqs_tensormap_free`.
+ *
+ * @param tensor pointer to an existing tensor map
+ * @param names names of the key dimensions to use for sorting, as an array
+ *              of `names_count` NULL-terminated UTF-8 strings
+ * @param names_count number of entries in the `names` array
+ * @param descending whether to sort the blocks in descending order
+ * @param permutation array to be filled with the permutation of the blocks
+ * @param permutation_count number of entries in the `permutation` array
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_tensormap_sort_keys(const struct eqs_tensormap_t *tensor,
+                                                const char *const *names,
+                                                uintptr_t names_count,
+                                                bool descending,
+                                                uintptr_t *permutation,
+                                                uintptr_t permutation_count);
+
+/**
+ * Get a new tensor map containing all the blocks of `tensor`, except for the
+ * blocks matching the given `selection`.
+ *
+ * The `selection` should have a subset of the names/dimensions of the keys of
+ * the tensor map, and a single entry. All the blocks matching this entry are
+ * removed, and the keys of the new tensor map only contain the entries for
+ * the remaining blocks.
+ *
+ * The result is a new tensor map, which should be freed with `eqs_tensormap_free`.
+ *
+ * @param tensor pointer to an existing tensor map
+ * @param selection labels with a single entry describing which blocks should
+ *                  be removed
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_tensormap_drop_blocks(const struct eqs_tensormap_t *tensor,
+                                                  struct eqs_labels_t selection);
+
+/**
+ * Compute a stable 64-bit fingerprint of this `tensor` map, and store it in
+ * `fingerprint`.
+ *
+ * The fingerprint covers all the metadata (keys, samples, components,
+ * properties, gradients, units, quantities and tensor map metadata) and the
+ * shape of all arrays. If `include_data` is `true`, the values stored in the
+ * arrays are also part of the fingerprint.
+ *
+ * The fingerprint does not depend on the platform, the process or the version
+ * of equistore, and can be used as a cache key. It does depend on the order of
+ * entries in the labels, use `eqs_tensormap_canonicalize` first to ignore it.
+ *
+ * @param tensor pointer to an existing tensor map
+ * @param include_data whether to include the values of the arrays in the
+ *                     fingerprint
+ * @param fingerprint pointer to be filled with the fingerprint
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_fingerprint(const struct eqs_tensormap_t *tensor,
+                                       bool include_data,
+                                       uint64_t *fingerprint);
+
+/**
+ * Compare the two tensor maps `first` and `second`, matching blocks by key,
+ * and report the differences in metadata, keys, units, labels, shapes and
+ * data.
+ *
+ * `equal` will be set to `true` if no difference was found. If `buffer` is not
+ * `NULL`, a text rendering of all the differences is written to it as an UTF-8
+ * encoded, NULL-terminated string.
+ *
+ * @param first pointer to an existing tensor map
+ * @param second pointer to another existing tensor map
+ * @param compare_data whether to compare the values inside the arrays
+ * @param tolerance absolute tolerance used when comparing the data
+ * @param equal pointer to be filled with the result of the comparison
+ * @param buffer buffer to be filled with the text report, or `NULL`
+ * @param buffer_size size of the buffer
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_diff(const struct eqs_tensormap_t *first,
+                                const struct eqs_tensormap_t *second,
+                                bool compare_data,
+                                double tolerance,
+                                bool *equal,
+                                char *buffer,
+                                uintptr_t buffer_size);
+
+/**
+ * Compare the two tensor maps `first` and `second`, matching blocks by key,
+ * and write a structured report of the differences to `buffer` as an UTF-8
+ * encoded, NULL-terminated JSON string.
+ *
+ * The JSON document is an object with the `metadata`, `keys_names`,
+ * `only_in_first`, `only_in_second` and `blocks` fields. Each entry in
+ * `blocks` contains the `key` of the block, the gradient `parameter` (or
+ * `"values"`) and a `difference` object, with a `kind` field set to one of
+ * `"unit"`, `"quantity"`, `"missing_gradient"`, `"samples"`, `"components"`,
+ * `"properties"`, `"shape"` or `"data"`. All the lists are empty if no
+ * difference was found.
+ *
+ * If the buffer is too small to hold the report, this function returns
+ * `EQS_BUFFER_SIZE_ERROR`, and it should be called again with a larger buffer.
+ *
+ * @param first pointer to an existing tensor map
+ * @param second pointer to another existing tensor map
+ * @param compare_data whether to compare the values inside the arrays
+ * @param tolerance absolute tolerance used when comparing the data
+ * @param buffer buffer to be filled with the JSON report
+ * @param buffer_size size of the buffer
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_diff_json(const struct eqs_tensormap_t *first,
+                                     const struct eqs_tensormap_t *second,
+                                     bool compare_data,
+                                     double tolerance,
+                                     char *buffer,
+                                     uintptr_t buffer_size);
+
+/**
+ * Expand all the blocks in all the `tensors` to use the same set of
+ * properties, i.e. the union of the properties of all blocks. Entries
+ * corresponding to properties missing in a block are filled with zeros.
+ *
+ * The resulting tensor maps can be directly summed or concatenated along
+ * samples. The union of properties is sorted in lexicographic order.
+ *
+ * `aligned` must point to an array of `tensors_count` pointers, which will be
+ * set to the newly allocated tensor maps, in the same order as `tensors`.
+ * These should be freed with `eqs_tensormap_free`.
+ *
+ * @param tensors array of pointers to existing tensor maps
+ * @param tensors_count number of entries in the `tensors` and `aligned` arrays
+ * @param aligned array of pointers to be filled with the aligned tensor maps
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_align_properties(const struct eqs_tensormap_t *const *tensors,
+                                            uintptr_t tensors_count,
+                                            struct eqs_tensormap_t **aligned);
+
+/**
+ * Restrict both `first` and `second` tensor maps to the samples they have in
+ * common.
+ *
+ * Blocks are matched by key, and both tensor maps must contain the same set of
+ * keys. For each pair of blocks, only the samples present in both blocks are
+ * kept, in the order they appear in the block from `first`; and gradients are
+ * updated accordingly.
+ *
+ * The new tensor maps are stored in `first_output` and `second_output`, and
+ * should be freed with `eqs_tensormap_free`. If `mappings` is not `NULL`, it
+ * must point to an array of `mappings_count` labels (one for each key in
+ * `first`), which will be filled with the positions of the kept samples in
+ * the original blocks, as labels with two dimensions: `first` and `second`.
+ * These labels must be freed with `eqs_labels_free`.
+ *
+ * @param first pointer to an existing tensor map
+ * @param second pointer to another existing tensor map
+ * @param first_output pointer to be filled with the restricted `first`
+ * @param second_output pointer to be filled with the restricted `second`
+ * @param mappings array of labels to be filled with the samples positions,
+ *                 or `NULL`
+ * @param mappings_count number of entries in the `mappings` array
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_intersect_samples(const struct eqs_tensormap_t *first,
+                                             const struct eqs_tensormap_t *second,
+                                             struct eqs_tensormap_t **first_output,
+                                             struct eqs_tensormap_t **second_output,
+                                             struct eqs_labels_t *mappings,
+                                             uintptr_t mappings_count);
+
+/**
+ * Make all the `tensors` carry gradients with respect to the same parameters.
+ *
+ * Different tensor maps can contain gradients with respect to different
+ * parameters, which prevents merging or combining them. The gradients only
+ * present in some of the tensor maps are handled according to
+ * `missing_gradients`, which must be one of:
+ *
+ * - `"error"`: return an error if some tensor maps are missing gradients;
+ * - `"zero"`: add the missing gradients, filled with zeros. Since gradients
+ *   are sparse, these new gradients do not contain any samples;
+ * - `"drop"`: remove all gradients which are not present in every tensor map.
+ *
+ * The new tensor maps are stored in `outputs`, which must contain space for
+ * `tensors_count` pointers. They should be freed with `eqs_tensormap_free`.
+ *
+ * @param tensors array of pointers to existing tensor maps
+ * @param tensors_count number of entries in the `tensors` and `outputs` arrays
+ * @param missing_gradients how to handle missing gradients
+ * @param outputs array of pointers to be filled with the new tensor maps
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_unify_gradients(const struct eqs_tensormap_t *const *tensors,
+                                           uintptr_t tensors_count,
+                                           const char *missing_gradients,
+                                           struct eqs_tensormap_t **outputs);
+
+/**
+ * Compute the outer product of the properties of `first` and `second`.
+ *
+ * Both tensor maps must have the same keys (possibly in a different order),
+ * and the blocks with the same key must have the same samples and components.
+ * The properties of the new blocks are the cartesian product of the
+ * properties of the two blocks, with the names of the properties prefixed by
+ * `first_` and `second_` respectively. The keys of the result are the keys of
+ * `first`. Blocks with gradients are not supported.
+ *
+ * The result is a new tensor map, which should be freed with `eqs_tensormap_free`.
+ *
+ * @param first pointer to an existing tensor map
+ * @param second pointer to another existing tensor map
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_tensormap_product(const struct eqs_tensormap_t *first,
+                                              const struct eqs_tensormap_t *second);
+
+/**
+ * Estimate the memory used by this `tensor` map, in bytes.
+ *
+ * The memory used by the keys is stored in `keys_usage`, and the memory used
+ * by each block (including labels and gradients) is stored in `blocks_usage`,
+ * which must contain space for as many entries as there are keys in the
+ * tensor map. The total memory used by the tensor map is stored in `total`.
+ * Labels shared between multiple blocks are only counted once in `total`.
+ * The size of data arrays is estimated from their shape, assuming they
+ * contain 64-bit floating point values, since `eqs_array_t` does not expose
+ * the actual type of the data. The memory used by arrays storing other types
+ * (e.g. 32-bit floating point values) is over- or under-estimated
+ * accordingly.
+ *
+ * @param tensor pointer to an existing tensor map
+ * @param keys_usage pointer to be filled with the memory used by the keys
+ * @param blocks_usage array to be filled with the memory used by each block
+ * @param blocks_count number of entries in the `blocks_usage` array
+ * @param total pointer to be filled with the total memory used
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_memory_usage(const struct eqs_tensormap_t *tensor,
+                                        struct eqs_memory_usage_t *keys_usage,
+                                        struct eqs_memory_usage_t *blocks_usage,
+                                        uintptr_t blocks_count,
+                                        struct eqs_memory_usage_t *total);
+
+/**
+ * Move the values of all the labels in this `tensor` map (keys, and samples,
+ * components and properties of all blocks and gradients) to a single buffer,
+ * sharing identical labels between blocks.
+ *
+ * This reduces the number of allocations and the memory fragmentation for
+ * tensor maps containing many small blocks. Labels obtained before calling
+ * this function with `eqs_block_labels` stay valid, and keep using their own
+ * copy of the values until they are freed.
+ *
+ * @param tensor pointer to an existing tensor map
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_compact_labels(struct eqs_tensormap_t *tensor);
+
+/**
+ * Get the metadata value associated with `key` in this `tensor` map.
+ *
+ * @param tensor pointer to an existing tensor map
+ * @param key NULL-terminated UTF-8 string containing the metadata key
+ * @param value will be set to a NULL-terminated UTF-8 string containing the
+ *              corresponding value, or to `NULL` if there is no metadata
+ *              associated with this key. The string is owned by the tensor
+ *              map, and only valid until the metadata is modified or the
+ *              tensor map is freed.
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_metadata(const struct eqs_tensormap_t *tensor,
+                                    const char *key,
+                                    const char **value);
+
+/**
+ * Set the metadata value associated with `key` in this `tensor` map.
+ *
+ * Metadata is free-form information attached to the tensor map (provenance,
+ * hyper-parameters, code version, ...) which is preserved by copies and
+ * serialization.
+ *
+ * @param tensor pointer to an existing tensor map
+ * @param key NULL-terminated UTF-8 string containing the metadata key
+ * @param value NULL-terminated UTF-8 string containing the new value, or
+ *              `NULL` to remove the metadata associated with `key`
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_set_metadata(struct eqs_tensormap_t *tensor,
+                                        const char *key,
+                                        const char *value);
+
+/**
+ * Get the list of all metadata keys defined in this `tensor` map, sorted in
+ * lexicographic order.
+ *
+ * @param tensor pointer to an existing tensor map
+ * @param keys will be set to the first element of an array of NULL-terminated
+ *             UTF-8 strings containing all the metadata keys
+ * @param keys_count will be set to the number of elements in `keys`
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_metadata_keys(const struct eqs_tensormap_t *tensor,
+                                         const char *const **keys,
+                                         uintptr_t *keys_count);
+
 /**
  * Load a tensor map from the file at the given path.
  *
@@ -768,6 +1842,364 @@ struct eqs_tensormap_t *eqs_tensormap_load(const char *path,
  */
 eqs_status_t eqs_tensormap_save(const char *path, const struct eqs_tensormap_t *tensor);
 
+/**
+ * Save a tensor map to the file at the given path, using the given `options`.
+ *
+ * If the file already exists, it is overwritten. Files saved with
+ * `eqs_save_options_t.labels_dictionary` set store the labels in
+ * zstd-compressed `<path>.npy.zst` files, and the shared dictionary in
+ * `/labels-dictionary.zstd`. Files saved with
+ * `eqs_save_options_t.mantissa_bits` set store the data arrays in
+ * zstd-compressed `<path>.npy.zst` files. They can be loaded with
+ * `eqs_tensormap_load` and the other loading functions.
+ *
+ * @param path path to the file as a NULL-terminated UTF-8 string
+ * @param tensor tensor map to save to the file
+ * @param options options controlling the serialization
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_save_with_options(const char *path,
+                                             const struct eqs_tensormap_t *tensor,
+                                             struct eqs_save_options_t options);
+
+/**
+ * Save a tensor map to the file at the given path, using the given `options`
+ * and reporting the number of blocks saved so far with `progress`.
+ *
+ * If the file already exists, it is overwritten.
+ *
+ * @param path path to the file as a NULL-terminated UTF-8 string
+ * @param tensor tensor map to save to the file
+ * @param options options controlling the serialization
+ * @param progress callbacks used to report the progress of this operation
+ *                 and to cancel it
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_save_with_progress(const char *path,
+                                              const struct eqs_tensormap_t *tensor,
+                                              struct eqs_save_options_t options,
+                                              struct eqs_progress_t progress);
+
+/**
+ * Load a tensor map from the given in-memory buffer.
+ *
+ * This function is similar to `eqs_tensormap_load`, but reads the data from
+ * an in-memory buffer containing the same content as a file saved with
+ * `eqs_tensormap_save`, instead of a file on disk.
+ *
+ * The memory allocated by this function should be released using
+ * `eqs_tensormap_free`.
+ *
+ * @param buffer buffer containing a previously serialized tensor map
+ * @param buffer_count number of elements in the buffer
+ * @param create_array callback function that will be used to create data
+ *                     arrays inside each block
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_tensormap_load_buffer(const uint8_t *buffer,
+                                                  uintptr_t buffer_count,
+                                                  eqs_create_array_callback_t create_array);
+
+/**
+ * Load a tensor map from the file at the given path, refusing data larger
+ * than the given `limits`.
+ *
+ * This function is similar to `eqs_tensormap_load`, and should be preferred
+ * to it when loading files from untrusted sources. Data over the limits is
+ * reported with `EQS_SERIALIZATION_ERROR`.
+ *
+ * The memory allocated by this function should be released using
+ * `eqs_tensormap_free`.
+ *
+ * @param path path to the file as a NULL-terminated UTF-8 string
+ * @param limits limits on the size of the loaded data
+ * @param create_array callback function that will be used to create data
+ *                     arrays inside each block
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_tensormap_load_with_limits(const char *path,
+                                                       struct eqs_load_limits_t limits,
+                                                       eqs_create_array_callback_t create_array);
+
+/**
+ * Load a tensor map from the given in-memory buffer, refusing data larger
+ * than the given `limits`.
+ *
+ * This function is similar to `eqs_tensormap_load_buffer`, see
+ * `eqs_tensormap_load_with_limits` for more information about the limits.
+ *
+ * The memory allocated by this function should be released using
+ * `eqs_tensormap_free`.
+ *
+ * @param buffer buffer containing a previously serialized tensor map
+ * @param buffer_count number of elements in the buffer
+ * @param limits limits on the size of the loaded data
+ * @param create_array callback function that will be used to create data
+ *                     arrays inside each block
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_tensormap_load_buffer_with_limits(const uint8_t *buffer,
+                                                              uintptr_t buffer_count,
+                                                              struct eqs_load_limits_t limits,
+                                                              eqs_create_array_callback_t create_array);
+
+/**
+ * Load a tensor map from the file at the given path, refusing data larger
+ * than the given `limits` and reporting the number of blocks loaded so far
+ * with `progress`.
+ *
+ * See `eqs_tensormap_load_with_limits` for more information about the limits.
+ *
+ * The memory allocated by this function should be released using
+ * `eqs_tensormap_free`.
+ *
+ * @param path path to the file as a NULL-terminated UTF-8 string
+ * @param limits limits on the size of the loaded data
+ * @param progress callbacks used to report the progress of this operation
+ *                 and to cancel it
+ * @param create_array callback function that will be used to create data
+ *                     arrays inside each block
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_tensormap_load_with_progress(const char *path,
+                                                         struct eqs_load_limits_t limits,
+                                                         struct eqs_progress_t progress,
+                                                         eqs_create_array_callback_t create_array);
+
+/**
+ * Save a tensor map to an in-memory buffer.
+ *
+ * The data is written directly to the buffer, which is grown as needed with
+ * the `realloc` callback. On input, `*buffer` should contain either `NULL` or
+ * a pointer previously allocated with `realloc`, and `*buffer_count` the size
+ * of this allocation. The existing allocation is re-used if it is large
+ * enough. On output, `*buffer` contains the (possibly new) allocation and
+ * `*buffer_count` the size of the serialized data, which can be smaller than
+ * the size of the allocation.
+ *
+ * The serialized data is the same as the content of a file created by
+ * `eqs_tensormap_save`.
+ *
+ * @param buffer pointer to the buffer which will contain the serialized data
+ * @param buffer_count pointer to the size of the existing allocation, which
+ *                     will be set to the size of the serialized data
+ * @param realloc_user_data custom data for the `realloc` callback. This will
+ *                          be passed as the first argument to `realloc` as-is.
+ * @param realloc function to allocate and grow the buffer
+ * @param tensor tensor map to save
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_tensormap_save_buffer(uint8_t **buffer,
+                                       uintptr_t *buffer_count,
+                                       void *realloc_user_data,
+                                       eqs_realloc_buffer_t realloc,
+                                       const struct eqs_tensormap_t *tensor);
+
+/**
+ * Load some or all of the blocks of a tensor map from the given `reader`.
+ *
+ * This function is similar to `eqs_tensormap_load`, but reads the data
+ * through the `reader.read_at` callback instead of from a file. Only the
+ * parts of the data that are needed are read: the ZIP central directory, the
+ * keys and the files of the requested blocks.
+ *
+ * The memory allocated by this function should be released using
+ * `eqs_tensormap_free`.
+ *
+ * @param reader callback-based random access to the serialized data
+ * @param blocks indexes of the blocks to load, in the order in which they
+ *               should appear in the new tensor map. If this is `NULL`, all
+ *               blocks are loaded.
+ * @param blocks_count number of entries in the `blocks` array
+ * @param create_array callback function that will be used to create data
+ *                     arrays inside each block
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_tensormap_load_read_at(struct eqs_read_at_t reader,
+                                                   const uintptr_t *blocks,
+                                                   uintptr_t blocks_count,
+                                                   eqs_create_array_callback_t create_array);
+
+/**
+ * Start a new chain of lazy element-wise operations from the given `source`
+ * tensor map.
+ *
+ * Operations are only recorded by the `eqs_lazy_tensormap_*` functions, and
+ * nothing is computed until `eqs_lazy_tensormap_compute` or
+ * `eqs_lazy_tensormap_compute_block` is called. All the recorded operations
+ * are then applied in a single pass over the data, without allocating
+ * intermediary arrays. Gradients are propagated through all operations.
+ *
+ * `source` must stay alive (and must not be modified) until the lazy tensor
+ * map is freed with `eqs_lazy_tensormap_free`.
+ *
+ * @param source pointer to an existing tensor map
+ *
+ * @returns A pointer to the newly allocated lazy tensor map, or a `NULL`
+ *          pointer in case of error. In case of error, you can use
+ *          `eqs_last_error()` to get the error message.
+ */
+struct eqs_lazy_tensormap_t *eqs_lazy_tensormap(const struct eqs_tensormap_t *source);
+
+/**
+ * Free the memory associated with a `lazy` tensor map previously created with
+ * `eqs_lazy_tensormap`. This does not free the tensor maps used in the
+ * operations.
+ *
+ * If `lazy` is `NULL`, this function does nothing.
+ *
+ * @param lazy pointer to an existing lazy tensor map, or `NULL`
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_lazy_tensormap_free(struct eqs_lazy_tensormap_t *lazy);
+
+/**
+ * Set the policy used by the binary operations recorded after this call when
+ * only some of the tensor maps contain gradients with respect to a given
+ * parameter. The default is `"error"`.
+ *
+ * `missing_gradients` must be one of `"error"` (return an error), `"zero"`
+ * (treat missing gradients as zero-filled, with the same metadata as the
+ * other tensor maps) or `"drop"` (only keep the gradients present in all
+ * tensor maps).
+ *
+ * @param lazy pointer to an existing lazy tensor map
+ * @param missing_gradients how to handle missing gradients, as a
+ *        NULL-terminated UTF-8 string
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_lazy_tensormap_missing_gradients(struct eqs_lazy_tensormap_t *lazy,
+                                                  const char *missing_gradients);
+
+/**
+ * Allow the binary operations recorded after this call to use tensor maps
+ * whose keys only contain a subset of the keys dimensions of the source
+ * tensor map. Each block of the source is then combined with the block of
+ * the other tensor map with the same values for the common keys dimensions.
+ * The default is `false`, requiring both tensor maps to have the same keys.
+ *
+ * @param lazy pointer to an existing lazy tensor map
+ * @param broadcast whether to broadcast keys in binary operations
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_lazy_tensormap_broadcast_keys(struct eqs_lazy_tensormap_t *lazy, bool broadcast);
+
+/**
+ * Add a unary element-wise operation to the chain of operations in `lazy`.
+ *
+ * `operation` must be one of `"add_scalar"` (`x + value`),
+ * `"multiply_scalar"` (`x * value`), `"pow"` (`x ^ value`), `"abs"`,
+ * `"sqrt"`, `"exp"` or `"log"`. `value` is ignored by the operations which
+ * do not use it.
+ *
+ * @param lazy pointer to an existing lazy tensor map
+ * @param operation name of the operation, as a NULL-terminated UTF-8 string
+ * @param value parameter of the operation
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_lazy_tensormap_unary(struct eqs_lazy_tensormap_t *lazy,
+                                      const char *operation,
+                                      double value);
+
+/**
+ * Add `other` to the current result of the operations in `lazy`. `other`
+ * must have the same metadata as the source of `lazy`.
+ *
+ * `other` must stay alive (and must not be modified) until `lazy` is freed.
+ *
+ * @param lazy pointer to an existing lazy tensor map
+ * @param other pointer to an existing tensor map
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_lazy_tensormap_add(struct eqs_lazy_tensormap_t *lazy,
+                                    const struct eqs_tensormap_t *other);
+
+/**
+ * Multiply the current result of the operations in `lazy` by `other`.
+ * `other` must have the same metadata as the source of `lazy`.
+ *
+ * `other` must stay alive (and must not be modified) until `lazy` is freed.
+ *
+ * @param lazy pointer to an existing lazy tensor map
+ * @param other pointer to an existing tensor map
+ *
+ * @returns The status code of this operation. If the status is not
+ *          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+ *          error message.
+ */
+eqs_status_t eqs_lazy_tensormap_multiply(struct eqs_lazy_tensormap_t *lazy,
+                                         const struct eqs_tensormap_t *other);
+
+/**
+ * Evaluate all the operations recorded in `lazy` for all blocks, and return
+ * the resulting tensor map.
+ *
+ * The result is a new tensor map, which should be freed with
+ * `eqs_tensormap_free`.
+ *
+ * @param lazy pointer to an existing lazy tensor map
+ *
+ * @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_tensormap_t *eqs_lazy_tensormap_compute(const struct eqs_lazy_tensormap_t *lazy);
+
+/**
+ * Evaluate all the operations recorded in `lazy` for the block at the given
+ * `index` only, and return the resulting block.
+ *
+ * The result is a new block, which should be freed with `eqs_block_free`.
+ *
+ * @param lazy pointer to an existing lazy tensor map
+ * @param index index of the block to compute
+ *
+ * @returns A pointer to the newly allocated block, or a `NULL` pointer in
+ *          case of error. In case of error, you can use `eqs_last_error()`
+ *          to get the error message.
+ */
+struct eqs_block_t *eqs_lazy_tensormap_compute_block(const struct eqs_lazy_tensormap_t *lazy,
+                                                     uintptr_t index);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif // __cplusplus
@@ -0,0 +1,16 @@
+//! Command-line tool to inspect, compare and convert serialized tensor maps.
+//! Run `equistore help` for the list of commands.
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mut stdout = std::io::stdout().lock();
+
+    match equistore_core::cli::run(&args, &mut stdout) {
+        Ok(status) => std::process::exit(status),
+        // use a different status than `diff` finding differences for errors
+        Err(error) => {
+            eprintln!("error: {}", error);
+            std::process::exit(2);
+        }
+    }
+}
@@ -4,7 +4,9 @@ use std::collections::{HashMap, BTreeSet};
 
 use crate::utils::ConstCString;
 use crate::{Labels, LabelsBuilder};
-use crate::{eqs_array_t, get_data_origin};
+use crate::{eqs_array_t, eqs_sample_mapping_t, get_data_origin};
+use crate::gradients::check_gradient_parameter;
+use crate::data::{compress_array, decompress_array};
 use crate::Error;
 
 /// A `Vec` which can not be modified
@@ -29,10 +31,78 @@ impl<'a, T> IntoIterator for &'a ImmutableVec<T> {
     }
 }
 
+/// Reference-counted `eqs_array_t`, shared between multiple blocks until one
+/// of them needs to modify the data (copy-on-write).
+///
+/// Read-only access to the array is available through `Deref`, while mutable
+/// access goes through [`SharedArray::make_mut`], which copies the array first
+/// if it is shared with other blocks.
+#[derive(Debug)]
+pub struct SharedArray {
+    array: Arc<eqs_array_t>,
+    // mutable access to the array was given to code outside of our control
+    // (i.e. through the C API), so we can not know when the array is modified.
+    // Any clone of this array must then do a full copy.
+    exposed: bool,
+}
+
+impl SharedArray {
+    fn new(array: eqs_array_t) -> SharedArray {
+        SharedArray {
+            array: Arc::new(array),
+            exposed: false,
+        }
+    }
+
+    /// Is this array currently shared with other blocks?
+    #[cfg(test)]
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.array) > 1
+    }
+
+    /// Get a new reference to this array. This only copies the data if
+    /// mutable access to the array was given through the C API.
+    pub fn try_clone(&self) -> Result<SharedArray, Error> {
+        if self.exposed {
+            return Ok(SharedArray::new(self.array.try_clone()?));
+        }
+
+        return Ok(SharedArray {
+            array: Arc::clone(&self.array),
+            exposed: false,
+        });
+    }
+
+    /// Get mutable access to this array, copying the data first if the array
+    /// is shared with other blocks.
+    pub fn make_mut(&mut self) -> Result<&mut eqs_array_t, Error> {
+        if Arc::get_mut(&mut self.array).is_none() {
+            self.array = Arc::new(self.array.try_clone()?);
+        }
+
+        return Ok(Arc::get_mut(&mut self.array).expect("array should not be shared"));
+    }
+
+    /// Get mutable access to this array, to be given to external code. All
+    /// subsequent clones of this array will copy the data.
+    pub(crate) fn expose_mut(&mut self) -> Result<&mut eqs_array_t, Error> {
+        self.exposed = true;
+        return self.make_mut();
+    }
+}
+
+impl std::ops::Deref for SharedArray {
+    type Target = eqs_array_t;
+
+    fn deref(&self) -> &Self::Target {
+        &self.array
+    }
+}
+
 /// Single data array with the corresponding metadata inside a `TensorBlock`
 #[derive(Debug)]
 pub struct BasicBlock {
-    pub data: eqs_array_t,
+    pub data: SharedArray,
     pub samples: Arc<Labels>,
     pub components: ImmutableVec<Arc<Labels>>,
     pub properties: Arc<Labels>,
@@ -112,6 +182,36 @@ fn check_component_labels(components: &[Arc<Labels>]) -> Result<(), Error> {
     Ok(())
 }
 
+/// Check that `block` and `other` have the same metadata before copying data
+/// for `context` (either "values" or a gradient parameter) between them
+fn check_same_metadata(context: &str, block: &BasicBlock, other: &BasicBlock) -> Result<(), Error> {
+    let mismatch = if block.samples != other.samples {
+        Some("samples")
+    } else if block.components != other.components {
+        Some("components")
+    } else if block.properties != other.properties {
+        Some("properties")
+    } else {
+        None
+    };
+
+    if let Some(labels) = mismatch {
+        return Err(Error::InvalidParameter(format!(
+            "can not copy {} data between blocks with different {}",
+            context, labels
+        )));
+    }
+
+    Ok(())
+}
+
+/// Copy the data in `other` to `block`, which must have the same shape
+fn copy_data(block: &mut BasicBlock, other: &BasicBlock) -> Result<(), Error> {
+    let data = other.data.contiguous_data()?;
+    block.data.make_mut()?.data_mut()?.copy_from_slice(&data);
+    Ok(())
+}
+
 impl BasicBlock {
     /// Create a new `BasicBlock`, validating the shape of data & labels
     pub fn new(
@@ -126,7 +226,12 @@ impl BasicBlock {
 
         check_component_labels(&components)?;
         let components = ImmutableVec(components);
-        return Ok(BasicBlock { data, samples, components, properties });
+        return Ok(BasicBlock {
+            data: SharedArray::new(data),
+            samples,
+            components,
+            properties,
+        });
     }
 
     fn components_to_properties(&mut self, dimensions: &[&str]) -> Result<(), Error> {
@@ -168,21 +273,22 @@ impl BasicBlock {
         new_shape[properties_axis] = new_properties.count();
         new_shape.remove(component_axis + 1);
 
-        self.data.swap_axes(component_axis + 1, properties_axis - 1)?;
-        self.data.reshape(&new_shape)?;
+        let data = self.data.make_mut()?;
+        data.swap_axes(component_axis + 1, properties_axis - 1)?;
+        data.reshape(&new_shape)?;
 
         self.properties = Arc::new(new_properties);
 
         Ok(())
     }
 
-    /// Try to copy this `BasicBlock`. This can fail if we are unable to copy
-    /// the underlying `eqs_array_t` data array
+    /// Try to copy this `BasicBlock`. The data array is shared with the new
+    /// block and only copied when one of them is modified, except if mutable
+    /// access to the data was given through the C API. This can fail if we are
+    /// unable to copy the underlying `eqs_array_t` data array
     pub fn try_clone(&self) -> Result<BasicBlock, Error> {
-        let data = self.data.try_clone()?;
-
         Ok(BasicBlock {
-            data,
+            data: self.data.try_clone()?,
             samples: Arc::clone(&self.samples),
             components: self.components.clone(),
             properties: Arc::clone(&self.properties),
@@ -198,6 +304,9 @@ pub struct TensorBlock {
     gradients: HashMap<String, BasicBlock>,
     // all the keys from `self.gradients`, as C-compatible strings
     gradient_parameters: Vec<ConstCString>,
+    // physical unit and quantity of the values, as C-compatible strings
+    unit: Option<ConstCString>,
+    quantity: Option<ConstCString>,
 }
 
 impl TensorBlock {
@@ -214,11 +323,13 @@ impl TensorBlock {
             values: BasicBlock::new(data.into(), samples, components, properties)?,
             gradients: HashMap::new(),
             gradient_parameters: Vec::new(),
+            unit: None,
+            quantity: None,
         })
     }
 
-    /// Try to copy this `TensorBlock`. This can fail if we are unable to copy
-    /// one of the underlying `eqs_array_t` data arrays
+    /// Try to copy this `TensorBlock`. Data arrays are shared with the new
+    /// block, and only copied when one of them is modified.
     pub fn try_clone(&self) -> Result<TensorBlock, Error> {
         let values = self.values.try_clone()?;
         let mut gradients = HashMap::new();
@@ -230,7 +341,9 @@ impl TensorBlock {
         return Ok(TensorBlock {
             values,
             gradients,
-            gradient_parameters
+            gradient_parameters,
+            unit: self.unit.clone(),
+            quantity: self.quantity.clone(),
         });
     }
 
@@ -239,7 +352,9 @@ impl TensorBlock {
         &self.values
     }
 
-    /// Get read-write access to the values data and metadata in this block
+    /// Get read-write access to the values data and metadata in this block.
+    /// Use [`SharedArray::make_mut`] on the `data` to modify the array without
+    /// affecting other blocks sharing it.
     pub fn values_mut(&mut self) -> &mut BasicBlock {
         &mut self.values
     }
@@ -266,14 +381,92 @@ impl TensorBlock {
         &self.gradient_parameters
     }
 
+    /// Remove the gradient with respect to the given parameter from this
+    /// block, returning it if it existed.
+    pub(crate) fn remove_gradient(&mut self, parameter: &str) -> Option<BasicBlock> {
+        let gradient = self.gradients.remove(parameter)?;
+        self.gradient_parameters.retain(|p| p.as_str() != parameter);
+        return Some(gradient);
+    }
+
+    /// Get the physical unit of the values in this block (e.g. `"eV"`), if it
+    /// was set.
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_ref().map(ConstCString::as_str)
+    }
+
+    /// Get the physical unit of the values in this block for the C API
+    pub fn unit_c(&self) -> Option<&ConstCString> {
+        self.unit.as_ref()
+    }
+
+    /// Set the physical unit of the values in this block, or remove it if
+    /// `unit` is `None`. The gradients are assumed to use the same unit as the
+    /// values, divided by the unit of the corresponding parameter.
+    pub fn set_unit(&mut self, unit: Option<&str>) -> Result<(), Error> {
+        self.unit = unit.map(|unit| to_const_c_string("unit", unit)).transpose()?;
+        Ok(())
+    }
+
+    /// Get the physical quantity represented by the values in this block (e.g.
+    /// `"energy"`), if it was set.
+    pub fn quantity(&self) -> Option<&str> {
+        self.quantity.as_ref().map(ConstCString::as_str)
+    }
+
+    /// Get the physical quantity represented by the values in this block for
+    /// the C API
+    pub fn quantity_c(&self) -> Option<&ConstCString> {
+        self.quantity.as_ref()
+    }
+
+    /// Set the physical quantity represented by the values in this block, or
+    /// remove it if `quantity` is `None`.
+    pub fn set_quantity(&mut self, quantity: Option<&str>) -> Result<(), Error> {
+        self.quantity = quantity.map(|quantity| to_const_c_string("quantity", quantity)).transpose()?;
+        Ok(())
+    }
+
+    /// Copy the unit and quantity of `other` into this block
+    pub(crate) fn copy_units_from(&mut self, other: &TensorBlock) {
+        self.unit.clone_from(&other.unit);
+        self.quantity.clone_from(&other.quantity);
+    }
+
+    /// Replace all the labels of this block (including the labels of the
+    /// gradients) by the result of `replace`. The labels are visited in the
+    /// same order on every call.
+    pub(crate) fn map_labels(&mut self, mut replace: impl FnMut(&Arc<Labels>) -> Arc<Labels>) {
+        let mut map_basic_block = |block: &mut BasicBlock| {
+            block.samples = replace(&block.samples);
+            block.components = ImmutableVec(block.components.iter().map(&mut replace).collect());
+            block.properties = replace(&block.properties);
+        };
+
+        map_basic_block(&mut self.values);
+        for parameter in &self.gradient_parameters {
+            let gradient = self.gradients.get_mut(parameter.as_str()).expect("missing gradient");
+            map_basic_block(gradient);
+        }
+    }
+
+    /// Do this block and `other` have the same unit and quantity?
+    pub(crate) fn same_units(&self, other: &TensorBlock) -> bool {
+        self.unit == other.unit && self.quantity == other.quantity
+    }
+
     /// Add a gradient with respect to `parameter` to this block.
     ///
     /// The gradient `data` is given as an array, and the samples and components
     /// labels must be provided. The property labels are assumed to match the
     /// ones of the values in this block.
     ///
-    /// The components labels must contain at least the same entries as the
-    /// value components labels, and can prepend other components labels.
+    /// The first dimension of the gradient samples must be named `sample`, and
+    /// contain the row of an existing sample in the values. The components
+    /// labels must contain at least the same entries as the value components
+    /// labels, and can prepend other components labels. If `parameter` was
+    /// registered with `register_gradient_parameter`, these other components
+    /// must match the registered ones.
     pub fn add_gradient(
         &mut self,
         parameter: &str,
@@ -303,40 +496,64 @@ impl TensorBlock {
             ))
         }
 
-        if samples.size() == 0 {
-            return Err(Error::InvalidParameter(
-                "gradients samples must have at least one dimension named 'sample', we got none".into()
-            ))
+        if samples.size() == 0 || samples.names()[0] != "sample" {
+            let names = samples.names();
+            let first = names.first().map_or(String::from("no dimensions"), |n| format!("'{}'", n));
+            return Err(Error::InvalidParameter(format!(
+                "invalid gradient samples for '{}': the first dimension must be \
+                named 'sample' and contain the row of the corresponding entry \
+                in the values, but got {} (dimensions are [{}]). Add a 'sample' \
+                dimension as the first dimension of the gradient samples",
+                parameter, first, names.join(", ")
+            )))
         }
 
-        if samples.size() < 1 || samples.names()[0] != "sample" {
-            return Err(Error::InvalidParameter(format!(
-                "'{}' is not valid for the first dimension in the gradients \
-                samples labels. It must must be 'sample'", samples.names()[0])
-            ))
+        let n_values_samples = self.values.samples.count();
+        for (row, entry) in samples.iter().enumerate() {
+            let sample = entry[0];
+            if sample.i32() < 0 || sample.usize() >= n_values_samples {
+                return Err(Error::InvalidParameter(format!(
+                    "invalid gradient samples for '{}': entry {} refers to \
+                    sample {}, but the values only contain {} samples. The \
+                    'sample' dimension must contain the row of an existing \
+                    sample in the values",
+                    parameter, row, sample, n_values_samples
+                )))
+            }
         }
 
         check_component_labels(&components)?;
-        if self.values.components.len() > components.len() {
-            return Err(Error::InvalidParameter(
-                "gradients components should contain at least as many labels \
-                as the values components".into()
-            ))
+        let values_components = &*self.values.components;
+        if values_components.len() > components.len() {
+            return Err(Error::InvalidParameter(format!(
+                "invalid gradient components for '{}': the values have {} \
+                components, but the gradient only has {}. Gradient components \
+                must contain all the values components, optionally preceded by \
+                gradient-specific components",
+                parameter, values_components.len(), components.len()
+            )))
         }
-        let extra_gradient_components = components.len() - self.values.components.len();
+        let extra_gradient_components = components.len() - values_components.len();
         for (component_i, (gradient_labels, values_labels)) in components.iter()
             .skip(extra_gradient_components)
-            .zip(&*self.values.components)
+            .zip(values_components)
             .enumerate() {
                 if gradient_labels != values_labels {
                     return Err(Error::InvalidParameter(format!(
-                        "gradients and values components mismatch for values \
-                        component {} (the corresponding names are [{}])",
-                        component_i, values_labels.names().join(", ")
+                        "invalid gradient components for '{}': gradient \
+                        component {} does not match values component {} \
+                        (with names [{}]). The last {} gradient components \
+                        must be the same as the values components, in the \
+                        same order",
+                        parameter, extra_gradient_components + component_i,
+                        component_i, values_labels.names().join(", "),
+                        values_components.len(),
                     )))
                 }
             }
 
+        check_gradient_parameter(parameter, &components, values_components.len())?;
+
         let properties = Arc::clone(&self.values.properties);
         check_data_and_labels(
             "gradient data and labels don't match", &data, &samples, &components, &properties
@@ -344,7 +561,7 @@ impl TensorBlock {
 
         let components = ImmutableVec(components);
         self.gradients.insert(parameter.into(), BasicBlock {
-            data,
+            data: SharedArray::new(data),
             samples,
             components,
             properties
@@ -356,6 +573,231 @@ impl TensorBlock {
         return Ok(())
     }
 
+    /// Create a new `TensorBlock` containing only the samples at the given
+    /// positions (in this order) in the values samples. The gradients are
+    /// updated to only contain entries related to the selected samples.
+    pub fn select_samples(&self, selected: &[usize]) -> Result<TensorBlock, Error> {
+        let values = &self.values;
+        let mut builder = LabelsBuilder::new(values.samples.names());
+        builder.reserve(selected.len());
+
+        // position of the old samples in the new block, if they are selected
+        let mut new_positions = vec![None; values.samples.count()];
+        let mut mapping = Vec::with_capacity(selected.len());
+        for (output, &input) in selected.iter().enumerate() {
+            if input >= values.samples.count() {
+                return Err(Error::InvalidParameter(format!(
+                    "can not select sample {}, this block only has {} samples",
+                    input, values.samples.count()
+                )));
+            }
+
+            builder.add(&values.samples[input])?;
+            new_positions[input] = Some(output);
+            mapping.push(eqs_sample_mapping_t { input, output });
+        }
+
+        let mut new_shape = values.data.shape()?.to_vec();
+        new_shape[0] = selected.len();
+        let mut new_data = values.data.create(&new_shape)?;
+        new_data.move_samples_from(&values.data, &mapping, 0..values.properties.count())?;
+
+        let mut new_block = TensorBlock::new(
+            new_data,
+            Arc::new(builder.finish()),
+            values.components.to_vec(),
+            Arc::clone(&values.properties),
+        )?;
+
+        for parameter in &self.gradient_parameters {
+            let parameter = parameter.as_str();
+            let gradient = &self.gradients[parameter];
+
+            let mut builder = LabelsBuilder::new(gradient.samples.names());
+            let mut mapping = Vec::new();
+            for (input, grad_sample) in gradient.samples.iter().enumerate() {
+                if let Some(new_sample) = new_positions[grad_sample[0].usize()] {
+                    let mut grad_sample = grad_sample.to_vec();
+                    grad_sample[0] = new_sample.into();
+                    builder.add(&grad_sample)?;
+                    mapping.push(eqs_sample_mapping_t { input, output: mapping.len() });
+                }
+            }
+
+            let mut new_shape = gradient.data.shape()?.to_vec();
+            new_shape[0] = mapping.len();
+            let mut new_gradient = gradient.data.create(&new_shape)?;
+            new_gradient.move_samples_from(&gradient.data, &mapping, 0..values.properties.count())?;
+
+            new_block.add_gradient(
+                parameter,
+                new_gradient,
+                Arc::new(builder.finish()),
+                gradient.components.to_vec(),
+            )?;
+        }
+
+        new_block.copy_units_from(self);
+
+        return Ok(new_block);
+    }
+
+    /// Set all the values in this block to `value`. The gradients are left
+    /// unchanged.
+    pub fn fill(&mut self, value: f64) -> Result<(), Error> {
+        self.values.data.make_mut()?.data_mut()?.fill(value);
+        Ok(())
+    }
+
+    /// Copy the values and gradients data from `other` into this block. Both
+    /// blocks must have the same samples, components and properties labels,
+    /// and the same set of gradients with the same labels.
+    pub fn copy_from(&mut self, other: &TensorBlock) -> Result<(), Error> {
+        check_same_metadata("values", &self.values, &other.values)?;
+
+        if self.gradients.len() != other.gradients.len() {
+            return Err(Error::InvalidParameter(format!(
+                "can not copy data from a block with {} gradients to a block with {} gradients",
+                other.gradients.len(), self.gradients.len()
+            )));
+        }
+
+        for (parameter, gradient) in &self.gradients {
+            let other_gradient = other.gradient(parameter).ok_or_else(|| Error::InvalidParameter(format!(
+                "can not copy data from a block without gradients with respect to '{}'", parameter
+            )))?;
+            check_same_metadata(parameter, gradient, other_gradient)?;
+        }
+
+        copy_data(&mut self.values, &other.values)?;
+        for (parameter, gradient) in &mut self.gradients {
+            copy_data(gradient, &other.gradients[parameter])?;
+        }
+
+        Ok(())
+    }
+
+    /// Create a new block with the same metadata and gradients as this one,
+    /// using `data` for the values. `data` must have the same shape as the
+    /// current values, and the same origin if this block contains gradients.
+    pub fn with_values(&self, data: eqs_array_t) -> Result<TensorBlock, Error> {
+        let shape = self.values.data.shape()?;
+        if data.shape()? != shape {
+            return Err(Error::InvalidParameter(format!(
+                "the new values array has shape {:?}, but the current values have shape {:?}",
+                data.shape()?, shape
+            )));
+        }
+
+        if !self.gradients.is_empty() && data.origin()? != self.values.data.origin()? {
+            return Err(Error::InvalidParameter(format!(
+                "the new values array has a different origin ('{}') than the \
+                gradients arrays ('{}')",
+                get_data_origin(data.origin()?),
+                get_data_origin(self.values.data.origin()?),
+            )));
+        }
+
+        let mut block = self.try_clone()?;
+        block.values.data = SharedArray::new(data);
+        return Ok(block);
+    }
+
+    /// Compress the values and gradients data of this block in memory with
+    /// zstd. The data is decompressed the first time it is accessed through
+    /// `eqs_array_t.data`, and stays uncompressed afterward.
+    ///
+    /// Compressed arrays use their own data origin, so code expecting the
+    /// original array type (e.g. numpy arrays in Python) should call
+    /// [`TensorBlock::decompress`] first.
+    pub fn compress(&mut self) -> Result<(), Error> {
+        self.values.data = SharedArray::new(compress_array(&self.values.data)?);
+        for gradient in self.gradients.values_mut() {
+            gradient.data = SharedArray::new(compress_array(&gradient.data)?);
+        }
+        Ok(())
+    }
+
+    /// Convert the data compressed with [`TensorBlock::compress`] back to the
+    /// original array type. Data which is not compressed is left unchanged.
+    pub fn decompress(&mut self) -> Result<(), Error> {
+        if let Some(data) = decompress_array(&self.values.data)? {
+            self.values.data = SharedArray::new(data);
+        }
+
+        for gradient in self.gradients.values_mut() {
+            if let Some(data) = decompress_array(&gradient.data)? {
+                gradient.data = SharedArray::new(data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Is the values data of this block currently compressed in memory? This
+    /// becomes `false` after the first access to the data.
+    #[cfg(test)]
+    pub fn is_compressed(&self) -> bool {
+        crate::data::compressed_size(&self.values.data).is_some()
+    }
+
+    /// Swap the samples and properties of this block, transposing the values
+    /// data with `eqs_array_t.swap_axes`. The components stay in the same
+    /// order.
+    ///
+    /// Blocks with gradients can not be transposed, since the samples of the
+    /// gradients refer to the samples of the values.
+    pub fn transpose(&mut self) -> Result<(), Error> {
+        if !self.gradients.is_empty() {
+            return Err(Error::InvalidParameter(
+                "can not transpose a block with gradients, remove the gradients first".into()
+            ));
+        }
+
+        let properties_axis = self.values.data.shape()?.len() - 1;
+        self.values.data.make_mut()?.swap_axes(0, properties_axis)?;
+        std::mem::swap(&mut self.values.samples, &mut self.values.properties);
+
+        Ok(())
+    }
+
+    /// Set the values for the samples at the given positions to `data`.
+    ///
+    /// `data` contains the new values for all the selected samples in
+    /// row-major order, i.e. it must contain `samples.len()` times the number
+    /// of values per sample (the product of the components and properties
+    /// sizes). The gradients are left unchanged.
+    pub fn assign_rows(&mut self, samples: &[usize], data: &[f64]) -> Result<(), Error> {
+        let row_size = self.values.data.shape()?[1..].iter().product::<usize>();
+        if data.len() != samples.len() * row_size {
+            return Err(Error::InvalidParameter(format!(
+                "expected {} values to assign to {} samples, got {}",
+                samples.len() * row_size, samples.len(), data.len()
+            )));
+        }
+
+        let n_samples = self.values.samples.count();
+        for &sample in samples {
+            if sample >= n_samples {
+                return Err(Error::InvalidParameter(format!(
+                    "can not assign to sample {}, this block only has {} samples",
+                    sample, n_samples
+                )));
+            }
+        }
+
+        if row_size == 0 {
+            return Ok(());
+        }
+
+        let values = self.values.data.make_mut()?.data_mut()?;
+        for (&sample, row) in samples.iter().zip(data.chunks_exact(row_size)) {
+            let start = sample * row_size;
+            values[start..(start + row_size)].copy_from_slice(row);
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn components_to_properties(&mut self, dimensions: &[&str]) -> Result<(), Error> {
         if dimensions.is_empty() {
             return Ok(());
@@ -370,6 +812,15 @@ impl TensorBlock {
     }
 }
 
+fn to_const_c_string(context: &str, value: &str) -> Result<ConstCString, Error> {
+    match CString::new(value) {
+        Ok(value) => Ok(ConstCString::new(value)),
+        Err(_) => Err(Error::InvalidParameter(format!(
+            "the {} of a block can not contain NULL bytes", context
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{LabelValue, LabelsBuilder};
@@ -474,6 +925,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn transpose() {
+        let samples = example_labels("samples", 4);
+        let components = vec![example_labels("component", 3)];
+        let properties = example_labels("properties", 7);
+        let mut block = TensorBlock::new(
+            TestArray::new(vec![4, 3, 7]), samples.clone(), components.clone(), properties.clone()
+        ).unwrap();
+
+        block.transpose().unwrap();
+        assert_eq!(block.values().samples, properties);
+        assert_eq!(block.values().components[0], components[0]);
+        assert_eq!(block.values().properties, samples);
+        assert_eq!(block.values().data.shape().unwrap(), [7, 3, 4]);
+
+        let gradient_samples = example_labels("sample", 7);
+        block.add_gradient("g", TestArray::new(vec![7, 3, 4]), gradient_samples, components).unwrap();
+        assert_eq!(
+            block.transpose().unwrap_err().to_string(),
+            "invalid parameter: can not transpose a block with gradients, remove the gradients first"
+        );
+    }
+
+    #[test]
+    fn copy_on_write() {
+        let samples = example_labels("samples", 2);
+        let properties = example_labels("properties", 1);
+        let data = TestArray::from_data(vec![2, 1], vec![1.0, 2.0]);
+        let mut block = TensorBlock::new(data, samples, vec![], properties).unwrap();
+
+        let copy = block.try_clone().unwrap();
+        assert!(block.values().data.is_shared());
+        assert!(copy.values().data.is_shared());
+
+        block.values_mut().data.make_mut().unwrap().data_mut().unwrap()[0] = 42.0;
+        assert!(!block.values().data.is_shared());
+        assert!(!copy.values().data.is_shared());
+
+        assert_eq!(block.values().data.data().unwrap(), [42.0, 2.0]);
+        assert_eq!(copy.values().data.data().unwrap(), [1.0, 2.0]);
+
+        // once mutable access was given to external code, copies are eager
+        block.values_mut().data.expose_mut().unwrap();
+        let copy = block.try_clone().unwrap();
+        assert!(!block.values().data.is_shared());
+        assert!(!copy.values().data.is_shared());
+    }
+
+    #[test]
+    fn update_data() {
+        let samples = example_labels("samples", 3);
+        let properties = example_labels("properties", 2);
+        let data = TestArray::new(vec![3, 2]);
+        let mut block = TensorBlock::new(data, samples.clone(), vec![], properties.clone()).unwrap();
+
+        block.fill(2.0).unwrap();
+        assert_eq!(block.values().data.data().unwrap(), [2.0; 6]);
+
+        block.assign_rows(&[2, 0], &[1.0, 2.0, 3.0, 4.0]).unwrap();
+        assert_eq!(block.values().data.data().unwrap(), [3.0, 4.0, 2.0, 2.0, 1.0, 2.0]);
+
+        let error = block.assign_rows(&[3], &[1.0, 2.0]).unwrap_err();
+        assert_eq!(error.to_string(), "invalid parameter: can not assign to sample 3, this block only has 3 samples");
+
+        let error = block.assign_rows(&[1], &[1.0]).unwrap_err();
+        assert_eq!(error.to_string(), "invalid parameter: expected 2 values to assign to 1 samples, got 1");
+
+        let data = TestArray::from_data(vec![3, 2], vec![6.0, 5.0, 4.0, 3.0, 2.0, 1.0]);
+        let other = TensorBlock::new(data, samples, vec![], properties.clone()).unwrap();
+        block.copy_from(&other).unwrap();
+        assert_eq!(block.values().data.data().unwrap(), [6.0, 5.0, 4.0, 3.0, 2.0, 1.0]);
+
+        let data = TestArray::new(vec![2, 2]);
+        let other = TensorBlock::new(data, example_labels("samples", 2), vec![], properties).unwrap();
+        let error = block.copy_from(&other).unwrap_err();
+        assert_eq!(error.to_string(), "invalid parameter: can not copy values data between blocks with different samples");
+    }
+
+    #[test]
+    fn units() {
+        let samples = example_labels("samples", 3);
+        let properties = example_labels("properties", 2);
+        let data = TestArray::new(vec![3, 2]);
+        let mut block = TensorBlock::new(data, samples, vec![], properties).unwrap();
+        assert_eq!(block.unit(), None);
+        assert_eq!(block.quantity(), None);
+
+        block.set_unit(Some("eV")).unwrap();
+        block.set_quantity(Some("energy")).unwrap();
+        assert_eq!(block.unit(), Some("eV"));
+        assert_eq!(block.quantity(), Some("energy"));
+
+        let copy = block.try_clone().unwrap();
+        assert!(copy.same_units(&block));
+
+        let selected = block.select_samples(&[1]).unwrap();
+        assert_eq!(selected.unit(), Some("eV"));
+        assert_eq!(selected.quantity(), Some("energy"));
+
+        block.set_unit(None).unwrap();
+        assert_eq!(block.unit(), None);
+        assert!(!copy.same_units(&block));
+
+        let error = block.set_unit(Some("e\0V")).unwrap_err();
+        assert_eq!(error.to_string(), "invalid parameter: the unit of a block can not contain NULL bytes");
+    }
+
     mod gradients {
         use super::*;
 
@@ -540,9 +1098,58 @@ mod tests {
             let result = block.add_gradient("wrong", gradient, gradient_samples, components);
             assert_eq!(
                 result.unwrap_err().to_string(),
-                "invalid parameter: gradients and values components mismatch \
-                for values component 0 (the corresponding names are [component])"
+                "invalid parameter: invalid gradient components for 'wrong': \
+                gradient component 1 does not match values component 0 (with \
+                names [component]). The last 1 gradient components must be the \
+                same as the values components, in the same order"
+            );
+        }
+
+        #[test]
+        fn conventions() {
+            let samples = example_labels("samples", 3);
+            let component = example_labels("component", 2);
+            let properties = example_labels("properties", 4);
+            let data = TestArray::new(vec![3, 2, 4]);
+            let mut block = TensorBlock::new(data, samples, vec![component.clone()], properties).unwrap();
+
+            let gradient_samples = example_labels("structure", 3);
+            let data = TestArray::new(vec![3, 2, 4]);
+            let result = block.add_gradient("g", data, gradient_samples, vec![component.clone()]);
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                "invalid parameter: invalid gradient samples for 'g': the first \
+                dimension must be named 'sample' and contain the row of the \
+                corresponding entry in the values, but got 'structure' (dimensions \
+                are [structure]). Add a 'sample' dimension as the first dimension \
+                of the gradient samples"
+            );
+
+            let gradient_samples = example_labels("sample", 4);
+            let data = TestArray::new(vec![4, 2, 4]);
+            let result = block.add_gradient("g", data, gradient_samples, vec![component.clone()]);
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                "invalid parameter: invalid gradient samples for 'g': entry 3 \
+                refers to sample 3, but the values only contain 3 samples. The \
+                'sample' dimension must contain the row of an existing sample \
+                in the values"
             );
+
+            let gradient_samples = example_labels("sample", 3);
+            let data = TestArray::new(vec![3, 4]);
+            let result = block.add_gradient("g", data, gradient_samples.clone(), vec![]);
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                "invalid parameter: invalid gradient components for 'g': the \
+                values have 1 components, but the gradient only has 0. Gradient \
+                components must contain all the values components, optionally \
+                preceded by gradient-specific components"
+            );
+
+            let xyz = example_labels("xyz", 3);
+            let data = TestArray::new(vec![3, 3, 2, 4]);
+            block.add_gradient("g", data, gradient_samples, vec![xyz, component]).unwrap();
         }
     }
 }
@@ -34,6 +34,11 @@ impl eqs_block_t {
     pub(super) fn into_block(self) -> TensorBlock {
         self.0
     }
+
+    /// Create a raw pointer to `eqs_block_t` using a rust Box
+    pub(super) fn into_boxed_raw(block: TensorBlock) -> *mut eqs_block_t {
+        return Box::into_raw(Box::new(eqs_block_t(block)));
+    }
 }
 
 
@@ -224,6 +229,10 @@ pub unsafe extern fn eqs_block_labels(
 /// The `values_gradients` parameter controls whether this function looks up
 /// labels for `"values"` or one of the gradients in this block.
 ///
+/// Arrays can be shared between multiple blocks after a copy, in which case
+/// this function will make a separate copy of the array for this block, since
+/// the caller can modify the data through the returned handle.
+///
 /// @param block pointer to an existing block
 /// @param values_gradients either `"values"` or the name of gradients to lookup
 /// @param data pointer to an empty `eqs_array_t` that will be set to the
@@ -251,7 +260,9 @@ pub unsafe extern fn eqs_block_data(
             }
         };
 
-        *data = basic_block.data.raw_copy();
+        // the caller can modify the array through this handle, so it needs
+        // to be unique and copied the next time the block is copied
+        *data = basic_block.data.expose_mut()?.raw_copy();
 
         Ok(())
     })
@@ -302,6 +313,47 @@ pub unsafe extern fn eqs_block_add_gradient(
     })
 }
 
+/// Register the convention for gradients with respect to `parameter`. All
+/// gradients with respect to this parameter added afterward with
+/// `eqs_block_add_gradient` must start with the given `components`, followed by
+/// the components of the values. Registering the same parameter multiple times
+/// replaces the previous convention.
+///
+/// @param parameter name of the gradient parameter as a NULL-terminated UTF-8
+///                  string (e.g. `"strain"`)
+/// @param components array of component labels that gradients with respect to
+///                   this parameter must have before the values components
+/// @param components_count number of entries in the `components` array
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_register_gradient_parameter(
+    parameter: *const c_char,
+    components: *const eqs_labels_t,
+    components_count: usize,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(parameter);
+        if components_count != 0 {
+            check_pointers!(components);
+        }
+
+        let parameter = CStr::from_ptr(parameter).to_str().unwrap();
+
+        let mut rust_components = Vec::new();
+        if components_count != 0 {
+            for component in std::slice::from_raw_parts(components, components_count) {
+                rust_components.push(eqs_labels_to_rust(component)?);
+            }
+        }
+
+        crate::register_gradient_parameter(parameter, rust_components)?;
+        Ok(())
+    })
+}
+
 /// Get a list of all gradients defined in this `block` in the `parameters` array.
 ///
 /// @param block pointer to an existing block
@@ -333,3 +385,239 @@ pub unsafe extern fn eqs_block_gradients_list(
         Ok(())
     })
 }
+
+
+/// Get the physical unit of the values in this `block` (e.g. `"eV"`).
+///
+/// @param block pointer to an existing block
+/// @param unit will be set to a NULL-terminated UTF-8 string containing the
+///             unit, or to `NULL` if the unit of this block is not set. The
+///             string is owned by the block, and only valid until the unit
+///             of this block is changed or the block is freed.
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_block_unit(
+    block: *const eqs_block_t,
+    unit: *mut *const c_char,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(block, unit);
+        (*unit) = match (*block).unit_c() {
+            Some(value) => value.as_c_str().as_ptr(),
+            None => std::ptr::null(),
+        };
+        Ok(())
+    })
+}
+
+
+/// Set the physical unit of the values in this `block`.
+///
+/// The unit is preserved by copies and serialization, and used to check the
+/// consistency of arithmetic operations.
+///
+/// @param block pointer to an existing block
+/// @param unit NULL-terminated UTF-8 string containing the new unit, or `NULL`
+///             to remove the unit of this block
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_block_set_unit(
+    block: *mut eqs_block_t,
+    unit: *const c_char,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(block);
+        let unit = if unit.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(unit).to_str().expect("invalid UTF8 unit"))
+        };
+
+        (*block).set_unit(unit)?;
+        Ok(())
+    })
+}
+
+
+/// Get the physical quantity represented by the values in this `block` (e.g.
+/// `"energy"`).
+///
+/// @param block pointer to an existing block
+/// @param quantity will be set to a NULL-terminated UTF-8 string containing
+///                 the quantity, or to `NULL` if the quantity of this block is
+///                 not set. The string is owned by the block, and only valid
+///                 until the quantity of this block is changed or the block
+///                 is freed.
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_block_quantity(
+    block: *const eqs_block_t,
+    quantity: *mut *const c_char,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(block, quantity);
+        (*quantity) = match (*block).quantity_c() {
+            Some(value) => value.as_c_str().as_ptr(),
+            None => std::ptr::null(),
+        };
+        Ok(())
+    })
+}
+
+
+/// Set the physical quantity represented by the values in this `block`.
+///
+/// The quantity is preserved by copies and serialization, and used to check
+/// the consistency of arithmetic operations.
+///
+/// @param block pointer to an existing block
+/// @param quantity NULL-terminated UTF-8 string containing the new quantity,
+///                 or `NULL` to remove the quantity of this block
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_block_set_quantity(
+    block: *mut eqs_block_t,
+    quantity: *const c_char,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(block);
+        let quantity = if quantity.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(quantity).to_str().expect("invalid UTF8 quantity"))
+        };
+
+        (*block).set_quantity(quantity)?;
+        Ok(())
+    })
+}
+
+
+/// Set all the values in this `block` to `value`. The gradients are left
+/// unchanged.
+///
+/// @param block pointer to an existing block
+/// @param value new value for all entries in the block values
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_block_fill(
+    block: *mut eqs_block_t,
+    value: f64,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(block);
+        (*block).fill(value)?;
+        Ok(())
+    })
+}
+
+
+/// Copy the values and gradients data from `other` into this `block`.
+///
+/// Both blocks must have the same samples, components and properties labels,
+/// and the same set of gradients with the same labels.
+///
+/// @param block pointer to an existing block, which will be modified
+/// @param other pointer to an existing block containing the data to copy
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_block_copy_from(
+    block: *mut eqs_block_t,
+    other: *const eqs_block_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(block, other);
+        if std::ptr::eq(block, other) {
+            // nothing to do
+            return Ok(());
+        }
+
+        (*block).copy_from(&*other)?;
+        Ok(())
+    })
+}
+
+
+/// Swap the samples and properties of this `block`, transposing the values
+/// data. The components stay in the same order.
+///
+/// Blocks with gradients can not be transposed, since the gradient samples
+/// refer to the samples of the values.
+///
+/// @param block pointer to an existing block, which will be modified
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_block_transpose(block: *mut eqs_block_t) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(block);
+        (*block).transpose()?;
+        Ok(())
+    })
+}
+
+
+/// Set the values of this `block` for the samples at the given positions to
+/// `data`. The gradients are left unchanged.
+///
+/// @param block pointer to an existing block
+/// @param samples positions of the samples to modify in the block
+/// @param samples_count number of entries in the `samples` array
+/// @param data new values for the selected samples, in row-major order. This
+///             must contain `samples_count` times the number of values for a
+///             single sample (i.e. the product of the size of all components
+///             and properties).
+/// @param data_count number of entries in the `data` array
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_block_assign_rows(
+    block: *mut eqs_block_t,
+    samples: *const usize,
+    samples_count: usize,
+    data: *const f64,
+    data_count: usize,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(block);
+
+        let samples: &[usize] = if samples_count == 0 {
+            &[]
+        } else {
+            check_pointers!(samples);
+            std::slice::from_raw_parts(samples, samples_count)
+        };
+
+        let data: &[f64] = if data_count == 0 {
+            &[]
+        } else {
+            check_pointers!(data);
+            std::slice::from_raw_parts(data, data_count)
+        };
+
+        (*block).assign_rows(samples, data)?;
+        Ok(())
+    })
+}
@@ -1,7 +1,7 @@
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_void};
 use std::ffi::CStr;
 
-use crate::eqs_data_origin_t;
+use crate::{eqs_data_origin_t, eqs_array_t};
 
 use super::{eqs_status_t, catch_unwind};
 use super::utils::copy_str_to_c;
@@ -55,3 +55,73 @@ pub unsafe extern fn eqs_get_data_origin(
         return copy_str_to_c(&origin, buffer, buffer_size);
     })
 }
+
+
+/// Register a new array backend with the given `name`, using the functions in
+/// `vtable` to manipulate the arrays. This also registers a data origin with
+/// the same `name`, which is returned in `origin`.
+///
+/// Arrays using the registered functions can then be created with
+/// `eqs_array_from_backend`, giving only the pointer to the backend-specific
+/// data. This allows foreign runtimes to provide their own storage, which will
+/// be used by all operations in equistore.
+///
+/// Calling this function multiple times with the same name replaces the
+/// previously registered functions.
+///
+/// @param name name of the backend as an UTF-8 encoded NULL-terminated string
+/// @param vtable array containing the functions of the backend. The `ptr` of
+///               this array is ignored, and all functions except `destroy`
+///               must be set.
+/// @param origin pointer to an `eqs_data_origin_t` where the origin associated
+///               with this backend will be stored
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_register_array_backend(
+    name: *const c_char,
+    vtable: *const eqs_array_t,
+    origin: *mut eqs_data_origin_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(name, vtable, origin);
+
+        let name = CStr::from_ptr(name).to_str().unwrap();
+        *origin = crate::register_array_backend(name.into(), &*vtable)?;
+
+        Ok(())
+    })
+}
+
+
+/// Create a new array managing `ptr`, using the functions registered for
+/// `origin` with `eqs_register_array_backend`.
+///
+/// The new array takes ownership of `ptr`, and will release it with the
+/// `destroy` function of the backend. If this function fails, `ptr` is not
+/// released.
+///
+/// @param origin data origin of a registered array backend
+/// @param ptr backend-specific data for the new array
+/// @param array pointer to an `eqs_array_t` that will be set to the new array
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_array_from_backend(
+    origin: eqs_data_origin_t,
+    ptr: *mut c_void,
+    array: *mut eqs_array_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(array);
+
+        let new_array = crate::array_from_backend(origin, ptr)?;
+        std::ptr::write(array, new_array);
+
+        Ok(())
+    })
+}
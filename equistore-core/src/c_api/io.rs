@@ -1,13 +1,15 @@
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_void};
 use std::ffi::CStr;
 use std::fs::File;
 use std::io::{BufReader, BufWriter};
 
 use crate::Error;
-use crate::data::eqs_array_t;
+use crate::data::{eqs_array_t, zeros_array};
+use crate::io::{ReadAt, ReadAtReader};
 
 use super::status::{eqs_status_t, catch_unwind};
 use super::tensor::eqs_tensormap_t;
+use super::progress::eqs_progress_t;
 
 /// Function pointer to create a new `eqs_array_t` when de-serializing tensor
 /// maps.
@@ -19,12 +21,98 @@ use super::tensor::eqs_tensormap_t;
 /// The newly created array should contains 64-bit floating points (`double`)
 /// data, and live on CPU, since equistore will use `eqs_array_t.data` to get
 /// the data pointer and write to it.
+///
+/// This can be set to `NULL`, in which case the data is loaded in arrays
+/// managed by equistore, which only support access through `eqs_array_t`
+/// functions. This is useful when providing a callback is impractical, e.g.
+/// when using equistore compiled to WebAssembly.
 #[allow(non_camel_case_types)]
-type eqs_create_array_callback_t = unsafe extern fn(
+type eqs_create_array_callback_t = Option<unsafe extern fn(
     shape: *const usize,
     shape_count: usize,
     array: *mut eqs_array_t,
-) -> eqs_status_t;
+) -> eqs_status_t>;
+
+/// Function pointer to grow in-memory buffers for `eqs_tensormap_save_buffer`.
+///
+/// This function takes an existing pointer in `ptr` and a new length in
+/// `new_size`, and grows the allocation. If `ptr` is `NULL`, it should create a
+/// new allocation. If it is unable to allocate memory, it should return a
+/// `NULL` pointer. This follows the API of the standard C function `realloc`,
+/// with an additional parameter `user_data` that can be used to hold custom
+/// data. In particular, the new allocation must start with the data from the
+/// old one.
+///
+/// This function will be called multiple times while serializing data, every
+/// time the buffer needs to grow.
+#[allow(non_camel_case_types)]
+type eqs_realloc_buffer_t = unsafe extern fn(
+    user_data: *mut c_void,
+    ptr: *mut u8,
+    new_size: usize,
+) -> *mut u8;
+
+/// `eqs_read_at_t` provides random access to serialized tensor map data, for
+/// use with `eqs_tensormap_load_read_at`.
+///
+/// This can be implemented on top of remote storage (HTTP servers, object
+/// stores, *etc.*), where each call to `read_at` is a request for a range of
+/// bytes. Only the parts of the data needed to load the requested blocks are
+/// read.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct eqs_read_at_t {
+    /// User-provided data should be stored here, it will be passed as the
+    /// first parameter to `read_at`.
+    pub user_data: *mut c_void,
+    /// Total size in bytes of the serialized data
+    pub size: u64,
+    /// Read up to `count` bytes starting at `offset` in the serialized data
+    /// into `buffer`, and store the number of bytes actually read in `*read`.
+    /// `*read` should only be set to 0 when `offset` is at or after the end of
+    /// the data.
+    pub read_at: Option<unsafe extern fn(
+        user_data: *mut c_void,
+        offset: u64,
+        buffer: *mut u8,
+        count: usize,
+        read: *mut usize,
+    ) -> eqs_status_t>,
+}
+
+/// Error used to carry the status of a failed `eqs_read_at_t.read_at` call
+/// through `std::io::Error`
+#[derive(Debug)]
+struct ReadAtError(eqs_status_t);
+
+impl std::fmt::Display for ReadAtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to read data with eqs_read_at_t.read_at (status {})", self.0.as_i32())
+    }
+}
+
+impl std::error::Error for ReadAtError {}
+
+impl ReadAt for eqs_read_at_t {
+    fn size(&self) -> std::io::Result<u64> {
+        Ok(self.size)
+    }
+
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let read_at = self.read_at.expect("eqs_read_at_t.read_at function is NULL");
+
+        let mut read = 0;
+        let status = unsafe {
+            read_at(self.user_data, offset, buffer.as_mut_ptr(), buffer.len(), &mut read)
+        };
+
+        if !status.is_success() {
+            return Err(std::io::Error::new(std::io::ErrorKind::Other, ReadAtError(status)));
+        }
+
+        return Ok(read.min(buffer.len()));
+    }
+}
 
 /// Load a tensor map from the file at the given path.
 ///
@@ -84,27 +172,11 @@ pub unsafe extern fn eqs_tensormap_load(
     let status = catch_unwind(move || {
         check_pointers!(path);
 
-        let create_array = |shape: Vec<usize>| {
-            let mut array = eqs_array_t::null();
-            let status = create_array(
-                shape.as_ptr(),
-                shape.len(),
-                &mut array
-            );
-
-            if status.is_success() {
-                return Ok(array);
-            } else {
-                return Err(Error::External {
-                    status: status,
-                    context: "failed to create a new array in eqs_tensormap_load".into()
-                });
-            }
-        };
-
         let path = CStr::from_ptr(path).to_str().expect("use UTF-8 for path");
         let file = BufReader::new(File::open(path)?);
-        let tensor = crate::io::load(file, create_array)?;
+        let tensor = crate::io::load(file, |shape| {
+            wrap_create_array(create_array, shape, "eqs_tensormap_load")
+        })?;
 
         // force the closure to capture the full unwind_wrapper, not just
         // unwind_wrapper.0
@@ -146,3 +218,670 @@ pub unsafe extern fn eqs_tensormap_save(
         Ok(())
     })
 }
+
+
+/// Options controlling how tensor maps are saved, for use with
+/// `eqs_tensormap_save_with_options`.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct eqs_save_options_t {
+    /// Compress all the labels with zstd, using a dictionary trained on the
+    /// labels of the tensor map and shared between all of them. This can
+    /// significantly reduce the size of files containing many small blocks,
+    /// but the resulting files can not be loaded with numpy.
+    pub labels_dictionary: bool,
+    /// Round the values and gradients data to keep only this number of bits
+    /// in the mantissa of the 64-bit floating point values (up to 52), and
+    /// store them in zstd-compressed `<path>.npy.zst` files. The relative
+    /// error introduced by the rounding is at most `2^-(mantissa_bits + 1)`.
+    /// Setting this to 0 keeps the data unchanged.
+    pub mantissa_bits: u32,
+}
+
+/// Save a tensor map to the file at the given path, using the given `options`.
+///
+/// If the file already exists, it is overwritten. Files saved with
+/// `eqs_save_options_t.labels_dictionary` set store the labels in
+/// zstd-compressed `<path>.npy.zst` files, and the shared dictionary in
+/// `/labels-dictionary.zstd`. Files saved with
+/// `eqs_save_options_t.mantissa_bits` set store the data arrays in
+/// zstd-compressed `<path>.npy.zst` files. They can be loaded with
+/// `eqs_tensormap_load` and the other loading functions.
+///
+/// @param path path to the file as a NULL-terminated UTF-8 string
+/// @param tensor tensor map to save to the file
+/// @param options options controlling the serialization
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_save_with_options(
+    path: *const c_char,
+    tensor: *const eqs_tensormap_t,
+    options: eqs_save_options_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(path, tensor);
+
+        let options = crate::io::SaveOptions {
+            labels_dictionary: options.labels_dictionary,
+            mantissa_bits: if options.mantissa_bits == 0 { None } else { Some(options.mantissa_bits) },
+        };
+
+        let path = CStr::from_ptr(path).to_str().expect("use UTF-8 for path");
+        let file = BufWriter::new(File::create(path)?);
+        crate::io::save_with_options(file, &*tensor, options)?;
+
+        Ok(())
+    })
+}
+
+/// Save a tensor map to the file at the given path, using the given `options`
+/// and reporting the number of blocks saved so far with `progress`.
+///
+/// If the file already exists, it is overwritten.
+///
+/// @param path path to the file as a NULL-terminated UTF-8 string
+/// @param tensor tensor map to save to the file
+/// @param options options controlling the serialization
+/// @param progress callbacks used to report the progress of this operation
+///                 and to cancel it
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_save_with_progress(
+    path: *const c_char,
+    tensor: *const eqs_tensormap_t,
+    options: eqs_save_options_t,
+    progress: eqs_progress_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(path, tensor);
+
+        let options = crate::io::SaveOptions {
+            labels_dictionary: options.labels_dictionary,
+            mantissa_bits: if options.mantissa_bits == 0 { None } else { Some(options.mantissa_bits) },
+        };
+
+        let path = CStr::from_ptr(path).to_str().expect("use UTF-8 for path");
+        let file = BufWriter::new(File::create(path)?);
+        progress.with(|progress| crate::io::save_with_progress(file, &*tensor, options, progress))?;
+
+        Ok(())
+    })
+}
+
+
+/// Load a tensor map from the given in-memory buffer.
+///
+/// This function is similar to `eqs_tensormap_load`, but reads the data from
+/// an in-memory buffer containing the same content as a file saved with
+/// `eqs_tensormap_save`, instead of a file on disk.
+///
+/// The memory allocated by this function should be released using
+/// `eqs_tensormap_free`.
+///
+/// @param buffer buffer containing a previously serialized tensor map
+/// @param buffer_count number of elements in the buffer
+/// @param create_array callback function that will be used to create data
+///                     arrays inside each block
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_load_buffer(
+    buffer: *const u8,
+    buffer_count: usize,
+    create_array: eqs_create_array_callback_t,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+    let status = catch_unwind(move || {
+        check_pointers!(buffer);
+
+        let buffer = std::slice::from_raw_parts(buffer, buffer_count);
+        let cursor = std::io::Cursor::new(buffer);
+        let tensor = crate::io::load(cursor, |shape| {
+            wrap_create_array(create_array, shape, "eqs_tensormap_load_buffer")
+        })?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *(unwind_wrapper.0) = eqs_tensormap_t::into_boxed_raw(tensor);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+/// Limits on the size of the data created when loading tensor maps, for use
+/// with `eqs_tensormap_load_with_limits` and
+/// `eqs_tensormap_load_buffer_with_limits`.
+///
+/// The sizes of the arrays and labels are read from the serialized data, and
+/// can be arbitrarily large in corrupted or malicious files. Data over these
+/// limits is refused before allocating memory for it. Setting any of the
+/// fields to 0 disables the corresponding limit.
+#[repr(C)]
+#[allow(non_camel_case_types, clippy::struct_field_names)]
+pub struct eqs_load_limits_t {
+    /// Maximal number of entries in any of the labels (keys, samples,
+    /// components or properties)
+    pub max_labels_entries: usize,
+    /// Maximal size in bytes of any array (values, gradients or strings)
+    pub max_array_bytes: usize,
+    /// Maximal number of blocks in the tensor map
+    pub max_blocks: usize,
+}
+
+impl From<eqs_load_limits_t> for crate::io::LoadLimits {
+    fn from(limits: eqs_load_limits_t) -> Self {
+        let limit = |value| if value == 0 { None } else { Some(value) };
+        return crate::io::LoadLimits {
+            max_labels_entries: limit(limits.max_labels_entries),
+            max_array_bytes: limit(limits.max_array_bytes),
+            max_blocks: limit(limits.max_blocks),
+        };
+    }
+}
+
+/// Load a tensor map from the file at the given path, refusing data larger
+/// than the given `limits`.
+///
+/// This function is similar to `eqs_tensormap_load`, and should be preferred
+/// to it when loading files from untrusted sources. Data over the limits is
+/// reported with `EQS_SERIALIZATION_ERROR`.
+///
+/// The memory allocated by this function should be released using
+/// `eqs_tensormap_free`.
+///
+/// @param path path to the file as a NULL-terminated UTF-8 string
+/// @param limits limits on the size of the loaded data
+/// @param create_array callback function that will be used to create data
+///                     arrays inside each block
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_load_with_limits(
+    path: *const c_char,
+    limits: eqs_load_limits_t,
+    create_array: eqs_create_array_callback_t,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+    let status = catch_unwind(move || {
+        check_pointers!(path);
+
+        let path = CStr::from_ptr(path).to_str().expect("use UTF-8 for path");
+        let file = BufReader::new(File::open(path)?);
+        let tensor = crate::io::load_with_limits(file, limits.into(), |shape| {
+            wrap_create_array(create_array, shape, "eqs_tensormap_load_with_limits")
+        })?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *(unwind_wrapper.0) = eqs_tensormap_t::into_boxed_raw(tensor);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+/// Load a tensor map from the given in-memory buffer, refusing data larger
+/// than the given `limits`.
+///
+/// This function is similar to `eqs_tensormap_load_buffer`, see
+/// `eqs_tensormap_load_with_limits` for more information about the limits.
+///
+/// The memory allocated by this function should be released using
+/// `eqs_tensormap_free`.
+///
+/// @param buffer buffer containing a previously serialized tensor map
+/// @param buffer_count number of elements in the buffer
+/// @param limits limits on the size of the loaded data
+/// @param create_array callback function that will be used to create data
+///                     arrays inside each block
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_load_buffer_with_limits(
+    buffer: *const u8,
+    buffer_count: usize,
+    limits: eqs_load_limits_t,
+    create_array: eqs_create_array_callback_t,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+    let status = catch_unwind(move || {
+        check_pointers!(buffer);
+
+        let buffer = std::slice::from_raw_parts(buffer, buffer_count);
+        let cursor = std::io::Cursor::new(buffer);
+        let tensor = crate::io::load_with_limits(cursor, limits.into(), |shape| {
+            wrap_create_array(create_array, shape, "eqs_tensormap_load_buffer_with_limits")
+        })?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *(unwind_wrapper.0) = eqs_tensormap_t::into_boxed_raw(tensor);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+/// Load a tensor map from the file at the given path, refusing data larger
+/// than the given `limits` and reporting the number of blocks loaded so far
+/// with `progress`.
+///
+/// See `eqs_tensormap_load_with_limits` for more information about the limits.
+///
+/// The memory allocated by this function should be released using
+/// `eqs_tensormap_free`.
+///
+/// @param path path to the file as a NULL-terminated UTF-8 string
+/// @param limits limits on the size of the loaded data
+/// @param progress callbacks used to report the progress of this operation
+///                 and to cancel it
+/// @param create_array callback function that will be used to create data
+///                     arrays inside each block
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_load_with_progress(
+    path: *const c_char,
+    limits: eqs_load_limits_t,
+    progress: eqs_progress_t,
+    create_array: eqs_create_array_callback_t,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+    let status = catch_unwind(move || {
+        check_pointers!(path);
+
+        let path = CStr::from_ptr(path).to_str().expect("use UTF-8 for path");
+        let file = BufReader::new(File::open(path)?);
+        let tensor = progress.with(|progress| {
+            crate::io::load_blocks_with_progress(file, None, limits.into(), progress, |shape| {
+                wrap_create_array(create_array, shape, "eqs_tensormap_load_with_progress")
+            })
+        })?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *(unwind_wrapper.0) = eqs_tensormap_t::into_boxed_raw(tensor);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+/// Save a tensor map to an in-memory buffer.
+///
+/// The data is written directly to the buffer, which is grown as needed with
+/// the `realloc` callback. On input, `*buffer` should contain either `NULL` or
+/// a pointer previously allocated with `realloc`, and `*buffer_count` the size
+/// of this allocation. The existing allocation is re-used if it is large
+/// enough. On output, `*buffer` contains the (possibly new) allocation and
+/// `*buffer_count` the size of the serialized data, which can be smaller than
+/// the size of the allocation.
+///
+/// The serialized data is the same as the content of a file created by
+/// `eqs_tensormap_save`.
+///
+/// @param buffer pointer to the buffer which will contain the serialized data
+/// @param buffer_count pointer to the size of the existing allocation, which
+///                     will be set to the size of the serialized data
+/// @param realloc_user_data custom data for the `realloc` callback. This will
+///                          be passed as the first argument to `realloc` as-is.
+/// @param realloc function to allocate and grow the buffer
+/// @param tensor tensor map to save
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_save_buffer(
+    buffer: *mut *mut u8,
+    buffer_count: *mut usize,
+    realloc_user_data: *mut c_void,
+    realloc: eqs_realloc_buffer_t,
+    tensor: *const eqs_tensormap_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(buffer, buffer_count, tensor);
+
+        let mut writer = ReallocBuffer {
+            data: *buffer,
+            capacity: if (*buffer).is_null() { 0 } else { *buffer_count },
+            len: 0,
+            position: 0,
+            user_data: realloc_user_data,
+            realloc: realloc,
+        };
+
+        let result = crate::io::save(&mut writer, &*tensor);
+
+        // the buffer might have been re-allocated even if saving failed
+        *buffer = writer.data;
+        *buffer_count = writer.len;
+
+        return result.map_err(|error| match error {
+            Error::Io(error) if error.kind() == std::io::ErrorKind::OutOfMemory => {
+                Error::OutOfMemory(error.to_string())
+            },
+            error => error,
+        });
+    })
+}
+
+/// Implementation of `std::io::Write` and `std::io::Seek` writing directly to
+/// a buffer managed by a `eqs_realloc_buffer_t` callback.
+struct ReallocBuffer {
+    /// pointer to the allocation, or NULL
+    data: *mut u8,
+    /// size of the allocation
+    capacity: usize,
+    /// number of bytes written to the buffer
+    len: usize,
+    /// current position for writes
+    position: usize,
+    user_data: *mut c_void,
+    realloc: eqs_realloc_buffer_t,
+}
+
+impl ReallocBuffer {
+    /// Make sure the allocation can contain at least `size` bytes
+    fn reserve(&mut self, size: usize) -> std::io::Result<()> {
+        if size <= self.capacity && !self.data.is_null() {
+            return Ok(());
+        }
+
+        // grow geometrically to limit the number of calls to `realloc`
+        let new_capacity = size.max(self.capacity.saturating_mul(2)).max(4096);
+        let data = unsafe { (self.realloc)(self.user_data, self.data, new_capacity) };
+        if data.is_null() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::OutOfMemory,
+                format!("failed to allocate {} bytes with the realloc callback", new_capacity),
+            ));
+        }
+
+        self.data = data;
+        self.capacity = new_capacity;
+
+        return Ok(());
+    }
+}
+
+impl std::io::Write for ReallocBuffer {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        let end = self.position.checked_add(data.len()).ok_or_else(|| std::io::Error::new(
+            std::io::ErrorKind::InvalidInput, "buffer size overflow"
+        ))?;
+        self.reserve(end)?;
+
+        unsafe {
+            if self.position > self.len {
+                // zero-fill the gap left after seeking past the end
+                std::ptr::write_bytes(self.data.add(self.len), 0, self.position - self.len);
+            }
+            std::ptr::copy_nonoverlapping(data.as_ptr(), self.data.add(self.position), data.len());
+        }
+
+        self.position = end;
+        self.len = self.len.max(end);
+
+        return Ok(data.len());
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::io::Seek for ReallocBuffer {
+    fn seek(&mut self, position: std::io::SeekFrom) -> std::io::Result<u64> {
+        let (base, offset) = match position {
+            std::io::SeekFrom::Start(offset) => (offset, 0),
+            std::io::SeekFrom::End(offset) => (self.len as u64, offset),
+            std::io::SeekFrom::Current(offset) => (self.position as u64, offset),
+        };
+
+        let new_position = if offset >= 0 {
+            base.checked_add(offset.unsigned_abs())
+        } else {
+            base.checked_sub(offset.unsigned_abs())
+        };
+
+        let new_position = new_position
+            .and_then(|position| usize::try_from(position).ok())
+            .ok_or_else(|| std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            ))?;
+
+        self.position = new_position;
+        return Ok(new_position as u64);
+    }
+}
+
+/// Load some or all of the blocks of a tensor map from the given `reader`.
+///
+/// This function is similar to `eqs_tensormap_load`, but reads the data
+/// through the `reader.read_at` callback instead of from a file. Only the
+/// parts of the data that are needed are read: the ZIP central directory, the
+/// keys and the files of the requested blocks.
+///
+/// The memory allocated by this function should be released using
+/// `eqs_tensormap_free`.
+///
+/// @param reader callback-based random access to the serialized data
+/// @param blocks indexes of the blocks to load, in the order in which they
+///               should appear in the new tensor map. If this is `NULL`, all
+///               blocks are loaded.
+/// @param blocks_count number of entries in the `blocks` array
+/// @param create_array callback function that will be used to create data
+///                     arrays inside each block
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_load_read_at(
+    reader: eqs_read_at_t,
+    blocks: *const usize,
+    blocks_count: usize,
+    create_array: eqs_create_array_callback_t,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+    let reader = std::panic::AssertUnwindSafe(reader);
+    let status = catch_unwind(move || {
+        if reader.read_at.is_none() {
+            return Err(Error::InvalidParameter(
+                "got invalid NULL pointer for reader.read_at".into()
+            ));
+        }
+
+        let selection = if blocks.is_null() {
+            None
+        } else {
+            Some(std::slice::from_raw_parts(blocks, blocks_count))
+        };
+
+        let reader = BufReader::with_capacity(READ_AT_BUFFER_SIZE, ReadAtReader::new(&*reader)?);
+        let tensor = crate::io::load_blocks(reader, selection, |shape| {
+            wrap_create_array(create_array, shape, "eqs_tensormap_load_read_at")
+        }).map_err(|error| match error {
+            Error::Io(error) if is_read_at_error(&error) => {
+                let status = error.into_inner()
+                    .and_then(|e| e.downcast::<ReadAtError>().ok())
+                    .expect("checked above that this is a ReadAtError")
+                    .0;
+
+                Error::External {
+                    status: status,
+                    context: "failed to read data in eqs_tensormap_load_read_at".into(),
+                }
+            },
+            error => error,
+        })?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *(unwind_wrapper.0) = eqs_tensormap_t::into_boxed_raw(tensor);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+/// Size of the buffer used to group small reads in `eqs_tensormap_load_read_at`
+const READ_AT_BUFFER_SIZE: usize = 64 * 1024;
+
+fn is_read_at_error(error: &std::io::Error) -> bool {
+    error.get_ref().map_or(false, |e| e.is::<ReadAtError>())
+}
+
+/// Call the `create_array` callback, and convert its status to an `Error`
+unsafe fn wrap_create_array(
+    create_array: eqs_create_array_callback_t,
+    shape: Vec<usize>,
+    function: &str,
+) -> Result<eqs_array_t, Error> {
+    let create_array = match create_array {
+        Some(create_array) => create_array,
+        None => return Ok(zeros_array(shape)),
+    };
+
+    let mut array = eqs_array_t::null();
+    let status = create_array(
+        shape.as_ptr(),
+        shape.len(),
+        &mut array
+    );
+
+    if status.is_success() {
+        return Ok(array);
+    } else {
+        return Err(Error::External {
+            status: status,
+            context: format!("failed to create a new array in {}", function),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Seek, SeekFrom, Write};
+
+    use super::*;
+
+    unsafe extern fn realloc_vec(user_data: *mut c_void, _ptr: *mut u8, new_size: usize) -> *mut u8 {
+        let vector = &mut *user_data.cast::<Vec<u8>>();
+        vector.resize(new_size, 0);
+        return vector.as_mut_ptr();
+    }
+
+    unsafe extern fn failing_realloc(_user_data: *mut c_void, _ptr: *mut u8, _new_size: usize) -> *mut u8 {
+        return std::ptr::null_mut();
+    }
+
+    fn realloc_buffer(user_data: *mut c_void, realloc: eqs_realloc_buffer_t) -> ReallocBuffer {
+        ReallocBuffer {
+            data: std::ptr::null_mut(),
+            capacity: 0,
+            len: 0,
+            position: 0,
+            user_data: user_data,
+            realloc: realloc,
+        }
+    }
+
+    fn write_and_seek<W: Write + Seek>(writer: &mut W) {
+        writer.write_all(b"hello").unwrap();
+        writer.seek(SeekFrom::Start(1)).unwrap();
+        writer.write_all(b"EL").unwrap();
+        writer.seek(SeekFrom::End(10)).unwrap();
+        writer.write_all(&[7; 10000]).unwrap();
+        writer.seek(SeekFrom::Current(-3)).unwrap();
+        writer.write_all(b"end").unwrap();
+        assert!(writer.seek(SeekFrom::Current(-20000)).is_err());
+    }
+
+    #[test]
+    fn write_to_realloc_buffer() {
+        let mut vector = Vec::new();
+        let mut writer = realloc_buffer((&mut vector as *mut Vec<u8>).cast(), realloc_vec);
+        write_and_seek(&mut writer);
+
+        let mut expected = std::io::Cursor::new(Vec::new());
+        write_and_seek(&mut expected);
+        let expected = expected.into_inner();
+
+        assert_eq!(writer.len, expected.len());
+        assert_eq!(writer.capacity, vector.len());
+        assert_eq!(writer.data, vector.as_mut_ptr());
+        assert_eq!(vector[..writer.len], expected);
+    }
+
+    #[test]
+    fn load_without_callback() {
+        let buffer = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data.npz")).unwrap();
+        unsafe {
+            let tensor = eqs_tensormap_load_buffer(buffer.as_ptr(), buffer.len(), None);
+            assert!(!tensor.is_null());
+
+            let block = &(*tensor).blocks()[0];
+            assert_eq!(block.values().data.shape().unwrap(), [18, 1, 3]);
+            assert_eq!(block.values().data.data().unwrap().len(), 18 * 3);
+
+            assert!(crate::c_api::tensor::eqs_tensormap_free(tensor).is_success());
+        }
+    }
+
+    #[test]
+    fn realloc_failure() {
+        let mut writer = realloc_buffer(std::ptr::null_mut(), failing_realloc);
+
+        let error = writer.write_all(b"hello").unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::OutOfMemory);
+        assert_eq!(error.to_string(), "failed to allocate 4096 bytes with the realloc callback");
+    }
+}
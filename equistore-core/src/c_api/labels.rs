@@ -2,8 +2,9 @@ use std::os::raw::{c_char, c_void};
 use std::ffi::CStr;
 use std::sync::Arc;
 
-use crate::{LabelValue, Labels, LabelsBuilder, Error};
+use crate::{Labels, LabelsBuilder, Error};
 use super::status::{eqs_status_t, catch_unwind};
+use super::utils::copy_str_to_c;
 
 /// A set of labels used to carry metadata associated with a tensor map.
 ///
@@ -34,7 +35,9 @@ pub struct eqs_labels_t {
     pub values: *const i32,
     /// Number of dimensions/size of a single entry in the set of labels
     pub size: usize,
-    /// Number entries in the set of labels
+    /// Number entries in the set of labels. Labels without dimensions
+    /// (`size == 0`) can contain at most one (empty) entry, in which case
+    /// `names` and `values` can be NULL.
     pub count: usize,
 }
 
@@ -54,7 +57,7 @@ pub unsafe fn rust_to_eqs_labels(labels: Arc<Labels>) -> eqs_labels_t {
     let values = if labels.count() == 0 || labels.size() == 0 {
         std::ptr::null()
     } else {
-        (&labels[0][0] as *const LabelValue).cast()
+        labels.values_as_i32().as_ptr()
     };
 
     let names = if labels.size() == 0 {
@@ -98,14 +101,21 @@ pub unsafe fn eqs_labels_to_rust(labels: &eqs_labels_t) -> Result<Arc<Labels>, E
 unsafe fn create_rust_labels(labels: &eqs_labels_t) -> Result<Arc<Labels>, Error> {
     assert!(!labels.is_rust());
 
-    if labels.names.is_null() {
+    if labels.names.is_null() && labels.size > 0 {
         return Err(Error::InvalidParameter("labels.names can not be NULL in eqs_labels_t".into()))
     }
 
-    if labels.values.is_null() && labels.count > 0 {
+    if labels.values.is_null() && labels.count > 0 && labels.size > 0 {
         return Err(Error::InvalidParameter("labels.values is NULL but labels.count is >0 in eqs_labels_t".into()))
     }
 
+    if labels.size == 0 && labels.count > 1 {
+        return Err(Error::InvalidParameter(format!(
+            "labels without dimensions can contain at most one entry, got {} in eqs_labels_t",
+            labels.count
+        )))
+    }
+
     let mut names = Vec::new();
     for i in 0..labels.size {
         let name = CStr::from_ptr(*(labels.names.add(i)));
@@ -118,14 +128,23 @@ unsafe fn create_rust_labels(labels: &eqs_labels_t) -> Result<Arc<Labels>, Error
         names.push(name);
     }
 
+    for (i, name) in names.iter().enumerate() {
+        if names[..i].contains(name) {
+            return Err(Error::InvalidParameter(format!(
+                "invalid labels: the name '{}' is used multiple times", name
+            )));
+        }
+    }
+
     let mut builder = LabelsBuilder::new(names);
-    builder.reserve(labels.count);
 
-    let slice = std::slice::from_raw_parts(labels.values.cast::<LabelValue>(), labels.count * labels.size);
-    if !slice.is_empty() {
-        for chunk in slice.chunks_exact(labels.size) {
-            builder.add(chunk)?;
+    if labels.size == 0 {
+        if labels.count == 1 {
+            builder.add_i32_row(&[])?;
         }
+    } else if labels.count != 0 {
+        let slice = std::slice::from_raw_parts(labels.values, labels.count * labels.size);
+        builder.add_all_i32(slice)?;
     }
 
     return Ok(Arc::new(builder.finish()));
@@ -168,8 +187,122 @@ pub unsafe extern fn eqs_labels_position(
             )));
         }
 
-        let label = std::slice::from_raw_parts(values.cast(), values_count);
-        *result = labels.position(label).map_or(-1, |p| p as i64);
+        let label = std::slice::from_raw_parts(values, values_count);
+        *result = match labels.position_of_ints(label) {
+            Some(position) => i64::try_from(position).map_err(|_| Error::InvalidParameter(format!(
+                "position {} of an entry in these labels does not fit in a 64-bit integer", position
+            )))?,
+            None => -1,
+        };
+
+        Ok(())
+    })
+}
+
+/// Get a description of the entries in `labels` as a numpy structured dtype,
+/// allowing to view the labels values as an array of records with named
+/// fields without copying them. This operation is only available if the labels
+/// correspond to a set of Rust Labels (i.e. `labels.internal_ptr_` is not
+/// NULL).
+///
+/// The dtype is written to `buffer` in the format used by numpy's
+/// `__array_interface__["descr"]`, e.g. `[('structure', '<i4'), ('center',
+/// '<i4')]`, with one native-endian 32-bit integer field per dimension.
+///
+/// @param labels set of labels with an associated Rust data structure
+/// @param buffer buffer to be filled with the dtype description, as an UTF-8
+///               encoded, NULL-terminated string
+/// @param buffer_size size of the buffer
+/// @param values will be set to a pointer to the first entry in the labels, or
+///               NULL if the labels are empty. Entries are packed one after the
+///               other, each one taking `4 * labels.size` bytes.
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_labels_structured_dtype(
+    labels: eqs_labels_t,
+    buffer: *mut c_char,
+    buffer_size: usize,
+    values: *mut *const i32,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(buffer, values);
+        if !labels.is_rust() {
+            return Err(Error::InvalidParameter(
+                "these labels do not support calling eqs_labels_structured_dtype, \
+                call eqs_labels_create first".into()
+            ));
+        }
+
+        let labels = &(*labels.internal_ptr_.cast::<Labels>());
+        *values = if labels.count() == 0 || labels.size() == 0 {
+            std::ptr::null()
+        } else {
+            labels.values_as_i32().as_ptr()
+        };
+
+        return copy_str_to_c(&labels.structured_dtype(), buffer, buffer_size);
+    })
+}
+
+/// Get the positions of multiple entries in the given set of `labels`, doing
+/// all the lookups in a single call. This operation is only available if the
+/// labels correspond to a set of Rust Labels (i.e. `labels.internal_ptr_` is
+/// not NULL).
+///
+/// @param labels set of labels with an associated Rust data structure
+/// @param queries pointer to the first element of a 2D row-major array
+///                containing the entries to lookup. Each row should contain
+///                `labels.size` elements, and there are `queries_count` rows
+///                in total.
+/// @param queries_count number of entries to lookup
+/// @param results pointer to an array of size `queries_count`, which will be
+///                filled with the position of each entry in the labels, or -1
+///                if the entry was not found
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_labels_positions(
+    labels: eqs_labels_t,
+    queries: *const i32,
+    queries_count: usize,
+    results: *mut i64,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        if !labels.is_rust() {
+            return Err(Error::InvalidParameter(
+                "these labels do not support calling eqs_labels_positions, \
+                call eqs_labels_create first".into()
+            ));
+        }
+
+        if queries_count == 0 {
+            return Ok(());
+        }
+        check_pointers!(queries, results);
+
+        let labels = &(*labels.internal_ptr_.cast::<Labels>());
+        let results = std::slice::from_raw_parts_mut(results, queries_count);
+        if labels.size() == 0 {
+            // the only possible entry in labels without dimensions is the
+            // empty one, which is present for scalar labels
+            results.fill(if labels.count() == 1 { 0 } else { -1 });
+            return Ok(());
+        }
+
+        let queries = std::slice::from_raw_parts(queries, queries_count * labels.size());
+        for (query, result) in queries.chunks_exact(labels.size()).zip(results) {
+            *result = match labels.position_of_ints(query) {
+                Some(position) => i64::try_from(position).map_err(|_| Error::InvalidParameter(format!(
+                    "position {} of an entry in these labels does not fit in a 64-bit integer", position
+                )))?,
+                None => -1,
+            };
+        }
 
         Ok(())
     })
@@ -182,6 +315,10 @@ pub unsafe extern fn eqs_labels_position(
 /// This allows using the `eqs_labels_positions` and `eqs_labels_clone`
 /// functions on the `eqs_labels_t`.
 ///
+/// All the `labels.count` entries in `labels.values` are validated (checking
+/// the names and that there are no duplicated entries) and copied in a single
+/// operation, so this function can be used to create large labels efficiently.
+///
 /// This function allocates memory which must be released `eqs_labels_free` when
 /// you don't need it anymore.
 ///
@@ -0,0 +1,418 @@
+use std::os::raw::c_char;
+use std::ffi::CStr;
+
+use crate::Error;
+use crate::tensor::{LazyTensorMap, MissingGradients, UnaryOp};
+
+use super::tensor::eqs_tensormap_t;
+use super::blocks::eqs_block_t;
+use super::status::{eqs_status_t, catch_unwind};
+
+/// Opaque type representing a lazily evaluated chain of element-wise
+/// operations on a `eqs_tensormap_t`.
+///
+/// The lazy tensor map only keeps pointers to the tensor maps used as source
+/// and in binary operations, these tensor maps must stay alive (and must not
+/// be modified) until the lazy tensor map is freed.
+#[allow(non_camel_case_types)]
+pub struct eqs_lazy_tensormap_t(LazyTensorMap<'static>);
+
+/// Start a new chain of lazy element-wise operations from the given `source`
+/// tensor map.
+///
+/// Operations are only recorded by the `eqs_lazy_tensormap_*` functions, and
+/// nothing is computed until `eqs_lazy_tensormap_compute` or
+/// `eqs_lazy_tensormap_compute_block` is called. All the recorded operations
+/// are then applied in a single pass over the data, without allocating
+/// intermediary arrays. Gradients are propagated through all operations.
+///
+/// `source` must stay alive (and must not be modified) until the lazy tensor
+/// map is freed with `eqs_lazy_tensormap_free`.
+///
+/// @param source pointer to an existing tensor map
+///
+/// @returns A pointer to the newly allocated lazy tensor map, or a `NULL`
+///          pointer in case of error. In case of error, you can use
+///          `eqs_last_error()` to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_lazy_tensormap(
+    source: *const eqs_tensormap_t,
+) -> *mut eqs_lazy_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+
+    let status = catch_unwind(move || {
+        check_pointers!(source);
+
+        let lazy = LazyTensorMap::new(&*source);
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *unwind_wrapper.0 = Box::into_raw(Box::new(eqs_lazy_tensormap_t(lazy)));
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+/// Free the memory associated with a `lazy` tensor map previously created with
+/// `eqs_lazy_tensormap`. This does not free the tensor maps used in the
+/// operations.
+///
+/// If `lazy` is `NULL`, this function does nothing.
+///
+/// @param lazy pointer to an existing lazy tensor map, or `NULL`
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_lazy_tensormap_free(lazy: *mut eqs_lazy_tensormap_t) -> eqs_status_t {
+    catch_unwind(|| {
+        if !lazy.is_null() {
+            std::mem::drop(Box::from_raw(lazy));
+        }
+
+        Ok(())
+    })
+}
+
+/// Set the policy used by the binary operations recorded after this call when
+/// only some of the tensor maps contain gradients with respect to a given
+/// parameter. The default is `"error"`.
+///
+/// `missing_gradients` must be one of `"error"` (return an error), `"zero"`
+/// (treat missing gradients as zero-filled, with the same metadata as the
+/// other tensor maps) or `"drop"` (only keep the gradients present in all
+/// tensor maps).
+///
+/// @param lazy pointer to an existing lazy tensor map
+/// @param missing_gradients how to handle missing gradients, as a
+///        NULL-terminated UTF-8 string
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_lazy_tensormap_missing_gradients(
+    lazy: *mut eqs_lazy_tensormap_t,
+    missing_gradients: *const c_char,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(lazy, missing_gradients);
+
+        let missing = CStr::from_ptr(missing_gradients).to_str().expect("invalid utf8");
+        let missing = missing.parse::<MissingGradients>()?;
+
+        (*lazy).0 = (*lazy).0.clone().missing_gradients(missing);
+
+        Ok(())
+    })
+}
+
+/// Allow the binary operations recorded after this call to use tensor maps
+/// whose keys only contain a subset of the keys dimensions of the source
+/// tensor map. Each block of the source is then combined with the block of
+/// the other tensor map with the same values for the common keys dimensions.
+/// The default is `false`, requiring both tensor maps to have the same keys.
+///
+/// @param lazy pointer to an existing lazy tensor map
+/// @param broadcast whether to broadcast keys in binary operations
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_lazy_tensormap_broadcast_keys(
+    lazy: *mut eqs_lazy_tensormap_t,
+    broadcast: bool,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(lazy);
+
+        (*lazy).0 = (*lazy).0.clone().broadcast_keys(broadcast);
+
+        Ok(())
+    })
+}
+
+/// Add a unary element-wise operation to the chain of operations in `lazy`.
+///
+/// `operation` must be one of `"add_scalar"` (`x + value`),
+/// `"multiply_scalar"` (`x * value`), `"pow"` (`x ^ value`), `"abs"`,
+/// `"sqrt"`, `"exp"` or `"log"`. `value` is ignored by the operations which
+/// do not use it.
+///
+/// @param lazy pointer to an existing lazy tensor map
+/// @param operation name of the operation, as a NULL-terminated UTF-8 string
+/// @param value parameter of the operation
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_lazy_tensormap_unary(
+    lazy: *mut eqs_lazy_tensormap_t,
+    operation: *const c_char,
+    value: f64,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(lazy, operation);
+
+        let operation = match CStr::from_ptr(operation).to_str().expect("invalid UTF8 operation") {
+            "add_scalar" => UnaryOp::AddScalar(value),
+            "multiply_scalar" => UnaryOp::MultiplyScalar(value),
+            "pow" => UnaryOp::Pow(value),
+            "abs" => UnaryOp::Abs,
+            "sqrt" => UnaryOp::Sqrt,
+            "exp" => UnaryOp::Exp,
+            "log" => UnaryOp::Log,
+            operation => return Err(Error::InvalidParameter(format!(
+                "unknown unary operation '{}', expected one of 'add_scalar', \
+                'multiply_scalar', 'pow', 'abs', 'sqrt', 'exp' or 'log'", operation
+            ))),
+        };
+
+        (*lazy).0 = (*lazy).0.clone().unary(operation);
+
+        Ok(())
+    })
+}
+
+/// Add `other` to the current result of the operations in `lazy`. `other`
+/// must have the same metadata as the source of `lazy`.
+///
+/// `other` must stay alive (and must not be modified) until `lazy` is freed.
+///
+/// @param lazy pointer to an existing lazy tensor map
+/// @param other pointer to an existing tensor map
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_lazy_tensormap_add(
+    lazy: *mut eqs_lazy_tensormap_t,
+    other: *const eqs_tensormap_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(lazy, other);
+
+        (*lazy).0 = (*lazy).0.clone().add(&*other)?;
+
+        Ok(())
+    })
+}
+
+/// Multiply the current result of the operations in `lazy` by `other`.
+/// `other` must have the same metadata as the source of `lazy`.
+///
+/// `other` must stay alive (and must not be modified) until `lazy` is freed.
+///
+/// @param lazy pointer to an existing lazy tensor map
+/// @param other pointer to an existing tensor map
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_lazy_tensormap_multiply(
+    lazy: *mut eqs_lazy_tensormap_t,
+    other: *const eqs_tensormap_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(lazy, other);
+
+        (*lazy).0 = (*lazy).0.clone().multiply(&*other)?;
+
+        Ok(())
+    })
+}
+
+/// Evaluate all the operations recorded in `lazy` for all blocks, and return
+/// the resulting tensor map.
+///
+/// The result is a new tensor map, which should be freed with
+/// `eqs_tensormap_free`.
+///
+/// @param lazy pointer to an existing lazy tensor map
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_lazy_tensormap_compute(
+    lazy: *const eqs_lazy_tensormap_t,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+
+    let status = catch_unwind(move || {
+        check_pointers!(lazy);
+
+        let tensor = (*lazy).0.compute()?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *unwind_wrapper.0 = eqs_tensormap_t::into_boxed_raw(tensor);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+/// Evaluate all the operations recorded in `lazy` for the block at the given
+/// `index` only, and return the resulting block.
+///
+/// The result is a new block, which should be freed with `eqs_block_free`.
+///
+/// @param lazy pointer to an existing lazy tensor map
+/// @param index index of the block to compute
+///
+/// @returns A pointer to the newly allocated block, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_lazy_tensormap_compute_block(
+    lazy: *const eqs_lazy_tensormap_t,
+    index: usize,
+) -> *mut eqs_block_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+
+    let status = catch_unwind(move || {
+        check_pointers!(lazy);
+
+        let block = (*lazy).0.compute_block(index)?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *unwind_wrapper.0 = eqs_block_t::into_boxed_raw(block);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{Labels, LabelsBuilder, LabelValue, TensorBlock, TensorMap};
+    use crate::data::TestArray;
+
+    use super::*;
+    use super::super::status::EQS_INVALID_PARAMETER_ERROR;
+    use super::super::tensor::eqs_tensormap_free;
+    use super::super::blocks::eqs_block_free;
+
+    fn new_tensor(names: Vec<&str>, keys: &[&[i32]], values: &[f64]) -> *mut eqs_tensormap_t {
+        fn labels(names: Vec<&str>, entries: &[&[i32]]) -> Labels {
+            let mut builder = LabelsBuilder::new(names);
+            for entry in entries {
+                builder.add(&entry.iter().copied().map(LabelValue::from).collect::<Vec<_>>()).unwrap();
+            }
+            return builder.finish();
+        }
+
+        let blocks = values.iter().map(|&value| TensorBlock::new(
+            TestArray::from_data(vec![1, 1], vec![value]),
+            Arc::new(labels(vec!["samples"], &[&[0]])),
+            vec![],
+            Arc::new(labels(vec!["properties"], &[&[0]])),
+        ).unwrap()).collect();
+
+        let tensor = TensorMap::new(labels(names, keys), blocks).unwrap();
+        return eqs_tensormap_t::into_boxed_raw(tensor);
+    }
+
+    #[test]
+    fn broadcast_keys() {
+        unsafe {
+            let tensor = new_tensor(vec!["key", "l"], &[&[0, 0], &[1, 0], &[0, 1]], &[1.0, 2.0, 3.0]);
+            let weights = new_tensor(vec!["key"], &[&[1], &[0]], &[10.0, 2.0]);
+
+            let lazy = eqs_lazy_tensormap(tensor);
+            assert!(!lazy.is_null());
+
+            let status = eqs_lazy_tensormap_multiply(lazy, weights);
+            assert_eq!(status.as_i32(), EQS_INVALID_PARAMETER_ERROR);
+
+            assert!(eqs_lazy_tensormap_broadcast_keys(lazy, true).is_success());
+            assert!(eqs_lazy_tensormap_multiply(lazy, weights).is_success());
+
+            let result = eqs_lazy_tensormap_compute(lazy);
+            assert!(!result.is_null());
+            assert_eq!((*result).keys(), (*tensor).keys());
+            assert_eq!((*result).blocks()[0].values().data.data().unwrap(), [2.0]);
+            assert_eq!((*result).blocks()[1].values().data.data().unwrap(), [20.0]);
+            assert_eq!((*result).blocks()[2].values().data.data().unwrap(), [6.0]);
+
+            assert!(eqs_tensormap_free(result).is_success());
+            assert!(eqs_lazy_tensormap_free(lazy).is_success());
+            assert!(eqs_tensormap_free(weights).is_success());
+            assert!(eqs_tensormap_free(tensor).is_success());
+        }
+    }
+
+    #[test]
+    fn lazy_operations() {
+        let file = std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data.npz")).unwrap();
+        let tensor = crate::io::load(file, |shape| Ok(TestArray::new(shape))).unwrap();
+        let tensor = eqs_tensormap_t::into_boxed_raw(tensor);
+        unsafe {
+            let lazy = eqs_lazy_tensormap(tensor);
+            assert!(!lazy.is_null());
+
+            let status = eqs_lazy_tensormap_unary(lazy, b"multiply_scalar\0".as_ptr().cast(), 2.0);
+            assert!(status.is_success());
+            let status = eqs_lazy_tensormap_add(lazy, tensor);
+            assert!(status.is_success());
+
+            let status = eqs_lazy_tensormap_unary(lazy, b"nothing\0".as_ptr().cast(), 2.0);
+            assert_eq!(status.as_i32(), EQS_INVALID_PARAMETER_ERROR);
+
+            let status = eqs_lazy_tensormap_missing_gradients(lazy, b"zero\0".as_ptr().cast());
+            assert!(status.is_success());
+            let status = eqs_lazy_tensormap_missing_gradients(lazy, b"nothing\0".as_ptr().cast());
+            assert_eq!(status.as_i32(), EQS_INVALID_PARAMETER_ERROR);
+
+            let result = eqs_lazy_tensormap_compute(lazy);
+            assert!(!result.is_null());
+            assert_eq!((*result).keys(), (*tensor).keys());
+
+            let expected = (*tensor).blocks()[0].values().data.data().unwrap()
+                .iter()
+                .map(|x| 3.0 * x)
+                .collect::<Vec<_>>();
+            assert_eq!((*result).blocks()[0].values().data.data().unwrap(), expected);
+
+            let block = eqs_lazy_tensormap_compute_block(lazy, 0);
+            assert!(!block.is_null());
+            assert_eq!((*block).values().data.data().unwrap(), expected);
+
+            let block_out_of_bounds = eqs_lazy_tensormap_compute_block(lazy, 10000);
+            assert!(block_out_of_bounds.is_null());
+
+            assert!(eqs_block_free(block).is_success());
+            assert!(eqs_tensormap_free(result).is_success());
+            assert!(eqs_lazy_tensormap_free(lazy).is_success());
+            assert!(eqs_tensormap_free(tensor).is_success());
+        }
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::doc_markdown)]
 use std::ffi::CString;
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_void};
+use std::sync::Arc;
 
 use once_cell::sync::Lazy;
 
@@ -22,8 +23,12 @@ pub use self::blocks::eqs_block_t;
 pub mod tensor;
 pub use self::tensor::eqs_tensormap_t;
 
+pub mod progress;
+
 pub mod io;
 
+pub mod lazy;
+
 mod utils;
 
 /// Disable printing of the message to stderr when some Rust code reach a panic.
@@ -48,15 +53,118 @@ pub extern fn eqs_disable_panic_printing() {
     }));
 }
 
+/// Function pointer called by equistore to report non-fatal warnings, with
+/// the `message` of the warning as a NULL-terminated UTF-8 string. The message
+/// is only valid during the call to this function.
+#[allow(non_camel_case_types)]
+pub type eqs_warning_callback_t = Option<unsafe extern fn(
+    user_data: *mut c_void,
+    message: *const c_char,
+)>;
+
+/// Wrapper around the user data of `eqs_warning_callback_t`, which can be
+/// used from multiple threads
+struct WarningUserData(*mut c_void);
+
+// SAFETY: the user registering the callback is responsible for making sure it
+// can be called from any thread
+unsafe impl Send for WarningUserData {}
+unsafe impl Sync for WarningUserData {}
+
+impl WarningUserData {
+    fn get(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+/// Set the function to call when equistore emits a non-fatal warning,
+/// replacing any previously registered callback. The warnings are ignored by
+/// default, or if `callback` is `NULL`.
+///
+/// The callback can be called from any thread, and should be thread-safe.
+///
+/// @param callback function to call with each warning message, or `NULL`
+/// @param user_data custom data for the `callback`. This will be passed as
+///                  the first argument to `callback` as-is.
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_set_warning_callback(
+    callback: eqs_warning_callback_t,
+    user_data: *mut c_void,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        let callback = callback.map(|callback| {
+            let user_data = WarningUserData(user_data);
+            let callback: crate::warnings::WarningCallback = Arc::new(move |message: &str| {
+                let message = CString::new(message).expect("warning message contains a NULL byte");
+                unsafe {
+                    callback(user_data.get(), message.as_ptr());
+                }
+            });
+            callback
+        });
+
+        crate::warnings::set_warning_callback(callback);
+        Ok(())
+    })
+}
+
+/// Major version of the equistore library corresponding to this header
+pub const EQS_VERSION_MAJOR: u32 = 0;
+/// Minor version of the equistore library corresponding to this header
+pub const EQS_VERSION_MINOR: u32 = 1;
+/// Patch version of the equistore library corresponding to this header
+pub const EQS_VERSION_PATCH: u32 = 0;
+
+/// Version of the C ABI of equistore corresponding to this header. This number
+/// is incremented every time a backward-incompatible change is made to the
+/// functions or types in the C API.
+pub const EQS_ABI_VERSION: u32 = 1;
+
 static VERSION: Lazy<CString> = Lazy::new(|| {
-    CString::new(env!("CARGO_PKG_VERSION")).expect("version contains NULL byte")
+    let mut version = format!("{}.{}.{}", EQS_VERSION_MAJOR, EQS_VERSION_MINOR, EQS_VERSION_PATCH);
+    let pre_release = env!("CARGO_PKG_VERSION_PRE");
+    if !pre_release.is_empty() {
+        version.push('-');
+        version.push_str(pre_release);
+    }
+    CString::new(version).expect("version contains NULL byte")
 });
 
 
 /// Get the version of the core equistore library as a string.
 ///
-/// This version should follow the `<major>.<minor>.<patch>[-<dev>]` format.
+/// This version should follow the `<major>.<minor>.<patch>[-<dev>]` format,
+/// where `<major>`, `<minor>` and `<patch>` are the values of
+/// `EQS_VERSION_MAJOR`, `EQS_VERSION_MINOR` and `EQS_VERSION_PATCH` in the
+/// header corresponding to this library.
 #[no_mangle]
 pub extern fn eqs_version() -> *const c_char {
     return VERSION.as_ptr();
 }
+
+/// Get the version of the C ABI implemented by the core equistore library.
+///
+/// Code loading equistore dynamically (e.g. plugins) should check that this
+/// is equal to the `EQS_ABI_VERSION` of the header they were compiled with
+/// before calling any other function.
+#[no_mangle]
+pub extern fn eqs_abi_version() -> u32 {
+    return EQS_ABI_VERSION;
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn version_constants() {
+        assert_eq!(super::EQS_VERSION_MAJOR.to_string(), env!("CARGO_PKG_VERSION_MAJOR"));
+        assert_eq!(super::EQS_VERSION_MINOR.to_string(), env!("CARGO_PKG_VERSION_MINOR"));
+        assert_eq!(super::EQS_VERSION_PATCH.to_string(), env!("CARGO_PKG_VERSION_PATCH"));
+
+        let version = unsafe { std::ffi::CStr::from_ptr(super::eqs_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+}
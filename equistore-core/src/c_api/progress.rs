@@ -0,0 +1,58 @@
+use std::os::raw::c_void;
+
+use crate::Progress;
+
+/// `eqs_progress_t` reports the progress of long-running operations
+/// (serialization and keys restructuring), as the number of blocks processed
+/// so far out of the total number of blocks; and allows to cancel these
+/// operations.
+///
+/// This can be used to display progress bars while these operations run, and
+/// to abort them cleanly from user interfaces.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct eqs_progress_t {
+    /// User-provided data should be stored here, it will be passed as the
+    /// first parameter to `callback` and `cancelled`.
+    pub user_data: *mut c_void,
+    /// Function called after each block is processed, with `done` blocks
+    /// processed so far out of `total`. If this is `NULL`, progress is not
+    /// reported.
+    pub callback: Option<unsafe extern fn(
+        user_data: *mut c_void,
+        done: usize,
+        total: usize,
+    )>,
+    /// Function called before each block is processed. If it returns `true`,
+    /// the operation stops and returns `EQS_CANCELLED_ERROR`. If this is
+    /// `NULL`, the operation can not be cancelled.
+    pub cancelled: Option<unsafe extern fn(user_data: *mut c_void) -> bool>,
+}
+
+impl eqs_progress_t {
+    /// Call `callback` (if any) with the given progress
+    pub fn report(&self, done: usize, total: usize) {
+        if let Some(callback) = self.callback {
+            unsafe {
+                callback(self.user_data, done, total);
+            }
+        }
+    }
+
+    /// Call `cancelled` (if any) to check if the operation should be cancelled
+    pub fn is_cancelled(&self) -> bool {
+        if let Some(cancelled) = self.cancelled {
+            unsafe {
+                return cancelled(self.user_data);
+            }
+        }
+        return false;
+    }
+
+    /// Call `function` with a `Progress` forwarding to this `eqs_progress_t`
+    pub fn with<T>(&self, function: impl FnOnce(Progress) -> T) -> T {
+        let report = |done, total| self.report(done, total);
+        let cancelled = || self.is_cancelled();
+        return function(Progress::new(&report).with_cancellation(&cancelled));
+    }
+}
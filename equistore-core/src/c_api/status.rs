@@ -44,6 +44,10 @@ pub const EQS_IO_ERROR: i32 = 2;
 /// Status code indicating errors in the serialization format when
 /// loading/writing `eqs_tensormap_t` to a file
 pub const EQS_SERIALIZATION_ERROR: i32 = 3;
+/// Status code used when memory allocation failed
+pub const EQS_OUT_OF_MEMORY_ERROR: i32 = 4;
+/// Status code used when an operation was cancelled through `eqs_progress_t`
+pub const EQS_CANCELLED_ERROR: i32 = 5;
 
 /// Status code used when a memory buffer is too small to fit the requested data
 pub const EQS_BUFFER_SIZE_ERROR: i32 = 254;
@@ -60,8 +64,14 @@ impl From<Error> for eqs_status_t {
         });
         match error {
             Error::InvalidParameter(_) => eqs_status_t(EQS_INVALID_PARAMETER_ERROR),
+            // out of bounds indexes are a kind of invalid parameter
+            Error::OutOfBounds {..} => eqs_status_t(EQS_INVALID_PARAMETER_ERROR),
+            // data over the loading limits is reported as a format error
+            Error::LimitExceeded(_) => eqs_status_t(EQS_SERIALIZATION_ERROR),
             Error::Io(_) => eqs_status_t(EQS_IO_ERROR),
             Error::Serialization(_) => eqs_status_t(EQS_SERIALIZATION_ERROR),
+            Error::OutOfMemory(_) => eqs_status_t(EQS_OUT_OF_MEMORY_ERROR),
+            Error::Cancelled => eqs_status_t(EQS_CANCELLED_ERROR),
             Error::BufferSize(_) => eqs_status_t(EQS_BUFFER_SIZE_ERROR),
             Error::External {status, .. } => status,
             Error::Internal(_) => eqs_status_t(EQS_INTERNAL_ERROR),
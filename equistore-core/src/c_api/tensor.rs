@@ -1,13 +1,19 @@
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_void};
 use std::sync::Arc;
 use std::ffi::CStr;
 use std::collections::BTreeSet;
 
-use crate::{TensorMap, TensorBlock, Error};
+use crate::{TensorMap, TensorBlock, LabelsBuilder, Error, eqs_array_t};
+use crate::tensor::{diff, DiffOptions};
+use crate::tensor::{align_properties, intersect_samples, MemoryUsage};
+use crate::tensor::{unify_gradients, MissingGradients};
+use crate::tensor::tensor_product;
 
 use super::labels::{eqs_labels_t, rust_to_eqs_labels, eqs_labels_to_rust};
 use super::blocks::eqs_block_t;
+use super::progress::eqs_progress_t;
 use super::status::{eqs_status_t, catch_unwind};
+use super::utils::copy_str_to_c;
 
 /// Opaque type representing a `TensorMap`.
 #[allow(non_camel_case_types)]
@@ -144,12 +150,67 @@ pub unsafe extern fn eqs_tensormap_copy(
     let status = catch_unwind(move || {
         check_pointers!(tensor);
         let new_tensor = (*tensor).try_clone()?;
-        let boxed = Box::new(eqs_tensormap_t(new_tensor));
 
         // force the closure to capture the full unwind_wrapper, not just
         // unwind_wrapper.0
         let _ = &unwind_wrapper;
-        *(unwind_wrapper.0) = Box::into_raw(boxed);
+        *(unwind_wrapper.0) = eqs_tensormap_t::into_boxed_raw(new_tensor);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+
+/// Create a new `eqs_tensormap_t` with the same keys, labels and gradients as
+/// `tensor`, using the given `arrays` as the values of the blocks. This can be
+/// used to re-create the same tensor map structure around new data without
+/// copying any of the metadata.
+///
+/// The new tensor map takes ownership of all the arrays, which will be
+/// released with `array.destroy(array.ptr)` when they are no longer needed,
+/// including if this function fails. The memory allocated by this function
+/// should be released using `eqs_tensormap_free`.
+///
+/// @param tensor existing tensor map
+/// @param arrays arrays containing the new values, one for each block of the
+///               tensor map, in the same order as the blocks. Each array must
+///               have the same shape as the corresponding block values.
+/// @param arrays_count number of elements in the `arrays` array
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer
+///          in case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_with_values(
+    tensor: *const eqs_tensormap_t,
+    arrays: *mut eqs_array_t,
+    arrays_count: usize,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+    let status = catch_unwind(move || {
+        check_pointers!(tensor);
+
+        let mut new_values = Vec::with_capacity(arrays_count);
+        if arrays_count != 0 {
+            check_pointers!(arrays);
+            for i in 0..arrays_count {
+                // move out of the arrays, the caller gave us ownership
+                new_values.push(std::ptr::read(arrays.add(i)));
+            }
+        }
+
+        let new_tensor = (*tensor).with_values(new_values)?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *(unwind_wrapper.0) = eqs_tensormap_t::into_boxed_raw(new_tensor);
         Ok(())
     });
 
@@ -205,7 +266,8 @@ pub unsafe extern fn eqs_tensormap_keys(
 ///
 /// @returns The status code of this operation. If the status is not
 ///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
-///          error message.
+///          error message. If `index` is out of bounds, this returns
+///          `EQS_INVALID_PARAMETER_ERROR`.
 #[no_mangle]
 pub unsafe extern fn eqs_tensormap_block_by_id(
     tensor: *mut eqs_tensormap_t,
@@ -215,7 +277,76 @@ pub unsafe extern fn eqs_tensormap_block_by_id(
     catch_unwind(|| {
         check_pointers!(tensor, block);
 
-        (*block) = (&mut (*tensor).blocks_mut()[index] as *mut TensorBlock).cast();
+        let blocks = (*tensor).blocks_mut();
+        let count = blocks.len();
+        let rust_block = blocks.get_mut(index).ok_or(Error::OutOfBounds { index, count })?;
+        (*block) = (rust_block as *mut TensorBlock).cast();
+
+        Ok(())
+    })
+}
+
+
+/// Function pointer called by `eqs_tensormap_foreach_block` for each block
+/// of a tensor map.
+///
+/// The callback gets the `index` of the block, the values of the corresponding
+/// `key` (containing one value for each dimension of the keys), and a pointer
+/// to the `block`. The block memory is managed by the tensor map, and follows
+/// the same rules as blocks obtained with `eqs_tensormap_block_by_id`. If the
+/// callback returns a non-zero `eqs_status_t`, the iteration stops.
+#[allow(non_camel_case_types)]
+pub type eqs_block_callback_t = Option<unsafe extern fn(
+    user_data: *mut c_void,
+    index: usize,
+    key: *const i32,
+    block: *mut eqs_block_t,
+) -> eqs_status_t>;
+
+/// Call `callback` for each block in this `tensor` map, in the same order as
+/// the keys. This gives access to all blocks with a single function call,
+/// instead of calling `eqs_tensormap_keys` and `eqs_tensormap_block_by_id`
+/// repeatedly.
+///
+/// The tensor map must not be modified by `callback`.
+///
+/// @param tensor pointer to an existing tensor map
+/// @param callback function to call for each block
+/// @param user_data custom data for the `callback`. This will be passed as
+///                  the first argument to `callback` as-is.
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message. If `callback` returns a non-zero status, this
+///          function returns the same status.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_foreach_block(
+    tensor: *mut eqs_tensormap_t,
+    callback: eqs_block_callback_t,
+    user_data: *mut c_void,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(tensor);
+        let callback = callback.ok_or_else(|| Error::InvalidParameter(
+            "got a NULL callback in eqs_tensormap_foreach_block".into()
+        ))?;
+
+        let keys = Arc::clone((*tensor).keys());
+        for (index, (key, block)) in keys.iter().zip((*tensor).blocks_mut()).enumerate() {
+            let status = callback(
+                user_data,
+                index,
+                key.as_ptr().cast(),
+                (block as *mut TensorBlock).cast(),
+            );
+
+            if !status.is_success() {
+                return Err(Error::External {
+                    status: status,
+                    context: format!("callback failed for block {} in eqs_tensormap_foreach_block", index),
+                });
+            }
+        }
 
         Ok(())
     })
@@ -270,6 +401,127 @@ pub unsafe extern fn eqs_tensormap_blocks_matching(
     })
 }
 
+/// Get a view over the blocks in this `tensor` matching the given `selection`,
+/// using the same rules as `eqs_tensormap_blocks_matching`. The blocks are not
+/// copied, making this a cheap way to iterate over subsets of a large tensor
+/// map.
+///
+/// The keys of the selected blocks are stored in `keys`, which contain all the
+/// dimensions of the tensor map keys. This function allocates memory for
+/// `keys` which must be released with `eqs_labels_free` when you don't need it
+/// anymore.
+///
+/// When calling this function, `*count` should contain the number of entries
+/// in `blocks`, which must be the same as the number of blocks in the tensor
+/// map. When the function returns successfully, `*count` will contain the
+/// number of blocks matching the selection, i.e. how many pointers were
+/// written to `blocks`. These blocks follow the same rules as blocks obtained
+/// with `eqs_tensormap_block_by_id`: their memory is managed by the tensor map
+/// and they should not be freed.
+///
+/// @param tensor pointer to an existing tensor map
+/// @param selection labels with a single entry describing which blocks are
+///                  requested
+/// @param keys pointer to be filled with the keys of the selected blocks
+/// @param blocks array to be filled with pointers to the selected blocks
+/// @param count number of entries in `blocks`
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_view(
+    tensor: *mut eqs_tensormap_t,
+    selection: eqs_labels_t,
+    keys: *mut eqs_labels_t,
+    blocks: *mut *mut eqs_block_t,
+    count: *mut usize,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(tensor, keys, blocks, count);
+
+        if *count != (*tensor).keys().count() {
+            return Err(Error::InvalidParameter(format!(
+                "expected space for {} blocks as input to eqs_tensormap_view, got space for {}",
+                (*tensor).keys().count(), *count
+            )));
+        }
+
+        if (*keys).is_rust() {
+            return Err(Error::InvalidParameter(
+                "these labels are already allocated, call eqs_labels_free first".into()
+            ));
+        }
+
+        let selection = eqs_labels_to_rust(&selection)?;
+        let mut view = (*tensor).view(&selection)?;
+        let view_keys = Arc::new(view.keys().clone());
+
+        let blocks = std::slice::from_raw_parts_mut(blocks, *count);
+        let mut view_count = 0;
+        for (output, block) in blocks.iter_mut().zip(view.blocks_mut()) {
+            *output = (block as *mut TensorBlock).cast();
+            view_count += 1;
+        }
+
+        *count = view_count;
+        *keys = rust_to_eqs_labels(view_keys);
+
+        Ok(())
+    })
+}
+
+/// Compress in memory the data of all blocks in this `tensor` matching the
+/// given `selection`, using the same rules as `eqs_tensormap_blocks_matching`.
+///
+/// The data of compressed blocks is stored with zstd, and is decompressed the
+/// first time it is accessed through `eqs_array_t.data`. Compressed arrays use
+/// a separate data origin, use `eqs_tensormap_decompress_blocks` to convert
+/// them back to the original array type.
+///
+/// @param tensor pointer to an existing tensor map
+/// @param selection labels with a single entry describing which blocks should
+///                  be compressed
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_compress_blocks(
+    tensor: *mut eqs_tensormap_t,
+    selection: eqs_labels_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(tensor);
+
+        let selection = eqs_labels_to_rust(&selection)?;
+        (*tensor).compress_blocks(&selection)?;
+
+        Ok(())
+    })
+}
+
+
+/// Convert the data of all blocks compressed with
+/// `eqs_tensormap_compress_blocks` in this `tensor` back to the original array
+/// type.
+///
+/// @param tensor pointer to an existing tensor map
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_decompress_blocks(
+    tensor: *mut eqs_tensormap_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(tensor);
+        (*tensor).decompress_blocks()?;
+        Ok(())
+    })
+}
+
 
 /// Merge blocks with the same value for selected keys dimensions along the
 /// property axis.
@@ -337,6 +589,51 @@ pub unsafe extern fn eqs_tensormap_keys_to_properties(
     return result;
 }
 
+/// Same as `eqs_tensormap_keys_to_properties`, reporting the number of new blocks
+/// created so far with `progress`.
+///
+/// @param tensor pointer to an existing tensor map
+/// @param keys_to_move description of the keys to move
+/// @param sort_samples whether to sort the samples lexicographically after
+///                     merging blocks
+/// @param progress callbacks used to report the progress of this operation
+///                 and to cancel it
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_keys_to_properties_with_progress(
+    tensor: *const eqs_tensormap_t,
+    keys_to_move: eqs_labels_t,
+    sort_samples: bool,
+    progress: eqs_progress_t,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+
+    let status = catch_unwind(move || {
+        check_pointers!(tensor);
+
+        let keys_to_move = eqs_labels_to_rust(&keys_to_move)?;
+        let moved = progress.with(|progress| {
+            (*tensor).keys_to_properties_with_progress(&keys_to_move, sort_samples, progress)
+        })?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *unwind_wrapper.0 = eqs_tensormap_t::into_boxed_raw(moved);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
 
 /// Move the given dimensions from the component labels to the property labels
 /// for each block in this tensor map.
@@ -443,3 +740,1071 @@ pub unsafe extern fn eqs_tensormap_keys_to_samples(
 
     return result;
 }
+
+/// Same as `eqs_tensormap_keys_to_samples`, reporting the number of new blocks
+/// created so far with `progress`.
+///
+/// @param tensor pointer to an existing tensor map
+/// @param keys_to_move description of the keys to move
+/// @param sort_samples whether to sort the samples lexicographically after
+///                     merging blocks
+/// @param progress callbacks used to report the progress of this operation
+///                 and to cancel it
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_keys_to_samples_with_progress(
+    tensor: *const eqs_tensormap_t,
+    keys_to_move: eqs_labels_t,
+    sort_samples: bool,
+    progress: eqs_progress_t,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+
+    let status = catch_unwind(move || {
+        check_pointers!(tensor);
+
+        let keys_to_move = eqs_labels_to_rust(&keys_to_move)?;
+        let moved = progress.with(|progress| {
+            (*tensor).keys_to_samples_with_progress(&keys_to_move, sort_samples, progress)
+        })?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *unwind_wrapper.0 = eqs_tensormap_t::into_boxed_raw(moved);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+/// Get a canonical version of this `tensor` map, where the keys are sorted in
+/// lexicographic order, and the samples and properties of each block (and the
+/// samples of each gradient) are sorted as well. The data arrays are re-ordered
+/// accordingly.
+///
+/// Two tensor maps containing the same data in different order will give the
+/// same result after canonicalization, and will be saved to the same bytes by
+/// `eqs_tensormap_save`. Components are not re-ordered.
+///
+/// The result is a new tensor map, which should be freed with `eqs_tensormap_free`.
+///
+/// @param tensor pointer to an existing tensor map
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_canonicalize(
+    tensor: *const eqs_tensormap_t,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+
+    let status = catch_unwind(move || {
+        check_pointers!(tensor);
+
+        let canonical = (*tensor).canonicalize()?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *unwind_wrapper.0 = eqs_tensormap_t::into_boxed_raw(canonical);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+/// Get a new tensor map where the blocks of `tensor` are sorted according to
+/// the values of the key dimensions in `names`.
+///
+/// The first dimension in `names` has the highest priority, and later
+/// dimensions are only used to break ties. Blocks with the same values for all
+/// the dimensions in `names` keep their relative order. If `descending` is
+/// `true`, the blocks are sorted in descending order instead.
+///
+/// The permutation applied to the blocks is stored in `permutation`, which
+/// must contain space for as many entries as there are keys in the tensor map:
+/// the block at index `i` in the new tensor map corresponds to the block at
+/// index `permutation[i]` in `tensor`.
+///
+/// The result is a new tensor map, which should be freed with `eqs_tensormap_free`.
+///
+/// @param tensor pointer to an existing tensor map
+/// @param names names of the key dimensions to use for sorting, as an array
+///              of `names_count` NULL-terminated UTF-8 strings
+/// @param names_count number of entries in the `names` array
+/// @param descending whether to sort the blocks in descending order
+/// @param permutation array to be filled with the permutation of the blocks
+/// @param permutation_count number of entries in the `permutation` array
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_sort_keys(
+    tensor: *const eqs_tensormap_t,
+    names: *const *const c_char,
+    names_count: usize,
+    descending: bool,
+    permutation: *mut usize,
+    permutation_count: usize,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+
+    let status = catch_unwind(move || {
+        check_pointers!(tensor);
+
+        let mut rust_names = Vec::new();
+        if names_count != 0 {
+            check_pointers!(names);
+            for &name in std::slice::from_raw_parts(names, names_count) {
+                check_pointers!(name);
+                rust_names.push(CStr::from_ptr(name).to_str().expect("invalid utf8"));
+            }
+        }
+
+        if permutation_count != (*tensor).keys().count() {
+            return Err(Error::InvalidParameter(format!(
+                "expected space for {} blocks in the permutation given to eqs_tensormap_sort_keys, got space for {}",
+                (*tensor).keys().count(), permutation_count
+            )));
+        }
+
+        let (sorted, rust_permutation) = (*tensor).sort_keys(&rust_names, descending)?;
+        if permutation_count != 0 {
+            check_pointers!(permutation);
+            let permutation = std::slice::from_raw_parts_mut(permutation, permutation_count);
+            permutation.copy_from_slice(&rust_permutation);
+        }
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *unwind_wrapper.0 = eqs_tensormap_t::into_boxed_raw(sorted);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+/// Get a new tensor map containing all the blocks of `tensor`, except for the
+/// blocks matching the given `selection`.
+///
+/// The `selection` should have a subset of the names/dimensions of the keys of
+/// the tensor map, and a single entry. All the blocks matching this entry are
+/// removed, and the keys of the new tensor map only contain the entries for
+/// the remaining blocks.
+///
+/// The result is a new tensor map, which should be freed with `eqs_tensormap_free`.
+///
+/// @param tensor pointer to an existing tensor map
+/// @param selection labels with a single entry describing which blocks should
+///                  be removed
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_drop_blocks(
+    tensor: *const eqs_tensormap_t,
+    selection: eqs_labels_t,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+
+    let status = catch_unwind(move || {
+        check_pointers!(tensor);
+
+        let selection = eqs_labels_to_rust(&selection)?;
+        let new_tensor = (*tensor).drop_blocks(&selection)?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *unwind_wrapper.0 = eqs_tensormap_t::into_boxed_raw(new_tensor);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+/// Compute a stable 64-bit fingerprint of this `tensor` map, and store it in
+/// `fingerprint`.
+///
+/// The fingerprint covers all the metadata (keys, samples, components,
+/// properties, gradients, units, quantities and tensor map metadata) and the
+/// shape of all arrays. If `include_data` is `true`, the values stored in the
+/// arrays are also part of the fingerprint.
+///
+/// The fingerprint does not depend on the platform, the process or the version
+/// of equistore, and can be used as a cache key. It does depend on the order of
+/// entries in the labels, use `eqs_tensormap_canonicalize` first to ignore it.
+///
+/// @param tensor pointer to an existing tensor map
+/// @param include_data whether to include the values of the arrays in the
+///                     fingerprint
+/// @param fingerprint pointer to be filled with the fingerprint
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_fingerprint(
+    tensor: *const eqs_tensormap_t,
+    include_data: bool,
+    fingerprint: *mut u64,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(tensor, fingerprint);
+
+        *fingerprint = (*tensor).fingerprint(include_data)?;
+
+        Ok(())
+    })
+}
+
+/// Compare the two tensor maps `first` and `second`, matching blocks by key,
+/// and report the differences in metadata, keys, units, labels, shapes and
+/// data.
+///
+/// `equal` will be set to `true` if no difference was found. If `buffer` is not
+/// `NULL`, a text rendering of all the differences is written to it as an UTF-8
+/// encoded, NULL-terminated string.
+///
+/// @param first pointer to an existing tensor map
+/// @param second pointer to another existing tensor map
+/// @param compare_data whether to compare the values inside the arrays
+/// @param tolerance absolute tolerance used when comparing the data
+/// @param equal pointer to be filled with the result of the comparison
+/// @param buffer buffer to be filled with the text report, or `NULL`
+/// @param buffer_size size of the buffer
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_diff(
+    first: *const eqs_tensormap_t,
+    second: *const eqs_tensormap_t,
+    compare_data: bool,
+    tolerance: f64,
+    equal: *mut bool,
+    buffer: *mut c_char,
+    buffer_size: usize,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(first, second, equal);
+
+        let options = DiffOptions { compare_data, tolerance };
+        let report = diff(&*first, &*second, options)?;
+
+        *equal = report.is_empty();
+        if !buffer.is_null() {
+            copy_str_to_c(&report.to_string(), buffer, buffer_size)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Compare the two tensor maps `first` and `second`, matching blocks by key,
+/// and write a structured report of the differences to `buffer` as an UTF-8
+/// encoded, NULL-terminated JSON string.
+///
+/// The JSON document is an object with the `metadata`, `keys_names`,
+/// `only_in_first`, `only_in_second` and `blocks` fields. Each entry in
+/// `blocks` contains the `key` of the block, the gradient `parameter` (or
+/// `"values"`) and a `difference` object, with a `kind` field set to one of
+/// `"unit"`, `"quantity"`, `"missing_gradient"`, `"samples"`, `"components"`,
+/// `"properties"`, `"shape"` or `"data"`. All the lists are empty if no
+/// difference was found.
+///
+/// If the buffer is too small to hold the report, this function returns
+/// `EQS_BUFFER_SIZE_ERROR`, and it should be called again with a larger buffer.
+///
+/// @param first pointer to an existing tensor map
+/// @param second pointer to another existing tensor map
+/// @param compare_data whether to compare the values inside the arrays
+/// @param tolerance absolute tolerance used when comparing the data
+/// @param buffer buffer to be filled with the JSON report
+/// @param buffer_size size of the buffer
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_diff_json(
+    first: *const eqs_tensormap_t,
+    second: *const eqs_tensormap_t,
+    compare_data: bool,
+    tolerance: f64,
+    buffer: *mut c_char,
+    buffer_size: usize,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(first, second, buffer);
+
+        let options = DiffOptions { compare_data, tolerance };
+        let report = diff(&*first, &*second, options)?;
+        copy_str_to_c(&report.to_json(), buffer, buffer_size)?;
+
+        Ok(())
+    })
+}
+
+/// Expand all the blocks in all the `tensors` to use the same set of
+/// properties, i.e. the union of the properties of all blocks. Entries
+/// corresponding to properties missing in a block are filled with zeros.
+///
+/// The resulting tensor maps can be directly summed or concatenated along
+/// samples. The union of properties is sorted in lexicographic order.
+///
+/// `aligned` must point to an array of `tensors_count` pointers, which will be
+/// set to the newly allocated tensor maps, in the same order as `tensors`.
+/// These should be freed with `eqs_tensormap_free`.
+///
+/// @param tensors array of pointers to existing tensor maps
+/// @param tensors_count number of entries in the `tensors` and `aligned` arrays
+/// @param aligned array of pointers to be filled with the aligned tensor maps
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_align_properties(
+    tensors: *const *const eqs_tensormap_t,
+    tensors_count: usize,
+    aligned: *mut *mut eqs_tensormap_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        if tensors_count == 0 {
+            return Ok(());
+        }
+        check_pointers!(tensors, aligned);
+
+        let mut rust_tensors = Vec::new();
+        for &tensor in std::slice::from_raw_parts(tensors, tensors_count) {
+            check_pointers!(tensor);
+            rust_tensors.push(&**tensor);
+        }
+
+        let result = align_properties(&rust_tensors)?;
+
+        let aligned = std::slice::from_raw_parts_mut(aligned, tensors_count);
+        for (output, tensor) in aligned.iter_mut().zip(result) {
+            *output = eqs_tensormap_t::into_boxed_raw(tensor);
+        }
+
+        Ok(())
+    })
+}
+
+/// Restrict both `first` and `second` tensor maps to the samples they have in
+/// common.
+///
+/// Blocks are matched by key, and both tensor maps must contain the same set of
+/// keys. For each pair of blocks, only the samples present in both blocks are
+/// kept, in the order they appear in the block from `first`; and gradients are
+/// updated accordingly.
+///
+/// The new tensor maps are stored in `first_output` and `second_output`, and
+/// should be freed with `eqs_tensormap_free`. If `mappings` is not `NULL`, it
+/// must point to an array of `mappings_count` labels (one for each key in
+/// `first`), which will be filled with the positions of the kept samples in
+/// the original blocks, as labels with two dimensions: `first` and `second`.
+/// These labels must be freed with `eqs_labels_free`.
+///
+/// @param first pointer to an existing tensor map
+/// @param second pointer to another existing tensor map
+/// @param first_output pointer to be filled with the restricted `first`
+/// @param second_output pointer to be filled with the restricted `second`
+/// @param mappings array of labels to be filled with the samples positions,
+///                 or `NULL`
+/// @param mappings_count number of entries in the `mappings` array
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_intersect_samples(
+    first: *const eqs_tensormap_t,
+    second: *const eqs_tensormap_t,
+    first_output: *mut *mut eqs_tensormap_t,
+    second_output: *mut *mut eqs_tensormap_t,
+    mappings: *mut eqs_labels_t,
+    mappings_count: usize,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(first, second, first_output, second_output);
+
+        if !mappings.is_null() && mappings_count != (*first).keys().count() {
+            return Err(Error::InvalidParameter(format!(
+                "expected space for {} mappings as input to eqs_tensormap_intersect_samples, got space for {}",
+                (*first).keys().count(), mappings_count
+            )));
+        }
+
+        let (first, second, intersections) = intersect_samples(&*first, &*second)?;
+
+        if !mappings.is_null() {
+            let mappings = std::slice::from_raw_parts_mut(mappings, mappings_count);
+            for (output, intersection) in mappings.iter_mut().zip(intersections) {
+                let mut builder = LabelsBuilder::new(vec!["first", "second"]);
+                builder.reserve(intersection.first.len());
+                for (&first_i, &second_i) in intersection.first.iter().zip(&intersection.second) {
+                    builder.add(&[first_i, second_i])?;
+                }
+                *output = rust_to_eqs_labels(Arc::new(builder.finish()));
+            }
+        }
+
+        *first_output = eqs_tensormap_t::into_boxed_raw(first);
+        *second_output = eqs_tensormap_t::into_boxed_raw(second);
+
+        Ok(())
+    })
+}
+
+/// Make all the `tensors` carry gradients with respect to the same parameters.
+///
+/// Different tensor maps can contain gradients with respect to different
+/// parameters, which prevents merging or combining them. The gradients only
+/// present in some of the tensor maps are handled according to
+/// `missing_gradients`, which must be one of:
+///
+/// - `"error"`: return an error if some tensor maps are missing gradients;
+/// - `"zero"`: add the missing gradients, filled with zeros. Since gradients
+///   are sparse, these new gradients do not contain any samples;
+/// - `"drop"`: remove all gradients which are not present in every tensor map.
+///
+/// The new tensor maps are stored in `outputs`, which must contain space for
+/// `tensors_count` pointers. They should be freed with `eqs_tensormap_free`.
+///
+/// @param tensors array of pointers to existing tensor maps
+/// @param tensors_count number of entries in the `tensors` and `outputs` arrays
+/// @param missing_gradients how to handle missing gradients
+/// @param outputs array of pointers to be filled with the new tensor maps
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_unify_gradients(
+    tensors: *const *const eqs_tensormap_t,
+    tensors_count: usize,
+    missing_gradients: *const c_char,
+    outputs: *mut *mut eqs_tensormap_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(missing_gradients);
+        let missing = CStr::from_ptr(missing_gradients).to_str().expect("invalid utf8");
+        let missing = missing.parse::<MissingGradients>()?;
+
+        if tensors_count == 0 {
+            return Ok(());
+        }
+        check_pointers!(tensors, outputs);
+
+        let mut rust_tensors = Vec::with_capacity(tensors_count);
+        for &tensor in std::slice::from_raw_parts(tensors, tensors_count) {
+            check_pointers!(tensor);
+            rust_tensors.push(&**tensor);
+        }
+
+        let results = unify_gradients(&rust_tensors, missing)?;
+
+        let outputs = std::slice::from_raw_parts_mut(outputs, tensors_count);
+        for (output, result) in outputs.iter_mut().zip(results) {
+            *output = eqs_tensormap_t::into_boxed_raw(result);
+        }
+
+        Ok(())
+    })
+}
+
+/// Compute the outer product of the properties of `first` and `second`.
+///
+/// Both tensor maps must have the same keys (possibly in a different order),
+/// and the blocks with the same key must have the same samples and components.
+/// The properties of the new blocks are the cartesian product of the
+/// properties of the two blocks, with the names of the properties prefixed by
+/// `first_` and `second_` respectively. The keys of the result are the keys of
+/// `first`. Blocks with gradients are not supported.
+///
+/// The result is a new tensor map, which should be freed with `eqs_tensormap_free`.
+///
+/// @param first pointer to an existing tensor map
+/// @param second pointer to another existing tensor map
+///
+/// @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in
+///          case of error. In case of error, you can use `eqs_last_error()`
+///          to get the error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_product(
+    first: *const eqs_tensormap_t,
+    second: *const eqs_tensormap_t,
+) -> *mut eqs_tensormap_t {
+    let mut result = std::ptr::null_mut();
+    let unwind_wrapper = std::panic::AssertUnwindSafe(&mut result);
+
+    let status = catch_unwind(move || {
+        check_pointers!(first, second);
+
+        let new_tensor = tensor_product(&*first, &*second)?;
+
+        // force the closure to capture the full unwind_wrapper, not just
+        // unwind_wrapper.0
+        let _ = &unwind_wrapper;
+        *unwind_wrapper.0 = eqs_tensormap_t::into_boxed_raw(new_tensor);
+        Ok(())
+    });
+
+    if !status.is_success() {
+        return std::ptr::null_mut();
+    }
+
+    return result;
+}
+
+/// Estimation of the memory used by some part of a tensor map, in bytes, for
+/// use with `eqs_tensormap_memory_usage`.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct eqs_memory_usage_t {
+    /// memory used by the values data arrays, assuming they contain 64-bit
+    /// floating point values
+    pub data: usize,
+    /// memory used to store the values (and names) of the labels
+    pub labels_values: usize,
+    /// memory used by the hash maps to lookup the position of labels entries
+    pub labels_positions: usize,
+    /// memory used by the gradients, including both data and labels
+    pub gradients: usize,
+}
+
+impl From<MemoryUsage> for eqs_memory_usage_t {
+    fn from(usage: MemoryUsage) -> eqs_memory_usage_t {
+        eqs_memory_usage_t {
+            data: usage.data,
+            labels_values: usage.labels_values,
+            labels_positions: usage.labels_positions,
+            gradients: usage.gradients,
+        }
+    }
+}
+
+/// Estimate the memory used by this `tensor` map, in bytes.
+///
+/// The memory used by the keys is stored in `keys_usage`, and the memory used
+/// by each block (including labels and gradients) is stored in `blocks_usage`,
+/// which must contain space for as many entries as there are keys in the
+/// tensor map. The total memory used by the tensor map is stored in `total`.
+/// Labels shared between multiple blocks are only counted once in `total`.
+/// The size of data arrays is estimated from their shape, assuming they
+/// contain 64-bit floating point values, since `eqs_array_t` does not expose
+/// the actual type of the data. The memory used by arrays storing other types
+/// (e.g. 32-bit floating point values) is over- or under-estimated
+/// accordingly.
+///
+/// @param tensor pointer to an existing tensor map
+/// @param keys_usage pointer to be filled with the memory used by the keys
+/// @param blocks_usage array to be filled with the memory used by each block
+/// @param blocks_count number of entries in the `blocks_usage` array
+/// @param total pointer to be filled with the total memory used
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_memory_usage(
+    tensor: *const eqs_tensormap_t,
+    keys_usage: *mut eqs_memory_usage_t,
+    blocks_usage: *mut eqs_memory_usage_t,
+    blocks_count: usize,
+    total: *mut eqs_memory_usage_t,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(tensor, keys_usage, total);
+
+        if blocks_count != (*tensor).keys().count() {
+            return Err(Error::InvalidParameter(format!(
+                "expected space for {} blocks as input to eqs_tensormap_memory_usage, got space for {}",
+                (*tensor).keys().count(), blocks_count
+            )));
+        }
+
+        let usage = (*tensor).memory_usage()?;
+        if blocks_count != 0 {
+            check_pointers!(blocks_usage);
+            let blocks_usage = std::slice::from_raw_parts_mut(blocks_usage, blocks_count);
+            for (output, block) in blocks_usage.iter_mut().zip(&usage.blocks) {
+                *output = (*block).into();
+            }
+        }
+
+        *keys_usage = usage.keys.into();
+        *total = usage.total.into();
+
+        Ok(())
+    })
+}
+
+/// Move the values of all the labels in this `tensor` map (keys, and samples,
+/// components and properties of all blocks and gradients) to a single buffer,
+/// sharing identical labels between blocks.
+///
+/// This reduces the number of allocations and the memory fragmentation for
+/// tensor maps containing many small blocks. Labels obtained before calling
+/// this function with `eqs_block_labels` stay valid, and keep using their own
+/// copy of the values until they are freed.
+///
+/// @param tensor pointer to an existing tensor map
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_compact_labels(tensor: *mut eqs_tensormap_t) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(tensor);
+        (*tensor).compact_labels();
+        Ok(())
+    })
+}
+
+
+/// Get the metadata value associated with `key` in this `tensor` map.
+///
+/// @param tensor pointer to an existing tensor map
+/// @param key NULL-terminated UTF-8 string containing the metadata key
+/// @param value will be set to a NULL-terminated UTF-8 string containing the
+///              corresponding value, or to `NULL` if there is no metadata
+///              associated with this key. The string is owned by the tensor
+///              map, and only valid until the metadata is modified or the
+///              tensor map is freed.
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_metadata(
+    tensor: *const eqs_tensormap_t,
+    key: *const c_char,
+    value: *mut *const c_char,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(tensor, key, value);
+        let key = CStr::from_ptr(key).to_str().expect("invalid UTF8 metadata key");
+
+        (*value) = match (*tensor).get_metadata_c(key) {
+            Some(value) => value.as_c_str().as_ptr(),
+            None => std::ptr::null(),
+        };
+        Ok(())
+    })
+}
+
+
+/// Set the metadata value associated with `key` in this `tensor` map.
+///
+/// Metadata is free-form information attached to the tensor map (provenance,
+/// hyper-parameters, code version, ...) which is preserved by copies and
+/// serialization.
+///
+/// @param tensor pointer to an existing tensor map
+/// @param key NULL-terminated UTF-8 string containing the metadata key
+/// @param value NULL-terminated UTF-8 string containing the new value, or
+///              `NULL` to remove the metadata associated with `key`
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_set_metadata(
+    tensor: *mut eqs_tensormap_t,
+    key: *const c_char,
+    value: *const c_char,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(tensor, key);
+        let key = CStr::from_ptr(key).to_str().expect("invalid UTF8 metadata key");
+        let value = if value.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(value).to_str().expect("invalid UTF8 metadata value"))
+        };
+
+        (*tensor).set_metadata(key, value)?;
+        Ok(())
+    })
+}
+
+
+/// Get the list of all metadata keys defined in this `tensor` map, sorted in
+/// lexicographic order.
+///
+/// @param tensor pointer to an existing tensor map
+/// @param keys will be set to the first element of an array of NULL-terminated
+///             UTF-8 strings containing all the metadata keys
+/// @param keys_count will be set to the number of elements in `keys`
+///
+/// @returns The status code of this operation. If the status is not
+///          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full
+///          error message.
+#[no_mangle]
+pub unsafe extern fn eqs_tensormap_metadata_keys(
+    tensor: *const eqs_tensormap_t,
+    keys: *mut *const *const c_char,
+    keys_count: *mut usize,
+) -> eqs_status_t {
+    catch_unwind(|| {
+        check_pointers!(tensor, keys, keys_count);
+
+        let list = (*tensor).metadata_keys_c();
+        (*keys_count) = list.len();
+
+        (*keys) = if list.is_empty() {
+            std::ptr::null()
+        } else {
+            list.as_ptr().cast()
+        };
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::status::EQS_INVALID_PARAMETER_ERROR;
+    use super::super::blocks::eqs_block_data;
+    use crate::data::TestArray;
+    use crate::eqs_array_t;
+
+    struct Visited {
+        keys: Vec<Vec<i32>>,
+        samples: Vec<usize>,
+    }
+
+    unsafe extern fn visit(user_data: *mut c_void, index: usize, key: *const i32, block: *mut eqs_block_t) -> eqs_status_t {
+        let visited = &mut *user_data.cast::<Visited>();
+        assert_eq!(index, visited.keys.len());
+        visited.keys.push(std::slice::from_raw_parts(key, 3).to_vec());
+
+        let block = &*block.cast::<TensorBlock>();
+        visited.samples.push(block.values().samples.count());
+
+        if index == 4 {
+            return eqs_status_t(EQS_INVALID_PARAMETER_ERROR);
+        }
+        return eqs_status_t(0);
+    }
+
+    #[test]
+    fn foreach_block() {
+        let buffer = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data.npz")).unwrap();
+        unsafe {
+            let tensor = super::super::io::eqs_tensormap_load_buffer(buffer.as_ptr(), buffer.len(), None);
+            assert!(!tensor.is_null());
+
+            let mut visited = Visited { keys: Vec::new(), samples: Vec::new() };
+            let status = eqs_tensormap_foreach_block(tensor, Some(visit), (&mut visited as *mut Visited).cast());
+            assert_eq!(status.as_i32(), EQS_INVALID_PARAMETER_ERROR);
+
+            // the iteration stops after the callback fails
+            assert_eq!(visited.keys.len(), 5);
+            for (key, expected) in visited.keys.iter().zip((*tensor).keys().iter()) {
+                assert_eq!(*key, expected.iter().map(|v| v.i32()).collect::<Vec<_>>());
+            }
+            assert_eq!(visited.samples[0], (*tensor).blocks()[0].values().samples.count());
+
+            let status = eqs_tensormap_foreach_block(tensor, None, std::ptr::null_mut());
+            assert_eq!(status.as_i32(), EQS_INVALID_PARAMETER_ERROR);
+
+            assert!(eqs_tensormap_free(tensor).is_success());
+        }
+    }
+
+    #[test]
+    fn diff_json() {
+        let file = std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data.npz")).unwrap();
+        let tensor = crate::io::load(file, |shape| Ok(TestArray::new(shape))).unwrap();
+        let tensor = eqs_tensormap_t::into_boxed_raw(tensor);
+
+        unsafe {
+            let mut report = vec![0 as c_char; 16];
+            let status = eqs_tensormap_diff_json(tensor, tensor, true, 0.0, report.as_mut_ptr(), report.len());
+            assert_eq!(status.as_i32(), super::super::EQS_BUFFER_SIZE_ERROR);
+
+            let mut report = vec![0 as c_char; 256];
+            let status = eqs_tensormap_diff_json(tensor, tensor, true, 0.0, report.as_mut_ptr(), report.len());
+            assert!(status.is_success());
+
+            let report = std::ffi::CStr::from_ptr(report.as_ptr()).to_str().unwrap();
+            assert_eq!(report, r#"{"metadata":[],"keys_names":null,"only_in_first":[],"only_in_second":[],"blocks":[]}"#);
+
+            assert!(eqs_tensormap_free(tensor).is_success());
+        }
+    }
+
+    #[test]
+    fn memory_usage() {
+        let file = std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data.npz")).unwrap();
+        let tensor = crate::io::load(file, |shape| Ok(TestArray::new(shape))).unwrap();
+        let tensor = eqs_tensormap_t::into_boxed_raw(tensor);
+
+        unsafe {
+            let expected = (*tensor).memory_usage().unwrap();
+
+            let mut keys = eqs_memory_usage_t::default();
+            let mut blocks = vec![eqs_memory_usage_t::default(); expected.blocks.len()];
+            let mut total = eqs_memory_usage_t::default();
+            let status = eqs_tensormap_memory_usage(tensor, &mut keys, blocks.as_mut_ptr(), blocks.len(), &mut total);
+            assert!(status.is_success());
+
+            assert_eq!(keys, expected.keys.into());
+            assert_eq!(total, expected.total.into());
+            assert!(total.data > 0 && total.labels_values > 0 && total.gradients > 0);
+            for (block, expected) in blocks.iter().zip(&expected.blocks) {
+                assert_eq!(*block, (*expected).into());
+            }
+
+            let status = eqs_tensormap_memory_usage(tensor, &mut keys, blocks.as_mut_ptr(), 2, &mut total);
+            assert_eq!(status.as_i32(), EQS_INVALID_PARAMETER_ERROR);
+
+            assert!(eqs_tensormap_free(tensor).is_success());
+        }
+    }
+
+    #[test]
+    fn copy_on_write() {
+        let file = std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data.npz")).unwrap();
+        let tensor = crate::io::load(file, |shape| Ok(TestArray::new(shape))).unwrap();
+        let tensor = eqs_tensormap_t::into_boxed_raw(tensor);
+
+        unsafe {
+            // copies share the data arrays with the original tensor
+            let copy = eqs_tensormap_copy(tensor);
+            assert!(!copy.is_null());
+            assert!((*tensor).blocks()[0].values().data.is_shared());
+            assert!((*copy).blocks()[0].values().data.is_shared());
+
+            // accessing the data of a block makes it unique
+            let mut block = std::ptr::null_mut();
+            let status = eqs_tensormap_block_by_id(copy, &mut block, 0);
+            assert!(status.is_success());
+
+            let mut array = eqs_array_t::null();
+            let status = eqs_block_data(block, b"values\0".as_ptr().cast(), &mut array);
+            assert!(status.is_success());
+            assert!(!(*tensor).blocks()[0].values().data.is_shared());
+            assert!(!(*copy).blocks()[0].values().data.is_shared());
+
+            // modifying the data does not affect the original tensor
+            let original = (*tensor).blocks()[0].values().data.data().unwrap()[0];
+            array.data_mut().unwrap()[0] = original + 1.0;
+            assert_eq!((*tensor).blocks()[0].values().data.data().unwrap()[..1], [original]);
+            assert_eq!((*copy).blocks()[0].values().data.data().unwrap()[..1], [original + 1.0]);
+
+            // once the data was given to the caller, further copies are eager
+            let second = eqs_tensormap_copy(copy);
+            assert!(!second.is_null());
+            assert!(!(*copy).blocks()[0].values().data.is_shared());
+            assert!((*copy).blocks()[1].values().data.is_shared());
+
+            assert!(eqs_tensormap_free(second).is_success());
+            assert!(eqs_tensormap_free(copy).is_success());
+            assert!(eqs_tensormap_free(tensor).is_success());
+        }
+    }
+
+    #[test]
+    fn concurrent_block_data() {
+        let file = std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data.npz")).unwrap();
+        let tensor = crate::io::load(file, |shape| Ok(TestArray::new(shape))).unwrap();
+        let tensor = eqs_tensormap_t::into_boxed_raw(tensor);
+
+        unsafe {
+            // the data arrays of the copy are shared with the original tensor,
+            // and each thread makes the array of a different block unique
+            let copy = eqs_tensormap_copy(tensor);
+            assert!(!copy.is_null());
+
+            let n_blocks = (*copy).keys().count();
+            let blocks = (0..n_blocks).map(|block_i| {
+                let mut block = std::ptr::null_mut();
+                let status = eqs_tensormap_block_by_id(copy, &mut block, block_i);
+                assert!(status.is_success());
+                // raw pointers are not Send, send the address instead
+                block as usize
+            }).collect::<Vec<_>>();
+
+            let threads = blocks.into_iter().map(|block| {
+                std::thread::spawn(move || {
+                    let block = block as *mut eqs_block_t;
+                    let mut array = eqs_array_t::null();
+                    let status = eqs_block_data(block, b"values\0".as_ptr().cast(), &mut array);
+                    assert!(status.is_success());
+
+                    for value in array.data_mut().unwrap() {
+                        *value = -1.0;
+                    }
+                })
+            }).collect::<Vec<_>>();
+
+            for thread in threads {
+                thread.join().unwrap();
+            }
+
+            for (original, copy) in (*tensor).blocks().iter().zip((*copy).blocks()) {
+                assert!(!original.values().data.is_shared());
+                assert!(!copy.values().data.is_shared());
+
+                let data = copy.values().data.data().unwrap();
+                assert_eq!(data, vec![-1.0; data.len()]);
+                assert_ne!(original.values().data.data().unwrap(), data);
+            }
+
+            assert!(eqs_tensormap_free(copy).is_success());
+            assert!(eqs_tensormap_free(tensor).is_success());
+        }
+    }
+
+    #[test]
+    fn unify_gradients() {
+        let file = std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data.npz")).unwrap();
+        let tensor = crate::io::load(file, |shape| Ok(TestArray::new(shape))).unwrap();
+        let tensor = eqs_tensormap_t::into_boxed_raw(tensor);
+        unsafe {
+            let tensors = [tensor.cast_const(), tensor.cast_const()];
+            let mut outputs = [std::ptr::null_mut(); 2];
+            let status = eqs_tensormap_unify_gradients(tensors.as_ptr(), 2, b"zero\0".as_ptr().cast(), outputs.as_mut_ptr());
+            assert!(status.is_success());
+            for output in outputs {
+                assert!(!output.is_null());
+                assert_eq!((*output).keys(), (*tensor).keys());
+                assert_eq!(
+                    (*output).blocks()[0].gradient_parameters_c(),
+                    (*tensor).blocks()[0].gradient_parameters_c(),
+                );
+                assert!(eqs_tensormap_free(output).is_success());
+            }
+
+            let status = eqs_tensormap_unify_gradients(tensors.as_ptr(), 2, b"nothing\0".as_ptr().cast(), outputs.as_mut_ptr());
+            assert_eq!(status.as_i32(), EQS_INVALID_PARAMETER_ERROR);
+
+            assert!(eqs_tensormap_free(tensor).is_success());
+        }
+    }
+
+    #[test]
+    fn product() {
+        let buffer = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data.npz")).unwrap();
+        unsafe {
+            let tensor = super::super::io::eqs_tensormap_load_buffer(buffer.as_ptr(), buffer.len(), None);
+            assert!(!tensor.is_null());
+
+            // blocks with gradients are not supported
+            let result = eqs_tensormap_product(tensor, tensor);
+            assert!(result.is_null());
+
+            for block in (*tensor).blocks_mut() {
+                let parameters = block.gradient_parameters_c().iter()
+                    .map(|p| p.as_str().to_owned())
+                    .collect::<Vec<_>>();
+                for parameter in parameters {
+                    block.remove_gradient(&parameter);
+                }
+            }
+
+            let result = eqs_tensormap_product(tensor, tensor);
+            assert!(!result.is_null());
+            assert_eq!((*result).keys(), (*tensor).keys());
+
+            let values = (*result).blocks()[0].values();
+            let properties = &(*tensor).blocks()[0].values().properties;
+            assert_eq!(values.properties.count(), properties.count() * properties.count());
+            assert_eq!(values.properties.names().len(), 2 * properties.names().len());
+
+            assert!(eqs_tensormap_free(result).is_success());
+            assert!(eqs_tensormap_free(tensor).is_success());
+        }
+    }
+
+    fn empty_labels() -> eqs_labels_t {
+        eqs_labels_t {
+            internal_ptr_: std::ptr::null_mut(),
+            names: std::ptr::null(),
+            values: std::ptr::null(),
+            size: 0,
+            count: 0,
+        }
+    }
+
+    #[test]
+    fn view() {
+        let file = std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data.npz")).unwrap();
+        let tensor = crate::io::load(file, |shape| Ok(TestArray::new(shape))).unwrap();
+        let tensor = eqs_tensormap_t::into_boxed_raw(tensor);
+
+        unsafe {
+            let names = [b"spherical_harmonics_l\0".as_ptr().cast::<c_char>()];
+            let values = [2];
+            let selection = || eqs_labels_t {
+                internal_ptr_: std::ptr::null_mut(),
+                names: names.as_ptr(),
+                values: values.as_ptr(),
+                size: 1,
+                count: 1,
+            };
+
+            let n_blocks = (*tensor).keys().count();
+            let mut keys = empty_labels();
+            let mut blocks = vec![std::ptr::null_mut(); n_blocks];
+            let mut count = n_blocks;
+            let status = eqs_tensormap_view(tensor, selection(), &mut keys, blocks.as_mut_ptr(), &mut count);
+            assert!(status.is_success());
+
+            let expected = (*tensor).blocks_matching(&eqs_labels_to_rust(&selection()).unwrap()).unwrap();
+            assert_eq!(count, expected.len());
+            assert!(count > 0);
+            for (&block, &block_i) in blocks.iter().zip(&expected) {
+                assert!(std::ptr::eq(block.cast::<TensorBlock>(), &(*tensor).blocks()[block_i]));
+            }
+
+            let keys_rust = eqs_labels_to_rust(&keys).unwrap();
+            assert_eq!(keys_rust.count(), count);
+            assert_eq!(keys_rust.names(), (*tensor).keys().names());
+
+            let mut count = 2;
+            let status = eqs_tensormap_view(tensor, selection(), &mut empty_labels(), blocks.as_mut_ptr(), &mut count);
+            assert_eq!(status.as_i32(), EQS_INVALID_PARAMETER_ERROR);
+
+            assert!(super::super::labels::eqs_labels_free(&mut keys).is_success());
+            assert!(eqs_tensormap_free(tensor).is_success());
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! Implementation of the `equistore` command-line tool, used to examine and
+//! convert serialized tensor maps without a Python environment. This module
+//! is only available with the `cli` feature, build the tool with
+//! `cargo build --release --features cli --bin equistore`.
+//!
+//! Only the NPZ format used by `equistore.save` is supported: there is no
+//! HDF5 backend in equistore-core, so `convert` can not read or write HDF5
+//! files.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+
+use crate::io::SaveOptions;
+use crate::tensor::{diff, DiffOptions};
+use crate::data::zeros_array;
+use crate::{Error, LabelsBuilder, TensorBlock, TensorMap};
+
+/// Usage message for the command-line tool
+pub const USAGE: &str = "\
+usage: equistore <command> [options]
+
+commands:
+    inspect <file>
+        print the keys, labels, shapes and memory usage of a tensor map
+    diff <first> <second> [--tolerance <value>] [--metadata-only]
+        report all the differences between two tensor maps
+    convert <input> <output> [--labels-dictionary] [--mantissa-bits <bits>]
+        save a tensor map again with different serialization options. Both
+        files must use the NPZ format, HDF5 is not supported
+    extract-block <input> <output> <selection>
+        save the blocks matching <selection> to a new file. The selection is
+        either the index of a block, or a list of key values such as
+        `center_species=6,neighbor_species=1`
+";
+
+/// Run the command-line tool with the given `args` (not including the program
+/// name), writing the output to `output`. This returns the exit status of the
+/// tool: `0` on success, and `1` if `diff` found differences.
+pub fn run<W: Write>(args: &[String], output: &mut W) -> Result<i32, Error> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => {
+            return Err(Error::InvalidParameter(format!("missing command\n\n{}", USAGE)));
+        }
+    };
+
+    match command {
+        "inspect" => {
+            let [path] = positional::<1>(command, args)?;
+            let tensor = load(&path)?;
+            inspect(&tensor, output)?;
+        }
+        "diff" => {
+            let (args, tolerance) = option_value(args, "--tolerance")?;
+            let (args, metadata_only) = flag(&args, "--metadata-only");
+            let [first, second] = positional::<2>(command, &args)?;
+
+            let options = DiffOptions {
+                compare_data: !metadata_only,
+                tolerance: match tolerance {
+                    Some(tolerance) => parse(&tolerance, "--tolerance")?,
+                    None => 0.0,
+                },
+            };
+
+            let difference = diff(&load(&first)?, &load(&second)?, options)?;
+            write!(output, "{}", difference)?;
+            if !difference.is_empty() {
+                return Ok(1);
+            }
+        }
+        "convert" => {
+            let (args, mantissa_bits) = option_value(args, "--mantissa-bits")?;
+            let (args, labels_dictionary) = flag(&args, "--labels-dictionary");
+            let [input, output_path] = positional::<2>(command, &args)?;
+
+            let options = SaveOptions {
+                labels_dictionary,
+                mantissa_bits: match mantissa_bits {
+                    Some(bits) => Some(parse(&bits, "--mantissa-bits")?),
+                    None => None,
+                },
+            };
+
+            save(&output_path, &load(&input)?, options)?;
+        }
+        "extract-block" => {
+            let [input, output_path, selection] = positional::<3>(command, args)?;
+            let tensor = extract_blocks(&input, &selection)?;
+            save(&output_path, &tensor, SaveOptions::default())?;
+            writeln!(output, "extracted {} block(s) to {}", tensor.keys().count(), output_path)?;
+        }
+        "help" | "--help" | "-h" => write!(output, "{}", USAGE)?,
+        _ => {
+            return Err(Error::InvalidParameter(format!(
+                "unknown command '{}'\n\n{}", command, USAGE
+            )));
+        }
+    }
+
+    return Ok(0);
+}
+
+/// Check that `args` contains exactly `N` positional arguments for `command`
+fn positional<const N: usize>(command: &str, args: &[String]) -> Result<[String; N], Error> {
+    if let Some(option) = args.iter().find(|arg| arg.starts_with("--")) {
+        return Err(Error::InvalidParameter(format!(
+            "unknown option '{}' for '{}'", option, command
+        )));
+    }
+
+    return args.to_vec().try_into().map_err(|_| Error::InvalidParameter(format!(
+        "'{}' expects {} argument(s), got {}\n\n{}", command, N, args.len(), USAGE
+    )));
+}
+
+/// Remove the `--name` flag from `args`, returning whether it was present
+fn flag(args: &[String], name: &str) -> (Vec<String>, bool) {
+    let remaining = args.iter().filter(|arg| *arg != name).cloned().collect::<Vec<_>>();
+    let present = remaining.len() != args.len();
+    return (remaining, present);
+}
+
+/// Remove the `--name <value>` option from `args`, returning its value
+fn option_value(args: &[String], name: &str) -> Result<(Vec<String>, Option<String>), Error> {
+    let mut remaining = Vec::new();
+    let mut value = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == name {
+            match args.next() {
+                Some(v) => value = Some(v.clone()),
+                None => return Err(Error::InvalidParameter(format!("missing value for {}", name))),
+            }
+        } else {
+            remaining.push(arg.clone());
+        }
+    }
+
+    return Ok((remaining, value));
+}
+
+fn parse<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, Error> {
+    value.parse().map_err(|_| Error::InvalidParameter(format!(
+        "invalid value '{}' for {}", value, name
+    )))
+}
+
+/// Check that `path` uses the NPZ format, the only one supported
+fn check_extension(path: &str) -> Result<(), Error> {
+    if path.ends_with(".h5") || path.ends_with(".hdf5") {
+        return Err(Error::InvalidParameter(format!(
+            "unsupported file format for '{}': HDF5 files are not supported, \
+            only .npz files are supported", path
+        )));
+    }
+
+    if !path.ends_with(".npz") {
+        return Err(Error::InvalidParameter(format!(
+            "unsupported file format for '{}', only .npz files are supported", path
+        )));
+    }
+    Ok(())
+}
+
+fn load(path: &str) -> Result<TensorMap, Error> {
+    check_extension(path)?;
+    let file = BufReader::new(File::open(path)?);
+    return crate::io::load(file, |shape| Ok(zeros_array(shape)));
+}
+
+fn save(path: &str, tensor: &TensorMap, options: SaveOptions) -> Result<(), Error> {
+    check_extension(path)?;
+    let file = BufWriter::new(File::create(path)?);
+    return crate::io::save_with_options(file, tensor, options);
+}
+
+/// Load the blocks matching `selection` from the file at `path`
+fn extract_blocks(path: &str, selection: &str) -> Result<TensorMap, Error> {
+    check_extension(path)?;
+
+    if let Ok(block_i) = selection.parse::<usize>() {
+        let file = BufReader::new(File::open(path)?);
+        return crate::io::load_blocks(file, Some(&[block_i]), |shape| Ok(zeros_array(shape)));
+    }
+
+    let mut names = Vec::new();
+    let mut values = Vec::new();
+    for entry in selection.split(',') {
+        let (name, value) = entry.split_once('=').ok_or_else(|| Error::InvalidParameter(format!(
+            "invalid block selection '{}', expected a block index or `name=value` pairs", selection
+        )))?;
+        names.push(name.trim());
+        values.push(parse::<i32>(value.trim(), name.trim())?);
+    }
+
+    let mut builder = LabelsBuilder::new(names);
+    builder.add(&values)?;
+    let selection = builder.finish();
+
+    let tensor = load(path)?;
+    return tensor.view(&selection)?.to_tensor_map();
+}
+
+fn format_values(values: impl Iterator<Item=String>) -> String {
+    values.collect::<Vec<_>>().join(", ")
+}
+
+fn inspect_block<W: Write>(block: &TensorBlock, output: &mut W) -> Result<(), Error> {
+    let values = block.values();
+    writeln!(output, "    samples: [{}] x {}", values.samples.names().join(", "), values.samples.count())?;
+    for component in &values.components {
+        writeln!(output, "    component: [{}] x {}", component.names().join(", "), component.count())?;
+    }
+    writeln!(output, "    properties: [{}] x {}", values.properties.names().join(", "), values.properties.count())?;
+
+    let mut parameters = block.gradients().keys().collect::<Vec<_>>();
+    parameters.sort();
+    for parameter in parameters {
+        let gradient = &block.gradients()[parameter];
+        writeln!(
+            output, "    gradient '{}': shape [{}]",
+            parameter, format_values(gradient.data.shape()?.iter().map(|n| n.to_string()))
+        )?;
+    }
+
+    Ok(())
+}
+
+fn inspect<W: Write>(tensor: &TensorMap, output: &mut W) -> Result<(), Error> {
+    let keys = tensor.keys();
+    writeln!(output, "keys: [{}], {} block(s)", keys.names().join(", "), keys.count())?;
+
+    for (key, value) in tensor.metadata() {
+        writeln!(output, "metadata: {} = {}", key, value)?;
+    }
+
+    let usage = tensor.memory_usage()?;
+    for (block_i, (key, block)) in keys.iter().zip(tensor.blocks()).enumerate() {
+        writeln!(
+            output, "block {} ({}): shape [{}], {} bytes",
+            block_i,
+            format_values(key.iter().map(|v| v.to_string())),
+            format_values(block.values().data.shape()?.iter().map(|n| n.to_string())),
+            usage.blocks[block_i].total(),
+        )?;
+        inspect_block(block, output)?;
+    }
+
+    writeln!(output, "total memory: {} bytes", usage.total.total())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn run_to_string(arguments: &[&str]) -> (i32, String) {
+        let mut output = Vec::new();
+        let status = run(&args(arguments), &mut output).unwrap();
+        return (status, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn commands() {
+        let data = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data.npz");
+        let directory = std::env::temp_dir().join(format!("equistore-cli-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = |name: &str| directory.join(name).to_str().unwrap().to_string();
+
+        let (status, output) = run_to_string(&["inspect", data]);
+        assert_eq!(status, 0);
+        assert!(output.starts_with("keys: [spherical_harmonics_l, center_species, neighbor_species], 27 block(s)\n"));
+        assert!(output.contains("    gradient 'positions': shape ["));
+
+        let (status, output) = run_to_string(&["diff", data, data]);
+        assert_eq!(status, 0);
+        assert_eq!(output, "no differences\n");
+
+        let rounded = path("rounded.npz");
+        run_to_string(&["convert", data, &rounded, "--mantissa-bits", "4"]);
+        let (status, _) = run_to_string(&["diff", data, &rounded]);
+        assert_eq!(status, 1);
+        let (status, _) = run_to_string(&["diff", data, &rounded, "--metadata-only"]);
+        assert_eq!(status, 0);
+
+        let extracted = path("extracted.npz");
+        let (_, output) = run_to_string(&["extract-block", data, &extracted, "center_species=6,neighbor_species=1"]);
+        assert!(output.starts_with("extracted 3 block(s)"));
+        let (_, output) = run_to_string(&["extract-block", data, &extracted, "2"]);
+        assert!(output.starts_with("extracted 1 block(s)"));
+
+        let error = run(&args(&["convert", data]), &mut Vec::new()).unwrap_err();
+        assert!(error.to_string().starts_with("invalid parameter: 'convert' expects 2 argument(s), got 1"));
+
+        let error = run(&args(&["convert", data, &path("output.h5")]), &mut Vec::new()).unwrap_err();
+        assert!(error.to_string().ends_with("HDF5 files are not supported, only .npz files are supported"));
+
+        let error = run(&args(&["convert", data, &path("output.json")]), &mut Vec::new()).unwrap_err();
+        assert!(error.to_string().ends_with("output.json', only .npz files are supported"));
+
+        let error = run(&args(&["inspect", data, "--all"]), &mut Vec::new()).unwrap_err();
+        assert_eq!(error.to_string(), "invalid parameter: unknown option '--all' for 'inspect'");
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,12 +1,17 @@
 use std::ops::Range;
+use std::borrow::Cow;
 use std::os::raw::c_void;
 use std::sync::Mutex;
+use std::collections::HashMap;
 
 use once_cell::sync::Lazy;
 
 use crate::c_api::eqs_status_t;
 use crate::Error;
 
+mod compressed;
+pub(crate) use self::compressed::{compress_array, decompress_array, compressed_size, zeros_array};
+
 /// A single 64-bit integer representing a data origin (numpy ndarray, rust
 /// ndarray, torch tensor, fortran array, ...).
 #[repr(transparent)]
@@ -50,6 +55,75 @@ pub fn get_data_origin(origin: eqs_data_origin_t) -> String {
     }
 }
 
+/// Functions of an `eqs_array_t` registered for a given data origin with
+/// `register_array_backend`
+struct ArrayBackend {
+    /// all the functions except `destroy`, with a NULL `ptr`
+    functions: eqs_array_t,
+    destroy: Option<unsafe extern fn(array: *mut c_void)>,
+}
+
+static REGISTERED_ARRAY_BACKENDS: Lazy<Mutex<HashMap<u64, ArrayBackend>>> = Lazy::new(|| {
+    Mutex::new(HashMap::new())
+});
+
+/// Register a new array backend with the given `name`, using the functions
+/// in `vtable`. The `ptr` of `vtable` is ignored. This returns the data origin
+/// associated with `name`, which can then be used with `array_from_backend` to
+/// create new arrays with these functions.
+///
+/// Registering a backend with the same name multiple times replaces the
+/// previous functions.
+pub fn register_array_backend(name: String, vtable: &eqs_array_t) -> Result<eqs_data_origin_t, Error> {
+    let required = [
+        ("origin", vtable.origin.is_some()),
+        ("data", vtable.data.is_some()),
+        ("shape", vtable.shape.is_some()),
+        ("reshape", vtable.reshape.is_some()),
+        ("swap_axes", vtable.swap_axes.is_some()),
+        ("create", vtable.create.is_some()),
+        ("copy", vtable.copy.is_some()),
+        ("move_samples_from", vtable.move_samples_from.is_some()),
+    ];
+
+    for &(function, is_set) in &required {
+        if !is_set {
+            return Err(Error::InvalidParameter(format!(
+                "can not register array backend '{}': eqs_array_t.{} function is NULL",
+                name, function
+            )));
+        }
+    }
+
+    let mut functions = vtable.raw_copy();
+    functions.ptr = std::ptr::null_mut();
+
+    let origin = register_data_origin(name);
+    let mut backends = REGISTERED_ARRAY_BACKENDS.lock().expect("mutex got poisoned");
+    backends.insert(origin.0, ArrayBackend {
+        functions,
+        destroy: vtable.destroy,
+    });
+
+    return Ok(origin);
+}
+
+/// Create a new `eqs_array_t` managing `ptr`, using the functions registered
+/// for `origin` with `register_array_backend`. The new array takes ownership
+/// of `ptr`, and will release it with the backend `destroy` function.
+pub fn array_from_backend(origin: eqs_data_origin_t, ptr: *mut c_void) -> Result<eqs_array_t, Error> {
+    let backends = REGISTERED_ARRAY_BACKENDS.lock().expect("mutex got poisoned");
+    let backend = backends.get(&origin.0).ok_or_else(|| Error::InvalidParameter(format!(
+        "no array backend registered for data origin '{}'", get_data_origin(origin)
+    )))?;
+
+    let mut array = backend.functions.raw_copy();
+    array.ptr = ptr;
+    array.destroy = backend.destroy;
+
+    return Ok(array);
+}
+
 // SAFETY: this should be checked by the user/implementor of `eqs_array_t`.
 unsafe impl Sync for eqs_array_t {}
 unsafe impl Send for eqs_array_t {}
@@ -67,6 +141,11 @@ unsafe impl Send for eqs_array_t {}
 /// **WARNING**: all function implementations **MUST** be thread-safe, and can
 /// be called from multiple threads at the same time. The `eqs_array_t` itself
 /// might be moved from one thread to another.
+///
+/// **ABI NOTE**: the `strides` field was added after equistore 0.1.0, growing
+/// this struct from 80 to 88 bytes on 64-bit platforms. Code creating
+/// `eqs_array_t` must be compiled against the same version of this header as
+/// the library it is linked to, and initialize all the fields.
 #[repr(C)]
 #[allow(non_camel_case_types)]
 pub struct eqs_array_t {
@@ -86,8 +165,8 @@ pub struct eqs_array_t {
     /// Get a pointer to the underlying data storage.
     ///
     /// This function is allowed to fail if the data is not accessible in RAM,
-    /// not stored as 64-bit floating point values, or not stored as a
-    /// C-contiguous array.
+    /// or not stored as 64-bit floating point values. If the data is not
+    /// stored as a C-contiguous array, the `strides` function must be set.
     data: Option<unsafe extern fn(
         array: *mut c_void,
         data: *mut *mut f64,
@@ -163,6 +242,23 @@ pub struct eqs_array_t {
         property_start: usize,
         property_end: usize,
     ) -> eqs_status_t>,
+
+    /// Get the strides of the array managed by this `eqs_array_t` in the
+    /// `*strides` pointer, and the number of dimensions (size of the
+    /// `*strides` array) in `*strides_count`.
+    ///
+    /// Strides are given in number of elements: the entry at `[i, j, ...]` is
+    /// stored at `data[i * strides[0] + j * strides[1] + ...]`, where `data` is
+    /// the pointer returned by `eqs_array_t.data`.
+    ///
+    /// This function can be set to `NULL` if the array is always stored as a
+    /// C-contiguous array. Arrays created with `eqs_array_t.create` or
+    /// `eqs_array_t.copy` are expected to be C-contiguous.
+    strides: Option<unsafe extern fn(
+        array: *const c_void,
+        strides: *mut *const usize,
+        strides_count: *mut usize,
+    ) -> eqs_status_t>,
 }
 
 /// Representation of a single sample moved from an array to another one
@@ -217,6 +313,7 @@ impl eqs_array_t {
             // do not copy destroy, the user should never call it
             destroy: None,
             move_samples_from: self.move_samples_from,
+            strides: self.strides,
         }
     }
 
@@ -233,6 +330,7 @@ impl eqs_array_t {
             copy: None,
             destroy: None,
             move_samples_from: None,
+            strides: None,
         }
     }
 
@@ -254,14 +352,8 @@ impl eqs_array_t {
         return Ok(origin);
     }
 
-    /// Get the underlying data for this array.
-    pub fn data(&self) -> Result<&[f64], Error> {
-        let shape = self.shape()?;
-        let mut len = 1;
-        for s in shape {
-            len *= s;
-        }
-
+    /// Get a pointer to the first element of this array
+    fn data_ptr(&self) -> Result<*mut f64, Error> {
         let function = self.data.expect("eqs_array_t.data function is NULL");
 
         let mut data_ptr = std::ptr::null_mut();
@@ -279,43 +371,163 @@ impl eqs_array_t {
             });
         }
 
+        return Ok(data_ptr);
+    }
+
+    /// Check that this array is C-contiguous before giving direct access to
+    /// the data
+    fn check_contiguous(&self) -> Result<(), Error> {
+        if !self.is_contiguous()? {
+            return Err(Error::InvalidParameter(
+                "can not access the data of a non-contiguous array directly, \
+                use `contiguous_data` or make a contiguous copy first".into()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Get the underlying data for this array. The array must be C-contiguous.
+    pub fn data(&self) -> Result<&[f64], Error> {
+        self.check_contiguous()?;
+        let len = self.shape()?.iter().product();
+        if len == 0 {
+            // the data pointer can be NULL for empty arrays
+            return Ok(&[]);
+        }
+
         let data = unsafe {
-            std::slice::from_raw_parts(data_ptr, len)
+            std::slice::from_raw_parts(self.data_ptr()?, len)
         };
 
         return Ok(data);
     }
 
-    /// Get the underlying data for this array.
+    /// Get the underlying data for this array. The array must be C-contiguous.
     pub fn data_mut(&mut self) -> Result<&mut [f64], Error> {
+        self.check_contiguous()?;
+        let len = self.shape()?.iter().product();
+        if len == 0 {
+            // the data pointer can be NULL for empty arrays
+            return Ok(&mut []);
+        }
+
+        let data = unsafe {
+            std::slice::from_raw_parts_mut(self.data_ptr()?, len)
+        };
+
+        return Ok(data);
+    }
+
+    /// Get the data of this array in C-contiguous order. This borrows the
+    /// data if the array is already C-contiguous, and makes a copy otherwise.
+    pub fn contiguous_data(&self) -> Result<Cow<'_, [f64]>, Error> {
+        if self.is_contiguous()? {
+            return self.data().map(Cow::Borrowed);
+        }
+
         let shape = self.shape()?;
-        let mut len = 1;
-        for s in shape {
-            len *= s;
+        let strides = self.strides()?.expect("non-contiguous arrays should have strides");
+        let len = shape.iter().product::<usize>();
+
+        // position of the last element of the array, plus one
+        let extent = shape.iter().zip(strides)
+            .map(|(&size, &stride)| (size - 1) * stride)
+            .sum::<usize>() + 1;
+
+        let data = unsafe {
+            std::slice::from_raw_parts(self.data_ptr()?, extent)
+        };
+
+        let mut output = Vec::new();
+        output.try_reserve_exact(len)?;
+        let mut index = vec![0; shape.len()];
+        let mut offset = 0;
+        for _ in 0..len {
+            output.push(data[offset]);
+
+            // go to the next element, incrementing the last axis first
+            for axis in (0..shape.len()).rev() {
+                index[axis] += 1;
+                offset += strides[axis];
+                if index[axis] < shape[axis] {
+                    break;
+                }
+
+                offset -= shape[axis] * strides[axis];
+                index[axis] = 0;
+            }
         }
 
-        let function = self.data.expect("eqs_array_t.data function is NULL");
+        return Ok(Cow::Owned(output));
+    }
 
-        let mut data_ptr = std::ptr::null_mut();
+    /// Get the strides of this array, in number of elements. This returns
+    /// `None` if the array does not define strides, i.e. if it is always
+    /// C-contiguous.
+    pub fn strides(&self) -> Result<Option<&[usize]>, Error> {
+        let function = match self.strides {
+            Some(function) => function,
+            None => return Ok(None),
+        };
+
+        let mut strides = std::ptr::null();
+        let mut strides_count: usize = 0;
 
         let status = unsafe {
             function(
                 self.ptr,
-                &mut data_ptr,
+                &mut strides,
+                &mut strides_count,
             )
         };
 
         if !status.is_success() {
             return Err(Error::External {
-                status, context: "calling eqs_array_t.data failed".into()
+                status, context: "calling eqs_array_t.strides failed".into()
             });
         }
 
-        let data = unsafe {
-            std::slice::from_raw_parts_mut(data_ptr, len)
+        let shape = self.shape()?;
+        if strides_count != shape.len() {
+            return Err(Error::InvalidParameter(format!(
+                "eqs_array_t.strides returned {} strides for an array with {} dimensions",
+                strides_count, shape.len()
+            )));
+        }
+
+        if strides_count == 0 {
+            return Ok(Some(&[]));
+        }
+
+        let strides = unsafe {
+            std::slice::from_raw_parts(strides, strides_count)
         };
 
-        return Ok(data);
+        return Ok(Some(strides));
+    }
+
+    /// Is this array stored as a C-contiguous array?
+    pub fn is_contiguous(&self) -> Result<bool, Error> {
+        let strides = match self.strides()? {
+            Some(strides) => strides,
+            None => return Ok(true),
+        };
+
+        let shape = self.shape()?;
+        if shape.contains(&0) {
+            return Ok(true);
+        }
+
+        let mut expected = 1;
+        for (&size, &stride) in shape.iter().zip(strides).rev() {
+            // the stride does not matter for axes with a single entry
+            if size != 1 && stride != expected {
+                return Ok(false);
+            }
+            expected *= size;
+        }
+
+        return Ok(true);
     }
 
     /// Get the shape of this array
@@ -471,10 +683,10 @@ impl eqs_array_t {
     }
 }
 
-#[cfg(test)]
-pub(crate) use self::tests::TestArray;
+#[cfg(any(test, fuzzing, feature = "benchmarks"))]
+pub use self::tests::TestArray;
 
-#[cfg(test)]
+#[cfg(any(test, fuzzing, feature = "benchmarks"))]
 mod tests {
     use crate::c_api::EQS_SUCCESS;
 
@@ -482,24 +694,34 @@ mod tests {
 
     pub struct TestArray {
         shape: Vec<usize>,
+        data: Vec<f64>,
     }
 
     impl TestArray {
         #[allow(clippy::new_ret_no_self)]
         pub fn new(shape: Vec<usize>) -> eqs_array_t {
-            let array = Box::new(TestArray {shape});
+            let data = vec![0.0; shape.iter().product()];
+            return TestArray::from_data(shape, data);
+        }
+
+        /// Create a new `TestArray` with the given shape, containing the
+        /// given `data` in row-major order
+        pub fn from_data(shape: Vec<usize>, data: Vec<f64>) -> eqs_array_t {
+            assert_eq!(shape.iter().product::<usize>(), data.len());
+            let array = Box::new(TestArray {shape, data});
 
             return eqs_array_t {
                 ptr: Box::into_raw(array).cast(),
                 origin: Some(TestArray::origin),
-                data: None,
+                data: Some(TestArray::data),
                 shape: Some(TestArray::shape),
                 reshape: Some(TestArray::reshape),
                 swap_axes: Some(TestArray::swap_axes),
-                create: None,
-                copy: None,
+                create: Some(TestArray::create),
+                copy: Some(TestArray::copy),
                 destroy: Some(TestArray::destroy),
-                move_samples_from: None,
+                move_samples_from: Some(TestArray::move_samples_from),
+                strides: None,
             }
         }
 
@@ -509,6 +731,13 @@ mod tests {
             return eqs_status_t(EQS_SUCCESS);
         }
 
+        unsafe extern fn data(ptr: *mut c_void, data: *mut *mut f64) -> eqs_status_t {
+            let ptr = ptr.cast::<TestArray>();
+            *data = (*ptr).data.as_mut_ptr();
+
+            return eqs_status_t(EQS_SUCCESS);
+        }
+
         unsafe extern fn shape(ptr: *const c_void, shape: *mut *const usize, shape_count: *mut usize) -> eqs_status_t {
             let ptr = ptr.cast::<TestArray>();
 
@@ -540,6 +769,55 @@ mod tests {
             return eqs_status_t(EQS_SUCCESS);
         }
 
+        unsafe extern fn create(
+            _: *const c_void,
+            shape_ptr: *const usize,
+            shape_count: usize,
+            new_array: *mut eqs_array_t,
+        ) -> eqs_status_t {
+            let shape = std::slice::from_raw_parts(shape_ptr, shape_count).to_vec();
+            *new_array = TestArray::new(shape);
+
+            return eqs_status_t(EQS_SUCCESS);
+        }
+
+        unsafe extern fn copy(ptr: *const c_void, new_array: *mut eqs_array_t) -> eqs_status_t {
+            let ptr = ptr.cast::<TestArray>();
+            *new_array = TestArray::from_data((*ptr).shape.clone(), (*ptr).data.clone());
+
+            return eqs_status_t(EQS_SUCCESS);
+        }
+
+        unsafe extern fn move_samples_from(
+            output: *mut c_void,
+            input: *const c_void,
+            samples: *const eqs_sample_mapping_t,
+            samples_count: usize,
+            property_start: usize,
+            property_end: usize,
+        ) -> eqs_status_t {
+            let output = &mut *output.cast::<TestArray>();
+            let input = &*input.cast::<TestArray>();
+            let samples = std::slice::from_raw_parts(samples, samples_count);
+
+            let output_properties = *output.shape.last().expect("empty shape");
+            let input_properties = *input.shape.last().expect("empty shape");
+            let components = input.shape[1..input.shape.len() - 1].iter().product::<usize>();
+            debug_assert_eq!(property_end - property_start, input_properties);
+
+            for sample in samples {
+                for component in 0..components {
+                    let input_start = (sample.input * components + component) * input_properties;
+                    let output_start = (sample.output * components + component) * output_properties;
+
+                    output.data[(output_start + property_start)..(output_start + property_end)]
+                        .copy_from_slice(&input.data[input_start..(input_start + input_properties)]);
+                }
+            }
+
+            return eqs_status_t(EQS_SUCCESS);
+        }
+
         unsafe extern fn destroy(ptr: *mut c_void) {
             let ptr = ptr.cast::<TestArray>();
             let boxed = Box::from_raw(ptr);
@@ -556,6 +834,72 @@ mod tests {
         assert_eq!(get_data_origin(origin), "test origin");
     }
 
+    #[test]
+    fn array_backend() {
+        let origin = register_data_origin("rust.TestArray.unregistered".into());
+        let error = array_from_backend(origin, std::ptr::null_mut()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: no array backend registered for data origin 'rust.TestArray.unregistered'"
+        );
+
+        let mut vtable = TestArray::new(vec![1, 1]);
+        let origin = register_array_backend("rust.TestArray.backend".into(), &vtable).unwrap();
+
+        let data = Box::new(TestArray { shape: vec![2, 1], data: vec![1.0, 2.0] });
+        let array = array_from_backend(origin, Box::into_raw(data).cast()).unwrap();
+        assert_eq!(array.shape().unwrap(), [2, 1]);
+        assert_eq!(array.data().unwrap(), [1.0, 2.0]);
+
+        vtable.reshape = None;
+        let error = register_array_backend("rust.TestArray.invalid".into(), &vtable).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: can not register array backend 'rust.TestArray.invalid': \
+            eqs_array_t.reshape function is NULL"
+        );
+    }
+
+    #[test]
+    fn strided_data() {
+        unsafe extern fn transposed_strides(_: *const c_void, strides: *mut *const usize, strides_count: *mut usize) -> eqs_status_t {
+            static STRIDES: [usize; 2] = [1, 3];
+            *strides = STRIDES.as_ptr();
+            *strides_count = STRIDES.len();
+            return eqs_status_t(EQS_SUCCESS);
+        }
+
+        let mut array = TestArray::from_data(vec![2, 3], vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert!(array.is_contiguous().unwrap());
+
+        // transpose the array without moving the data
+        array.swap_axes(0, 1).unwrap();
+        array.strides = Some(transposed_strides);
+        assert!(!array.is_contiguous().unwrap());
+
+        assert_eq!(
+            array.data().unwrap_err().to_string(),
+            "invalid parameter: can not access the data of a non-contiguous \
+            array directly, use `contiguous_data` or make a contiguous copy first"
+        );
+        assert_eq!(&*array.contiguous_data().unwrap(), [0.0, 3.0, 1.0, 4.0, 2.0, 5.0]);
+    }
+
+    #[test]
+    fn empty_data() {
+        unsafe extern fn null_data(_: *mut c_void, data: *mut *mut f64) -> eqs_status_t {
+            *data = std::ptr::null_mut();
+            return eqs_status_t(EQS_SUCCESS);
+        }
+
+        let mut array = TestArray::new(vec![0, 3]);
+        array.data = Some(null_data);
+
+        assert!(array.data().unwrap().is_empty());
+        assert!(array.data_mut().unwrap().is_empty());
+        assert!(array.contiguous_data().unwrap().is_empty());
+    }
+
     #[test]
     fn debug() {
         let data: eqs_array_t = TestArray::new(vec![3, 4, 5]);
@@ -0,0 +1,361 @@
+use std::os::raw::c_void;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+use crate::c_api::{EQS_SUCCESS, EQS_INVALID_PARAMETER_ERROR};
+use crate::c_api::eqs_status_t;
+use crate::Error;
+use crate::warnings::warn;
+
+use super::{eqs_array_t, eqs_data_origin_t, eqs_sample_mapping_t};
+use super::register_data_origin;
+
+/// Data origin used by all compressed arrays
+static COMPRESSED_ORIGIN: Lazy<eqs_data_origin_t> = Lazy::new(|| {
+    register_data_origin("equistore.CompressedArray".into())
+});
+
+/// Storage for the data of a [`CompressedArray`]
+enum Storage {
+    /// zstd-compressed data, in native endianness
+    Compressed(Vec<u8>),
+    /// Uncompressed data, used after the first access to the array
+    Uncompressed(Vec<f64>),
+}
+
+impl Storage {
+    fn compress(data: &[f64]) -> Result<Storage, Error> {
+        let bytes = data.iter().flat_map(|v| v.to_ne_bytes()).collect::<Vec<_>>();
+        let compressed = zstd::bulk::compress(&bytes, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+        return Ok(Storage::Compressed(compressed));
+    }
+
+    /// Get the uncompressed data, decompressing it first if needed
+    fn uncompressed(&mut self, len: usize) -> Result<&mut Vec<f64>, Error> {
+        if let Storage::Compressed(compressed) = self {
+            warn(&format!(
+                "accessing the data of a compressed array, {} values will be decompressed in memory", len
+            ));
+
+            let bytes = zstd::bulk::decompress(compressed, len * std::mem::size_of::<f64>())?;
+            let data = bytes.chunks_exact(std::mem::size_of::<f64>())
+                .map(|chunk| f64::from_ne_bytes(chunk.try_into().expect("wrong chunk size")))
+                .collect::<Vec<_>>();
+
+            if data.len() != len {
+                return Err(Error::Internal(format!(
+                    "expected {} values after decompression, got {}", len, data.len()
+                )));
+            }
+
+            *self = Storage::Uncompressed(data);
+        }
+
+        match self {
+            Storage::Uncompressed(data) => Ok(data),
+            Storage::Compressed(_) => unreachable!(),
+        }
+    }
+}
+
+/// Array containing zstd-compressed data in memory, which is transparently
+/// decompressed the first time the data is accessed through
+/// `eqs_array_t.data`.
+///
+/// The array also keeps an empty array created from the original array,
+/// which is used to convert the data back to the original array type in
+/// [`decompress_array`].
+struct CompressedArray {
+    shape: Vec<usize>,
+    storage: Mutex<Storage>,
+    original: Option<eqs_array_t>,
+}
+
+impl CompressedArray {
+    fn into_array(self) -> eqs_array_t {
+        let mut array = eqs_array_t::null();
+        array.ptr = Box::into_raw(Box::new(self)).cast();
+        array.origin = Some(CompressedArray::origin);
+        array.data = Some(CompressedArray::data);
+        array.shape = Some(CompressedArray::shape);
+        array.reshape = Some(CompressedArray::reshape);
+        array.swap_axes = Some(CompressedArray::swap_axes);
+        array.create = Some(CompressedArray::create);
+        array.copy = Some(CompressedArray::copy);
+        array.destroy = Some(CompressedArray::destroy);
+        array.move_samples_from = Some(CompressedArray::move_samples_from);
+        return array;
+    }
+
+    fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    /// Convert the result of `function` to an `eqs_status_t`
+    fn status(function: impl FnOnce() -> Result<(), Error>) -> eqs_status_t {
+        match function() {
+            Ok(()) => eqs_status_t(EQS_SUCCESS),
+            Err(_) => eqs_status_t(EQS_INVALID_PARAMETER_ERROR),
+        }
+    }
+
+    unsafe extern fn origin(_: *const c_void, origin: *mut eqs_data_origin_t) -> eqs_status_t {
+        *origin = *COMPRESSED_ORIGIN;
+        return eqs_status_t(EQS_SUCCESS);
+    }
+
+    unsafe extern fn data(ptr: *mut c_void, data: *mut *mut f64) -> eqs_status_t {
+        let array = &*ptr.cast::<CompressedArray>();
+        CompressedArray::status(|| {
+            let mut storage = array.storage.lock().expect("mutex was poisoned");
+            // the data is never moved after decompression, so the pointer
+            // stays valid after releasing the lock
+            *data = storage.uncompressed(array.len())?.as_mut_ptr();
+            Ok(())
+        })
+    }
+
+    unsafe extern fn shape(ptr: *const c_void, shape: *mut *const usize, shape_count: *mut usize) -> eqs_status_t {
+        let array = &*ptr.cast::<CompressedArray>();
+        *shape = array.shape.as_ptr();
+        *shape_count = array.shape.len();
+        return eqs_status_t(EQS_SUCCESS);
+    }
+
+    unsafe extern fn reshape(ptr: *mut c_void, shape: *const usize, shape_count: usize) -> eqs_status_t {
+        let array = &mut *ptr.cast::<CompressedArray>();
+        array.shape = std::slice::from_raw_parts(shape, shape_count).to_vec();
+        return eqs_status_t(EQS_SUCCESS);
+    }
+
+    unsafe extern fn swap_axes(ptr: *mut c_void, axis_1: usize, axis_2: usize) -> eqs_status_t {
+        let array = &mut *ptr.cast::<CompressedArray>();
+        CompressedArray::status(|| {
+            let len = array.len();
+            let data = array.storage.get_mut().expect("mutex was poisoned").uncompressed(len)?;
+
+            let shape = &array.shape;
+            let mut new_shape = shape.clone();
+            new_shape.swap(axis_1, axis_2);
+
+            let mut new_data = vec![0.0; len];
+            let mut index = vec![0; shape.len()];
+            for value in data.iter() {
+                let mut new_index = index.clone();
+                new_index.swap(axis_1, axis_2);
+                let position = new_index.iter().zip(&new_shape).fold(0, |acc, (i, n)| acc * n + i);
+                new_data[position] = *value;
+
+                // increment the multi-dimensional index in row-major order
+                for axis in (0..shape.len()).rev() {
+                    index[axis] += 1;
+                    if index[axis] < shape[axis] {
+                        break;
+                    }
+                    index[axis] = 0;
+                }
+            }
+
+            *data = new_data;
+            array.shape = new_shape;
+            Ok(())
+        })
+    }
+
+    unsafe extern fn create(
+        ptr: *const c_void,
+        shape: *const usize,
+        shape_count: usize,
+        new_array: *mut eqs_array_t,
+    ) -> eqs_status_t {
+        let array = &*ptr.cast::<CompressedArray>();
+        CompressedArray::status(|| {
+            let shape = std::slice::from_raw_parts(shape, shape_count).to_vec();
+            let original = match &array.original {
+                Some(original) => Some(original.try_clone()?),
+                None => None,
+            };
+
+            *new_array = CompressedArray {
+                storage: Mutex::new(Storage::Uncompressed(vec![0.0; shape.iter().product()])),
+                shape,
+                original,
+            }.into_array();
+            Ok(())
+        })
+    }
+
+    unsafe extern fn copy(ptr: *const c_void, new_array: *mut eqs_array_t) -> eqs_status_t {
+        let array = &*ptr.cast::<CompressedArray>();
+        CompressedArray::status(|| {
+            let storage = match &*array.storage.lock().expect("mutex was poisoned") {
+                Storage::Compressed(compressed) => Storage::Compressed(compressed.clone()),
+                Storage::Uncompressed(data) => Storage::Uncompressed(data.clone()),
+            };
+
+            let original = match &array.original {
+                Some(original) => Some(original.try_clone()?),
+                None => None,
+            };
+
+            *new_array = CompressedArray {
+                shape: array.shape.clone(),
+                storage: Mutex::new(storage),
+                original,
+            }.into_array();
+            Ok(())
+        })
+    }
+
+    unsafe extern fn move_samples_from(
+        output: *mut c_void,
+        input: *const c_void,
+        samples: *const eqs_sample_mapping_t,
+        samples_count: usize,
+        property_start: usize,
+        property_end: usize,
+    ) -> eqs_status_t {
+        let output = &mut *output.cast::<CompressedArray>();
+        let input = &*input.cast::<CompressedArray>();
+        let samples = std::slice::from_raw_parts(samples, samples_count);
+
+        CompressedArray::status(|| {
+            let output_len = output.len();
+            let output_data = output.storage.get_mut().expect("mutex was poisoned").uncompressed(output_len)?;
+            let mut input_storage = input.storage.lock().expect("mutex was poisoned");
+            let input_data = input_storage.uncompressed(input.len())?;
+
+            let output_properties = *output.shape.last().expect("empty shape");
+            let input_properties = property_end - property_start;
+            let components = input.shape[1..input.shape.len() - 1].iter().product::<usize>();
+
+            for sample in samples {
+                for component in 0..components {
+                    let input_start = (sample.input * components + component) * input_properties;
+                    let output_start = (sample.output * components + component) * output_properties;
+
+                    output_data[(output_start + property_start)..(output_start + property_end)]
+                        .copy_from_slice(&input_data[input_start..(input_start + input_properties)]);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    unsafe extern fn destroy(ptr: *mut c_void) {
+        let boxed = Box::from_raw(ptr.cast::<CompressedArray>());
+        std::mem::drop(boxed);
+    }
+}
+
+/// Create a new uncompressed array managed by Rust with the given `shape`,
+/// filled with zeros. This is used when loading data without any other array
+/// backend available.
+pub(crate) fn zeros_array(shape: Vec<usize>) -> eqs_array_t {
+    return CompressedArray {
+        storage: Mutex::new(Storage::Uncompressed(vec![0.0; shape.iter().product()])),
+        shape,
+        original: None,
+    }.into_array();
+}
+
+/// Get the `CompressedArray` behind this `array`, if any
+fn as_compressed(array: &eqs_array_t) -> Option<&CompressedArray> {
+    if array.ptr.is_null() || array.origin().ok()? != *COMPRESSED_ORIGIN {
+        return None;
+    }
+    return Some(unsafe { &*array.ptr.cast::<CompressedArray>() });
+}
+
+/// Get the size in bytes of the compressed data in `array`, or `None` if the
+/// array is not compressed or was already decompressed.
+pub(crate) fn compressed_size(array: &eqs_array_t) -> Option<usize> {
+    let array = as_compressed(array)?;
+    let storage = array.storage.lock().expect("mutex was poisoned");
+    match &*storage {
+        Storage::Compressed(compressed) => Some(compressed.len()),
+        Storage::Uncompressed(_) => None,
+    }
+}
+
+/// Compress the data of `array` in memory. Arrays which are already
+/// compressed are copied.
+pub(crate) fn compress_array(array: &eqs_array_t) -> Result<eqs_array_t, Error> {
+    if as_compressed(array).is_some() {
+        return array.try_clone();
+    }
+
+    let shape = array.shape()?.to_vec();
+    let storage = Storage::compress(&array.contiguous_data()?)?;
+
+    // keep an empty array with the same type as the original one
+    let mut empty_shape = shape.clone();
+    empty_shape[0] = 0;
+    let original = array.create(&empty_shape)?;
+
+    return Ok(CompressedArray {
+        shape,
+        storage: Mutex::new(storage),
+        original: Some(original),
+    }.into_array());
+}
+
+/// Convert a compressed `array` back to the original array type, returning
+/// `None` if the array is not compressed.
+pub(crate) fn decompress_array(array: &eqs_array_t) -> Result<Option<eqs_array_t>, Error> {
+    let compressed = match as_compressed(array) {
+        Some(compressed) => compressed,
+        None => return Ok(None),
+    };
+
+    let original = match &compressed.original {
+        Some(original) => original,
+        None => return Ok(None),
+    };
+
+    let mut new_array = original.create(&compressed.shape)?;
+    new_array.data_mut()?.copy_from_slice(array.data()?);
+    return Ok(Some(new_array));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::TestArray;
+    use crate::get_data_origin;
+
+    use super::*;
+
+    #[test]
+    fn compress() {
+        let array = TestArray::from_data(vec![2, 2], vec![1.0, 2.0, 3.0, 4.0]);
+        assert!(compressed_size(&array).is_none());
+        assert!(decompress_array(&array).unwrap().is_none());
+
+        let compressed = compress_array(&array).unwrap();
+        assert!(compressed_size(&compressed).is_some());
+        assert_eq!(compressed.origin().unwrap(), *COMPRESSED_ORIGIN);
+        assert_eq!(compressed.shape().unwrap(), [2, 2]);
+
+        let copy = compressed.try_clone().unwrap();
+        assert!(compressed_size(&copy).is_some());
+
+        // data is decompressed on first access
+        assert_eq!(compressed.data().unwrap(), [1.0, 2.0, 3.0, 4.0]);
+        assert!(compressed_size(&compressed).is_none());
+
+        let original = decompress_array(&copy).unwrap().unwrap();
+        assert_eq!(get_data_origin(original.origin().unwrap()), "rust.TestArray");
+        assert_eq!(original.data().unwrap(), [1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn swap_axes() {
+        let array = TestArray::from_data(vec![2, 3], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let mut compressed = compress_array(&array).unwrap();
+
+        compressed.swap_axes(0, 1).unwrap();
+        assert_eq!(compressed.shape().unwrap(), [3, 2]);
+        assert_eq!(compressed.data().unwrap(), [1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+    }
+}
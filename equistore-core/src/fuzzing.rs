@@ -0,0 +1,19 @@
+//! Entry points used by the fuzz targets in `equistore-core/fuzz`. This module
+//! is only available when compiling with `--cfg fuzzing`, which `cargo fuzz`
+//! sets automatically.
+
+use crate::data::TestArray;
+
+/// Try to load a serialized `TensorMap` from arbitrary bytes. This should
+/// never crash, only return errors for invalid data.
+pub fn load(data: &[u8]) {
+    let reader = std::io::Cursor::new(data);
+    let _ = crate::io::load(reader, |shape| Ok(TestArray::new(shape)));
+}
+
+/// Same as [`load`], but only loading the first block of the serialized
+/// `TensorMap`, which goes through a different code path for the keys.
+pub fn load_first_block(data: &[u8]) {
+    let reader = std::io::Cursor::new(data);
+    let _ = crate::io::load_blocks(reader, Some(&[0]), |shape| Ok(TestArray::new(shape)));
+}
@@ -0,0 +1,114 @@
+use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use crate::{Labels, Error};
+
+/// Conventions for gradients with respect to a given parameter: the
+/// components that gradients must have in addition to the values components.
+/// For example, gradients with respect to `strain` would usually declare two
+/// components with 3 entries each.
+static REGISTERED_GRADIENT_PARAMETERS: Lazy<Mutex<HashMap<String, Vec<Arc<Labels>>>>> = Lazy::new(|| {
+    Mutex::new(HashMap::new())
+});
+
+/// Register the convention for gradients with respect to `parameter`. All
+/// gradients with respect to this parameter must then start with the given
+/// `components`, followed by the components of the values.
+///
+/// Registering the same parameter multiple times replaces the previous
+/// convention.
+pub fn register_gradient_parameter(parameter: &str, components: Vec<Arc<Labels>>) -> Result<(), Error> {
+    if parameter == "values" {
+        return Err(Error::InvalidParameter(
+            "can not register gradient parameter named 'values'".into()
+        ));
+    }
+
+    for (i, component) in components.iter().enumerate() {
+        if component.size() != 1 || component.is_empty() {
+            return Err(Error::InvalidParameter(format!(
+                "invalid component {} for gradient parameter '{}': component \
+                labels must have a single dimension and at least one entry",
+                i, parameter
+            )));
+        }
+    }
+
+    let mut registered = REGISTERED_GRADIENT_PARAMETERS.lock().expect("mutex got poisoned");
+    registered.insert(parameter.into(), components);
+
+    return Ok(());
+}
+
+/// Get the components registered for gradients with respect to `parameter`
+/// with `register_gradient_parameter`, or `None` if this parameter was not
+/// registered.
+pub fn gradient_parameter_components(parameter: &str) -> Option<Vec<Arc<Labels>>> {
+    let registered = REGISTERED_GRADIENT_PARAMETERS.lock().expect("mutex got poisoned");
+    return registered.get(parameter).cloned();
+}
+
+/// Check that the gradient `components` (including the values components)
+/// follow the convention registered for `parameter`, if any.
+pub(crate) fn check_gradient_parameter(parameter: &str, components: &[Arc<Labels>], n_values_components: usize) -> Result<(), Error> {
+    let expected = match gradient_parameter_components(parameter) {
+        Some(expected) => expected,
+        None => return Ok(()),
+    };
+
+    let extra = &components[..components.len() - n_values_components];
+    if extra != expected {
+        let display = |components: &[Arc<Labels>]| {
+            components.iter()
+                .map(|c| format!("{} ({} entries)", c.names()[0], c.count()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        return Err(Error::InvalidParameter(format!(
+            "invalid gradient components for '{}': this parameter was registered \
+            with the components [{}] before the values components, but got [{}]",
+            parameter, display(&expected), display(extra)
+        )));
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LabelValue, LabelsBuilder};
+    use super::*;
+
+    fn xyz(name: &str) -> Arc<Labels> {
+        let mut labels = LabelsBuilder::new(vec![name]);
+        for i in 0..3 {
+            labels.add(&[LabelValue::from(i)]).unwrap();
+        }
+        return Arc::new(labels.finish());
+    }
+
+    #[test]
+    fn registry() {
+        assert!(gradient_parameter_components("tests::unregistered").is_none());
+        check_gradient_parameter("tests::unregistered", &[xyz("xyz")], 0).unwrap();
+
+        register_gradient_parameter("tests::strain", vec![xyz("xyz_1"), xyz("xyz_2")]).unwrap();
+        assert_eq!(gradient_parameter_components("tests::strain").unwrap().len(), 2);
+
+        check_gradient_parameter("tests::strain", &[xyz("xyz_1"), xyz("xyz_2"), xyz("c")], 1).unwrap();
+
+        let error = check_gradient_parameter("tests::strain", &[xyz("xyz_1"), xyz("c")], 1).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: invalid gradient components for 'tests::strain': \
+            this parameter was registered with the components [xyz_1 (3 entries), \
+            xyz_2 (3 entries)] before the values components, but got [xyz_1 (3 entries)]"
+        );
+
+        let error = register_gradient_parameter("values", vec![]).unwrap_err();
+        assert_eq!(error.to_string(), "invalid parameter: can not register gradient parameter named 'values'");
+    }
+}
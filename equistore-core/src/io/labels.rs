@@ -1,10 +1,10 @@
 use std::fmt::Write;
 
-use byteorder::{LittleEndian, ReadBytesExt, BigEndian, WriteBytesExt, NativeEndian};
+use byteorder::{LittleEndian, ReadBytesExt, BigEndian, WriteBytesExt};
 use py_literal::Value as PyValue;
 
 use super::{Header, check_for_extra_bytes};
-use crate::{Error, Labels, LabelsBuilder, LabelValue};
+use crate::{Error, Labels, LabelsBuilder};
 
 /// Read `Labels` stored using numpy's NPY format.
 ///
@@ -13,16 +13,33 @@ use crate::{Error, Labels, LabelsBuilder, LabelValue};
 /// associating name and either "<i4" for little endian file or ">i4" for big
 /// endian file. Data is stored in exactly the same way as inside a `Labels`,
 /// i.e. a big blob of 32-bit integers.
-pub fn read_npy_labels<R: std::io::Read>(mut reader: R) -> Result<Labels, Error> {
+///
+/// If `max_entries` is `Some`, labels with more entries are refused with
+/// `Error::LimitExceeded` before allocating memory for them.
+pub fn read_npy_labels<R: std::io::Read>(mut reader: R, max_entries: Option<usize>) -> Result<Labels, Error> {
     let header = Header::from_reader(&mut reader)?;
     if header.fortran_order {
         return Err(Error::Serialization("Labels can not be loaded from fortran-order arrays".into()));
     } else if header.shape.len() != 1 {
         return Err(Error::Serialization("Expected a 1-D array when loading Labels".into()));
     }
+
+    if let Some(max) = max_entries {
+        if header.shape[0] > max {
+            return Err(Error::LimitExceeded(format!(
+                "Labels contain {} entries, more than the maximal number of {} entries",
+                header.shape[0], max
+            )));
+        }
+    }
     let (names, endianness) = check_type_descriptor(header.type_descriptor)?;
 
-    let mut data = vec![0; header.shape[0] * names.len()];
+    let size = header.shape[0].checked_mul(names.len()).ok_or_else(|| Error::Serialization(
+        "the size of the Labels overflows usize".into()
+    ))?;
+    let mut data = Vec::new();
+    data.try_reserve_exact(size)?;
+    data.resize(size, 0);
     match endianness {
         Endianness::LittleEndian => reader.read_i32_into::<LittleEndian>(&mut data)?,
         Endianness::BigEndian => reader.read_i32_into::<BigEndian>(&mut data)?,
@@ -31,8 +48,13 @@ pub fn read_npy_labels<R: std::io::Read>(mut reader: R) -> Result<Labels, Error>
     check_for_extra_bytes(&mut reader)?;
 
     let mut builder = LabelsBuilder::new(names.iter().map(|s| &**s).collect());
-    for chunk in data.chunks_exact(names.len()) {
-        builder.add(&chunk.iter().map(|&i| LabelValue::new(i)).collect::<Vec<_>>())?;
+    if names.is_empty() {
+        // labels without dimensions contain at most a single empty entry
+        for _ in 0..header.shape[0] {
+            builder.add_i32_row(&[])?;
+        }
+    } else {
+        builder.add_all_i32(&data)?;
     }
 
     return Ok(builder.finish());
@@ -41,16 +63,11 @@ pub fn read_npy_labels<R: std::io::Read>(mut reader: R) -> Result<Labels, Error>
 /// Write `Labels` to the writer using numpy's NPY format.
 ///
 /// See [`read_npy_labels`] for more information on how `Labels` are stored to
-/// files.
+/// files. The data is always written in little-endian byte order.
 pub fn write_npy_labels<W: std::io::Write>(writer: &mut W, labels: &Labels) -> Result<(), Error> {
     let mut type_descriptor = String::from("[");
     for name in labels.names() {
-        if cfg!(target_endian = "little") {
-            write!(type_descriptor, "('{}', '<i4'), ", name).expect("failed to write dtype");
-        } else {
-            assert!(cfg!(target_endian = "big"));
-            write!(type_descriptor, "('{}', '>i4'), ", name).expect("failed to write dtype");
-        }
+        write!(type_descriptor, "('{}', '<i4'), ", name).expect("failed to write dtype");
     }
     type_descriptor += "]";
 
@@ -63,7 +80,7 @@ pub fn write_npy_labels<W: std::io::Write>(writer: &mut W, labels: &Labels) -> R
 
     for entry in labels {
         for value in entry {
-            writer.write_i32::<NativeEndian>(value.i32())?;
+            writer.write_i32::<LittleEndian>(value.i32())?;
         }
     }
 
@@ -131,5 +148,6 @@ fn check_type_descriptor(desc: PyValue) -> Result<(Vec<String>, Endianness), Err
         }
     }
 
-    return Ok((names, endianness.expect("failed to find endianness")));
+    // labels without dimensions have no fields, the endianness does not matter
+    return Ok((names, endianness.unwrap_or(Endianness::LittleEndian)));
 }
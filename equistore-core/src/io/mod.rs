@@ -1,10 +1,12 @@
+use std::io::{Read, Write};
 use std::sync::Arc;
 
-use byteorder::{LittleEndian, BigEndian, ReadBytesExt, WriteBytesExt, NativeEndian};
+use byteorder::{LittleEndian, BigEndian, ReadBytesExt, WriteBytesExt};
 use py_literal::Value as PyValue;
 use zip::{ZipArchive, ZipWriter, DateTime};
+use zip::read::ZipFile;
 
-use crate::{TensorMap, Error, TensorBlock, eqs_array_t};
+use crate::{TensorMap, Error, TensorBlock, Labels, LabelsBuilder, LabelsPool, Progress, eqs_array_t};
 
 
 mod npy_header;
@@ -13,6 +15,9 @@ pub use self::npy_header::Header;
 mod labels;
 use self::labels::{read_npy_labels, write_npy_labels};
 
+mod read_at;
+pub use self::read_at::{ReadAt, ReadAtReader};
+
 /// Load the serialized tensor map from the given path.
 ///
 /// Arrays for the values and gradient data will be created with the given
@@ -43,6 +48,10 @@ use self::labels::{read_npy_labels, write_npy_labels};
 ///                         / values / properties.npy
 ///                         / values / data.npy
 ///
+///                         # optional unit and quantity of the values
+///                         / values / unit.npy
+///                         / values / quantity.npy
+///
 ///                         # optional sections for gradients, one by parameter
 ///                         /   gradients / <parameter> / samples.npy
 ///                                                     /   components  / 0.npy
@@ -50,122 +59,523 @@ use self::labels::{read_npy_labels, write_npy_labels};
 ///                                                                     / <n_components>.npy
 ///                                                     /   data.npy
 /// ```
+///
+/// The unit and quantity are stored as 0-dimensional arrays of unicode strings.
+///
+/// All numeric data (labels, values, gradients and strings) is always written
+/// in little-endian byte order, regardless of the endianness of the current
+/// platform. When loading, both little-endian and big-endian arrays are
+/// accepted, and converted to the native byte order as needed.
+///
+/// Finally, if the tensor map contains free-form metadata, the keys and values
+/// are stored as 1-dimensional arrays of unicode strings in
+/// `/metadata/keys.npy` and `/metadata/values.npy` respectively.
+///
+/// Files saved with [`SaveOptions::labels_dictionary`] store the labels as
+/// zstd-compressed NPY files (`<path>.npy.zst`), which can use the shared
+/// dictionary in `/labels-dictionary.zstd`. Files saved with
+/// [`SaveOptions::mantissa_bits`] store the values and gradients data as
+/// zstd-compressed NPY files (`<path>.npy.zst`) without dictionary. These are
+/// loaded transparently.
+///
+/// Files using optional features of the format list the corresponding
+/// capabilities as a 1-dimensional array of unicode strings in
+/// `/capabilities.npy` (see [`SaveOptions::capabilities`]). Loading a file
+/// fails with the list of missing capabilities if some of them are not in
+/// [`SUPPORTED_CAPABILITIES`]. Files without optional features do not contain
+/// this array, and can be loaded by all versions of equistore.
 pub fn load<R, F>(reader: R, create_array: F) -> Result<TensorMap, Error>
     where R: std::io::Read + std::io::Seek,
           F: Fn(Vec<usize>) -> Result<eqs_array_t, Error>
+{
+    return load_blocks(reader, None, create_array);
+}
+
+/// Limits on the size of the data created when loading a `TensorMap`.
+///
+/// The sizes of labels and arrays are taken from the headers of the
+/// corresponding files, which can claim arbitrary sizes in corrupted or
+/// malicious files. Setting these limits allows to refuse such files with
+/// [`Error::LimitExceeded`] before allocating any memory for them. `None`
+/// means that there is no limit.
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(clippy::struct_field_names)]
+pub struct LoadLimits {
+    /// Maximal number of entries in any of the `Labels` (keys, samples,
+    /// components or properties)
+    pub max_labels_entries: Option<usize>,
+    /// Maximal size in bytes of any array (values, gradients or strings)
+    pub max_array_bytes: Option<usize>,
+    /// Maximal number of blocks in the `TensorMap`
+    pub max_blocks: Option<usize>,
+}
+
+impl LoadLimits {
+    fn check_array_bytes(&self, bytes: Option<usize>, path: &str) -> Result<(), Error> {
+        let bytes = bytes.ok_or_else(|| Error::Serialization(format!(
+            "the size of the array in '{}' overflows usize", path
+        )))?;
+
+        if let Some(max) = self.max_array_bytes {
+            if bytes > max {
+                return Err(Error::LimitExceeded(format!(
+                    "the array in '{}' contains {} bytes, more than the maximal size of {} bytes",
+                    path, bytes, max
+                )));
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+/// Load the serialized tensor map from the given reader, refusing data larger
+/// than the given `limits`.
+///
+/// See [`load`] for the description of the format.
+pub fn load_with_limits<R, F>(reader: R, limits: LoadLimits, create_array: F) -> Result<TensorMap, Error>
+    where R: std::io::Read + std::io::Seek,
+          F: Fn(Vec<usize>) -> Result<eqs_array_t, Error>
+{
+    return load_blocks_with_limits(reader, None, limits, create_array);
+}
+
+/// Load some of the blocks of a serialized tensor map from the given reader.
+///
+/// If `selection` is `Some`, only the blocks at the given indexes in the
+/// serialized tensor map are loaded, in this order, and the keys of the
+/// resulting tensor map only contain the corresponding entries. Other blocks
+/// are never read, which makes this function efficient when used with a
+/// [`ReadAtReader`] over remote data. If `selection` is `None`, all the
+/// blocks are loaded.
+///
+/// See [`load`] for the description of the format.
+pub fn load_blocks<R, F>(reader: R, selection: Option<&[usize]>, create_array: F) -> Result<TensorMap, Error>
+    where R: std::io::Read + std::io::Seek,
+          F: Fn(Vec<usize>) -> Result<eqs_array_t, Error>
+{
+    return load_blocks_with_limits(reader, selection, LoadLimits::default(), create_array);
+}
+
+/// Load some of the blocks of a serialized tensor map from the given reader,
+/// refusing data larger than the given `limits`.
+///
+/// See [`load_blocks`] and [`LoadLimits`] for more information.
+pub fn load_blocks_with_limits<R, F>(
+    reader: R,
+    selection: Option<&[usize]>,
+    limits: LoadLimits,
+    create_array: F,
+) -> Result<TensorMap, Error>
+    where R: std::io::Read + std::io::Seek,
+          F: Fn(Vec<usize>) -> Result<eqs_array_t, Error>
+{
+    return load_blocks_with_progress(reader, selection, limits, Progress::none(), create_array);
+}
+
+/// Same as [`load_blocks_with_limits`], reporting the number of blocks loaded
+/// so far to `progress`, and checking for cancellation before loading each
+/// block.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(selection = ?selection)))]
+pub fn load_blocks_with_progress<R, F>(
+    reader: R,
+    selection: Option<&[usize]>,
+    limits: LoadLimits,
+    progress: Progress,
+    create_array: F,
+) -> Result<TensorMap, Error>
+    where R: std::io::Read + std::io::Seek,
+          F: Fn(Vec<usize>) -> Result<eqs_array_t, Error>
 {
     let mut archive = ZipArchive::new(reader).map_err(|e| ("<root>".into(), e))?;
 
-    let path = String::from("keys.npy");
-    let keys = read_npy_labels(archive.by_name(&path).map_err(|e| (path, e))?)?;
+    if let Some(file) = optional_file(&mut archive, CAPABILITIES)? {
+        let capabilities = read_string_array(file, CAPABILITIES, &limits)?;
+        check_capabilities(&capabilities)?;
+    }
+
+    let dictionary = match optional_file(&mut archive, LABELS_DICTIONARY)? {
+        Some(mut file) => {
+            let mut dictionary = Vec::new();
+            file.read_to_end(&mut dictionary)?;
+            dictionary
+        }
+        None => Vec::new(),
+    };
+
+    let mut keys = read_labels(&mut archive, "keys.npy", &dictionary, &limits)?;
+
+    let block_ids = if let Some(selection) = selection {
+        let mut builder = LabelsBuilder::new(keys.names());
+        for &block_i in selection {
+            if block_i >= keys.count() {
+                return Err(Error::InvalidParameter(format!(
+                    "can not load block {}, the serialized tensor map only contains {} blocks",
+                    block_i, keys.count()
+                )));
+            }
+            builder.add(&keys[block_i])?;
+        }
+        keys = builder.finish();
+
+        selection.to_vec()
+    } else {
+        (0..keys.count()).collect()
+    };
+
+    if let Some(max) = limits.max_blocks {
+        if block_ids.len() > max {
+            return Err(Error::LimitExceeded(format!(
+                "the tensor map contains {} blocks, more than the maximal number of {} blocks",
+                block_ids.len(), max
+            )));
+        }
+    }
 
     let mut parameters = Vec::new();
     for name in archive.file_names() {
-        if name.starts_with("blocks/0/gradients/") && name.ends_with("/data.npy") {
-            let (_, parameter) = name.split_at(19);
-            let (parameter, _) = parameter.split_at(parameter.len() - 9);
+        let parameter = name.strip_prefix("blocks/0/gradients/").and_then(|name| {
+            name.strip_suffix("/data.npy").or_else(|| name.strip_suffix("/data.npy.zst"))
+        });
+
+        if let Some(parameter) = parameter {
             parameters.push(parameter.to_string());
         }
     }
 
+    // components and properties are usually shared between blocks, use a
+    // pool to only allocate them once
+    let mut pool = LabelsPool::new();
+
+    let n_blocks = block_ids.len();
     let mut blocks = Vec::new();
-    for block_i in 0..keys.count() {
-        let path = format!("blocks/{}/values/data.npy", block_i);
-        let data_file = archive.by_name(&path).map_err(|e| (path, e))?;
-        let (data, shape) = read_data(data_file, &create_array)?;
+    for block_i in block_ids {
+        progress.check()?;
 
-        let path = format!("blocks/{}/values/samples.npy", block_i);
-        let samples_file = archive.by_name(&path).map_err(|e| (path, e))?;
-        let samples = Arc::new(read_npy_labels(samples_file)?);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("load_block", block = block_i).entered();
+
+        let block = read_block(
+            &mut archive,
+            block_i,
+            &parameters,
+            &dictionary,
+            &limits,
+            &mut pool,
+            &create_array,
+        )?;
+
+        blocks.push(block);
+        progress.report(blocks.len(), n_blocks);
+    }
+
+    let mut tensor = TensorMap::new(keys, blocks)?;
+
+    read_metadata(&mut archive, &mut tensor, &limits)?;
+
+    return Ok(tensor);
+}
+
+
+// Read the block at index `block_i` in the archive, including all its
+// gradients
+fn read_block<R, F>(
+    archive: &mut ZipArchive<R>,
+    block_i: usize,
+    parameters: &[String],
+    dictionary: &[u8],
+    limits: &LoadLimits,
+    pool: &mut LabelsPool,
+    create_array: &F,
+) -> Result<TensorBlock, Error>
+    where R: std::io::Read + std::io::Seek,
+          F: Fn(Vec<usize>) -> Result<eqs_array_t, Error>
+{
+    let path = format!("blocks/{}/values/data.npy", block_i);
+    let (data, shape) = read_data_file(archive, &path, limits, create_array)?;
+
+    let path = format!("blocks/{}/values/samples.npy", block_i);
+    let samples = Arc::new(read_labels(archive, &path, dictionary, limits)?);
+
+    let mut components = Vec::new();
+    for i in 0..(shape.len() - 2) {
+        let path = format!("blocks/{}/values/components/{}.npy", block_i, i);
+        components.push(pool.intern(read_labels(archive, &path, dictionary, limits)?));
+    }
+
+    let path = format!("blocks/{}/values/properties.npy", block_i);
+    let properties = pool.intern(read_labels(archive, &path, dictionary, limits)?);
+
+    let mut block = TensorBlock::new(data, samples, components, properties)?;
+
+    let path = format!("blocks/{}/values/unit.npy", block_i);
+    if let Some(file) = optional_file(archive, &path)? {
+        block.set_unit(Some(&read_string(file, &path, limits)?))?;
+    }
+
+    let path = format!("blocks/{}/values/quantity.npy", block_i);
+    if let Some(file) = optional_file(archive, &path)? {
+        block.set_quantity(Some(&read_string(file, &path, limits)?))?;
+    }
+
+    for parameter in parameters {
+        let path = format!("blocks/{}/gradients/{}/data.npy", block_i, parameter);
+        let (data, shape) = read_data_file(archive, &path, limits, create_array)?;
+
+        let path = format!("blocks/{}/gradients/{}/samples.npy", block_i, parameter);
+        let samples = Arc::new(read_labels(archive, &path, dictionary, limits)?);
 
         let mut components = Vec::new();
         for i in 0..(shape.len() - 2) {
-            let path = format!("blocks/{}/values/components/{}.npy", block_i, i);
-            let component_file = archive.by_name(&path).map_err(|e| (path, e))?;
-            components.push(Arc::new(read_npy_labels(component_file)?));
+            let path = format!("blocks/{}/gradients/{}/components/{}.npy", block_i, parameter, i);
+            components.push(pool.intern(read_labels(archive, &path, dictionary, limits)?));
         }
 
-        let path = format!("blocks/{}/values/properties.npy", block_i);
-        let properties_file = archive.by_name(&path).map_err(|e| (path, e))?;
-        let properties = Arc::new(read_npy_labels(properties_file)?);
-
-        let mut block = TensorBlock::new(data, samples, components, properties)?;
+        block.add_gradient(parameter, data, samples, components)?;
+    }
 
-        for parameter in &parameters {
-            let path = format!("blocks/{}/gradients/{}/data.npy", block_i, parameter);
-            let data_file = archive.by_name(&path).map_err(|e| (path, e))?;
-            let (data, shape) = read_data(data_file, &create_array)?;
+    return Ok(block);
+}
 
-            let path = format!("blocks/{}/gradients/{}/samples.npy", block_i, parameter);
-            let samples_file = archive.by_name(&path).map_err(|e| (path, e))?;
-            let samples = Arc::new(read_npy_labels(samples_file)?);
+// Read the free-form metadata from the archive, if any, and add it to `tensor`
+fn read_metadata<R>(archive: &mut ZipArchive<R>, tensor: &mut TensorMap, limits: &LoadLimits) -> Result<(), Error>
+    where R: std::io::Read + std::io::Seek
+{
+    let metadata_keys = match optional_file(archive, "metadata/keys.npy")? {
+        Some(keys_file) => Some(read_string_array(keys_file, "metadata/keys.npy", limits)?),
+        None => None,
+    };
 
-            let mut components = Vec::new();
-            for i in 0..(shape.len() - 2) {
-                let path = format!("blocks/{}/gradients/{}/components/{}.npy", block_i, parameter, i);
-                let component_file = archive.by_name(&path).map_err(|e| (path, e))?;
-                components.push(Arc::new(read_npy_labels(component_file)?));
-            }
+    if let Some(metadata_keys) = metadata_keys {
+        let path = String::from("metadata/values.npy");
+        let values_file = archive.by_name(&path).map_err(|e| (path.clone(), e))?;
+        let metadata_values = read_string_array(values_file, &path, limits)?;
 
-            block.add_gradient(parameter, data, samples, components)?;
+        if metadata_keys.len() != metadata_values.len() {
+            return Err(Error::Serialization(format!(
+                "expected the same number of metadata keys and values, got {} and {}",
+                metadata_keys.len(), metadata_values.len()
+            )));
         }
 
-        blocks.push(block);
+        for (key, value) in metadata_keys.iter().zip(&metadata_values) {
+            tensor.set_metadata(key, Some(value))?;
+        }
     }
 
-    return TensorMap::new(keys, blocks);
+    return Ok(());
 }
 
 
+/// Options controlling how a `TensorMap` is saved
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SaveOptions {
+    /// Compress all the labels with zstd, using a dictionary trained on the
+    /// labels of the tensor map and shared between all of them.
+    ///
+    /// This can significantly reduce the size of files dominated by metadata,
+    /// such as tensor maps with many small blocks. The labels are then stored
+    /// in `<path>.npy.zst` files instead of `<path>.npy`, and the dictionary
+    /// in `/labels-dictionary.zstd`. Files created with this option can not be
+    /// loaded with numpy.
+    pub labels_dictionary: bool,
+    /// Round the values and gradients data to keep only this number of bits
+    /// in the mantissa of the 64-bit floating point values (between 0 and 52,
+    /// full precision is 52 bits), rounding to the nearest representable
+    /// value. `None` keeps the data unchanged.
+    ///
+    /// The relative error introduced by the rounding is at most
+    /// `2^-(mantissa_bits + 1)`. The rounded data compresses much better, and
+    /// is stored in zstd-compressed `<path>.npy.zst` files instead of
+    /// `<path>.npy`. Files created with this option can not be loaded with
+    /// numpy.
+    pub mantissa_bits: Option<u32>,
+}
+
+/// Capability of files with zstd-compressed labels, created with
+/// [`SaveOptions::labels_dictionary`]
+const ZSTD_LABELS: &str = "zstd-labels";
+/// Capability of files with zstd-compressed data, created with
+/// [`SaveOptions::mantissa_bits`]
+const ZSTD_DATA: &str = "zstd-data";
+
+/// Optional capabilities of the serialization format that this version of
+/// equistore can load
+pub const SUPPORTED_CAPABILITIES: &[&str] = &[ZSTD_LABELS, ZSTD_DATA];
+
+impl SaveOptions {
+    /// Get the capabilities a reader needs to load files saved with these
+    /// options. All optional features of the format are disabled by default,
+    /// so files saved with the default options can be loaded by older
+    /// versions of equistore.
+    pub fn capabilities(&self) -> Vec<&'static str> {
+        let mut capabilities = Vec::new();
+        if self.labels_dictionary {
+            capabilities.push(ZSTD_LABELS);
+        }
+        if self.mantissa_bits.is_some() {
+            capabilities.push(ZSTD_DATA);
+        }
+        return capabilities;
+    }
+}
+
+/// Check that all the `capabilities` required by a file are supported
+fn check_capabilities(capabilities: &[String]) -> Result<(), Error> {
+    let missing = capabilities.iter()
+        .filter(|capability| !SUPPORTED_CAPABILITIES.contains(&capability.as_str()))
+        .map(|capability| capability.as_str())
+        .collect::<Vec<_>>();
+
+    if !missing.is_empty() {
+        return Err(Error::Serialization(format!(
+            "this file requires capabilities which are not supported by this \
+            version of equistore: {}", missing.join(", ")
+        )));
+    }
+
+    return Ok(());
+}
+
 /// Save the given tensor to a file (or any other writer).
 ///
 /// The format used is documented in the [`load`] function, and is based on
 /// numpy's NPZ format (i.e. zip archive containing NPY files).
 pub fn save<W: std::io::Write + std::io::Seek>(writer: W, tensor: &TensorMap) -> Result<(), Error> {
+    return save_with_options(writer, tensor, SaveOptions::default());
+}
+
+/// Save the given tensor to a file (or any other writer), using the given
+/// options.
+///
+/// See [`save`] and [`SaveOptions`] for more information.
+pub fn save_with_options<W>(writer: W, tensor: &TensorMap, save_options: SaveOptions) -> Result<(), Error>
+    where W: std::io::Write + std::io::Seek
+{
+    return save_with_progress(writer, tensor, save_options, Progress::none());
+}
+
+/// Same as [`save_with_options`], reporting the number of blocks saved so far
+/// to `progress`, and checking for cancellation before saving each block.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    skip_all, fields(
+        blocks = tensor.keys().count(),
+        labels_dictionary = save_options.labels_dictionary,
+        mantissa_bits = ?save_options.mantissa_bits,
+    )
+))]
+pub fn save_with_progress<W>(
+    writer: W,
+    tensor: &TensorMap,
+    save_options: SaveOptions,
+    progress: Progress,
+) -> Result<(), Error>
+    where W: std::io::Write + std::io::Seek
+{
+    if let Some(bits) = save_options.mantissa_bits {
+        if bits > MANTISSA_BITS {
+            return Err(Error::InvalidParameter(format!(
+                "mantissa_bits must be between 0 and {}, got {}", MANTISSA_BITS, bits
+            )));
+        }
+    }
+
     let mut archive = ZipWriter::new(writer);
     let options = zip::write::FileOptions::default()
         .compression_method(zip::CompressionMethod::Stored)
         .large_file(true)
         .last_modified_time(DateTime::from_date_and_time(2000, 1, 1, 0, 0, 0).expect("invalid datetime"));
 
-    let path = String::from("keys.npy");
-    archive.start_file(&path, options).map_err(|e| (path, e))?;
-    write_npy_labels(&mut archive, tensor.keys())?;
+    let capabilities = save_options.capabilities();
+    if !capabilities.is_empty() {
+        let path = String::from(CAPABILITIES);
+        archive.start_file(&path, options).map_err(|e| (path, e))?;
+        write_string_array(&mut archive, &capabilities)?;
+    }
+
+    let dictionary = if save_options.labels_dictionary {
+        let dictionary = train_labels_dictionary(tensor)?;
+        if !dictionary.is_empty() {
+            let path = String::from(LABELS_DICTIONARY);
+            archive.start_file(&path, options).map_err(|e| (path, e))?;
+            archive.write_all(&dictionary)?;
+        }
+        Some(dictionary)
+    } else {
+        None
+    };
+    let dictionary = dictionary.as_deref();
+
+    write_labels(&mut archive, options, "keys.npy", tensor.keys(), dictionary)?;
 
     for (block_i, block) in tensor.blocks().iter().enumerate() {
+        progress.check()?;
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "save_block",
+            block = block_i,
+            samples = block.values().samples.count(),
+            properties = block.values().properties.count(),
+        ).entered();
+
         let path = format!("blocks/{}/values/data.npy", block_i);
-        archive.start_file(&path, options).map_err(|e| (path, e))?;
-        write_data(&mut archive, &block.values().data)?;
+        write_data_file(&mut archive, options, &path, &block.values().data, save_options.mantissa_bits)?;
 
         let path = format!("blocks/{}/values/samples.npy", block_i);
-        archive.start_file(&path, options).map_err(|e| (path, e))?;
-        write_npy_labels(&mut archive, &block.values().samples)?;
+        write_labels(&mut archive, options, &path, &block.values().samples, dictionary)?;
 
         for (i, component) in block.values().components.iter().enumerate() {
             let path = format!("blocks/{}/values/components/{}.npy", block_i, i);
-            archive.start_file(&path, options).map_err(|e| (path, e))?;
-            write_npy_labels(&mut archive, component)?;
+            write_labels(&mut archive, options, &path, component, dictionary)?;
         }
 
         let path = format!("blocks/{}/values/properties.npy", block_i);
-        archive.start_file(&path, options).map_err(|e| (path, e))?;
-        write_npy_labels(&mut archive, &block.values().properties)?;
+        write_labels(&mut archive, options, &path, &block.values().properties, dictionary)?;
 
-        for (parameter, gradient) in block.gradients() {
-            let path = format!("blocks/{}/gradients/{}/data.npy", block_i, parameter);
+        if let Some(unit) = block.unit() {
+            let path = format!("blocks/{}/values/unit.npy", block_i);
+            archive.start_file(&path, options).map_err(|e| (path, e))?;
+            write_string(&mut archive, unit)?;
+        }
+
+        if let Some(quantity) = block.quantity() {
+            let path = format!("blocks/{}/values/quantity.npy", block_i);
             archive.start_file(&path, options).map_err(|e| (path, e))?;
-            write_data(&mut archive, &gradient.data)?;
+            write_string(&mut archive, quantity)?;
+        }
+
+        // sort the gradients by parameter to get reproducible files
+        let mut gradients = block.gradients().iter().collect::<Vec<_>>();
+        gradients.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        for (parameter, gradient) in gradients {
+            let path = format!("blocks/{}/gradients/{}/data.npy", block_i, parameter);
+            write_data_file(&mut archive, options, &path, &gradient.data, save_options.mantissa_bits)?;
 
             let path = format!("blocks/{}/gradients/{}/samples.npy", block_i, parameter);
-            archive.start_file(&path, options).map_err(|e| (path, e))?;
-            write_npy_labels(&mut archive, &gradient.samples)?;
+            write_labels(&mut archive, options, &path, &gradient.samples, dictionary)?;
 
             for (i, component) in gradient.components.iter().enumerate() {
                 let path = format!("blocks/{}/gradients/{}/components/{}.npy", block_i, parameter, i);
-                archive.start_file(&path, options).map_err(|e| (path, e))?;
-                write_npy_labels(&mut archive, component)?;
+                write_labels(&mut archive, options, &path, component, dictionary)?;
             }
         }
+
+        progress.report(block_i + 1, tensor.blocks().len());
+    }
+
+    let metadata = tensor.metadata().collect::<Vec<_>>();
+    if !metadata.is_empty() {
+        let metadata_keys = metadata.iter().map(|(key, _)| *key).collect::<Vec<_>>();
+        let path = String::from("metadata/keys.npy");
+        archive.start_file(&path, options).map_err(|e| (path, e))?;
+        write_string_array(&mut archive, &metadata_keys)?;
+
+        let metadata_values = metadata.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+        let path = String::from("metadata/values.npy");
+        archive.start_file(&path, options).map_err(|e| (path, e))?;
+        write_string_array(&mut archive, &metadata_values)?;
     }
 
     archive.finish().map_err(|e| ("<root>".into(), e))?;
@@ -173,8 +583,129 @@ pub fn save<W: std::io::Write + std::io::Seek>(writer: W, tensor: &TensorMap) ->
     return Ok(());
 }
 
+/// Path of the list of capabilities required to load the file in the archive
+const CAPABILITIES: &str = "capabilities.npy";
+
+/// Path of the shared zstd dictionary for labels in the archive
+const LABELS_DICTIONARY: &str = "labels-dictionary.zstd";
+
+/// Maximal size of the shared zstd dictionary for labels
+const MAX_LABELS_DICTIONARY_SIZE: usize = 110 * 1024;
+
+// Train a zstd dictionary on all the labels in this tensor map. This returns
+// an empty dictionary if there is not enough data to train one, in which case
+// the labels are compressed without a dictionary.
+fn train_labels_dictionary(tensor: &TensorMap) -> Result<Vec<u8>, Error> {
+    let mut samples = Vec::new();
+    let mut add_sample = |labels: &Labels| -> Result<(), Error> {
+        let mut buffer = Vec::new();
+        write_npy_labels(&mut buffer, labels)?;
+        samples.push(buffer);
+        Ok(())
+    };
+
+    add_sample(tensor.keys())?;
+    for block in tensor.blocks() {
+        let values = block.values();
+        add_sample(&values.samples)?;
+        for component in &values.components {
+            add_sample(component)?;
+        }
+        add_sample(&values.properties)?;
+
+        // use the same order as `save` to get reproducible dictionaries
+        let mut gradients = block.gradients().iter().collect::<Vec<_>>();
+        gradients.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        for (_, gradient) in gradients {
+            add_sample(&gradient.samples)?;
+            for component in &gradient.components {
+                add_sample(component)?;
+            }
+        }
+    }
+
+    let total_size = samples.iter().map(Vec::len).sum::<usize>();
+    let max_size = (total_size / 8).clamp(1024, MAX_LABELS_DICTIONARY_SIZE);
+
+    return Ok(zstd::dict::from_samples(&samples, max_size).unwrap_or_default());
+}
+
+// Write labels to the file at `path` in the archive, compressing them with
+// zstd and the given `dictionary` if it is not `None`
+fn write_labels<W>(
+    archive: &mut ZipWriter<W>,
+    options: zip::write::FileOptions,
+    path: &str,
+    labels: &Labels,
+    dictionary: Option<&[u8]>,
+) -> Result<(), Error> where W: std::io::Write + std::io::Seek {
+    if let Some(dictionary) = dictionary {
+        let path = format!("{}.zst", path);
+        archive.start_file(&path, options).map_err(|e| (path, e))?;
+
+        let mut encoder = zstd::stream::write::Encoder::with_dictionary(
+            archive,
+            zstd::DEFAULT_COMPRESSION_LEVEL,
+            dictionary
+        )?;
+        write_npy_labels(&mut encoder, labels)?;
+        encoder.finish()?;
+    } else {
+        archive.start_file(path, options).map_err(|e| (path.to_owned(), e))?;
+        write_npy_labels(archive, labels)?;
+    }
+
+    return Ok(());
+}
+
+// Read labels from the file at `path` in the archive, or from the
+// zstd-compressed `<path>.zst` file if it exists
+fn read_labels<R>(archive: &mut ZipArchive<R>, path: &str, dictionary: &[u8], limits: &LoadLimits) -> Result<Labels, Error>
+    where R: std::io::Read + std::io::Seek
+{
+    let compressed = format!("{}.zst", path);
+    match archive.by_name(&compressed) {
+        Ok(file) => {
+            let reader = std::io::BufReader::new(file);
+            let decoder = zstd::stream::read::Decoder::with_dictionary(reader, dictionary)?;
+            return read_npy_labels(decoder, limits.max_labels_entries);
+        }
+        Err(zip::result::ZipError::FileNotFound) => {},
+        Err(e) => return Err(Error::from((compressed, e))),
+    }
+
+    let file = archive.by_name(path).map_err(|e| (path.to_owned(), e))?;
+    return read_npy_labels(file, limits.max_labels_entries);
+}
+
+// Read a data array from the file at `path` in the archive, or from the
+// zstd-compressed `<path>.zst` file if it exists
+fn read_data_file<R, F>(
+    archive: &mut ZipArchive<R>,
+    path: &str,
+    limits: &LoadLimits,
+    create_array: &F,
+) -> Result<(eqs_array_t, Vec<usize>), Error>
+    where R: std::io::Read + std::io::Seek,
+          F: Fn(Vec<usize>) -> Result<eqs_array_t, Error>
+{
+    let compressed = format!("{}.zst", path);
+    match archive.by_name(&compressed) {
+        Ok(file) => {
+            let decoder = zstd::stream::read::Decoder::new(file)?;
+            return read_data(decoder, &compressed, limits, create_array);
+        }
+        Err(zip::result::ZipError::FileNotFound) => {},
+        Err(e) => return Err(Error::from((compressed, e))),
+    }
+
+    let file = archive.by_name(path).map_err(|e| (path.to_owned(), e))?;
+    return read_data(file, path, limits, create_array);
+}
+
 // Read a data array from the given reader, using numpy's NPY format
-fn read_data<R, F>(mut reader: R, create_array: &F) -> Result<(eqs_array_t, Vec<usize>), Error>
+fn read_data<R, F>(mut reader: R, path: &str, limits: &LoadLimits, create_array: &F) -> Result<(eqs_array_t, Vec<usize>), Error>
     where R: std::io::Read, F: Fn(Vec<usize>) -> Result<eqs_array_t, Error>
 {
     let header = Header::from_reader(&mut reader)?;
@@ -183,6 +714,9 @@ fn read_data<R, F>(mut reader: R, create_array: &F) -> Result<(eqs_array_t, Vec<
     }
 
     let shape = header.shape;
+    let bytes = shape.iter().try_fold(8_usize, |size, &n| size.checked_mul(n));
+    limits.check_array_bytes(bytes, path)?;
+
     let mut array = create_array(shape.clone())?;
 
     match header.type_descriptor {
@@ -205,6 +739,94 @@ fn read_data<R, F>(mut reader: R, create_array: &F) -> Result<(eqs_array_t, Vec<
     return Ok((array, shape));
 }
 
+// Get the file at `path` in the archive, or `None` if this file does not exist
+fn optional_file<'a, R>(archive: &'a mut ZipArchive<R>, path: &str) -> Result<Option<ZipFile<'a>>, Error>
+    where R: std::io::Read + std::io::Seek
+{
+    match archive.by_name(path) {
+        Ok(file) => Ok(Some(file)),
+        Err(zip::result::ZipError::FileNotFound) => Ok(None),
+        Err(e) => Err(Error::from((path.to_owned(), e))),
+    }
+}
+
+// Read a string from the given reader, stored as a 0-dimensional unicode array
+// using numpy's NPY format
+fn read_string<R: std::io::Read>(reader: R, path: &str, limits: &LoadLimits) -> Result<String, Error> {
+    let (mut strings, shape) = read_unicode_array(reader, path, limits)?;
+    if !shape.is_empty() {
+        return Err(Error::Serialization(format!(
+            "expected a 0-dimensional array for string data, got an array with shape {:?}",
+            shape
+        )));
+    }
+
+    return Ok(strings.remove(0));
+}
+
+// Read a list of strings from the given reader, stored as a 1-dimensional
+// unicode array using numpy's NPY format
+fn read_string_array<R: std::io::Read>(reader: R, path: &str, limits: &LoadLimits) -> Result<Vec<String>, Error> {
+    let (strings, shape) = read_unicode_array(reader, path, limits)?;
+    if shape.len() != 1 {
+        return Err(Error::Serialization(format!(
+            "expected a 1-dimensional array for a list of strings, got an array with shape {:?}",
+            shape
+        )));
+    }
+
+    return Ok(strings);
+}
+
+// Read an array of unicode strings of any shape using numpy's NPY format,
+// returning the strings and the shape of the array
+fn read_unicode_array<R: std::io::Read>(mut reader: R, path: &str, limits: &LoadLimits) -> Result<(Vec<String>, Vec<usize>), Error> {
+    let header = Header::from_reader(&mut reader)?;
+    if header.fortran_order && header.shape.len() > 1 {
+        return Err(Error::Serialization("string data can not be loaded from fortran-order arrays".into()));
+    }
+
+    let (little_endian, length) = match header.type_descriptor {
+        PyValue::String(ref s) if s.starts_with("<U") => (true, s[2..].parse::<usize>().ok()),
+        PyValue::String(ref s) if s.starts_with(">U") => (false, s[2..].parse::<usize>().ok()),
+        _ => (true, None),
+    };
+
+    let length = length.ok_or_else(|| Error::Serialization(format!(
+        "unknown type for string data, expected unicode string, got {}",
+        header.type_descriptor
+    )))?;
+
+    let count = header.shape.iter().try_fold(1_usize, |size, &n| size.checked_mul(n));
+    let bytes = count.and_then(|count| count.checked_mul(length)).and_then(|size| size.checked_mul(4));
+    limits.check_array_bytes(bytes, path)?;
+
+    let count = count.expect("checked above");
+    let mut strings = Vec::with_capacity(count);
+    let mut codepoints = vec![0; length];
+    for _ in 0..count {
+        if little_endian {
+            reader.read_u32_into::<LittleEndian>(&mut codepoints)?;
+        } else {
+            reader.read_u32_into::<BigEndian>(&mut codepoints)?;
+        }
+
+        let mut string = String::with_capacity(length);
+        // numpy pads strings with NULL characters
+        for &codepoint in codepoints.iter().take_while(|&&c| c != 0) {
+            let c = char::from_u32(codepoint).ok_or_else(|| Error::Serialization(format!(
+                "invalid unicode codepoint in string data: {:#x}", codepoint
+            )))?;
+            string.push(c);
+        }
+        strings.push(string);
+    }
+
+    check_for_extra_bytes(&mut reader)?;
+
+    return Ok((strings, header.shape));
+}
+
 // returns an error if the given reader contains any more data
 fn check_for_extra_bytes<R: std::io::Read>(reader: &mut R) -> Result<(), Error> {
     let extra = reader.read_to_end(&mut Vec::new())?;
@@ -215,25 +837,395 @@ fn check_for_extra_bytes<R: std::io::Read>(reader: &mut R) -> Result<(), Error>
     }
 }
 
-// Write an array to the given writer, using numpy's NPY format
-fn write_data<W: std::io::Write>(writer: &mut W, array: &eqs_array_t) -> Result<(), Error> {
-    let type_descriptor = if cfg!(target_endian = "little") {
-        "'<f8'"
+/// Number of explicitly stored bits in the mantissa of a 64-bit float
+const MANTISSA_BITS: u32 = 52;
+
+/// Round `value` to the nearest floating point value with only `bits` bits in
+/// the mantissa, breaking ties to even. Infinite and NaN values are left
+/// unchanged.
+fn round_mantissa(value: f64, bits: u32) -> f64 {
+    if bits >= MANTISSA_BITS || !value.is_finite() {
+        return value;
+    }
+
+    let dropped = MANTISSA_BITS - bits;
+    let value = value.to_bits();
+    let half = (1_u64 << (dropped - 1)) - 1 + ((value >> dropped) & 1);
+    let mask = !((1_u64 << dropped) - 1);
+
+    return f64::from_bits((value + half) & mask);
+}
+
+// Write a data array to the file at `path` in the archive. If `mantissa_bits`
+// is not `None`, the data is rounded and written to a zstd-compressed
+// `<path>.zst` file instead.
+fn write_data_file<W>(
+    archive: &mut ZipWriter<W>,
+    options: zip::write::FileOptions,
+    path: &str,
+    array: &eqs_array_t,
+    mantissa_bits: Option<u32>,
+) -> Result<(), Error> where W: std::io::Write + std::io::Seek {
+    if let Some(bits) = mantissa_bits {
+        let path = format!("{}.zst", path);
+        archive.start_file(&path, options).map_err(|e| (path, e))?;
+
+        let mut encoder = zstd::stream::write::Encoder::new(archive, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+        write_data(&mut encoder, array, |value| round_mantissa(value, bits))?;
+        encoder.finish()?;
     } else {
-        "'>f8'"
-    };
+        archive.start_file(path, options).map_err(|e| (path.to_owned(), e))?;
+        write_data(archive, array, |value| value)?;
+    }
+
+    return Ok(());
+}
 
+// Write an array to the given writer, using numpy's NPY format. All values are
+// passed through `transform` before being written.
+fn write_data<W: std::io::Write>(writer: &mut W, array: &eqs_array_t, transform: impl Fn(f64) -> f64) -> Result<(), Error> {
     let header = Header {
-        type_descriptor: type_descriptor.parse().expect("invalid dtype"),
+        type_descriptor: "'<f8'".parse().expect("invalid dtype"),
         fortran_order: false,
         shape: array.shape()?.to_vec(),
     };
 
     header.write(&mut *writer)?;
 
-    for &value in array.data()? {
-        writer.write_f64::<NativeEndian>(value)?;
+    for &value in array.contiguous_data()?.iter() {
+        writer.write_f64::<LittleEndian>(transform(value))?;
     }
 
     return Ok(());
 }
+
+// Write a single string as a 0-dimensional unicode array using numpy's NPY
+// format
+fn write_string<W: std::io::Write>(writer: &mut W, string: &str) -> Result<(), Error> {
+    return write_unicode_array(writer, &[string], Vec::new());
+}
+
+// Write a list of strings as a 1-dimensional unicode array using numpy's NPY
+// format
+fn write_string_array<W: std::io::Write>(writer: &mut W, strings: &[&str]) -> Result<(), Error> {
+    return write_unicode_array(writer, strings, vec![strings.len()]);
+}
+
+fn write_unicode_array<W: std::io::Write>(writer: &mut W, strings: &[&str], shape: Vec<usize>) -> Result<(), Error> {
+    // all strings are padded with NULL to the length of the longest one
+    let length = strings.iter().map(|s| s.chars().count()).max().unwrap_or(0).max(1);
+
+    let header = Header {
+        type_descriptor: format!("'<U{}'", length).parse().expect("invalid dtype"),
+        fortran_order: false,
+        shape,
+    };
+
+    header.write(&mut *writer)?;
+
+    for string in strings {
+        let mut count = 0;
+        for c in string.chars() {
+            writer.write_u32::<LittleEndian>(u32::from(c))?;
+            count += 1;
+        }
+
+        for _ in count..length {
+            writer.write_u32::<LittleEndian>(0)?;
+        }
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use byteorder::{BigEndian, WriteBytesExt};
+
+    use crate::data::TestArray;
+    use crate::{LabelsBuilder, LabelValue};
+    use super::labels::{read_npy_labels, write_npy_labels};
+    use super::*;
+
+    // Create a NPY file with the given header and big-endian data, as it
+    // would be produced by numpy on a big-endian platform
+    fn big_endian_npy(type_descriptor: &str, shape: Vec<usize>, write: impl Fn(&mut Vec<u8>)) -> Vec<u8> {
+        let header = Header {
+            type_descriptor: type_descriptor.parse().unwrap(),
+            fortran_order: false,
+            shape,
+        };
+
+        let mut buffer = Vec::new();
+        header.write(&mut buffer).unwrap();
+        write(&mut buffer);
+        return buffer;
+    }
+
+    fn header_and_data(buffer: &[u8]) -> (Header, &[u8]) {
+        let mut reader = buffer;
+        let header = Header::from_reader(&mut reader).unwrap();
+        return (header, reader);
+    }
+
+    #[test]
+    fn data_endianness() {
+        let array = TestArray::from_data(vec![3], vec![1.0, -2.5, 1e-300]);
+
+        let mut buffer = Vec::new();
+        write_data(&mut buffer, &array, |value| value).unwrap();
+
+        let (header, data) = header_and_data(&buffer);
+        assert_eq!(header.type_descriptor.as_string().unwrap(), "<f8");
+        let expected = [1.0_f64, -2.5, 1e-300].iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+        assert_eq!(data, expected);
+
+        let (array, shape) = read_data(&*buffer, "data.npy", &LoadLimits::default(), &|shape| Ok(TestArray::new(shape))).unwrap();
+        assert_eq!(shape, [3]);
+        assert_eq!(array.data().unwrap(), [1.0, -2.5, 1e-300]);
+
+        let buffer = big_endian_npy("'>f8'", vec![3], |buffer| {
+            for &value in &[1.0, -2.5, 1e-300] {
+                buffer.write_f64::<BigEndian>(value).unwrap();
+            }
+        });
+        let (array, _) = read_data(&*buffer, "data.npy", &LoadLimits::default(), &|shape| Ok(TestArray::new(shape))).unwrap();
+        assert_eq!(array.data().unwrap(), [1.0, -2.5, 1e-300]);
+    }
+
+    #[test]
+    fn labels_endianness() {
+        let mut builder = LabelsBuilder::new(vec!["a", "b"]);
+        builder.add(&[LabelValue::new(1), LabelValue::new(-300)]).unwrap();
+        builder.add(&[LabelValue::new(70000), LabelValue::new(2)]).unwrap();
+        let labels = builder.finish();
+
+        let mut buffer = Vec::new();
+        write_npy_labels(&mut buffer, &labels).unwrap();
+
+        let (header, data) = header_and_data(&buffer);
+        let type_descriptor = "[('a', '<i4'), ('b', '<i4')]".parse::<PyValue>().unwrap();
+        assert_eq!(header.type_descriptor, type_descriptor);
+        let expected = [1_i32, -300, 70000, 2].iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+        assert_eq!(data, expected);
+
+        assert_eq!(read_npy_labels(&*buffer, None).unwrap(), labels);
+
+        let buffer = big_endian_npy("[('a', '>i4'), ('b', '>i4')]", vec![2], |buffer| {
+            for &value in &[1, -300, 70000, 2] {
+                buffer.write_i32::<BigEndian>(value).unwrap();
+            }
+        });
+        assert_eq!(read_npy_labels(&*buffer, None).unwrap(), labels);
+    }
+
+    #[test]
+    fn scalar_labels() {
+        let labels = Labels::scalar();
+        assert_eq!(labels.count(), 1);
+
+        let mut buffer = Vec::new();
+        write_npy_labels(&mut buffer, &labels).unwrap();
+        let loaded = read_npy_labels(&*buffer, None).unwrap();
+        assert_eq!(loaded.size(), 0);
+        assert_eq!(loaded.count(), 1);
+        assert_eq!(loaded, labels);
+    }
+
+    #[test]
+    fn string_endianness() {
+        let mut buffer = Vec::new();
+        write_string(&mut buffer, "eV").unwrap();
+
+        let (header, data) = header_and_data(&buffer);
+        assert_eq!(header.type_descriptor.as_string().unwrap(), "<U2");
+        let expected = ['e', 'V'].iter().flat_map(|&c| u32::from(c).to_le_bytes()).collect::<Vec<_>>();
+        assert_eq!(data, expected);
+
+        assert_eq!(read_string(&*buffer, "unit.npy", &LoadLimits::default()).unwrap(), "eV");
+
+        let buffer = big_endian_npy("'>U2'", vec![], |buffer| {
+            for c in "eV".chars() {
+                buffer.write_u32::<BigEndian>(u32::from(c)).unwrap();
+            }
+        });
+        assert_eq!(read_string(&*buffer, "unit.npy", &LoadLimits::default()).unwrap(), "eV");
+    }
+
+    fn build_labels(names: Vec<&str>, values: impl Iterator<Item=Vec<i32>>) -> Labels {
+        let mut builder = LabelsBuilder::new(names);
+        for entry in values {
+            builder.add(&entry).unwrap();
+        }
+        return builder.finish();
+    }
+
+    #[test]
+    fn labels_dictionary() {
+        // many small blocks with very similar labels
+        let mut blocks = Vec::new();
+        for block_i in 0..50 {
+            let samples = build_labels(vec!["structure", "center"], (0..10).map(|i| vec![i / 2, i % 2 + block_i]));
+            let properties = build_labels(vec!["n"], (0..3).map(|i| vec![i]));
+            blocks.push(TensorBlock::new(
+                TestArray::new(vec![10, 3]),
+                Arc::new(samples),
+                vec![],
+                Arc::new(properties),
+            ).unwrap());
+        }
+        let keys = build_labels(vec!["key"], (0..50).map(|i| vec![i]));
+        let tensor = TensorMap::new(keys, blocks).unwrap();
+
+        let mut plain = std::io::Cursor::new(Vec::new());
+        save(&mut plain, &tensor).unwrap();
+        let plain = plain.into_inner();
+
+        let mut compressed = std::io::Cursor::new(Vec::new());
+        let options = SaveOptions { labels_dictionary: true, ..Default::default() };
+        save_with_options(&mut compressed, &tensor, options).unwrap();
+        let compressed = compressed.into_inner();
+
+        assert!(compressed.len() < plain.len());
+
+        let archive = ZipArchive::new(std::io::Cursor::new(&compressed)).unwrap();
+        let names = archive.file_names().collect::<Vec<_>>();
+        assert!(names.contains(&"keys.npy.zst"));
+        assert!(names.contains(&"blocks/3/values/samples.npy.zst"));
+        assert!(!names.contains(&"blocks/3/values/samples.npy"));
+        assert!(names.contains(&"blocks/3/values/data.npy"));
+
+        let loaded = load(std::io::Cursor::new(&compressed), |shape| Ok(TestArray::new(shape))).unwrap();
+        assert_eq!(loaded.keys(), tensor.keys());
+        for (block, expected) in loaded.blocks().iter().zip(tensor.blocks()) {
+            assert_eq!(block.values().samples, expected.values().samples);
+            assert_eq!(block.values().properties, expected.values().properties);
+        }
+    }
+
+    #[test]
+    fn capabilities() {
+        let tensor = TensorMap::new(
+            build_labels(vec!["key"], std::iter::once(vec![0])),
+            vec![TensorBlock::new(
+                TestArray::new(vec![2, 1]),
+                Arc::new(build_labels(vec!["sample"], (0..2).map(|i| vec![i]))),
+                vec![],
+                Arc::new(Labels::scalar()),
+            ).unwrap()],
+        ).unwrap();
+
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        save(&mut buffer, &tensor).unwrap();
+        let archive = ZipArchive::new(std::io::Cursor::new(buffer.into_inner())).unwrap();
+        assert!(!archive.file_names().any(|name| name == CAPABILITIES));
+
+        let options = SaveOptions { labels_dictionary: true, mantissa_bits: Some(12) };
+        assert_eq!(options.capabilities(), [ZSTD_LABELS, ZSTD_DATA]);
+
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        save_with_options(&mut buffer, &tensor, options).unwrap();
+        let mut archive = ZipArchive::new(std::io::Cursor::new(buffer.into_inner())).unwrap();
+        let file = archive.by_name(CAPABILITIES).unwrap();
+        let capabilities = read_string_array(file, CAPABILITIES, &LoadLimits::default()).unwrap();
+        assert_eq!(capabilities, [ZSTD_LABELS, ZSTD_DATA]);
+
+        // files requiring unknown capabilities are rejected
+        let mut archive = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        archive.start_file(CAPABILITIES, zip::write::FileOptions::default()).unwrap();
+        write_string_array(&mut archive, &[ZSTD_DATA, "string-labels", "f32-data"]).unwrap();
+        let buffer = archive.finish().unwrap().into_inner();
+
+        let error = load(std::io::Cursor::new(buffer), |shape| Ok(TestArray::new(shape))).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "serialization format error: this file requires capabilities which are \
+            not supported by this version of equistore: string-labels, f32-data"
+        );
+    }
+
+    #[test]
+    fn mantissa_bits() {
+        let rounded = |value: f64, bits| round_mantissa(value, bits).to_bits();
+        assert_eq!(rounded(1.0 + 0.5 + 0.25, 1), 2.0_f64.to_bits());
+        assert_eq!(rounded(1.0 + 0.25, 1), 1.0_f64.to_bits());
+        assert_eq!(rounded(-1.0 - 0.75, 2), (-1.75_f64).to_bits());
+        assert_eq!(rounded(std::f64::consts::PI, 52), std::f64::consts::PI.to_bits());
+        assert!(round_mantissa(f64::NAN, 4).is_nan());
+        assert_eq!(rounded(f64::INFINITY, 4), f64::INFINITY.to_bits());
+
+        let data = (0..1000).map(|i| (f64::from(i) * 0.37).sin()).collect::<Vec<_>>();
+        let block = TensorBlock::new(
+            TestArray::from_data(vec![1000, 1], data.clone()),
+            Arc::new(build_labels(vec!["samples"], (0..1000).map(|i| vec![i]))),
+            vec![],
+            Arc::new(build_labels(vec!["properties"], std::iter::once(vec![0]))),
+        ).unwrap();
+        let tensor = TensorMap::new(build_labels(vec!["key"], std::iter::once(vec![0])), vec![block]).unwrap();
+
+        let mut plain = std::io::Cursor::new(Vec::new());
+        save(&mut plain, &tensor).unwrap();
+        let plain = plain.into_inner();
+
+        let mut rounded = std::io::Cursor::new(Vec::new());
+        let options = SaveOptions { mantissa_bits: Some(10), ..Default::default() };
+        save_with_options(&mut rounded, &tensor, options).unwrap();
+        let rounded = rounded.into_inner();
+
+        assert!(rounded.len() < plain.len());
+
+        let archive = ZipArchive::new(std::io::Cursor::new(&rounded)).unwrap();
+        assert!(archive.file_names().any(|name| name == "blocks/0/values/data.npy.zst"));
+
+        let loaded = load(std::io::Cursor::new(&rounded), |shape| Ok(TestArray::new(shape))).unwrap();
+        let loaded = loaded.blocks()[0].values().data.data().unwrap();
+        for (&value, &expected) in loaded.iter().zip(&data) {
+            assert!((value - expected).abs() <= expected.abs() * 2.0_f64.powi(-11));
+        }
+
+        let options = SaveOptions { mantissa_bits: Some(53), ..Default::default() };
+        let error = save_with_options(std::io::Cursor::new(Vec::new()), &tensor, options).unwrap_err();
+        assert_eq!(error.to_string(), "invalid parameter: mantissa_bits must be between 0 and 52, got 53");
+    }
+
+    #[test]
+    fn load_limits() {
+        let mut blocks = Vec::new();
+        for _ in 0..3 {
+            let samples = build_labels(vec!["structure"], (0..10).map(|i| vec![i]));
+            let properties = build_labels(vec!["n"], (0..3).map(|i| vec![i]));
+            blocks.push(TensorBlock::new(
+                TestArray::new(vec![10, 3]),
+                Arc::new(samples),
+                vec![],
+                Arc::new(properties),
+            ).unwrap());
+        }
+        let keys = build_labels(vec!["key"], (0..3).map(|i| vec![i]));
+        let tensor = TensorMap::new(keys, blocks).unwrap();
+
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        save(&mut buffer, &tensor).unwrap();
+        let buffer = buffer.into_inner();
+
+        let load = |limits| load_with_limits(
+            std::io::Cursor::new(&buffer), limits, |shape| Ok(TestArray::new(shape))
+        );
+
+        let limits = LoadLimits {
+            max_labels_entries: Some(10),
+            max_array_bytes: Some(240),
+            max_blocks: Some(3),
+        };
+        assert!(load(limits).is_ok());
+
+        let limits = LoadLimits { max_labels_entries: Some(9), ..Default::default() };
+        assert!(matches!(load(limits), Err(Error::LimitExceeded(_))));
+
+        let limits = LoadLimits { max_array_bytes: Some(239), ..Default::default() };
+        assert!(matches!(load(limits), Err(Error::LimitExceeded(_))));
+
+        let limits = LoadLimits { max_blocks: Some(2), ..Default::default() };
+        assert!(matches!(load(limits), Err(Error::LimitExceeded(_))));
+    }
+}
@@ -0,0 +1,139 @@
+use std::io::SeekFrom;
+
+/// Random access to serialized data, without a notion of current position.
+///
+/// This is a more restricted interface than `std::io::Read + std::io::Seek`,
+/// which can be implemented on top of remote storage (HTTP servers, object
+/// stores, *etc.*) where each read is a separate request for a range of
+/// bytes. When loading a `TensorMap` through this interface, only the ZIP
+/// central directory and the files that are actually needed are read.
+pub trait ReadAt {
+    /// Get the total size in bytes of the data
+    fn size(&self) -> std::io::Result<u64>;
+
+    /// Read data starting at `offset` into `buffer`, returning the number of
+    /// bytes read. This should only return `0` if `offset` is at or after the
+    /// end of the data, or if `buffer` is empty.
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> std::io::Result<usize>;
+}
+
+impl ReadAt for [u8] {
+    fn size(&self) -> std::io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let start = usize::try_from(offset).unwrap_or(usize::MAX).min(self.len());
+        let count = buffer.len().min(self.len() - start);
+        buffer[..count].copy_from_slice(&self[start..start + count]);
+        return Ok(count);
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for &T {
+    fn size(&self) -> std::io::Result<u64> {
+        (**self).size()
+    }
+
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> std::io::Result<usize> {
+        (**self).read_at(offset, buffer)
+    }
+}
+
+/// Adapter implementing `std::io::Read + std::io::Seek` on top of `ReadAt`, to
+/// be able to use the latter with [`super::load`].
+///
+/// Each call to `read` calls `ReadAt::read_at`, so this should usually be
+/// wrapped in a `std::io::BufReader` to reduce the number of requests.
+pub struct ReadAtReader<T> {
+    inner: T,
+    size: u64,
+    position: u64,
+}
+
+impl<T: ReadAt> ReadAtReader<T> {
+    /// Create a new `ReadAtReader` starting at the beginning of `inner`
+    pub fn new(inner: T) -> std::io::Result<ReadAtReader<T>> {
+        let size = inner.size()?;
+        return Ok(ReadAtReader {
+            inner: inner,
+            size: size,
+            position: 0,
+        });
+    }
+}
+
+impl<T: ReadAt> std::io::Read for ReadAtReader<T> {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        if self.position >= self.size {
+            return Ok(0);
+        }
+
+        let count = self.inner.read_at(self.position, buffer)?;
+        self.position += count as u64;
+        return Ok(count);
+    }
+}
+
+impl<T: ReadAt> std::io::Seek for ReadAtReader<T> {
+    fn seek(&mut self, position: SeekFrom) -> std::io::Result<u64> {
+        let (base, offset) = match position {
+            SeekFrom::Start(offset) => {
+                self.position = offset;
+                return Ok(offset);
+            }
+            SeekFrom::End(offset) => (self.size, offset),
+            SeekFrom::Current(offset) => (self.position, offset),
+        };
+
+        let new_position = if offset >= 0 {
+            base.checked_add(offset.unsigned_abs())
+        } else {
+            base.checked_sub(offset.unsigned_abs())
+        };
+
+        match new_position {
+            Some(new_position) => {
+                self.position = new_position;
+                return Ok(new_position);
+            }
+            None => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "invalid seek to a negative or overflowing position",
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Seek, SeekFrom};
+
+    use super::*;
+
+    #[test]
+    fn reader() {
+        let data = (0..100).collect::<Vec<u8>>();
+        let mut reader = ReadAtReader::new(&*data).unwrap();
+
+        let mut buffer = [0; 10];
+        reader.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        assert_eq!(reader.seek(SeekFrom::Current(5)).unwrap(), 15);
+        reader.read_exact(&mut buffer[..2]).unwrap();
+        assert_eq!(buffer[..2], [15, 16]);
+
+        assert_eq!(reader.seek(SeekFrom::End(-3)).unwrap(), 97);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [97, 98, 99]);
+
+        assert_eq!(reader.seek(SeekFrom::Start(200)).unwrap(), 200);
+        assert_eq!(reader.read(&mut buffer).unwrap(), 0);
+
+        assert!(reader.seek(SeekFrom::Current(-300)).is_err());
+    }
+}
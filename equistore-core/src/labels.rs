@@ -1,14 +1,122 @@
 #![allow(clippy::default_trait_access, clippy::module_name_repetitions)]
 
-use std::ffi::CString;
-use std::collections::{BTreeSet, HashMap};
-use std::collections::hash_map::Entry;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::ops::{Deref, Range};
 
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use hashbrown::{HashMap, hash_map::Entry};
+
+use once_cell::race::OnceBox;
 use smallvec::SmallVec;
 
 use crate::Error;
+#[cfg(feature = "std")]
 use crate::utils::ConstCString;
 
+/// Type used to store the names of the labels. With the standard library,
+/// names are stored as const C strings for easier integration with the C API.
+#[cfg(feature = "std")]
+type LabelName = ConstCString;
+#[cfg(not(feature = "std"))]
+type LabelName = String;
+
+/// Number of values stored inline in the keys of the positions hash map.
+/// Labels with more than this number of columns will store their keys on the
+/// heap. Gradient samples commonly have up to 5 or 6 columns, so we use 8 to
+/// keep all of them inline.
+///
+/// This is a constant instead of a parameter of `Labels` since it is part of
+/// the type of the hash map, and making it configurable would make `Labels`
+/// generic. Larger labels are fully supported, and only pay for one
+/// additional allocation per entry.
+const INLINE_KEY_SIZE: usize = 8;
+
+/// Key used in the hash map from label entries to positions
+#[derive(Clone)]
+struct LabelKey(SmallVec<[LabelValue; INLINE_KEY_SIZE]>);
+
+impl Borrow<LabelRow> for LabelKey {
+    fn borrow(&self) -> &LabelRow {
+        LabelRow::new(&self.0)
+    }
+}
+
+impl PartialEq for LabelKey {
+    fn eq(&self, other: &LabelKey) -> bool {
+        let this: &LabelRow = self.borrow();
+        this == other.borrow()
+    }
+}
+
+impl Eq for LabelKey {}
+
+impl Hash for LabelKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let row: &LabelRow = self.borrow();
+        row.hash(state);
+    }
+}
+
+/// A single entry in `Labels`, used to look up entries in the positions hash
+/// map without allocating a `LabelKey`.
+///
+/// Since the values are contiguous 32-bit integers, rows are hashed and
+/// compared as a single slice of bytes instead of value by value. This allows
+/// the hasher to process multiple values at once, and the comparison to use
+/// `memcmp`.
+#[repr(transparent)]
+struct LabelRow([LabelValue]);
+
+impl LabelRow {
+    fn new(values: &[LabelValue]) -> &LabelRow {
+        // SAFETY: LabelRow is repr(transparent) over [LabelValue]
+        unsafe { &*(values as *const [LabelValue] as *const LabelRow) }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        // SAFETY: LabelValue is repr(transparent) over i32, which does not
+        // contain any padding bytes
+        unsafe {
+            core::slice::from_raw_parts(self.0.as_ptr().cast::<u8>(), core::mem::size_of_val(&self.0))
+        }
+    }
+}
+
+impl PartialEq for LabelRow {
+    fn eq(&self, other: &LabelRow) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for LabelRow {}
+
+impl Hash for LabelRow {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // all rows in a given hash map have the same size, so there is no need
+        // to hash the length separately
+        state.write(self.as_bytes());
+    }
+}
+
+/// Lookup structure going from a label entry to its position in `Labels`
+#[derive(Clone)]
+enum Positions {
+    /// Hash map from entries to positions. This is the default, and gives the
+    /// fastest lookups.
+    Hash(HashMap<LabelKey, usize, ahash::RandomState>),
+    /// Positions of all entries, sorted in the lexicographic order of the
+    /// entries, for lookup with a binary search. This is created by
+    /// `Labels::freeze`, and uses a lot less memory than the hash map.
+    Sorted(Vec<usize>),
+}
+
 /// A single value inside a label. This is represented as a 32-bit signed
 /// integer, with a couple of helper function to get its value as usize/isize.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -27,14 +135,14 @@ impl PartialEq<LabelValue> for i32 {
     }
 }
 
-impl std::fmt::Debug for LabelValue {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for LabelValue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
-impl std::fmt::Display for LabelValue {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for LabelValue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
@@ -91,6 +199,24 @@ impl LabelValue {
     pub fn i32(self) -> i32 {
         self.0
     }
+
+    /// Reinterpret a slice of `i32` as a slice of `LabelValue`, without
+    /// copying the data
+    pub fn from_i32_slice(values: &[i32]) -> &[LabelValue] {
+        // SAFETY: LabelValue is repr(transparent) over i32
+        unsafe {
+            core::slice::from_raw_parts(values.as_ptr().cast::<LabelValue>(), values.len())
+        }
+    }
+
+    /// Reinterpret a slice of `LabelValue` as a slice of `i32`, without
+    /// copying the data
+    pub fn as_i32_slice(values: &[LabelValue]) -> &[i32] {
+        // SAFETY: LabelValue is repr(transparent) over i32
+        unsafe {
+            core::slice::from_raw_parts(values.as_ptr().cast::<i32>(), values.len())
+        }
+    }
 }
 
 /// Builder for `Labels`, this should be used to construct `Labels`.
@@ -98,7 +224,7 @@ pub struct LabelsBuilder {
     // cf `Labels` for the documentation of the fields
     names: Vec<String>,
     values: Vec<LabelValue>,
-    positions: HashMap<SmallVec<[LabelValue; 4]>, usize, ahash::RandomState>,
+    positions: HashMap<LabelKey, usize, ahash::RandomState>,
 }
 
 impl LabelsBuilder {
@@ -124,6 +250,17 @@ impl LabelsBuilder {
         self.positions.reserve(additional);
     }
 
+    /// Try to reserve space for `additional` other entries in the labels,
+    /// returning an error if the memory can not be allocated.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), Error> {
+        let values = additional.checked_mul(self.names.len()).ok_or_else(|| Error::OutOfMemory(
+            format!("can not allocate memory for {} entries in labels", additional)
+        ))?;
+        self.values.try_reserve(values)?;
+        self.positions.try_reserve(additional)?;
+        return Ok(());
+    }
+
     /// Get the number of labels in a single value
     pub fn size(&self) -> usize {
         self.names.len()
@@ -140,17 +277,13 @@ impl LabelsBuilder {
             entry.len(), self.size()
         );
 
-        let entry = entry.iter().copied().map(Into::into).collect::<SmallVec<_>>();
-        self.values.extend(&entry);
+        let entry = LabelKey(entry.iter().copied().map(Into::into).collect());
+        self.values.extend(&entry.0);
 
         let new_position = self.positions.len();
         match self.positions.entry(entry) {
             Entry::Occupied(entry) => {
-                let values_display = entry.key().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
-                return Err(Error::InvalidParameter(format!(
-                    "can not have the same label value multiple time: [{}] is already present at position {}",
-                    values_display, entry.get()
-                )));
+                return Err(duplicated_entry_error(&entry.key().0, *entry.get()));
             },
             Entry::Vacant(entry) => {
                 entry.insert(new_position);
@@ -160,29 +293,104 @@ impl LabelsBuilder {
         Ok(())
     }
 
+    /// Add multiple entries to this set of labels at once. `values` must
+    /// contain the entries one after the other, and its length must be a
+    /// multiple of `self.size()`.
+    ///
+    /// This is equivalent to calling `add` for each entry, but copies all the
+    /// values in a single operation, which is much faster for large labels.
+    /// If any entry is already present, an error is returned and no entry is
+    /// added.
+    pub fn add_all(&mut self, values: &[LabelValue]) -> Result<(), Error> {
+        let size = self.size();
+        if size == 0 {
+            // the only entry in labels without names is the empty one, use
+            // `add(&[])` to add it
+            assert!(values.is_empty(), "can not add values to labels without names");
+            return Ok(());
+        }
+
+        assert_eq!(
+            values.len() % size, 0,
+            "wrong size for added labels: got {} values, which is not a multiple of {}",
+            values.len(), size
+        );
+
+        let start = self.positions.len();
+        self.try_reserve(values.len() / size)?;
+        for (i, entry) in values.chunks_exact(size).enumerate() {
+            match self.positions.entry(LabelKey(SmallVec::from_slice(entry))) {
+                Entry::Occupied(entry) => {
+                    let error = duplicated_entry_error(&entry.key().0, *entry.get());
+                    // remove the entries added by this call before returning
+                    for entry in values.chunks_exact(size).take(i) {
+                        self.positions.remove(LabelRow::new(entry));
+                    }
+                    return Err(error);
+                },
+                Entry::Vacant(entry) => {
+                    entry.insert(start + i);
+                }
+            }
+        }
+
+        self.values.extend_from_slice(values);
+
+        Ok(())
+    }
+
+    /// Add a single `entry`, given as a slice of `i32`, to this set of labels.
+    ///
+    /// This is the same as `LabelsBuilder::add`, without converting the
+    /// values one by one.
+    pub fn add_i32_row(&mut self, entry: &[i32]) -> Result<(), Error> {
+        return self.add(LabelValue::from_i32_slice(entry));
+    }
+
+    /// Add multiple entries, given as a slice of `i32`, to this set of labels
+    /// at once. This is the same as `LabelsBuilder::add_all`.
+    pub fn add_all_i32(&mut self, values: &[i32]) -> Result<(), Error> {
+        return self.add_all(LabelValue::from_i32_slice(values));
+    }
+
     /// Finish building the `Labels`
     pub fn finish(self) -> Labels {
         if self.names.is_empty() {
+            // labels without names contain at most a single (empty) entry,
+            // tracked by the positions
             assert!(self.values.is_empty());
             return Labels {
                 names: Vec::new(),
-                values: Vec::new(),
-                positions: Default::default(),
+                values: LabelValues::Owned(Vec::new()),
+                positions: Positions::Hash(self.positions),
+                sorted_order: OnceBox::new(),
             }
         }
 
+        #[cfg(feature = "std")]
         let names = self.names.into_iter()
-            .map(|s| ConstCString::new(CString::new(s).expect("invalid C string")))
+            .map(|s| ConstCString::new(std::ffi::CString::new(s).expect("invalid C string")))
             .collect::<Vec<_>>();
+        #[cfg(not(feature = "std"))]
+        let names = self.names;
 
         return Labels {
             names: names,
-            values: self.values,
-            positions: self.positions,
+            values: LabelValues::Owned(self.values),
+            positions: Positions::Hash(self.positions),
+            sorted_order: OnceBox::new(),
         };
     }
 }
 
+fn duplicated_entry_error(entry: &[LabelValue], position: usize) -> Error {
+    let values_display = entry.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
+    return Error::InvalidParameter(format!(
+        "can not have the same label value multiple time: [{}] is already present at position {}",
+        values_display, position
+    ));
+}
+
 /// Check if the given name is a valid identifier, to be used as a
 /// column name in `Labels`.
 pub fn is_valid_label_name(name: &str) -> bool {
@@ -203,6 +411,40 @@ pub fn is_valid_label_name(name: &str) -> bool {
     return true;
 }
 
+/// Storage for the values of `Labels`, either owned by the labels or allocated
+/// in a buffer shared with other labels by [`LabelsArena::finish`].
+#[derive(Clone)]
+enum LabelValues {
+    Owned(Vec<LabelValue>),
+    Shared {
+        buffer: Arc<[LabelValue]>,
+        range: Range<usize>,
+    },
+}
+
+impl Deref for LabelValues {
+    type Target = [LabelValue];
+
+    fn deref(&self) -> &[LabelValue] {
+        match self {
+            LabelValues::Owned(values) => values,
+            LabelValues::Shared { buffer, range } => &buffer[range.clone()],
+        }
+    }
+}
+
+impl LabelValues {
+    /// Memory used by these values, in bytes. Shared values only count their
+    /// own part of the shared buffer.
+    fn memory_usage(&self) -> usize {
+        let count = match self {
+            LabelValues::Owned(values) => values.capacity(),
+            LabelValues::Shared { range, .. } => range.len(),
+        };
+        return count * core::mem::size_of::<LabelValue>();
+    }
+}
+
 /// A set of labels used to carry metadata associated with a tensor map.
 ///
 /// This is similar to a list of named tuples, but stored as a 2D array of shape
@@ -210,23 +452,40 @@ pub fn is_valid_label_name(name: &str) -> bool {
 /// columns of this array. Each row/entry in this array is unique, and they are
 /// often (but not always) sorted in  lexicographic order.
 ///
+/// Labels without any dimension can contain at most one (empty) entry. This is
+/// used for example as the properties of blocks containing scalar values,
+/// see [`Labels::scalar`].
+///
 /// The main way to construct a new set of labels is to use a `LabelsBuilder`.
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone)]
 pub struct Labels {
-    /// Names of the labels, stored as const C strings for easier integration
-    /// with the C API
-    names: Vec<ConstCString>,
+    /// Names of the labels
+    names: Vec<LabelName>,
     /// Values of the labels, as a linearized 2D array in row-major order
-    values: Vec<LabelValue>,
+    values: LabelValues,
     /// Store the position of all the known labels, for faster access later.
     /// This uses `XxHash64` instead of the default hasher in std since
     /// `XxHash64` is much faster and we don't need the cryptographic strength
-    /// hash from std.
-    positions: HashMap<SmallVec<[LabelValue; 4]>, usize, ahash::RandomState>,
+    /// hash from std. Frozen labels use a sorted index instead.
+    positions: Positions,
+    /// Positions of the entries in lexicographic order, computed on first use
+    /// by `Labels::sorted_order` for labels which are not frozen
+    sorted_order: OnceBox<Vec<usize>>,
+}
+
+impl PartialEq for Labels {
+    fn eq(&self, other: &Labels) -> bool {
+        // the positions are fully determined by the values, and do not need
+        // to be compared. The count is required for labels without names,
+        // which have no values.
+        self.names == other.names && *self.values == *other.values && self.count() == other.count()
+    }
 }
 
-impl std::fmt::Debug for Labels {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Eq for Labels {}
+
+impl core::fmt::Debug for Labels {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         writeln!(f, "Labels{{")?;
         writeln!(f, "    {}", self.names().join(", "))?;
 
@@ -245,6 +504,15 @@ impl std::fmt::Debug for Labels {
 }
 
 impl Labels {
+    /// Create labels without any dimension, containing a single empty entry.
+    /// This can be used as the properties of blocks storing a single scalar
+    /// value per sample (e.g. energies), instead of a dummy dimension.
+    pub fn scalar() -> Labels {
+        let mut builder = LabelsBuilder::new(Vec::new());
+        builder.add::<LabelValue>(&[]).expect("empty labels can contain an empty entry");
+        return builder.finish();
+    }
+
     /// Get the number of entries/named values in a single label
     pub fn size(&self) -> usize {
         self.names.len()
@@ -257,6 +525,7 @@ impl Labels {
 
     /// Get the names of the entries/columns in this set of labels as
     /// C-compatible (null terminated) strings
+    #[cfg(feature = "std")]
     pub fn c_names(&self) -> &[ConstCString] {
         &self.names
     }
@@ -264,7 +533,11 @@ impl Labels {
     /// Get the total number of entries in this set of labels
     pub fn count(&self) -> usize {
         if self.size() == 0 {
-            return 0;
+            // labels without dimensions can only contain the empty entry
+            return match self.positions {
+                Positions::Hash(ref positions) => positions.len(),
+                Positions::Sorted(ref order) => order.len(),
+            };
         } else {
             return self.values.len() / self.size();
         }
@@ -277,42 +550,301 @@ impl Labels {
 
     /// Check whether the given `label` is part of this set of labels
     pub fn contains(&self, label: &[LabelValue]) -> bool {
-        self.positions.contains_key(label)
+        if label.len() != self.size() {
+            return false;
+        }
+
+        match self.positions {
+            Positions::Hash(ref positions) => positions.contains_key(LabelRow::new(label)),
+            Positions::Sorted(_) => self.sorted_position(label).is_some(),
+        }
     }
 
     /// Get the position (i.e. row index) of the given label in the full labels
     /// array, or None.
+    ///
+    /// The lookup is done directly from the borrowed `value`, without
+    /// allocating a new key.
     pub fn position(&self, value: &[LabelValue]) -> Option<usize> {
         assert!(value.len() == self.size(), "invalid size of index in Labels::position");
 
-        self.positions.get(value).copied()
+        match self.positions {
+            Positions::Hash(ref positions) => positions.get(LabelRow::new(value)).copied(),
+            Positions::Sorted(_) => self.sorted_position(value),
+        }
+    }
+
+    /// Get the position of the given label, given as a slice of `i32`. This
+    /// is the same as `Labels::position`, without having to convert the values
+    /// to `LabelValue` first.
+    pub fn position_of_ints(&self, value: &[i32]) -> Option<usize> {
+        return self.position(LabelValue::from_i32_slice(value));
+    }
+
+    /// Look for the position of `value` with a binary search in the sorted
+    /// index of frozen labels
+    fn sorted_position(&self, value: &[LabelValue]) -> Option<usize> {
+        let order = match self.positions {
+            Positions::Sorted(ref order) => order,
+            Positions::Hash(_) => unreachable!("labels are not frozen"),
+        };
+
+        let size = self.size();
+        return order.binary_search_by(|&i| {
+            self.values[i * size..(i + 1) * size].cmp(value)
+        }).ok().map(|i| order[i]);
+    }
+
+    /// Get the positions of all entries of these labels, sorted in the
+    /// lexicographic order of the entries.
+    ///
+    /// The labels always keep the user-provided order of the entries, and this
+    /// gives an additional index for ordered queries (ranges of values,
+    /// prefix scans, *etc.*) using a binary search. The sorted order is
+    /// computed the first time this function is called, and then cached.
+    /// Frozen labels re-use their sorted index.
+    pub fn sorted_order(&self) -> &[usize] {
+        match self.positions {
+            Positions::Sorted(ref order) => order,
+            Positions::Hash(_) => self.sorted_order.get_or_init(|| Box::new(self.sort_entries())),
+        }
+    }
+
+    /// Get the positions of all entries starting with the given `prefix`
+    /// values, in the lexicographic order of the entries. The `prefix` can
+    /// contain values for any number of the first dimensions of these labels.
+    pub fn positions_with_prefix(&self, prefix: &[LabelValue]) -> &[usize] {
+        assert!(prefix.len() <= self.size(), "invalid size of prefix in Labels::positions_with_prefix");
+
+        let size = self.size();
+        let order = self.sorted_order();
+        let entry_prefix = |i: usize| &self.values[i * size..i * size + prefix.len()];
+
+        let start = order.partition_point(|&i| entry_prefix(i) < prefix);
+        let stop = start + order[start..].partition_point(|&i| entry_prefix(i) == prefix);
+        return &order[start..stop];
+    }
+
+    /// Get the positions of all entries where the value of the `column`
+    /// dimension is inside the given `range`, in increasing order.
+    ///
+    /// If these labels have a sorted index (because they are frozen, or
+    /// `Labels::sorted_order` was already called) and `column` is the first
+    /// dimension, the entries are found with a binary search. Otherwise, this
+    /// function scans all the entries.
+    pub fn entries_in_range<T>(&self, column: &str, range: core::ops::RangeInclusive<T>) -> Result<Vec<usize>, Error>
+        where T: Copy + Into<LabelValue>
+    {
+        let column_i = self.names.iter().position(|name| name.as_str() == column).ok_or_else(|| {
+            Error::InvalidParameter(format!(
+                "'{}' is not part of the dimensions of these labels", column
+            ))
+        })?;
+
+        let (min, max) = ((*range.start()).into(), (*range.end()).into());
+        let size = self.size();
+
+        let sorted_order = match self.positions {
+            Positions::Sorted(ref order) => Some(order),
+            Positions::Hash(_) => self.sorted_order.get(),
+        };
+
+        let mut positions = match sorted_order {
+            Some(order) if column_i == 0 => {
+                let start = order.partition_point(|&i| self.values[i * size] < min);
+                let stop = start + order[start..].partition_point(|&i| self.values[i * size] <= max);
+                order[start..stop].to_vec()
+            }
+            _ => {
+                return Ok((0..self.count()).filter(|&i| {
+                    let value = self.values[i * size + column_i];
+                    min <= value && value <= max
+                }).collect());
+            }
+        };
+
+        positions.sort_unstable();
+        return Ok(positions);
+    }
+
+    /// Get the unique combinations of values for the dimensions in `names`,
+    /// in the order of their first appearance in these labels, together with
+    /// the index of the combination (i.e. the group) of each entry.
+    ///
+    /// This is the basic building block for operations grouping entries
+    /// together, such as moving some dimensions of the keys to the samples,
+    /// or reductions over all entries sharing the same values.
+    pub fn unique_subset(&self, names: &[&str]) -> Result<(Labels, Vec<usize>), Error> {
+        let mut columns = Vec::new();
+        for (i, &name) in names.iter().enumerate() {
+            if names[..i].contains(&name) {
+                return Err(Error::InvalidParameter(format!(
+                    "'{}' is included multiple times in the dimensions", name
+                )));
+            }
+
+            let column = self.names.iter().position(|n| n.as_str() == name).ok_or_else(|| {
+                Error::InvalidParameter(format!(
+                    "'{}' is not part of the dimensions of these labels", name
+                ))
+            })?;
+            columns.push(column);
+        }
+
+        let mut groups = HashMap::<LabelKey, usize, ahash::RandomState>::default();
+        let mut builder = LabelsBuilder::new(names.to_vec());
+        let mut entry_groups = Vec::with_capacity(self.count());
+        for entry in self {
+            let subset = LabelKey(columns.iter().map(|&i| entry[i]).collect());
+            let n_groups = groups.len();
+            let group = match groups.entry(subset) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    builder.add(&entry.key().0)?;
+                    *entry.insert(n_groups)
+                }
+            };
+            entry_groups.push(group);
+        }
+
+        return Ok((builder.finish(), entry_groups));
+    }
+
+    /// Sort the positions of all entries in lexicographic order
+    fn sort_entries(&self) -> Vec<usize> {
+        let size = self.size();
+        let mut order = (0..self.count()).collect::<Vec<_>>();
+        order.sort_unstable_by(|&i, &j| {
+            self.values[i * size..(i + 1) * size].cmp(&self.values[j * size..(j + 1) * size])
+        });
+        return order;
+    }
+
+    /// Freeze these labels, replacing the hash map used for lookup with a
+    /// sorted index of the entries. Lookups in frozen labels use a binary
+    /// search, which is slower than the hash map, but the index only uses one
+    /// `usize` per entry, reducing the memory used by large labels by about a
+    /// factor 2. This is useful for labels which are created once and then
+    /// kept around for a long time.
+    ///
+    /// Calling this function on already frozen labels does nothing.
+    pub fn freeze(&mut self) {
+        if self.is_frozen() {
+            return;
+        }
+
+        let order = match self.sorted_order.get() {
+            Some(order) => order.clone(),
+            None => self.sort_entries(),
+        };
+
+        self.sorted_order = OnceBox::new();
+        self.positions = Positions::Sorted(order);
+    }
+
+    /// Check if these labels have been frozen with `Labels::freeze`
+    pub fn is_frozen(&self) -> bool {
+        matches!(self.positions, Positions::Sorted(_))
+    }
+
+    /// Estimate the memory used by this set of labels, in bytes. This returns
+    /// the memory used to store the values, and the memory used by the hash
+    /// map (or sorted index) for fast lookup of positions, including the
+    /// cached sorted order.
+    pub fn memory_usage(&self) -> (usize, usize) {
+        let values = self.values.memory_usage()
+            + self.names.iter().map(|name| name.as_str().len() + 1).sum::<usize>();
+
+        let positions = match self.positions {
+            Positions::Hash(ref positions) => {
+                // hashbrown stores one control byte per bucket in addition to
+                // the key/value pair
+                let entry_size = core::mem::size_of::<(LabelKey, usize)>() + 1;
+                let mut memory = positions.capacity() * entry_size;
+                if self.size() > INLINE_KEY_SIZE {
+                    // keys with more than INLINE_KEY_SIZE values are stored on
+                    // the heap
+                    memory += self.count() * self.size() * core::mem::size_of::<LabelValue>();
+                }
+                memory
+            }
+            Positions::Sorted(ref order) => order.capacity() * core::mem::size_of::<usize>(),
+        } + self.sorted_order.get().map_or(0, |order| order.capacity() * core::mem::size_of::<usize>());
+
+        return (values, positions);
+    }
+
+    /// Get the entry at position `i` in this set of labels, or `None` if `i`
+    /// is out of bounds.
+    pub fn get(&self, i: usize) -> Option<&[LabelValue]> {
+        if i >= self.count() {
+            return None;
+        }
+
+        let start = i * self.size();
+        let stop = (i + 1) * self.size();
+        return Some(&self.values[start..stop]);
+    }
+
+    /// Get a description of the entries of these labels as a numpy structured
+    /// dtype, in the format used by `__array_interface__["descr"]`. Each entry
+    /// is a record with one native-endian 32-bit integer field per dimension,
+    /// matching the memory layout of [`Labels::values_as_i32`].
+    pub fn structured_dtype(&self) -> String {
+        let type_str = if cfg!(target_endian = "little") { "<i4" } else { ">i4" };
+        let fields = self.names().iter()
+            .map(|name| format!("('{}', '{}')", name, type_str))
+            .collect::<Vec<_>>();
+        return format!("[{}]", fields.join(", "));
+    }
+
+    /// Get all the values in this set of labels as a single slice of `i32`,
+    /// containing the entries one after the other.
+    pub fn values_as_i32(&self) -> &[i32] {
+        return LabelValue::as_i32_slice(&self.values);
     }
 
     /// Iterate over the entries in this set of labels
-    pub fn iter(&self) -> Iter {
-        debug_assert!(self.values.len() % self.names.len() == 0);
+    pub fn iter(&self) -> Iter<'_> {
         return Iter {
-            chunks: self.values.chunks_exact(self.names.len())
+            values: &self.values,
+            size: self.size(),
+            current: 0,
+            count: self.count(),
         };
     }
 }
 
 /// iterator over `Labels` entries
 pub struct Iter<'a> {
-    chunks: std::slice::ChunksExact<'a, LabelValue>,
+    values: &'a [LabelValue],
+    size: usize,
+    current: usize,
+    count: usize,
 }
 
 impl<'a> Iterator for Iter<'a> {
     type Item = &'a [LabelValue];
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.chunks.next()
+        if self.current == self.count {
+            return None;
+        }
+
+        let start = self.current * self.size;
+        self.current += 1;
+        return Some(&self.values[start..(start + self.size)]);
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.current;
+        (remaining, Some(remaining))
     }
 }
 
-impl<'a> ExactSizeIterator for Iter<'a> {
+impl ExactSizeIterator for Iter<'_> {
     fn len(&self) -> usize {
-        self.chunks.len()
+        self.count - self.current
     }
 }
 
@@ -324,11 +856,426 @@ impl<'a> IntoIterator for &'a Labels {
     }
 }
 
-impl std::ops::Index<usize> for Labels {
+impl core::ops::Index<usize> for Labels {
     type Output = [LabelValue];
     fn index(&self, i: usize) -> &[LabelValue] {
-        let start = i * self.size();
-        let stop = (i + 1) * self.size();
-        &self.values[start..stop]
+        match self.get(i) {
+            Some(entry) => entry,
+            None => panic!(
+                "out of bounds access in Labels: the index is {} but there are only {} entries",
+                i, self.count()
+            ),
+        }
+    }
+}
+
+/// Pool of shared `Labels`, used to avoid allocating the same labels multiple
+/// times when creating a large number of small blocks.
+///
+/// Tensor maps with thousands of blocks usually share the same components
+/// and properties between blocks. Passing all labels through
+/// [`LabelsPool::intern`] gives back a single `Arc<Labels>` for all identical
+/// labels, keeping a single allocation alive for the values and positions of
+/// these labels.
+#[derive(Default)]
+pub struct LabelsPool {
+    labels: HashMap<u64, Vec<Arc<Labels>>, ahash::RandomState>,
+}
+
+impl LabelsPool {
+    /// Create a new empty `LabelsPool`
+    pub fn new() -> LabelsPool {
+        LabelsPool::default()
+    }
+
+    /// Get a shared version of `labels`, re-using existing labels from the
+    /// pool if the same labels were already interned.
+    pub fn intern(&mut self, labels: Labels) -> Arc<Labels> {
+        let hash = labels_hash(self.labels.hasher(), &labels);
+        let bucket = self.labels.entry(hash).or_default();
+        for existing in bucket.iter() {
+            if **existing == labels {
+                return Arc::clone(existing);
+            }
+        }
+
+        let labels = Arc::new(labels);
+        bucket.push(Arc::clone(&labels));
+        return labels;
+    }
+}
+
+/// Hash the names and values of `labels` with the given `hasher`
+fn labels_hash(hasher: &impl BuildHasher, labels: &Labels) -> u64 {
+    let mut hasher = hasher.build_hasher();
+    labels.names().hash(&mut hasher);
+    labels.values.hash(&mut hasher);
+    return hasher.finish();
+}
+
+/// Arena storing the values of many `Labels` in a single allocation.
+///
+/// Tensor maps with thousands of small blocks spend a lot of time and memory
+/// in the allocator for the values of their labels. Labels added to the arena
+/// with [`LabelsArena::add`] are interned (identical labels are only stored
+/// once), and [`LabelsArena::finish`] then copies the values of all the
+/// distinct labels to a single buffer, shared by all the resulting `Labels`.
+/// The positions of the entries are still stored separately for each set of
+/// labels.
+#[derive(Default)]
+pub struct LabelsArena {
+    labels: Vec<Labels>,
+    interned: HashMap<u64, Vec<usize>, ahash::RandomState>,
+}
+
+impl LabelsArena {
+    /// Create a new empty `LabelsArena`
+    pub fn new() -> LabelsArena {
+        LabelsArena::default()
+    }
+
+    /// Add `labels` to this arena, and get the index of the corresponding
+    /// labels in the output of [`LabelsArena::finish`]. Labels identical to
+    /// labels already in the arena get the same index.
+    pub fn add(&mut self, labels: &Labels) -> usize {
+        let hash = labels_hash(self.interned.hasher(), labels);
+        let bucket = self.interned.entry(hash).or_default();
+        for &index in bucket.iter() {
+            if self.labels[index] == *labels {
+                return index;
+            }
+        }
+
+        let index = self.labels.len();
+        self.labels.push(labels.clone());
+        bucket.push(index);
+        return index;
+    }
+
+    /// Move the values of all the labels in this arena to a single buffer,
+    /// and get the corresponding labels, in the order of their index.
+    pub fn finish(self) -> Vec<Arc<Labels>> {
+        let size = self.labels.iter().map(|labels| labels.values.len()).sum();
+        let mut buffer = Vec::with_capacity(size);
+        let mut ranges = Vec::with_capacity(self.labels.len());
+        for labels in &self.labels {
+            let start = buffer.len();
+            buffer.extend_from_slice(&labels.values);
+            ranges.push(start..buffer.len());
+        }
+
+        let buffer: Arc<[LabelValue]> = buffer.into();
+        return self.labels.into_iter().zip(ranges).map(|(mut labels, range)| {
+            labels.values = LabelValues::Shared { buffer: Arc::clone(&buffer), range };
+            Arc::new(labels)
+        }).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn labels(values: &[[i32; 2]]) -> Labels {
+        let mut builder = LabelsBuilder::new(vec!["a", "b"]);
+        for entry in values {
+            builder.add(entry).unwrap();
+        }
+        return builder.finish();
+    }
+
+    #[test]
+    fn sorted_order() {
+        let mut labels = labels(&[[2, 0], [0, 3], [1, 1], [0, 1], [2, -1]]);
+        assert_eq!(labels.sorted_order(), [3, 1, 2, 4, 0]);
+
+        // the user-provided order is unchanged
+        assert_eq!(labels[0], [2, 0]);
+        assert_eq!(labels.position_of_ints(&[0, 1]), Some(3));
+
+        assert_eq!(labels.positions_with_prefix(&[LabelValue::new(0)]), [3, 1]);
+        assert_eq!(labels.positions_with_prefix(&[LabelValue::new(2)]), [4, 0]);
+        assert_eq!(labels.positions_with_prefix(&[LabelValue::new(5)]), [] as [usize; 0]);
+        assert_eq!(labels.positions_with_prefix(&[LabelValue::new(1), LabelValue::new(1)]), [2]);
+        assert_eq!(labels.positions_with_prefix(&[]).len(), 5);
+
+        labels.freeze();
+        assert_eq!(labels.sorted_order(), [3, 1, 2, 4, 0]);
+        assert_eq!(labels.positions_with_prefix(&[LabelValue::new(0)]), [3, 1]);
+    }
+
+    #[test]
+    fn entries_in_range() {
+        let mut labels = labels(&[[2, 0], [0, 3], [1, 1], [0, 1], [2, -1], [4, 2]]);
+
+        // without a sorted index
+        assert_eq!(labels.entries_in_range("a", 0..=1).unwrap(), [1, 2, 3]);
+        assert_eq!(labels.entries_in_range("b", 0..=2).unwrap(), [0, 2, 3, 5]);
+        assert_eq!(labels.entries_in_range("a", 5..=8).unwrap(), []);
+
+        // with a sorted index
+        labels.sorted_order();
+        assert_eq!(labels.entries_in_range("a", 0..=1).unwrap(), [1, 2, 3]);
+        assert_eq!(labels.entries_in_range("a", 1..=3).unwrap(), [0, 2, 4]);
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = 3..=1;
+        assert_eq!(labels.entries_in_range("a", empty).unwrap(), []);
+        assert_eq!(labels.entries_in_range("b", 0..=2).unwrap(), [0, 2, 3, 5]);
+
+        labels.freeze();
+        assert_eq!(labels.entries_in_range("a", -5..=2).unwrap(), [0, 1, 2, 3, 4]);
+
+        let error = labels.entries_in_range("c", 0..=1).unwrap_err();
+        assert_eq!(error.to_string(), "invalid parameter: 'c' is not part of the dimensions of these labels");
+    }
+
+    #[test]
+    fn unique_subset() {
+        let labels = labels(&[[2, 0], [0, 3], [2, 1], [0, 2], [1, 0]]);
+
+        let (unique, groups) = labels.unique_subset(&["a"]).unwrap();
+        assert_eq!(unique.names(), ["a"]);
+        assert_eq!(unique.values_as_i32(), [2, 0, 1]);
+        assert_eq!(groups, [0, 1, 0, 1, 2]);
+
+        let (unique, groups) = labels.unique_subset(&["b"]).unwrap();
+        assert_eq!(unique.values_as_i32(), [0, 3, 1, 2]);
+        assert_eq!(groups, [0, 1, 2, 3, 0]);
+
+        let (unique, groups) = labels.unique_subset(&["b", "a"]).unwrap();
+        assert_eq!(unique.names(), ["b", "a"]);
+        assert_eq!(unique.values_as_i32(), [0, 2, 3, 0, 1, 2, 2, 0, 0, 1]);
+        assert_eq!(groups, [0, 1, 2, 3, 4]);
+
+        let (unique, groups) = labels.unique_subset(&[]).unwrap();
+        assert_eq!(unique.size(), 0);
+        assert_eq!(unique.count(), 1);
+        assert_eq!(groups, [0, 0, 0, 0, 0]);
+
+        let error = labels.unique_subset(&["a", "a"]).unwrap_err();
+        assert_eq!(error.to_string(), "invalid parameter: 'a' is included multiple times in the dimensions");
+
+        let error = labels.unique_subset(&["c"]).unwrap_err();
+        assert_eq!(error.to_string(), "invalid parameter: 'c' is not part of the dimensions of these labels");
+    }
+
+    #[test]
+    fn add_all() {
+        let mut builder = LabelsBuilder::new(vec!["a", "b"]);
+        builder.add(&[0, 1]).unwrap();
+
+        let values = [2, 3, 4, 5].map(LabelValue::new);
+        builder.add_all(&values).unwrap();
+
+        // nothing is added if one of the entries is already present
+        let values = [6, 7, 0, 1].map(LabelValue::new);
+        let error = builder.add_all(&values).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: can not have the same label value multiple time: \
+            [0, 1] is already present at position 0"
+        );
+        builder.add(&[6, 7]).unwrap();
+
+        let labels = builder.finish();
+        assert_eq!(labels.count(), 4);
+        assert_eq!(labels.position(&[LabelValue::new(4), LabelValue::new(5)]), Some(2));
+        assert_eq!(labels.position(&[LabelValue::new(6), LabelValue::new(7)]), Some(3));
+    }
+
+    #[test]
+    fn try_reserve() {
+        let mut builder = LabelsBuilder::new(vec!["a", "b"]);
+        builder.try_reserve(10).unwrap();
+
+        let error = builder.try_reserve(usize::MAX).unwrap_err();
+        assert!(matches!(error, Error::OutOfMemory(_)));
+        assert_eq!(error.to_string(), format!("out of memory: can not allocate memory for {} entries in labels", usize::MAX));
+
+        let mut builder = LabelsBuilder::new(vec!["a"]);
+        let error = builder.try_reserve(usize::MAX / 2).unwrap_err();
+        assert!(matches!(error, Error::OutOfMemory(_)));
+    }
+
+    #[test]
+    fn i32_accessors() {
+        let values = [1, -2, 3, i32::MIN, i32::MAX, 0];
+        let label_values = LabelValue::from_i32_slice(&values);
+        assert_eq!(label_values.len(), 6);
+        assert_eq!(label_values[1], LabelValue::new(-2));
+        assert_eq!(label_values[3].i32(), i32::MIN);
+        // no copy is made in either direction
+        assert_eq!(label_values.as_ptr().cast::<i32>(), values.as_ptr());
+        assert_eq!(LabelValue::as_i32_slice(label_values).as_ptr(), values.as_ptr());
+        assert!(LabelValue::from_i32_slice(&[]).is_empty());
+
+        let mut builder = LabelsBuilder::new(vec!["a", "b"]);
+        builder.add_i32_row(&[4, 5]).unwrap();
+        builder.add_all_i32(&values).unwrap();
+        builder.add_all_i32(&[]).unwrap();
+
+        // duplicated entries are rejected without adding any value
+        let error = builder.add_all_i32(&[6, 7, 1, -2]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: can not have the same label value multiple time: \
+            [1, -2] is already present at position 1"
+        );
+
+        let labels = builder.finish();
+        assert_eq!(labels.count(), 4);
+        assert_eq!(labels.values_as_i32(), [4, 5, 1, -2, 3, i32::MIN, i32::MAX, 0]);
+
+        assert_eq!(labels.position_of_ints(&[4, 5]), Some(0));
+        assert_eq!(labels.position_of_ints(&[3, i32::MIN]), Some(2));
+        assert_eq!(labels.position_of_ints(&[i32::MAX, 0]), Some(3));
+        assert_eq!(labels.position_of_ints(&[5, 4]), None);
+        assert_eq!(
+            labels.position_of_ints(&[1, -2]),
+            labels.position(&[LabelValue::new(1), LabelValue::new(-2)]),
+        );
+    }
+
+    #[test]
+    fn label_rows() {
+        let values = [LabelValue::new(1), LabelValue::new(-2), LabelValue::new(0)];
+
+        // rows are compared as bytes, including the length
+        assert!(LabelRow::new(&values[..2]) == LabelRow::new(&[LabelValue::new(1), LabelValue::new(-2)]));
+        assert!(LabelRow::new(&values[..2]) != LabelRow::new(&values));
+        assert!(LabelRow::new(&values[..1]) != LabelRow::new(&[LabelValue::new(-1)]));
+        assert!(LabelRow::new(&[]) == LabelRow::new(&values[..0]));
+
+        // keys hash and compare like the borrowed rows, so they can be looked
+        // up in the hash map from rows
+        let key = LabelKey(values.iter().copied().collect());
+        assert!(key == LabelKey(SmallVec::from_slice(&values)));
+
+        let hasher = ahash::RandomState::with_seeds(1, 2, 3, 4);
+        let mut key_state = hasher.build_hasher();
+        key.hash(&mut key_state);
+        let mut row_state = hasher.build_hasher();
+        LabelRow::new(&values).hash(&mut row_state);
+        assert_eq!(key_state.finish(), row_state.finish());
+    }
+
+    #[test]
+    fn negative_values_lookup() {
+        let mut labels = labels(&[[-1, 0], [0, -1], [-2, -3], [i32::MIN, i32::MAX]]);
+        assert_eq!(labels.position_of_ints(&[-1, 0]), Some(0));
+        assert_eq!(labels.position_of_ints(&[0, -1]), Some(1));
+        assert_eq!(labels.position_of_ints(&[-2, -3]), Some(2));
+        assert_eq!(labels.position_of_ints(&[i32::MIN, i32::MAX]), Some(3));
+        assert_eq!(labels.position_of_ints(&[i32::MAX, i32::MIN]), None);
+        assert_eq!(labels.position_of_ints(&[-3, -2]), None);
+
+        // frozen labels find the same positions with a binary search, even if
+        // the byte representation of negative values is not sorted
+        assert!(!labels.is_frozen());
+        let hash_positions = labels.iter().map(|entry| labels.position(entry)).collect::<Vec<_>>();
+        labels.freeze();
+        assert!(labels.is_frozen());
+        let frozen_positions = labels.iter().map(|entry| labels.position(entry)).collect::<Vec<_>>();
+        assert_eq!(hash_positions, frozen_positions);
+        assert_eq!(labels.position_of_ints(&[i32::MAX, i32::MIN]), None);
+        assert_eq!(labels.position_of_ints(&[-3, -2]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid size of index in Labels::position")]
+    fn position_wrong_length() {
+        let labels = labels(&[[0, 1], [1, 0]]);
+        labels.position_of_ints(&[0, 1, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid size of index in Labels::position")]
+    fn frozen_position_wrong_length() {
+        let mut labels = labels(&[[0, 1], [1, 0]]);
+        labels.freeze();
+        labels.position_of_ints(&[0]);
+    }
+
+    #[test]
+    fn wide_labels() {
+        // labels with more columns than INLINE_KEY_SIZE store their keys on
+        // the heap
+        let entry = |i: i32| (0..10).map(|j| LabelValue::new(i * j)).collect::<Vec<_>>();
+        let names = (0..10).map(|i| format!("dim_{}", i)).collect::<Vec<_>>();
+        let mut builder = LabelsBuilder::new(names.iter().map(|n| &**n).collect());
+        for i in 0..10 {
+            builder.add(&entry(i)).unwrap();
+        }
+        let mut labels = builder.finish();
+        assert!(labels.size() > INLINE_KEY_SIZE);
+        assert_eq!(labels.count(), 10);
+
+        for (position, i) in (0..10).enumerate() {
+            assert_eq!(labels.position(&entry(i)), Some(position));
+        }
+        assert_eq!(labels.position(&[LabelValue::new(-1); 10]), None);
+
+        // the heap-allocated keys are included in the memory usage
+        let (_, positions) = labels.memory_usage();
+        assert!(positions >= labels.count() * labels.size() * core::mem::size_of::<LabelValue>());
+
+        labels.freeze();
+        assert_eq!(labels.position(&entry(3)), Some(3));
+        assert_eq!(labels.position(&[LabelValue::new(-1); 10]), None);
+    }
+
+    #[test]
+    fn pool() {
+        // this test also runs without the standard library, with
+        // `cargo test --lib --no-default-features`
+        let mut pool = LabelsPool::new();
+        let first = pool.intern(labels(&[[2, 0], [0, 3]]));
+        let second = pool.intern(labels(&[[1, 1]]));
+        let third = pool.intern(labels(&[[2, 0], [0, 3]]));
+
+        assert!(Arc::ptr_eq(&first, &third));
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert_eq!(first.names(), ["a", "b"]);
+        assert_eq!(third.position(&[LabelValue::new(0), LabelValue::new(3)]), Some(1));
+
+        let mut builder = LabelsBuilder::new(vec!["a"]);
+        builder.add(&[1]).unwrap();
+        let error = builder.add(&[1]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: can not have the same label value multiple time: \
+            [1] is already present at position 0"
+        );
+    }
+
+    #[test]
+    fn arena() {
+        let first = labels(&[[2, 0], [0, 3]]);
+        let second = labels(&[[1, 1]]);
+        let empty = LabelsBuilder::new(vec![]).finish();
+
+        let mut arena = LabelsArena::new();
+        assert_eq!(arena.add(&first), 0);
+        assert_eq!(arena.add(&second), 1);
+        // identical labels are interned
+        assert_eq!(arena.add(&labels(&[[2, 0], [0, 3]])), 0);
+        assert_eq!(arena.add(&empty), 2);
+
+        let compacted = arena.finish();
+        assert_eq!(compacted.len(), 3);
+        assert_eq!(*compacted[0], first);
+        assert_eq!(*compacted[1], second);
+        assert_eq!(*compacted[2], empty);
+
+        // all the values are stored in the same buffer
+        let first_values = compacted[0][1].as_ptr_range();
+        let second_values = compacted[1][0].as_ptr_range();
+        assert_eq!(first_values.end, second_values.start);
+
+        // lookups still work with the shared values
+        assert_eq!(compacted[0].position(&[LabelValue::new(0), LabelValue::new(3)]), Some(1));
+        assert_eq!(compacted[0].memory_usage().0, first.memory_usage().0);
     }
 }
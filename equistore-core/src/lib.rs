@@ -7,28 +7,95 @@
 #![allow(clippy::missing_errors_doc, clippy::missing_panics_doc, clippy::missing_safety_doc)]
 #![allow(clippy::similar_names, clippy::borrow_as_ptr, clippy::uninlined_format_args)]
 
+// Without the `std` feature, only `Labels` are available, for use in `no_std`
+// environments with an allocator.
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
 
+use alloc::string::{String, ToString};
+
+#[cfg(feature = "std")]
 mod utils;
 
 mod labels;
-use self::labels::{LabelsBuilder, LabelValue, Labels};
-
+#[cfg(feature = "std")]
+use self::labels::{LabelsBuilder, LabelValue, Labels, LabelsPool, LabelsArena};
+// without the standard library, the C API is not available and the labels
+// are used directly from Rust
+#[cfg(not(feature = "std"))]
+pub use self::labels::{LabelsBuilder, LabelValue, Labels, LabelsPool, LabelsArena};
+
+#[cfg(feature = "std")]
 mod data;
+#[cfg(feature = "std")]
 use self::data::{eqs_array_t, eqs_sample_mapping_t, eqs_data_origin_t};
+#[cfg(feature = "std")]
 use self::data::{register_data_origin, get_data_origin};
+#[cfg(feature = "std")]
+use self::data::{register_array_backend, array_from_backend};
 
+#[cfg(feature = "std")]
 mod blocks;
+#[cfg(feature = "std")]
 use self::blocks::{BasicBlock, TensorBlock};
 
+#[cfg(feature = "std")]
+mod gradients;
+#[cfg(feature = "std")]
+use self::gradients::register_gradient_parameter;
+
+#[cfg(feature = "std")]
 mod tensor;
+#[cfg(feature = "std")]
 use self::tensor::TensorMap;
 
+#[cfg(feature = "std")]
 #[doc(hidden)]
 mod c_api;
+#[cfg(feature = "std")]
 use c_api::eqs_status_t;
 
+#[cfg(feature = "std")]
 mod io;
 
+#[cfg(feature = "std")]
+mod progress;
+#[cfg(feature = "std")]
+use self::progress::Progress;
+
+#[cfg(feature = "std")]
+mod warnings;
+
+#[cfg(fuzzing)]
+pub mod fuzzing;
+
+// Internal types used by the benchmarks in `equistore-core/benches`. This is
+// not part of the public API of this crate.
+#[cfg(feature = "benchmarks")]
+#[doc(hidden)]
+pub mod internals {
+    pub use crate::labels::{Labels, LabelsBuilder};
+    pub use crate::blocks::TensorBlock;
+    pub use crate::tensor::TensorMap;
+    pub use crate::data::TestArray;
+    pub use crate::io::{save, load};
+}
+
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod cli;
+
+// The main data structures are immutable unless accessed through a `&mut`
+// reference, and can be used from multiple threads at the same time. The C API
+// relies on this to allow concurrent calls on the same object.
+#[cfg(feature = "std")]
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Labels>();
+    assert_send_sync::<TensorBlock>();
+    assert_send_sync::<TensorMap>();
+};
+
 /// The possible sources of error in equistore
 #[derive(Debug)]
 pub enum Error {
@@ -37,10 +104,23 @@ pub enum Error {
     /// A buffer passed to a C API function does not have the right size
     BufferSize(String),
     /// I/O error when loading/writing `TensorMap` to a file
+    #[cfg(feature = "std")]
     Io(std::io::Error),
     /// Serialization format error when loading/writing `TensorMap` to a file
     Serialization(String),
+    /// Failed to allocate memory
+    OutOfMemory(String),
+    /// Some data exceeded one of the limits set when loading a `TensorMap`
+    LimitExceeded(String),
+    /// The operation was cancelled by the user before completion
+    Cancelled,
+    /// An index is out of bounds for a collection of `count` elements
+    OutOfBounds {
+        index: usize,
+        count: usize,
+    },
     /// External error, coming from a function used as a callback in `eqs_array_t`
+    #[cfg(feature = "std")]
     External {
         status: eqs_status_t,
         context: String,
@@ -49,24 +129,35 @@ pub enum Error {
     Internal(String),
 }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Error::InvalidParameter(e) => write!(f, "invalid parameter: {}", e),
+            #[cfg(feature = "std")]
             Error::Io(e) => write!(f, "io error: {}", e),
             Error::Serialization(e) => write!(f, "serialization format error: {}", e),
+            Error::OutOfMemory(e) => write!(f, "out of memory: {}", e),
+            Error::LimitExceeded(e) => write!(f, "limit exceeded: {}", e),
+            Error::Cancelled => write!(f, "the operation was cancelled"),
+            Error::OutOfBounds { index, count } => write!(f, "out of bounds: the index is {} but there are only {} entries", index, count),
             Error::BufferSize(e) => write!(f, "buffer is not big enough: {}", e),
+            #[cfg(feature = "std")]
             Error::External { status, context } => write!(f, "external error: {} (status {})", context, status.as_i32()),
             Error::Internal(e) => write!(f, "internal error (this is likely a bug, please report it): {}", e),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Error::InvalidParameter(_) |
             Error::Serialization(_) |
+            Error::OutOfMemory(_) |
+            Error::LimitExceeded(_) |
+            Error::Cancelled |
+            Error::OutOfBounds {..} |
             Error::Internal(_) |
             Error::BufferSize(_) |
             Error::External {..} => None,
@@ -75,12 +166,26 @@ impl std::error::Error for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for Error {
     fn from(error: std::io::Error) -> Self {
         Error::Io(error)
     }
 }
 
+impl From<alloc::collections::TryReserveError> for Error {
+    fn from(error: alloc::collections::TryReserveError) -> Self {
+        Error::OutOfMemory(error.to_string())
+    }
+}
+
+impl From<hashbrown::TryReserveError> for Error {
+    fn from(error: hashbrown::TryReserveError) -> Self {
+        Error::OutOfMemory(alloc::format!("{:?}", error))
+    }
+}
+
+#[cfg(feature = "std")]
 impl From<(String, zip::result::ZipError)> for Error {
     fn from((path, error): (String, zip::result::ZipError)) -> Self {
         match error {
@@ -91,6 +196,7 @@ impl From<(String, zip::result::ZipError)> for Error {
 }
 
 // Box<dyn Any + Send + 'static> is the error type in std::panic::catch_unwind
+#[cfg(feature = "std")]
 impl From<Box<dyn std::any::Any + Send + 'static>> for Error {
     fn from(error: Box<dyn std::any::Any + Send + 'static>) -> Error {
         let message = if let Some(message) = error.downcast_ref::<String>() {
@@ -0,0 +1,56 @@
+use crate::Error;
+
+/// Reporting of the progress of long-running operations (serialization and
+/// keys restructuring), as a number of blocks processed out of the total number
+/// of blocks; and cooperative cancellation of these operations.
+#[derive(Clone, Copy, Default)]
+pub struct Progress<'a> {
+    callback: Option<&'a dyn Fn(usize, usize)>,
+    cancelled: Option<&'a dyn Fn() -> bool>,
+}
+
+impl std::fmt::Debug for Progress<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Progress")
+            .field("callback", &self.callback.map(|_| "<callback>"))
+            .field("cancelled", &self.cancelled.map(|_| "<callback>"))
+            .finish()
+    }
+}
+
+impl<'a> Progress<'a> {
+    /// Do not report progress
+    pub fn none() -> Progress<'static> {
+        Progress { callback: None, cancelled: None }
+    }
+
+    /// Report progress by calling `callback` with the number of blocks
+    /// processed so far and the total number of blocks, after each block.
+    pub fn new(callback: &'a dyn Fn(usize, usize)) -> Progress<'a> {
+        Progress { callback: Some(callback), cancelled: None }
+    }
+
+    /// Cancel the operation with `Error::Cancelled` if `cancelled` returns
+    /// `true` when called between two blocks.
+    pub fn with_cancellation(self, cancelled: &'a dyn Fn() -> bool) -> Progress<'a> {
+        Progress { callback: self.callback, cancelled: Some(cancelled) }
+    }
+
+    /// Report that `done` blocks out of `total` have been processed
+    pub fn report(&self, done: usize, total: usize) {
+        if let Some(callback) = self.callback {
+            callback(done, total);
+        }
+    }
+
+    /// Check if the operation should be cancelled, returning
+    /// `Error::Cancelled` if it is the case.
+    pub fn check(&self) -> Result<(), Error> {
+        if let Some(cancelled) = self.cancelled {
+            if cancelled() {
+                return Err(Error::Cancelled);
+            }
+        }
+        return Ok(());
+    }
+}
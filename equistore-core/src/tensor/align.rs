@@ -0,0 +1,277 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use crate::labels::{Labels, LabelsBuilder};
+use crate::{Error, BasicBlock, TensorBlock, eqs_array_t};
+
+use super::{TensorMap, zip_maps};
+
+/// Expand all the blocks in all the `tensors` to use the same set of
+/// properties, i.e. the union of the properties of all blocks. Entries
+/// corresponding to properties missing in a block are filled with zeros.
+///
+/// The resulting tensor maps (one for each of the input `tensors`) can then be
+/// directly summed or concatenated along samples. The union of properties is
+/// sorted in lexicographic order. All blocks must have the same property names,
+/// and this function requires access to the data through `eqs_array_t.data`.
+pub fn align_properties(tensors: &[&TensorMap]) -> Result<Vec<TensorMap>, Error> {
+    let mut names = None;
+    let mut union = BTreeSet::new();
+    for tensor in tensors {
+        for block in tensor.blocks() {
+            let properties = &block.values().properties;
+            let expected = names.get_or_insert_with(|| properties.names());
+            if properties.names() != *expected {
+                return Err(Error::InvalidParameter(format!(
+                    "can not align properties with different names: got [{}] and [{}]",
+                    properties.names().join(", "), expected.join(", ")
+                )));
+            }
+
+            for entry in properties.iter() {
+                union.insert(entry.to_vec());
+            }
+        }
+    }
+
+    let names = match names {
+        Some(names) => names,
+        None => {
+            // no blocks, nothing to align
+            return tensors.iter().map(|tensor| tensor.try_clone()).collect();
+        }
+    };
+
+    let mut builder = LabelsBuilder::new(names);
+    builder.reserve(union.len());
+    for entry in union {
+        builder.add(&entry)?;
+    }
+    let properties = Arc::new(builder.finish());
+
+    let mut result = Vec::with_capacity(tensors.len());
+    for tensor in tensors {
+        let mut blocks = Vec::with_capacity(tensor.blocks().len());
+        for block in tensor.blocks() {
+            blocks.push(expand_block_properties(block, &properties)?);
+        }
+        let mut aligned = TensorMap::new((**tensor.keys()).clone(), blocks)?;
+        aligned.copy_metadata_from(tensor);
+        result.push(aligned);
+    }
+
+    return Ok(result);
+}
+
+fn expand_block_properties(block: &TensorBlock, properties: &Arc<Labels>) -> Result<TensorBlock, Error> {
+    let values = block.values();
+    if values.properties == *properties {
+        return block.try_clone();
+    }
+
+    let positions = values.properties.iter()
+        .map(|entry| properties.position(entry).expect("missing property in the union"))
+        .collect::<Vec<_>>();
+
+    let mut new_block = TensorBlock::new(
+        expand_properties(values, &positions, properties.count())?,
+        Arc::clone(&values.samples),
+        values.components.to_vec(),
+        Arc::clone(properties),
+    )?;
+
+    let mut parameters = block.gradients().keys().collect::<Vec<_>>();
+    parameters.sort_unstable();
+    for parameter in parameters {
+        let gradient = &block.gradients()[parameter];
+        new_block.add_gradient(
+            parameter,
+            expand_properties(gradient, &positions, properties.count())?,
+            Arc::clone(&gradient.samples),
+            gradient.components.to_vec(),
+        )?;
+    }
+
+    new_block.copy_units_from(block);
+
+    return Ok(new_block);
+}
+
+/// Create a new array with `n_properties` properties, where property `p` of
+/// `block.data` is stored at `positions[p]`, and everything else is zero.
+fn expand_properties(block: &BasicBlock, positions: &[usize], n_properties: usize) -> Result<eqs_array_t, Error> {
+    let shape = block.data.shape()?;
+    let old_properties = shape[shape.len() - 1];
+    debug_assert_eq!(old_properties, positions.len());
+
+    let mut new_shape = shape.to_vec();
+    let property_axis = new_shape.len() - 1;
+    new_shape[property_axis] = n_properties;
+
+    let mut new_array = block.data.create(&new_shape)?;
+    if old_properties == 0 {
+        return Ok(new_array);
+    }
+
+    let input = block.data.contiguous_data()?;
+    let output = new_array.data_mut()?;
+
+    for (input_row, output_row) in input.chunks_exact(old_properties).zip(output.chunks_exact_mut(n_properties)) {
+        for (&value, &position) in input_row.iter().zip(positions) {
+            output_row[position] = value;
+        }
+    }
+
+    return Ok(new_array);
+}
+
+/// Positions of the samples kept by [`intersect_samples`] for a single pair of
+/// blocks, in the original blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SamplesIntersection {
+    /// positions of the kept samples in the block from the first tensor map
+    pub first: Vec<usize>,
+    /// positions of the kept samples in the block from the second tensor map
+    pub second: Vec<usize>,
+}
+
+/// Restrict both `first` and `second` to the samples they have in common.
+///
+/// Blocks are matched by key, and both tensor maps must contain the same set of
+/// keys. For each pair of blocks, only the samples present in both blocks are
+/// kept, in the order they appear in the block from `first`; and gradients are
+/// updated accordingly.
+///
+/// This returns the two restricted tensor maps (with keys in the same order as
+/// `first`), and for each key the positions of the kept samples in the original
+/// blocks.
+pub fn intersect_samples(
+    first: &TensorMap,
+    second: &TensorMap,
+) -> Result<(TensorMap, TensorMap, Vec<SamplesIntersection>), Error> {
+    let mut first_blocks = Vec::with_capacity(first.blocks().len());
+    let mut second_blocks = Vec::with_capacity(first.blocks().len());
+    let mut intersections = Vec::with_capacity(first.blocks().len());
+    for blocks in zip_maps(&[first, second])? {
+        let (first_block, second_block) = (blocks[0], blocks[1]);
+
+        let first_samples = &first_block.values().samples;
+        let second_samples = &second_block.values().samples;
+        if first_samples.names() != second_samples.names() {
+            return Err(Error::InvalidParameter(format!(
+                "can not intersect samples with different names: got [{}] and [{}]",
+                first_samples.names().join(", "), second_samples.names().join(", ")
+            )));
+        }
+
+        let mut intersection = SamplesIntersection {
+            first: Vec::new(),
+            second: Vec::new(),
+        };
+        for (first_i, sample) in first_samples.iter().enumerate() {
+            if let Some(second_i) = second_samples.position(sample) {
+                intersection.first.push(first_i);
+                intersection.second.push(second_i);
+            }
+        }
+
+        first_blocks.push(first_block.select_samples(&intersection.first)?);
+        second_blocks.push(second_block.select_samples(&intersection.second)?);
+        intersections.push(intersection);
+    }
+
+    let keys = (**first.keys()).clone();
+    let mut first_intersected = TensorMap::new(keys.clone(), first_blocks)?;
+    first_intersected.copy_metadata_from(first);
+    let mut second_intersected = TensorMap::new(keys, second_blocks)?;
+    second_intersected.copy_metadata_from(second);
+
+    return Ok((first_intersected, second_intersected, intersections));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::TestArray;
+
+    use super::*;
+    use super::super::utils::example_labels;
+
+    fn tensor(properties: Vec<[i32; 1]>, data: Vec<f64>) -> TensorMap {
+        let count = properties.len();
+        let block = TensorBlock::new(
+            TestArray::from_data(vec![2, count], data),
+            example_labels(vec!["samples"], vec![[0], [1]]),
+            vec![],
+            example_labels(vec!["properties"], properties),
+        ).unwrap();
+
+        let keys = example_labels(vec!["key"], vec![[0]]);
+        return TensorMap::new((*keys).clone(), vec![block]).unwrap();
+    }
+
+    #[test]
+    fn align() {
+        let mut first = tensor(vec![[3], [1]], vec![1.0, 2.0, 3.0, 4.0]);
+        first.set_metadata("origin", Some("first")).unwrap();
+        first.blocks_mut()[0].set_unit(Some("eV")).unwrap();
+        let second = tensor(vec![[2]], vec![5.0, 6.0]);
+
+        let aligned = align_properties(&[&first, &second]).unwrap();
+        assert_eq!(aligned.len(), 2);
+        assert_eq!(aligned[0].metadata().collect::<Vec<_>>(), [("origin", "first")]);
+        assert_eq!(aligned[0].blocks()[0].unit(), Some("eV"));
+        assert_eq!(aligned[1].metadata().count(), 0);
+        assert_eq!(aligned[1].blocks()[0].unit(), None);
+
+        let expected = example_labels(vec!["properties"], vec![[1], [2], [3]]);
+        let block = aligned[0].blocks()[0].values();
+        assert_eq!(block.properties, expected);
+        assert_eq!(block.data.data().unwrap(), [2.0, 0.0, 1.0, 4.0, 0.0, 3.0]);
+
+        let block = aligned[1].blocks()[0].values();
+        assert_eq!(block.properties, expected);
+        assert_eq!(block.data.data().unwrap(), [0.0, 5.0, 0.0, 0.0, 6.0, 0.0]);
+    }
+
+    #[test]
+    fn intersect() {
+        let first = TensorMap::new(
+            (*example_labels(vec!["key"], vec![[0]])).clone(),
+            vec![TensorBlock::new(
+                TestArray::from_data(vec![3, 1], vec![1.0, 2.0, 3.0]),
+                example_labels(vec!["samples"], vec![[0], [1], [2]]),
+                vec![],
+                example_labels(vec!["properties"], vec![[0]]),
+            ).unwrap()],
+        ).unwrap();
+
+        let second = TensorMap::new(
+            (*example_labels(vec!["key"], vec![[0]])).clone(),
+            vec![TensorBlock::new(
+                TestArray::from_data(vec![3, 1], vec![4.0, 5.0, 6.0]),
+                example_labels(vec!["samples"], vec![[2], [3], [0]]),
+                vec![],
+                example_labels(vec!["properties"], vec![[0]]),
+            ).unwrap()],
+        ).unwrap();
+
+        let mut first = first;
+        first.set_metadata("origin", Some("first")).unwrap();
+        first.blocks_mut()[0].set_unit(Some("eV")).unwrap();
+
+        let (first, second, intersections) = intersect_samples(&first, &second).unwrap();
+        assert_eq!(first.metadata().collect::<Vec<_>>(), [("origin", "first")]);
+        assert_eq!(first.blocks()[0].unit(), Some("eV"));
+        assert_eq!(second.metadata().count(), 0);
+        assert_eq!(intersections, [SamplesIntersection {
+            first: vec![0, 2],
+            second: vec![2, 0],
+        }]);
+
+        let expected = example_labels(vec!["samples"], vec![[0], [2]]);
+        assert_eq!(first.blocks()[0].values().samples, expected);
+        assert_eq!(first.blocks()[0].values().data.data().unwrap(), [1.0, 3.0]);
+        assert_eq!(second.blocks()[0].values().samples, expected);
+        assert_eq!(second.blocks()[0].values().data.data().unwrap(), [6.0, 4.0]);
+    }
+}
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use crate::labels::{Labels, LabelsBuilder, LabelValue};
+use crate::{Error, TensorBlock, BasicBlock, eqs_array_t};
+
+use super::TensorMap;
+
+impl TensorMap {
+    /// Get a canonical version of this `TensorMap`, where the keys are sorted
+    /// in lexicographic order, and the samples and properties of each block
+    /// (and the samples of each gradient) are sorted as well. The data arrays
+    /// are re-ordered accordingly.
+    ///
+    /// Two tensor maps containing the same data in different order will give
+    /// the same result after canonicalization, and will be serialized to the
+    /// same bytes by [`crate::io::save`].
+    ///
+    /// Components are not re-ordered, since their order usually carries
+    /// meaning. This function requires access to the data of all arrays
+    /// through `eqs_array_t.data`.
+    pub fn canonicalize(&self) -> Result<TensorMap, Error> {
+        let (keys, keys_order) = sorted_labels(&self.keys)?;
+
+        let mut blocks = Vec::with_capacity(self.blocks.len());
+        for block_i in keys_order {
+            blocks.push(canonicalize_block(&self.blocks[block_i])?);
+        }
+
+        let mut tensor = TensorMap::new(keys, blocks)?;
+        tensor.copy_metadata_from(self);
+
+        return Ok(tensor);
+    }
+}
+
+/// Sort the given `labels`, returning the sorted labels and the position of
+/// each new entry in the original labels.
+pub(super) fn sorted_labels(labels: &Labels) -> Result<(Labels, Vec<usize>), Error> {
+    let mut order = (0..labels.count()).collect::<Vec<_>>();
+    order.sort_unstable_by(|&a, &b| labels[a].cmp(&labels[b]));
+
+    let sorted = reorder_labels(labels, &order)?;
+    return Ok((sorted, order));
+}
+
+/// Create new `Labels` containing the entries of `labels`, in the given
+/// `order`.
+pub(super) fn reorder_labels(labels: &Labels, order: &[usize]) -> Result<Labels, Error> {
+    let mut builder = LabelsBuilder::new(labels.names());
+    builder.reserve(order.len());
+    for &i in order {
+        builder.add(&labels[i])?;
+    }
+    return Ok(builder.finish());
+}
+
+fn canonicalize_block(block: &TensorBlock) -> Result<TensorBlock, Error> {
+    let values = block.values();
+    let (samples, samples_order) = sorted_labels(&values.samples)?;
+    let (properties, properties_order) = sorted_labels(&values.properties)?;
+
+    let data = reorder_data(&values.data, &samples_order, &properties_order)?;
+    let mut new_block = TensorBlock::new(
+        data,
+        Arc::new(samples),
+        values.components.to_vec(),
+        Arc::new(properties),
+    )?;
+
+    // position of each old sample in the new samples
+    let mut new_sample_position = vec![0; samples_order.len()];
+    for (new_i, &old_i) in samples_order.iter().enumerate() {
+        new_sample_position[old_i] = new_i;
+    }
+
+    let mut parameters = block.gradients().keys().collect::<Vec<_>>();
+    parameters.sort_unstable();
+    for parameter in parameters {
+        let gradient = &block.gradients()[parameter];
+        let (samples, order) = canonicalize_gradient_samples(gradient, &new_sample_position)?;
+        let data = reorder_data(&gradient.data, &order, &properties_order)?;
+
+        new_block.add_gradient(
+            parameter,
+            data,
+            Arc::new(samples),
+            gradient.components.to_vec(),
+        )?;
+    }
+
+    new_block.copy_units_from(block);
+
+    return Ok(new_block);
+}
+
+/// Update the gradient samples to refer to the new position of the values
+/// samples, and sort the result.
+fn canonicalize_gradient_samples(
+    gradient: &BasicBlock,
+    new_sample_position: &[usize],
+) -> Result<(Labels, Vec<usize>), Error> {
+    let mut entries = Vec::with_capacity(gradient.samples.count());
+    for (i, entry) in gradient.samples.iter().enumerate() {
+        let mut entry = entry.to_vec();
+        entry[0] = LabelValue::from(new_sample_position[entry[0].usize()]);
+        entries.push((entry, i));
+    }
+    entries.sort_unstable();
+
+    let mut builder = LabelsBuilder::new(gradient.samples.names());
+    builder.reserve(entries.len());
+    let mut order = Vec::with_capacity(entries.len());
+    for (entry, i) in entries {
+        builder.add(&entry)?;
+        order.push(i);
+    }
+
+    return Ok((builder.finish(), order));
+}
+
+/// Create a new array with the same shape as `array`, where entry
+/// `[s, ..., p]` contains the data from `array[samples[s], ..., properties[p]]`
+pub(super) fn reorder_data(
+    array: &eqs_array_t,
+    samples: &[usize],
+    properties: &[usize],
+) -> Result<eqs_array_t, Error> {
+    let shape = array.shape()?;
+    let n_properties = shape[shape.len() - 1];
+    let n_components = shape[1..shape.len() - 1].iter().product::<usize>();
+    debug_assert_eq!(shape[0], samples.len());
+    debug_assert_eq!(n_properties, properties.len());
+
+    let mut new_array = array.create(shape)?;
+    let input = array.contiguous_data()?;
+    let output = new_array.data_mut()?;
+
+    for (new_sample, &old_sample) in samples.iter().enumerate() {
+        for component in 0..n_components {
+            let input_start = (old_sample * n_components + component) * n_properties;
+            let output_start = (new_sample * n_components + component) * n_properties;
+            for (new_property, &old_property) in properties.iter().enumerate() {
+                output[output_start + new_property] = input[input_start + old_property];
+            }
+        }
+    }
+
+    return Ok(new_array);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::TestArray;
+
+    use super::*;
+    use super::super::utils::example_labels;
+
+    #[test]
+    fn canonicalize() {
+        let mut block = TensorBlock::new(
+            TestArray::from_data(vec![2, 3], vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]),
+            example_labels(vec!["samples"], vec![[4], [1]]),
+            vec![],
+            example_labels(vec!["properties"], vec![[2], [0], [1]]),
+        ).unwrap();
+
+        block.add_gradient(
+            "parameter",
+            TestArray::from_data(vec![3, 3], vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]),
+            example_labels(vec!["sample", "atom"], vec![[0, 0], [1, 1], [0, 1]]),
+            vec![],
+        ).unwrap();
+
+        let mut other = TensorBlock::new(
+            TestArray::new(vec![1, 1]),
+            example_labels(vec!["samples"], vec![[0]]),
+            vec![],
+            example_labels(vec!["properties"], vec![[0]]),
+        ).unwrap();
+        other.add_gradient(
+            "parameter",
+            TestArray::new(vec![0, 1]),
+            Arc::new(LabelsBuilder::new(vec!["sample", "atom"]).finish()),
+            vec![],
+        ).unwrap();
+
+        let keys = example_labels(vec!["key"], vec![[1], [0]]);
+        let tensor = TensorMap::new((*keys).clone(), vec![block, other]).unwrap();
+
+        let canonical = tensor.canonicalize().unwrap();
+        assert_eq!(**canonical.keys(), *example_labels(vec!["key"], vec![[0], [1]]));
+
+        let block = &canonical.blocks()[1];
+        let values = block.values();
+        assert_eq!(*values.samples, *example_labels(vec!["samples"], vec![[1], [4]]));
+        assert_eq!(*values.properties, *example_labels(vec!["properties"], vec![[0], [1], [2]]));
+        assert_eq!(values.data.data().unwrap(), [4.0, 5.0, 3.0, 1.0, 2.0, 0.0]);
+
+        let gradient = block.gradient("parameter").unwrap();
+        assert_eq!(
+            *gradient.samples,
+            *example_labels(vec!["sample", "atom"], vec![[0, 1], [1, 0], [1, 1]])
+        );
+        assert_eq!(gradient.data.data().unwrap(), [4.0, 5.0, 3.0, 1.0, 2.0, 0.0, 7.0, 8.0, 6.0]);
+    }
+}
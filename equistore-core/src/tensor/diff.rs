@@ -0,0 +1,576 @@
+use std::fmt::Write;
+
+use crate::{Error, BasicBlock, TensorBlock, LabelValue};
+
+use super::TensorMap;
+
+/// Options for the [`diff`] function
+#[derive(Debug, Clone, Copy)]
+pub struct DiffOptions {
+    /// Should we compare the values inside the arrays? This requires access to
+    /// the data through `eqs_array_t.data`.
+    pub compare_data: bool,
+    /// Absolute tolerance used when comparing data, differences smaller than
+    /// this are not reported.
+    pub tolerance: f64,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            compare_data: true,
+            tolerance: 0.0,
+        }
+    }
+}
+
+/// Kind of difference between two blocks with the same key
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockDifference {
+    /// The blocks have different units, given as `(first, second)`
+    Unit(Option<String>, Option<String>),
+    /// The blocks represent different quantities, given as `(first, second)`
+    Quantity(Option<String>, Option<String>),
+    /// The gradient only exists in one of the blocks
+    MissingGradient {
+        /// Is the gradient present in the first tensor map?
+        in_first: bool,
+    },
+    /// The samples labels are different
+    Samples,
+    /// The component labels at the given position are different
+    Components(usize),
+    /// The properties labels are different
+    Properties,
+    /// The arrays have different shapes
+    Shape(Vec<usize>, Vec<usize>),
+    /// The data differs, with the given maximal absolute element-wise
+    /// difference
+    Data {
+        max_difference: f64,
+    },
+}
+
+/// Single difference between two blocks sharing the same key
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockDiff {
+    /// key of the block in both tensor maps
+    pub key: Vec<LabelValue>,
+    /// either `"values"` or the parameter of the gradient
+    pub parameter: String,
+    /// what is different between the two blocks
+    pub difference: BlockDifference,
+}
+
+/// Structured report of the differences between two tensor maps, created by
+/// [`diff`].
+///
+/// The `Display` implementation renders this report as text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TensorMapDiff {
+    /// Metadata entries with different values in the two tensor maps, as
+    /// `(key, first value, second value)`. A missing value is `None`.
+    pub metadata: Vec<(String, Option<String>, Option<String>)>,
+    /// Names of the keys in both tensor maps, if they differ. When the names
+    /// are different, no other comparison is made.
+    pub keys_names: Option<(Vec<String>, Vec<String>)>,
+    /// Keys only present in the first tensor map
+    pub only_in_first: Vec<Vec<LabelValue>>,
+    /// Keys only present in the second tensor map
+    pub only_in_second: Vec<Vec<LabelValue>>,
+    /// Differences between blocks present in both tensor maps
+    pub blocks: Vec<BlockDiff>,
+}
+
+impl TensorMapDiff {
+    /// Are the two tensor maps equivalent?
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty()
+            && self.keys_names.is_none()
+            && self.only_in_first.is_empty()
+            && self.only_in_second.is_empty()
+            && self.blocks.is_empty()
+    }
+}
+
+fn format_key(key: &[LabelValue]) -> String {
+    let values = key.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    return format!("({})", values.join(", "));
+}
+
+fn format_optional(value: Option<&String>) -> String {
+    if let Some(value) = value {
+        format!("'{}'", value)
+    } else {
+        "none".into()
+    }
+}
+
+impl std::fmt::Display for TensorMapDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+
+        for (key, first, second) in &self.metadata {
+            writeln!(f, "metadata '{}': {} and {}", key, format_optional(first.as_ref()), format_optional(second.as_ref()))?;
+        }
+
+        if let Some((first, second)) = &self.keys_names {
+            return writeln!(
+                f, "different keys names: [{}] and [{}]",
+                first.join(", "), second.join(", ")
+            );
+        }
+
+        for key in &self.only_in_first {
+            writeln!(f, "block {} is only present in the first tensor map", format_key(key))?;
+        }
+
+        for key in &self.only_in_second {
+            writeln!(f, "block {} is only present in the second tensor map", format_key(key))?;
+        }
+
+        for block in &self.blocks {
+            write!(f, "block {} ({}): ", format_key(&block.key), block.parameter)?;
+            match &block.difference {
+                BlockDifference::Unit(first, second) => {
+                    writeln!(f, "different units: {} and {}", format_optional(first.as_ref()), format_optional(second.as_ref()))?;
+                }
+                BlockDifference::Quantity(first, second) => {
+                    writeln!(f, "different quantities: {} and {}", format_optional(first.as_ref()), format_optional(second.as_ref()))?;
+                }
+                BlockDifference::MissingGradient { in_first } => {
+                    let present = if *in_first { "first" } else { "second" };
+                    writeln!(f, "gradient is only present in the {} tensor map", present)?;
+                }
+                BlockDifference::Samples => writeln!(f, "different samples")?,
+                BlockDifference::Components(i) => writeln!(f, "different component {}", i)?,
+                BlockDifference::Properties => writeln!(f, "different properties")?,
+                BlockDifference::Shape(first, second) => {
+                    writeln!(f, "different shapes: {:?} and {:?}", first, second)?;
+                }
+                BlockDifference::Data { max_difference } => {
+                    writeln!(f, "different data, max absolute difference is {:e}", max_difference)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TensorMapDiff {
+    /// Export this report as a JSON string.
+    ///
+    /// The JSON document is an object with the same fields as this struct.
+    /// Missing values are `null`, and each entry in `blocks` contains the
+    /// `key`, the `parameter` and a `difference` object. The `kind` field of
+    /// the `difference` is one of `"unit"`, `"quantity"`, `"missing_gradient"`,
+    /// `"samples"`, `"components"`, `"properties"`, `"shape"` or `"data"`, and
+    /// the other fields of this object depend on the `kind`. A non-finite
+    /// `max_difference` is exported as `null`.
+    pub fn to_json(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str("{\"metadata\":");
+        write_json_list(&mut output, &self.metadata, |output, (key, first, second)| {
+            output.push_str("{\"key\":");
+            write_json_string(output, key);
+            output.push_str(",\"first\":");
+            write_json_optional(output, first.as_ref());
+            output.push_str(",\"second\":");
+            write_json_optional(output, second.as_ref());
+            output.push('}');
+        });
+
+        output.push_str(",\"keys_names\":");
+        if let Some((first, second)) = &self.keys_names {
+            output.push_str("{\"first\":");
+            write_json_list(&mut output, first, |output, name| write_json_string(output, name));
+            output.push_str(",\"second\":");
+            write_json_list(&mut output, second, |output, name| write_json_string(output, name));
+            output.push('}');
+        } else {
+            output.push_str("null");
+        }
+
+        output.push_str(",\"only_in_first\":");
+        write_json_list(&mut output, &self.only_in_first, |output, key| write_json_key(output, key));
+        output.push_str(",\"only_in_second\":");
+        write_json_list(&mut output, &self.only_in_second, |output, key| write_json_key(output, key));
+
+        output.push_str(",\"blocks\":");
+        write_json_list(&mut output, &self.blocks, |output, block| {
+            output.push_str("{\"key\":");
+            write_json_key(output, &block.key);
+            output.push_str(",\"parameter\":");
+            write_json_string(output, &block.parameter);
+            output.push_str(",\"difference\":{\"kind\":");
+            match &block.difference {
+                BlockDifference::Unit(first, second) => {
+                    output.push_str("\"unit\",\"first\":");
+                    write_json_optional(output, first.as_ref());
+                    output.push_str(",\"second\":");
+                    write_json_optional(output, second.as_ref());
+                }
+                BlockDifference::Quantity(first, second) => {
+                    output.push_str("\"quantity\",\"first\":");
+                    write_json_optional(output, first.as_ref());
+                    output.push_str(",\"second\":");
+                    write_json_optional(output, second.as_ref());
+                }
+                BlockDifference::MissingGradient { in_first } => {
+                    write!(output, "\"missing_gradient\",\"in_first\":{}", in_first).expect("failed to write to string");
+                }
+                BlockDifference::Samples => output.push_str("\"samples\""),
+                BlockDifference::Components(i) => {
+                    write!(output, "\"components\",\"index\":{}", i).expect("failed to write to string");
+                }
+                BlockDifference::Properties => output.push_str("\"properties\""),
+                BlockDifference::Shape(first, second) => {
+                    output.push_str("\"shape\",\"first\":");
+                    write_json_list(output, first, |output, size| output.push_str(&size.to_string()));
+                    output.push_str(",\"second\":");
+                    write_json_list(output, second, |output, size| output.push_str(&size.to_string()));
+                }
+                BlockDifference::Data { max_difference } => {
+                    output.push_str("\"data\",\"max_difference\":");
+                    if max_difference.is_finite() {
+                        write!(output, "{:?}", max_difference).expect("failed to write to string");
+                    } else {
+                        output.push_str("null");
+                    }
+                }
+            }
+            output.push_str("}}");
+        });
+        output.push('}');
+
+        return output;
+    }
+}
+
+fn write_json_list<T>(output: &mut String, values: &[T], mut write: impl FnMut(&mut String, &T)) {
+    output.push('[');
+    for (i, value) in values.iter().enumerate() {
+        if i != 0 {
+            output.push(',');
+        }
+        write(output, value);
+    }
+    output.push(']');
+}
+
+fn write_json_key(output: &mut String, key: &[LabelValue]) {
+    write_json_list(output, key, |output, value| output.push_str(&value.to_string()));
+}
+
+fn write_json_string(output: &mut String, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                write!(output, "\\u{:04x}", c as u32).expect("failed to write to string");
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+fn write_json_optional(output: &mut String, value: Option<&String>) {
+    if let Some(value) = value {
+        write_json_string(output, value);
+    } else {
+        output.push_str("null");
+    }
+}
+
+/// Compare the two tensor maps `first` and `second`, and report all the
+/// differences in metadata, keys, units, labels, shapes and data. Blocks are
+/// matched by key, so the order of the blocks does not matter.
+pub fn diff(first: &TensorMap, second: &TensorMap, options: DiffOptions) -> Result<TensorMapDiff, Error> {
+    let mut result = TensorMapDiff::default();
+
+    let mut metadata_keys = first.metadata().chain(second.metadata()).map(|(key, _)| key).collect::<Vec<_>>();
+    metadata_keys.sort_unstable();
+    metadata_keys.dedup();
+    for key in metadata_keys {
+        let first_value = first.get_metadata(key);
+        let second_value = second.get_metadata(key);
+        if first_value != second_value {
+            result.metadata.push((
+                key.to_owned(),
+                first_value.map(str::to_owned),
+                second_value.map(str::to_owned),
+            ));
+        }
+    }
+
+    if first.keys().names() != second.keys().names() {
+        result.keys_names = Some((
+            first.keys().names().iter().map(|&s| s.to_owned()).collect(),
+            second.keys().names().iter().map(|&s| s.to_owned()).collect(),
+        ));
+        return Ok(result);
+    }
+
+    for (first_i, key) in first.keys().iter().enumerate() {
+        if let Some(second_i) = second.keys().position(key) {
+            diff_blocks(
+                key,
+                &first.blocks()[first_i],
+                &second.blocks()[second_i],
+                options,
+                &mut result.blocks,
+            )?;
+        } else {
+            result.only_in_first.push(key.to_vec());
+        }
+    }
+
+    for key in second.keys().iter() {
+        if !first.keys().contains(key) {
+            result.only_in_second.push(key.to_vec());
+        }
+    }
+
+    return Ok(result);
+}
+
+fn diff_blocks(
+    key: &[LabelValue],
+    first: &TensorBlock,
+    second: &TensorBlock,
+    options: DiffOptions,
+    output: &mut Vec<BlockDiff>,
+) -> Result<(), Error> {
+    let mut push = |parameter: &str, difference| {
+        output.push(BlockDiff {
+            key: key.to_vec(),
+            parameter: parameter.to_owned(),
+            difference,
+        });
+    };
+
+    if first.unit() != second.unit() {
+        push("values", BlockDifference::Unit(
+            first.unit().map(str::to_owned),
+            second.unit().map(str::to_owned),
+        ));
+    }
+
+    if first.quantity() != second.quantity() {
+        push("values", BlockDifference::Quantity(
+            first.quantity().map(str::to_owned),
+            second.quantity().map(str::to_owned),
+        ));
+    }
+
+    for difference in diff_basic_blocks(first.values(), second.values(), options)? {
+        push("values", difference);
+    }
+
+    let mut parameters = first.gradients().keys().chain(second.gradients().keys()).collect::<Vec<_>>();
+    parameters.sort_unstable();
+    parameters.dedup();
+
+    for parameter in parameters {
+        match (first.gradient(parameter), second.gradient(parameter)) {
+            (Some(first), Some(second)) => {
+                for difference in diff_basic_blocks(first, second, options)? {
+                    push(parameter, difference);
+                }
+            }
+            (Some(_), None) => push(parameter, BlockDifference::MissingGradient { in_first: true }),
+            (None, Some(_)) => push(parameter, BlockDifference::MissingGradient { in_first: false }),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    Ok(())
+}
+
+fn diff_basic_blocks(first: &BasicBlock, second: &BasicBlock, options: DiffOptions) -> Result<Vec<BlockDifference>, Error> {
+    let mut differences = Vec::new();
+
+    let mut same_labels = true;
+    if first.samples != second.samples {
+        differences.push(BlockDifference::Samples);
+        same_labels = false;
+    }
+
+    if first.components.len() == second.components.len() {
+        for (i, (first, second)) in first.components.iter().zip(&second.components).enumerate() {
+            if first != second {
+                differences.push(BlockDifference::Components(i));
+                same_labels = false;
+            }
+        }
+    } else {
+        same_labels = false;
+    }
+
+    if first.properties != second.properties {
+        differences.push(BlockDifference::Properties);
+        same_labels = false;
+    }
+
+    let first_shape = first.data.shape()?;
+    let second_shape = second.data.shape()?;
+    if first_shape != second_shape {
+        differences.push(BlockDifference::Shape(first_shape.to_vec(), second_shape.to_vec()));
+        same_labels = false;
+    }
+
+    // only compare the data if it can be matched element by element
+    if options.compare_data && same_labels {
+        let mut max_difference = 0.0_f64;
+        for (a, b) in first.data.contiguous_data()?.iter().zip(second.data.contiguous_data()?.iter()) {
+            let difference = if a.is_nan() && b.is_nan() {
+                0.0
+            } else if a.is_nan() || b.is_nan() {
+                f64::INFINITY
+            } else {
+                (a - b).abs()
+            };
+            max_difference = max_difference.max(difference);
+        }
+
+        if max_difference > options.tolerance {
+            differences.push(BlockDifference::Data { max_difference });
+        }
+    }
+
+    return Ok(differences);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::TestArray;
+
+    use super::*;
+    use super::super::utils::example_labels;
+
+    fn tensor(keys: Vec<[i32; 1]>, data: &[f64]) -> TensorMap {
+        let mut blocks = Vec::new();
+        for _ in &keys {
+            blocks.push(TensorBlock::new(
+                TestArray::from_data(vec![2, 1], data.to_vec()),
+                example_labels(vec!["samples"], vec![[0], [1]]),
+                vec![],
+                example_labels(vec!["properties"], vec![[0]]),
+            ).unwrap());
+        }
+
+        let keys = example_labels(vec!["key"], keys);
+        return TensorMap::new((*keys).clone(), blocks).unwrap();
+    }
+
+    #[test]
+    fn diff_tensors() {
+        let first = tensor(vec![[0], [1]], &[1.0, 2.0]);
+        let second = tensor(vec![[1], [0]], &[1.0, 2.0]);
+        let result = diff(&first, &second, DiffOptions::default()).unwrap();
+        assert!(result.is_empty());
+        assert_eq!(result.to_string(), "no differences\n");
+
+        let second = tensor(vec![[1], [2]], &[1.0, 2.5]);
+        let result = diff(&first, &second, DiffOptions::default()).unwrap();
+        assert_eq!(result.only_in_first, [[LabelValue::new(0)]]);
+        assert_eq!(result.only_in_second, [[LabelValue::new(2)]]);
+        assert_eq!(result.blocks, [BlockDiff {
+            key: vec![LabelValue::new(1)],
+            parameter: "values".into(),
+            difference: BlockDifference::Data { max_difference: 0.5 },
+        }]);
+
+        assert_eq!(result.to_string(),
+            "block (0) is only present in the first tensor map\n\
+            block (2) is only present in the second tensor map\n\
+            block (1) (values): different data, max absolute difference is 5e-1\n"
+        );
+
+        let options = DiffOptions { compare_data: true, tolerance: 1.0 };
+        let result = diff(&first, &second, options).unwrap();
+        assert!(result.blocks.is_empty());
+    }
+
+    #[test]
+    fn diff_json() {
+        let first = tensor(vec![[0], [1]], &[1.0, 2.0]);
+        let second = tensor(vec![[1], [0]], &[1.0, 2.0]);
+        let result = diff(&first, &second, DiffOptions::default()).unwrap();
+        assert_eq!(result.to_json(),
+            r#"{"metadata":[],"keys_names":null,"only_in_first":[],"only_in_second":[],"blocks":[]}"#
+        );
+
+        let mut second = tensor(vec![[1], [2]], &[1.0, 2.5]);
+        second.blocks_mut()[0].set_unit(Some("e\"V")).unwrap();
+        second.set_metadata("origin", Some("b")).unwrap();
+        let result = diff(&first, &second, DiffOptions::default()).unwrap();
+        assert_eq!(result.to_json(), concat!(
+            r#"{"metadata":[{"key":"origin","first":null,"second":"b"}],"keys_names":null,"#,
+            r#""only_in_first":[[0]],"only_in_second":[[2]],"blocks":["#,
+            r#"{"key":[1],"parameter":"values","difference":{"kind":"unit","first":null,"second":"e\"V"}},"#,
+            r#"{"key":[1],"parameter":"values","difference":{"kind":"data","max_difference":0.5}}]}"#,
+        ));
+    }
+
+    #[test]
+    fn diff_units() {
+        let first = tensor(vec![[0]], &[1.0, 2.0]);
+        let mut second = tensor(vec![[0]], &[1.0, 2.0]);
+        second.blocks_mut()[0].set_unit(Some("eV")).unwrap();
+        second.blocks_mut()[0].set_quantity(Some("energy")).unwrap();
+
+        let result = diff(&first, &second, DiffOptions::default()).unwrap();
+        assert_eq!(result.blocks, [
+            BlockDiff {
+                key: vec![LabelValue::new(0)],
+                parameter: "values".into(),
+                difference: BlockDifference::Unit(None, Some("eV".into())),
+            },
+            BlockDiff {
+                key: vec![LabelValue::new(0)],
+                parameter: "values".into(),
+                difference: BlockDifference::Quantity(None, Some("energy".into())),
+            },
+        ]);
+
+        assert_eq!(result.to_string(),
+            "block (0) (values): different units: none and 'eV'\n\
+            block (0) (values): different quantities: none and 'energy'\n"
+        );
+    }
+
+    #[test]
+    fn diff_metadata() {
+        let mut first = tensor(vec![[0]], &[1.0, 2.0]);
+        let mut second = tensor(vec![[0]], &[1.0, 2.0]);
+        first.set_metadata("origin", Some("a")).unwrap();
+        first.set_metadata("version", Some("1")).unwrap();
+        second.set_metadata("origin", Some("b")).unwrap();
+        second.set_metadata("version", Some("1")).unwrap();
+        second.set_metadata("zzz", Some("new")).unwrap();
+
+        let result = diff(&first, &second, DiffOptions::default()).unwrap();
+        assert!(!result.is_empty());
+        assert!(result.blocks.is_empty());
+        assert_eq!(result.metadata, [
+            ("origin".into(), Some("a".into()), Some("b".into())),
+            ("zzz".into(), None, Some("new".into())),
+        ]);
+
+        assert_eq!(result.to_string(),
+            "metadata 'origin': 'a' and 'b'\n\
+            metadata 'zzz': none and 'new'\n"
+        );
+    }
+}
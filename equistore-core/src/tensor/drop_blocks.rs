@@ -0,0 +1,84 @@
+use crate::{Error, Labels};
+
+use super::TensorMap;
+use super::canonicalize::reorder_labels;
+
+impl TensorMap {
+    /// Get a new `TensorMap` containing all the blocks of this tensor map,
+    /// except for the blocks matching the given `selection`.
+    ///
+    /// The `selection` follows the same rules as in
+    /// [`TensorMap::blocks_matching`], and can match multiple blocks. The keys
+    /// of the new tensor map only contain the entries for the remaining
+    /// blocks, in the same order as in `self`.
+    pub fn drop_blocks(&self, selection: &Labels) -> Result<TensorMap, Error> {
+        let dropped = self.blocks_matching(selection)?;
+        let kept = (0..self.blocks.len())
+            .filter(|block_i| !dropped.contains(block_i))
+            .collect::<Vec<_>>();
+
+        let keys = reorder_labels(&self.keys, &kept)?;
+
+        let mut blocks = Vec::with_capacity(kept.len());
+        for &block_i in &kept {
+            blocks.push(self.blocks[block_i].try_clone()?);
+        }
+
+        let mut tensor = TensorMap::new(keys, blocks)?;
+        tensor.copy_metadata_from(self);
+
+        return Ok(tensor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::TestArray;
+    use crate::{LabelsBuilder, TensorBlock};
+
+    use super::*;
+    use super::super::utils::example_labels;
+
+    fn tensor() -> TensorMap {
+        let keys = example_labels(vec!["species", "l"], vec![[6, 0], [1, 1], [6, 1], [1, 0]]);
+
+        let mut blocks = Vec::new();
+        // use different samples for each block, to check which blocks are kept
+        for sample in 0..4 {
+            blocks.push(TensorBlock::new(
+                TestArray::new(vec![1, 1]),
+                example_labels(vec!["samples"], vec![[sample]]),
+                vec![],
+                example_labels(vec!["properties"], vec![[0]]),
+            ).unwrap());
+        }
+
+        return TensorMap::new((*keys).clone(), blocks).unwrap();
+    }
+
+    fn selection(name: &str, value: i32) -> Labels {
+        let mut builder = LabelsBuilder::new(vec![name]);
+        builder.add(&[value]).unwrap();
+        return builder.finish();
+    }
+
+    #[test]
+    fn drop_blocks() {
+        let tensor = tensor();
+
+        let result = tensor.drop_blocks(&selection("species", 6)).unwrap();
+        assert_eq!(**result.keys(), *example_labels(vec!["species", "l"], vec![[1, 1], [1, 0]]));
+        assert_eq!(*result.blocks()[0].values().samples, *example_labels(vec!["samples"], vec![[1]]));
+        assert_eq!(*result.blocks()[1].values().samples, *example_labels(vec!["samples"], vec![[3]]));
+
+        // nothing matching the selection
+        let result = tensor.drop_blocks(&selection("l", 3)).unwrap();
+        assert_eq!(result.keys(), tensor.keys());
+
+        let error = tensor.drop_blocks(&selection("n", 3)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: 'n' is not part of the keys for this tensor"
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{Error, Labels, BasicBlock};
+
+use super::TensorMap;
+
+impl TensorMap {
+    /// Compute a stable 64-bit fingerprint of this `TensorMap`.
+    ///
+    /// The fingerprint covers all the metadata (keys, samples, components,
+    /// properties and gradients parameters and labels, as well as the unit and
+    /// quantity of the blocks and the free-form metadata of the tensor map)
+    /// and the shape of all arrays. If `include_data` is `true`, the values stored in the arrays
+    /// are also part of the fingerprint, which requires access to the data
+    /// through `eqs_array_t.data`.
+    ///
+    /// The fingerprint does not depend on the platform, the process or the
+    /// version of equistore, and can be used as a cache key. It does depend on
+    /// the order of the entries in the different `Labels`, call
+    /// [`TensorMap::canonicalize`] first to ignore the order.
+    pub fn fingerprint(&self, include_data: bool) -> Result<u64, Error> {
+        let mut hasher = StableHasher::new();
+
+        hasher.write_usize(self.metadata.len());
+        for (key, value) in self.metadata() {
+            hasher.write_str(key);
+            hasher.write_str(value);
+        }
+
+        hasher.write_labels(&self.keys);
+        hasher.write_usize(self.blocks.len());
+        for block in &self.blocks {
+            hasher.write_optional_str(block.unit());
+            hasher.write_optional_str(block.quantity());
+            hasher.write_basic_block(block.values(), include_data)?;
+
+            let mut gradients = block.gradients().iter().collect::<Vec<_>>();
+            gradients.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+            hasher.write_usize(gradients.len());
+            for (parameter, gradient) in gradients {
+                hasher.write_str(parameter);
+                hasher.write_basic_block(gradient, include_data)?;
+            }
+        }
+
+        return Ok(hasher.finish());
+    }
+}
+
+/// Implementation of the 64-bit FNV-1a hash, which gives the same results
+/// everywhere (contrary to the hashers in std or ahash).
+struct StableHasher {
+    state: u64,
+}
+
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    fn new() -> StableHasher {
+        StableHasher { state: StableHasher::OFFSET_BASIS }
+    }
+
+    fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= u64::from(byte);
+            self.state = self.state.wrapping_mul(StableHasher::PRIME);
+        }
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        // always use 64-bit to get the same results on 32-bit platforms
+        self.write(&(value as u64).to_le_bytes());
+    }
+
+    fn write_str(&mut self, value: &str) {
+        self.write_usize(value.len());
+        self.write(value.as_bytes());
+    }
+
+    fn write_optional_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.write(&[1]);
+                self.write_str(value);
+            }
+            None => self.write(&[0]),
+        }
+    }
+
+    fn write_labels(&mut self, labels: &Labels) {
+        self.write_usize(labels.size());
+        for name in labels.names() {
+            self.write_str(name);
+        }
+
+        self.write_usize(labels.count());
+        for entry in labels {
+            for value in entry {
+                self.write(&value.i32().to_le_bytes());
+            }
+        }
+    }
+
+    fn write_basic_block(&mut self, block: &BasicBlock, include_data: bool) -> Result<(), Error> {
+        self.write_labels(&block.samples);
+        self.write_usize(block.components.len());
+        for component in &block.components {
+            self.write_labels(component);
+        }
+        self.write_labels(&block.properties);
+
+        let shape = block.data.shape()?;
+        self.write_usize(shape.len());
+        for &size in shape {
+            self.write_usize(size);
+        }
+
+        if include_data {
+            for value in block.data.contiguous_data()?.iter() {
+                // normalize all NaN to the same value, and -0.0 to 0.0 (adding
+                // 0.0 to -0.0 gives 0.0)
+                let value = if value.is_nan() { f64::NAN } else { value + 0.0 };
+                self.write(&value.to_bits().to_le_bytes());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TensorBlock;
+    use crate::data::TestArray;
+
+    use super::*;
+    use super::super::utils::example_labels;
+
+    fn tensor(data: Vec<f64>) -> TensorMap {
+        let block = TensorBlock::new(
+            TestArray::from_data(vec![2, 1], data),
+            example_labels(vec!["samples"], vec![[0], [1]]),
+            vec![],
+            example_labels(vec!["properties"], vec![[0]]),
+        ).unwrap();
+
+        let keys = example_labels(vec!["key"], vec![[0]]);
+        return TensorMap::new((*keys).clone(), vec![block]).unwrap();
+    }
+
+    #[test]
+    fn fingerprint() {
+        let first = tensor(vec![1.0, 2.0]);
+        let second = tensor(vec![1.0, 3.0]);
+
+        assert_eq!(first.fingerprint(false).unwrap(), first.fingerprint(false).unwrap());
+        assert_eq!(first.fingerprint(false).unwrap(), second.fingerprint(false).unwrap());
+
+        assert_eq!(first.fingerprint(true).unwrap(), first.fingerprint(true).unwrap());
+        assert_ne!(first.fingerprint(true).unwrap(), second.fingerprint(true).unwrap());
+        assert_ne!(first.fingerprint(true).unwrap(), first.fingerprint(false).unwrap());
+    }
+
+    #[test]
+    fn fingerprint_units() {
+        let first = tensor(vec![1.0, 2.0]);
+        let mut second = tensor(vec![1.0, 2.0]);
+        assert_eq!(first.fingerprint(true).unwrap(), second.fingerprint(true).unwrap());
+
+        second.blocks_mut()[0].set_unit(Some("eV")).unwrap();
+        let with_unit = second.fingerprint(true).unwrap();
+        assert_ne!(first.fingerprint(true).unwrap(), with_unit);
+
+        second.blocks_mut()[0].set_quantity(Some("energy")).unwrap();
+        assert_ne!(second.fingerprint(true).unwrap(), with_unit);
+
+        // an empty unit is different from no unit
+        second.blocks_mut()[0].set_quantity(None).unwrap();
+        second.blocks_mut()[0].set_unit(Some("")).unwrap();
+        assert_ne!(first.fingerprint(true).unwrap(), second.fingerprint(true).unwrap());
+    }
+
+    #[test]
+    fn fingerprint_metadata() {
+        let first = tensor(vec![1.0, 2.0]);
+        let mut second = tensor(vec![1.0, 2.0]);
+
+        second.set_metadata("origin", Some("test")).unwrap();
+        let with_metadata = second.fingerprint(false).unwrap();
+        assert_ne!(first.fingerprint(false).unwrap(), with_metadata);
+
+        second.set_metadata("origin", Some("other")).unwrap();
+        assert_ne!(second.fingerprint(false).unwrap(), with_metadata);
+
+        second.set_metadata("origin", None).unwrap();
+        assert_eq!(first.fingerprint(false).unwrap(), second.fingerprint(false).unwrap());
+    }
+}
@@ -2,13 +2,14 @@ use std::sync::Arc;
 
 use indexmap::IndexSet;
 
-use crate::labels::{Labels, LabelsBuilder};
-use crate::{Error, TensorBlock};
+use crate::labels::{Labels, LabelsBuilder, LabelValue};
+use crate::{Error, Progress, TensorBlock};
 
 use crate::data::eqs_sample_mapping_t;
 
 use super::TensorMap;
 use super::utils::{KeyAndBlock, remove_dimensions_from_keys, merge_samples, merge_gradient_samples};
+use super::utils::{check_components_equal, check_units_equal};
 
 
 impl TensorMap {
@@ -39,6 +40,21 @@ impl TensorMap {
     /// lexicographically sorted. Otherwise they are kept in the order in which
     /// they appear in the blocks.
     pub fn keys_to_properties(&self, keys_to_move: &Labels, sort_samples: bool) -> Result<TensorMap, Error> {
+        return self.keys_to_properties_with_progress(keys_to_move, sort_samples, Progress::none());
+    }
+
+    /// Same as [`TensorMap::keys_to_properties`], reporting the number of new
+    /// blocks created so far to `progress`, and checking for cancellation
+    /// before creating each new block.
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip_all, fields(blocks = self.blocks.len(), keys_to_move = ?keys_to_move.names())
+    ))]
+    pub fn keys_to_properties_with_progress(
+        &self,
+        keys_to_move: &Labels,
+        sort_samples: bool,
+        progress: Progress,
+    ) -> Result<TensorMap, Error> {
         let names_to_move = keys_to_move.names();
         let splitted_keys = remove_dimensions_from_keys(&self.keys, &names_to_move)?;
 
@@ -50,6 +66,8 @@ impl TensorMap {
 
         let mut new_blocks = Vec::new();
         if splitted_keys.new_keys.count() == 1 {
+            progress.check()?;
+
             // create a single block with everything
             let blocks_to_merge = self.keys.iter()
                 .zip(&self.blocks)
@@ -63,15 +81,21 @@ impl TensorMap {
                 })
                 .collect::<Vec<_>>();
 
+            let keys = self.keys.iter().collect::<Vec<_>>();
             let block = merge_blocks_along_properties(
                 &blocks_to_merge,
+                &keys,
                 keys_to_move,
                 &names_to_move,
                 sort_samples,
             )?;
             new_blocks.push(block);
+            progress.report(1, 1);
         } else {
+            let n_new_blocks = splitted_keys.new_keys.count();
             for entry in splitted_keys.new_keys.iter() {
+                progress.check()?;
+
                 let mut selection = LabelsBuilder::new(splitted_keys.new_keys.names());
                 selection.add(entry)?;
 
@@ -89,24 +113,32 @@ impl TensorMap {
                     })
                     .collect::<Vec<_>>();
 
+                let keys = matching.iter().map(|&i| &self.keys[i]).collect::<Vec<_>>();
                 let block = merge_blocks_along_properties(
                     &blocks_to_merge,
+                    &keys,
                     keys_to_move,
                     &names_to_move,
                     sort_samples,
                 )?;
                 new_blocks.push(block);
+                progress.report(new_blocks.len(), n_new_blocks);
             }
         }
 
-        return TensorMap::new(splitted_keys.new_keys, new_blocks);
+        let mut tensor = TensorMap::new(splitted_keys.new_keys, new_blocks)?;
+        tensor.copy_metadata_from(self);
+
+        return Ok(tensor);
     }
 }
 
 /// Merge the given `blocks` along the property axis.
 #[allow(clippy::too_many_lines)]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(blocks = blocks_to_merge.len())))]
 fn merge_blocks_along_properties(
     blocks_to_merge: &[KeyAndBlock],
+    keys: &[&[LabelValue]],
     keys_to_move: Option<&Labels>,
     extracted_names: &[&str],
     sort_samples: bool,
@@ -114,16 +146,22 @@ fn merge_blocks_along_properties(
     assert!(!blocks_to_merge.is_empty());
 
     let first_block = blocks_to_merge[0].1;
-    let first_components_label = &first_block.values().components;
     let first_property_labels = &first_block.values().properties;
-    for (_, block) in blocks_to_merge {
-        if &block.values().components != first_components_label {
-            return Err(Error::InvalidParameter(
-                "can not move keys to properties if the blocks have \
-                different components labels, call components_to_properties first".into()
-            ));
-        }
 
+    check_units_equal(
+        blocks_to_merge,
+        "can not move keys to properties if the blocks have different units",
+    )?;
+
+    let values = blocks_to_merge.iter().map(|(_, block)| block.values()).collect::<Vec<_>>();
+    check_components_equal(
+        &values,
+        keys,
+        "can not move keys to properties if the blocks have different components \
+        labels, call components_to_properties first",
+    )?;
+
+    for (_, block) in blocks_to_merge {
         if keys_to_move.is_some() && &block.values().properties != first_property_labels {
             // TODO: this might be possible but also pretty slow. It would
             // requires to lookup the position of properties one by one in the
@@ -249,6 +287,18 @@ fn merge_blocks_along_properties(
         let mut new_gradient = first_block.values().data.create(&new_shape)?;
         let new_components = first_gradient.components.to_vec();
 
+        let gradients = blocks_to_merge.iter()
+            .map(|(_, block)| block.gradient(parameter).expect("missing gradient"))
+            .collect::<Vec<_>>();
+        check_components_equal(
+            &gradients,
+            keys,
+            &format!(
+                "can not move keys to properties if the gradients with respect to \
+                '{}' have different components labels", parameter
+            ),
+        )?;
+
         for (((_, block), samples_mapping), property_range) in blocks_to_merge.iter().zip(&samples_mappings).zip(&property_ranges) {
             if property_range.is_none() {
                 continue;
@@ -256,7 +306,6 @@ fn merge_blocks_along_properties(
             let property_range = property_range.as_ref().unwrap();
 
             let gradient = block.gradient(parameter).expect("missing gradient");
-            debug_assert!(*gradient.components == *new_components);
 
             let mut samples_to_move = Vec::new();
             for (sample_i, grad_sample) in gradient.samples.iter().enumerate() {
@@ -286,5 +335,7 @@ fn merge_blocks_along_properties(
         ).expect("created invalid gradients");
     }
 
+    new_block.copy_units_from(first_block);
+
     return Ok(new_block);
 }
@@ -1,12 +1,13 @@
 use std::sync::Arc;
 
-use crate::labels::{Labels, LabelsBuilder};
-use crate::{Error, TensorBlock};
+use crate::labels::{Labels, LabelsBuilder, LabelValue};
+use crate::{Error, Progress, TensorBlock};
 
 use crate::data::eqs_sample_mapping_t;
 
 use super::TensorMap;
 use super::utils::{KeyAndBlock, remove_dimensions_from_keys, merge_samples, merge_gradient_samples};
+use super::utils::{check_components_equal, check_units_equal};
 
 impl TensorMap {
     /// Merge blocks with the same value for selected keys dimensions along the
@@ -29,6 +30,21 @@ impl TensorMap {
     /// This function is only implemented if all merged block have the same
     /// property labels.
     pub fn keys_to_samples(&self, keys_to_move: &Labels, sort_samples: bool) -> Result<TensorMap, Error> {
+        return self.keys_to_samples_with_progress(keys_to_move, sort_samples, Progress::none());
+    }
+
+    /// Same as [`TensorMap::keys_to_samples`], reporting the number of new
+    /// blocks created so far to `progress`, and checking for cancellation
+    /// before creating each new block.
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip_all, fields(blocks = self.blocks.len(), keys_to_move = ?keys_to_move.names())
+    ))]
+    pub fn keys_to_samples_with_progress(
+        &self,
+        keys_to_move: &Labels,
+        sort_samples: bool,
+        progress: Progress,
+    ) -> Result<TensorMap, Error> {
         if keys_to_move.count() > 0 {
             return Err(Error::InvalidParameter(
                 "user provided values for the keys to move is not yet implemented, \
@@ -41,6 +57,8 @@ impl TensorMap {
 
         let mut new_blocks = Vec::new();
         if splitted_keys.new_keys.count() == 1 {
+            progress.check()?;
+
             // create a single block with everything
             let blocks_to_merge = self.keys.iter()
                 .zip(&self.blocks)
@@ -54,14 +72,20 @@ impl TensorMap {
                 })
                 .collect::<Vec<_>>();
 
+            let keys = self.keys.iter().collect::<Vec<_>>();
             let block = merge_blocks_along_samples(
                 &blocks_to_merge,
+                &keys,
                 &names_to_move,
                 sort_samples,
             )?;
             new_blocks.push(block);
+            progress.report(1, 1);
         } else {
+            let n_new_blocks = splitted_keys.new_keys.count();
             for entry in splitted_keys.new_keys.iter() {
+                progress.check()?;
+
                 let mut selection = LabelsBuilder::new(splitted_keys.new_keys.names());
                 selection.add(entry)?;
 
@@ -79,38 +103,51 @@ impl TensorMap {
                     })
                     .collect::<Vec<_>>();
 
+                let keys = matching.iter().map(|&i| &self.keys[i]).collect::<Vec<_>>();
                 new_blocks.push(merge_blocks_along_samples(
                     &blocks_to_merge,
+                    &keys,
                     &names_to_move,
                     sort_samples,
                 )?);
+                progress.report(new_blocks.len(), n_new_blocks);
             }
         }
 
-        return TensorMap::new(splitted_keys.new_keys, new_blocks);
+        let mut tensor = TensorMap::new(splitted_keys.new_keys, new_blocks)?;
+        tensor.copy_metadata_from(self);
+
+        return Ok(tensor);
     }
 }
 
 /// Merge the given `blocks` along the sample axis.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(blocks = blocks_to_merge.len())))]
 fn merge_blocks_along_samples(
     blocks_to_merge: &[KeyAndBlock],
+    keys: &[&[LabelValue]],
     extracted_names: &[&str],
     sort_samples: bool,
 ) -> Result<TensorBlock, Error> {
     assert!(!blocks_to_merge.is_empty());
 
     let first_block = blocks_to_merge[0].1;
-    let first_components_label = &first_block.values().components;
     let first_properties_label = &first_block.values().properties;
 
-    for (_, block) in blocks_to_merge {
-        if &block.values().components != first_components_label {
-            return Err(Error::InvalidParameter(
-                "can not move keys to samples if the blocks have \
-                different components labels, call components_to_properties first".into()
-            ))
-        }
+    check_units_equal(
+        blocks_to_merge,
+        "can not move keys to samples if the blocks have different units",
+    )?;
+
+    let values = blocks_to_merge.iter().map(|(_, block)| block.values()).collect::<Vec<_>>();
+    check_components_equal(
+        &values,
+        keys,
+        "can not move keys to samples if the blocks have different components \
+        labels, call components_to_properties first",
+    )?;
 
+    for (_, block) in blocks_to_merge {
         if &block.values().properties != first_properties_label {
             return Err(Error::InvalidParameter(
                 "can not move keys to samples if the blocks have \
@@ -166,9 +203,20 @@ fn merge_blocks_along_samples(
         let mut new_gradient = first_block.values().data.create(&new_shape)?;
         let new_components = first_gradient.components.to_vec();
 
+        let gradients = blocks_to_merge.iter()
+            .map(|(_, block)| block.gradient(parameter).expect("missing gradient"))
+            .collect::<Vec<_>>();
+        check_components_equal(
+            &gradients,
+            keys,
+            &format!(
+                "can not move keys to samples if the gradients with respect to \
+                '{}' have different components labels", parameter
+            ),
+        )?;
+
         for ((_, block), samples_mapping) in blocks_to_merge.iter().zip(&samples_mappings) {
             let gradient = block.gradient(parameter).expect("missing gradient");
-            debug_assert!(*gradient.components == *new_components);
 
             let mut samples_to_move = Vec::new();
             for (sample_i, grad_sample) in gradient.samples.iter().enumerate() {
@@ -198,5 +246,7 @@ fn merge_blocks_along_samples(
         ).expect("created invalid gradients");
     }
 
+    new_block.copy_units_from(first_block);
+
     return Ok(new_block);
 }
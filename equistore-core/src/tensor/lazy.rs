@@ -0,0 +1,505 @@
+use std::sync::Arc;
+
+use crate::{Error, BasicBlock, TensorBlock, eqs_array_t};
+
+use super::{TensorMap, MissingGradients};
+
+/// Unary element-wise operation in a [`LazyTensorMap`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    /// `x + value`
+    AddScalar(f64),
+    /// `x * value`
+    MultiplyScalar(f64),
+    /// `x ^ value`
+    Pow(f64),
+    /// `|x|`
+    Abs,
+    /// `sqrt(x)`
+    Sqrt,
+    /// `exp(x)`
+    Exp,
+    /// `ln(x)`
+    Log,
+}
+
+impl UnaryOp {
+    /// Apply this operation to `x`, and to the derivative `dx` of `x` with
+    /// respect to some parameter.
+    fn apply(self, x: f64, dx: f64) -> (f64, f64) {
+        match self {
+            UnaryOp::AddScalar(value) => (x + value, dx),
+            UnaryOp::MultiplyScalar(value) => (x * value, dx * value),
+            UnaryOp::Pow(value) => (x.powf(value), value * x.powf(value - 1.0) * dx),
+            UnaryOp::Abs => (x.abs(), x.signum() * dx),
+            UnaryOp::Sqrt => {
+                let sqrt = x.sqrt();
+                (sqrt, 0.5 * dx / sqrt)
+            },
+            UnaryOp::Exp => {
+                let exp = x.exp();
+                (exp, exp * dx)
+            },
+            UnaryOp::Log => (x.ln(), dx / x),
+        }
+    }
+}
+
+/// Operations recorded in a [`LazyTensorMap`]. Binary operations store the
+/// other tensor map, and the position of the block to use in it for each block
+/// of the source tensor map.
+#[derive(Debug, Clone)]
+enum LazyOp<'a> {
+    Unary(UnaryOp),
+    Add(&'a TensorMap, Vec<usize>),
+    Multiply(&'a TensorMap, Vec<usize>),
+}
+
+/// Lazily evaluated chain of element-wise operations on a `TensorMap`.
+///
+/// Operations are only recorded when calling the different functions, and
+/// nothing is computed until [`LazyTensorMap::compute`] or
+/// [`LazyTensorMap::compute_block`] is called. All the recorded operations are
+/// then applied in a single pass over the data, without allocating
+/// intermediary arrays. Gradients are propagated through all operations.
+///
+/// Evaluation requires access to the data through `eqs_array_t.data`.
+#[derive(Debug, Clone)]
+pub struct LazyTensorMap<'a> {
+    source: &'a TensorMap,
+    operations: Vec<LazyOp<'a>>,
+    missing_gradients: MissingGradients,
+    broadcast_keys: bool,
+}
+
+impl<'a> LazyTensorMap<'a> {
+    /// Start a new chain of lazy operations from the given `source`
+    pub fn new(source: &'a TensorMap) -> LazyTensorMap<'a> {
+        LazyTensorMap {
+            source,
+            operations: Vec::new(),
+            missing_gradients: MissingGradients::Error,
+            broadcast_keys: false,
+        }
+    }
+
+    /// Set the policy used by the binary operations recorded after this call
+    /// when only some of the tensor maps contain gradients with respect to a
+    /// given parameter. The default is `MissingGradients::Error`.
+    ///
+    /// With `MissingGradients::Zero`, the tensor maps without a gradient are
+    /// treated as if they had a zero-filled gradient with the same metadata as
+    /// the other tensor maps. With `MissingGradients::Drop`, only the
+    /// gradients present in all tensor maps are kept.
+    #[must_use]
+    pub fn missing_gradients(mut self, policy: MissingGradients) -> LazyTensorMap<'a> {
+        self.missing_gradients = policy;
+        self
+    }
+
+    /// Allow the binary operations recorded after this call to use tensor maps
+    /// whose keys only contain a subset of the keys dimensions of the source
+    /// tensor map. Each block of the source is then combined with the block
+    /// of the other tensor map with the same values for the common keys
+    /// dimensions, and the same block of the other tensor map can be used
+    /// multiple times. The default is `false`, requiring the same keys.
+    #[must_use]
+    pub fn broadcast_keys(mut self, broadcast: bool) -> LazyTensorMap<'a> {
+        self.broadcast_keys = broadcast;
+        self
+    }
+
+    /// Add a unary element-wise operation to the chain
+    #[must_use]
+    pub fn unary(mut self, operation: UnaryOp) -> LazyTensorMap<'a> {
+        self.operations.push(LazyOp::Unary(operation));
+        self
+    }
+
+    /// Add `other` to the current result. `other` must have exactly the same
+    /// metadata as the source tensor map, except for missing gradients if
+    /// allowed by [`LazyTensorMap::missing_gradients`] and keys if allowed by
+    /// [`LazyTensorMap::broadcast_keys`].
+    pub fn add(mut self, other: &'a TensorMap) -> Result<LazyTensorMap<'a>, Error> {
+        let positions = self.other_positions(other)?;
+        check_same_metadata(self.source, other, &positions, self.missing_gradients)?;
+        self.operations.push(LazyOp::Add(other, positions));
+        Ok(self)
+    }
+
+    /// Multiply the current result by `other`. `other` must have exactly the
+    /// same metadata as the source tensor map, except for missing gradients if
+    /// allowed by [`LazyTensorMap::missing_gradients`] and keys if allowed by
+    /// [`LazyTensorMap::broadcast_keys`].
+    pub fn multiply(mut self, other: &'a TensorMap) -> Result<LazyTensorMap<'a>, Error> {
+        let positions = self.other_positions(other)?;
+        check_same_metadata(self.source, other, &positions, self.missing_gradients)?;
+        self.operations.push(LazyOp::Multiply(other, positions));
+        Ok(self)
+    }
+
+    /// Evaluate all operations for the block at the given `index`
+    pub fn compute_block(&self, index: usize) -> Result<TensorBlock, Error> {
+        let block = self.source.blocks().get(index).ok_or_else(|| Error::InvalidParameter(format!(
+            "block index {} is out of bounds for a tensor map with {} blocks",
+            index, self.source.blocks().len()
+        )))?;
+
+        let values = block.values();
+        let mut new_block = TensorBlock::new(
+            self.evaluate(index, values, None)?,
+            Arc::clone(&values.samples),
+            values.components.to_vec(),
+            Arc::clone(&values.properties),
+        )?;
+
+        for (parameter, gradient) in self.gradients(index)? {
+            new_block.add_gradient(
+                parameter,
+                self.evaluate(index, values, Some((parameter, gradient)))?,
+                Arc::clone(&gradient.samples),
+                gradient.components.to_vec(),
+            )?;
+        }
+
+        return Ok(new_block);
+    }
+
+    /// Evaluate all operations for all blocks, and return the resulting
+    /// `TensorMap`.
+    pub fn compute(&self) -> Result<TensorMap, Error> {
+        let mut blocks = Vec::with_capacity(self.source.blocks().len());
+        for index in 0..self.source.blocks().len() {
+            blocks.push(self.compute_block(index)?);
+        }
+
+        return TensorMap::new((**self.source.keys()).clone(), blocks);
+    }
+
+    /// Get the position of the block in `other` to combine with each block of
+    /// the source tensor map.
+    fn other_positions(&self, other: &TensorMap) -> Result<Vec<usize>, Error> {
+        let keys = self.source.keys();
+        if !self.broadcast_keys {
+            if keys != other.keys() {
+                return Err(Error::InvalidParameter(
+                    "lazy binary operations require both tensor maps to have the same keys".into()
+                ));
+            }
+            return Ok((0..keys.count()).collect());
+        }
+
+        let mut dimensions = Vec::new();
+        for name in other.keys().names() {
+            match keys.names().iter().position(|n| *n == name) {
+                Some(dimension) => dimensions.push(dimension),
+                None => return Err(Error::InvalidParameter(format!(
+                    "can not broadcast keys in lazy binary operations: '{}' \
+                    is not one of the keys dimensions of the source tensor map", name
+                ))),
+            }
+        }
+
+        let mut positions = Vec::with_capacity(keys.count());
+        for key in keys.iter() {
+            let common = dimensions.iter().map(|&d| key[d]).collect::<Vec<_>>();
+            if let Some(position) = other.keys().position(&common) {
+                positions.push(position);
+            } else {
+                let common = common.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                return Err(Error::InvalidParameter(format!(
+                    "can not broadcast keys in lazy binary operations: \
+                    missing block with key ({}) in the other tensor map",
+                    common.join(", ")
+                )));
+            }
+        }
+
+        return Ok(positions);
+    }
+
+    /// Get the gradients in the result for the block at `index`, together with
+    /// the gradient block defining their metadata. Depending on
+    /// `self.missing_gradients`, this contains either all the gradients
+    /// present in at least one tensor map or only the ones present in all of
+    /// them.
+    fn gradients(&self, index: usize) -> Result<Vec<(&'a str, &'a BasicBlock)>, Error> {
+        let mut blocks = vec![&self.source.blocks()[index]];
+        for operation in &self.operations {
+            if let LazyOp::Add(other, positions) | LazyOp::Multiply(other, positions) = operation {
+                blocks.push(&other.blocks()[positions[index]]);
+            }
+        }
+
+        let mut gradients: Vec<(&str, &BasicBlock)> = Vec::new();
+        for block in &blocks {
+            for parameter in block.gradient_parameters_c() {
+                let parameter = parameter.as_str();
+                let gradient = block.gradient(parameter).expect("missing gradient");
+                match gradients.iter().find(|(p, _)| *p == parameter) {
+                    Some((_, existing)) => {
+                        if existing.samples != gradient.samples || existing.components != gradient.components {
+                            return Err(Error::InvalidParameter(format!(
+                                "lazy binary operations require all gradients with \
+                                respect to '{}' to have the same metadata, but block \
+                                {} is different", parameter, index
+                            )));
+                        }
+                    }
+                    None => gradients.push((parameter, gradient)),
+                }
+            }
+        }
+
+        if self.missing_gradients != MissingGradients::Zero {
+            gradients.retain(|(parameter, _)| blocks.iter().all(|b| b.gradient(parameter).is_some()));
+        }
+
+        return Ok(gradients);
+    }
+
+    /// Evaluate the data for either the values (if `gradient` is `None`) or
+    /// the gradient with respect to a parameter of the block at `index`. The
+    /// gradient block is used for metadata, and zeros are used for the data of
+    /// tensor maps without this gradient.
+    fn evaluate(&self, index: usize, values: &BasicBlock, gradient: Option<(&str, &BasicBlock)>) -> Result<eqs_array_t, Error> {
+        let values_data = values.data.contiguous_data()?;
+        let values_inner = values_data.len().checked_div(values.samples.count()).unwrap_or(0);
+
+        let basic_block = match gradient {
+            None => values,
+            Some((_, gradient)) => gradient,
+        };
+        let shape = basic_block.data.shape()?.to_vec();
+        let zeros = || vec![0.0; shape.iter().product()];
+
+        // collect the data of all other tensor maps used in binary operations
+        let mut others = Vec::new();
+        for operation in &self.operations {
+            match operation {
+                LazyOp::Unary(_) => others.push(None),
+                LazyOp::Add(other, positions) | LazyOp::Multiply(other, positions) => {
+                    let other = &other.blocks()[positions[index]];
+                    let other_gradient = match gradient {
+                        Some((parameter, _)) => match other.gradient(parameter) {
+                            Some(other_gradient) => Some(other_gradient.data.contiguous_data()?),
+                            None => Some(zeros().into()),
+                        },
+                        None => None,
+                    };
+                    others.push(Some((other.values().data.contiguous_data()?, other_gradient)));
+                }
+            }
+        }
+
+        let mut output = basic_block.data.create(&shape)?;
+        let output_data = output.data_mut()?;
+        let input_data = match gradient {
+            None => values.data.contiguous_data()?,
+            Some((parameter, _)) => match self.source.blocks()[index].gradient(parameter) {
+                Some(source_gradient) => source_gradient.data.contiguous_data()?,
+                None => zeros().into(),
+            },
+        };
+
+        let inner = input_data.len().checked_div(basic_block.samples.count()).unwrap_or(0);
+        for (row, sample) in basic_block.samples.iter().enumerate() {
+            // position of the corresponding values sample
+            let values_row = if gradient.is_some() { sample[0].usize() } else { row };
+
+            for j in 0..inner {
+                let i = row * inner + j;
+                // gradients can have additional components before the values'
+                // components, so we need to wrap around
+                let values_i = values_row * values_inner + j % values_inner;
+
+                let mut x = values_data[values_i];
+                let mut dx = input_data[i];
+                for (operation, other) in self.operations.iter().zip(&others) {
+                    match (operation, other) {
+                        (LazyOp::Unary(op), _) => {
+                            let (new_x, new_dx) = op.apply(x, dx);
+                            x = new_x;
+                            dx = new_dx;
+                        },
+                        (LazyOp::Add(..), Some((other_values, other_gradient))) => {
+                            x += other_values[values_i];
+                            dx += other_gradient.as_ref().map_or(other_values[values_i], |g| g[i]);
+                        },
+                        (LazyOp::Multiply(..), Some((other_values, other_gradient))) => {
+                            let y = other_values[values_i];
+                            dx = match other_gradient {
+                                Some(dy) => dx * y + x * dy[i],
+                                None => dx * y,
+                            };
+                            x *= y;
+                        },
+                        _ => unreachable!(),
+                    }
+                }
+
+                output_data[i] = if gradient.is_some() { dx } else { x };
+            }
+        }
+
+        return Ok(output);
+    }
+}
+
+/// Check that the blocks of `first` and the corresponding blocks of `second`
+/// (at the given `positions`) have the same labels and gradients. Gradients
+/// present in only one of them are allowed unless `missing_gradients` is
+/// `MissingGradients::Error`.
+fn check_same_metadata(first: &TensorMap, second: &TensorMap, positions: &[usize], missing_gradients: MissingGradients) -> Result<(), Error> {
+    for (block_i, first) in first.blocks().iter().enumerate() {
+        let second = &second.blocks()[positions[block_i]];
+        let mut same = first.values().samples == second.values().samples
+            && first.values().components == second.values().components
+            && first.values().properties == second.values().properties;
+
+        for (parameter, gradient) in first.gradients() {
+            same = same && match second.gradient(parameter) {
+                Some(other) => gradient.samples == other.samples && gradient.components == other.components,
+                None => missing_gradients != MissingGradients::Error,
+            };
+        }
+
+        if missing_gradients == MissingGradients::Error {
+            same = same && first.gradients().len() == second.gradients().len();
+        }
+
+        if !same {
+            return Err(Error::InvalidParameter(format!(
+                "lazy binary operations require both tensor maps to have the \
+                same metadata, but block {} is different", block_i
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::TestArray;
+
+    use super::*;
+    use super::super::utils::example_labels;
+
+    fn tensor(data: Vec<f64>, gradient: Vec<f64>) -> TensorMap {
+        let mut block = values_only(data);
+
+        block.add_gradient(
+            "parameter",
+            TestArray::from_data(vec![1, 1], gradient),
+            example_labels(vec!["sample"], vec![[1]]),
+            vec![],
+        ).unwrap();
+
+        let keys = example_labels(vec!["key"], vec![[0]]);
+        return TensorMap::new((*keys).clone(), vec![block]).unwrap();
+    }
+
+    fn values_only(data: Vec<f64>) -> TensorBlock {
+        return TensorBlock::new(
+            TestArray::from_data(vec![2, 1], data),
+            example_labels(vec!["samples"], vec![[0], [1]]),
+            vec![],
+            example_labels(vec!["properties"], vec![[0]]),
+        ).unwrap();
+    }
+
+    #[test]
+    fn fused_operations() {
+        let first = tensor(vec![1.0, 2.0], vec![1.0]);
+        let second = tensor(vec![3.0, 4.0], vec![2.0]);
+
+        let result = LazyTensorMap::new(&first)
+            .unary(UnaryOp::MultiplyScalar(2.0))
+            .multiply(&second).unwrap()
+            .unary(UnaryOp::AddScalar(1.0))
+            .compute().unwrap();
+
+        let block = &result.blocks()[0];
+        assert_eq!(block.values().data.data().unwrap(), [7.0, 17.0]);
+        // d(2 * x * y + 1) = 2 * (dx * y + x * dy) = 2 * (1 * 4 + 2 * 2)
+        assert_eq!(block.gradient("parameter").unwrap().data.data().unwrap(), [16.0]);
+    }
+
+    #[test]
+    fn missing_gradients() {
+        let first = tensor(vec![1.0, 2.0], vec![1.0]);
+        let keys = example_labels(vec!["key"], vec![[0]]);
+        let second = TensorMap::new((*keys).clone(), vec![values_only(vec![3.0, 4.0])]).unwrap();
+
+        let error = LazyTensorMap::new(&first).add(&second).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: lazy binary operations require both tensor maps \
+            to have the same metadata, but block 0 is different"
+        );
+
+        let result = LazyTensorMap::new(&second)
+            .missing_gradients(MissingGradients::Zero)
+            .multiply(&first).unwrap()
+            .compute().unwrap();
+
+        let block = &result.blocks()[0];
+        assert_eq!(block.values().data.data().unwrap(), [3.0, 8.0]);
+        // d(x * y) = dx * y + x * dy = 0 * 2 + 4 * 1
+        assert_eq!(block.gradient("parameter").unwrap().data.data().unwrap(), [4.0]);
+
+        let result = LazyTensorMap::new(&first)
+            .missing_gradients(MissingGradients::Drop)
+            .add(&second).unwrap()
+            .compute().unwrap();
+
+        let block = &result.blocks()[0];
+        assert_eq!(block.values().data.data().unwrap(), [4.0, 6.0]);
+        assert!(block.gradients().is_empty());
+    }
+
+    #[test]
+    fn broadcast_keys() {
+        let keys = example_labels(vec!["key", "l"], vec![[0, 0], [1, 0], [0, 1]]);
+        let blocks = vec![values_only(vec![1.0, 2.0]), values_only(vec![3.0, 4.0]), values_only(vec![5.0, 6.0])];
+        let first = TensorMap::new((*keys).clone(), blocks).unwrap();
+
+        let keys = example_labels(vec!["key"], vec![[1], [0]]);
+        let weights = TensorMap::new((*keys).clone(), vec![values_only(vec![10.0, 20.0]), values_only(vec![2.0, 3.0])]).unwrap();
+
+        let error = LazyTensorMap::new(&first).multiply(&weights).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: lazy binary operations require both tensor maps to have the same keys"
+        );
+
+        let result = LazyTensorMap::new(&first)
+            .broadcast_keys(true)
+            .multiply(&weights).unwrap()
+            .compute().unwrap();
+
+        assert_eq!(result.keys(), first.keys());
+        assert_eq!(result.blocks()[0].values().data.data().unwrap(), [2.0, 6.0]);
+        assert_eq!(result.blocks()[1].values().data.data().unwrap(), [30.0, 80.0]);
+        assert_eq!(result.blocks()[2].values().data.data().unwrap(), [10.0, 18.0]);
+
+        let keys = example_labels(vec!["key"], vec![[0]]);
+        let missing = TensorMap::new((*keys).clone(), vec![values_only(vec![1.0, 1.0])]).unwrap();
+        let error = LazyTensorMap::new(&first).broadcast_keys(true).add(&missing).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: can not broadcast keys in lazy binary operations: \
+            missing block with key (1) in the other tensor map"
+        );
+
+        let keys = example_labels(vec!["other"], vec![[0]]);
+        let other = TensorMap::new((*keys).clone(), vec![values_only(vec![1.0, 1.0])]).unwrap();
+        let error = LazyTensorMap::new(&first).broadcast_keys(true).add(&other).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: can not broadcast keys in lazy binary operations: \
+            'other' is not one of the keys dimensions of the source tensor map"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use crate::{Error, Labels, BasicBlock, TensorBlock};
+use crate::data::compressed_size;
+
+use super::TensorMap;
+
+/// Estimation of the memory used by some part of a `TensorMap`, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// memory used by the values data arrays, assuming they contain 64-bit
+    /// floating point values
+    pub data: usize,
+    /// memory used to store the values (and names) of the labels
+    pub labels_values: usize,
+    /// memory used by the hash maps to lookup the position of labels entries
+    pub labels_positions: usize,
+    /// memory used by the gradients, including both data and labels
+    pub gradients: usize,
+}
+
+impl MemoryUsage {
+    /// Get the total memory used, in bytes
+    pub fn total(&self) -> usize {
+        self.data + self.labels_values + self.labels_positions + self.gradients
+    }
+}
+
+impl std::ops::AddAssign for MemoryUsage {
+    fn add_assign(&mut self, other: MemoryUsage) {
+        self.data += other.data;
+        self.labels_values += other.labels_values;
+        self.labels_positions += other.labels_positions;
+        self.gradients += other.gradients;
+    }
+}
+
+/// Breakdown of the memory used by a `TensorMap`, created by
+/// [`TensorMap::memory_usage`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TensorMapMemoryUsage {
+    /// memory used by the keys
+    pub keys: MemoryUsage,
+    /// memory used by each block, in the same order as the blocks
+    pub blocks: Vec<MemoryUsage>,
+    /// memory used by the whole tensor map. `Labels` shared between multiple
+    /// blocks are only counted once here, so this can be smaller than the sum
+    /// of all blocks.
+    pub total: MemoryUsage,
+}
+
+/// Keep track of `Labels` that have already been counted
+#[derive(Default)]
+struct SeenLabels(BTreeSet<*const Labels>);
+
+impl SeenLabels {
+    /// Add the memory used by `labels` to `usage` if it was not already seen
+    fn count(&mut self, labels: &Arc<Labels>, usage: &mut MemoryUsage) {
+        if self.0.insert(Arc::as_ptr(labels)) {
+            let (values, positions) = labels.memory_usage();
+            usage.labels_values += values;
+            usage.labels_positions += positions;
+        }
+    }
+}
+
+fn data_size(block: &BasicBlock) -> Result<usize, Error> {
+    if let Some(size) = compressed_size(&block.data) {
+        return Ok(size);
+    }
+
+    let count = block.data.shape()?.iter().product::<usize>();
+    return Ok(count * std::mem::size_of::<f64>());
+}
+
+fn basic_block_usage(block: &BasicBlock, seen: &mut SeenLabels) -> Result<MemoryUsage, Error> {
+    let mut usage = MemoryUsage {
+        data: data_size(block)?,
+        ..Default::default()
+    };
+
+    seen.count(&block.samples, &mut usage);
+    for component in &block.components {
+        seen.count(component, &mut usage);
+    }
+    seen.count(&block.properties, &mut usage);
+
+    return Ok(usage);
+}
+
+fn block_usage(block: &TensorBlock, seen: &mut SeenLabels) -> Result<MemoryUsage, Error> {
+    let mut usage = basic_block_usage(block.values(), seen)?;
+    for gradient in block.gradients().values() {
+        usage.gradients += basic_block_usage(gradient, seen)?.total();
+    }
+
+    return Ok(usage);
+}
+
+impl TensorMap {
+    /// Estimate the memory used by this `TensorMap`, with a breakdown by block
+    /// and by kind of data (data arrays, labels values, labels positions hash
+    /// maps, and gradients).
+    ///
+    /// The size of data arrays is estimated from their shape, assuming they
+    /// contain 64-bit floating point values: `eqs_array_t` only gives access
+    /// to the data as `f64`, and does not expose the actual element type. The
+    /// memory used by arrays storing other types (e.g. 32-bit floating point
+    /// torch tensors) is over- or under-estimated accordingly. For arrays
+    /// compressed with [`TensorBlock::compress`], the size of the compressed
+    /// data is used instead.
+    pub fn memory_usage(&self) -> Result<TensorMapMemoryUsage, Error> {
+        let mut keys = MemoryUsage::default();
+        let (values, positions) = self.keys.memory_usage();
+        keys.labels_values = values;
+        keys.labels_positions = positions;
+
+        let mut total = keys;
+        let mut total_seen = SeenLabels::default();
+        let mut blocks = Vec::with_capacity(self.blocks.len());
+        for block in &self.blocks {
+            blocks.push(block_usage(block, &mut SeenLabels::default())?);
+            total += block_usage(block, &mut total_seen)?;
+        }
+
+        return Ok(TensorMapMemoryUsage { keys, blocks, total });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::TestArray;
+
+    use super::*;
+    use super::super::utils::example_labels;
+
+    #[test]
+    fn memory_usage() {
+        let properties = example_labels(vec!["properties"], vec![[0], [1], [2]]);
+        let mut blocks = Vec::new();
+        for _ in 0..2 {
+            blocks.push(TensorBlock::new(
+                TestArray::new(vec![2, 3]),
+                example_labels(vec!["samples"], vec![[0], [1]]),
+                vec![],
+                Arc::clone(&properties),
+            ).unwrap());
+        }
+
+        let keys = example_labels(vec!["key"], vec![[0], [1]]);
+        let tensor = TensorMap::new((*keys).clone(), blocks).unwrap();
+        let usage = tensor.memory_usage().unwrap();
+
+        assert_eq!(usage.blocks.len(), 2);
+        assert_eq!(usage.blocks[0].data, 6 * 8);
+        assert_eq!(usage.blocks[0].gradients, 0);
+        assert_eq!(usage.total.data, 2 * 6 * 8);
+
+        // properties are shared between blocks, and only counted once
+        assert!(usage.total.labels_values < usage.keys.labels_values + 2 * usage.blocks[0].labels_values);
+    }
+
+    #[test]
+    fn compressed_blocks() {
+        let mut blocks = Vec::new();
+        for _ in 0..2 {
+            blocks.push(TensorBlock::new(
+                TestArray::new(vec![2, 30]),
+                example_labels(vec!["samples"], vec![[0], [1]]),
+                vec![],
+                example_labels(vec!["properties"], (0..30).map(|i| [i]).collect()),
+            ).unwrap());
+        }
+
+        let keys = example_labels(vec!["key"], vec![[0], [1]]);
+        let mut tensor = TensorMap::new((*keys).clone(), blocks).unwrap();
+
+        let mut selection = crate::LabelsBuilder::new(vec!["key"]);
+        selection.add(&[1]).unwrap();
+        tensor.compress_blocks(&selection.finish()).unwrap();
+        assert!(!tensor.blocks()[0].is_compressed());
+        assert!(tensor.blocks()[1].is_compressed());
+
+        let usage = tensor.memory_usage().unwrap();
+        assert_eq!(usage.blocks[0].data, 60 * 8);
+        assert!(usage.blocks[1].data < 60 * 8);
+
+        tensor.decompress_blocks().unwrap();
+        assert!(!tensor.blocks()[1].is_compressed());
+        assert_eq!(tensor.blocks()[1].values().data.data().unwrap(), [0.0; 60]);
+        assert_eq!(tensor.memory_usage().unwrap().blocks[1].data, 60 * 8);
+    }
+}
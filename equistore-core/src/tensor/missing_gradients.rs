@@ -0,0 +1,232 @@
+use std::sync::Arc;
+
+use crate::{LabelsBuilder, Error};
+use super::TensorMap;
+
+/// What to do with gradients which are present in some blocks of a
+/// `TensorMap`, but missing from others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingGradients {
+    /// Return an error if some blocks are missing gradients
+    Error,
+    /// Add the missing gradients, filled with zeros. Since gradients are
+    /// sparse, the new gradients do not contain any sample.
+    Zero,
+    /// Remove gradients which are not present in all blocks
+    Drop,
+}
+
+impl std::str::FromStr for MissingGradients {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<MissingGradients, Error> {
+        match value {
+            "error" => Ok(MissingGradients::Error),
+            "zero" => Ok(MissingGradients::Zero),
+            "drop" => Ok(MissingGradients::Drop),
+            _ => Err(Error::InvalidParameter(format!(
+                "invalid value for missing gradients: expected 'error', 'zero' \
+                or 'drop', got '{}'", value
+            ))),
+        }
+    }
+}
+
+/// Make all the `tensors` carry gradients with respect to the same
+/// parameters, returning new tensor maps.
+///
+/// Inside a single tensor map all blocks have the same gradients, but
+/// different tensor maps can contain different gradients. The gradients which
+/// are only defined in some of the tensor maps are handled according to
+/// `missing`. With `MissingGradients::Zero`, the new gradients use the same
+/// samples dimensions and gradient-specific components as the first tensor map
+/// containing this gradient.
+pub fn unify_gradients(tensors: &[&TensorMap], missing: MissingGradients) -> Result<Vec<TensorMap>, Error> {
+    // all the gradient parameters, in the order they are first seen
+    let mut parameters = Vec::<&str>::new();
+    for tensor in tensors {
+        for parameter in gradient_parameters(tensor) {
+            if !parameters.contains(&parameter) {
+                parameters.push(parameter);
+            }
+        }
+    }
+
+    let common = parameters.iter()
+        .copied()
+        .filter(|p| tensors.iter().all(|t| gradient_parameters(t).contains(p)))
+        .collect::<Vec<_>>();
+
+    let mut results = Vec::with_capacity(tensors.len());
+    for (tensor_i, tensor) in tensors.iter().enumerate() {
+        let present = gradient_parameters(tensor);
+
+        let mut blocks = Vec::with_capacity(tensor.blocks.len());
+        for block in &tensor.blocks {
+            blocks.push(block.try_clone()?);
+        }
+
+        for &parameter in &parameters {
+            if present.contains(&parameter) {
+                if missing == MissingGradients::Drop && !common.contains(&parameter) {
+                    for block in &mut blocks {
+                        block.remove_gradient(parameter);
+                    }
+                }
+                continue;
+            }
+
+            match missing {
+                MissingGradients::Error => {
+                    return Err(Error::InvalidParameter(format!(
+                        "tensor map {} does not have gradients with respect to \
+                        '{}', which are present in other tensor maps",
+                        tensor_i, parameter
+                    )));
+                }
+                MissingGradients::Drop => {}
+                MissingGradients::Zero => {
+                    let (reference, gradient) = tensors.iter()
+                        .filter_map(|t| t.blocks.first())
+                        .find_map(|b| b.gradient(parameter).map(|g| (b, g)))
+                        .expect("missing gradient parameter");
+
+                    // keep the gradient-specific components of the reference,
+                    // and use the values components of each block
+                    let n_extra = gradient.components.len() - reference.values().components.len();
+                    let samples = Arc::new(LabelsBuilder::new(gradient.samples.names()).finish());
+
+                    for block in &mut blocks {
+                        let mut components = gradient.components[..n_extra].to_vec();
+                        components.extend(block.values().components.iter().cloned());
+
+                        let mut shape = vec![0];
+                        shape.extend(components.iter().map(|c| c.count()));
+                        shape.push(block.values().properties.count());
+                        let data = block.values().data.create(&shape)?;
+
+                        block.add_gradient(parameter, data, Arc::clone(&samples), components)?;
+                    }
+                }
+            }
+        }
+
+        let mut result = TensorMap::new((*tensor.keys).clone(), blocks)?;
+        result.copy_metadata_from(tensor);
+        results.push(result);
+    }
+
+    return Ok(results);
+}
+
+/// Get the gradient parameters of a tensor map, which are the same for all
+/// blocks
+fn gradient_parameters(tensor: &TensorMap) -> Vec<&str> {
+    tensor.blocks.first().map_or(Vec::new(), |block| {
+        block.gradient_parameters_c().iter().map(|p| p.as_str()).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::TestArray;
+    use crate::TensorBlock;
+
+    use super::*;
+    use super::super::utils::example_labels;
+
+    fn tensors() -> (TensorMap, TensorMap) {
+        let keys = example_labels(vec!["key"], vec![[0]]);
+
+        let mut block = TensorBlock::new(
+            TestArray::new(vec![2, 3, 1]),
+            example_labels(vec!["samples"], vec![[0], [1]]),
+            vec![example_labels(vec!["component"], vec![[0], [1], [2]])],
+            example_labels(vec!["properties"], vec![[0]]),
+        ).unwrap();
+        block.add_gradient(
+            "positions",
+            TestArray::new(vec![1, 3, 3, 1]),
+            example_labels(vec!["sample", "atom"], vec![[1, 4]]),
+            vec![
+                example_labels(vec!["xyz"], vec![[0], [1], [2]]),
+                example_labels(vec!["component"], vec![[0], [1], [2]]),
+            ],
+        ).unwrap();
+        block.add_gradient(
+            "cell",
+            TestArray::new(vec![2, 3, 1]),
+            example_labels(vec!["sample"], vec![[0], [1]]),
+            vec![example_labels(vec!["component"], vec![[0], [1], [2]])],
+        ).unwrap();
+        let first = TensorMap::new((*keys).clone(), vec![block]).unwrap();
+
+        let mut block = TensorBlock::new(
+            TestArray::new(vec![1, 1, 2]),
+            example_labels(vec!["samples"], vec![[0]]),
+            vec![example_labels(vec!["component"], vec![[0]])],
+            example_labels(vec!["properties"], vec![[0], [1]]),
+        ).unwrap();
+        block.add_gradient(
+            "cell",
+            TestArray::new(vec![1, 1, 2]),
+            example_labels(vec!["sample"], vec![[0]]),
+            vec![example_labels(vec!["component"], vec![[0]])],
+        ).unwrap();
+        let second = TensorMap::new((*keys).clone(), vec![block]).unwrap();
+
+        return (first, second);
+    }
+
+    fn parameters(tensor: &TensorMap) -> Vec<&str> {
+        tensor.blocks()[0].gradient_parameters_c().iter().map(|p| p.as_str()).collect()
+    }
+
+    #[test]
+    fn error() {
+        let (first, second) = tensors();
+        let error = unify_gradients(&[&first, &second], MissingGradients::Error).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: tensor map 1 does not have gradients with \
+            respect to 'positions', which are present in other tensor maps"
+        );
+
+        let result = unify_gradients(&[&first, &first], MissingGradients::Error).unwrap();
+        assert_eq!(parameters(&result[1]), ["positions", "cell"]);
+
+        let error = "nothing".parse::<MissingGradients>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: invalid value for missing gradients: expected \
+            'error', 'zero' or 'drop', got 'nothing'"
+        );
+    }
+
+    #[test]
+    fn zero() {
+        let (first, second) = tensors();
+        let result = unify_gradients(&[&first, &second], MissingGradients::Zero).unwrap();
+
+        assert_eq!(parameters(&result[0]), ["positions", "cell"]);
+        assert_eq!(parameters(&result[1]), ["cell", "positions"]);
+
+        let gradient = result[1].blocks()[0].gradient("positions").unwrap();
+        assert_eq!(gradient.samples.names(), ["sample", "atom"]);
+        assert_eq!(gradient.samples.count(), 0);
+        assert_eq!(gradient.components.len(), 2);
+        assert_eq!(*gradient.components[0], *example_labels(vec!["xyz"], vec![[0], [1], [2]]));
+        assert_eq!(*gradient.components[1], *example_labels(vec!["component"], vec![[0]]));
+        assert_eq!(gradient.data.shape().unwrap(), [0, 3, 1, 2]);
+    }
+
+    #[test]
+    fn drop() {
+        let (first, second) = tensors();
+        let result = unify_gradients(&[&first, &second], MissingGradients::Drop).unwrap();
+
+        assert_eq!(parameters(&result[0]), ["cell"]);
+        assert_eq!(parameters(&result[1]), ["cell"]);
+        assert!(result[0].blocks()[0].gradient("positions").is_none());
+    }
+}
@@ -1,14 +1,43 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::CString;
 use std::sync::Arc;
 
+use crate::utils::ConstCString;
 use crate::{TensorBlock, BasicBlock};
-use crate::{Labels, Error};
-use crate::get_data_origin;
+use crate::{Labels, LabelsArena, Error};
+use crate::{get_data_origin, eqs_array_t};
 
 mod utils;
 
 mod keys_to_samples;
 mod keys_to_properties;
+mod canonicalize;
+mod fingerprint;
+mod sort_keys;
+mod drop_blocks;
+
+mod diff;
+pub use self::diff::{diff, DiffOptions};
+
+mod align;
+pub use self::align::{align_properties, intersect_samples};
+
+mod lazy;
+pub use self::lazy::{LazyTensorMap, UnaryOp};
+
+mod memory;
+pub use self::memory::MemoryUsage;
+
+mod missing_gradients;
+pub use self::missing_gradients::{unify_gradients, MissingGradients};
+
+mod view;
+
+mod zip;
+pub use self::zip::zip_maps;
+
+mod product;
+pub use self::product::tensor_product;
 
 
 /// A tensor map is the main user-facing struct of this library, and can store
@@ -20,11 +49,18 @@ mod keys_to_properties;
 /// It provides functions to merge blocks together by moving some of these keys
 /// to the samples or properties labels of the blocks, transforming the sparse
 /// representation of the data to a dense one.
+///
+/// A tensor map can also carry free-form string metadata (provenance,
+/// hyper-parameters, code version, *etc.*) as key/value pairs. This metadata is
+/// preserved when copying and serializing the tensor map.
 #[derive(Debug)]
 pub struct TensorMap {
     keys: Arc<Labels>,
     blocks: Vec<TensorBlock>,
-    // TODO: arbitrary tensor-level metadata? e.g. using `HashMap<String, String>`
+    // free-form metadata, with values stored as C-compatible strings
+    metadata: BTreeMap<String, ConstCString>,
+    // all the keys from `self.metadata`, as C-compatible strings
+    metadata_keys: Vec<ConstCString>,
 }
 
 fn check_labels_names(
@@ -164,11 +200,13 @@ impl TensorMap {
         Ok(TensorMap {
             keys: Arc::new(keys),
             blocks,
+            metadata: BTreeMap::new(),
+            metadata_keys: Vec::new(),
         })
     }
 
-    /// Try to copy this `TensorMap`. This can fail if we are unable to copy the
-    /// underlying `eqs_array_t` data array
+    /// Try to copy this `TensorMap`. Data arrays are shared with the new
+    /// tensor map, and only copied when one of them is modified.
     pub fn try_clone(&self) -> Result<TensorMap, Error> {
         let mut blocks = Vec::new();
         for block in &self.blocks {
@@ -177,7 +215,38 @@ impl TensorMap {
 
         return Ok(TensorMap {
             keys: Arc::clone(&self.keys),
-            blocks
+            blocks,
+            metadata: self.metadata.clone(),
+            metadata_keys: self.metadata_keys.clone(),
+        });
+    }
+
+    /// Create a new `TensorMap` with the same keys, metadata and gradients as
+    /// this one, using `new_values` as the values arrays of the blocks. There
+    /// must be one array for each block, with the same shape as the current
+    /// values (see [`TensorBlock::with_values`]).
+    pub fn with_values(&self, new_values: Vec<eqs_array_t>) -> Result<TensorMap, Error> {
+        if new_values.len() != self.blocks.len() {
+            return Err(Error::InvalidParameter(format!(
+                "expected {} values arrays, one for each block, got {}",
+                self.blocks.len(), new_values.len()
+            )));
+        }
+
+        let mut blocks = Vec::with_capacity(self.blocks.len());
+        for (block_i, (block, data)) in self.blocks.iter().zip(new_values).enumerate() {
+            let block = block.with_values(data).map_err(|error| match error {
+                Error::InvalidParameter(e) => Error::InvalidParameter(format!("block {}: {}", block_i, e)),
+                error => error,
+            })?;
+            blocks.push(block);
+        }
+
+        return Ok(TensorMap {
+            keys: Arc::clone(&self.keys),
+            blocks,
+            metadata: self.metadata.clone(),
+            metadata_keys: self.metadata_keys.clone(),
         });
     }
 
@@ -191,11 +260,102 @@ impl TensorMap {
         &mut self.blocks
     }
 
+    /// Move the values of all the labels in this `TensorMap` (keys, and
+    /// samples, components and properties of all blocks and gradients) to a
+    /// single buffer, sharing identical labels between blocks.
+    ///
+    /// This is opt-in, and reduces the number of allocations (and the memory
+    /// fragmentation) for tensor maps containing many small blocks. See
+    /// [`LabelsArena`] for more information.
+    pub fn compact_labels(&mut self) {
+        let mut arena = LabelsArena::new();
+        let mut indexes = vec![arena.add(&self.keys)];
+        for block in &mut self.blocks {
+            block.map_labels(|labels| {
+                indexes.push(arena.add(labels));
+                Arc::clone(labels)
+            });
+        }
+
+        let compacted = arena.finish();
+        let mut indexes = indexes.into_iter();
+        let mut next = || Arc::clone(&compacted[indexes.next().expect("missing labels")]);
+
+        self.keys = next();
+        for block in &mut self.blocks {
+            block.map_labels(|_| next());
+        }
+    }
+
     /// Get the keys defined in this `TensorMap`
     pub fn keys(&self) -> &Arc<Labels> {
         &self.keys
     }
 
+    /// Get an iterator over the `(key, value)` metadata pairs of this
+    /// `TensorMap`, sorted by key.
+    pub fn metadata(&self) -> impl Iterator<Item=(&str, &str)> {
+        self.metadata.iter().map(|(key, value)| (&**key, value.as_str()))
+    }
+
+    /// Get the metadata value associated with `key`, if any
+    pub fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(ConstCString::as_str)
+    }
+
+    /// Get the metadata value associated with `key` for the C API
+    pub fn get_metadata_c(&self, key: &str) -> Option<&ConstCString> {
+        self.metadata.get(key)
+    }
+
+    /// Get the list of metadata keys in this `TensorMap` for the C API, sorted
+    /// in lexicographic order
+    pub fn metadata_keys_c(&self) -> &[ConstCString] {
+        &self.metadata_keys
+    }
+
+    /// Set the metadata value associated with `key` to `value`, overwriting
+    /// any previous value. If `value` is `None`, the corresponding entry is
+    /// removed from the metadata.
+    pub fn set_metadata(&mut self, key: &str, value: Option<&str>) -> Result<(), Error> {
+        if key.is_empty() {
+            return Err(Error::InvalidParameter(
+                "metadata keys can not be empty".into()
+            ));
+        }
+
+        let c_key = CString::new(key).map_err(|_| Error::InvalidParameter(format!(
+            "metadata key '{}' can not contain NULL bytes", key
+        )))?;
+
+        match value {
+            Some(value) => {
+                let value = CString::new(value).map_err(|_| Error::InvalidParameter(format!(
+                    "the metadata value for '{}' can not contain NULL bytes", key
+                )))?;
+
+                if self.metadata.insert(key.to_owned(), ConstCString::new(value)).is_none() {
+                    self.metadata_keys.push(ConstCString::new(c_key));
+                    self.metadata_keys.sort_unstable_by(|a, b| a.as_c_str().cmp(b.as_c_str()));
+                }
+            }
+            None => {
+                if self.metadata.remove(key).is_some() {
+                    self.metadata_keys.retain(|k| k.as_c_str() != c_key.as_c_str());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copy all the metadata from `other` into this `TensorMap`, replacing
+    /// the existing metadata.
+    pub(crate) fn copy_metadata_from(&mut self, other: &TensorMap) {
+        self.metadata.clone_from(&other.metadata);
+        self.metadata_keys.clone_from(&other.metadata_keys);
+    }
+
     /// Get the index of blocks matching the given selection.
     ///
     /// The selection must contains a single entry, defining the requested key
@@ -248,8 +408,31 @@ impl TensorMap {
         return Ok(matching);
     }
 
+    /// Compress the data of all blocks matching the given `selection` (using
+    /// the same rules as [`TensorMap::blocks_matching`]) in memory, see
+    /// [`TensorBlock::compress`]. This reduces the memory used by rarely
+    /// accessed blocks, at the cost of decompressing them on first access.
+    pub fn compress_blocks(&mut self, selection: &Labels) -> Result<(), Error> {
+        for block_i in self.blocks_matching(selection)? {
+            self.blocks[block_i].compress()?;
+        }
+        return Ok(());
+    }
+
+    /// Convert the data of all compressed blocks back to the original array
+    /// type, see [`TensorBlock::decompress`].
+    pub fn decompress_blocks(&mut self) -> Result<(), Error> {
+        for block in &mut self.blocks {
+            block.decompress()?;
+        }
+        return Ok(());
+    }
+
     /// Move the given dimensions from the component labels to the property labels
     /// for each block in this `TensorMap`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        skip_all, fields(blocks = self.blocks.len(), dimensions = ?dimensions)
+    ))]
     pub fn components_to_properties(&self, dimensions: &[&str]) -> Result<TensorMap, Error> {
         let mut clone = self.try_clone()?;
 
@@ -268,7 +451,7 @@ impl TensorMap {
 
 #[cfg(test)]
 mod tests {
-    use crate::LabelsBuilder;
+    use crate::{LabelsBuilder, LabelValue};
     use crate::data::TestArray;
 
     use super::*;
@@ -458,4 +641,186 @@ mod tests {
             "invalid parameter: 'key_3' is not part of the keys for this tensor"
         );
     }
+
+    #[test]
+    fn compact_labels() {
+        let mut blocks = Vec::new();
+        for i in 0..3 {
+            let mut block = TensorBlock::new(
+                TestArray::from_data(vec![2, 1], vec![f64::from(i), 1.0]),
+                example_labels(vec!["samples"], vec![[i], [i + 3]]),
+                vec![],
+                example_labels(vec!["properties"], vec![[0]]),
+            ).unwrap();
+            block.add_gradient(
+                "parameter",
+                TestArray::new(vec![1, 1]),
+                example_labels(vec!["sample", "atom"], vec![[0, 2]]),
+                vec![],
+            ).unwrap();
+            blocks.push(block);
+        }
+        let keys = example_labels(vec!["key"], vec![[0], [1], [2]]);
+        let mut tensor = TensorMap::new((*keys).clone(), blocks).unwrap();
+
+        let first_properties = &tensor.blocks()[0].values().properties;
+        assert!(!Arc::ptr_eq(first_properties, &tensor.blocks()[1].values().properties));
+
+        tensor.compact_labels();
+
+        assert_eq!(*tensor.keys(), keys);
+        for (i, block) in tensor.blocks().iter().enumerate() {
+            let i = i32::try_from(i).unwrap();
+            assert_eq!(block.values().samples, example_labels(vec!["samples"], vec![[i], [i + 3]]));
+            assert_eq!(block.values().data.data().unwrap(), [f64::from(i), 1.0]);
+        }
+
+        // identical labels are now shared between blocks
+        let first = tensor.blocks()[0].values();
+        let second = tensor.blocks()[1].values();
+        assert!(Arc::ptr_eq(&first.properties, &second.properties));
+        assert!(!Arc::ptr_eq(&first.samples, &second.samples));
+
+        let first_gradient = tensor.blocks()[0].gradient("parameter").unwrap();
+        let second_gradient = tensor.blocks()[1].gradient("parameter").unwrap();
+        assert!(Arc::ptr_eq(&first_gradient.samples, &second_gradient.samples));
+    }
+
+    #[test]
+    fn concurrent_reads() {
+        let mut blocks = Vec::new();
+        for i in 0..4 {
+            blocks.push(TensorBlock::new(
+                TestArray::from_data(vec![2, 1], vec![f64::from(i), 1.0]),
+                example_labels(vec!["samples"], vec![[0], [1]]),
+                vec![],
+                example_labels(vec!["properties"], vec![[0]]),
+            ).unwrap());
+        }
+        let keys = example_labels(vec!["key"], vec![[0], [1], [2], [3]]);
+        let tensor = Arc::new(TensorMap::new((*keys).clone(), blocks).unwrap());
+
+        let threads = (0..8).map(|_| {
+            let tensor = Arc::clone(&tensor);
+            std::thread::spawn(move || {
+                for _ in 0..1000 {
+                    for (i, key) in tensor.keys().iter().enumerate() {
+                        assert_eq!(tensor.keys().position(key), Some(i));
+
+                        let block = &tensor.blocks()[i];
+                        assert!(block.values().samples.contains(&[LabelValue::new(1)]));
+                        assert_eq!(block.values().data.data().unwrap(), [f64::from(key[0].i32()), 1.0]);
+                    }
+
+                    // copies share the data with the original tensor map
+                    let copy = tensor.try_clone().unwrap();
+                    assert_eq!(copy.blocks().len(), 4);
+                }
+            })
+        }).collect::<Vec<_>>();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert!(!tensor.blocks()[0].values().data.is_shared());
+    }
+
+    #[test]
+    fn metadata() {
+        let keys = example_labels(vec!["key"], vec![[0]]);
+        let block = TensorBlock::new(
+            TestArray::new(vec![1, 1]),
+            example_labels(vec!["samples"], vec![[0]]),
+            vec![],
+            example_labels(vec!["properties"], vec![[0]]),
+        ).unwrap();
+        let mut tensor = TensorMap::new((*keys).clone(), vec![block]).unwrap();
+        assert_eq!(tensor.metadata().count(), 0);
+
+        tensor.set_metadata("version", Some("1.0")).unwrap();
+        tensor.set_metadata("author", Some("someone")).unwrap();
+        tensor.set_metadata("version", Some("2.0")).unwrap();
+        assert_eq!(tensor.get_metadata("version"), Some("2.0"));
+        assert_eq!(tensor.get_metadata("missing"), None);
+        assert_eq!(tensor.metadata().collect::<Vec<_>>(), [("author", "someone"), ("version", "2.0")]);
+
+        let keys = tensor.metadata_keys_c().iter().map(ConstCString::as_str).collect::<Vec<_>>();
+        assert_eq!(keys, ["author", "version"]);
+
+        let copy = tensor.try_clone().unwrap();
+        assert_eq!(copy.get_metadata("author"), Some("someone"));
+
+        tensor.set_metadata("author", None).unwrap();
+        assert_eq!(tensor.get_metadata("author"), None);
+        assert_eq!(tensor.metadata_keys_c().len(), 1);
+        assert_eq!(copy.metadata().count(), 2);
+
+        let error = tensor.set_metadata("", Some("value")).unwrap_err();
+        assert_eq!(error.to_string(), "invalid parameter: metadata keys can not be empty");
+    }
+
+    #[test]
+    fn with_values() {
+        let keys = example_labels(vec!["key"], vec![[0]]);
+        let mut block = TensorBlock::new(
+            TestArray::from_data(vec![2, 1], vec![1.0, 2.0]),
+            example_labels(vec!["samples"], vec![[0], [1]]),
+            vec![],
+            example_labels(vec!["properties"], vec![[0]]),
+        ).unwrap();
+        block.add_gradient(
+            "parameter",
+            TestArray::from_data(vec![1, 1], vec![3.0]),
+            example_labels(vec!["sample"], vec![[1]]),
+            vec![],
+        ).unwrap();
+        let tensor = TensorMap::new((*keys).clone(), vec![block]).unwrap();
+
+        let new = tensor.with_values(vec![TestArray::from_data(vec![2, 1], vec![4.0, 5.0])]).unwrap();
+        let block = &new.blocks()[0];
+        assert_eq!(block.values().data.data().unwrap(), [4.0, 5.0]);
+        assert!(Arc::ptr_eq(&block.values().samples, &tensor.blocks()[0].values().samples));
+        assert_eq!(block.gradient("parameter").unwrap().data.data().unwrap(), [3.0]);
+        assert!(block.gradient("parameter").unwrap().data.is_shared());
+
+        let error = tensor.with_values(vec![TestArray::new(vec![3, 1])]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: block 0: the new values array has shape [3, 1], \
+            but the current values have shape [2, 1]"
+        );
+
+        let error = tensor.with_values(vec![]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: expected 1 values arrays, one for each block, got 0"
+        );
+    }
+
+    #[test]
+    fn scalar_properties() {
+        let mut blocks = Vec::new();
+        for i in 0..2 {
+            blocks.push(TensorBlock::new(
+                TestArray::from_data(vec![2, 1], vec![f64::from(i), 1.0]),
+                example_labels(vec!["samples"], vec![[0], [1]]),
+                vec![],
+                Arc::new(Labels::scalar()),
+            ).unwrap());
+        }
+        let keys = example_labels(vec!["key"], vec![[0], [1]]);
+        let tensor = TensorMap::new((*keys).clone(), blocks).unwrap();
+        assert_eq!(tensor.blocks()[0].values().properties.size(), 0);
+        assert_eq!(tensor.blocks()[0].values().properties.count(), 1);
+
+        let mut keys_to_move = LabelsBuilder::new(vec!["key"]);
+        keys_to_move.add(&[0]).unwrap();
+        keys_to_move.add(&[1]).unwrap();
+        let moved = tensor.keys_to_properties(&keys_to_move.finish(), true).unwrap();
+
+        let block = &moved.blocks()[0];
+        assert_eq!(*block.values().properties, *example_labels(vec!["key"], vec![[0], [1]]));
+        assert_eq!(block.values().data.data().unwrap(), [0.0, 1.0, 1.0, 1.0]);
+    }
 }
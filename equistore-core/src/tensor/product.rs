@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use crate::{Error, LabelsBuilder, TensorBlock};
+
+use super::{TensorMap, zip_maps};
+
+/// Prefix added to the names of the properties coming from the first tensor
+/// map in [`tensor_product`]
+const FIRST_PREFIX: &str = "first_";
+/// Prefix added to the names of the properties coming from the second tensor
+/// map in [`tensor_product`]
+const SECOND_PREFIX: &str = "second_";
+
+/// Compute the outer product of the properties of `first` and `second`.
+///
+/// Both tensor maps must have the same keys (possibly in a different order),
+/// and the blocks with the same key must have the same samples and components.
+/// The blocks of the result contain the product of all pairs of properties,
+/// for each sample and each entry of the components. The properties of the
+/// new blocks are the cartesian product of the properties of the two blocks,
+/// with the names of the properties prefixed by `first_` and `second_`
+/// respectively.
+///
+/// The keys of the result are the keys of `first`. Gradients are not
+/// supported, and the result does not contain any unit or metadata.
+pub fn tensor_product(first: &TensorMap, second: &TensorMap) -> Result<TensorMap, Error> {
+    let mut blocks = Vec::with_capacity(first.keys().count());
+    for (key, pair) in first.keys().iter().zip(zip_maps(&[first, second])?) {
+        let block = block_product(pair[0], pair[1]).map_err(|error| match error {
+            Error::InvalidParameter(message) => {
+                let key = key.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                Error::InvalidParameter(format!(
+                    "{} for the block with key ({})", message, key.join(", ")
+                ))
+            }
+            error => error,
+        })?;
+        blocks.push(block);
+    }
+
+    return TensorMap::new((**first.keys()).clone(), blocks);
+}
+
+/// Compute the outer product of the properties of a single pair of blocks
+fn block_product(first: &TensorBlock, second: &TensorBlock) -> Result<TensorBlock, Error> {
+    if !first.gradients().is_empty() || !second.gradients().is_empty() {
+        return Err(Error::InvalidParameter(
+            "tensor_product does not support blocks with gradients".into()
+        ));
+    }
+
+    let first_values = first.values();
+    let second_values = second.values();
+    if first_values.samples != second_values.samples {
+        return Err(Error::InvalidParameter(
+            "tensor_product requires the same samples in both blocks".into()
+        ));
+    }
+
+    if first_values.components.len() != second_values.components.len()
+        || first_values.components.iter().zip(second_values.components.iter()).any(|(a, b)| a != b) {
+        return Err(Error::InvalidParameter(
+            "tensor_product requires the same components in both blocks".into()
+        ));
+    }
+
+    let first_properties = &first_values.properties;
+    let second_properties = &second_values.properties;
+
+    let names = first_properties.names().iter()
+        .map(|name| format!("{}{}", FIRST_PREFIX, name))
+        .chain(second_properties.names().iter().map(|name| format!("{}{}", SECOND_PREFIX, name)))
+        .collect::<Vec<_>>();
+
+    let mut properties = LabelsBuilder::new(names.iter().map(|name| &**name).collect());
+    properties.reserve(first_properties.count() * second_properties.count());
+    for first_property in first_properties.iter() {
+        for second_property in second_properties.iter() {
+            let mut property = first_property.to_vec();
+            property.extend_from_slice(second_property);
+            properties.add(&property)?;
+        }
+    }
+    let properties = properties.finish();
+
+    let mut shape = first_values.data.shape()?.to_vec();
+    let properties_axis = shape.len() - 1;
+    shape[properties_axis] = properties.count();
+    let mut data = first_values.data.create(&shape)?;
+
+    let first_data = first_values.data.contiguous_data()?;
+    let second_data = second_values.data.contiguous_data()?;
+    let first_count = first_properties.count();
+    let second_count = second_properties.count();
+
+    let output = data.data_mut()?;
+    let row_size = first_count * second_count;
+    if row_size != 0 {
+        for (row_i, row) in output.chunks_exact_mut(row_size).enumerate() {
+            let first_row = &first_data[row_i * first_count..(row_i + 1) * first_count];
+            let second_row = &second_data[row_i * second_count..(row_i + 1) * second_count];
+            for (output, &first_value) in row.chunks_exact_mut(second_count).zip(first_row) {
+                for (output, &second_value) in output.iter_mut().zip(second_row) {
+                    *output = first_value * second_value;
+                }
+            }
+        }
+    }
+
+    return TensorBlock::new(
+        data,
+        Arc::clone(&first_values.samples),
+        first_values.components.to_vec(),
+        Arc::new(properties),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::TestArray;
+
+    use super::*;
+    use super::super::utils::example_labels;
+
+    fn tensor(keys: Vec<[i32; 1]>, properties: &[[i32; 1]], data: &[f64]) -> TensorMap {
+        let mut blocks = Vec::new();
+        for _ in &keys {
+            blocks.push(TensorBlock::new(
+                TestArray::from_data(vec![2, properties.len()], data.to_vec()),
+                example_labels(vec!["samples"], vec![[0], [1]]),
+                vec![],
+                example_labels(vec!["n"], properties.to_vec()),
+            ).unwrap());
+        }
+
+        let keys = example_labels(vec!["key"], keys);
+        return TensorMap::new((*keys).clone(), blocks).unwrap();
+    }
+
+    #[test]
+    fn product() {
+        let first = tensor(vec![[0], [1]], &[[0], [1]], &[1.0, 2.0, 3.0, 4.0]);
+        let second = tensor(vec![[1], [0]], &[[0], [1], [2]], &[1.0, 10.0, 100.0, -1.0, -2.0, -3.0]);
+
+        let product = tensor_product(&first, &second).unwrap();
+        assert_eq!(product.keys(), first.keys());
+
+        let block = &product.blocks()[0];
+        let values = block.values();
+        assert_eq!(values.samples, first.blocks()[0].values().samples);
+        assert_eq!(values.properties.names(), ["first_n", "second_n"]);
+        assert_eq!(values.properties.values_as_i32(), [0, 0, 0, 1, 0, 2, 1, 0, 1, 1, 1, 2]);
+        assert_eq!(values.data.shape().unwrap(), [2, 6]);
+        assert_eq!(values.data.data().unwrap(), [
+            1.0, 10.0, 100.0, 2.0, 20.0, 200.0,
+            -3.0, -6.0, -9.0, -4.0, -8.0, -12.0,
+        ]);
+
+        let mut other = tensor(vec![[0], [1]], &[[0]], &[1.0, 2.0]);
+        let block = &mut other.blocks_mut()[1];
+        block.add_gradient(
+            "g",
+            TestArray::new(vec![1, 1]),
+            example_labels(vec!["sample"], vec![[0]]),
+            vec![],
+        ).unwrap();
+
+        let error = tensor_product(&first, &other).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: tensor_product does not support blocks with \
+            gradients for the block with key (1)"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use std::cmp::Ordering;
+
+use crate::Error;
+
+use super::TensorMap;
+use super::canonicalize::reorder_labels;
+
+impl TensorMap {
+    /// Get a new `TensorMap` where the blocks are sorted according to the
+    /// values of the key dimensions in `names`.
+    ///
+    /// The first dimension in `names` has the highest priority, and later
+    /// dimensions are only used to break ties. Blocks with the same values for
+    /// all dimensions in `names` keep their relative order. If `descending` is
+    /// `true`, the blocks are sorted in descending order instead.
+    ///
+    /// This function also returns the permutation applied to the blocks: the
+    /// block at index `i` in the new tensor map corresponds to the block at
+    /// index `permutation[i]` in `self`.
+    pub fn sort_keys(&self, names: &[&str], descending: bool) -> Result<(TensorMap, Vec<usize>), Error> {
+        let keys_names = self.keys.names();
+        let mut columns = Vec::with_capacity(names.len());
+        for name in names {
+            let column = keys_names.iter().position(|key_name| key_name == name).ok_or_else(|| {
+                Error::InvalidParameter(format!(
+                    "'{}' is not part of the keys for this tensor map", name
+                ))
+            })?;
+
+            if columns.contains(&column) {
+                return Err(Error::InvalidParameter(format!(
+                    "'{}' is present more than once in the key dimensions to sort by", name
+                )));
+            }
+            columns.push(column);
+        }
+
+        let mut permutation = (0..self.keys.count()).collect::<Vec<_>>();
+        permutation.sort_by(|&a, &b| {
+            let ordering = columns.iter()
+                .map(|&column| self.keys[a][column].cmp(&self.keys[b][column]))
+                .find(|&ordering| ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal);
+
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        let keys = reorder_labels(&self.keys, &permutation)?;
+
+        let mut blocks = Vec::with_capacity(self.blocks.len());
+        for &block_i in &permutation {
+            blocks.push(self.blocks[block_i].try_clone()?);
+        }
+
+        let mut tensor = TensorMap::new(keys, blocks)?;
+        tensor.copy_metadata_from(self);
+
+        return Ok((tensor, permutation));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::TestArray;
+    use crate::TensorBlock;
+
+    use super::*;
+    use super::super::utils::example_labels;
+
+    fn tensor() -> TensorMap {
+        let keys = example_labels(vec!["species", "l"], vec![[6, 0], [1, 1], [6, 1], [1, 0]]);
+
+        let mut blocks = Vec::new();
+        for _ in 0..keys.count() {
+            blocks.push(TensorBlock::new(
+                TestArray::new(vec![1, 1]),
+                example_labels(vec!["samples"], vec![[0]]),
+                vec![],
+                example_labels(vec!["properties"], vec![[0]]),
+            ).unwrap());
+        }
+
+        return TensorMap::new((*keys).clone(), blocks).unwrap();
+    }
+
+    #[test]
+    fn sort_keys() {
+        let tensor = tensor();
+
+        let (sorted, permutation) = tensor.sort_keys(&["l", "species"], false).unwrap();
+        assert_eq!(permutation, [3, 0, 1, 2]);
+        assert_eq!(**sorted.keys(), *example_labels(vec!["species", "l"], vec![[1, 0], [6, 0], [1, 1], [6, 1]]));
+
+        let (sorted, permutation) = tensor.sort_keys(&["species"], true).unwrap();
+        // blocks with the same species keep their relative order
+        assert_eq!(permutation, [0, 2, 1, 3]);
+        assert_eq!(**sorted.keys(), *example_labels(vec!["species", "l"], vec![[6, 0], [6, 1], [1, 1], [1, 0]]));
+
+        let (_, permutation) = tensor.sort_keys(&[], false).unwrap();
+        assert_eq!(permutation, [0, 1, 2, 3]);
+
+        let error = tensor.sort_keys(&["n"], false).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: 'n' is not part of the keys for this tensor map"
+        );
+
+        let error = tensor.sort_keys(&["l", "l"], false).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: 'l' is present more than once in the key dimensions to sort by"
+        );
+    }
+}
@@ -4,7 +4,7 @@ use std::sync::Arc;
 use indexmap::IndexSet;
 
 use crate::labels::{Labels, LabelsBuilder, LabelValue};
-use crate::{Error, TensorBlock, eqs_sample_mapping_t};
+use crate::{Error, BasicBlock, TensorBlock, eqs_sample_mapping_t};
 
 /// single block and part of the associated key, this is used for the various
 /// `keys_to_xxx` functions
@@ -41,34 +41,18 @@ pub fn remove_dimensions_from_keys(keys: &Labels, dimensions: &[&str]) -> Result
         }
     }
 
-    let mut remaining_names = Vec::new();
-    let mut remaining_i = Vec::new();
-    for (i, &name) in names.iter().enumerate() {
-        if !extracted_i.contains(&i) {
-            remaining_names.push(name);
-            remaining_i.push(i);
-        }
-    }
+    let remaining_names = names.iter()
+        .enumerate()
+        .filter(|(i, _)| !extracted_i.contains(i))
+        .map(|(_, &name)| name)
+        .collect::<Vec<_>>();
 
-    let remaining_keys = if remaining_i.is_empty() {
+    let remaining_keys = if remaining_names.is_empty() {
         let mut builder = LabelsBuilder::new(vec!["_"]);
         builder.add(&[0])?;
         builder.finish()
     } else {
-        let mut remaining_keys = IndexSet::new();
-        for key in keys.iter() {
-            let mut label = Vec::new();
-            for &i in &remaining_i {
-                label.push(key[i]);
-            }
-            remaining_keys.insert(label);
-        }
-
-        let mut remaining_keys_builder = LabelsBuilder::new(remaining_names);
-        for entry in remaining_keys {
-            remaining_keys_builder.add(&entry)?;
-        }
-        remaining_keys_builder.finish()
+        keys.unique_subset(&remaining_names)?.0
     };
 
     return Ok(RemovedDimensionsKeys {
@@ -165,6 +149,97 @@ pub fn merge_samples(
     return (merged_samples, samples_mappings)
 }
 
+/// Check that all the `blocks` have exactly the same components labels, which
+/// is required before merging them along samples or properties.
+///
+/// `keys` contains the key of each block in the tensor map, and is used to
+/// identify the mismatched blocks. The error message starts with `context`,
+/// and contains the keys of the first block and of the first mismatched block,
+/// the mismatched component and the first differing entry.
+pub fn check_components_equal(blocks: &[&BasicBlock], keys: &[&[LabelValue]], context: &str) -> Result<(), Error> {
+    debug_assert_eq!(blocks.len(), keys.len());
+    let first_components = match blocks.first() {
+        Some(block) => &block.components,
+        None => return Ok(()),
+    };
+    let first_key = format_key(keys[0]);
+
+    for (block, key) in blocks.iter().zip(keys).skip(1) {
+        let components = &block.components;
+        if components.len() != first_components.len() {
+            return Err(Error::InvalidParameter(format!(
+                "{}: components mismatch for the block with key ({}): this block \
+                has {} components while the block with key ({}) has {}",
+                context, format_key(key), components.len(), first_key, first_components.len()
+            )));
+        }
+
+        for (component_i, (component, first)) in components.iter().zip(first_components).enumerate() {
+            if component == first {
+                continue;
+            }
+
+            if component.names() != first.names() {
+                return Err(Error::InvalidParameter(format!(
+                    "{}: components mismatch for the block with key ({}): component \
+                    {} is named [{}] while it is named [{}] in the block with key ({})",
+                    context, format_key(key), component_i,
+                    component.names().join(", "),
+                    first.names().join(", "),
+                    first_key,
+                )));
+            }
+
+            for (entry_i, (entry, first_entry)) in component.iter().zip(first.iter()).enumerate() {
+                if entry != first_entry {
+                    return Err(Error::InvalidParameter(format!(
+                        "{}: components mismatch for the block with key ({}): entry {} \
+                        of component [{}] is {:?} while it is {:?} in the block with key ({})",
+                        context, format_key(key), entry_i, component.names().join(", "),
+                        entry, first_entry, first_key,
+                    )));
+                }
+            }
+
+            return Err(Error::InvalidParameter(format!(
+                "{}: components mismatch for the block with key ({}): component [{}] \
+                has {} entries while it has {} in the block with key ({})",
+                context, format_key(key), component.names().join(", "),
+                component.count(), first.count(), first_key,
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Format the values of a key for use in error messages
+fn format_key(key: &[LabelValue]) -> String {
+    key.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Check that all the blocks have the same unit and quantity
+pub fn check_units_equal(blocks: &[KeyAndBlock], context: &str) -> Result<(), Error> {
+    let first = match blocks.first() {
+        Some((_, block)) => block,
+        None => return Ok(()),
+    };
+
+    for (block_i, (_, block)) in blocks.iter().enumerate().skip(1) {
+        if !block.same_units(first) {
+            return Err(Error::InvalidParameter(format!(
+                "{}: block {} has unit '{}' and quantity '{}', while the first \
+                block has unit '{}' and quantity '{}'",
+                context, block_i,
+                block.unit().unwrap_or(""), block.quantity().unwrap_or(""),
+                first.unit().unwrap_or(""), first.quantity().unwrap_or(""),
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 /******************************************************************************/
 
 #[cfg(test)]
@@ -185,3 +260,51 @@ mod tests_utils {
         return Arc::new(labels.finish());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::TensorBlock;
+    use crate::data::TestArray;
+
+    use super::*;
+
+    fn block_with_component(values: Vec<[i32; 1]>) -> TensorBlock {
+        let count = values.len();
+        TensorBlock::new(
+            TestArray::new(vec![1, count, 1]),
+            example_labels(vec!["samples"], vec![[0]]),
+            vec![example_labels(vec!["component"], values)],
+            example_labels(vec!["properties"], vec![[0]]),
+        ).unwrap()
+    }
+
+    #[test]
+    fn components_equal() {
+        let block_1 = block_with_component(vec![[0], [1], [2]]);
+        let block_2 = block_with_component(vec![[0], [1], [2]]);
+        let block_3 = block_with_component(vec![[0], [3], [2]]);
+        let block_4 = block_with_component(vec![[0], [1]]);
+
+        let keys = example_labels(vec!["key_1", "key_2"], vec![[0, 1], [0, 3], [2, 1]]);
+        let keys = keys.iter().collect::<Vec<_>>();
+
+        let blocks = vec![block_1.values(), block_2.values()];
+        assert!(check_components_equal(&blocks, &keys[..2], "merging blocks").is_ok());
+
+        let blocks = vec![block_1.values(), block_2.values(), block_3.values()];
+        assert_eq!(
+            check_components_equal(&blocks, &keys, "merging blocks").unwrap_err().to_string(),
+            "invalid parameter: merging blocks: components mismatch for the block with \
+            key (2, 1): entry 1 of component [component] is [3] while it is [1] in the \
+            block with key (0, 1)"
+        );
+
+        let blocks = vec![block_1.values(), block_4.values()];
+        assert_eq!(
+            check_components_equal(&blocks, &keys[..2], "merging blocks").unwrap_err().to_string(),
+            "invalid parameter: merging blocks: components mismatch for the block with \
+            key (0, 3): component [component] has 2 entries while it has 3 in the block \
+            with key (0, 1)"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{Error, Labels, TensorBlock};
+
+use super::TensorMap;
+use super::canonicalize::reorder_labels;
+
+/// View over a subset of the blocks of a [`TensorMap`].
+///
+/// The view references the blocks of the original tensor map instead of
+/// copying them, making it cheap to create and iterate over different subsets
+/// of a large tensor map.
+#[derive(Debug)]
+pub struct TensorMapView<'a> {
+    tensor: &'a mut TensorMap,
+    keys: Labels,
+    blocks: Vec<usize>,
+}
+
+impl TensorMap {
+    /// Get a view over the blocks matching the given `selection`, following
+    /// the same rules as [`TensorMap::blocks_matching`]. The keys of the view
+    /// contain all the dimensions of the keys of this tensor map, and only the
+    /// entries for the selected blocks.
+    pub fn view(&mut self, selection: &Labels) -> Result<TensorMapView<'_>, Error> {
+        let blocks = self.blocks_matching(selection)?;
+        let keys = reorder_labels(&self.keys, &blocks)?;
+
+        return Ok(TensorMapView {
+            tensor: self,
+            keys,
+            blocks,
+        });
+    }
+}
+
+impl TensorMapView<'_> {
+    /// Get the keys of the blocks in this view
+    pub fn keys(&self) -> &Labels {
+        &self.keys
+    }
+
+    /// Iterate over the blocks in this view, in the same order as the keys
+    pub fn blocks_mut(&mut self) -> impl Iterator<Item=&mut TensorBlock> + '_ {
+        // `blocks_matching` returns the indexes of the blocks in increasing
+        // order, so the blocks come in the same order as the keys
+        let selected = &self.blocks;
+        self.tensor.blocks_mut()
+            .iter_mut()
+            .enumerate()
+            .filter(move |(block_i, _)| selected.contains(block_i))
+            .map(|(_, block)| block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::TestArray;
+    use crate::LabelsBuilder;
+
+    use super::*;
+    use super::super::utils::example_labels;
+
+    #[test]
+    fn view() {
+        let keys = example_labels(vec!["species", "l"], vec![[6, 0], [1, 1], [6, 1], [1, 0]]);
+        let mut blocks = Vec::new();
+        for sample in 0..4 {
+            blocks.push(TensorBlock::new(
+                TestArray::new(vec![1, 1]),
+                example_labels(vec!["samples"], vec![[sample]]),
+                vec![],
+                example_labels(vec!["properties"], vec![[0]]),
+            ).unwrap());
+        }
+        let mut tensor = TensorMap::new((*keys).clone(), blocks).unwrap();
+
+        let mut selection = LabelsBuilder::new(vec!["species"]);
+        selection.add(&[6]).unwrap();
+        let mut view = tensor.view(&selection.finish()).unwrap();
+
+        assert_eq!(*view.keys(), *example_labels(vec!["species", "l"], vec![[6, 0], [6, 1]]));
+
+        let samples = view.blocks_mut().map(|b| b.values().samples[0][0].i32()).collect::<Vec<_>>();
+        assert_eq!(samples, [0, 2]);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{Error, TensorBlock};
+
+use super::TensorMap;
+
+/// Iterator over the blocks of multiple tensor maps sharing the same keys,
+/// created with [`zip_maps`].
+#[derive(Debug, Clone)]
+pub struct ZipMaps<'a> {
+    tensors: Vec<&'a TensorMap>,
+    /// for each key of the first tensor map, position of the corresponding
+    /// block in all the tensor maps
+    positions: std::vec::IntoIter<Vec<usize>>,
+}
+
+impl<'a> Iterator for ZipMaps<'a> {
+    type Item = Vec<&'a TensorBlock>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let positions = self.positions.next()?;
+        let blocks = self.tensors.iter()
+            .zip(positions)
+            .map(|(tensor, position)| &tensor.blocks()[position])
+            .collect();
+        return Some(blocks);
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.positions.size_hint()
+    }
+}
+
+impl ExactSizeIterator for ZipMaps<'_> {}
+
+/// Iterate over the blocks of all `tensors` with the same key.
+///
+/// All the tensor maps must have the same set of keys, but the keys can be in
+/// a different order. The iterator yields one `Vec` of blocks for each key, in
+/// the order of the keys of the first tensor map, containing the block from
+/// each of the `tensors` in order.
+pub fn zip_maps<'a>(tensors: &[&'a TensorMap]) -> Result<ZipMaps<'a>, Error> {
+    let first = match tensors.first() {
+        Some(first) => first,
+        None => return Ok(ZipMaps { tensors: Vec::new(), positions: Vec::new().into_iter() }),
+    };
+
+    for (tensor_i, tensor) in tensors.iter().enumerate().skip(1) {
+        if tensor.keys().names() != first.keys().names() {
+            return Err(Error::InvalidParameter(format!(
+                "can not iterate over tensor maps with different keys names: \
+                got [{}] for tensor map 0 and [{}] for tensor map {}",
+                first.keys().names().join(", "), tensor.keys().names().join(", "), tensor_i
+            )));
+        }
+
+        if tensor.keys().count() != first.keys().count() {
+            return Err(Error::InvalidParameter(format!(
+                "can not iterate over tensor maps with different keys: got {} \
+                keys for tensor map 0 and {} keys for tensor map {}",
+                first.keys().count(), tensor.keys().count(), tensor_i
+            )));
+        }
+    }
+
+    let mut positions = Vec::with_capacity(first.keys().count());
+    for (first_i, key) in first.keys().iter().enumerate() {
+        let mut key_positions = Vec::with_capacity(tensors.len());
+        key_positions.push(first_i);
+
+        for (tensor_i, tensor) in tensors.iter().enumerate().skip(1) {
+            // fast path for tensor maps with keys in the same order
+            if tensor.keys().get(first_i) == Some(key) {
+                key_positions.push(first_i);
+                continue;
+            }
+
+            if let Some(position) = tensor.keys().position(key) {
+                key_positions.push(position);
+            } else {
+                let key = key.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                return Err(Error::InvalidParameter(format!(
+                    "can not iterate over tensor maps with different keys: \
+                    block with key ({}) is missing in tensor map {}",
+                    key.join(", "), tensor_i
+                )));
+            }
+        }
+
+        positions.push(key_positions);
+    }
+
+    return Ok(ZipMaps {
+        tensors: tensors.to_vec(),
+        positions: positions.into_iter(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::TestArray;
+
+    use super::*;
+    use super::super::utils::example_labels;
+
+    fn tensor(keys: Vec<[i32; 1]>) -> TensorMap {
+        let mut blocks = Vec::new();
+        for key in &keys {
+            blocks.push(TensorBlock::new(
+                TestArray::new(vec![1, 1]),
+                example_labels(vec!["samples"], vec![*key]),
+                vec![],
+                example_labels(vec!["properties"], vec![[0]]),
+            ).unwrap());
+        }
+
+        let keys = example_labels(vec!["key"], keys);
+        return TensorMap::new((*keys).clone(), blocks).unwrap();
+    }
+
+    #[test]
+    fn zip() {
+        let first = tensor(vec![[0], [1], [2]]);
+        let second = tensor(vec![[2], [0], [1]]);
+        let third = tensor(vec![[0], [1], [2]]);
+
+        let zipped = zip_maps(&[&first, &second, &third]).unwrap();
+        assert_eq!(zipped.len(), 3);
+        for (key, blocks) in first.keys().iter().zip(zipped) {
+            assert_eq!(blocks.len(), 3);
+            for block in blocks {
+                assert_eq!(block.values().samples[0], *key);
+            }
+        }
+
+        let error = zip_maps(&[&first, &tensor(vec![[0], [1], [3]])]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: can not iterate over tensor maps with different \
+            keys: block with key (2) is missing in tensor map 1"
+        );
+
+        let error = zip_maps(&[&first, &tensor(vec![[0]])]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid parameter: can not iterate over tensor maps with different \
+            keys: got 3 keys for tensor map 0 and 1 keys for tensor map 1"
+        );
+
+        assert_eq!(zip_maps(&[]).unwrap().count(), 0);
+    }
+}
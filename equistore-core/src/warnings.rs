@@ -0,0 +1,59 @@
+//! Reporting of non-fatal diagnostics, such as data being implicitly
+//! decompressed. Bindings can register a callback with
+//! [`set_warning_callback`] to forward these warnings to the user, e.g. using
+//! Python's `warnings` module. Without a callback, warnings are ignored.
+
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+/// Function called with the message of each warning emitted by equistore
+pub type WarningCallback = Arc<dyn Fn(&str) + Send + Sync>;
+
+static WARNING_CALLBACK: Lazy<Mutex<Option<WarningCallback>>> = Lazy::new(|| Mutex::new(None));
+
+/// Set the function called when equistore emits a warning, replacing any
+/// previously registered callback. Use `None` to ignore all warnings.
+pub fn set_warning_callback(callback: Option<WarningCallback>) {
+    let mut current = WARNING_CALLBACK.lock().expect("mutex was poisoned");
+    *current = callback;
+}
+
+/// Emit a warning with the given `message`, calling the registered callback
+/// if there is one.
+pub fn warn(message: &str) {
+    // clone the callback to release the lock before calling it, allowing the
+    // callback to register a different callback
+    let callback = WARNING_CALLBACK.lock().expect("mutex was poisoned").clone();
+    if let Some(callback) = callback {
+        callback(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn callback() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+
+        let captured = Arc::clone(&messages);
+        set_warning_callback(Some(Arc::new(move |message: &str| {
+            captured.lock().unwrap().push(message.to_string());
+        })));
+
+        warn("first warning");
+        warn("second warning");
+
+        set_warning_callback(None);
+        warn("ignored warning");
+
+        // other tests running in parallel can emit warnings too, so we only
+        // check for the messages emitted here
+        let messages = messages.lock().unwrap();
+        assert!(messages.iter().any(|m| m == "first warning"));
+        assert!(messages.iter().any(|m| m == "second warning"));
+        assert!(!messages.iter().any(|m| m == "ignored warning"));
+    }
+}
@@ -41,4 +41,14 @@ TEST_CASE("Labels") {
         Labels({"not an ident"}, {{0}}),
         "invalid parameter: 'not an ident' is not a valid label name"
     );
+
+    CHECK_THROWS_WITH(
+        Labels({"foo", "foo"}, {{0, 1}}),
+        "invalid parameter: invalid labels: the name 'foo' is used multiple times"
+    );
+
+    CHECK_THROWS_WITH(
+        Labels({"foo", "bar"}, {{0, 1}, {2, 3}, {0, 1}}),
+        "invalid parameter: can not have the same label value multiple time: [0, 1] is already present at position 0"
+    );
 }
@@ -19,6 +19,11 @@ TEST_CASE("TensorMap") {
         const auto values = block.values();
         CHECK(values(0, 0, 0) == 3);
 
+        CHECK_THROWS_WITH(
+            tensor.block_by_id(12),
+            "out of bounds: the index is 12 but there are only 4 entries"
+        );
+
         // block by selection
         auto selection = Labels({"key_1", "key_2"}, {{1, 0}});
         auto matching = tensor.blocks_matching(selection);
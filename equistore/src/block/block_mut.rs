@@ -2,7 +2,8 @@ use std::ffi::{CString, CStr};
 use std::iter::FusedIterator;
 
 use crate::c_api::eqs_block_t;
-use crate::{ArrayRefMut, Labels};
+use crate::errors::check_status;
+use crate::{ArrayRefMut, Error, Labels};
 
 use super::TensorBlockRef;
 use super::block_ref::{block_array, block_metadata};
@@ -107,6 +108,45 @@ impl<'a> TensorBlockRefMut<'a> {
             block: self.data.as_ptr() as *mut _,
         }
     }
+
+    /// Set all the values in this block to `value`. The gradients are left
+    /// unchanged.
+    #[inline]
+    pub fn fill(&mut self, value: f64) -> Result<(), Error> {
+        unsafe {
+            check_status(crate::c_api::eqs_block_fill(self.as_mut_ptr(), value))
+        }
+    }
+
+    /// Copy the values and gradients data from `other` into this block.
+    ///
+    /// Both blocks must have the same samples, components and properties
+    /// labels, and the same set of gradients with the same labels.
+    #[inline]
+    pub fn copy_from(&mut self, other: TensorBlockRef<'_>) -> Result<(), Error> {
+        unsafe {
+            check_status(crate::c_api::eqs_block_copy_from(self.as_mut_ptr(), other.as_ptr()))
+        }
+    }
+
+    /// Set the values of this block for the samples at the given positions to
+    /// `data`. The gradients are left unchanged.
+    ///
+    /// `data` is given in row-major order, and must contain `samples.len()`
+    /// times the number of values for a single sample (i.e. the product of the
+    /// size of all components and properties).
+    #[inline]
+    pub fn assign_rows(&mut self, samples: &[usize], data: &[f64]) -> Result<(), Error> {
+        unsafe {
+            check_status(crate::c_api::eqs_block_assign_rows(
+                self.as_mut_ptr(),
+                samples.as_ptr(),
+                samples.len(),
+                data.as_ptr(),
+                data.len(),
+            ))
+        }
+    }
 }
 
 /// Iterator over parameter/[`BasicBlockMut`] pairs for all gradients in a
@@ -177,6 +177,10 @@ impl<'a> TensorBlockRef<'a> {
             )).expect("failed to get gradient list");
         }
 
+        if parameters_count == 0 {
+            return Vec::new();
+        }
+
         unsafe {
             let parameters = std::slice::from_raw_parts(parameters_ptr, parameters_count);
             return parameters.iter()
@@ -11,3 +11,96 @@ pub use self::block_mut::{TensorBlockRefMut, BasicBlockMut, GradientsMutIter};
 
 mod owned;
 pub use self::owned::TensorBlock;
+
+mod take;
+
+use crate::{Error, Labels};
+use crate::errors::check_status;
+
+/// Register the convention for gradients with respect to `parameter`. All
+/// gradients with respect to this parameter added afterward must start with
+/// the given `components`, followed by the components of the values.
+///
+/// For example, gradients with respect to `"strain"` would usually declare two
+/// components with 3 entries each. Registering the same parameter multiple
+/// times replaces the previous convention.
+pub fn register_gradient_parameter(parameter: &str, components: &[Labels]) -> Result<(), Error> {
+    let mut parameter = parameter.to_owned().into_bytes();
+    parameter.push(b'\0');
+
+    let c_components = components.iter().map(|c| c.as_eqs_labels_t()).collect::<Vec<_>>();
+
+    unsafe {
+        check_status(crate::c_api::eqs_register_gradient_parameter(
+            parameter.as_ptr().cast(),
+            c_components.as_ptr(),
+            c_components.len(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Labels, TensorBlock};
+    use super::register_gradient_parameter;
+
+    #[test]
+    fn gradient_parameter() {
+        let xyz = Labels::new(["xyz"], &[[0], [1], [2]]);
+        register_gradient_parameter("rust-test-strain", std::slice::from_ref(&xyz)).unwrap();
+
+        let mut block = TensorBlock::new(
+            ndarray::Array::zeros(vec![1, 1]),
+            Labels::new(["samples"], &[[0]]),
+            &[],
+            Labels::new(["properties"], &[[0]]),
+        ).unwrap();
+
+        let error = block.add_gradient(
+            "rust-test-strain",
+            ndarray::Array::zeros(vec![1, 1]),
+            Labels::new(["sample"], &[[0]]),
+            &[],
+        ).unwrap_err();
+        assert_eq!(error.message,
+            "invalid parameter: invalid gradient components for 'rust-test-strain': \
+            this parameter was registered with the components [xyz (3 entries)] \
+            before the values components, but got []"
+        );
+
+        block.add_gradient(
+            "rust-test-strain",
+            ndarray::Array::zeros(vec![1, 3, 1]),
+            Labels::new(["sample"], &[[0]]),
+            &[xyz],
+        ).unwrap();
+
+        let error = register_gradient_parameter("values", &[]).unwrap_err();
+        assert_eq!(error.message, "invalid parameter: can not register gradient parameter named 'values'");
+    }
+
+    #[test]
+    fn modify_values() {
+        let new_block = || TensorBlock::new(
+            ndarray::Array::zeros(vec![3, 2]),
+            Labels::new(["samples"], &[[0], [1], [2]]),
+            &[],
+            Labels::new(["properties"], &[[0], [1]]),
+        ).unwrap();
+
+        let mut block = new_block();
+        block.as_ref_mut().fill(2.0).unwrap();
+        block.as_ref_mut().assign_rows(&[2, 0], &[1.0, 2.0, 3.0, 4.0]).unwrap();
+        assert_eq!(
+            block.as_ref().values().data.as_array(),
+            ndarray::arr2(&[[3.0, 4.0], [2.0, 2.0], [1.0, 2.0]]).into_dyn()
+        );
+
+        let error = block.as_ref_mut().assign_rows(&[0], &[1.0]).unwrap_err();
+        assert!(error.message.starts_with("invalid parameter"), "{}", error.message);
+
+        let mut other = new_block();
+        other.as_ref_mut().copy_from(block.as_ref()).unwrap();
+        assert_eq!(other.as_ref().values().data.as_array(), block.as_ref().values().data.as_array());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::c_api::eqs_block_t;
+use crate::c_api::{eqs_array_t, eqs_block_t};
 use crate::errors::check_status;
 use crate::{Array, Labels, Error, TensorBlockRef};
 
@@ -63,6 +63,22 @@ impl TensorBlock {
         samples: Labels,
         components: &[Labels],
         properties: Labels
+    ) -> Result<TensorBlock, Error> {
+        return TensorBlock::from_raw_array(
+            (Box::new(data) as Box<dyn Array>).into(),
+            &samples,
+            components,
+            &properties,
+        );
+    }
+
+    /// Create a new [`TensorBlock`] with the given raw `eqs_array_t` as
+    /// values. The block takes ownership of the array.
+    pub(crate) fn from_raw_array(
+        data: eqs_array_t,
+        samples: &Labels,
+        components: &[Labels],
+        properties: &Labels
     ) -> Result<TensorBlock, Error> {
         let mut c_components = Vec::new();
         for component in components {
@@ -71,7 +87,7 @@ impl TensorBlock {
 
         let ptr = unsafe {
             crate::c_api::eqs_block(
-                (Box::new(data) as Box<dyn Array>).into(),
+                data,
                 samples.as_eqs_labels_t(),
                 c_components.as_ptr(),
                 c_components.len(),
@@ -100,13 +116,41 @@ impl TensorBlock {
         data: impl Array,
         samples: Labels,
         components: &[Labels],
+    ) -> Result<(), Error> {
+        return self.add_raw_gradient(
+            parameter,
+            (Box::new(data) as Box<dyn Array>).into(),
+            &samples,
+            components,
+        );
+    }
+
+    /// Swap the samples and properties of this block, transposing the values
+    /// data. The components stay in the same order.
+    ///
+    /// Blocks with gradients can not be transposed, since the gradient
+    /// samples refer to the samples of the values.
+    #[inline]
+    pub fn transpose(&mut self) -> Result<(), Error> {
+        unsafe {
+            check_status(crate::c_api::eqs_block_transpose(self.as_ref_mut().as_mut_ptr()))
+        }
+    }
+
+    /// Add a gradient with respect to `parameter` to this block, using the
+    /// given raw `eqs_array_t` as data. The block takes ownership of the array.
+    pub(crate) fn add_raw_gradient(
+        &mut self,
+        parameter: &str,
+        mut data: eqs_array_t,
+        samples: &Labels,
+        components: &[Labels],
     ) -> Result<(), Error> {
         let mut parameter = parameter.to_owned().into_bytes();
         parameter.push(b'\0');
 
         let c_components = components.iter().map(|c| c.as_eqs_labels_t()).collect::<Vec<_>>();
 
-        let mut data = (Box::new(data) as Box<dyn Array>).into();
         unsafe {
             check_status(crate::c_api::eqs_block_add_gradient(
                 self.as_ref_mut().as_mut_ptr(),
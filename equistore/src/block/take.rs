@@ -0,0 +1,294 @@
+use crate::c_api::{eqs_array_t, eqs_sample_mapping_t};
+use crate::{Labels, LabelsBuilder, Error, TensorBlock};
+
+use super::TensorBlockRef;
+
+/// Name of the dimension added to the samples/properties by
+/// [`TensorBlockRef::take_samples`] and [`TensorBlockRef::take_properties`]
+const REPLICA: &str = "replica";
+
+impl<'a> TensorBlockRef<'a> {
+    /// Create a new [`TensorBlock`] containing the samples at the given
+    /// `indices` of this block, in the order of `indices`.
+    ///
+    /// Contrary to a selection with [`Labels`], `indices` can contain the same
+    /// sample multiple times, which is useful for bootstrapping or minibatch
+    /// sampling. To keep the samples unique, the new block samples contain an
+    /// additional `"replica"` dimension, counting how many times the
+    /// corresponding sample was already taken before. The gradients samples are
+    /// updated to refer to the new samples, and duplicated as needed.
+    pub fn take_samples(&self, indices: &[usize]) -> Result<TensorBlock, Error> {
+        let values = self.values();
+        let samples = replicated_labels(&values.samples, indices, "samples")?;
+
+        let mapping = indices.iter().enumerate()
+            .map(|(output, &input)| eqs_sample_mapping_t { input, output })
+            .collect::<Vec<_>>();
+        let new_values = gather_samples(values.data.as_raw(), &mapping)?;
+
+        let mut block = TensorBlock::from_raw_array(
+            new_values,
+            &samples,
+            &values.components,
+            &values.properties,
+        )?;
+
+        for (parameter, gradient) in self.gradients() {
+            // list of gradient rows associated with each of the values samples
+            let mut gradient_rows = vec![Vec::new(); values.samples.count()];
+            for (row, grad_sample) in gradient.samples.iter().enumerate() {
+                gradient_rows[grad_sample[0].usize()].push(row);
+            }
+
+            let mut builder = LabelsBuilder::new(gradient.samples.names());
+            let mut mapping = Vec::new();
+            for (new_sample, &sample) in indices.iter().enumerate() {
+                for &row in &gradient_rows[sample] {
+                    let mut grad_sample = gradient.samples[row].to_vec();
+                    grad_sample[0] = new_sample.into();
+                    builder.add(grad_sample.as_slice());
+
+                    mapping.push(eqs_sample_mapping_t { input: row, output: mapping.len() });
+                }
+            }
+
+            let new_gradient = gather_samples(gradient.data.as_raw(), &mapping)?;
+            block.add_raw_gradient(
+                parameter,
+                new_gradient,
+                &builder.finish(),
+                &gradient.components,
+            )?;
+        }
+
+        return Ok(block);
+    }
+
+    /// Create a new [`TensorBlock`] containing the properties at the given
+    /// `indices` of this block, in the order of `indices`.
+    ///
+    /// As for [`TensorBlockRef::take_samples`], `indices` can contain the same
+    /// property multiple times, and the new block properties contain an
+    /// additional `"replica"` dimension to keep them unique.
+    pub fn take_properties(&self, indices: &[usize]) -> Result<TensorBlock, Error> {
+        let values = self.values();
+        let properties = replicated_labels(&values.properties, indices, "properties")?;
+
+        // `eqs_array_t.move_samples_from` can only gather entries along the
+        // first axis, so we use it on a copy of this block where the first and
+        // last axes of all arrays are swapped. This copy is only used as a
+        // source of data, and it does not matter that its metadata no longer
+        // matches the arrays.
+        let mut transposed = self.try_clone()?;
+        let mut transposed = transposed.as_ref_mut();
+
+        let new_values = gather_properties(transposed.values_mut().data.as_raw_mut(), indices)?;
+        let mut block = TensorBlock::from_raw_array(
+            new_values,
+            &values.samples,
+            &values.components,
+            &properties,
+        )?;
+
+        for (parameter, mut gradient) in transposed.gradients_mut() {
+            let new_gradient = gather_properties(gradient.data.as_raw_mut(), indices)?;
+            block.add_raw_gradient(
+                parameter,
+                new_gradient,
+                &gradient.samples,
+                &gradient.components,
+            )?;
+        }
+
+        return Ok(block);
+    }
+}
+
+/// Create new labels containing the entries of `labels` at the given
+/// `indices`, with an additional `"replica"` dimension to disambiguate
+/// duplicated indices.
+fn replicated_labels(labels: &Labels, indices: &[usize], axis: &str) -> Result<Labels, Error> {
+    let mut names = labels.names();
+    if names.contains(&REPLICA) {
+        return Err(Error {
+            code: None,
+            message: format!(
+                "can not take {} from this block, the '{}' dimension is already used",
+                axis, REPLICA
+            ),
+        });
+    }
+    names.push(REPLICA);
+
+    let mut replicas = vec![0_i32; labels.count()];
+    let mut builder = LabelsBuilder::new(names);
+    builder.reserve(indices.len());
+    for &index in indices {
+        if index >= labels.count() {
+            return Err(Error {
+                code: None,
+                message: format!(
+                    "index {} is out of bounds for the {} of this block, which has {} entries",
+                    index, axis, labels.count()
+                ),
+            });
+        }
+
+        let mut entry = labels[index].to_vec();
+        entry.push(replicas[index].into());
+        replicas[index] += 1;
+
+        builder.add(entry.as_slice());
+    }
+
+    return Ok(builder.finish());
+}
+
+/// Create a new array with the same origin as `input`, containing the samples
+/// of `input` as described by `mapping`.
+fn gather_samples(input: &eqs_array_t, mapping: &[eqs_sample_mapping_t]) -> Result<eqs_array_t, Error> {
+    let mut shape = input.shape()?.to_vec();
+    let n_properties = shape[shape.len() - 1];
+    shape[0] = mapping.len();
+
+    let mut output = input.create(&shape)?;
+    if let Err(error) = output.move_samples_from(input, mapping, 0..n_properties) {
+        destroy(output);
+        return Err(error);
+    }
+
+    return Ok(output);
+}
+
+/// Create a new array containing the properties of `input` at the given
+/// `indices`. The first and last axes of `input` are swapped in the process.
+fn gather_properties(input: &mut eqs_array_t, indices: &[usize]) -> Result<eqs_array_t, Error> {
+    let mut shape = input.shape()?.to_vec();
+    let last_axis = shape.len() - 1;
+    shape[last_axis] = indices.len();
+
+    input.swap_axes(0, last_axis)?;
+
+    let mapping = indices.iter().enumerate()
+        .map(|(output, &index)| eqs_sample_mapping_t { input: index, output })
+        .collect::<Vec<_>>();
+
+    let mut output = gather_samples(input, &mapping)?;
+
+    // reshaping after swapping the axes back makes sure the data is laid out
+    // contiguously in memory
+    if let Err(error) = output.swap_axes(0, last_axis) {
+        destroy(output);
+        return Err(error);
+    }
+
+    if let Err(error) = output.reshape(&shape) {
+        destroy(output);
+        return Err(error);
+    }
+
+    return Ok(output);
+}
+
+/// Release the memory associated with an array we own
+fn destroy(array: eqs_array_t) {
+    if let Some(destroy) = array.destroy {
+        unsafe {
+            destroy(array.ptr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Labels, TensorBlock};
+
+    #[allow(clippy::cast_precision_loss)]
+    fn example_block() -> TensorBlock {
+        let values = ndarray::Array2::from_shape_fn((3, 2), |(i, j)| (10 * i + j) as f64);
+        let mut block = TensorBlock::new(
+            values.into_dyn(),
+            Labels::new(["structure"], &[[0], [1], [2]]),
+            &[],
+            Labels::new(["n"], &[[0], [1]]),
+        ).unwrap();
+
+        let gradient = ndarray::Array3::from_shape_fn((4, 3, 2), |(i, _, j)| (100 * i + j) as f64);
+        block.add_gradient(
+            "positions",
+            gradient.into_dyn(),
+            Labels::new(["sample", "atom"], &[[0, 0], [0, 1], [2, 0], [2, 4]]),
+            &[Labels::new(["xyz"], &[[0], [1], [2]])],
+        ).unwrap();
+
+        return block;
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn take_samples() {
+        let block = example_block();
+        let taken = block.as_ref().take_samples(&[2, 1, 2]).unwrap();
+
+        let values = taken.as_ref().values();
+        assert_eq!(values.samples, Labels::new(["structure", "replica"], &[[2, 0], [1, 0], [2, 1]]));
+        assert_eq!(values.properties, Labels::new(["n"], &[[0], [1]]));
+
+        let array = values.data.as_array();
+        assert_eq!(array.shape(), [3, 2]);
+        assert_eq!(array[[0, 1]], 21.0);
+        assert_eq!(array[[1, 0]], 10.0);
+        assert_eq!(array[[2, 1]], 21.0);
+
+        let gradient = taken.as_ref().gradient("positions").unwrap();
+        assert_eq!(gradient.samples, Labels::new(["sample", "atom"], &[[0, 0], [0, 4], [2, 0], [2, 4]]));
+
+        let array = gradient.data.as_array();
+        assert_eq!(array.shape(), [4, 3, 2]);
+        assert_eq!(array[[0, 0, 0]], 200.0);
+        assert_eq!(array[[1, 2, 1]], 301.0);
+        assert_eq!(array[[2, 0, 0]], 200.0);
+        assert_eq!(array[[3, 1, 0]], 300.0);
+
+        let error = block.as_ref().take_samples(&[3]).unwrap_err();
+        assert_eq!(
+            error.message,
+            "index 3 is out of bounds for the samples of this block, which has 3 entries"
+        );
+
+        let error = taken.as_ref().take_samples(&[0]).unwrap_err();
+        assert_eq!(
+            error.message,
+            "can not take samples from this block, the 'replica' dimension is already used"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn take_properties() {
+        let block = example_block();
+        let taken = block.as_ref().take_properties(&[1, 1, 0]).unwrap();
+
+        let values = taken.as_ref().values();
+        assert_eq!(values.samples, Labels::new(["structure"], &[[0], [1], [2]]));
+        assert_eq!(values.properties, Labels::new(["n", "replica"], &[[1, 0], [1, 1], [0, 0]]));
+
+        let array = values.data.as_array();
+        assert_eq!(array.shape(), [3, 3]);
+        assert_eq!(array[[1, 0]], 11.0);
+        assert_eq!(array[[1, 1]], 11.0);
+        assert_eq!(array[[2, 2]], 20.0);
+
+        let gradient = taken.as_ref().gradient("positions").unwrap();
+        assert_eq!(gradient.samples, Labels::new(["sample", "atom"], &[[0, 0], [0, 1], [2, 0], [2, 4]]));
+
+        let array = gradient.data.as_array();
+        assert_eq!(array.shape(), [4, 3, 3]);
+        assert_eq!(array[[3, 2, 0]], 301.0);
+        assert_eq!(array[[3, 2, 1]], 301.0);
+        assert_eq!(array[[3, 2, 2]], 300.0);
+
+        // the original block is unchanged
+        assert_eq!(block.as_ref().values().data.as_array().shape(), [3, 2]);
+    }
+}
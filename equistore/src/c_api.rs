@@ -12,6 +12,7 @@ pub const EQS_SUCCESS: i32 = 0;
 pub const EQS_INVALID_PARAMETER_ERROR: i32 = 1;
 pub const EQS_IO_ERROR: i32 = 2;
 pub const EQS_SERIALIZATION_ERROR: i32 = 3;
+pub const EQS_CANCELLED_ERROR: i32 = 5;
 pub const EQS_BUFFER_SIZE_ERROR: i32 = 254;
 pub const EQS_INTERNAL_ERROR: i32 = 255;
 #[repr(C)]
@@ -21,6 +22,11 @@ pub struct eqs_block_t {
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+pub struct eqs_lazy_tensormap_t {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct eqs_tensormap_t {
     _unused: [u8; 0],
 }
@@ -165,7 +171,7 @@ pub struct eqs_array_t {
             origin: *mut eqs_data_origin_t,
         ) -> eqs_status_t,
     >,
-    #[doc = " Get a pointer to the underlying data storage.\n\n This function is allowed to fail if the data is not accessible in RAM,\n not stored as 64-bit floating point values, or not stored as a\n C-contiguous array."]
+    #[doc = " Get a pointer to the underlying data storage.\n\n This function is allowed to fail if the data is not accessible in RAM,\n or not stored as 64-bit floating point values. If the data is not\n stored as a C-contiguous array, the `strides` function must be set."]
     pub data: ::std::option::Option<
         unsafe extern "C" fn(
             array: *mut ::std::os::raw::c_void,
@@ -225,6 +231,14 @@ pub struct eqs_array_t {
             property_end: usize,
         ) -> eqs_status_t,
     >,
+    #[doc = " Get the strides of the array managed by this `eqs_array_t` in the\n `*strides` pointer, and the number of dimensions (size of the\n `*strides` array) in `*strides_count`.\n\n Strides are given in number of elements: the entry at `[i, j, ...]` is\n stored at `data[i * strides[0] + j * strides[1] + ...]`, where `data` is\n the pointer returned by `eqs_array_t.data`.\n\n This function can be set to `NULL` if the array is always stored as a\n C-contiguous array. Arrays created with `eqs_array_t.create` or\n `eqs_array_t.copy` are expected to be C-contiguous."]
+    pub strides: ::std::option::Option<
+        unsafe extern "C" fn(
+            array: *const ::std::os::raw::c_void,
+            strides: *mut *const usize,
+            strides_count: *mut usize,
+        ) -> eqs_status_t,
+    >,
 }
 #[test]
 fn bindgen_test_layout_eqs_array_t() {
@@ -232,7 +246,7 @@ fn bindgen_test_layout_eqs_array_t() {
     let ptr = UNINIT.as_ptr();
     assert_eq!(
         ::std::mem::size_of::<eqs_array_t>(),
-        80usize,
+        88usize,
         concat!("Size of: ", stringify!(eqs_array_t))
     );
     assert_eq!(
@@ -340,8 +354,18 @@ fn bindgen_test_layout_eqs_array_t() {
             stringify!(move_samples_from)
         )
     );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).strides) as usize - ptr as usize },
+        80usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_array_t),
+            "::",
+            stringify!(strides)
+        )
+    );
 }
-#[doc = " Function pointer to create a new `eqs_array_t` when de-serializing tensor\n maps.\n\n This function gets the `shape` of the array (the `shape` contains\n `shape_count` elements) and should return a new valid `eqs_array_t` or a\n non-zero `eqs_status_t`.\n\n The newly created array should contains 64-bit floating points (`double`)\n data, and live on CPU, since equistore will use `eqs_array_t.data` to get\n the data pointer and write to it."]
+#[doc = " Function pointer to create a new `eqs_array_t` when de-serializing tensor\n maps.\n\n This function gets the `shape` of the array (the `shape` contains\n `shape_count` elements) and should return a new valid `eqs_array_t` or a\n non-zero `eqs_status_t`.\n\n The newly created array should contains 64-bit floating points (`double`)\n data, and live on CPU, since equistore will use `eqs_array_t.data` to get\n the data pointer and write to it.\n\n This can be set to `NULL`, in which case the data is loaded in arrays\n managed by equistore, which only support access through `eqs_array_t`\n functions. This is useful when providing a callback is impractical, e.g.\n when using equistore compiled to WebAssembly."]
 pub type eqs_create_array_callback_t = ::std::option::Option<
     unsafe extern "C" fn(
         shape: *const usize,
@@ -349,6 +373,306 @@ pub type eqs_create_array_callback_t = ::std::option::Option<
         array: *mut eqs_array_t,
     ) -> eqs_status_t,
 >;
+#[doc = " Function pointer to grow in-memory buffers for `eqs_tensormap_save_buffer`.\n\n This function takes an existing pointer in `ptr` and a new length in\n `new_size`, and grows the allocation. If `ptr` is `NULL`, it should create a\n new allocation. If it is unable to allocate memory, it should return a\n `NULL` pointer. This follows the API of the standard C function `realloc`,\n with an additional parameter `user_data` that can be used to hold custom\n data. In particular, the new allocation must start with the data from the\n old one.\n\n This function will be called multiple times while serializing data, every\n time the buffer needs to grow."]
+pub type eqs_realloc_buffer_t = ::std::option::Option<
+    unsafe extern "C" fn(
+        user_data: *mut ::std::os::raw::c_void,
+        ptr: *mut u8,
+        new_size: usize,
+    ) -> *mut u8,
+>;
+#[doc = " `eqs_progress_t` reports the progress of long-running operations\n (serialization and keys restructuring), as the number of blocks processed\n so far out of the total number of blocks; and allows to cancel these\n operations.\n\n This can be used to display progress bars while these operations run, and\n to abort them cleanly from user interfaces."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct eqs_progress_t {
+    #[doc = " User-provided data should be stored here, it will be passed as the\n first parameter to `callback` and `cancelled`."]
+    pub user_data: *mut ::std::os::raw::c_void,
+    #[doc = " Function called after each block is processed, with `done` blocks\n processed so far out of `total`. If this is `NULL`, progress is not\n reported."]
+    pub callback: ::std::option::Option<
+        unsafe extern "C" fn(user_data: *mut ::std::os::raw::c_void, done: usize, total: usize),
+    >,
+    #[doc = " Function called before each block is processed. If it returns `true`,\n the operation stops and returns `EQS_CANCELLED_ERROR`. If this is\n `NULL`, the operation can not be cancelled."]
+    pub cancelled: ::std::option::Option<
+        unsafe extern "C" fn(user_data: *mut ::std::os::raw::c_void) -> bool,
+    >,
+}
+#[test]
+fn bindgen_test_layout_eqs_progress_t() {
+    const UNINIT: ::std::mem::MaybeUninit<eqs_progress_t> = ::std::mem::MaybeUninit::uninit();
+    let ptr = UNINIT.as_ptr();
+    assert_eq!(
+        ::std::mem::size_of::<eqs_progress_t>(),
+        24usize,
+        concat!("Size of: ", stringify!(eqs_progress_t))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<eqs_progress_t>(),
+        8usize,
+        concat!("Alignment of ", stringify!(eqs_progress_t))
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).user_data) as usize - ptr as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_progress_t),
+            "::",
+            stringify!(user_data)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).callback) as usize - ptr as usize },
+        8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_progress_t),
+            "::",
+            stringify!(callback)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).cancelled) as usize - ptr as usize },
+        16usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_progress_t),
+            "::",
+            stringify!(cancelled)
+        )
+    );
+}
+#[doc = " `eqs_read_at_t` provides random access to serialized tensor map data, for\n use with `eqs_tensormap_load_read_at`.\n\n This can be implemented on top of remote storage (HTTP servers, object\n stores, *etc.*), where each call to `read_at` is a request for a range of\n bytes. Only the parts of the data needed to load the requested blocks are\n read."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct eqs_read_at_t {
+    #[doc = " User-provided data should be stored here, it will be passed as the\n first parameter to `read_at`."]
+    pub user_data: *mut ::std::os::raw::c_void,
+    #[doc = " Total size in bytes of the serialized data"]
+    pub size: u64,
+    #[doc = " Read up to `count` bytes starting at `offset` in the serialized data\n into `buffer`, and store the number of bytes actually read in `*read`.\n `*read` should only be set to 0 when `offset` is at or after the end of\n the data."]
+    pub read_at: ::std::option::Option<
+        unsafe extern "C" fn(
+            user_data: *mut ::std::os::raw::c_void,
+            offset: u64,
+            buffer: *mut u8,
+            count: usize,
+            read: *mut usize,
+        ) -> eqs_status_t,
+    >,
+}
+#[test]
+fn bindgen_test_layout_eqs_read_at_t() {
+    const UNINIT: ::std::mem::MaybeUninit<eqs_read_at_t> = ::std::mem::MaybeUninit::uninit();
+    let ptr = UNINIT.as_ptr();
+    assert_eq!(
+        ::std::mem::size_of::<eqs_read_at_t>(),
+        24usize,
+        concat!("Size of: ", stringify!(eqs_read_at_t))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<eqs_read_at_t>(),
+        8usize,
+        concat!("Alignment of ", stringify!(eqs_read_at_t))
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).user_data) as usize - ptr as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_read_at_t),
+            "::",
+            stringify!(user_data)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).size) as usize - ptr as usize },
+        8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_read_at_t),
+            "::",
+            stringify!(size)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).read_at) as usize - ptr as usize },
+        16usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_read_at_t),
+            "::",
+            stringify!(read_at)
+        )
+    );
+}
+#[doc = " Estimation of the memory used by some part of a tensor map, in bytes, for\n use with `eqs_tensormap_memory_usage`."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct eqs_memory_usage_t {
+    #[doc = " memory used by the values data arrays, assuming they contain 64-bit\n floating point values"]
+    pub data: usize,
+    #[doc = " memory used to store the values (and names) of the labels"]
+    pub labels_values: usize,
+    #[doc = " memory used by the hash maps to lookup the position of labels entries"]
+    pub labels_positions: usize,
+    #[doc = " memory used by the gradients, including both data and labels"]
+    pub gradients: usize,
+}
+#[test]
+fn bindgen_test_layout_eqs_memory_usage_t() {
+    const UNINIT: ::std::mem::MaybeUninit<eqs_memory_usage_t> = ::std::mem::MaybeUninit::uninit();
+    let ptr = UNINIT.as_ptr();
+    assert_eq!(
+        ::std::mem::size_of::<eqs_memory_usage_t>(),
+        32usize,
+        concat!("Size of: ", stringify!(eqs_memory_usage_t))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<eqs_memory_usage_t>(),
+        8usize,
+        concat!("Alignment of ", stringify!(eqs_memory_usage_t))
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).data) as usize - ptr as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_memory_usage_t),
+            "::",
+            stringify!(data)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).labels_values) as usize - ptr as usize },
+        8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_memory_usage_t),
+            "::",
+            stringify!(labels_values)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).labels_positions) as usize - ptr as usize },
+        16usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_memory_usage_t),
+            "::",
+            stringify!(labels_positions)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).gradients) as usize - ptr as usize },
+        24usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_memory_usage_t),
+            "::",
+            stringify!(gradients)
+        )
+    );
+}
+#[doc = " Options controlling how tensor maps are saved, for use with\n `eqs_tensormap_save_with_options`."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct eqs_save_options_t {
+    #[doc = " Compress all the labels with zstd, using a dictionary trained on the\n labels of the tensor map and shared between all of them. This can\n significantly reduce the size of files containing many small blocks,\n but the resulting files can not be loaded with numpy."]
+    pub labels_dictionary: bool,
+    #[doc = " Round the values and gradients data to keep only this number of bits\n in the mantissa of the 64-bit floating point values (up to 52), and\n store them in zstd-compressed `<path>.npy.zst` files. The relative\n error introduced by the rounding is at most `2^-(mantissa_bits + 1)`.\n Setting this to 0 keeps the data unchanged."]
+    pub mantissa_bits: u32,
+}
+#[test]
+fn bindgen_test_layout_eqs_save_options_t() {
+    const UNINIT: ::std::mem::MaybeUninit<eqs_save_options_t> = ::std::mem::MaybeUninit::uninit();
+    let ptr = UNINIT.as_ptr();
+    assert_eq!(
+        ::std::mem::size_of::<eqs_save_options_t>(),
+        8usize,
+        concat!("Size of: ", stringify!(eqs_save_options_t))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<eqs_save_options_t>(),
+        4usize,
+        concat!("Alignment of ", stringify!(eqs_save_options_t))
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).labels_dictionary) as usize - ptr as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_save_options_t),
+            "::",
+            stringify!(labels_dictionary)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).mantissa_bits) as usize - ptr as usize },
+        4usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_save_options_t),
+            "::",
+            stringify!(mantissa_bits)
+        )
+    );
+}
+#[doc = " Limits on the size of the data created when loading tensor maps, for use\n with `eqs_tensormap_load_with_limits` and\n `eqs_tensormap_load_buffer_with_limits`.\n\n The sizes of the arrays and labels are read from the serialized data, and\n can be arbitrarily large in corrupted or malicious files. Data over these\n limits is refused before allocating memory for it. Setting any of the\n fields to 0 disables the corresponding limit."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct eqs_load_limits_t {
+    #[doc = " Maximal number of entries in any of the labels (keys, samples,\n components or properties)"]
+    pub max_labels_entries: usize,
+    #[doc = " Maximal size in bytes of any array (values, gradients or strings)"]
+    pub max_array_bytes: usize,
+    #[doc = " Maximal number of blocks in the tensor map"]
+    pub max_blocks: usize,
+}
+#[test]
+fn bindgen_test_layout_eqs_load_limits_t() {
+    const UNINIT: ::std::mem::MaybeUninit<eqs_load_limits_t> = ::std::mem::MaybeUninit::uninit();
+    let ptr = UNINIT.as_ptr();
+    assert_eq!(
+        ::std::mem::size_of::<eqs_load_limits_t>(),
+        24usize,
+        concat!("Size of: ", stringify!(eqs_load_limits_t))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<eqs_load_limits_t>(),
+        8usize,
+        concat!("Alignment of ", stringify!(eqs_load_limits_t))
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).max_labels_entries) as usize - ptr as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_load_limits_t),
+            "::",
+            stringify!(max_labels_entries)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).max_array_bytes) as usize - ptr as usize },
+        8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_load_limits_t),
+            "::",
+            stringify!(max_array_bytes)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).max_blocks) as usize - ptr as usize },
+        16usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(eqs_load_limits_t),
+            "::",
+            stringify!(max_blocks)
+        )
+    );
+}
 extern "C" {
     #[doc = " Disable printing of the message to stderr when some Rust code reach a panic.\n\n All panics from Rust code are caught anyway and translated to an error\n status code, and the message is stored and accessible through\n `eqs_last_error`. To print the error message and Rust backtrace anyway,\n users can set the `RUST_BACKTRACE` environment variable to 1."]
     pub fn eqs_disable_panic_printing();
@@ -425,12 +749,37 @@ extern "C" {
         components_count: usize,
     ) -> eqs_status_t;
     #[must_use]
+    #[doc = " Register the convention for gradients with respect to `parameter`. All\n gradients with respect to this parameter added afterward with\n `eqs_block_add_gradient` must start with the given `components`, followed by\n the components of the values. Registering the same parameter multiple times\n replaces the previous convention.\n\n @param parameter name of the gradient parameter as a NULL-terminated UTF-8\n                  string (e.g. `\"strain\"`)\n @param components array of component labels that gradients with respect to\n                   this parameter must have before the values components\n @param components_count number of entries in the `components` array\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_register_gradient_parameter(
+        parameter: *const ::std::os::raw::c_char,
+        components: *const eqs_labels_t,
+        components_count: usize,
+    ) -> eqs_status_t;
+    #[must_use]
     #[doc = " Get a list of all gradients defined in this `block` in the `parameters` array.\n\n @param block pointer to an existing block\n @param parameters will be set to the first element of an array of\n                   NULL-terminated UTF-8 strings containing all the\n                   parameters for which a gradient exists in the block\n @param parameters_count will be set to the number of elements in `parameters`\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
     pub fn eqs_block_gradients_list(
         block: *const eqs_block_t,
         parameters: *mut *const *const ::std::os::raw::c_char,
         parameters_count: *mut usize,
     ) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Set all the values in this `block` to `value`. The gradients are left\n unchanged.\n\n @param block pointer to an existing block\n @param value new value for all entries in the block values\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_block_fill(block: *mut eqs_block_t, value: f64) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Copy the values and gradients data from `other` into this `block`.\n\n Both blocks must have the same samples, components and properties labels,\n and the same set of gradients with the same labels.\n\n @param block pointer to an existing block, which will be modified\n @param other pointer to an existing block containing the data to copy\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_block_copy_from(block: *mut eqs_block_t, other: *const eqs_block_t) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Swap the samples and properties of this `block`, transposing the values\n data. The components stay in the same order.\n\n Blocks with gradients can not be transposed, since the gradient samples\n refer to the samples of the values.\n\n @param block pointer to an existing block, which will be modified\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_block_transpose(block: *mut eqs_block_t) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Set the values of this `block` for the samples at the given positions to\n `data`. The gradients are left unchanged.\n\n @param block pointer to an existing block\n @param samples positions of the samples to modify in the block\n @param samples_count number of entries in the `samples` array\n @param data new values for the selected samples, in row-major order. This\n             must contain `samples_count` times the number of values for a\n             single sample (i.e. the product of the size of all components\n             and properties).\n @param data_count number of entries in the `data` array\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_block_assign_rows(
+        block: *mut eqs_block_t,
+        samples: *const usize,
+        samples_count: usize,
+        data: *const f64,
+        data_count: usize,
+    ) -> eqs_status_t;
     #[doc = " Create a new `eqs_tensormap_t` with the given `keys` and `blocks`.\n `blocks_count` must be set to the number of entries in the blocks array.\n\n The new tensor map takes ownership of the blocks, which should not be\n released separately.\n\n The memory allocated by this function and the blocks should be released\n using `eqs_tensormap_free`.\n\n @param keys labels containing the keys associated with each block\n @param blocks pointer to the first element of an array of blocks\n @param blocks_count number of elements in the `blocks` array\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
     pub fn eqs_tensormap(
         keys: eqs_labels_t,
@@ -442,6 +791,12 @@ extern "C" {
     pub fn eqs_tensormap_free(tensor: *mut eqs_tensormap_t) -> eqs_status_t;
     #[doc = " Make a copy of an `eqs_tensormap_t`.\n\n The memory allocated by this function and the blocks should be released\n using `eqs_tensormap_free`.\n\n @param tensor existing tensor to copy\n\n @returns A pointer to the newly allocated tensor, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
     pub fn eqs_tensormap_copy(tensor: *const eqs_tensormap_t) -> *mut eqs_tensormap_t;
+    #[doc = " Create a new `eqs_tensormap_t` with the same keys, labels and gradients as\n `tensor`, using the given `arrays` as the values of the blocks. This can be\n used to re-create the same tensor map structure around new data without\n copying any of the metadata.\n\n The new tensor map takes ownership of all the arrays, which will be\n released with `array.destroy(array.ptr)` when they are no longer needed,\n including if this function fails. The memory allocated by this function\n should be released using `eqs_tensormap_free`.\n\n @param tensor existing tensor map\n @param arrays arrays containing the new values, one for each block of the\n               tensor map, in the same order as the blocks. Each array must\n               have the same shape as the corresponding block values.\n @param arrays_count number of elements in the `arrays` array\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer\n          in case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
+    pub fn eqs_tensormap_with_values(
+        tensor: *const eqs_tensormap_t,
+        arrays: *mut eqs_array_t,
+        arrays_count: usize,
+    ) -> *mut eqs_tensormap_t;
     #[must_use]
     #[doc = " Get the keys for the given `tensor` map.\n\n This function allocates memory for `keys` which must be released\n `eqs_labels_free` when you don't need it anymore.\n\n @param tensor pointer to an existing tensor map\n @param keys pointer to be filled with the keys of the tensor map\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
     pub fn eqs_tensormap_keys(
@@ -449,7 +804,7 @@ extern "C" {
         keys: *mut eqs_labels_t,
     ) -> eqs_status_t;
     #[must_use]
-    #[doc = " Get a pointer to the `index`-th block in this tensor map.\n\n The block memory is still managed by the tensor map, this block should not\n be freed. The block is invalidated when the tensor map is freed with\n `eqs_tensormap_free` or the set of keys is modified by calling one\n of the `eqs_tensormap_keys_to_XXX` function.\n\n @param tensor pointer to an existing tensor map\n @param block pointer to be filled with a block\n @param index index of the block to get\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    #[doc = " Get a pointer to the `index`-th block in this tensor map.\n\n The block memory is still managed by the tensor map, this block should not\n be freed. The block is invalidated when the tensor map is freed with\n `eqs_tensormap_free` or the set of keys is modified by calling one\n of the `eqs_tensormap_keys_to_XXX` function.\n\n @param tensor pointer to an existing tensor map\n @param block pointer to be filled with a block\n @param index index of the block to get\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message. If `index` is out of bounds, this returns\n          `EQS_INVALID_PARAMETER_ERROR`."]
     pub fn eqs_tensormap_block_by_id(
         tensor: *mut eqs_tensormap_t,
         block: *mut *mut eqs_block_t,
@@ -463,12 +818,28 @@ extern "C" {
         count: *mut usize,
         selection: eqs_labels_t,
     ) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Compress in memory the data of all blocks in this `tensor` matching the\n given `selection`, using the same rules as `eqs_tensormap_blocks_matching`.\n\n The data of compressed blocks is stored with zstd, and is decompressed the\n first time it is accessed through `eqs_array_t.data`. Compressed arrays use\n a separate data origin, use `eqs_tensormap_decompress_blocks` to convert\n them back to the original array type.\n\n @param tensor pointer to an existing tensor map\n @param selection labels with a single entry describing which blocks should\n                  be compressed\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_tensormap_compress_blocks(
+        tensor: *mut eqs_tensormap_t,
+        selection: eqs_labels_t,
+    ) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Convert the data of all blocks compressed with\n `eqs_tensormap_compress_blocks` in this `tensor` back to the original array\n type.\n\n @param tensor pointer to an existing tensor map\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_tensormap_decompress_blocks(tensor: *mut eqs_tensormap_t) -> eqs_status_t;
     #[doc = " Merge blocks with the same value for selected keys dimensions along the\n property axis.\n\n The dimensions (names) of `keys_to_move` will be moved from the keys to\n the property labels, and blocks with the same remaining keys dimensions\n will be merged together along the property axis.\n\n If `keys_to_move` does not contains any entries (`keys_to_move.count\n == 0`), then the new property labels will contain entries corresponding\n to the merged blocks only. For example, merging a block with key `a=0`\n and properties `p=1, 2` with a block with key `a=2` and properties `p=1,\n 3` will produce a block with properties `a, p = (0, 1), (0, 2), (2, 1),\n (2, 3)`.\n\n If `keys_to_move` contains entries, then the property labels must be the\n same for all the merged blocks. In that case, the merged property labels\n will contains each of the entries of `keys_to_move` and then the current\n property labels. For example, using `a=2, 3` in `keys_to_move`, and\n blocks with properties `p=1, 2` will result in `a, p = (2, 1), (2, 2),\n (3, 1), (3, 2)`.\n\n The new sample labels will contains all of the merged blocks sample\n labels. The order of the samples is controlled by `sort_samples`. If\n `sort_samples` is true, samples are re-ordered to keep them\n lexicographically sorted. Otherwise they are kept in the order in which\n they appear in the blocks.\n\n The result is a new tensor map, which should be freed with `eqs_tensormap_free`.\n\n @param tensor pointer to an existing tensor map\n @param keys_to_move description of the keys to move\n @param sort_samples whether to sort the samples lexicographically after\n                     merging blocks\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
     pub fn eqs_tensormap_keys_to_properties(
         tensor: *const eqs_tensormap_t,
         keys_to_move: eqs_labels_t,
         sort_samples: bool,
     ) -> *mut eqs_tensormap_t;
+    #[doc = " Same as `eqs_tensormap_keys_to_properties`, reporting the number of new blocks\n created so far with `progress`.\n\n @param tensor pointer to an existing tensor map\n @param keys_to_move description of the keys to move\n @param sort_samples whether to sort the samples lexicographically after\n                     merging blocks\n @param progress callbacks used to report the progress of this operation\n                 and to cancel it\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
+    pub fn eqs_tensormap_keys_to_properties_with_progress(
+        tensor: *const eqs_tensormap_t,
+        keys_to_move: eqs_labels_t,
+        sort_samples: bool,
+        progress: eqs_progress_t,
+    ) -> *mut eqs_tensormap_t;
     #[doc = " Move the given dimensions from the component labels to the property labels\n for each block in this tensor map.\n\n `dimensions` must be an array of `dimensions_count` NULL-terminated strings,\n encoded as UTF-8.\n\n @param tensor pointer to an existing tensor map\n @param dimensions names of the key dimensions to move to the properties\n @param dimensions_count number of entries in the `dimensions` array\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
     pub fn eqs_tensormap_components_to_properties(
         tensor: *mut eqs_tensormap_t,
@@ -481,6 +852,63 @@ extern "C" {
         keys_to_move: eqs_labels_t,
         sort_samples: bool,
     ) -> *mut eqs_tensormap_t;
+    #[doc = " Same as `eqs_tensormap_keys_to_samples`, reporting the number of new blocks\n created so far with `progress`.\n\n @param tensor pointer to an existing tensor map\n @param keys_to_move description of the keys to move\n @param sort_samples whether to sort the samples lexicographically after\n                     merging blocks\n @param progress callbacks used to report the progress of this operation\n                 and to cancel it\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
+    pub fn eqs_tensormap_keys_to_samples_with_progress(
+        tensor: *const eqs_tensormap_t,
+        keys_to_move: eqs_labels_t,
+        sort_samples: bool,
+        progress: eqs_progress_t,
+    ) -> *mut eqs_tensormap_t;
+    #[doc = " Get a canonical version of this `tensor` map, where the keys are sorted in\n lexicographic order, and the samples and properties of each block (and the\n samples of each gradient) are sorted as well. The data arrays are re-ordered\n accordingly.\n\n Two tensor maps containing the same data in different order will give the\n same result after canonicalization, and will be saved to the same bytes by\n `eqs_tensormap_save`. Components are not re-ordered.\n\n The result is a new tensor map, which should be freed with `eqs_tensormap_free`.\n\n @param tensor pointer to an existing tensor map\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
+    pub fn eqs_tensormap_canonicalize(tensor: *const eqs_tensormap_t) -> *mut eqs_tensormap_t;
+    #[must_use]
+    #[doc = " Compute a stable 64-bit fingerprint of this `tensor` map, and store it in\n `fingerprint`.\n\n The fingerprint covers all the metadata (keys, samples, components,\n properties, gradients, units, quantities and tensor map metadata) and the\n shape of all arrays. If `include_data` is `true`, the values stored in the\n arrays are also part of the fingerprint.\n\n The fingerprint does not depend on the platform, the process or the version\n of equistore, and can be used as a cache key. It does depend on the order of\n entries in the labels, use `eqs_tensormap_canonicalize` first to ignore it.\n\n @param tensor pointer to an existing tensor map\n @param include_data whether to include the values of the arrays in the\n                     fingerprint\n @param fingerprint pointer to be filled with the fingerprint\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_tensormap_fingerprint(
+        tensor: *const eqs_tensormap_t,
+        include_data: bool,
+        fingerprint: *mut u64,
+    ) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Compare the two tensor maps `first` and `second`, matching blocks by key,\n and write a structured report of the differences to `buffer` as an UTF-8\n encoded, NULL-terminated JSON string.\n\n The JSON document is an object with the `metadata`, `keys_names`,\n `only_in_first`, `only_in_second` and `blocks` fields. Each entry in\n `blocks` contains the `key` of the block, the gradient `parameter` (or\n `\"values\"`) and a `difference` object, with a `kind` field set to one of\n `\"unit\"`, `\"quantity\"`, `\"missing_gradient\"`, `\"samples\"`, `\"components\"`,\n `\"properties\"`, `\"shape\"` or `\"data\"`. All the lists are empty if no\n difference was found.\n\n If the buffer is too small to hold the report, this function returns\n `EQS_BUFFER_SIZE_ERROR`, and it should be called again with a larger buffer.\n\n @param first pointer to an existing tensor map\n @param second pointer to another existing tensor map\n @param compare_data whether to compare the values inside the arrays\n @param tolerance absolute tolerance used when comparing the data\n @param buffer buffer to be filled with the JSON report\n @param buffer_size size of the buffer\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_tensormap_diff_json(
+        first: *const eqs_tensormap_t,
+        second: *const eqs_tensormap_t,
+        compare_data: bool,
+        tolerance: f64,
+        buffer: *mut ::std::os::raw::c_char,
+        buffer_size: usize,
+    ) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Expand all the blocks in all the `tensors` to use the same set of\n properties, i.e. the union of the properties of all blocks. Entries\n corresponding to properties missing in a block are filled with zeros.\n\n The resulting tensor maps can be directly summed or concatenated along\n samples. The union of properties is sorted in lexicographic order.\n\n `aligned` must point to an array of `tensors_count` pointers, which will be\n set to the newly allocated tensor maps, in the same order as `tensors`.\n These should be freed with `eqs_tensormap_free`.\n\n @param tensors array of pointers to existing tensor maps\n @param tensors_count number of entries in the `tensors` and `aligned` arrays\n @param aligned array of pointers to be filled with the aligned tensor maps\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_tensormap_align_properties(
+        tensors: *const *const eqs_tensormap_t,
+        tensors_count: usize,
+        aligned: *mut *mut eqs_tensormap_t,
+    ) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Restrict both `first` and `second` tensor maps to the samples they have in\n common.\n\n Blocks are matched by key, and both tensor maps must contain the same set of\n keys. For each pair of blocks, only the samples present in both blocks are\n kept, in the order they appear in the block from `first`; and gradients are\n updated accordingly.\n\n The new tensor maps are stored in `first_output` and `second_output`, and\n should be freed with `eqs_tensormap_free`. If `mappings` is not `NULL`, it\n must point to an array of `mappings_count` labels (one for each key in\n `first`), which will be filled with the positions of the kept samples in\n the original blocks, as labels with two dimensions: `first` and `second`.\n These labels must be freed with `eqs_labels_free`.\n\n @param first pointer to an existing tensor map\n @param second pointer to another existing tensor map\n @param first_output pointer to be filled with the restricted `first`\n @param second_output pointer to be filled with the restricted `second`\n @param mappings array of labels to be filled with the samples positions,\n                 or `NULL`\n @param mappings_count number of entries in the `mappings` array\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_tensormap_intersect_samples(
+        first: *const eqs_tensormap_t,
+        second: *const eqs_tensormap_t,
+        first_output: *mut *mut eqs_tensormap_t,
+        second_output: *mut *mut eqs_tensormap_t,
+        mappings: *mut eqs_labels_t,
+        mappings_count: usize,
+    ) -> eqs_status_t;
+    #[doc = " Compute the outer product of the properties of `first` and `second`.\n\n Both tensor maps must have the same keys (possibly in a different order),\n and the blocks with the same key must have the same samples and components.\n The properties of the new blocks are the cartesian product of the\n properties of the two blocks, with the names of the properties prefixed by\n `first_` and `second_` respectively. The keys of the result are the keys of\n `first`. Blocks with gradients are not supported.\n\n The result is a new tensor map, which should be freed with `eqs_tensormap_free`.\n\n @param first pointer to an existing tensor map\n @param second pointer to another existing tensor map\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
+    pub fn eqs_tensormap_product(
+        first: *const eqs_tensormap_t,
+        second: *const eqs_tensormap_t,
+    ) -> *mut eqs_tensormap_t;
+    #[must_use]
+    #[doc = " Estimate the memory used by this `tensor` map, in bytes.\n\n The memory used by the keys is stored in `keys_usage`, and the memory used\n by each block (including labels and gradients) is stored in `blocks_usage`,\n which must contain space for as many entries as there are keys in the\n tensor map. The total memory used by the tensor map is stored in `total`.\n Labels shared between multiple blocks are only counted once in `total`.\n The size of data arrays is estimated from their shape, assuming they\n contain 64-bit floating point values, since `eqs_array_t` does not expose\n the actual type of the data. The memory used by arrays storing other types\n (e.g. 32-bit floating point values) is over- or under-estimated\n accordingly.\n\n @param tensor pointer to an existing tensor map\n @param keys_usage pointer to be filled with the memory used by the keys\n @param blocks_usage array to be filled with the memory used by each block\n @param blocks_count number of entries in the `blocks_usage` array\n @param total pointer to be filled with the total memory used\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_tensormap_memory_usage(
+        tensor: *const eqs_tensormap_t,
+        keys_usage: *mut eqs_memory_usage_t,
+        blocks_usage: *mut eqs_memory_usage_t,
+        blocks_count: usize,
+        total: *mut eqs_memory_usage_t,
+    ) -> eqs_status_t;
     #[doc = " Load a tensor map from the file at the given path.\n\n Arrays for the values and gradient data will be created with the given\n `create_array` callback, and filled by this function with the corresponding\n data.\n\n The memory allocated by this function should be released using\n `eqs_tensormap_free`.\n\n `TensorMap` are serialized using numpy's `.npz` format, i.e. a ZIP file\n without compression (storage method is STORED), where each file is stored as\n a `.npy` array. Both the ZIP and NPY format are well documented:\n\n - ZIP: <https://pkware.cachefly.net/webdocs/casestudies/APPNOTE.TXT>\n - NPY: <https://numpy.org/doc/stable/reference/generated/numpy.lib.format.html>\n\n We add other restriction on top of these formats when saving/loading data.\n First, `Labels` instances are saved as structured array, see the `labels`\n module for more information. Only 32-bit integers are supported for Labels,\n and only 64-bit floats are supported for data (values and gradients).\n\n Second, the path of the files in the archive also carry meaning. The keys of\n the `TensorMap` are stored in `/keys.npy`, and then different blocks are\n stored as\n\n ```bash\n /  blocks / <block_id>  / values / samples.npy\n                         / values / components  / 0.npy\n                                                / <...>.npy\n                                                / <n_components>.npy\n                         / values / properties.npy\n                         / values / data.npy\n\n                         # optional sections for gradients, one by parameter\n                         /   gradients / <parameter> / samples.npy\n                                                     /   components  / 0.npy\n                                                                     / <...>.npy\n                                                                     / <n_components>.npy\n                                                     /   data.npy\n ```\n\n @param path path to the file as a NULL-terminated UTF-8 string\n @param create_array callback function that will be used to create data\n                     arrays inside each block\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
     pub fn eqs_tensormap_load(
         path: *const ::std::os::raw::c_char,
@@ -492,4 +920,104 @@ extern "C" {
         path: *const ::std::os::raw::c_char,
         tensor: *const eqs_tensormap_t,
     ) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Save a tensor map to the file at the given path, using the given `options`.\n\n If the file already exists, it is overwritten. Files saved with\n `eqs_save_options_t.labels_dictionary` set store the labels in\n zstd-compressed `<path>.npy.zst` files, and the shared dictionary in\n `/labels-dictionary.zstd`. Files saved with\n `eqs_save_options_t.mantissa_bits` set store the data arrays in\n zstd-compressed `<path>.npy.zst` files. They can be loaded with\n `eqs_tensormap_load` and the other loading functions.\n\n @param path path to the file as a NULL-terminated UTF-8 string\n @param tensor tensor map to save to the file\n @param options options controlling the serialization\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_tensormap_save_with_options(
+        path: *const ::std::os::raw::c_char,
+        tensor: *const eqs_tensormap_t,
+        options: eqs_save_options_t,
+    ) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Save a tensor map to the file at the given path, using the given `options`\n and reporting the number of blocks saved so far with `progress`.\n\n If the file already exists, it is overwritten.\n\n @param path path to the file as a NULL-terminated UTF-8 string\n @param tensor tensor map to save to the file\n @param options options controlling the serialization\n @param progress callbacks used to report the progress of this operation\n                 and to cancel it\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_tensormap_save_with_progress(
+        path: *const ::std::os::raw::c_char,
+        tensor: *const eqs_tensormap_t,
+        options: eqs_save_options_t,
+        progress: eqs_progress_t,
+    ) -> eqs_status_t;
+    #[doc = " Load a tensor map from the given in-memory buffer.\n\n This function is similar to `eqs_tensormap_load`, but reads the data from\n an in-memory buffer containing the same content as a file saved with\n `eqs_tensormap_save`, instead of a file on disk.\n\n The memory allocated by this function should be released using\n `eqs_tensormap_free`.\n\n @param buffer buffer containing a previously serialized tensor map\n @param buffer_count number of elements in the buffer\n @param create_array callback function that will be used to create data\n                     arrays inside each block\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
+    pub fn eqs_tensormap_load_buffer(
+        buffer: *const u8,
+        buffer_count: usize,
+        create_array: eqs_create_array_callback_t,
+    ) -> *mut eqs_tensormap_t;
+    #[doc = " Load a tensor map from the file at the given path, refusing data larger\n than the given `limits`.\n\n This function is similar to `eqs_tensormap_load`, and should be preferred\n to it when loading files from untrusted sources. Data over the limits is\n reported with `EQS_SERIALIZATION_ERROR`.\n\n The memory allocated by this function should be released using\n `eqs_tensormap_free`.\n\n @param path path to the file as a NULL-terminated UTF-8 string\n @param limits limits on the size of the loaded data\n @param create_array callback function that will be used to create data\n                     arrays inside each block\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
+    pub fn eqs_tensormap_load_with_limits(
+        path: *const ::std::os::raw::c_char,
+        limits: eqs_load_limits_t,
+        create_array: eqs_create_array_callback_t,
+    ) -> *mut eqs_tensormap_t;
+    #[doc = " Load a tensor map from the given in-memory buffer, refusing data larger\n than the given `limits`.\n\n This function is similar to `eqs_tensormap_load_buffer`, see\n `eqs_tensormap_load_with_limits` for more information about the limits.\n\n The memory allocated by this function should be released using\n `eqs_tensormap_free`.\n\n @param buffer buffer containing a previously serialized tensor map\n @param buffer_count number of elements in the buffer\n @param limits limits on the size of the loaded data\n @param create_array callback function that will be used to create data\n                     arrays inside each block\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
+    pub fn eqs_tensormap_load_buffer_with_limits(
+        buffer: *const u8,
+        buffer_count: usize,
+        limits: eqs_load_limits_t,
+        create_array: eqs_create_array_callback_t,
+    ) -> *mut eqs_tensormap_t;
+    #[doc = " Load a tensor map from the file at the given path, refusing data larger\n than the given `limits` and reporting the number of blocks loaded so far\n with `progress`.\n\n See `eqs_tensormap_load_with_limits` for more information about the limits.\n\n The memory allocated by this function should be released using\n `eqs_tensormap_free`.\n\n @param path path to the file as a NULL-terminated UTF-8 string\n @param limits limits on the size of the loaded data\n @param progress callbacks used to report the progress of this operation\n                 and to cancel it\n @param create_array callback function that will be used to create data\n                     arrays inside each block\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
+    pub fn eqs_tensormap_load_with_progress(
+        path: *const ::std::os::raw::c_char,
+        limits: eqs_load_limits_t,
+        progress: eqs_progress_t,
+        create_array: eqs_create_array_callback_t,
+    ) -> *mut eqs_tensormap_t;
+    #[must_use]
+    #[doc = " Save a tensor map to an in-memory buffer.\n\n The data is written directly to the buffer, which is grown as needed with\n the `realloc` callback. On input, `*buffer` should contain either `NULL` or\n a pointer previously allocated with `realloc`, and `*buffer_count` the size\n of this allocation. The existing allocation is re-used if it is large\n enough. On output, `*buffer` contains the (possibly new) allocation and\n `*buffer_count` the size of the serialized data, which can be smaller than\n the size of the allocation.\n\n The serialized data is the same as the content of a file created by\n `eqs_tensormap_save`.\n\n @param buffer pointer to the buffer which will contain the serialized data\n @param buffer_count pointer to the size of the existing allocation, which\n                     will be set to the size of the serialized data\n @param realloc_user_data custom data for the `realloc` callback. This will\n                          be passed as the first argument to `realloc` as-is.\n @param realloc function to allocate and grow the buffer\n @param tensor tensor map to save\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_tensormap_save_buffer(
+        buffer: *mut *mut u8,
+        buffer_count: *mut usize,
+        realloc_user_data: *mut ::std::os::raw::c_void,
+        realloc: eqs_realloc_buffer_t,
+        tensor: *const eqs_tensormap_t,
+    ) -> eqs_status_t;
+    #[doc = " Load some or all of the blocks of a tensor map from the given `reader`.\n\n This function is similar to `eqs_tensormap_load`, but reads the data\n through the `reader.read_at` callback instead of from a file. Only the\n parts of the data that are needed are read: the ZIP central directory, the\n keys and the files of the requested blocks.\n\n The memory allocated by this function should be released using\n `eqs_tensormap_free`.\n\n @param reader callback-based random access to the serialized data\n @param blocks indexes of the blocks to load, in the order in which they\n               should appear in the new tensor map. If this is `NULL`, all\n               blocks are loaded.\n @param blocks_count number of entries in the `blocks` array\n @param create_array callback function that will be used to create data\n                     arrays inside each block\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
+    pub fn eqs_tensormap_load_read_at(
+        reader: eqs_read_at_t,
+        blocks: *const usize,
+        blocks_count: usize,
+        create_array: eqs_create_array_callback_t,
+    ) -> *mut eqs_tensormap_t;
+    #[doc = " Start a new chain of lazy element-wise operations from the given `source`\n tensor map.\n\n Operations are only recorded by the `eqs_lazy_tensormap_*` functions, and\n nothing is computed until `eqs_lazy_tensormap_compute` or\n `eqs_lazy_tensormap_compute_block` is called. All the recorded operations\n are then applied in a single pass over the data, without allocating\n intermediary arrays. Gradients are propagated through all operations.\n\n `source` must stay alive (and must not be modified) until the lazy tensor\n map is freed with `eqs_lazy_tensormap_free`.\n\n @param source pointer to an existing tensor map\n\n @returns A pointer to the newly allocated lazy tensor map, or a `NULL`\n          pointer in case of error. In case of error, you can use\n          `eqs_last_error()` to get the error message."]
+    pub fn eqs_lazy_tensormap(source: *const eqs_tensormap_t) -> *mut eqs_lazy_tensormap_t;
+    #[must_use]
+    #[doc = " Free the memory associated with a `lazy` tensor map previously created with\n `eqs_lazy_tensormap`. This does not free the tensor maps used in the\n operations.\n\n If `lazy` is `NULL`, this function does nothing.\n\n @param lazy pointer to an existing lazy tensor map, or `NULL`\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_lazy_tensormap_free(lazy: *mut eqs_lazy_tensormap_t) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Set the policy used by the binary operations recorded after this call when\n only some of the tensor maps contain gradients with respect to a given\n parameter. The default is `\"error\"`.\n\n `missing_gradients` must be one of `\"error\"` (return an error), `\"zero\"`\n (treat missing gradients as zero-filled, with the same metadata as the\n other tensor maps) or `\"drop\"` (only keep the gradients present in all\n tensor maps).\n\n @param lazy pointer to an existing lazy tensor map\n @param missing_gradients how to handle missing gradients, as a\n        NULL-terminated UTF-8 string\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_lazy_tensormap_missing_gradients(
+        lazy: *mut eqs_lazy_tensormap_t,
+        missing_gradients: *const ::std::os::raw::c_char,
+    ) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Allow the binary operations recorded after this call to use tensor maps\n whose keys only contain a subset of the keys dimensions of the source\n tensor map. Each block of the source is then combined with the block of\n the other tensor map with the same values for the common keys dimensions.\n The default is `false`, requiring both tensor maps to have the same keys.\n\n @param lazy pointer to an existing lazy tensor map\n @param broadcast whether to broadcast keys in binary operations\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_lazy_tensormap_broadcast_keys(
+        lazy: *mut eqs_lazy_tensormap_t,
+        broadcast: bool,
+    ) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Add a unary element-wise operation to the chain of operations in `lazy`.\n\n `operation` must be one of `\"add_scalar\"` (`x + value`),\n `\"multiply_scalar\"` (`x * value`), `\"pow\"` (`x ^ value`), `\"abs\"`,\n `\"sqrt\"`, `\"exp\"` or `\"log\"`. `value` is ignored by the operations which\n do not use it.\n\n @param lazy pointer to an existing lazy tensor map\n @param operation name of the operation, as a NULL-terminated UTF-8 string\n @param value parameter of the operation\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_lazy_tensormap_unary(
+        lazy: *mut eqs_lazy_tensormap_t,
+        operation: *const ::std::os::raw::c_char,
+        value: f64,
+    ) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Add `other` to the current result of the operations in `lazy`. `other`\n must have the same metadata as the source of `lazy`.\n\n `other` must stay alive (and must not be modified) until `lazy` is freed.\n\n @param lazy pointer to an existing lazy tensor map\n @param other pointer to an existing tensor map\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_lazy_tensormap_add(
+        lazy: *mut eqs_lazy_tensormap_t,
+        other: *const eqs_tensormap_t,
+    ) -> eqs_status_t;
+    #[must_use]
+    #[doc = " Multiply the current result of the operations in `lazy` by `other`.\n `other` must have the same metadata as the source of `lazy`.\n\n `other` must stay alive (and must not be modified) until `lazy` is freed.\n\n @param lazy pointer to an existing lazy tensor map\n @param other pointer to an existing tensor map\n\n @returns The status code of this operation. If the status is not\n          `EQS_SUCCESS`, you can use `eqs_last_error()` to get the full\n          error message."]
+    pub fn eqs_lazy_tensormap_multiply(
+        lazy: *mut eqs_lazy_tensormap_t,
+        other: *const eqs_tensormap_t,
+    ) -> eqs_status_t;
+    #[doc = " Evaluate all the operations recorded in `lazy` for all blocks, and return\n the resulting tensor map.\n\n The result is a new tensor map, which should be freed with\n `eqs_tensormap_free`.\n\n @param lazy pointer to an existing lazy tensor map\n\n @returns A pointer to the newly allocated tensor map, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
+    pub fn eqs_lazy_tensormap_compute(lazy: *const eqs_lazy_tensormap_t) -> *mut eqs_tensormap_t;
+    #[doc = " Evaluate all the operations recorded in `lazy` for the block at the given\n `index` only, and return the resulting block.\n\n The result is a new block, which should be freed with `eqs_block_free`.\n\n @param lazy pointer to an existing lazy tensor map\n @param index index of the block to compute\n\n @returns A pointer to the newly allocated block, or a `NULL` pointer in\n          case of error. In case of error, you can use `eqs_last_error()`\n          to get the error message."]
+    pub fn eqs_lazy_tensormap_compute_block(
+        lazy: *const eqs_lazy_tensormap_t,
+        index: usize,
+    ) -> *mut eqs_block_t;
 }
@@ -63,6 +63,37 @@ pub trait Array: std::any::Any + Send + Sync {
         samples: &[eqs_sample_mapping_t],
         properties: Range<usize>,
     );
+
+    /// Reserve space for at least `additional_samples` more samples (i.e.
+    /// entries along the first axis) in this array, so that the following
+    /// calls to [`Array::append_samples`] do not need to reallocate memory.
+    ///
+    /// The default implementation does nothing.
+    fn reserve(&mut self, additional_samples: usize) {
+        let _ = additional_samples;
+    }
+
+    /// Add all the samples from `input` at the end of this array, growing the
+    /// first axis of the array.
+    ///
+    /// `input` must have the same type as this array, and the same shape
+    /// except for the first axis. Implementations should use amortized
+    /// growth, such that appending samples repeatedly does not reallocate the
+    /// full array each time.
+    ///
+    /// The default implementation panics.
+    fn append_samples(&mut self, input: &dyn Array) {
+        let _ = input;
+        panic!("Array::append_samples() is not implemented for this array type");
+    }
+}
+
+fn check_append_shapes(shape: &[usize], input: &[usize]) {
+    assert!(
+        !shape.is_empty() && !input.is_empty() && shape[1..] == input[1..],
+        "can not append samples from an array with shape {:?} to an array with shape {:?}",
+        input, shape
+    );
 }
 
 impl From<Box<dyn Array>> for eqs_array_t {
@@ -83,6 +114,7 @@ impl From<Box<dyn Array>> for eqs_array_t {
             copy: Some(rust_array_copy),
             destroy: Some(rust_array_destroy),
             move_samples_from: Some(rust_array_move_samples_from),
+            strides: None,
         }
     }
 }
@@ -296,6 +328,26 @@ impl Array for ndarray::ArrayD<f64> {
             output_location.assign(&value);
         }
     }
+
+    fn reserve(&mut self, additional_samples: usize) {
+        // ndarray 0.15 does not allow to reserve memory directly, so we
+        // re-create the array from a `Vec` with the right capacity. The
+        // capacity is then used by `append` below.
+        let shape = self.shape().to_vec();
+        let sample_size = shape[1..].iter().product::<usize>();
+
+        let mut data = Vec::with_capacity(self.len() + additional_samples * sample_size);
+        data.extend(self.iter().copied());
+        *self = ndarray::ArrayD::from_shape_vec(shape, data).expect("invalid shape");
+    }
+
+    fn append_samples(&mut self, input: &dyn Array) {
+        let input = input.as_any().downcast_ref::<ndarray::ArrayD<f64>>().expect("input must be a ndarray");
+        check_append_shapes(self.shape(), input.shape());
+
+        // `append` grows the underlying `Vec`, which ensures amortized growth
+        self.append(ndarray::Axis(0), input.view()).expect("failed to append samples");
+    }
 }
 
 /******************************************************************************/
@@ -351,4 +403,9 @@ impl Array for EmptyArray {
     fn move_samples_from(&mut self, _: &dyn Array, _: &[eqs_sample_mapping_t], _: Range<usize>) {
         panic!("can not call Array::move_samples_from() for EmptyArray");
     }
+
+    fn append_samples(&mut self, input: &dyn Array) {
+        check_append_shapes(&self.shape, input.shape());
+        self.shape[0] += input.shape()[0];
+    }
 }
@@ -261,6 +261,7 @@ impl eqs_array_t {
             copy: None,
             destroy: None,
             move_samples_from: None,
+            strides: None,
         }
     }
 
@@ -365,7 +366,8 @@ impl eqs_array_t {
             create: None,
             copy: None,
             destroy: None,
-            move_samples_from: None
+            move_samples_from: None,
+            strides: None,
         };
         unsafe {
             check_status_external(
@@ -1,4 +1,5 @@
 mod origin;
+pub(crate) use self::origin::get_data_origin;
 
 mod array_ref;
 pub use self::array_ref::{ArrayRef, ArrayRefMut};
@@ -66,4 +67,34 @@ mod tests {
         ]).unwrap();
         assert_eq!(other.as_array(), expected);
     }
+
+    #[test]
+    fn append_samples() {
+        let mut array = ArrayD::from_elem(vec![2, 3], 1.0);
+        array.reserve(4);
+        for i in 0..4 {
+            array.append_samples(&ArrayD::from_elem(vec![1, 3], f64::from(i)));
+        }
+
+        let expected = ArrayD::from_shape_vec(vec![6, 3], vec![
+            1.0, 1.0, 1.0,
+            1.0, 1.0, 1.0,
+            0.0, 0.0, 0.0,
+            1.0, 1.0, 1.0,
+            2.0, 2.0, 2.0,
+            3.0, 3.0, 3.0,
+        ]).unwrap();
+        assert_eq!(array, expected);
+
+        let mut empty = EmptyArray::new(vec![2, 4]);
+        empty.append_samples(&EmptyArray::new(vec![3, 4]));
+        assert_eq!(Array::shape(&empty), [5, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "can not append samples from an array with shape [1, 2] to an array with shape [2, 3]")]
+    fn append_samples_wrong_shape() {
+        let mut array = ArrayD::from_elem(vec![2, 3], 1.0);
+        array.append_samples(&ArrayD::from_elem(vec![1, 2], 1.0));
+    }
 }
@@ -20,7 +20,7 @@ pub(super) fn register_data_origin(name: String) -> Result<eqs_data_origin_t, Er
 }
 
 /// Get the name associated with a data origin
-pub(super) fn get_data_origin(origin: eqs_data_origin_t) -> Result<String, Error> {
+pub(crate) fn get_data_origin(origin: eqs_data_origin_t) -> Result<String, Error> {
     use std::ffi::CStr;
 
     let mut buffer: Vec<u8> = vec![0; 32];
@@ -0,0 +1,264 @@
+use std::ffi::CStr;
+
+use serde_json::Value;
+
+use crate::errors::check_status;
+use crate::{Error, TensorMap};
+
+/// Options for [`TensorMap::diff`]
+#[derive(Debug, Clone, Copy)]
+pub struct DiffOptions {
+    /// Should we compare the values inside the arrays?
+    pub compare_data: bool,
+    /// Absolute tolerance used when comparing data, differences smaller than
+    /// this are not reported.
+    pub tolerance: f64,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            compare_data: true,
+            tolerance: 0.0,
+        }
+    }
+}
+
+/// Kind of difference between two blocks with the same key
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockDifference {
+    /// The blocks have different units, given as `(first, second)`
+    Unit(Option<String>, Option<String>),
+    /// The blocks represent different quantities, given as `(first, second)`
+    Quantity(Option<String>, Option<String>),
+    /// The gradient only exists in one of the blocks
+    MissingGradient {
+        /// Is the gradient present in the first tensor map?
+        in_first: bool,
+    },
+    /// The samples labels are different
+    Samples,
+    /// The component labels at the given position are different
+    Components(usize),
+    /// The properties labels are different
+    Properties,
+    /// The arrays have different shapes
+    Shape(Vec<usize>, Vec<usize>),
+    /// The data differs, with the given maximal absolute element-wise
+    /// difference. This is `None` if the difference is not finite (e.g. when
+    /// only one of the arrays contains `NaN`).
+    Data {
+        max_difference: Option<f64>,
+    },
+}
+
+/// Single difference between two blocks sharing the same key
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockDiff {
+    /// key of the block in both tensor maps
+    pub key: Vec<i32>,
+    /// either `"values"` or the parameter of the gradient
+    pub parameter: String,
+    /// what is different between the two blocks
+    pub difference: BlockDifference,
+}
+
+/// Structured report of the differences between two tensor maps, created by
+/// [`TensorMap::diff`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TensorMapDiff {
+    /// Metadata entries with different values in the two tensor maps, as
+    /// `(key, first value, second value)`. A missing value is `None`.
+    pub metadata: Vec<(String, Option<String>, Option<String>)>,
+    /// Names of the keys in both tensor maps, if they differ. When the names
+    /// are different, no other comparison is made.
+    pub keys_names: Option<(Vec<String>, Vec<String>)>,
+    /// Keys only present in the first tensor map
+    pub only_in_first: Vec<Vec<i32>>,
+    /// Keys only present in the second tensor map
+    pub only_in_second: Vec<Vec<i32>>,
+    /// Differences between blocks present in both tensor maps
+    pub blocks: Vec<BlockDiff>,
+}
+
+impl TensorMapDiff {
+    /// Are the two tensor maps equivalent?
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty()
+            && self.keys_names.is_none()
+            && self.only_in_first.is_empty()
+            && self.only_in_second.is_empty()
+            && self.blocks.is_empty()
+    }
+
+    /// Create a `TensorMapDiff` from the JSON report produced by
+    /// `eqs_tensormap_diff_json`
+    fn from_json(json: &str) -> TensorMapDiff {
+        let report: Value = serde_json::from_str(json).expect("invalid JSON in diff report");
+
+        let metadata = json_list(&report["metadata"], |entry| (
+            json_str(&entry["key"]),
+            json_optional_str(&entry["first"]),
+            json_optional_str(&entry["second"]),
+        ));
+
+        let keys_names = if report["keys_names"].is_null() {
+            None
+        } else {
+            Some((
+                json_list(&report["keys_names"]["first"], json_str),
+                json_list(&report["keys_names"]["second"], json_str),
+            ))
+        };
+
+        let blocks = json_list(&report["blocks"], |block| {
+            let difference = &block["difference"];
+            let difference = match difference["kind"].as_str() {
+                Some("unit") => BlockDifference::Unit(
+                    json_optional_str(&difference["first"]),
+                    json_optional_str(&difference["second"]),
+                ),
+                Some("quantity") => BlockDifference::Quantity(
+                    json_optional_str(&difference["first"]),
+                    json_optional_str(&difference["second"]),
+                ),
+                Some("missing_gradient") => BlockDifference::MissingGradient {
+                    in_first: difference["in_first"].as_bool().expect("in_first should be a boolean"),
+                },
+                Some("samples") => BlockDifference::Samples,
+                Some("components") => BlockDifference::Components(json_usize(&difference["index"])),
+                Some("properties") => BlockDifference::Properties,
+                Some("shape") => BlockDifference::Shape(
+                    json_list(&difference["first"], json_usize),
+                    json_list(&difference["second"], json_usize),
+                ),
+                Some("data") => BlockDifference::Data {
+                    max_difference: difference["max_difference"].as_f64(),
+                },
+                kind => panic!("unknown kind of difference in diff report: {:?}", kind),
+            };
+
+            BlockDiff {
+                key: json_list(&block["key"], json_i32),
+                parameter: json_str(&block["parameter"]),
+                difference: difference,
+            }
+        });
+
+        return TensorMapDiff {
+            metadata: metadata,
+            keys_names: keys_names,
+            only_in_first: json_list(&report["only_in_first"], |key| json_list(key, json_i32)),
+            only_in_second: json_list(&report["only_in_second"], |key| json_list(key, json_i32)),
+            blocks: blocks,
+        };
+    }
+}
+
+fn json_list<T>(value: &Value, parse: impl FnMut(&Value) -> T) -> Vec<T> {
+    value.as_array().expect("expected a list in diff report").iter().map(parse).collect()
+}
+
+fn json_str(value: &Value) -> String {
+    value.as_str().expect("expected a string in diff report").to_owned()
+}
+
+fn json_optional_str(value: &Value) -> Option<String> {
+    value.as_str().map(str::to_owned)
+}
+
+fn json_i32(value: &Value) -> i32 {
+    let value = value.as_i64().expect("expected an integer in diff report");
+    return i32::try_from(value).expect("integer in diff report does not fit in i32");
+}
+
+fn json_usize(value: &Value) -> usize {
+    let value = value.as_u64().expect("expected an integer in diff report");
+    return usize::try_from(value).expect("integer in diff report does not fit in usize");
+}
+
+impl TensorMap {
+    /// Compare this tensor map with `other`, and report all the differences
+    /// in metadata, keys, units, labels, shapes and data. Blocks are matched by
+    /// key, so the order of the blocks does not matter.
+    ///
+    /// See [`TensorMapDiff`] for the structure of the report.
+    pub fn diff(&self, other: &TensorMap, options: DiffOptions) -> Result<TensorMapDiff, Error> {
+        let mut buffer: Vec<u8> = vec![0; 1024];
+        loop {
+            let status = unsafe {
+                crate::c_api::eqs_tensormap_diff_json(
+                    self.ptr,
+                    other.ptr,
+                    options.compare_data,
+                    options.tolerance,
+                    buffer.as_mut_ptr().cast(),
+                    buffer.len(),
+                )
+            };
+
+            if status == crate::c_api::EQS_BUFFER_SIZE_ERROR {
+                buffer.resize(2 * buffer.len(), 0);
+            } else {
+                check_status(status)?;
+                break;
+            }
+        }
+
+        let first_null = buffer.iter().position(|&c| c == 0).expect("should contain a NULL byte");
+        buffer.resize(first_null + 1, 0);
+
+        let json = CStr::from_bytes_with_nul(&buffer).expect("should have a single NULL byte");
+        return Ok(TensorMapDiff::from_json(json.to_str().expect("should be UTF8")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Labels, TensorBlock, TensorMap};
+    use super::*;
+
+    fn tensor(keys: &[[i32; 1]], data: &[f64; 2]) -> TensorMap {
+        let blocks = keys.iter().map(|_| TensorBlock::new(
+            ndarray::arr2(&[[data[0]], [data[1]]]).into_dyn(),
+            Labels::new(["samples"], &[[0], [1]]),
+            &[],
+            Labels::new(["properties"], &[[0]]),
+        ).unwrap()).collect();
+
+        return TensorMap::new(Labels::new(["key"], keys), blocks).unwrap();
+    }
+
+    #[test]
+    fn diff() {
+        let first = tensor(&[[0], [1]], &[1.0, 2.0]);
+        let second = tensor(&[[1], [0]], &[1.0, 2.0]);
+        let report = first.diff(&second, DiffOptions::default()).unwrap();
+        assert!(report.is_empty());
+
+        let second = tensor(&[[1], [2]], &[1.0, 2.5]);
+        let report = first.diff(&second, DiffOptions::default()).unwrap();
+        assert_eq!(report.only_in_first, [[0]]);
+        assert_eq!(report.only_in_second, [[2]]);
+        assert_eq!(report.blocks, [BlockDiff {
+            key: vec![1],
+            parameter: "values".into(),
+            difference: BlockDifference::Data { max_difference: Some(0.5) },
+        }]);
+
+        let options = DiffOptions { compare_data: true, tolerance: 1.0 };
+        let report = first.diff(&second, options).unwrap();
+        assert!(report.blocks.is_empty());
+
+        let other = TensorMap::new(Labels::new(["other"], &[[0]]), vec![
+            TensorBlock::new(
+                ndarray::arr2(&[[1.0]]).into_dyn(),
+                Labels::new(["samples"], &[[0]]),
+                &[],
+                Labels::new(["properties"], &[[0]]),
+            ).unwrap()
+        ]).unwrap();
+        let report = first.diff(&other, DiffOptions::default()).unwrap();
+        assert_eq!(report.keys_names, Some((vec!["key".into()], vec!["other".into()])));
+    }
+}
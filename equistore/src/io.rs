@@ -1,10 +1,13 @@
 //! Input/Output facilities for storing [`TensorMap`] on disk
 
+use std::cell::RefCell;
 use std::ffi::CString;
+use std::os::raw::c_void;
 
-use crate::c_api::{eqs_array_t, eqs_status_t};
+use crate::c_api::{eqs_array_t, eqs_status_t, eqs_read_at_t, eqs_save_options_t, eqs_load_limits_t};
 use crate::errors::{check_status, check_ptr};
-use crate::{TensorMap, Error, Array};
+use crate::progress::ProgressCallback;
+use crate::{TensorMap, Error, Array, Progress};
 
 /// Load the serialized tensor map from the given path.
 ///
@@ -72,6 +75,479 @@ pub fn save(path: impl AsRef<std::path::Path>, tensor: &TensorMap) -> Result<(),
     }
 }
 
+/// Options controlling how a `TensorMap` is saved with [`save_with_options`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SaveOptions {
+    /// Compress all the labels with zstd, using a dictionary trained on the
+    /// labels of the tensor map and shared between all of them.
+    ///
+    /// This can significantly reduce the size of files containing many small
+    /// blocks, but the resulting files can not be loaded with numpy.
+    pub labels_dictionary: bool,
+    /// Round the values and gradients data to keep only this number of bits
+    /// in the mantissa of the 64-bit floating point values (up to 52). The
+    /// rounded data is stored zstd-compressed, and the resulting files can
+    /// not be loaded with numpy. `None` keeps the data unchanged.
+    pub mantissa_bits: Option<u32>,
+}
+
+/// Save the given tensor to a file, using the given `options`.
+///
+/// Files created by this function can be loaded with [`load`] and the other
+/// loading functions.
+pub fn save_with_options(
+    path: impl AsRef<std::path::Path>,
+    tensor: &TensorMap,
+    options: SaveOptions,
+) -> Result<(), Error> {
+    let path = path.as_ref().as_os_str().to_str().expect("this path is not valid UTF8");
+    let path = CString::new(path).expect("this path contains a NULL byte");
+
+    let options = eqs_save_options_t {
+        labels_dictionary: options.labels_dictionary,
+        mantissa_bits: options.mantissa_bits.unwrap_or(0),
+    };
+
+    unsafe {
+        check_status(crate::c_api::eqs_tensormap_save_with_options(
+            path.as_ptr(),
+            tensor.ptr,
+            options,
+        ))
+    }
+}
+
+/// Save the given tensor to a file using the given `options`, reporting the
+/// number of blocks saved so far to `progress`, and stopping with an error if
+/// `progress` is cancelled before saving a block. See [`Progress`] for more
+/// information.
+pub fn save_with_progress(
+    path: impl AsRef<std::path::Path>,
+    tensor: &TensorMap,
+    options: SaveOptions,
+    progress: impl Progress,
+) -> Result<(), Error> {
+    let path = path.as_ref().as_os_str().to_str().expect("this path is not valid UTF8");
+    let path = CString::new(path).expect("this path contains a NULL byte");
+
+    let options = eqs_save_options_t {
+        labels_dictionary: options.labels_dictionary,
+        mantissa_bits: options.mantissa_bits.unwrap_or(0),
+    };
+
+    let progress = ProgressCallback::new(progress);
+    let status = unsafe {
+        crate::c_api::eqs_tensormap_save_with_progress(
+            path.as_ptr(),
+            tensor.ptr,
+            options,
+            progress.as_eqs_progress_t(),
+        )
+    };
+    progress.finish();
+
+    return check_status(status);
+}
+
+/// Load a serialized tensor map from an in-memory `buffer`.
+///
+/// The buffer should contain the same data as a file created by [`save`], see
+/// the [`load`] function for more information on the format.
+pub fn load_buffer(buffer: &[u8]) -> Result<TensorMap, Error> {
+    let ptr = unsafe {
+        crate::c_api::eqs_tensormap_load_buffer(
+            buffer.as_ptr(),
+            buffer.len(),
+            Some(create_ndarray)
+        )
+    };
+
+    check_ptr(ptr)?;
+
+    return Ok(unsafe { TensorMap::from_raw(ptr) });
+}
+
+/// Limits on the size of the data created when loading a `TensorMap` with
+/// [`load_with_limits`] or [`load_buffer_with_limits`].
+///
+/// The sizes of labels and arrays are read from the serialized data, and can
+/// be arbitrarily large in corrupted or malicious files. Data over these limits
+/// is refused before allocating memory for it. `None` means that there is no
+/// limit.
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(clippy::struct_field_names)]
+pub struct LoadLimits {
+    /// Maximal number of entries in any of the `Labels` (keys, samples,
+    /// components or properties)
+    pub max_labels_entries: Option<usize>,
+    /// Maximal size in bytes of any array (values, gradients or strings)
+    pub max_array_bytes: Option<usize>,
+    /// Maximal number of blocks in the `TensorMap`
+    pub max_blocks: Option<usize>,
+}
+
+impl From<LoadLimits> for eqs_load_limits_t {
+    fn from(limits: LoadLimits) -> Self {
+        eqs_load_limits_t {
+            max_labels_entries: limits.max_labels_entries.unwrap_or(0),
+            max_array_bytes: limits.max_array_bytes.unwrap_or(0),
+            max_blocks: limits.max_blocks.unwrap_or(0),
+        }
+    }
+}
+
+/// Load the serialized tensor map from the given path, refusing data larger
+/// than the given `limits`.
+///
+/// This should be preferred to [`load`] when loading files from untrusted
+/// sources.
+pub fn load_with_limits(path: impl AsRef<std::path::Path>, limits: LoadLimits) -> Result<TensorMap, Error> {
+    let path = path.as_ref().as_os_str().to_str().expect("this path is not valid UTF8");
+    let path = CString::new(path).expect("this path contains a NULL byte");
+
+    let ptr = unsafe {
+        crate::c_api::eqs_tensormap_load_with_limits(
+            path.as_ptr(),
+            limits.into(),
+            Some(create_ndarray)
+        )
+    };
+
+    check_ptr(ptr)?;
+
+    return Ok(unsafe { TensorMap::from_raw(ptr) });
+}
+
+/// Load the serialized tensor map from the given path, refusing data larger
+/// than the given `limits`, reporting the number of blocks loaded so far to
+/// `progress`, and stopping with an error if `progress` is cancelled before
+/// loading a block. See [`Progress`] for more information.
+pub fn load_with_progress(
+    path: impl AsRef<std::path::Path>,
+    limits: LoadLimits,
+    progress: impl Progress,
+) -> Result<TensorMap, Error> {
+    let path = path.as_ref().as_os_str().to_str().expect("this path is not valid UTF8");
+    let path = CString::new(path).expect("this path contains a NULL byte");
+
+    let progress = ProgressCallback::new(progress);
+    let ptr = unsafe {
+        crate::c_api::eqs_tensormap_load_with_progress(
+            path.as_ptr(),
+            limits.into(),
+            progress.as_eqs_progress_t(),
+            Some(create_ndarray)
+        )
+    };
+    progress.finish();
+
+    check_ptr(ptr)?;
+
+    return Ok(unsafe { TensorMap::from_raw(ptr) });
+}
+
+/// Load a serialized tensor map from an in-memory `buffer`, refusing data
+/// larger than the given `limits`.
+pub fn load_buffer_with_limits(buffer: &[u8], limits: LoadLimits) -> Result<TensorMap, Error> {
+    let ptr = unsafe {
+        crate::c_api::eqs_tensormap_load_buffer_with_limits(
+            buffer.as_ptr(),
+            buffer.len(),
+            limits.into(),
+            Some(create_ndarray)
+        )
+    };
+
+    check_ptr(ptr)?;
+
+    return Ok(unsafe { TensorMap::from_raw(ptr) });
+}
+
+/// Save the given tensor to an in-memory `buffer`.
+///
+/// The `buffer` is resized to fit the serialized data, and any previous
+/// content is overwritten. The format used is documented in the [`load`]
+/// function.
+pub fn save_buffer(tensor: &TensorMap, buffer: &mut Vec<u8>) -> Result<(), Error> {
+    let mut buffer_ptr = buffer.as_mut_ptr();
+    let mut buffer_count = buffer.len();
+
+    unsafe {
+        check_status(crate::c_api::eqs_tensormap_save_buffer(
+            &mut buffer_ptr,
+            &mut buffer_count,
+            (buffer as *mut Vec<u8>).cast(),
+            Some(realloc_vec),
+            tensor.ptr,
+        ))?;
+    }
+
+    buffer.truncate(buffer_count);
+
+    return Ok(());
+}
+
+/// Load a serialized tensor map from the given asynchronous `reader`.
+///
+/// The ZIP format used for serialization requires random access to the data,
+/// so the data is read from `reader` in memory without blocking the runtime,
+/// and then deserialized on `tokio`'s blocking thread pool with
+/// [`load_buffer`].
+#[cfg(feature = "tokio")]
+pub async fn load_async<R>(mut reader: R) -> Result<TensorMap, Error>
+    where R: tokio::io::AsyncRead + Unpin
+{
+    use tokio::io::AsyncReadExt;
+
+    let mut buffer = Vec::new();
+    reader.read_to_end(&mut buffer).await.map_err(io_error)?;
+
+    return tokio::task::spawn_blocking(move || load_buffer(&buffer))
+        .await
+        .map_err(join_error)?;
+}
+
+/// Save the given tensor to an asynchronous `writer`.
+///
+/// The tensor is serialized in memory on `tokio`'s blocking thread pool with
+/// [`save_buffer`], working on a copy of `tensor` which shares its data
+/// arrays with `tensor`. The resulting data is then written to `writer`
+/// without blocking the runtime.
+#[cfg(feature = "tokio")]
+pub async fn save_async<W>(mut writer: W, tensor: &TensorMap) -> Result<(), Error>
+    where W: tokio::io::AsyncWrite + Unpin
+{
+    use tokio::io::AsyncWriteExt;
+
+    let tensor = tensor.try_clone()?;
+    let buffer = tokio::task::spawn_blocking(move || {
+        let mut buffer = Vec::new();
+        save_buffer(&tensor, &mut buffer)?;
+        Ok::<_, Error>(buffer)
+    }).await.map_err(join_error)??;
+
+    writer.write_all(&buffer).await.map_err(io_error)?;
+    writer.flush().await.map_err(io_error)?;
+
+    return Ok(());
+}
+
+/// Random access to serialized data, without a notion of current position.
+///
+/// This can be implemented on top of remote storage (HTTP servers, object
+/// stores, *etc.*) where each read is a separate request for a range of bytes,
+/// and used with [`load_read_at`] to only fetch the parts of the data that are
+/// needed.
+pub trait ReadAt {
+    /// Get the total size in bytes of the data
+    fn size(&self) -> std::io::Result<u64>;
+
+    /// Read data starting at `offset` into `buffer`, returning the number of
+    /// bytes read. This should only return `0` if `offset` is at or after the
+    /// end of the data, or if `buffer` is empty.
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> std::io::Result<usize>;
+}
+
+impl ReadAt for [u8] {
+    fn size(&self) -> std::io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let start = usize::try_from(offset).unwrap_or(usize::MAX).min(self.len());
+        let count = buffer.len().min(self.len() - start);
+        buffer[..count].copy_from_slice(&self[start..start + count]);
+        return Ok(count);
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for &T {
+    fn size(&self) -> std::io::Result<u64> {
+        (**self).size()
+    }
+
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> std::io::Result<usize> {
+        (**self).read_at(offset, buffer)
+    }
+}
+
+/// Load some or all of the blocks of a serialized tensor map from `reader`.
+///
+/// If `blocks` is `Some`, only the blocks at the given indexes in the
+/// serialized tensor map are loaded, in this order, and the keys of the
+/// resulting tensor map only contain the corresponding entries. If `blocks` is
+/// `None`, all the blocks are loaded.
+///
+/// Only the ZIP central directory, the keys and the files of the requested
+/// blocks are read from `reader`, which makes this function suitable to load
+/// a subset of a large tensor map stored remotely. The format is documented in
+/// the [`load`] function.
+pub fn load_read_at<T: ReadAt>(reader: T, blocks: Option<&[usize]>) -> Result<TensorMap, Error> {
+    let state = ReadAtState {
+        reader: reader,
+        error: RefCell::new(None),
+    };
+
+    let c_reader = eqs_read_at_t {
+        user_data: &state as *const ReadAtState<T> as *mut c_void,
+        size: state.reader.size().map_err(io_error)?,
+        read_at: Some(read_at_callback::<T>),
+    };
+
+    let (blocks, blocks_count) = match blocks {
+        Some(blocks) => (blocks.as_ptr(), blocks.len()),
+        None => (std::ptr::null(), 0),
+    };
+
+    let ptr = unsafe {
+        crate::c_api::eqs_tensormap_load_read_at(
+            c_reader,
+            blocks,
+            blocks_count,
+            Some(create_ndarray),
+        )
+    };
+
+    if let Some(error) = state.error.into_inner() {
+        return Err(io_error(error));
+    }
+
+    check_ptr(ptr)?;
+
+    return Ok(unsafe { TensorMap::from_raw(ptr) });
+}
+
+/// Data passed to `read_at_callback`, storing the last error from `reader` to
+/// give it back to the user
+struct ReadAtState<T> {
+    reader: T,
+    error: RefCell<Option<std::io::Error>>,
+}
+
+/// Status code returned by `read_at_callback` when the reader failed
+const READ_AT_FAILED: eqs_status_t = -1;
+
+/// callback used to read data from a `ReadAt` implementation in `load_read_at`
+unsafe extern fn read_at_callback<T: ReadAt>(
+    user_data: *mut c_void,
+    offset: u64,
+    buffer: *mut u8,
+    count: usize,
+    read: *mut usize,
+) -> eqs_status_t {
+    let mut failed = false;
+    let status = crate::errors::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let state = &*user_data.cast::<ReadAtState<T>>();
+        let buffer = std::slice::from_raw_parts_mut(buffer, count);
+
+        match state.reader.read_at(offset, buffer) {
+            Ok(size) => *read = size,
+            Err(error) => {
+                *state.error.borrow_mut() = Some(error);
+                failed = true;
+            }
+        }
+    }));
+
+    if failed {
+        return READ_AT_FAILED;
+    }
+
+    return status;
+}
+
+/// Implementation of [`ReadAt`] for objects in an [`object_store`], to load
+/// tensor maps from S3, Google Cloud Storage, Azure Blob Storage, *etc.* with
+/// [`load_read_at`].
+///
+/// The asynchronous requests are executed on the given `tokio` runtime, and
+/// this reader must not be used from inside an asynchronous context (use
+/// `tokio::task::spawn_blocking` instead).
+#[cfg(feature = "object_store")]
+pub struct ObjectStoreReader {
+    store: std::sync::Arc<dyn object_store::ObjectStore>,
+    path: object_store::path::Path,
+    size: u64,
+    runtime: tokio::runtime::Handle,
+}
+
+#[cfg(feature = "object_store")]
+impl ObjectStoreReader {
+    /// Create a new reader for the object at `path` in the given `store`,
+    /// running requests on `runtime`. This fetches the size of the object.
+    pub fn new(
+        store: std::sync::Arc<dyn object_store::ObjectStore>,
+        path: object_store::path::Path,
+        runtime: tokio::runtime::Handle,
+    ) -> Result<ObjectStoreReader, Error> {
+        let metadata = runtime.block_on(store.head(&path)).map_err(|error| Error {
+            code: None,
+            message: format!("object store error: {}", error),
+        })?;
+
+        return Ok(ObjectStoreReader {
+            store: store,
+            path: path,
+            size: metadata.size as u64,
+            runtime: runtime,
+        });
+    }
+}
+
+#[cfg(feature = "object_store")]
+impl ReadAt for ObjectStoreReader {
+    fn size(&self) -> std::io::Result<u64> {
+        Ok(self.size)
+    }
+
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> std::io::Result<usize> {
+        if offset >= self.size || buffer.is_empty() {
+            return Ok(0);
+        }
+
+        let end = self.size.min(offset + buffer.len() as u64);
+        let to_usize = |value: u64| usize::try_from(value).map_err(|_| std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "offset is too large for this platform",
+        ));
+        let range = to_usize(offset)?..to_usize(end)?;
+
+        let bytes = self.runtime.block_on(self.store.get_range(&self.path, range))
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))?;
+
+        buffer[..bytes.len()].copy_from_slice(&bytes);
+        return Ok(bytes.len());
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn io_error(error: std::io::Error) -> Error {
+    Error {
+        code: None,
+        message: format!("io error: {}", error),
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[allow(clippy::needless_pass_by_value)]
+fn join_error(error: tokio::task::JoinError) -> Error {
+    Error {
+        code: None,
+        message: format!("failed to run (de)serialization task: {}", error),
+    }
+}
+
+/// callback used to grow a `Vec<u8>` when saving a `TensorMap` to a buffer
+unsafe extern fn realloc_vec(user_data: *mut c_void, _ptr: *mut u8, new_size: usize) -> *mut u8 {
+    let result = std::panic::catch_unwind(|| {
+        let vector = &mut *user_data.cast::<Vec<u8>>();
+        vector.resize(new_size, 0);
+        vector.as_mut_ptr()
+    });
+
+    // returning NULL will make equistore-core report an allocation error
+    return result.unwrap_or(std::ptr::null_mut());
+}
 
 /// callback used to create `ndarray::ArrayD` when loading a `TensorMap`
 unsafe extern fn create_ndarray(
@@ -1,12 +1,16 @@
 use std:: ffi::CStr;
 use std::ffi::CString;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::hash_map::{Entry, RandomState};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::Mutex;
 use std::iter::FusedIterator;
 
 use smallvec::SmallVec;
 
 use crate::c_api::eqs_labels_t;
 use crate::errors::check_status;
+use crate::Error;
 
 impl eqs_labels_t {
     /// Create an `eqs_labels_t` with all members set to null pointers/zero
@@ -210,6 +214,62 @@ impl Labels {
         return builder.finish();
     }
 
+    /// Create a set of `Labels` containing all the combinations of values
+    /// from the given `dimensions` (a list of names and ranges of values) for
+    /// which `filter` returns `true`.
+    ///
+    /// The entries are generated in lexicographic order, i.e. the values of the
+    /// last dimension change the fastest. This is useful to create properties
+    /// from hyper-parameters, for example all the `(n, l, m)` with `n < n_max`
+    /// and `|m| <= l <= l_max`:
+    ///
+    /// ```
+    /// # use equistore::Labels;
+    /// let (n_max, l_max) = (4, 3);
+    /// let properties = Labels::from_grid(
+    ///     &[("n", 0..n_max), ("l", 0..(l_max + 1)), ("m", -l_max..(l_max + 1))],
+    ///     |entry| entry[2].abs() <= entry[1],
+    /// );
+    /// assert_eq!(properties.count(), 4 * 16);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `dimensions` is empty or the set of names is not valid
+    pub fn from_grid<F>(dimensions: &[(&str, std::ops::Range<i32>)], mut filter: F) -> Labels
+        where F: FnMut(&[i32]) -> bool
+    {
+        assert!(!dimensions.is_empty(), "can not create labels from a grid without dimensions");
+
+        let mut builder = LabelsBuilder::new(dimensions.iter().map(|&(name, _)| name).collect());
+        if dimensions.iter().any(|(_, range)| range.is_empty()) {
+            return builder.finish();
+        }
+
+        let mut entry = dimensions.iter().map(|(_, range)| range.start).collect::<Vec<_>>();
+        loop {
+            if filter(&entry) {
+                builder.add(&entry);
+            }
+
+            // go to the next entry, incrementing the last dimension first
+            let mut dimension = dimensions.len();
+            loop {
+                if dimension == 0 {
+                    return builder.finish();
+                }
+                dimension -= 1;
+
+                let range = &dimensions[dimension].1;
+                entry[dimension] += 1;
+                if entry[dimension] < range.end {
+                    break;
+                }
+                entry[dimension] = range.start;
+            }
+        }
+    }
+
     /// Create a set of `Labels` with the given names, containing no entries.
     #[inline]
     pub fn empty(names: Vec<&str>) -> Labels {
@@ -240,6 +300,202 @@ impl Labels {
         }
     }
 
+    /// Get a new set of labels where the values of the `column` dimension
+    /// are replaced through the `mapping` table, and all other dimensions are
+    /// unchanged.
+    ///
+    /// This can be used to renumber structures, or to convert atomic numbers
+    /// to species indexes. This function returns an error if some values in
+    /// `column` are not present in `mapping`, or if the new labels would
+    /// contain duplicated entries.
+    pub fn remap_values(&self, column: &str, mapping: &HashMap<i32, i32>) -> Result<Labels, Error> {
+        return self.map_column(column, |value| {
+            mapping.get(&value).copied().ok_or_else(|| Error {
+                code: None,
+                message: format!("the value {} for '{}' is not present in the mapping", value, column),
+            })
+        });
+    }
+
+    /// Get a new set of labels where `delta` is added to all the values of the
+    /// `column` dimension, and all other dimensions are unchanged.
+    ///
+    /// This can be used to shift the `structure` indexes when concatenating
+    /// multiple datasets. This function returns an error if the new values
+    /// overflow `i32`, or if the new labels would contain duplicated entries.
+    pub fn offset_column(&self, column: &str, delta: i32) -> Result<Labels, Error> {
+        return self.map_column(column, |value| {
+            value.checked_add(delta).ok_or_else(|| Error {
+                code: None,
+                message: format!("overflow when adding {} to the value {} for '{}'", delta, value, column),
+            })
+        });
+    }
+
+    /// Get a new set of labels with the same values, where `prefix` is added
+    /// at the beginning of all the names.
+    ///
+    /// This is useful when joining data from multiple tensor maps with the
+    /// same names for different things (e.g. two different `atom` columns).
+    /// This function returns an error if the new names are not valid label
+    /// names.
+    pub fn add_prefix(&self, prefix: &str) -> Result<Labels, Error> {
+        let names = self.names().iter().map(|name| format!("{}{}", prefix, name)).collect::<Vec<_>>();
+        return self.rename_all(names.iter().map(String::as_str).collect());
+    }
+
+    /// Get a new set of labels with the same values, where `prefix` is removed
+    /// from the beginning of all the names.
+    ///
+    /// This is the inverse of [`Labels::add_prefix`]. This function returns an
+    /// error if some of the names do not start with `prefix`, or if the new
+    /// names are not valid label names.
+    pub fn strip_prefix(&self, prefix: &str) -> Result<Labels, Error> {
+        let names = self.names().into_iter().map(|name| {
+            name.strip_prefix(prefix).ok_or_else(|| Error {
+                code: None,
+                message: format!("the name '{}' does not start with '{}'", name, prefix),
+            })
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        return self.rename_all(names);
+    }
+
+    /// Create new labels with the same values and the given `names`
+    fn rename_all(&self, names: Vec<&str>) -> Result<Labels, Error> {
+        // the new names are validated by equistore-core when creating the
+        // labels, but `LabelsBuilder::new` panics for duplicated names
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].contains(name) {
+                return Err(Error {
+                    code: None,
+                    message: format!("invalid labels: the name '{}' is used multiple times", name),
+                });
+            }
+        }
+
+        let mut builder = LabelsBuilder::new(names);
+        builder.values.extend_from_slice(self.values());
+        return builder.try_finish();
+    }
+
+    /// Write these labels to `writer` in CSV format.
+    ///
+    /// The first line contains the names of the dimensions, and each of the
+    /// following lines contains the values of one entry, separated by commas.
+    /// This is mainly intended to inspect and edit small sets of labels by
+    /// hand, see [`Labels::from_csv`] to read them back.
+    pub fn to_csv<W: std::io::Write>(&self, writer: W) -> Result<(), Error> {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(writer);
+
+        writeln!(writer, "{}", self.names().join(",")).map_err(csv_io_error)?;
+        for entry in self {
+            let line = entry.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+            writeln!(writer, "{}", line.join(",")).map_err(csv_io_error)?;
+        }
+
+        writer.flush().map_err(csv_io_error)?;
+        return Ok(());
+    }
+
+    /// Read labels in CSV format from `reader`, as written by
+    /// [`Labels::to_csv`].
+    ///
+    /// The first line must contain the names of the dimensions, which must be
+    /// valid label names. Each of the following non-empty lines must contain
+    /// one integer value for each dimension. Whitespace around names and values
+    /// is ignored. This function returns an error if the data is not valid CSV
+    /// for labels, or if it contains duplicated entries.
+    pub fn from_csv<R: std::io::Read>(reader: R) -> Result<Labels, Error> {
+        use std::io::BufRead;
+
+        let mut lines = std::io::BufReader::new(reader).lines();
+
+        let header = match lines.next() {
+            Some(line) => line.map_err(csv_io_error)?,
+            None => return Err(Error {
+                code: None,
+                message: "invalid CSV for labels: missing header with the dimensions names".into(),
+            }),
+        };
+
+        let names = header.split(',').map(str::trim).collect::<Vec<_>>();
+        if names.iter().all(|name| name.is_empty()) {
+            return Err(Error {
+                code: None,
+                message: "invalid CSV for labels: the header does not contain any dimension name".into(),
+            });
+        }
+
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].contains(name) {
+                return Err(Error {
+                    code: None,
+                    message: format!("invalid CSV for labels: the name '{}' is used multiple times", name),
+                });
+            }
+        }
+
+        // the names are validated by equistore-core when creating the labels
+        let mut builder = LabelsBuilder::new(names.clone());
+        for (line_i, line) in lines.enumerate() {
+            let line = line.map_err(csv_io_error)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // the header is on line 1
+            let line_number = line_i + 2;
+            let values = line.split(',').map(|value| {
+                value.trim().parse::<i32>().map_err(|_| Error {
+                    code: None,
+                    message: format!(
+                        "invalid CSV for labels: expected an integer on line {}, got '{}'",
+                        line_number, value.trim()
+                    ),
+                })
+            }).collect::<Result<Vec<_>, _>>()?;
+
+            if values.len() != names.len() {
+                return Err(Error {
+                    code: None,
+                    message: format!(
+                        "invalid CSV for labels: expected {} values on line {}, got {}",
+                        names.len(), line_number, values.len()
+                    ),
+                });
+            }
+
+            builder.add(&values);
+        }
+
+        return builder.try_finish();
+    }
+
+    /// Create new labels by applying `function` to all the values of the
+    /// `column` dimension.
+    fn map_column<F>(&self, column: &str, mut function: F) -> Result<Labels, Error>
+        where F: FnMut(i32) -> Result<i32, Error>
+    {
+        let names = self.names();
+        let column_i = names.iter().position(|&name| name == column).ok_or_else(|| Error {
+            code: None,
+            message: format!("'{}' is not part of these labels", column),
+        })?;
+
+        let mut builder = LabelsBuilder::new(names);
+        builder.reserve(self.count());
+        for entry in self {
+            let mut new_entry = entry.iter().copied().collect::<SmallVec<[LabelValue; 16]>>();
+            new_entry[column_i] = LabelValue::new(function(entry[column_i].i32())?);
+            builder.add(new_entry.as_slice());
+        }
+
+        return builder.try_finish();
+    }
+
     /// Get the total number of entries in this set of labels
     #[inline]
     pub fn count(&self) -> usize {
@@ -277,6 +533,19 @@ impl Labels {
         return result.try_into().ok();
     }
 
+    /// Get the entry at position `i` in this set of labels, or `None` if `i`
+    /// is out of bounds.
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<&[LabelValue]> {
+        if i >= self.count() {
+            return None;
+        }
+
+        let start = i * self.size();
+        let stop = (i + 1) * self.size();
+        return Some(&self.values()[start..stop]);
+    }
+
     /// Iterate over the entries in this set of labels
     #[inline]
     pub fn iter(&self) -> LabelsIter<'_> {
@@ -310,6 +579,11 @@ impl Labels {
     }
 
     pub(crate) fn values(&self) -> &[LabelValue] {
+        if self.count() == 0 || self.size() == 0 {
+            // the values pointer can be NULL for empty labels
+            return &[];
+        }
+
         unsafe {
             std::slice::from_raw_parts(self.raw.values.cast(), self.count() * self.size())
         }
@@ -352,9 +626,13 @@ impl std::ops::Index<usize> for Labels {
 
     #[inline]
     fn index(&self, i: usize) -> &[LabelValue] {
-        let start = i * self.size();
-        let stop = (i + 1) * self.size();
-        &self.values()[start..stop]
+        match self.get(i) {
+            Some(entry) => entry,
+            None => panic!(
+                "out of bounds access in Labels: the index is {} but there are only {} entries",
+                i, self.count()
+            ),
+        }
     }
 }
 
@@ -471,6 +749,9 @@ pub struct LabelsBuilder {
     // cf `Labels` for the documentation of the fields
     names: Vec<String>,
     values: Vec<LabelValue>,
+    // position of the entries in `values`. This is only created when
+    // `add_or_get` is called for the first time.
+    positions: Option<HashMap<SmallVec<[LabelValue; 16]>, usize>>,
 }
 
 impl LabelsBuilder {
@@ -483,6 +764,7 @@ impl LabelsBuilder {
         LabelsBuilder {
             names: names.into_iter().map(|s| s.into()).collect(),
             values: Vec::new(),
+            positions: None,
         }
     }
 
@@ -514,11 +796,60 @@ impl LabelsBuilder {
         // requiring an extra heap allocation
         let entry = entry.iter().copied().map(Into::into).collect::<SmallVec<[LabelValue; 16]>>();
         self.values.extend(&entry);
+
+        if let Some(positions) = &mut self.positions {
+            let new_position = positions.len();
+            positions.entry(entry).or_insert(new_position);
+        }
+    }
+
+    /// Add a single `entry` to this set of labels if it is not already
+    /// present, and get the position of this entry in the labels.
+    ///
+    /// Contrary to [`LabelsBuilder::add`], this function can be called
+    /// multiple times with the same entry, which is useful when accumulating
+    /// entries containing duplicates (e.g. samples from multiple neighbor
+    /// lists).
+    pub fn add_or_get<T>(&mut self, entry: &[T]) -> usize where T: Copy + Into<LabelValue> {
+        assert_eq!(
+            self.size(), entry.len(),
+            "wrong size for added label: got {}, but expected {}",
+            entry.len(), self.size()
+        );
+
+        let size = self.size();
+        let values = &self.values;
+        let positions = self.positions.get_or_insert_with(|| {
+            let mut positions = HashMap::new();
+            for entry in values.chunks_exact(size) {
+                let new_position = positions.len();
+                positions.entry(entry.iter().copied().collect()).or_insert(new_position);
+            }
+            positions
+        });
+
+        let entry = entry.iter().copied().map(Into::into).collect::<SmallVec<[LabelValue; 16]>>();
+        let new_position = positions.len();
+        match positions.entry(entry) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                self.values.extend(entry.key());
+                entry.insert(new_position);
+                new_position
+            }
+        }
     }
 
     /// Finish building the `Labels`
     #[inline]
     pub fn finish(self) -> Labels {
+        return self.try_finish().expect("invalid labels?");
+    }
+
+    /// Finish building the `Labels`, returning an error instead of panicking
+    /// if the labels are invalid (e.g. because they contain duplicated
+    /// entries).
+    pub(crate) fn try_finish(self) -> Result<Labels, Error> {
         let mut raw_names = Vec::new();
         let mut raw_names_ptr = Vec::new();
         for name in &self.names {
@@ -536,15 +867,96 @@ impl LabelsBuilder {
         };
 
         unsafe {
-            check_status(
-                crate::c_api::eqs_labels_create(&mut raw_labels)
-            ).expect("invalid labels?");
+            check_status(crate::c_api::eqs_labels_create(&mut raw_labels))?;
         }
 
-        return unsafe { Labels::from_raw(raw_labels) };
+        return Ok(unsafe { Labels::from_raw(raw_labels) });
     }
 }
 
+#[allow(clippy::needless_pass_by_value)]
+fn csv_io_error(error: std::io::Error) -> Error {
+    Error {
+        code: None,
+        message: format!("io error: {}", error),
+    }
+}
+
+/// Number of independent hash sets in `ConcurrentLabelsBuilder`
+const CONCURRENT_BUILDER_SHARDS: usize = 32;
+
+/// Builder for `Labels` which can be shared between multiple threads, with
+/// entries added concurrently from all threads.
+///
+/// The entries are stored in multiple independent hash sets protected by
+/// separate locks, to reduce contention between threads. Adding the same entry
+/// multiple times is allowed, and only one copy of the entry will be kept.
+/// Since the order in which the entries are added depends on thread
+/// scheduling, the entries are sorted when calling
+/// [`ConcurrentLabelsBuilder::finish`], making the final `Labels`
+/// deterministic.
+pub struct ConcurrentLabelsBuilder {
+    names: Vec<String>,
+    hasher: RandomState,
+    shards: Vec<Mutex<HashSet<SmallVec<[LabelValue; 16]>>>>,
+}
+
+impl ConcurrentLabelsBuilder {
+    /// Create a new empty `ConcurrentLabelsBuilder` with the given `names`
+    pub fn new(names: Vec<&str>) -> ConcurrentLabelsBuilder {
+        let n_unique_names = names.iter().collect::<BTreeSet<_>>().len();
+        assert!(n_unique_names == names.len(), "invalid labels: the same name is used multiple times");
+
+        ConcurrentLabelsBuilder {
+            names: names.into_iter().map(|s| s.into()).collect(),
+            hasher: RandomState::new(),
+            shards: (0..CONCURRENT_BUILDER_SHARDS).map(|_| Mutex::new(HashSet::new())).collect(),
+        }
+    }
+
+    /// Get the number of labels in a single value
+    pub fn size(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Add a single `entry` to this set of labels. This function can be called
+    /// from multiple threads at the same time, and does nothing if the entry
+    /// is already present.
+    pub fn add<T>(&self, entry: &[T]) where T: Copy + Into<LabelValue> {
+        assert_eq!(
+            self.size(), entry.len(),
+            "wrong size for added label: got {}, but expected {}",
+            entry.len(), self.size()
+        );
+
+        let entry = entry.iter().copied().map(Into::into).collect::<SmallVec<[LabelValue; 16]>>();
+
+        let mut hasher = self.hasher.build_hasher();
+        entry.hash(&mut hasher);
+        #[allow(clippy::cast_possible_truncation)]
+        let shard = (hasher.finish() % CONCURRENT_BUILDER_SHARDS as u64) as usize;
+
+        self.shards[shard].lock().expect("mutex was poisoned").insert(entry);
+    }
+
+    /// Finish building the `Labels`, with all the entries sorted in
+    /// lexicographic order
+    pub fn finish(self) -> Labels {
+        let mut entries = Vec::new();
+        for shard in self.shards {
+            entries.extend(shard.into_inner().expect("mutex was poisoned"));
+        }
+        entries.sort_unstable();
+
+        let mut builder = LabelsBuilder::new(self.names.iter().map(|s| &**s).collect());
+        builder.reserve(entries.len());
+        for entry in &entries {
+            builder.add(entry.as_slice());
+        }
+
+        return builder.finish();
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -567,6 +979,67 @@ mod tests {
         assert_eq!(idx[2], [-4, -2413]);
     }
 
+    #[test]
+    fn add_or_get() {
+        let mut builder = LabelsBuilder::new(vec!["structure", "center"]);
+        builder.add(&[0, 3]);
+        assert_eq!(builder.add_or_get(&[0, 1]), 1);
+        assert_eq!(builder.add_or_get(&[0, 2]), 2);
+        assert_eq!(builder.add_or_get(&[0, 1]), 1);
+        assert_eq!(builder.add_or_get(&[0, 3]), 0);
+
+        builder.add(&[1, 0]);
+        assert_eq!(builder.add_or_get(&[1, 0]), 3);
+
+        let labels = builder.finish();
+        assert_eq!(labels, Labels::new(["structure", "center"], &[[0, 3], [0, 1], [0, 2], [1, 0]]));
+    }
+
+    #[test]
+    fn concurrent_builder() {
+        let builder = std::sync::Arc::new(ConcurrentLabelsBuilder::new(vec!["structure", "center"]));
+
+        let mut threads = Vec::new();
+        for thread_i in 0..4 {
+            let builder = std::sync::Arc::clone(&builder);
+            threads.push(std::thread::spawn(move || {
+                // all threads add the entries for structures 0 and 1
+                for structure in [0, 1, thread_i + 2] {
+                    for center in (0..3).rev() {
+                        builder.add(&[structure, center]);
+                    }
+                }
+            }));
+        }
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let builder = std::sync::Arc::try_unwrap(builder).unwrap_or_else(|_| panic!("builder is still shared"));
+        let labels = builder.finish();
+        assert_eq!(labels.count(), 18);
+        assert_eq!(labels[0], [0, 0]);
+        assert_eq!(labels[1], [0, 1]);
+        assert_eq!(labels[2], [0, 2]);
+        assert_eq!(labels[3], [1, 0]);
+        assert_eq!(labels[17], [5, 2]);
+    }
+
+    #[test]
+    fn from_grid() {
+        let labels = Labels::from_grid(&[("l", 0..3), ("m", -2..3)], |entry| entry[1].abs() <= entry[0]);
+        assert_eq!(labels, Labels::new(["l", "m"], &[
+            [0, 0],
+            [1, -1], [1, 0], [1, 1],
+            [2, -2], [2, -1], [2, 0], [2, 1], [2, 2],
+        ]));
+
+        let labels = Labels::from_grid(&[("n", 0..2), ("l", 0..0)], |_| true);
+        assert_eq!(labels.names(), ["n", "l"]);
+        assert_eq!(labels.count(), 0);
+    }
+
     #[test]
     fn direct_construct() {
         let labels = Labels::new(
@@ -625,6 +1098,109 @@ mod tests {
         builder.finish();
     }
 
+    #[test]
+    fn remap_values() {
+        let labels = Labels::new(["structure", "species"], &[[0, 1], [0, 8], [1, 1], [1, 6]]);
+
+        let mapping = [(1, 0), (6, 1), (8, 2)].into_iter().collect::<HashMap<_, _>>();
+        let remapped = labels.remap_values("species", &mapping).unwrap();
+        assert_eq!(remapped, Labels::new(["structure", "species"], &[[0, 0], [0, 2], [1, 0], [1, 1]]));
+
+        let mapping = [(1, 0), (8, 2)].into_iter().collect::<HashMap<_, _>>();
+        let error = labels.remap_values("species", &mapping).unwrap_err();
+        assert_eq!(error.message, "the value 6 for 'species' is not present in the mapping");
+
+        let error = labels.remap_values("center", &mapping).unwrap_err();
+        assert_eq!(error.message, "'center' is not part of these labels");
+
+        let mapping = [(1, 0), (6, 0), (8, 0)].into_iter().collect::<HashMap<_, _>>();
+        let error = labels.remap_values("species", &mapping).unwrap_err();
+        assert_eq!(
+            error.message,
+            "invalid parameter: can not have the same label value multiple time: [0, 0] is already present at position 0"
+        );
+    }
+
+    #[test]
+    fn offset_column() {
+        let labels = Labels::new(["structure", "center"], &[[0, 1], [0, 2], [1, 0]]);
+
+        let shifted = labels.offset_column("structure", 10).unwrap();
+        assert_eq!(shifted, Labels::new(["structure", "center"], &[[10, 1], [10, 2], [11, 0]]));
+
+        let shifted = labels.offset_column("center", -1).unwrap();
+        assert_eq!(shifted, Labels::new(["structure", "center"], &[[0, 0], [0, 1], [1, -1]]));
+
+        let error = labels.offset_column("structure", i32::MAX).unwrap_err();
+        assert_eq!(error.message, "overflow when adding 2147483647 to the value 1 for 'structure'");
+
+        let error = labels.offset_column("species", 1).unwrap_err();
+        assert_eq!(error.message, "'species' is not part of these labels");
+    }
+
+    #[test]
+    fn prefix() {
+        let labels = Labels::new(["structure", "atom"], &[[0, 1], [0, 2], [1, 0]]);
+
+        let prefixed = labels.add_prefix("first_").unwrap();
+        assert_eq!(prefixed.names(), ["first_structure", "first_atom"]);
+        assert_eq!(prefixed.iter().collect::<Vec<_>>(), labels.iter().collect::<Vec<_>>());
+
+        let stripped = prefixed.strip_prefix("first_").unwrap();
+        assert_eq!(stripped, labels);
+
+        let error = prefixed.strip_prefix("first_s").unwrap_err();
+        assert_eq!(error.message, "the name 'first_atom' does not start with 'first_s'");
+
+        let error = labels.add_prefix("3").unwrap_err();
+        assert_eq!(error.message, "invalid parameter: '3structure' is not a valid label name");
+
+        let error = prefixed.strip_prefix("first_structure").unwrap_err();
+        assert_eq!(error.message, "the name 'first_atom' does not start with 'first_structure'");
+
+        let labels = Labels::new(["a_1", "a_b"], &[[0, 1]]);
+        let error = labels.strip_prefix("a_").unwrap_err();
+        assert_eq!(error.message, "invalid parameter: '1' is not a valid label name");
+    }
+
+    #[test]
+    fn csv() {
+        let labels = Labels::new(["structure", "center"], &[[0, 1], [0, -2], [13, 0]]);
+
+        let mut buffer = Vec::new();
+        labels.to_csv(&mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer.clone()).unwrap(), "structure,center\n0,1\n0,-2\n13,0\n");
+        assert_eq!(Labels::from_csv(&*buffer).unwrap(), labels);
+
+        let labels = Labels::from_csv(" structure , center \n 0, 1\n\n2 ,3\n".as_bytes()).unwrap();
+        assert_eq!(labels, Labels::new(["structure", "center"], &[[0, 1], [2, 3]]));
+
+        let labels = Labels::from_csv("structure\n".as_bytes()).unwrap();
+        assert_eq!(labels.names(), ["structure"]);
+        assert_eq!(labels.count(), 0);
+
+        let error = Labels::from_csv("".as_bytes()).unwrap_err();
+        assert_eq!(error.message, "invalid CSV for labels: missing header with the dimensions names");
+
+        let error = Labels::from_csv("structure,33 bar\n".as_bytes()).unwrap_err();
+        assert_eq!(error.message, "invalid parameter: '33 bar' is not a valid label name");
+
+        let error = Labels::from_csv("structure,structure\n".as_bytes()).unwrap_err();
+        assert_eq!(error.message, "invalid CSV for labels: the name 'structure' is used multiple times");
+
+        let error = Labels::from_csv("structure,center\n0,1\n0,x\n".as_bytes()).unwrap_err();
+        assert_eq!(error.message, "invalid CSV for labels: expected an integer on line 3, got 'x'");
+
+        let error = Labels::from_csv("structure,center\n0,1,2\n".as_bytes()).unwrap_err();
+        assert_eq!(error.message, "invalid CSV for labels: expected 2 values on line 2, got 3");
+
+        let error = Labels::from_csv("structure,center\n0,1\n0,1\n".as_bytes()).unwrap_err();
+        assert_eq!(
+            error.message,
+            "invalid parameter: can not have the same label value multiple time: [0, 1] is already present at position 0"
+        );
+    }
+
     #[test]
     fn single_label() {
         let labels = Labels::single();
@@ -646,6 +1222,16 @@ mod tests {
 
         assert_eq!(labels[1], [1, 243]);
         assert_eq!(labels[2], [-4, -2413]);
+
+        assert_eq!(labels.get(0).unwrap(), [2, 3]);
+        assert_eq!(labels.get(3), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds access in Labels: the index is 3 but there are only 3 entries")]
+    fn indexing_out_of_bounds() {
+        let labels = Labels::new(["foo"], &[[2], [1], [-4]]);
+        let _ = &labels[3];
     }
 
     #[test]
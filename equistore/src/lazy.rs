@@ -0,0 +1,301 @@
+use std::ffi::CString;
+use std::marker::PhantomData;
+
+use crate::c_api::eqs_lazy_tensormap_t;
+use crate::errors::{check_status, check_ptr};
+use crate::{Error, TensorBlock, TensorMap};
+
+/// Element-wise unary operations which can be recorded in a
+/// [`LazyTensorMap`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    /// `x + value`
+    AddScalar(f64),
+    /// `x * value`
+    MultiplyScalar(f64),
+    /// `x ^ value`
+    Pow(f64),
+    /// `|x|`
+    Abs,
+    /// `sqrt(x)`
+    Sqrt,
+    /// `exp(x)`
+    Exp,
+    /// `ln(x)`
+    Log,
+}
+
+impl UnaryOp {
+    /// Get the name and parameter of this operation, as expected by
+    /// `eqs_lazy_tensormap_unary`
+    fn name_and_value(self) -> (&'static str, f64) {
+        match self {
+            UnaryOp::AddScalar(value) => ("add_scalar", value),
+            UnaryOp::MultiplyScalar(value) => ("multiply_scalar", value),
+            UnaryOp::Pow(value) => ("pow", value),
+            UnaryOp::Abs => ("abs", 0.0),
+            UnaryOp::Sqrt => ("sqrt", 0.0),
+            UnaryOp::Exp => ("exp", 0.0),
+            UnaryOp::Log => ("log", 0.0),
+        }
+    }
+}
+
+/// How binary operations in a [`LazyTensorMap`] should handle gradients
+/// present in only some of the tensor maps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingGradients {
+    /// Return an error
+    Error,
+    /// Treat missing gradients as zero-filled, with the same metadata as in
+    /// the other tensor maps
+    Zero,
+    /// Only keep the gradients present in all tensor maps
+    Drop,
+}
+
+impl MissingGradients {
+    fn as_str(self) -> &'static str {
+        match self {
+            MissingGradients::Error => "error",
+            MissingGradients::Zero => "zero",
+            MissingGradients::Drop => "drop",
+        }
+    }
+}
+
+/// Chain of element-wise operations on a [`TensorMap`], which are only
+/// evaluated when calling [`LazyTensorMap::compute`] or
+/// [`LazyTensorMap::compute_block`].
+///
+/// All the recorded operations are applied in a single pass over the data,
+/// without allocating intermediary arrays. Gradients are propagated through
+/// all operations.
+///
+/// ```
+/// # use equistore::{TensorMap, LazyTensorMap, UnaryOp};
+/// # fn example(tensor: &TensorMap) -> Result<TensorMap, equistore::Error> {
+/// // compute `2 * tensor + tensor` without any intermediary tensor map
+/// let result = LazyTensorMap::new(tensor)?
+///     .unary(UnaryOp::MultiplyScalar(2.0))?
+///     .add(tensor)?
+///     .compute()?;
+/// # Ok(result)
+/// # }
+/// ```
+pub struct LazyTensorMap<'a> {
+    ptr: *mut eqs_lazy_tensormap_t,
+    // the lazy tensor map keeps pointers to all the tensor maps used in the
+    // operations
+    marker: PhantomData<&'a TensorMap>,
+}
+
+impl std::fmt::Debug for LazyTensorMap<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyTensorMap").finish_non_exhaustive()
+    }
+}
+
+impl Drop for LazyTensorMap<'_> {
+    #[allow(unused_must_use)]
+    fn drop(&mut self) {
+        unsafe {
+            crate::c_api::eqs_lazy_tensormap_free(self.ptr);
+        }
+    }
+}
+
+impl<'a> LazyTensorMap<'a> {
+    /// Start a new chain of lazy operations from the given `source` tensor
+    /// map.
+    pub fn new(source: &'a TensorMap) -> Result<LazyTensorMap<'a>, Error> {
+        let ptr = unsafe {
+            crate::c_api::eqs_lazy_tensormap(source.ptr)
+        };
+        check_ptr(ptr)?;
+
+        return Ok(LazyTensorMap { ptr, marker: PhantomData });
+    }
+
+    /// Set the policy used by the binary operations recorded after this call
+    /// when only some of the tensor maps contain gradients with respect to a
+    /// given parameter. The default is [`MissingGradients::Error`].
+    pub fn missing_gradients(self, policy: MissingGradients) -> Result<LazyTensorMap<'a>, Error> {
+        let policy = CString::new(policy.as_str()).expect("policy should not contain NULL bytes");
+
+        unsafe {
+            check_status(crate::c_api::eqs_lazy_tensormap_missing_gradients(self.ptr, policy.as_ptr()))?;
+        }
+
+        return Ok(self);
+    }
+
+    /// Allow the binary operations recorded after this call to use tensor
+    /// maps whose keys only contain a subset of the keys dimensions of the
+    /// source tensor map. Each block of the source is then combined with the
+    /// block of the other tensor map with the same values for the common keys
+    /// dimensions. The default is `false`, requiring the same keys.
+    pub fn broadcast_keys(self, broadcast: bool) -> Result<LazyTensorMap<'a>, Error> {
+        unsafe {
+            check_status(crate::c_api::eqs_lazy_tensormap_broadcast_keys(self.ptr, broadcast))?;
+        }
+
+        return Ok(self);
+    }
+
+    /// Record an element-wise unary `operation`
+    pub fn unary(self, operation: UnaryOp) -> Result<LazyTensorMap<'a>, Error> {
+        let (name, value) = operation.name_and_value();
+        let name = CString::new(name).expect("operation name should not contain NULL bytes");
+
+        unsafe {
+            check_status(crate::c_api::eqs_lazy_tensormap_unary(self.ptr, name.as_ptr(), value))?;
+        }
+
+        return Ok(self);
+    }
+
+    /// Record the addition of `other`, which must have the same metadata as
+    /// the source tensor map.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(self, other: &'a TensorMap) -> Result<LazyTensorMap<'a>, Error> {
+        unsafe {
+            check_status(crate::c_api::eqs_lazy_tensormap_add(self.ptr, other.ptr))?;
+        }
+
+        return Ok(self);
+    }
+
+    /// Record the element-wise multiplication by `other`, which must have the
+    /// same metadata as the source tensor map.
+    pub fn multiply(self, other: &'a TensorMap) -> Result<LazyTensorMap<'a>, Error> {
+        unsafe {
+            check_status(crate::c_api::eqs_lazy_tensormap_multiply(self.ptr, other.ptr))?;
+        }
+
+        return Ok(self);
+    }
+
+    /// Evaluate all the recorded operations for all blocks
+    pub fn compute(&self) -> Result<TensorMap, Error> {
+        let ptr = unsafe {
+            crate::c_api::eqs_lazy_tensormap_compute(self.ptr)
+        };
+        check_ptr(ptr)?;
+
+        return Ok(unsafe { TensorMap::from_raw(ptr) });
+    }
+
+    /// Evaluate all the recorded operations for the block at the given
+    /// `index` only
+    pub fn compute_block(&self, index: usize) -> Result<TensorBlock, Error> {
+        let ptr = unsafe {
+            crate::c_api::eqs_lazy_tensormap_compute_block(self.ptr, index)
+        };
+        check_ptr(ptr)?;
+
+        return Ok(unsafe { TensorBlock::from_raw(ptr) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Labels, TensorBlock, TensorMap};
+    use super::*;
+
+    fn tensor(values: &[f64; 2]) -> TensorMap {
+        let block = TensorBlock::new(
+            ndarray::arr2(&[[values[0]], [values[1]]]).into_dyn(),
+            Labels::new(["samples"], &[[0], [1]]),
+            &[],
+            Labels::new(["properties"], &[[0]]),
+        ).unwrap();
+
+        return TensorMap::new(Labels::new(["key"], &[[0]]), vec![block]).unwrap();
+    }
+
+    #[test]
+    fn lazy() {
+        let first = tensor(&[1.0, 4.0]);
+        let second = tensor(&[3.0, 2.0]);
+
+        let lazy = LazyTensorMap::new(&first).unwrap()
+            .unary(UnaryOp::Sqrt).unwrap()
+            .multiply(&second).unwrap()
+            .unary(UnaryOp::AddScalar(1.0)).unwrap();
+
+        let result = lazy.compute().unwrap();
+        let block = result.block_by_id(0);
+        assert_eq!(block.values().data.as_array(), ndarray::arr2(&[[4.0], [5.0]]).into_dyn());
+
+        let block = lazy.compute_block(0).unwrap();
+        assert_eq!(block.as_ref().values().data.as_array(), ndarray::arr2(&[[4.0], [5.0]]).into_dyn());
+
+        assert!(lazy.compute_block(3).is_err());
+    }
+
+    #[test]
+    fn broadcast_keys() {
+        let blocks = [1.0, 2.0, 3.0].iter().map(|&value| TensorBlock::new(
+            ndarray::arr2(&[[value]]).into_dyn(),
+            Labels::new(["samples"], &[[0]]),
+            &[],
+            Labels::new(["properties"], &[[0]]),
+        ).unwrap()).collect();
+        let first = TensorMap::new(Labels::new(["key", "l"], &[[0, 0], [1, 0], [0, 1]]), blocks).unwrap();
+
+        let blocks = [10.0, 2.0].iter().map(|&value| TensorBlock::new(
+            ndarray::arr2(&[[value]]).into_dyn(),
+            Labels::new(["samples"], &[[0]]),
+            &[],
+            Labels::new(["properties"], &[[0]]),
+        ).unwrap()).collect();
+        let weights = TensorMap::new(Labels::new(["key"], &[[1], [0]]), blocks).unwrap();
+
+        assert!(LazyTensorMap::new(&first).unwrap().multiply(&weights).is_err());
+
+        let result = LazyTensorMap::new(&first).unwrap()
+            .broadcast_keys(true).unwrap()
+            .multiply(&weights).unwrap()
+            .compute().unwrap();
+
+        assert_eq!(result.block_by_id(0).values().data.as_array(), ndarray::arr2(&[[2.0]]).into_dyn());
+        assert_eq!(result.block_by_id(1).values().data.as_array(), ndarray::arr2(&[[20.0]]).into_dyn());
+        assert_eq!(result.block_by_id(2).values().data.as_array(), ndarray::arr2(&[[6.0]]).into_dyn());
+    }
+
+    #[test]
+    fn missing_gradients() {
+        let first = tensor(&[1.0, 4.0]);
+        let mut block = TensorBlock::new(
+            ndarray::arr2(&[[3.0], [2.0]]).into_dyn(),
+            Labels::new(["samples"], &[[0], [1]]),
+            &[],
+            Labels::new(["properties"], &[[0]]),
+        ).unwrap();
+        block.add_gradient(
+            "parameter",
+            ndarray::arr2(&[[1.0], [1.0]]).into_dyn(),
+            Labels::new(["sample"], &[[0], [1]]),
+            &[],
+        ).unwrap();
+        let second = TensorMap::new(Labels::new(["key"], &[[0]]), vec![block]).unwrap();
+
+        assert!(LazyTensorMap::new(&first).unwrap().add(&second).is_err());
+
+        let result = LazyTensorMap::new(&first).unwrap()
+            .missing_gradients(MissingGradients::Zero).unwrap()
+            .add(&second).unwrap()
+            .compute().unwrap();
+
+        let block = result.block_by_id(0);
+        let gradient = block.gradient("parameter").unwrap();
+        assert_eq!(gradient.data.as_array(), ndarray::arr2(&[[1.0], [1.0]]).into_dyn());
+
+        let result = LazyTensorMap::new(&first).unwrap()
+            .missing_gradients(MissingGradients::Drop).unwrap()
+            .add(&second).unwrap()
+            .compute().unwrap();
+        assert!(result.block_by_id(0).gradient("parameter").is_none());
+    }
+}
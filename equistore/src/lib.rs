@@ -19,6 +19,23 @@
 //! [dependencies]
 //! equistore = {version = "...", features = ["static"]}
 //! ```
+//!
+//! The `tokio` feature adds [`io::load_async`] and [`io::save_async`], to
+//! load and save `TensorMap` with `tokio`'s `AsyncRead` and `AsyncWrite`.
+//!
+//! The `object_store` feature adds [`io::ObjectStoreReader`], which can be
+//! used with [`io::load_read_at`] to load some or all of the blocks of a
+//! `TensorMap` stored in S3, Google Cloud Storage, Azure Blob Storage, *etc.*
+//!
+//! The [`labels!`], [`tensorblock!`] and [`tensormap!`] macros in the
+//! [`testing`] module can be used to create small tensor maps from literal
+//! values, for example in tests.
+//!
+//! The `proptest` feature adds strategies in [`testing`] to generate random
+//! valid `Labels`, `TensorBlock` and `TensorMap` for property-based tests.
+//!
+//! The `serde` feature implements `serde::Serialize` and `serde::Deserialize`
+//! for [`TensorMapSummary`] and the associated types.
 
 #![warn(clippy::all, clippy::pedantic)]
 
@@ -47,6 +64,7 @@ pub use self::data::{Array, EmptyArray};
 
 mod labels;
 pub use self::labels::{Labels, LabelsBuilder, LabelValue};
+pub use self::labels::ConcurrentLabelsBuilder;
 pub use self::labels::{LabelsIter, LabelsFixedSizeIter};
 
 #[cfg(feature = "rayon")]
@@ -56,6 +74,7 @@ mod block;
 pub use self::block::{TensorBlock, TensorBlockRef, TensorBlockRefMut};
 pub use self::block::{BasicBlock, BasicBlockMut};
 pub use self::block::{GradientsIter, GradientsMutIter};
+pub use self::block::register_gradient_parameter;
 
 mod tensor;
 pub use self::tensor::TensorMap;
@@ -63,8 +82,25 @@ pub use self::tensor::{TensorMapIter, TensorMapIterMut};
 #[cfg(feature = "rayon")]
 pub use self::tensor::{TensorMapParIter, TensorMapParIterMut};
 
+mod summary;
+pub use self::summary::{TensorMapSummary, BlockSummary, ArraySummary};
+
+mod diff;
+pub use self::diff::{DiffOptions, TensorMapDiff, BlockDiff, BlockDifference};
+
+mod memory;
+pub use self::memory::{MemoryUsage, TensorMapMemoryUsage};
+
+mod lazy;
+pub use self::lazy::{LazyTensorMap, UnaryOp, MissingGradients};
+
 pub mod io;
 
+mod progress;
+pub use self::progress::{Progress, CancellationToken, WithProgress};
+
+pub mod testing;
+
 
 /// Path where the equistore shared library has been built
 pub fn c_api_install_dir() -> &'static str {
@@ -0,0 +1,111 @@
+use crate::c_api::eqs_memory_usage_t;
+use crate::errors::check_status;
+use crate::{Error, TensorMap};
+
+/// Estimation of the memory used by some part of a [`TensorMap`], in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// memory used by the values data arrays, assuming they contain 64-bit
+    /// floating point values
+    pub data: usize,
+    /// memory used to store the values (and names) of the labels
+    pub labels_values: usize,
+    /// memory used by the hash maps to lookup the position of labels entries
+    pub labels_positions: usize,
+    /// memory used by the gradients, including both data and labels
+    pub gradients: usize,
+}
+
+impl MemoryUsage {
+    /// Get the total memory used, in bytes
+    pub fn total(&self) -> usize {
+        self.data + self.labels_values + self.labels_positions + self.gradients
+    }
+}
+
+impl From<eqs_memory_usage_t> for MemoryUsage {
+    fn from(usage: eqs_memory_usage_t) -> MemoryUsage {
+        MemoryUsage {
+            data: usage.data,
+            labels_values: usage.labels_values,
+            labels_positions: usage.labels_positions,
+            gradients: usage.gradients,
+        }
+    }
+}
+
+/// Breakdown of the memory used by a [`TensorMap`], created by
+/// [`TensorMap::memory_usage`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TensorMapMemoryUsage {
+    /// memory used by the keys
+    pub keys: MemoryUsage,
+    /// memory used by each block, in the same order as the blocks
+    pub blocks: Vec<MemoryUsage>,
+    /// memory used by the whole tensor map. `Labels` shared between multiple
+    /// blocks are only counted once here, so this can be smaller than the sum
+    /// of all blocks.
+    pub total: MemoryUsage,
+}
+
+impl TensorMap {
+    /// Estimate the memory used by this `TensorMap`, with a breakdown by block
+    /// and by kind of data (data arrays, labels values, labels positions hash
+    /// maps, and gradients).
+    ///
+    /// The size of data arrays is estimated from their shape, assuming they
+    /// contain 64-bit floating point values. The memory used by arrays storing
+    /// other types is over- or under-estimated accordingly.
+    pub fn memory_usage(&self) -> Result<TensorMapMemoryUsage, Error> {
+        let empty = eqs_memory_usage_t {
+            data: 0,
+            labels_values: 0,
+            labels_positions: 0,
+            gradients: 0,
+        };
+
+        let mut keys = empty;
+        let mut blocks = vec![empty; self.keys().count()];
+        let mut total = empty;
+
+        unsafe {
+            check_status(crate::c_api::eqs_tensormap_memory_usage(
+                self.ptr,
+                &mut keys,
+                blocks.as_mut_ptr(),
+                blocks.len(),
+                &mut total,
+            ))?;
+        }
+
+        return Ok(TensorMapMemoryUsage {
+            keys: keys.into(),
+            blocks: blocks.into_iter().map(MemoryUsage::from).collect(),
+            total: total.into(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Labels, TensorBlock, TensorMap};
+
+    #[test]
+    fn memory_usage() {
+        let blocks = (0..2).map(|_| TensorBlock::new(
+            ndarray::Array::zeros(vec![2, 3]),
+            Labels::new(["samples"], &[[0], [1]]),
+            &[],
+            Labels::new(["properties"], &[[0], [1], [2]]),
+        ).unwrap()).collect();
+        let tensor = TensorMap::new(Labels::new(["key"], &[[0], [1]]), blocks).unwrap();
+
+        let usage = tensor.memory_usage().unwrap();
+        assert_eq!(usage.blocks.len(), 2);
+        assert_eq!(usage.blocks[0].data, 6 * 8);
+        assert_eq!(usage.blocks[0].gradients, 0);
+        assert_eq!(usage.total.data, 2 * 6 * 8);
+        assert!(usage.keys.labels_values > 0);
+        assert!(usage.total.total() > usage.total.data);
+    }
+}
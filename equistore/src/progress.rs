@@ -0,0 +1,179 @@
+//! Progress reporting and cancellation for long-running operations
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::os::raw::c_void;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::c_api::eqs_progress_t;
+
+/// Progress reporting and cooperative cancellation of long-running operations
+/// (serialization and keys restructuring), such as
+/// [`TensorMap::keys_to_samples_with_progress`](crate::TensorMap::keys_to_samples_with_progress)
+/// or [`io::save_with_progress`](crate::io::save_with_progress).
+///
+/// This is implemented for closures taking the number of blocks processed so
+/// far and the total number of blocks, for [`CancellationToken`], and for the
+/// combination of both created by [`CancellationToken::with_progress`].
+pub trait Progress {
+    /// Called after each block is processed, with `done` blocks processed so
+    /// far out of `total`.
+    fn report(&mut self, done: usize, total: usize) {
+        let _ = (done, total);
+    }
+
+    /// Called before each block is processed. If this returns `true`, the
+    /// operation stops with an error.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+impl<F: FnMut(usize, usize)> Progress for F {
+    fn report(&mut self, done: usize, total: usize) {
+        self(done, total);
+    }
+}
+
+/// Token used to cancel long-running operations from another thread.
+///
+/// All the clones of a token share the same state, so a token can be cloned,
+/// given to an operation running in a background thread, and cancelled from
+/// the main thread. The operation then stops before processing the next block
+/// and returns an error with the `EQS_CANCELLED_ERROR` code.
+///
+/// ```no_run
+/// use equistore::CancellationToken;
+/// # let tensor: equistore::TensorMap = todo!();
+///
+/// let token = CancellationToken::new();
+/// let background = token.clone();
+/// let handle = std::thread::spawn(move || {
+///     equistore::io::save_with_progress("data.npz", &tensor, Default::default(), &background)
+/// });
+///
+/// // the user changed their mind
+/// token.cancel();
+/// assert!(handle.join().unwrap().is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a new token, which is not cancelled yet
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Cancel all the operations using this token or any of its clones
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if this token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Combine this token with a `callback` reporting progress, see
+    /// [`Progress`].
+    pub fn with_progress<F: FnMut(usize, usize)>(&self, callback: F) -> WithProgress<'_, F> {
+        WithProgress { token: self, callback }
+    }
+}
+
+impl Progress for &CancellationToken {
+    fn is_cancelled(&self) -> bool {
+        CancellationToken::is_cancelled(self)
+    }
+}
+
+/// A [`CancellationToken`] and a progress callback, created by
+/// [`CancellationToken::with_progress`].
+pub struct WithProgress<'a, F> {
+    token: &'a CancellationToken,
+    callback: F,
+}
+
+impl<F: FnMut(usize, usize)> Progress for WithProgress<'_, F> {
+    fn report(&mut self, done: usize, total: usize) {
+        (self.callback)(done, total);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+/// Wrapper around a [`Progress`] implementation, used to report progress
+/// through the C API with `eqs_progress_t`.
+///
+/// Panics in the callbacks can not unwind through the C API. They are caught
+/// and stored instead, and resumed by [`ProgressCallback::finish`] once the C
+/// API function returned.
+pub(crate) struct ProgressCallback<P> {
+    progress: RefCell<P>,
+    panic: RefCell<Option<Box<dyn Any + Send + 'static>>>,
+}
+
+impl<P: Progress> ProgressCallback<P> {
+    pub(crate) fn new(progress: P) -> ProgressCallback<P> {
+        ProgressCallback {
+            progress: RefCell::new(progress),
+            panic: RefCell::new(None),
+        }
+    }
+
+    /// Get the `eqs_progress_t` calling this callback. The returned value
+    /// must not outlive `self`.
+    pub(crate) fn as_eqs_progress_t(&self) -> eqs_progress_t {
+        eqs_progress_t {
+            user_data: self as *const ProgressCallback<P> as *mut c_void,
+            callback: Some(progress_callback::<P>),
+            cancelled: Some(cancelled_callback::<P>),
+        }
+    }
+
+    /// Resume the panic from the callbacks, if any
+    pub(crate) fn finish(self) {
+        if let Some(panic) = self.panic.into_inner() {
+            std::panic::resume_unwind(panic);
+        }
+    }
+
+    /// Call `function`, storing any panic for later
+    fn call<T>(&self, default: T, function: impl FnOnce(&mut P) -> T) -> T {
+        if self.panic.borrow().is_some() {
+            // do not call the callbacks again after a panic
+            return default;
+        }
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            function(&mut self.progress.borrow_mut())
+        }));
+
+        match result {
+            Ok(value) => value,
+            Err(panic) => {
+                *self.panic.borrow_mut() = Some(panic);
+                default
+            }
+        }
+    }
+}
+
+/// callback used to report progress from the C API to a `ProgressCallback`
+unsafe extern fn progress_callback<P: Progress>(user_data: *mut c_void, done: usize, total: usize) {
+    let state = &*user_data.cast::<ProgressCallback<P>>();
+    state.call((), |progress| progress.report(done, total));
+}
+
+/// callback used to check for cancellation in a `ProgressCallback` from the
+/// C API. The operation is cancelled after a panic in the callbacks.
+unsafe extern fn cancelled_callback<P: Progress>(user_data: *mut c_void) -> bool {
+    let state = &*user_data.cast::<ProgressCallback<P>>();
+    return state.call(true, |progress| progress.is_cancelled());
+}
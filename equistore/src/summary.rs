@@ -0,0 +1,277 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::data::get_data_origin;
+use crate::{BasicBlock, Error, TensorMap};
+
+/// Summary of the content of a [`TensorMap`], without any of the data.
+///
+/// This contains the names of all dimensions, the shape and size of all arrays
+/// and the keys of all blocks, and can be exported to JSON with
+/// [`TensorMapSummary::to_json`]. This is intended to be stored next to
+/// serialized tensor maps, to allow indexing them without loading the data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TensorMapSummary {
+    /// Names of the dimensions of the keys
+    pub keys_names: Vec<String>,
+    /// Summary of all the blocks, in the same order as in the tensor map
+    pub blocks: Vec<BlockSummary>,
+    /// Total size in bytes of the data (values and gradients) in all blocks
+    pub memory: usize,
+}
+
+/// Summary of a single block in a [`TensorMapSummary`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockSummary {
+    /// Values of the key associated with this block, in the same order as
+    /// [`TensorMapSummary::keys_names`]
+    pub key: Vec<i32>,
+    /// Summary of the values of this block
+    pub values: ArraySummary,
+    /// Summary of the gradients of this block, indexed by the gradient
+    /// parameter
+    pub gradients: BTreeMap<String, ArraySummary>,
+}
+
+/// Summary of a single data array (values or gradient) and the associated
+/// metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArraySummary {
+    /// Shape of the data array
+    pub shape: Vec<usize>,
+    /// Type of the data in the array. This is currently always `"float64"`.
+    pub dtype: String,
+    /// Name of the origin of the data array (e.g. `"rust.Box<dyn Array>"` or
+    /// `"equistore.data.array.numpy"`)
+    pub origin: String,
+    /// Size of the data array in bytes
+    pub memory: usize,
+    /// Names of the sample dimensions
+    pub samples_names: Vec<String>,
+    /// Names of the dimensions of each set of components
+    pub components_names: Vec<Vec<String>>,
+    /// Names of the property dimensions
+    pub properties_names: Vec<String>,
+}
+
+impl ArraySummary {
+    fn new(block: &BasicBlock<'_>) -> Result<ArraySummary, Error> {
+        let array = block.data.as_raw();
+        let shape = array.shape()?.to_vec();
+        let memory = shape.iter().product::<usize>() * std::mem::size_of::<f64>();
+
+        let names = |labels: &crate::Labels| {
+            labels.names().into_iter().map(String::from).collect::<Vec<_>>()
+        };
+
+        return Ok(ArraySummary {
+            shape: shape,
+            dtype: "float64".into(),
+            origin: get_data_origin(array.origin()?)?,
+            memory: memory,
+            samples_names: names(&block.samples),
+            components_names: block.components.iter().map(names).collect(),
+            properties_names: names(&block.properties),
+        });
+    }
+
+    fn write_json(&self, output: &mut String) {
+        output.push_str("{\"shape\":");
+        write_json_list(output, &self.shape, |output, value| {
+            write!(output, "{}", value).expect("failed to write to string");
+        });
+        output.push_str(",\"dtype\":");
+        write_json_string(output, &self.dtype);
+        output.push_str(",\"origin\":");
+        write_json_string(output, &self.origin);
+        write!(output, ",\"memory\":{}", self.memory).expect("failed to write to string");
+        output.push_str(",\"samples_names\":");
+        write_json_strings(output, &self.samples_names);
+        output.push_str(",\"components_names\":");
+        write_json_list(output, &self.components_names, |output, names| write_json_strings(output, names));
+        output.push_str(",\"properties_names\":");
+        write_json_strings(output, &self.properties_names);
+        output.push('}');
+    }
+}
+
+impl TensorMapSummary {
+    /// Export this summary as a JSON string.
+    ///
+    /// The JSON document is an object with the same fields as this struct,
+    /// gradients being stored as an object mapping the gradient parameters to
+    /// the corresponding array summaries.
+    pub fn to_json(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str("{\"keys_names\":");
+        write_json_strings(&mut output, &self.keys_names);
+        output.push_str(",\"blocks\":");
+        write_json_list(&mut output, &self.blocks, |output, block| {
+            output.push_str("{\"key\":");
+            write_json_list(output, &block.key, |output, value| {
+                write!(output, "{}", value).expect("failed to write to string");
+            });
+            output.push_str(",\"values\":");
+            block.values.write_json(output);
+            output.push_str(",\"gradients\":{");
+            for (i, (parameter, gradient)) in block.gradients.iter().enumerate() {
+                if i != 0 {
+                    output.push(',');
+                }
+                write_json_string(output, parameter);
+                output.push(':');
+                gradient.write_json(output);
+            }
+            output.push_str("}}");
+        });
+        write!(output, ",\"memory\":{}}}", self.memory).expect("failed to write to string");
+
+        return output;
+    }
+}
+
+impl TensorMap {
+    /// Get a summary of the content of this tensor map, containing the shape,
+    /// metadata names and memory usage of all blocks, without the data itself.
+    ///
+    /// See [`TensorMapSummary`] for more information.
+    pub fn summary(&self) -> Result<TensorMapSummary, Error> {
+        let mut blocks = Vec::new();
+        let mut memory = 0;
+        for (key, block) in self {
+            let values = ArraySummary::new(&block.values())?;
+            memory += values.memory;
+
+            let mut gradients = BTreeMap::new();
+            for (parameter, gradient) in block.gradients() {
+                let gradient = ArraySummary::new(&gradient)?;
+                memory += gradient.memory;
+                gradients.insert(parameter.to_owned(), gradient);
+            }
+
+            blocks.push(BlockSummary {
+                key: key.iter().map(|value| value.i32()).collect(),
+                values: values,
+                gradients: gradients,
+            });
+        }
+
+        return Ok(TensorMapSummary {
+            keys_names: self.keys().names().into_iter().map(String::from).collect(),
+            blocks: blocks,
+            memory: memory,
+        });
+    }
+}
+
+fn write_json_list<T>(output: &mut String, values: &[T], mut write_value: impl FnMut(&mut String, &T)) {
+    output.push('[');
+    for (i, value) in values.iter().enumerate() {
+        if i != 0 {
+            output.push(',');
+        }
+        write_value(output, value);
+    }
+    output.push(']');
+}
+
+fn write_json_strings(output: &mut String, strings: &[String]) {
+    write_json_list(output, strings, |output, string| write_json_string(output, string));
+}
+
+fn write_json_string(output: &mut String, string: &str) {
+    output.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                write!(output, "\\u{:04x}", u32::from(c)).expect("failed to write to string");
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Labels, TensorBlock, TensorMap};
+
+    use super::*;
+
+    #[test]
+    fn summary() {
+        let mut block_1 = TensorBlock::new(
+            ndarray::ArrayD::from_elem(vec![2, 3], 1.0),
+            Labels::new(["samples"], &[[0], [1]]),
+            &[],
+            Labels::new(["properties"], &[[-2], [0], [1]]),
+        ).unwrap();
+
+        block_1.add_gradient(
+            "positions",
+            ndarray::ArrayD::from_elem(vec![1, 3, 3], 1.0),
+            Labels::new(["sample", "atom"], &[[0, 1]]),
+            &[Labels::new(["direction"], &[[0], [1], [2]])],
+        ).unwrap();
+
+        let mut block_2 = TensorBlock::new(
+            ndarray::ArrayD::from_elem(vec![1, 3], 3.0),
+            Labels::new(["samples"], &[[1]]),
+            &[],
+            Labels::new(["properties"], &[[-2], [0], [1]]),
+        ).unwrap();
+
+        block_2.add_gradient(
+            "positions",
+            ndarray::ArrayD::from_elem(vec![0, 3, 3], 1.0),
+            Labels::empty(vec!["sample", "atom"]),
+            &[Labels::new(["direction"], &[[0], [1], [2]])],
+        ).unwrap();
+
+        let tensor = TensorMap::new(
+            Labels::new(["key"], &[[1], [-4]]),
+            vec![block_1, block_2],
+        ).unwrap();
+
+        let summary = tensor.summary().unwrap();
+        assert_eq!(summary.keys_names, ["key"]);
+        assert_eq!(summary.memory, (6 + 9 + 3) * 8);
+        assert_eq!(summary.blocks.len(), 2);
+
+        let block = &summary.blocks[1];
+        assert_eq!(block.key, [-4]);
+        assert_eq!(block.values.shape, [1, 3]);
+        assert_eq!(block.values.dtype, "float64");
+        assert_eq!(block.values.origin, "rust.Box<dyn Array>");
+        assert_eq!(block.values.memory, 24);
+        assert_eq!(block.values.samples_names, ["samples"]);
+        assert!(block.values.components_names.is_empty());
+        assert_eq!(block.values.properties_names, ["properties"]);
+
+        let gradient = &block.gradients["positions"];
+        assert_eq!(gradient.shape, [0, 3, 3]);
+        assert_eq!(gradient.memory, 0);
+        assert_eq!(gradient.samples_names, ["sample", "atom"]);
+        assert_eq!(gradient.components_names, [["direction"]]);
+
+        let json = summary.to_json();
+        assert!(json.starts_with(r#"{"keys_names":["key"],"blocks":[{"key":[1],"values":{"shape":[2,3],"dtype":"float64","origin":"rust.Box<dyn Array>","memory":48,"samples_names":["samples"],"components_names":[],"properties_names":["properties"]},"gradients":{"positions":{"shape":[1,3,3],"#));
+        assert!(json.ends_with(r#""properties_names":["properties"]}}}],"memory":144}"#));
+    }
+
+    #[test]
+    fn json_string() {
+        let mut output = String::new();
+        write_json_string(&mut output, "a \"quoted\"\\ string\n\u{1}é");
+        assert_eq!(output, r#""a \"quoted\"\\ string\n\u0001é""#);
+    }
+}
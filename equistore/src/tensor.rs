@@ -2,10 +2,10 @@ use std::ffi::CString;
 use std::iter::FusedIterator;
 
 use crate::block::{TensorBlockRefMut};
-use crate::c_api::{eqs_tensormap_t, eqs_labels_t};
+use crate::c_api::{eqs_tensormap_t, eqs_labels_t, eqs_array_t};
 
 use crate::errors::{check_status, check_ptr};
-use crate::{Error, TensorBlock, TensorBlockRef, Labels, LabelValue};
+use crate::{Array, Error, TensorBlock, TensorBlockRef, Labels, LabelsBuilder, LabelValue, Progress};
 
 /// [`TensorMap`] is the main user-facing struct of this library, and can
 /// store any kind of data used in atomistic machine learning.
@@ -129,6 +129,28 @@ impl TensorMap {
         return Ok(unsafe { TensorMap::from_raw(ptr) });
     }
 
+    /// Create a new `TensorMap` with the same keys, labels and gradients as
+    /// this one, using `values` as the values of the blocks.
+    ///
+    /// There must be one array for each block, in the same order as the
+    /// blocks, and each array must have the same shape as the corresponding
+    /// block values. The metadata is shared with this `TensorMap` and not
+    /// copied.
+    #[inline]
+    pub fn with_values(&self, values: Vec<impl Array>) -> Result<TensorMap, Error> {
+        let mut arrays = values.into_iter()
+            .map(|array| (Box::new(array) as Box<dyn Array>).into())
+            .collect::<Vec<eqs_array_t>>();
+
+        let ptr = unsafe {
+            crate::c_api::eqs_tensormap_with_values(self.ptr, arrays.as_mut_ptr(), arrays.len())
+        };
+        // the arrays are now owned by equistore-core, even in case of error
+        check_ptr(ptr)?;
+
+        return Ok(unsafe { TensorMap::from_raw(ptr) });
+    }
+
     /// Get the keys defined in this `TensorMap`
     #[inline]
     pub fn keys(&self) -> &Labels {
@@ -259,6 +281,16 @@ impl TensorMap {
         return Ok(self.block_by_id(id));
     }
 
+    /// Get a mutable reference to the block matching the given selection.
+    ///
+    /// This function uses [`TensorMap::blocks_matching`] under the hood to find
+    /// the matching block.
+    #[inline]
+    pub fn block_mut(&mut self, selection: &Labels) -> Result<TensorBlockRefMut<'_>, Error> {
+        let id = self.block_matching(selection)?;
+        return Ok(self.block_mut_by_id(id));
+    }
+
     /// Get a reference to every blocks in this `TensorMap`
     #[inline]
     pub fn blocks(&self) -> Vec<TensorBlockRef<'_>> {
@@ -279,6 +311,99 @@ impl TensorMap {
         return blocks;
     }
 
+    /// Create a new `TensorMap` from a dense 3-dimensional array, for the
+    /// common case where all blocks share the same samples and properties.
+    ///
+    /// `data` must have a shape of `(keys.count(), samples.count(),
+    /// n_properties)`. The block associated with the `i`-th key will contain
+    /// `data[i, :, :]` as values, the given `samples`, no components, and
+    /// properties named `"property"` containing the range `0..n_properties`.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn from_dense(keys: Labels, samples: Labels, data: ndarray::Array3<f64>) -> Result<TensorMap, Error> {
+        let (n_keys, n_samples, n_properties) = data.dim();
+        if n_keys != keys.count() || n_samples != samples.count() {
+            return Err(Error {
+                code: None,
+                message: format!(
+                    "invalid shape for dense data: expected ({}, {}, n_properties) \
+                    from the keys and samples, got ({}, {}, {})",
+                    keys.count(), samples.count(), n_keys, n_samples, n_properties
+                ),
+            });
+        }
+
+        let mut properties = LabelsBuilder::new(vec!["property"]);
+        for i in 0..n_properties {
+            properties.add(&[i]);
+        }
+        let properties = properties.finish();
+
+        let mut blocks = Vec::with_capacity(n_keys);
+        for values in data.outer_iter() {
+            blocks.push(TensorBlock::new(
+                values.to_owned().into_dyn(),
+                samples.clone(),
+                &[],
+                properties.clone(),
+            )?);
+        }
+
+        return TensorMap::new(keys, blocks);
+    }
+
+    /// Convert this `TensorMap` to a dense 3-dimensional array, with a shape
+    /// of `(keys.count(), n_samples, n_properties)`.
+    ///
+    /// This is the inverse of [`TensorMap::from_dense`], and is only possible
+    /// if all blocks have the same samples and properties, no components and
+    /// no gradients. Empty tensor maps can not be converted, since they do not
+    /// define the number of samples and properties. This function will panic
+    /// if the data in the blocks is not stored in `ndarray::ArrayD`.
+    pub fn to_dense(&self) -> Result<ndarray::Array3<f64>, Error> {
+        let blocks = self.blocks();
+        let (samples, properties) = match blocks.first() {
+            Some(block) => {
+                let values = block.values();
+                (values.samples, values.properties)
+            },
+            None => return Err(Error {
+                code: None,
+                message: "can not convert an empty TensorMap to a dense array".into(),
+            }),
+        };
+
+        let mut dense = ndarray::Array3::zeros((blocks.len(), samples.count(), properties.count()));
+        for (block_i, (block, mut output)) in blocks.iter().zip(dense.outer_iter_mut()).enumerate() {
+            let values = block.values();
+            if !values.components.is_empty() || !block.gradient_list().is_empty() {
+                return Err(Error {
+                    code: None,
+                    message: format!(
+                        "can not convert this TensorMap to a dense array: \
+                        block {} contains components or gradients", block_i
+                    ),
+                });
+            }
+
+            if values.samples != samples || values.properties != properties {
+                return Err(Error {
+                    code: None,
+                    message: format!(
+                        "can not convert this TensorMap to a dense array: \
+                        block {} has different samples or properties than \
+                        the first block", block_i
+                    ),
+                });
+            }
+
+            let array = values.data.as_array().view().into_dimensionality::<ndarray::Ix2>()
+                .expect("values of a block without components should be 2-dimensional");
+            output.assign(&array);
+        }
+
+        return Ok(dense);
+    }
+
     /// Merge blocks with the same value for selected keys dimensions along the
     /// samples axis.
     ///
@@ -312,6 +437,98 @@ impl TensorMap {
         return Ok(unsafe { TensorMap::from_raw(ptr) });
     }
 
+    /// Same as [`TensorMap::keys_to_samples`], reporting the number of new blocks
+    /// created so far to `progress`, and stopping with an error if `progress`
+    /// is cancelled before creating a new block. See [`Progress`] for more
+    /// information.
+    #[inline]
+    pub fn keys_to_samples_with_progress(
+        &self,
+        keys_to_move: &Labels,
+        sort_samples: bool,
+        progress: impl Progress,
+    ) -> Result<TensorMap, Error> {
+        let progress = crate::progress::ProgressCallback::new(progress);
+        let ptr = unsafe {
+            crate::c_api::eqs_tensormap_keys_to_samples_with_progress(
+                self.ptr,
+                keys_to_move.as_eqs_labels_t(),
+                sort_samples,
+                progress.as_eqs_progress_t(),
+            )
+        };
+        progress.finish();
+
+        check_ptr(ptr)?;
+        return Ok(unsafe { TensorMap::from_raw(ptr) });
+    }
+
+    /// Compute the outer product of the properties of `self` and `other`.
+    ///
+    /// Both tensor maps must have the same keys (possibly in a different
+    /// order), and the blocks with the same key must have the same samples and
+    /// components. The properties of the new blocks are the cartesian product
+    /// of the properties of the two blocks, with the names of the properties
+    /// prefixed by `first_` and `second_` respectively. The keys of the result
+    /// are the keys of `self`. Blocks with gradients are not supported.
+    #[inline]
+    pub fn tensor_product(&self, other: &TensorMap) -> Result<TensorMap, Error> {
+        let ptr = unsafe {
+            crate::c_api::eqs_tensormap_product(self.ptr, other.ptr)
+        };
+
+        check_ptr(ptr)?;
+        return Ok(unsafe { TensorMap::from_raw(ptr) });
+    }
+
+    /// Expand all the blocks in all the `tensors` to use the same set of
+    /// properties, i.e. the union of the properties of all blocks. Entries
+    /// corresponding to properties missing in a block are filled with zeros.
+    ///
+    /// The resulting tensor maps are returned in the same order as `tensors`,
+    /// and can be directly summed or concatenated along samples. The union of
+    /// properties is sorted in lexicographic order.
+    #[inline]
+    pub fn align_properties(tensors: &[&TensorMap]) -> Result<Vec<TensorMap>, Error> {
+        let tensors_ptr = tensors.iter().map(|tensor| tensor.ptr as *const _).collect::<Vec<_>>();
+        let mut aligned = vec![std::ptr::null_mut(); tensors.len()];
+
+        unsafe {
+            check_status(crate::c_api::eqs_tensormap_align_properties(
+                tensors_ptr.as_ptr(),
+                tensors_ptr.len(),
+                aligned.as_mut_ptr(),
+            ))?;
+        }
+
+        return Ok(aligned.into_iter().map(|ptr| unsafe { TensorMap::from_raw(ptr) }).collect());
+    }
+
+    /// Restrict both `self` and `other` to the samples they have in common.
+    ///
+    /// Blocks are matched by key, and both tensor maps must contain the same
+    /// set of keys. For each pair of blocks, only the samples present in both
+    /// blocks are kept, in the order they appear in the block from `self`; and
+    /// gradients are updated accordingly.
+    #[inline]
+    pub fn intersect_samples(&self, other: &TensorMap) -> Result<(TensorMap, TensorMap), Error> {
+        let mut first = std::ptr::null_mut();
+        let mut second = std::ptr::null_mut();
+
+        unsafe {
+            check_status(crate::c_api::eqs_tensormap_intersect_samples(
+                self.ptr,
+                other.ptr,
+                &mut first,
+                &mut second,
+                std::ptr::null_mut(),
+                0,
+            ))?;
+
+            return Ok((TensorMap::from_raw(first), TensorMap::from_raw(second)));
+        }
+    }
+
     /// Merge blocks with the same value for selected keys dimensions along the
     /// property axis.
     ///
@@ -352,6 +569,32 @@ impl TensorMap {
         return Ok(unsafe { TensorMap::from_raw(ptr) });
     }
 
+    /// Same as [`TensorMap::keys_to_properties`], reporting the number of new blocks
+    /// created so far to `progress`, and stopping with an error if `progress`
+    /// is cancelled before creating a new block. See [`Progress`] for more
+    /// information.
+    #[inline]
+    pub fn keys_to_properties_with_progress(
+        &self,
+        keys_to_move: &Labels,
+        sort_samples: bool,
+        progress: impl Progress,
+    ) -> Result<TensorMap, Error> {
+        let progress = crate::progress::ProgressCallback::new(progress);
+        let ptr = unsafe {
+            crate::c_api::eqs_tensormap_keys_to_properties_with_progress(
+                self.ptr,
+                keys_to_move.as_eqs_labels_t(),
+                sort_samples,
+                progress.as_eqs_progress_t(),
+            )
+        };
+        progress.finish();
+
+        check_ptr(ptr)?;
+        return Ok(unsafe { TensorMap::from_raw(ptr) });
+    }
+
     /// Move the given dimensions from the component labels to the property
     /// labels for each block in this `TensorMap`.
     #[inline]
@@ -377,6 +620,73 @@ impl TensorMap {
         return Ok(unsafe { TensorMap::from_raw(ptr) });
     }
 
+    /// Get a canonical version of this `TensorMap`, where the keys are sorted
+    /// in lexicographic order, and the samples and properties of each block
+    /// (and the samples of each gradient) are sorted as well. The data arrays
+    /// are re-ordered accordingly, and the components are left unchanged.
+    ///
+    /// Two tensor maps containing the same data in a different order will be
+    /// identical after canonicalization.
+    #[inline]
+    pub fn canonicalize(&self) -> Result<TensorMap, Error> {
+        let ptr = unsafe {
+            crate::c_api::eqs_tensormap_canonicalize(self.ptr)
+        };
+
+        check_ptr(ptr)?;
+        return Ok(unsafe { TensorMap::from_raw(ptr) });
+    }
+
+    /// Compute a stable 64-bit fingerprint of this `TensorMap`.
+    ///
+    /// The fingerprint covers all the metadata (keys, samples, components,
+    /// properties, gradients, units, quantities and tensor map metadata) and
+    /// the shape of all arrays. If `include_data` is `true`, the values stored
+    /// in the arrays are also part of the fingerprint.
+    ///
+    /// The fingerprint does not depend on the platform, the process or the
+    /// version of equistore, and can be used as a cache key. It does depend on
+    /// the order of entries in the labels, use [`TensorMap::canonicalize`]
+    /// first to ignore it.
+    #[inline]
+    pub fn fingerprint(&self, include_data: bool) -> Result<u64, Error> {
+        let mut fingerprint = 0;
+        unsafe {
+            check_status(crate::c_api::eqs_tensormap_fingerprint(
+                self.ptr,
+                include_data,
+                &mut fingerprint,
+            ))?;
+        }
+
+        return Ok(fingerprint);
+    }
+
+    /// Compress in memory the data of all blocks matching the given
+    /// `selection`, using the same rules as [`TensorMap::blocks_matching`].
+    ///
+    /// The data of compressed blocks is stored with zstd, and uses a separate
+    /// data origin: the arrays can not be accessed as `ndarray` until
+    /// [`TensorMap::decompress_blocks`] is called.
+    #[inline]
+    pub fn compress_blocks(&mut self, selection: &Labels) -> Result<(), Error> {
+        unsafe {
+            check_status(crate::c_api::eqs_tensormap_compress_blocks(
+                self.ptr,
+                selection.as_eqs_labels_t(),
+            ))
+        }
+    }
+
+    /// Convert the data of all blocks compressed with
+    /// [`TensorMap::compress_blocks`] back to the original array type.
+    #[inline]
+    pub fn decompress_blocks(&mut self) -> Result<(), Error> {
+        unsafe {
+            check_status(crate::c_api::eqs_tensormap_decompress_blocks(self.ptr))
+        }
+    }
+
     /// Get an iterator over the keys and associated blocks
     #[inline]
     pub fn iter(&self) -> TensorMapIter<'_> {
@@ -634,5 +944,205 @@ mod tests {
             *array *= 2.0;
             assert_eq!(array[[0, 0]], 2.0 * (key[0].i32() as f64));
         }
+
+        // multiple immutable references to blocks can be alive at once
+        let first = tensor.block(&Labels::new(["key"], &[[1]])).unwrap();
+        let second = tensor.block_by_id(1);
+        assert_eq!(first.values().data.to_array()[[0, 0]], 2.0);
+        assert_eq!(second.values().data.to_array()[[0, 0]], 6.0);
+
+        let mut block = tensor.block_mut(&Labels::new(["key"], &[[-4]])).unwrap();
+        block.values_mut().data.to_array_mut().fill(0.0);
+        assert_eq!(tensor.block_by_id(2).values().data.to_array()[[0, 0]], 0.0);
+
+        let error = tensor.block_mut(&Labels::new(["key"], &[[5]])).unwrap_err();
+        assert_eq!(error.message, "no blocks matched the selection (key = 5)");
+    }
+
+    #[test]
+    fn canonicalize() {
+        let block_1 = TensorBlock::new(
+            ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0]]).into_dyn(),
+            Labels::new(["samples"], &[[2], [0]]),
+            &[],
+            Labels::new(["properties"], &[[1], [-1]]),
+        ).unwrap();
+        let block_2 = TensorBlock::new(
+            ndarray::arr2(&[[5.0]]).into_dyn(),
+            Labels::new(["samples"], &[[1]]),
+            &[],
+            Labels::new(["properties"], &[[0]]),
+        ).unwrap();
+
+        let tensor = TensorMap::new(
+            Labels::new(["key"], &[[3], [1]]),
+            vec![block_1, block_2],
+        ).unwrap();
+
+        let canonical = tensor.canonicalize().unwrap();
+        assert_eq!(*canonical.keys(), Labels::new(["key"], &[[1], [3]]));
+
+        let block = canonical.block_by_id(1);
+        assert_eq!(block.values().samples, Labels::new(["samples"], &[[0], [2]]));
+        assert_eq!(block.values().properties, Labels::new(["properties"], &[[-1], [1]]));
+        assert_eq!(block.values().data.as_array(), ndarray::arr2(&[[4.0, 3.0], [2.0, 1.0]]).into_dyn());
+    }
+
+    #[test]
+    fn align_properties() {
+        let first = TensorMap::new(Labels::new(["key"], &[[0]]), vec![TensorBlock::new(
+            ndarray::arr2(&[[1.0, 2.0]]).into_dyn(),
+            Labels::new(["samples"], &[[0]]),
+            &[],
+            Labels::new(["properties"], &[[0], [2]]),
+        ).unwrap()]).unwrap();
+
+        let second = TensorMap::new(Labels::new(["key"], &[[0]]), vec![TensorBlock::new(
+            ndarray::arr2(&[[3.0, 4.0]]).into_dyn(),
+            Labels::new(["samples"], &[[1]]),
+            &[],
+            Labels::new(["properties"], &[[1], [2]]),
+        ).unwrap()]).unwrap();
+
+        let aligned = TensorMap::align_properties(&[&first, &second]).unwrap();
+        assert_eq!(aligned.len(), 2);
+
+        let expected = Labels::new(["properties"], &[[0], [1], [2]]);
+        let values = aligned[0].block_by_id(0).values();
+        assert_eq!(values.properties, expected);
+        assert_eq!(values.data.as_array(), ndarray::arr2(&[[1.0, 0.0, 2.0]]).into_dyn());
+
+        let values = aligned[1].block_by_id(0).values();
+        assert_eq!(values.properties, expected);
+        assert_eq!(values.data.as_array(), ndarray::arr2(&[[0.0, 3.0, 4.0]]).into_dyn());
+    }
+
+    #[test]
+    fn intersect_samples() {
+        let first = TensorMap::new(Labels::new(["key"], &[[0]]), vec![TensorBlock::new(
+            ndarray::arr2(&[[1.0], [2.0], [3.0]]).into_dyn(),
+            Labels::new(["samples"], &[[0], [1], [2]]),
+            &[],
+            Labels::new(["properties"], &[[0]]),
+        ).unwrap()]).unwrap();
+
+        let second = TensorMap::new(Labels::new(["key"], &[[0]]), vec![TensorBlock::new(
+            ndarray::arr2(&[[4.0], [5.0]]).into_dyn(),
+            Labels::new(["samples"], &[[2], [0]]),
+            &[],
+            Labels::new(["properties"], &[[0]]),
+        ).unwrap()]).unwrap();
+
+        let (first, second) = first.intersect_samples(&second).unwrap();
+
+        let expected = Labels::new(["samples"], &[[0], [2]]);
+        let values = first.block_by_id(0).values();
+        assert_eq!(values.samples, expected);
+        assert_eq!(values.data.as_array(), ndarray::arr2(&[[1.0], [3.0]]).into_dyn());
+
+        let values = second.block_by_id(0).values();
+        assert_eq!(values.samples, expected);
+        assert_eq!(values.data.as_array(), ndarray::arr2(&[[5.0], [4.0]]).into_dyn());
+    }
+
+    #[test]
+    fn fingerprint() {
+        let block = TensorBlock::new(
+            ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0]]).into_dyn(),
+            Labels::new(["samples"], &[[0], [1]]),
+            &[],
+            Labels::new(["properties"], &[[0], [1]]),
+        ).unwrap();
+        let mut tensor = TensorMap::new(Labels::new(["key"], &[[0]]), vec![block]).unwrap();
+
+        let with_data = tensor.fingerprint(true).unwrap();
+        let without_data = tensor.fingerprint(false).unwrap();
+        assert_ne!(with_data, without_data);
+        assert_eq!(tensor.try_clone().unwrap().fingerprint(true).unwrap(), with_data);
+
+        tensor.block_mut_by_id(0).values_mut().data.to_array_mut()[[0, 0]] = 10.0;
+        assert_ne!(tensor.fingerprint(true).unwrap(), with_data);
+        assert_eq!(tensor.fingerprint(false).unwrap(), without_data);
+    }
+
+    #[test]
+    fn with_values() {
+        let block = TensorBlock::new(
+            ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0]]).into_dyn(),
+            Labels::new(["samples"], &[[0], [1]]),
+            &[],
+            Labels::new(["properties"], &[[0], [1]]),
+        ).unwrap();
+        let tensor = TensorMap::new(Labels::new(["key"], &[[0]]), vec![block]).unwrap();
+
+        let new_values = ndarray::arr2(&[[5.0, 6.0], [7.0, 8.0]]).into_dyn();
+        let new_tensor = tensor.with_values(vec![new_values.clone()]).unwrap();
+        assert_eq!(new_tensor.keys(), tensor.keys());
+
+        let values = new_tensor.block_by_id(0).values();
+        assert_eq!(values.samples, Labels::new(["samples"], &[[0], [1]]));
+        assert_eq!(values.data.as_array(), new_values);
+
+        let error = tensor.with_values(vec![ndarray::ArrayD::<f64>::zeros(vec![3, 2])]).unwrap_err();
+        assert!(error.message.starts_with("invalid parameter"), "{}", error.message);
+
+        let error = tensor.with_values(Vec::<ndarray::ArrayD<f64>>::new()).unwrap_err();
+        assert_eq!(error.message, "invalid parameter: expected 1 values arrays, one for each block, got 0");
+    }
+
+    #[test]
+    fn compress_blocks() {
+        let blocks = (0..2).map(|_| TensorBlock::new(
+            ndarray::Array::zeros(vec![2, 30]),
+            Labels::new(["samples"], &[[0], [1]]),
+            &[],
+            Labels::new(["properties"], &(0..30).map(|i| [i]).collect::<Vec<_>>()),
+        ).unwrap()).collect();
+        let mut tensor = TensorMap::new(Labels::new(["key"], &[[0], [1]]), blocks).unwrap();
+
+        tensor.compress_blocks(&Labels::new(["key"], &[[1]])).unwrap();
+        let usage = tensor.memory_usage().unwrap();
+        assert_eq!(usage.blocks[0].data, 60 * 8);
+        assert!(usage.blocks[1].data < 60 * 8);
+
+        tensor.decompress_blocks().unwrap();
+        assert_eq!(tensor.memory_usage().unwrap().blocks[1].data, 60 * 8);
+
+        let block = tensor.block_by_id(1);
+        assert_eq!(block.values().data.as_array(), ndarray::Array::<f64, _>::zeros(vec![2, 30]));
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss, clippy::float_cmp)]
+    fn dense() {
+        let data = ndarray::Array3::from_shape_fn((2, 3, 4), |(i, j, k)| (100 * i + 10 * j + k) as f64);
+        let tensor = TensorMap::from_dense(
+            Labels::new(["key"], &[[1], [3]]),
+            Labels::new(["structure"], &[[0], [1], [2]]),
+            data.clone(),
+        ).unwrap();
+
+        assert_eq!(tensor.keys().count(), 2);
+        let block = tensor.block_by_id(1);
+        assert_eq!(block.values().samples, Labels::new(["structure"], &[[0], [1], [2]]));
+        assert_eq!(block.values().properties, Labels::new(["property"], &[[0], [1], [2], [3]]));
+        assert_eq!(block.values().data.as_array()[[2, 3]], 123.0);
+
+        assert_eq!(tensor.to_dense().unwrap(), data);
+
+        let error = TensorMap::from_dense(
+            Labels::new(["key"], &[[1]]),
+            Labels::new(["structure"], &[[0], [1], [2]]),
+            data,
+        ).unwrap_err();
+        assert_eq!(
+            error.message,
+            "invalid shape for dense data: expected (1, 3, n_properties) from \
+            the keys and samples, got (2, 3, 4)"
+        );
+
+        let empty = TensorMap::new(Labels::empty(vec!["key"]), vec![]).unwrap();
+        let error = empty.to_dense().unwrap_err();
+        assert_eq!(error.message, "can not convert an empty TensorMap to a dense array");
     }
 }
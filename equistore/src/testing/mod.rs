@@ -0,0 +1,212 @@
+//! Helpers to create small [`Labels`](crate::Labels),
+//! [`TensorBlock`](crate::TensorBlock) and [`TensorMap`](crate::TensorMap)
+//! from literal values, mostly intended for tests.
+//!
+//! The [`labels!`](crate::labels), [`tensorblock!`](crate::tensorblock) and
+//! [`tensormap!`](crate::tensormap) macros panic if the data is not valid,
+//! instead of returning an error.
+//!
+//! ```
+//! use equistore::{labels, tensorblock, tensormap};
+//! use ndarray::array;
+//!
+//! let tensor = tensormap! {
+//!     keys: labels!(["key"] => [[0], [1]]),
+//!     blocks: [
+//!         tensorblock! {
+//!             values: array![[1.0, 2.0], [3.0, 4.0]],
+//!             samples: labels!(["structure"] => [[0], [1]]),
+//!             components: [],
+//!             properties: labels!(["n"] => [[0], [1]]),
+//!             gradients: {
+//!                 "positions": {
+//!                     values: array![[[0.0, 1.0], [1.0, 0.0], [0.0, 0.0]]],
+//!                     samples: labels!(["sample", "atom"] => [[1, 2]]),
+//!                     components: [labels!(["direction"] => [[0], [1], [2]])],
+//!                 },
+//!             },
+//!         },
+//!         tensorblock! {
+//!             values: array![[5.0, 6.0]],
+//!             samples: labels!(["structure"] => [[1]]),
+//!             components: [],
+//!             properties: labels!(["n"] => [[0], [1]]),
+//!             gradients: {
+//!                 "positions": {
+//!                     values: array![[[1.0, 0.0], [0.0, 1.0], [1.0, 1.0]]],
+//!                     samples: labels!(["sample", "atom"] => [[0, 3]]),
+//!                     components: [labels!(["direction"] => [[0], [1], [2]])],
+//!                 },
+//!             },
+//!         },
+//!     ],
+//! };
+//!
+//! assert_eq!(tensor.keys().count(), 2);
+//! assert_eq!(tensor.block_by_id(1).gradient_list(), ["positions"]);
+//! ```
+//!
+//! With the `proptest` feature, the [`strategies`] module also provides
+//! strategies to generate random valid objects for property-based testing.
+
+#[cfg(feature = "proptest")]
+pub mod strategies;
+
+/// Convert an array of any dimension to the dynamic dimension array used by
+/// [`tensorblock!`](crate::tensorblock).
+#[doc(hidden)]
+pub fn into_array<D: ndarray::Dimension>(array: ndarray::Array<f64, D>) -> ndarray::ArrayD<f64> {
+    return array.into_dyn();
+}
+
+/// Create [`Labels`](crate::Labels) from a list of names and a list of
+/// entries.
+///
+/// ```
+/// # use equistore::labels;
+/// let labels = labels!(["structure", "center"] => [[0, 1], [0, 2], [1, 1]]);
+/// assert_eq!(labels.names(), ["structure", "center"]);
+/// assert_eq!(labels.count(), 3);
+///
+/// let empty = labels!(["structure"] => []);
+/// assert!(empty.is_empty());
+/// ```
+///
+/// # Panics
+///
+/// If the set of names is not valid, or any of the entries is duplicated
+#[macro_export]
+macro_rules! labels {
+    ([$($name:expr),+ $(,)?] => [$([$($value:expr),* $(,)?]),* $(,)?]) => {{
+        #[allow(unused_mut)]
+        let mut builder = $crate::LabelsBuilder::new(::std::vec![$($name),+]);
+        $(
+            builder.add::<i32>(&[$($value),*]);
+        )*
+        builder.finish()
+    }};
+}
+
+/// Create a [`TensorBlock`](crate::TensorBlock) from its values, metadata and
+/// optional gradients.
+///
+/// The values (and gradients values) can be any `ndarray::Array<f64, D>`, for
+/// example created with `ndarray::array!`. See the [module
+/// documentation](crate::testing) for an example.
+///
+/// # Panics
+///
+/// If the values shape does not match the metadata, or the gradients are not
+/// valid for this block
+#[macro_export]
+macro_rules! tensorblock {
+    (
+        values: $values:expr,
+        samples: $samples:expr,
+        components: [$($component:expr),* $(,)?],
+        properties: $properties:expr
+        $(, gradients: {
+            $($parameter:literal: {
+                values: $gradient_values:expr,
+                samples: $gradient_samples:expr,
+                components: [$($gradient_component:expr),* $(,)?] $(,)?
+            }),* $(,)?
+        })?
+        $(,)?
+    ) => {{
+        #[allow(unused_mut)]
+        let mut block = $crate::TensorBlock::new(
+            $crate::testing::into_array($values),
+            $samples,
+            &[$($component),*],
+            $properties,
+        ).expect("invalid block in tensorblock!");
+        $($(
+            block.add_gradient(
+                $parameter,
+                $crate::testing::into_array($gradient_values),
+                $gradient_samples,
+                &[$($gradient_component),*],
+            ).expect("invalid gradient in tensorblock!");
+        )*)?
+        block
+    }};
+}
+
+/// Create a [`TensorMap`](crate::TensorMap) from its keys and a list of
+/// blocks, typically created with [`tensorblock!`](crate::tensorblock). See
+/// the [module documentation](crate::testing) for an example.
+///
+/// # Panics
+///
+/// If the keys do not match the blocks, or the blocks are not compatible with
+/// one another
+#[macro_export]
+macro_rules! tensormap {
+    (keys: $keys:expr, blocks: [$($block:expr),* $(,)?] $(,)?) => {
+        $crate::TensorMap::new($keys, ::std::vec![$($block),*]).expect("invalid tensor in tensormap!")
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array4};
+
+    use crate::Labels;
+
+    #[test]
+    fn labels() {
+        let labels = labels!(["a", "b"] => [[0, 1], [-3, 2],]);
+        assert_eq!(labels, Labels::new(["a", "b"], &[[0, 1], [-3, 2]]));
+
+        let labels = labels!(["a"] => []);
+        assert_eq!(labels, Labels::empty(vec!["a"]));
+    }
+
+    #[test]
+    fn tensormap() {
+        let tensor = tensormap! {
+            keys: labels!(["key"] => [[3]]),
+            blocks: [
+                tensorblock! {
+                    values: array![[[1.0], [2.0]]],
+                    samples: labels!(["structure"] => [[0]]),
+                    components: [labels!(["m"] => [[-1], [1]])],
+                    properties: labels!(["n"] => [[0]]),
+                    gradients: {
+                        "positions": {
+                            values: Array4::from_shape_vec((2, 1, 2, 1), vec![1.0, 2.0, 3.0, 4.0]).unwrap(),
+                            samples: labels!(["sample", "atom"] => [[0, 0], [0, 1]]),
+                            components: [
+                                labels!(["direction"] => [[0]]),
+                                labels!(["m"] => [[-1], [1]]),
+                            ],
+                        },
+                    },
+                },
+            ],
+        };
+
+        assert_eq!(*tensor.keys(), Labels::new(["key"], &[[3]]));
+
+        let block = tensor.block_by_id(0);
+        let values = block.values();
+        assert_eq!(values.data.as_array(), array![[[1.0], [2.0]]].into_dyn());
+        assert_eq!(values.components, [Labels::new(["m"], &[[-1], [1]])]);
+
+        let gradient = block.gradient("positions").unwrap();
+        assert_eq!(gradient.data.as_array().shape(), [2, 1, 2, 1]);
+        assert_eq!(gradient.samples, Labels::new(["sample", "atom"], &[[0, 0], [0, 1]]));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid block in tensorblock!")]
+    fn invalid_block() {
+        tensorblock! {
+            values: array![[1.0, 2.0]],
+            samples: labels!(["structure"] => [[0], [1]]),
+            components: [],
+            properties: labels!(["n"] => [[0], [1]]),
+        };
+    }
+}
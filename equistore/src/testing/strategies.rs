@@ -0,0 +1,222 @@
+//! [`proptest`] strategies generating random valid [`Labels`],
+//! [`TensorBlock`] and [`TensorMap`], for property-based testing.
+//!
+//! All the generated arrays contain `f64` values (this is the only data type
+//! available for Rust arrays), and label values are taken in the `-5..5`
+//! range to make collisions between different objects likely.
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use equistore::testing::strategies;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn clone_is_equal(tensor in strategies::tensormap(4)) {
+//!         let clone = tensor.try_clone().unwrap();
+//!         prop_assert_eq!(clone.keys(), tensor.keys());
+//!     }
+//! }
+//! ```
+
+use proptest::collection::{hash_set, vec, SizeRange};
+use proptest::prelude::*;
+
+use crate::{Labels, LabelsBuilder, TensorBlock, TensorMap};
+
+/// Range of values used for all the generated labels
+const LABEL_VALUES: std::ops::Range<i32> = -5..5;
+
+/// Create a strategy generating `Labels` with the given `names`, and a number
+/// of entries in the `count` range.
+///
+/// The entries are sorted. Since the label values are taken from a small
+/// range, `count` should stay well below `10^names.len()`.
+pub fn labels_with_names(names: Vec<String>, count: impl Into<SizeRange>) -> impl Strategy<Value = Labels> {
+    assert!(!names.is_empty(), "labels_with_names needs at least one name");
+
+    let size = names.len();
+    return hash_set(vec(LABEL_VALUES, size), count).prop_map(move |entries| {
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        entries.sort_unstable();
+
+        let mut builder = LabelsBuilder::new(names.iter().map(|name| &**name).collect());
+        for entry in entries {
+            builder.add(&entry);
+        }
+        builder.finish()
+    });
+}
+
+/// Create a strategy generating `Labels` with between 1 and `max_dimensions`
+/// dimensions, named `dimension_0`, `dimension_1`, *etc.*, and between 0 and
+/// `max_count` entries.
+///
+/// # Panics
+///
+/// If `max_dimensions` is zero or `max_count` is larger than 10
+pub fn labels(max_dimensions: usize, max_count: usize) -> impl Strategy<Value = Labels> {
+    assert!(max_dimensions > 0, "max_dimensions must be at least 1");
+    assert!(max_count <= 10, "max_count can be at most 10");
+
+    return (1..=max_dimensions).prop_flat_map(move |dimensions| {
+        let names = (0..dimensions).map(|i| format!("dimension_{}", i)).collect();
+        labels_with_names(names, 0..=max_count)
+    });
+}
+
+/// Create a strategy generating `TensorBlock` with `n_components` components
+/// and a gradient with respect to each of the given `gradients` parameters.
+///
+/// The blocks have between 0 and 6 samples named `["structure", "center"]`,
+/// between 1 and 3 entries in each component (named `component_0`,
+/// `component_1`, *etc.*) and between 1 and 4 properties named `["property"]`.
+/// The gradients samples are named `["sample", "atom"]`, and the gradients
+/// have an additional `["direction"]` component with 3 entries.
+pub fn tensorblock(n_components: usize, gradients: Vec<String>) -> impl Strategy<Value = TensorBlock> {
+    let samples = labels_with_names(vec!["structure".into(), "center".into()], 0..=6);
+    let properties = labels_with_names(vec!["property".into()], 1..=4);
+    let components = vec(1..=3_usize, n_components);
+
+    return (samples, components, properties).prop_flat_map(move |(samples, components, properties)| {
+        let components = components.iter()
+            .enumerate()
+            .map(|(i, &size)| component(&format!("component_{}", i), size))
+            .collect::<Vec<_>>();
+
+        let mut shape = vec![samples.count()];
+        shape.extend(components.iter().map(|c| c.count()));
+        shape.push(properties.count());
+        let values = vec(-1.0..1.0_f64, shape.iter().product::<usize>());
+
+        let gradients = gradients.iter()
+            .map(|parameter| gradient(parameter.clone(), samples.count(), &shape))
+            .collect::<Vec<_>>();
+
+        (Just(samples), Just(components), Just(properties), values, gradients)
+    }).prop_map(|(samples, components, properties, values, gradients)| {
+        let mut shape = vec![samples.count()];
+        shape.extend(components.iter().map(|c| c.count()));
+        shape.push(properties.count());
+
+        let values = ndarray::ArrayD::from_shape_vec(shape, values).expect("invalid shape");
+        let mut block = TensorBlock::new(values, samples, &components, properties)
+            .expect("generated an invalid block");
+
+        let mut gradient_components = vec![component("direction", 3)];
+        gradient_components.extend(components);
+        for (parameter, samples, data) in gradients {
+            block.add_gradient(&parameter, data, samples, &gradient_components)
+                .expect("generated an invalid gradient");
+        }
+
+        block
+    });
+}
+
+/// Create a strategy generating `TensorMap` with between 1 and `max_blocks`
+/// blocks. All the blocks in a given map have the same number of components
+/// (between 0 and 2), and either all of them or none of them have gradients
+/// with respect to `"positions"`. See [`tensorblock`] for the other metadata
+/// of the blocks.
+///
+/// # Panics
+///
+/// If `max_blocks` is zero or larger than 10
+pub fn tensormap(max_blocks: usize) -> impl Strategy<Value = TensorMap> {
+    assert!(max_blocks > 0, "max_blocks must be at least 1");
+    assert!(max_blocks <= 10, "max_blocks can be at most 10");
+
+    return (0..=2_usize, any::<bool>()).prop_flat_map(move |(n_components, with_gradients)| {
+        let gradients = if with_gradients {
+            vec!["positions".to_string()]
+        } else {
+            vec![]
+        };
+
+        labels_with_names(vec!["key".into()], 1..=max_blocks).prop_flat_map(move |keys| {
+            let blocks = (0..keys.count())
+                .map(|_| tensorblock(n_components, gradients.clone()).boxed())
+                .collect::<Vec<_>>();
+            (Just(keys), blocks)
+        })
+    }).prop_map(|(keys, blocks)| {
+        TensorMap::new(keys, blocks).expect("generated an invalid tensor map")
+    });
+}
+
+/// Labels for a component with the given `name` and `size` entries
+fn component(name: &str, size: usize) -> Labels {
+    let mut builder = LabelsBuilder::new(vec![name]);
+    for i in 0..size {
+        builder.add(&[i]);
+    }
+    return builder.finish();
+}
+
+/// Strategy generating the parameter, samples and data of a gradient for a
+/// block with `n_samples` samples and the given values `shape`
+fn gradient(parameter: String, n_samples: usize, shape: &[usize]) -> BoxedStrategy<(String, Labels, ndarray::ArrayD<f64>)> {
+    let names = vec!["sample".to_string(), "atom".to_string()];
+    if n_samples == 0 {
+        let mut shape = shape.to_vec();
+        shape.insert(1, 3);
+        let data = ndarray::ArrayD::from_elem(shape, 0.0);
+        let samples = Labels::empty(names.iter().map(|name| &**name).collect());
+        return Just((parameter, samples, data)).boxed();
+    }
+
+    // the gradient samples must refer to existing samples in the values
+    let entries = hash_set((0..n_samples, 0..3_usize), 0..=(2 * n_samples));
+
+    let shape = shape[1..].to_vec();
+    return entries.prop_flat_map(move |entries| {
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        entries.sort_unstable();
+
+        let mut builder = LabelsBuilder::new(names.iter().map(|name| &**name).collect());
+        for (sample, atom) in entries {
+            builder.add(&[sample, atom]);
+        }
+        let samples = builder.finish();
+
+        let mut shape = shape.clone();
+        shape.insert(0, 3);
+        shape.insert(0, samples.count());
+        let size = shape.iter().product::<usize>();
+
+        (Just(parameter.clone()), Just(samples), Just(shape), vec(-1.0..1.0_f64, size))
+    }).prop_map(|(parameter, samples, shape, data)| {
+        let data = ndarray::ArrayD::from_shape_vec(shape, data).expect("invalid shape");
+        (parameter, samples, data)
+    }).boxed();
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn generated_labels(labels in labels(3, 8)) {
+            prop_assert!(labels.size() >= 1 && labels.size() <= 3);
+            prop_assert!(labels.count() <= 8);
+        }
+
+        #[test]
+        fn generated_tensors(tensor in tensormap(4)) {
+            let mut buffer = Vec::new();
+            crate::io::save_buffer(&tensor, &mut buffer).unwrap();
+            let loaded = crate::io::load_buffer(&buffer).unwrap();
+            prop_assert_eq!(loaded.keys(), tensor.keys());
+
+            for (block, loaded) in tensor.blocks().iter().zip(loaded.blocks()) {
+                let values = block.values();
+                let loaded_values = loaded.values();
+                prop_assert_eq!(values.data.as_array(), loaded_values.data.as_array());
+                prop_assert_eq!(block.gradient_list(), loaded.gradient_list());
+            }
+        }
+    }
+}
@@ -22,3 +22,89 @@ fn load_file() {
     assert_eq!(gradient.components[1].names(), ["spherical_harmonics_m"]);
     assert_eq!(gradient.properties.names(), ["n"]);
 }
+
+#[test]
+fn buffer_roundtrip() {
+    let tensor = equistore::io::load("../equistore-core/tests/data.npz").unwrap();
+
+    let mut buffer = Vec::new();
+    equistore::io::save_buffer(&tensor, &mut buffer).unwrap();
+
+    let loaded = equistore::io::load_buffer(&buffer).unwrap();
+    assert_eq!(loaded.keys(), tensor.keys());
+
+    let block = loaded.block_by_id(13);
+    assert_eq!(block.values().data.as_array(), tensor.block_by_id(13).values().data.as_array());
+}
+
+#[test]
+fn load_read_at() {
+    let buffer = std::fs::read("../equistore-core/tests/data.npz").unwrap();
+    let tensor = equistore::io::load_buffer(&buffer).unwrap();
+
+    let loaded = equistore::io::load_read_at(&*buffer, None).unwrap();
+    assert_eq!(loaded.keys(), tensor.keys());
+
+    let loaded = equistore::io::load_read_at(&*buffer, Some(&[13, 2][..])).unwrap();
+    assert_eq!(loaded.keys().count(), 2);
+    assert_eq!(loaded.keys()[0], tensor.keys()[13]);
+    assert_eq!(loaded.keys()[1], tensor.keys()[2]);
+
+    let block = loaded.block_by_id(0);
+    assert_eq!(block.values().data.as_array(), tensor.block_by_id(13).values().data.as_array());
+
+    let error = equistore::io::load_read_at(&*buffer, Some(&[27][..])).unwrap_err();
+    assert_eq!(error.message, "invalid parameter: can not load block 27, the serialized tensor map only contains 27 blocks");
+}
+
+#[test]
+fn load_with_progress() {
+    let mut reported = Vec::new();
+    let tensor = equistore::io::load_with_progress(
+        "../equistore-core/tests/data.npz",
+        equistore::io::LoadLimits::default(),
+        |done, total| reported.push((done, total)),
+    ).unwrap();
+
+    let count = tensor.keys().count();
+    assert_eq!(reported, (1..=count).map(|done| (done, count)).collect::<Vec<_>>());
+}
+
+#[test]
+fn cancel_load() {
+    let token = equistore::CancellationToken::new();
+    let mut loaded = 0;
+    let result = equistore::io::load_with_progress(
+        "../equistore-core/tests/data.npz",
+        equistore::io::LoadLimits::default(),
+        token.with_progress(|done, _| {
+            loaded = done;
+            if done == 3 {
+                token.cancel();
+            }
+        }),
+    );
+
+    let error = result.unwrap_err();
+    assert_eq!(error.message, "the operation was cancelled");
+    assert_eq!(loaded, 3);
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn async_roundtrip() {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let tensor = equistore::io::load("../equistore-core/tests/data.npz").unwrap();
+
+    let buffer = runtime.block_on(async {
+        let mut buffer = Vec::new();
+        equistore::io::save_async(&mut buffer, &tensor).await.unwrap();
+        buffer
+    });
+
+    let loaded = runtime.block_on(equistore::io::load_async(&*buffer)).unwrap();
+    assert_eq!(loaded.keys(), tensor.keys());
+
+    let block = loaded.block_by_id(13);
+    assert_eq!(block.values().data.as_array(), tensor.block_by_id(13).values().data.as_array());
+}
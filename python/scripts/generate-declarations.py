@@ -107,6 +107,8 @@ def c_type_name(name):
         return "c_uintptr_t"
     elif name == "void":
         return "None"
+    elif name == "uint8_t":
+        return "ctypes.c_uint8"
     elif name == "int32_t":
         return "ctypes.c_int32"
     elif name == "uint32_t":
@@ -2,4 +2,5 @@ typedef int uint64_t;
 typedef int int64_t;
 typedef int int32_t;
 typedef int uint32_t;
+typedef int uint8_t;
 typedef int uintptr_t;
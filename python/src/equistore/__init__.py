@@ -16,12 +16,19 @@ The API also includes more advanced functionalities like
 """
 
 from .version import __version__  # noqa
-from .block import TensorBlock  # noqa
+from .block import TensorBlock, register_gradient_parameter  # noqa
 from .labels import Labels  # noqa
 from .status import EquistoreError  # noqa
 from .tensor import TensorMap  # noqa
 
-from .io import load, save  # noqa
+from .io import (  # noqa
+    load,
+    load_buffer,
+    load_sharded,
+    save,
+    save_buffer,
+    save_sharded,
+)
 from .operations import *  # noqa
 
 
@@ -24,18 +24,25 @@ EQS_SUCCESS = 0
 EQS_INVALID_PARAMETER_ERROR = 1
 EQS_IO_ERROR = 2
 EQS_SERIALIZATION_ERROR = 3
+EQS_OUT_OF_MEMORY_ERROR = 4
 EQS_BUFFER_SIZE_ERROR = 254
 EQS_INTERNAL_ERROR = 255
 
 
 eqs_status_t = ctypes.c_int32
 eqs_data_origin_t = ctypes.c_uint64
+eqs_realloc_buffer_t = CFUNCTYPE(POINTER(ctypes.c_uint8), ctypes.c_void_p, POINTER(ctypes.c_uint8), c_uintptr_t)
+eqs_warning_callback_t = CFUNCTYPE(None, ctypes.c_void_p, ctypes.c_char_p)
 
 
 class eqs_block_t(ctypes.Structure):
     pass
 
 
+class eqs_lazy_tensormap_t(ctypes.Structure):
+    pass
+
+
 class eqs_tensormap_t(ctypes.Structure):
     pass
 
@@ -75,6 +82,37 @@ eqs_array_t._fields_ = [
     ("copy", CFUNCTYPE(eqs_status_t, ctypes.c_void_p, POINTER(eqs_array_t))),
     ("destroy", CFUNCTYPE(None, ctypes.c_void_p)),
     ("move_samples_from", CFUNCTYPE(eqs_status_t, ctypes.c_void_p, ctypes.c_void_p, POINTER(eqs_sample_mapping_t), c_uintptr_t, c_uintptr_t, c_uintptr_t)),
+    ("strides", CFUNCTYPE(eqs_status_t, ctypes.c_void_p, POINTER(POINTER(c_uintptr_t)), POINTER(c_uintptr_t))),
+]
+
+
+class eqs_read_at_t(ctypes.Structure):
+    pass
+
+eqs_read_at_t._fields_ = [
+    ("user_data", ctypes.c_void_p),
+    ("size", ctypes.c_uint64),
+    ("read_at", CFUNCTYPE(eqs_status_t, ctypes.c_void_p, ctypes.c_uint64, POINTER(ctypes.c_uint8), c_uintptr_t, POINTER(c_uintptr_t))),
+]
+
+
+class eqs_memory_usage_t(ctypes.Structure):
+    pass
+
+eqs_memory_usage_t._fields_ = [
+    ("data", c_uintptr_t),
+    ("labels_values", c_uintptr_t),
+    ("labels_positions", c_uintptr_t),
+    ("gradients", c_uintptr_t),
+]
+
+
+class eqs_save_options_t(ctypes.Structure):
+    pass
+
+eqs_save_options_t._fields_ = [
+    ("labels_dictionary", ctypes.c_bool),
+    ("mantissa_bits", ctypes.c_uint32),
 ]
 
 
@@ -88,10 +126,20 @@ def setup_functions(lib):
     ]
     lib.eqs_disable_panic_printing.restype = None
 
+    lib.eqs_set_warning_callback.argtypes = [
+        eqs_warning_callback_t,
+        ctypes.c_void_p,
+    ]
+    lib.eqs_set_warning_callback.restype = _check_status
+
     lib.eqs_version.argtypes = [
     ]
     lib.eqs_version.restype = ctypes.c_char_p
 
+    lib.eqs_abi_version.argtypes = [
+    ]
+    lib.eqs_abi_version.restype = ctypes.c_uint32
+
     lib.eqs_last_error.argtypes = [
     ]
     lib.eqs_last_error.restype = ctypes.c_char_p
@@ -104,6 +152,22 @@ def setup_functions(lib):
     ]
     lib.eqs_labels_position.restype = _check_status
 
+    lib.eqs_labels_structured_dtype.argtypes = [
+        eqs_labels_t,
+        ctypes.c_char_p,
+        c_uintptr_t,
+        POINTER(POINTER(ctypes.c_int32)),
+    ]
+    lib.eqs_labels_structured_dtype.restype = _check_status
+
+    lib.eqs_labels_positions.argtypes = [
+        eqs_labels_t,
+        POINTER(ctypes.c_int32),
+        c_uintptr_t,
+        POINTER(ctypes.c_int64),
+    ]
+    lib.eqs_labels_positions.restype = _check_status
+
     lib.eqs_labels_create.argtypes = [
         POINTER(eqs_labels_t),
     ]
@@ -177,6 +241,13 @@ def setup_functions(lib):
     ]
     lib.eqs_block_add_gradient.restype = _check_status
 
+    lib.eqs_register_gradient_parameter.argtypes = [
+        ctypes.c_char_p,
+        POINTER(eqs_labels_t),
+        c_uintptr_t,
+    ]
+    lib.eqs_register_gradient_parameter.restype = _check_status
+
     lib.eqs_block_gradients_list.argtypes = [
         POINTER(eqs_block_t),
         POINTER(POINTER(ctypes.c_char_p)),
@@ -184,6 +255,56 @@ def setup_functions(lib):
     ]
     lib.eqs_block_gradients_list.restype = _check_status
 
+    lib.eqs_block_transpose.argtypes = [
+        POINTER(eqs_block_t),
+    ]
+    lib.eqs_block_transpose.restype = _check_status
+
+    lib.eqs_block_fill.argtypes = [
+        POINTER(eqs_block_t),
+        ctypes.c_double,
+    ]
+    lib.eqs_block_fill.restype = _check_status
+
+    lib.eqs_block_copy_from.argtypes = [
+        POINTER(eqs_block_t),
+        POINTER(eqs_block_t),
+    ]
+    lib.eqs_block_copy_from.restype = _check_status
+
+    lib.eqs_block_assign_rows.argtypes = [
+        POINTER(eqs_block_t),
+        POINTER(c_uintptr_t),
+        c_uintptr_t,
+        POINTER(ctypes.c_double),
+        c_uintptr_t,
+    ]
+    lib.eqs_block_assign_rows.restype = _check_status
+
+    lib.eqs_block_unit.argtypes = [
+        POINTER(eqs_block_t),
+        POINTER(ctypes.c_char_p),
+    ]
+    lib.eqs_block_unit.restype = _check_status
+
+    lib.eqs_block_set_unit.argtypes = [
+        POINTER(eqs_block_t),
+        ctypes.c_char_p,
+    ]
+    lib.eqs_block_set_unit.restype = _check_status
+
+    lib.eqs_block_quantity.argtypes = [
+        POINTER(eqs_block_t),
+        POINTER(ctypes.c_char_p),
+    ]
+    lib.eqs_block_quantity.restype = _check_status
+
+    lib.eqs_block_set_quantity.argtypes = [
+        POINTER(eqs_block_t),
+        ctypes.c_char_p,
+    ]
+    lib.eqs_block_set_quantity.restype = _check_status
+
     lib.eqs_tensormap.argtypes = [
         eqs_labels_t,
         POINTER(POINTER(eqs_block_t)),
@@ -201,6 +322,13 @@ def setup_functions(lib):
     ]
     lib.eqs_tensormap_copy.restype = POINTER(eqs_tensormap_t)
 
+    lib.eqs_tensormap_with_values.argtypes = [
+        POINTER(eqs_tensormap_t),
+        POINTER(eqs_array_t),
+        c_uintptr_t,
+    ]
+    lib.eqs_tensormap_with_values.restype = POINTER(eqs_tensormap_t)
+
     lib.eqs_tensormap_keys.argtypes = [
         POINTER(eqs_tensormap_t),
         POINTER(eqs_labels_t),
@@ -222,6 +350,26 @@ def setup_functions(lib):
     ]
     lib.eqs_tensormap_blocks_matching.restype = _check_status
 
+    lib.eqs_tensormap_compress_blocks.argtypes = [
+        POINTER(eqs_tensormap_t),
+        eqs_labels_t,
+    ]
+    lib.eqs_tensormap_compress_blocks.restype = _check_status
+
+    lib.eqs_tensormap_decompress_blocks.argtypes = [
+        POINTER(eqs_tensormap_t),
+    ]
+    lib.eqs_tensormap_decompress_blocks.restype = _check_status
+
+    lib.eqs_tensormap_view.argtypes = [
+        POINTER(eqs_tensormap_t),
+        eqs_labels_t,
+        POINTER(eqs_labels_t),
+        POINTER(POINTER(eqs_block_t)),
+        POINTER(c_uintptr_t),
+    ]
+    lib.eqs_tensormap_view.restype = _check_status
+
     lib.eqs_tensormap_keys_to_properties.argtypes = [
         POINTER(eqs_tensormap_t),
         eqs_labels_t,
@@ -236,6 +384,16 @@ def setup_functions(lib):
     ]
     lib.eqs_tensormap_components_to_properties.restype = POINTER(eqs_tensormap_t)
 
+    lib.eqs_tensormap_sort_keys.argtypes = [
+        POINTER(eqs_tensormap_t),
+        POINTER(ctypes.c_char_p),
+        c_uintptr_t,
+        ctypes.c_bool,
+        POINTER(c_uintptr_t),
+        c_uintptr_t,
+    ]
+    lib.eqs_tensormap_sort_keys.restype = POINTER(eqs_tensormap_t)
+
     lib.eqs_tensormap_keys_to_samples.argtypes = [
         POINTER(eqs_tensormap_t),
         eqs_labels_t,
@@ -243,6 +401,100 @@ def setup_functions(lib):
     ]
     lib.eqs_tensormap_keys_to_samples.restype = POINTER(eqs_tensormap_t)
 
+    lib.eqs_tensormap_drop_blocks.argtypes = [
+        POINTER(eqs_tensormap_t),
+        eqs_labels_t,
+    ]
+    lib.eqs_tensormap_drop_blocks.restype = POINTER(eqs_tensormap_t)
+
+    lib.eqs_tensormap_canonicalize.argtypes = [
+        POINTER(eqs_tensormap_t),
+    ]
+    lib.eqs_tensormap_canonicalize.restype = POINTER(eqs_tensormap_t)
+
+    lib.eqs_tensormap_fingerprint.argtypes = [
+        POINTER(eqs_tensormap_t),
+        ctypes.c_bool,
+        POINTER(ctypes.c_uint64),
+    ]
+    lib.eqs_tensormap_fingerprint.restype = _check_status
+
+    lib.eqs_tensormap_diff_json.argtypes = [
+        POINTER(eqs_tensormap_t),
+        POINTER(eqs_tensormap_t),
+        ctypes.c_bool,
+        ctypes.c_double,
+        ctypes.c_char_p,
+        c_uintptr_t,
+    ]
+    lib.eqs_tensormap_diff_json.restype = _check_status
+
+    lib.eqs_tensormap_align_properties.argtypes = [
+        POINTER(POINTER(eqs_tensormap_t)),
+        c_uintptr_t,
+        POINTER(POINTER(eqs_tensormap_t)),
+    ]
+    lib.eqs_tensormap_align_properties.restype = _check_status
+
+    lib.eqs_tensormap_intersect_samples.argtypes = [
+        POINTER(eqs_tensormap_t),
+        POINTER(eqs_tensormap_t),
+        POINTER(POINTER(eqs_tensormap_t)),
+        POINTER(POINTER(eqs_tensormap_t)),
+        POINTER(eqs_labels_t),
+        c_uintptr_t,
+    ]
+    lib.eqs_tensormap_intersect_samples.restype = _check_status
+
+    lib.eqs_tensormap_unify_gradients.argtypes = [
+        POINTER(POINTER(eqs_tensormap_t)),
+        c_uintptr_t,
+        ctypes.c_char_p,
+        POINTER(POINTER(eqs_tensormap_t)),
+    ]
+    lib.eqs_tensormap_unify_gradients.restype = _check_status
+
+    lib.eqs_tensormap_product.argtypes = [
+        POINTER(eqs_tensormap_t),
+        POINTER(eqs_tensormap_t),
+    ]
+    lib.eqs_tensormap_product.restype = POINTER(eqs_tensormap_t)
+
+    lib.eqs_tensormap_memory_usage.argtypes = [
+        POINTER(eqs_tensormap_t),
+        POINTER(eqs_memory_usage_t),
+        POINTER(eqs_memory_usage_t),
+        c_uintptr_t,
+        POINTER(eqs_memory_usage_t),
+    ]
+    lib.eqs_tensormap_memory_usage.restype = _check_status
+
+    lib.eqs_tensormap_compact_labels.argtypes = [
+        POINTER(eqs_tensormap_t),
+    ]
+    lib.eqs_tensormap_compact_labels.restype = _check_status
+
+    lib.eqs_tensormap_metadata.argtypes = [
+        POINTER(eqs_tensormap_t),
+        ctypes.c_char_p,
+        POINTER(ctypes.c_char_p),
+    ]
+    lib.eqs_tensormap_metadata.restype = _check_status
+
+    lib.eqs_tensormap_set_metadata.argtypes = [
+        POINTER(eqs_tensormap_t),
+        ctypes.c_char_p,
+        ctypes.c_char_p,
+    ]
+    lib.eqs_tensormap_set_metadata.restype = _check_status
+
+    lib.eqs_tensormap_metadata_keys.argtypes = [
+        POINTER(eqs_tensormap_t),
+        POINTER(POINTER(ctypes.c_char_p)),
+        POINTER(c_uintptr_t),
+    ]
+    lib.eqs_tensormap_metadata_keys.restype = _check_status
+
     lib.eqs_tensormap_load.argtypes = [
         ctypes.c_char_p,
         eqs_create_array_callback_t,
@@ -254,3 +506,86 @@ def setup_functions(lib):
         POINTER(eqs_tensormap_t),
     ]
     lib.eqs_tensormap_save.restype = _check_status
+
+    lib.eqs_tensormap_load_buffer.argtypes = [
+        POINTER(ctypes.c_uint8),
+        c_uintptr_t,
+        eqs_create_array_callback_t,
+    ]
+    lib.eqs_tensormap_load_buffer.restype = POINTER(eqs_tensormap_t)
+
+    lib.eqs_tensormap_save_buffer.argtypes = [
+        POINTER(POINTER(ctypes.c_uint8)),
+        POINTER(c_uintptr_t),
+        ctypes.c_void_p,
+        eqs_realloc_buffer_t,
+        POINTER(eqs_tensormap_t),
+    ]
+    lib.eqs_tensormap_save_buffer.restype = _check_status
+
+    lib.eqs_tensormap_load_read_at.argtypes = [
+        eqs_read_at_t,
+        POINTER(c_uintptr_t),
+        c_uintptr_t,
+        eqs_create_array_callback_t,
+    ]
+    lib.eqs_tensormap_load_read_at.restype = POINTER(eqs_tensormap_t)
+
+    lib.eqs_lazy_tensormap.argtypes = [
+        POINTER(eqs_tensormap_t),
+    ]
+    lib.eqs_lazy_tensormap.restype = POINTER(eqs_lazy_tensormap_t)
+
+    lib.eqs_lazy_tensormap_free.argtypes = [
+        POINTER(eqs_lazy_tensormap_t),
+    ]
+    lib.eqs_lazy_tensormap_free.restype = _check_status
+
+    lib.eqs_lazy_tensormap_missing_gradients.argtypes = [
+        POINTER(eqs_lazy_tensormap_t),
+        ctypes.c_char_p,
+    ]
+    lib.eqs_lazy_tensormap_missing_gradients.restype = _check_status
+
+    lib.eqs_lazy_tensormap_broadcast_keys.argtypes = [
+        POINTER(eqs_lazy_tensormap_t),
+        ctypes.c_bool,
+    ]
+    lib.eqs_lazy_tensormap_broadcast_keys.restype = _check_status
+
+    lib.eqs_lazy_tensormap_unary.argtypes = [
+        POINTER(eqs_lazy_tensormap_t),
+        ctypes.c_char_p,
+        ctypes.c_double,
+    ]
+    lib.eqs_lazy_tensormap_unary.restype = _check_status
+
+    lib.eqs_lazy_tensormap_add.argtypes = [
+        POINTER(eqs_lazy_tensormap_t),
+        POINTER(eqs_tensormap_t),
+    ]
+    lib.eqs_lazy_tensormap_add.restype = _check_status
+
+    lib.eqs_lazy_tensormap_multiply.argtypes = [
+        POINTER(eqs_lazy_tensormap_t),
+        POINTER(eqs_tensormap_t),
+    ]
+    lib.eqs_lazy_tensormap_multiply.restype = _check_status
+
+    lib.eqs_lazy_tensormap_compute.argtypes = [
+        POINTER(eqs_lazy_tensormap_t),
+    ]
+    lib.eqs_lazy_tensormap_compute.restype = POINTER(eqs_tensormap_t)
+
+    lib.eqs_lazy_tensormap_compute_block.argtypes = [
+        POINTER(eqs_lazy_tensormap_t),
+        c_uintptr_t,
+    ]
+    lib.eqs_lazy_tensormap_compute_block.restype = POINTER(eqs_block_t)
+
+    lib.eqs_tensormap_save_with_options.argtypes = [
+        ctypes.c_char_p,
+        POINTER(eqs_tensormap_t),
+        eqs_save_options_t,
+    ]
+    lib.eqs_tensormap_save_with_options.restype = _check_status
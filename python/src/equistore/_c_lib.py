@@ -1,10 +1,11 @@
 import os
 import sys
+import warnings
 from ctypes import cdll
 
 from pkg_resources import parse_version
 
-from ._c_api import setup_functions
+from ._c_api import eqs_warning_callback_t, setup_functions
 from .data.extract import ExternalCpuArray, register_external_data_wrapper
 from .version import __version__
 
@@ -12,6 +13,14 @@ from .version import __version__
 _HERE = os.path.realpath(os.path.dirname(__file__))
 
 
+def _forward_warning(_user_data, message):
+    warnings.warn(message.decode("utf8"), stacklevel=2)
+
+
+# keep a reference to the callback alive for as long as the library is loaded
+_WARNING_CALLBACK = eqs_warning_callback_t(_forward_warning)
+
+
 def _compatible_versions(actual, minimal):
     actual = parse_version(actual)
     minimal = parse_version(minimal)
@@ -41,6 +50,9 @@ class LibraryFinder(object):
             # the error will be transformed to a Python exception anyway
             self._cached_dll.eqs_disable_panic_printing()
 
+            # forward non-fatal warnings to Python's warnings module
+            self._cached_dll.eqs_set_warning_callback(_WARNING_CALLBACK, None)
+
             version = self._cached_dll.eqs_version().decode("utf8")
             if not _compatible_versions(version, __version__):
                 self._cached_dll = None
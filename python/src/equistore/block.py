@@ -1,6 +1,8 @@
 import copy
 import ctypes
-from typing import Generator, List, Tuple
+from typing import Generator, List, Optional, Tuple
+
+import numpy as np
 
 from ._c_api import c_uintptr_t, eqs_array_t, eqs_labels_t
 from ._c_lib import _get_library
@@ -200,11 +202,123 @@ class TensorBlock:
         property_axis = len(self.values.shape) - 1
         return self._labels(property_axis)
 
+    @property
+    def dtype(self):
+        """
+        Type of the values in this block, as a numpy ``dtype`` or a torch
+        ``dtype`` depending on the array type.
+        """
+        return self.values.dtype
+
     def _labels(self, axis) -> Labels:
         result = eqs_labels_t()
         self._lib.eqs_block_labels(self._ptr, "values".encode("utf8"), axis, result)
         return Labels._from_eqs_labels_t(result)
 
+    @property
+    def unit(self) -> Optional[str]:
+        """
+        Physical unit of the values in this block (e.g. ``"eV"``), or ``None``
+        if the unit is not set.
+
+        The unit is preserved when copying and serializing the block, and
+        arithmetic operations such as :py:func:`equistore.add` check that the
+        units of their inputs are consistent.
+        """
+        unit = ctypes.c_char_p()
+        self._lib.eqs_block_unit(self._ptr, unit)
+        if unit.value is None:
+            return None
+        return unit.value.decode("utf8")
+
+    @unit.setter
+    def unit(self, unit: Optional[str]):
+        self._check_not_in_tensor_map("unit")
+        if unit is not None:
+            unit = unit.encode("utf8")
+        self._lib.eqs_block_set_unit(self._ptr, unit)
+
+    @property
+    def quantity(self) -> Optional[str]:
+        """
+        Physical quantity represented by the values in this block (e.g.
+        ``"energy"``), or ``None`` if the quantity is not set.
+
+        The quantity is preserved when copying and serializing the block, and
+        arithmetic operations such as :py:func:`equistore.add` check that the
+        quantities of their inputs are consistent.
+        """
+        quantity = ctypes.c_char_p()
+        self._lib.eqs_block_quantity(self._ptr, quantity)
+        if quantity.value is None:
+            return None
+        return quantity.value.decode("utf8")
+
+    @quantity.setter
+    def quantity(self, quantity: Optional[str]):
+        self._check_not_in_tensor_map("quantity")
+        if quantity is not None:
+            quantity = quantity.encode("utf8")
+        self._lib.eqs_block_set_quantity(self._ptr, quantity)
+
+    def transpose(self):
+        """
+        Swap the samples and properties of this block in-place, transposing the
+        values accordingly. The components stay in the same order.
+
+        Blocks with gradients can not be transposed, since the samples of the
+        gradients refer to the samples of the values.
+        """
+        if self._parent is not None:
+            raise ValueError(
+                "can not transpose this block since it is a view inside a TensorMap"
+            )
+        self._lib.eqs_block_transpose(self._ptr)
+
+    def _check_not_in_tensor_map(self, what: str):
+        if self._parent is not None:
+            raise ValueError(
+                f"can not set the {what} of this block since it is a view inside "
+                "a TensorMap"
+            )
+
+    def fill(self, value: float):
+        """
+        Set all the values in this block to ``value``, in-place. The gradients
+        are left unchanged.
+        """
+        self._lib.eqs_block_fill(self._ptr, value)
+
+    def copy_from(self, other: "TensorBlock"):
+        """
+        Copy the values and gradients data from ``other`` into this block,
+        in-place.
+
+        Both blocks must have the same samples, components and properties
+        labels, and the same set of gradients with the same labels.
+        """
+        self._lib.eqs_block_copy_from(self._ptr, other._ptr)
+
+    def assign_rows(self, samples, data):
+        """
+        Set the values of this block for the samples at the given positions
+        to ``data``, in-place. The gradients are left unchanged.
+
+        :param samples: positions of the samples to modify in this block
+        :param data: new values for the selected samples, with one row for each
+            entry in ``samples``, and the same number of values per row as in
+            this block
+        """
+        samples = np.ascontiguousarray(samples, dtype=np.uintp)
+        data = np.ascontiguousarray(data, dtype=np.float64)
+        self._lib.eqs_block_assign_rows(
+            self._ptr,
+            samples.ctypes.data_as(ctypes.POINTER(c_uintptr_t)),
+            samples.size,
+            data.ctypes.data_as(ctypes.POINTER(ctypes.c_double)),
+            data.size,
+        )
+
     def gradient(self, parameter: str) -> "Gradient":
         """
         Get the gradient of the block ``values``  with respect to the
@@ -453,6 +567,33 @@ class Gradient:
         return Labels._from_eqs_labels_t(result)
 
 
+def register_gradient_parameter(parameter: str, components: List[Labels]):
+    """
+    Register the convention for gradients with respect to ``parameter``. All
+    gradients with respect to this parameter added afterward with
+    :py:func:`TensorBlock.add_gradient` must start with the given
+    ``components``, followed by the components of the values.
+
+    For example, gradients with respect to ``"strain"`` would usually declare two
+    components with 3 entries each. Registering the same parameter multiple times
+    replaces the previous convention.
+
+    :param parameter: name of the gradient parameter
+    :param components: components that gradients with respect to this parameter
+        must have before the values components. Each component must have a single
+        dimension and at least one entry.
+    """
+    lib = _get_library()
+
+    components_array = ctypes.ARRAY(eqs_labels_t, len(components))()
+    for i, component in enumerate(components):
+        components_array[i] = component._as_eqs_labels_t()
+
+    lib.eqs_register_gradient_parameter(
+        parameter.encode("utf8"), components_array, len(components)
+    )
+
+
 def _get_raw_array(lib, block_ptr, name) -> eqs_array_t:
     data = eqs_array_t()
     lib.eqs_block_data(block_ptr, name.encode("utf8"), data)
@@ -94,6 +94,7 @@ class ArrayWrapper:
         eqs_array.move_samples_from = eqs_array.move_samples_from.__class__(
             _eqs_array_move_samples_from
         )
+        eqs_array.strides = eqs_array.strides.__class__(_eqs_array_strides)
 
         self._eqs_array = eqs_array
 
@@ -137,8 +138,8 @@ def _eqs_array_data(this, data):
         # freed immediately.
         array = array.numpy()
 
-    if not array.data.c_contiguous:
-        raise ValueError("can not get data pointer for non contiguous array")
+    if any(stride < 0 for stride in array.strides):
+        raise ValueError("can not get data pointer for array with negative strides")
 
     if not array.dtype == np.float64:
         raise ValueError(f"can not get data pointer for array type {array.dtype}")
@@ -154,6 +155,29 @@ def _eqs_array_shape(this, shape_ptr, shape_count):
     shape_count[0] = len(wrapper._shape)
 
 
+@catch_exceptions
+def _eqs_array_strides(this, strides_ptr, strides_count):
+    wrapper = _object_from_ptr(this)
+    array = wrapper.array
+
+    if _is_numpy_array(array):
+        for stride in array.strides:
+            if stride < 0 or stride % array.itemsize != 0:
+                raise ValueError(
+                    f"can not use array with strides {array.strides} in equistore"
+                )
+        strides = [stride // array.itemsize for stride in array.strides]
+    elif _is_torch_array(array):
+        strides = array.stride()
+    else:
+        raise TypeError(f"can not get the strides of array type {type(array)}")
+
+    # keep the strides alive in the wrapper until the next call
+    wrapper._strides = ctypes.ARRAY(c_uintptr_t, len(strides))(*strides)
+    strides_ptr[0] = wrapper._strides
+    strides_count[0] = len(strides)
+
+
 @catch_exceptions
 def _eqs_array_reshape(this, shape_ptr, shape_count):
     wrapper = _object_from_ptr(this)
@@ -200,7 +224,7 @@ def _eqs_array_copy(this, new_array):
     if _is_numpy_array(wrapper.array):
         array = wrapper.array.copy()
     elif _is_torch_array(wrapper.array):
-        array = wrapper.array.clone()
+        array = wrapper.array.clone(memory_format=torch.contiguous_format)
 
     new_wrapper = ArrayWrapper(array)
     new_array[0] = new_wrapper.into_eqs_array()
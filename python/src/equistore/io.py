@@ -1,10 +1,18 @@
 import ctypes
+import json
+import os
 import warnings
-from typing import Callable
+from typing import Callable, Optional, Union
 
 import numpy as np
 
-from ._c_api import c_uintptr_t, eqs_array_t, eqs_create_array_callback_t
+from ._c_api import (
+    c_uintptr_t,
+    eqs_array_t,
+    eqs_create_array_callback_t,
+    eqs_realloc_buffer_t,
+    eqs_save_options_t,
+)
 from ._c_lib import _get_library
 from .block import TensorBlock
 from .data.array import ArrayWrapper, _is_numpy_array, _is_torch_array
@@ -102,7 +110,13 @@ def load_custom_array(path: str, create_array: CreateArrayCallback) -> TensorMap
     return TensorMap._from_ptr(ptr)
 
 
-def save(path: str, tensor: TensorMap, use_numpy=False):
+def save(
+    path: str,
+    tensor: TensorMap,
+    use_numpy=False,
+    labels_dictionary=False,
+    mantissa_bits: Optional[int] = None,
+):
     """Save the given :py:class:`equistore.TensorMap` to a file at ``path``.
 
     :py:class:`equistore.TensorMap` are serialized using numpy's ``.npz``
@@ -116,7 +130,27 @@ def save(path: str, tensor: TensorMap, use_numpy=False):
         should be able to process more dtypes than the native implementation,
         which is limited to float64, but the native implementation is usually
         faster than going through numpy.
+    :param labels_dictionary: compress all the labels with zstd, using a
+        dictionary shared between all of them. This can significantly reduce the
+        size of files containing many small blocks, but the resulting files can
+        only be loaded by equistore, and not directly with numpy. This is not
+        supported with ``use_numpy=True``.
+    :param mantissa_bits: round the values and gradients data to keep only this
+        number of bits (between 1 and 52) in the mantissa of the 64-bit floating
+        point values, and store them compressed with zstd. The relative error
+        introduced by the rounding is at most ``2^-(mantissa_bits + 1)``. The
+        resulting files can only be loaded by equistore. This is not supported
+        with ``use_numpy=True``.
     """
+    if use_numpy and labels_dictionary:
+        raise ValueError("labels_dictionary=True is not supported with use_numpy=True")
+
+    if use_numpy and mantissa_bits is not None:
+        raise ValueError("mantissa_bits is not supported with use_numpy=True")
+
+    if mantissa_bits is not None and not 1 <= mantissa_bits <= 52:
+        raise ValueError(f"mantissa_bits must be between 1 and 52, got {mantissa_bits}")
+
     if not path.endswith(".npz"):
         path += ".npz"
         warnings.warn(
@@ -129,7 +163,235 @@ def save(path: str, tensor: TensorMap, use_numpy=False):
         np.savez(path, **all_entries)
     else:
         lib = _get_library()
-        lib.eqs_tensormap_save(path.encode("utf8"), tensor._ptr)
+        if labels_dictionary or mantissa_bits is not None:
+            options = eqs_save_options_t(
+                labels_dictionary=labels_dictionary,
+                mantissa_bits=0 if mantissa_bits is None else mantissa_bits,
+            )
+            lib.eqs_tensormap_save_with_options(
+                path.encode("utf8"), tensor._ptr, options
+            )
+        else:
+            lib.eqs_tensormap_save(path.encode("utf8"), tensor._ptr)
+
+
+def load_buffer(buffer: Union[bytes, bytearray, memoryview]) -> TensorMap:
+    """
+    Load a :py:class:`equistore.TensorMap` from an in-memory ``buffer``,
+    containing the same data as a file created by :py:func:`equistore.save`.
+
+    The data is loaded in numpy arrays.
+
+    :param buffer: buffer containing a serialized tensor map
+    """
+    buffer = bytes(buffer)
+
+    lib = _get_library()
+    ptr = lib.eqs_tensormap_load_buffer(
+        ctypes.cast(buffer, ctypes.POINTER(ctypes.c_uint8)),
+        len(buffer),
+        eqs_create_array_callback_t(create_numpy_array),
+    )
+
+    return TensorMap._from_ptr(ptr)
+
+
+# ctypes does not support returning pointers from Python callbacks, so we
+# declare the callback as returning `void*` and cast it to the right type
+_realloc_buffer_t = ctypes.CFUNCTYPE(
+    ctypes.c_void_p, ctypes.c_void_p, ctypes.c_void_p, c_uintptr_t
+)
+
+
+def save_buffer(tensor: TensorMap) -> bytes:
+    """
+    Save the given :py:class:`equistore.TensorMap` to an in-memory buffer,
+    using the same format as :py:func:`equistore.save`.
+
+    :param tensor: tensor to save
+    """
+    # keep the current allocation alive until the data is copied to `bytes`
+    allocation = [None]
+
+    def realloc(user_data, ptr, new_size):
+        try:
+            new_allocation = ctypes.create_string_buffer(new_size)
+        except MemoryError:
+            return None
+
+        # this callback is called multiple times as the buffer grows, and
+        # should keep the existing data like C's realloc
+        if allocation[0] is not None:
+            size = min(len(allocation[0]), new_size)
+            ctypes.memmove(new_allocation, allocation[0], size)
+
+        allocation[0] = new_allocation
+        return ctypes.addressof(new_allocation)
+
+    callback = _realloc_buffer_t(realloc)
+
+    buffer = ctypes.POINTER(ctypes.c_uint8)()
+    buffer_count = c_uintptr_t(0)
+
+    lib = _get_library()
+    lib.eqs_tensormap_save_buffer(
+        buffer,
+        buffer_count,
+        None,
+        ctypes.cast(callback, eqs_realloc_buffer_t),
+        tensor._ptr,
+    )
+
+    return ctypes.string_at(buffer, buffer_count.value)
+
+
+_SHARDED_MANIFEST = "manifest.json"
+_SHARDED_FORMAT = "equistore-sharded"
+_SHARDED_VERSION = 1
+
+
+def save_sharded(
+    directory: str, tensor: TensorMap, max_shard_bytes: int, use_numpy=False
+):
+    """Save the given :py:class:`equistore.TensorMap` to multiple files in
+    ``directory``, each containing some of the blocks.
+
+    This is useful for very large tensor maps, since some file systems and
+    transfer tools do not handle very large single files well. The blocks are
+    grouped in shards such that the data in each shard is at most
+    ``max_shard_bytes`` bytes; a block larger than this is stored alone in its
+    own shard. Each shard is a regular ``.npz`` file, as created by
+    :py:func:`equistore.save`, and a ``manifest.json`` file records which
+    blocks are stored in which shard, as well as the keys and metadata of the
+    full tensor map.
+
+    Use :py:func:`equistore.load_sharded` to load the data back.
+
+    :param directory: path to the directory where to save the data. It will be
+        created if it does not exist.
+    :param tensor: tensor to save
+    :param max_shard_bytes: maximal size in bytes of the data in each shard
+    :param use_numpy: should we use numpy or the native implementation to write
+        the shards? See :py:func:`equistore.save`.
+    """
+    if max_shard_bytes <= 0:
+        raise ValueError(
+            f"max_shard_bytes must be a positive integer, got {max_shard_bytes}"
+        )
+
+    os.makedirs(directory, exist_ok=True)
+
+    shards = []
+    current = []
+    current_bytes = 0
+    for block_i, (_, block) in enumerate(tensor):
+        nbytes = _block_nbytes(block)
+        if len(current) != 0 and current_bytes + nbytes > max_shard_bytes:
+            shards.append(current)
+            current = []
+            current_bytes = 0
+
+        current.append(block_i)
+        current_bytes += nbytes
+
+    if len(current) != 0:
+        shards.append(current)
+
+    keys = tensor.keys.asarray()
+    manifest = {
+        "format": _SHARDED_FORMAT,
+        "version": _SHARDED_VERSION,
+        "keys": {
+            "names": list(tensor.keys.names),
+            "values": keys.tolist(),
+        },
+        "metadata": tensor.metadata,
+        "shards": [],
+    }
+
+    for shard_i, blocks in enumerate(shards):
+        shard = TensorMap(
+            keys=Labels(tensor.keys.names, keys[blocks]),
+            blocks=[tensor.block(block_i).copy() for block_i in blocks],
+        )
+
+        path = f"shard-{shard_i}.npz"
+        save(os.path.join(directory, path), shard, use_numpy=use_numpy)
+        manifest["shards"].append({"path": path, "blocks": blocks})
+
+    with open(os.path.join(directory, _SHARDED_MANIFEST), "w") as fd:
+        json.dump(manifest, fd, indent=2)
+
+
+def load_sharded(directory: str, use_numpy=False) -> TensorMap:
+    """
+    Load a :py:class:`equistore.TensorMap` previously saved in multiple files
+    with :py:func:`equistore.save_sharded`.
+
+    :param directory: path to the directory containing the manifest and the
+        shards
+    :param use_numpy: should we use numpy or the native implementation to read
+        the shards? See :py:func:`equistore.load`.
+    """
+    with open(os.path.join(directory, _SHARDED_MANIFEST)) as fd:
+        manifest = json.load(fd)
+
+    if manifest.get("format") != _SHARDED_FORMAT:
+        raise ValueError(
+            f"'{directory}' does not contain a sharded equistore TensorMap"
+        )
+
+    if manifest.get("version") != _SHARDED_VERSION:
+        raise ValueError(
+            f"unsupported version for sharded TensorMap: {manifest.get('version')}"
+        )
+
+    names = manifest["keys"]["names"]
+    values = np.array(manifest["keys"]["values"], dtype=np.int32)
+    keys = Labels(names, values.reshape(-1, len(names)))
+
+    blocks = [None] * len(keys)
+    for shard in manifest["shards"]:
+        path = os.path.join(directory, shard["path"])
+        tensor = load(path, use_numpy=use_numpy)
+        if len(tensor.keys) != len(shard["blocks"]):
+            raise ValueError(
+                f"expected {len(shard['blocks'])} blocks in '{path}', "
+                f"got {len(tensor.keys)}"
+            )
+
+        for position, block_i in enumerate(shard["blocks"]):
+            blocks[block_i] = tensor.block(position).copy()
+
+    missing = [block_i for block_i, block in enumerate(blocks) if block is None]
+    if len(missing) != 0:
+        raise ValueError(f"the blocks {missing} are missing from all shards")
+
+    tensor = TensorMap(keys, blocks)
+    for key, value in manifest["metadata"].items():
+        tensor.set_metadata(key, value)
+
+    return tensor
+
+
+def _array_nbytes(array):
+    if _is_torch_array(array):
+        return array.element_size() * array.numel()
+    else:
+        return array.nbytes
+
+
+def _block_nbytes(block):
+    nbytes = _array_nbytes(block.values)
+    nbytes += block.samples.nbytes + block.properties.nbytes
+    nbytes += sum(component.nbytes for component in block.components)
+
+    for parameter in block.gradients_list():
+        gradient = block.gradient(parameter)
+        nbytes += _array_nbytes(gradient.data) + gradient.samples.nbytes
+        nbytes += sum(component.nbytes for component in gradient.components)
+
+    return nbytes
 
 
 def _array_to_numpy(array):
@@ -151,6 +413,10 @@ def _tensor_map_to_dict(tensor_map):
         for i, component in enumerate(block.components):
             result[f"{prefix}/components/{i}"] = component
         result[f"{prefix}/properties"] = block.properties
+        if block.unit is not None:
+            result[f"{prefix}/unit"] = np.array(block.unit)
+        if block.quantity is not None:
+            result[f"{prefix}/quantity"] = np.array(block.quantity)
 
         for parameter in block.gradients_list():
             gradient = block.gradient(parameter)
@@ -160,16 +426,50 @@ def _tensor_map_to_dict(tensor_map):
             for i, component in enumerate(gradient.components):
                 result[f"{prefix}/components/{i}"] = component
 
-    return result
+    metadata = tensor_map.metadata
+    if len(metadata) != 0:
+        result["metadata/keys"] = np.array(list(metadata.keys()))
+        result["metadata/values"] = np.array(list(metadata.values()))
+
+    # always write the data in little-endian byte order, to get the same files
+    # regardless of the platform (this is also what the native implementation
+    # does)
+    return {name: _little_endian(array) for name, array in result.items()}
+
+
+def _little_endian(array):
+    array = np.asarray(array)
+    return array.astype(array.dtype.newbyteorder("<"), copy=False)
+
+
+def _native_endian(array):
+    return array.astype(array.dtype.newbyteorder("="), copy=False)
 
 
 def _labels_from_npz(data):
+    # files created on a platform with a different endianness need to be
+    # converted before we can view the data as native 32-bit integers
+    data = _native_endian(data)
     names = data.dtype.names
     return Labels(names=names, values=data.view(dtype=np.int32).reshape(-1, len(names)))
 
 
 def _read_npz(path):
     dictionary = np.load(path)
+    if "labels-dictionary.zstd" in dictionary.files or any(
+        name.endswith(".npy.zst") for name in dictionary.files
+    ):
+        raise ValueError(
+            f"'{path}' contains zstd-compressed labels or data, which can not be "
+            "loaded with use_numpy=True"
+        )
+
+    if "capabilities" in dictionary.files:
+        capabilities = ", ".join(dictionary["capabilities"])
+        raise ValueError(
+            f"'{path}' requires capabilities which are not supported with "
+            f"use_numpy=True: {capabilities}"
+        )
 
     keys = _labels_from_npz(dictionary["keys"])
     blocks = []
@@ -177,7 +477,7 @@ def _read_npz(path):
     gradient_parameters = []
     for block_i in range(len(keys)):
         prefix = f"blocks/{block_i}/values"
-        data = dictionary[f"{prefix}/data"]
+        data = _native_endian(dictionary[f"{prefix}/data"])
 
         samples = _labels_from_npz(dictionary[f"{prefix}/samples"])
         components = []
@@ -187,6 +487,10 @@ def _read_npz(path):
         properties = _labels_from_npz(dictionary[f"{prefix}/properties"])
 
         block = TensorBlock(data, samples, components, properties)
+        if f"{prefix}/unit" in dictionary:
+            block.unit = dictionary[f"{prefix}/unit"].item()
+        if f"{prefix}/quantity" in dictionary:
+            block.quantity = dictionary[f"{prefix}/quantity"].item()
 
         if block_i == 0:
             prefix = f"blocks/{block_i}/gradients/"
@@ -196,7 +500,7 @@ def _read_npz(path):
 
         for parameter in gradient_parameters:
             prefix = f"blocks/{block_i}/gradients/{parameter}"
-            data = dictionary[f"{prefix}/data"]
+            data = _native_endian(dictionary[f"{prefix}/data"])
 
             samples = _labels_from_npz(dictionary[f"{prefix}/samples"])
             components = []
@@ -209,4 +513,11 @@ def _read_npz(path):
 
         blocks.append(block)
 
-    return TensorMap(keys, blocks)
+    tensor = TensorMap(keys, blocks)
+    if "metadata/keys" in dictionary:
+        metadata_keys = dictionary["metadata/keys"]
+        metadata_values = dictionary["metadata/values"]
+        for key, value in zip(metadata_keys, metadata_values):
+            tensor.set_metadata(str(key), str(value))
+
+    return tensor
@@ -1,3 +1,4 @@
+import ast
 import ctypes
 from collections import namedtuple
 from typing import List, Optional, Union
@@ -6,7 +7,7 @@ import numpy as np
 
 from ._c_api import eqs_labels_t
 from ._c_lib import _get_library
-from .utils import _ptr_to_const_ndarray
+from .utils import _call_with_growing_buffer, _ptr_to_const_ndarray
 
 
 class Labels(np.ndarray):
@@ -154,6 +155,15 @@ class Labels(np.ndarray):
         """
         return Labels(names=["_"], values=np.zeros(shape=(1, 1), dtype=np.int32))
 
+    @staticmethod
+    def scalar() -> "Labels":
+        """
+        Get labels without any dimension and a single entry. These can be used
+        as the properties of blocks storing a single scalar value per sample
+        (e.g. energies), instead of a dummy property dimension.
+        """
+        return Labels(names=[], values=np.zeros(shape=(1, 0), dtype=np.int32))
+
     @staticmethod
     def empty(names) -> "Labels":
         """Label with given names but no values.
@@ -204,13 +214,31 @@ class Labels(np.ndarray):
         for i in range(eqs_labels.size):
             names.append(eqs_labels.names[i].decode("utf8"))
 
-        if eqs_labels.count != 0:
-            shape = (eqs_labels.count, eqs_labels.size)
-            values = _ptr_to_const_ndarray(
-                ptr=eqs_labels.values, shape=shape, dtype=np.int32
-            )
-            values.flags.writeable = False
+        if eqs_labels.size == 0:
+            # labels without dimensions have no values to point to
+            values = np.zeros(shape=(eqs_labels.count, 0), dtype=np.int32)
             return Labels(names, values, _eqs_labels_t=eqs_labels)
+        elif eqs_labels.count != 0:
+            assert eqs_labels.internal_ptr_ is not None
+            lib = _get_library()
+
+            values_ptr = ctypes.POINTER(ctypes.c_int32)()
+            descr = _call_with_growing_buffer(
+                lambda buffer, bufflen: lib.eqs_labels_structured_dtype(
+                    eqs_labels, buffer, bufflen, values_ptr
+                )
+            )
+            dtype = np.dtype(ast.literal_eval(descr))
+
+            # view the values as an array of records without copying them
+            shape = (eqs_labels.count, eqs_labels.size)
+            values = _ptr_to_const_ndarray(ptr=values_ptr, shape=shape, dtype=np.int32)
+            values = values.view(dtype=dtype).reshape((eqs_labels.count,))
+
+            obj = values.view(Labels)
+            obj._lib = lib
+            obj._eqs_labels_t = eqs_labels
+            return obj
         else:
             return Labels(
                 names=names,
@@ -244,6 +272,39 @@ class Labels(np.ndarray):
         else:
             return None
 
+    def positions(self, labels) -> np.ndarray:
+        """
+        Get the positions of multiple entries in this set of labels at once.
+
+        ``labels`` should be a 2D array (or a :py:class:`Labels`) with one
+        row for each entry to lookup. The result is an array of integers
+        containing the position of each entry, or ``-1`` for entries which are
+        not present in this set of labels.
+
+        This is only available if the labels comes from a
+        :py:class:`TensorBlock` or a :py:class:`TensorMap`.
+        """
+        lib = _get_library()
+
+        if isinstance(labels, Labels):
+            labels = labels.asarray()
+        labels = np.ascontiguousarray(labels, dtype=np.int32)
+        if len(labels.shape) != 2 or labels.shape[1] != len(self.names):
+            raise ValueError(
+                f"expected a 2D array with {len(self.names)} columns in "
+                f"Labels.positions, got an array with shape {labels.shape}"
+            )
+
+        result = np.empty(labels.shape[0], dtype=np.int64)
+        lib.eqs_labels_positions(
+            self._eqs_labels_t,
+            labels.ctypes.data_as(ctypes.POINTER(ctypes.c_int32)),
+            labels.shape[0],
+            result.ctypes.data_as(ctypes.POINTER(ctypes.c_int64)),
+        )
+
+        return result
+
     def asarray(self):
         """Get a view of these ``Labels`` as a raw 2D array of integers"""
         return self.view(dtype=np.int32).reshape(self.shape[0], -1)
@@ -9,23 +9,48 @@ and automatically dispatch to the right function for a given TensorMap.
 """
 from .abs import abs  # noqa
 from .add import add  # noqa
+from .align_properties import align_properties  # noqa
 from .allclose import (  # noqa
     allclose,
     allclose_block,
     allclose_block_raise,
     allclose_raise,
 )
+from .baseline import fit_composition_baseline, remove_baseline  # noqa
+from .batch import Batcher  # noqa
+from .block_diagonal import block_diagonal  # noqa
+from .cholesky import cholesky, solve_triangular  # noqa
+from .diff import diff  # noqa
 from .divide import divide  # noqa
 from .dot import dot  # noqa
-from .drop_blocks import drop_blocks
+from .drop_blocks import drop_blocks, prune_empty  # noqa
 from .empty_like import empty_like, empty_like_block  # noqa
 from .equal import equal, equal_block, equal_block_raise, equal_raise  # noqa
 from .equal_metadata import equal_metadata  # noqa
-from .join import join  # noqa
+from .gram import covariance, gram  # noqa
+from .intersect_samples import intersect_samples  # noqa
+from .join import join, join_components, join_components_block  # noqa
+from .kernels import linear_kernel, polynomial_kernel  # noqa
+from .lazy import LazyTensorMap  # noqa
+from .long_table import from_long_table, to_long_table  # noqa
 from .lstsq import lstsq  # noqa
+from .masked import (  # noqa
+    apply_mask,
+    apply_mask_block,
+    masked_add,
+    masked_divide,
+    masked_mean_over_samples,
+    masked_mean_over_samples_block,
+    masked_multiply,
+    masked_subtract,
+    masked_sum_over_samples,
+    masked_sum_over_samples_block,
+)
 from .multiply import multiply  # noqa
 from .ones_like import ones_like, ones_like_block  # noqa
+from .online_stats import OnlineStats  # noqa
 from .random_like import random_uniform_like, random_uniform_like_block  # noqa
+from .pca import pca  # noqa
 from .pow import pow  # noqa
 from .reduce_over_samples import (  # noqa
     mean_over_samples,
@@ -38,23 +63,41 @@ from .reduce_over_samples import (  # noqa
     var_over_samples_block,
 )
 from .remove_gradients import remove_gradients  # noqa
+from .scale import scale_properties, scale_properties_block  # noqa
+from .selection import (  # noqa
+    select_cur,
+    select_cur_block,
+    select_fps,
+    select_fps_block,
+)
 from .slice import slice, slice_block  # noqa
 from .solve import solve  # noqa
-from .split import split, split_block  # nopa
+from .split import kfold_split, split, split_block, subsample  # noqa
 from .subtract import subtract  # noqa
+from .symmetrize import symmetrize, symmetrize_block  # noqa
+from .tensor_product import tensor_product  # noqa
 from .unique_metadata import unique_metadata, unique_metadata_block  # noqa
 from .zeros_like import zeros_like, zeros_like_block  # noqa
 
 __all__ = [
     "abs",
     "add",
+    "align_properties",
+    "apply_mask",
+    "apply_mask_block",
     "allclose",
     "allclose_raise",
     "allclose_block",
     "allclose_block_raise",
+    "Batcher",
+    "block_diagonal",
+    "cholesky",
+    "covariance",
+    "diff",
     "divide",
     "dot",
     "drop_blocks",
+    "prune_empty",
     "empty_like",
     "empty_like_block",
     "equal",
@@ -62,27 +105,60 @@ __all__ = [
     "equal_block",
     "equal_block_raise",
     "equal_metadata",
+    "fit_composition_baseline",
+    "from_long_table",
+    "gram",
+    "intersect_samples",
     "join",
+    "join_components",
+    "join_components_block",
+    "kfold_split",
+    "LazyTensorMap",
+    "linear_kernel",
     "lstsq",
+    "masked_add",
+    "masked_divide",
+    "masked_mean_over_samples",
+    "masked_mean_over_samples_block",
+    "masked_multiply",
+    "masked_subtract",
+    "masked_sum_over_samples",
+    "masked_sum_over_samples_block",
     "mean_over_samples",
     "mean_over_samples_block",
     "ones_like",
     "ones_like_block",
+    "OnlineStats",
     "random_uniform_like",
     "random_uniform_like_block",
     "multiply",
+    "pca",
+    "polynomial_kernel",
     "pow",
+    "remove_baseline",
     "remove_gradients",
+    "scale_properties",
+    "scale_properties_block",
+    "select_cur",
+    "select_cur_block",
+    "select_fps",
+    "select_fps_block",
     "slice",
     "slice_block",
     "std_over_samples",
     "std_over_samples_block",
     "solve",
+    "solve_triangular",
     "split",
     "split_block",
+    "subsample",
     "subtract",
     "sum_over_samples",
     "sum_over_samples_block",
+    "symmetrize",
+    "symmetrize_block",
+    "tensor_product",
+    "to_long_table",
     "unique_metadata",
     "unique_metadata_block",
     "var_over_samples",
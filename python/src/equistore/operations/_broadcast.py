@@ -0,0 +1,112 @@
+from typing import Optional, Tuple
+
+from ..block import TensorBlock
+from ..tensor import TensorMap
+from .equal_metadata import _check_blocks
+
+
+def _check_broadcast(broadcast: Optional[str], B, fname: str):
+    """Check that ``broadcast`` is a valid broadcasting mode for ``B``"""
+    if broadcast is None:
+        return
+
+    if broadcast not in ["samples", "properties"]:
+        raise ValueError(
+            f"invalid value for broadcast in '{fname}': expected 'samples' or "
+            f"'properties', got '{broadcast}'"
+        )
+
+    if not isinstance(B, TensorMap):
+        raise ValueError(f"broadcast can only be used in '{fname}' with a TensorMap")
+
+
+def _broadcast_values(
+    block_a: TensorBlock, block_b: TensorBlock, broadcast: str, fname: str
+):
+    """Check that ``block_b`` is a reduced version of ``block_a`` along the
+    ``broadcast`` axis, and get its values in a shape which can be broadcasted
+    against ``block_a.values``"""
+    if broadcast == "samples":
+        _check_blocks(
+            block_a, block_b, props=["components", "properties"], fname=fname
+        )
+        if len(block_b.samples) != 1:
+            raise ValueError(
+                f"Inputs to '{fname}' with broadcast='samples' should have a "
+                f"single sample in the blocks of B, got {len(block_b.samples)}"
+            )
+    else:
+        assert broadcast == "properties"
+        _check_blocks(block_a, block_b, props=["samples", "components"], fname=fname)
+        if len(block_b.properties) != 1:
+            raise ValueError(
+                f"Inputs to '{fname}' with broadcast='properties' should have a "
+                f"single property in the blocks of B, got {len(block_b.properties)}"
+            )
+
+    if len(block_b.gradients_list()) != 0:
+        raise ValueError(
+            f"Inputs to '{fname}' with broadcast='{broadcast}' should not have "
+            "gradients in the blocks of B"
+        )
+
+    return block_b.values
+
+
+def _broadcast_gradient(values, block_values, gradient):
+    """Get the broadcasted ``values`` (from :py:func:`_broadcast_values`) in a
+    shape which can be broadcasted against ``gradient.data``"""
+    if values.shape[0] != 1:
+        values = values[gradient.samples["sample"]]
+
+    # gradients can have additional components before the values' components
+    n_extra = len(gradient.data.shape) - len(block_values.shape)
+    shape = (values.shape[0],) + (1,) * n_extra + tuple(values.shape[1:])
+    return values.reshape(shape)
+
+
+def _broadcast_keys(A: TensorMap, B, fname: str) -> Tuple[TensorMap, TensorMap]:
+    """Get versions of ``A`` and ``B`` with the same keys, when the key names
+    of one of them are a subset of the key names of the other. Each block of
+    the tensor map with fewer key dimensions is used for all the keys of the
+    other tensor map with the same values for the common dimensions."""
+    if not isinstance(B, TensorMap):
+        raise ValueError(
+            f"broadcast_keys can only be used in '{fname}' with a TensorMap"
+        )
+
+    names_a = A.keys.names
+    names_b = B.keys.names
+    if set(names_a) == set(names_b):
+        return A, B
+    elif set(names_b).issubset(names_a):
+        return A, _expand_keys(B, A, "B", fname)
+    elif set(names_a).issubset(names_b):
+        return _expand_keys(A, B, "A", fname), B
+    else:
+        raise ValueError(
+            f"can not broadcast the keys of the inputs to '{fname}': the key "
+            f"names of one input must be a subset of the other, got {names_a} "
+            f"and {names_b}"
+        )
+
+
+def _expand_keys(
+    tensor: TensorMap, reference: TensorMap, name: str, fname: str
+) -> TensorMap:
+    """Create a new tensor map with the same keys as ``reference``, using the
+    blocks of ``tensor`` matching each key on the common dimensions"""
+    names = tensor.keys.names
+
+    blocks = []
+    for key in reference.keys:
+        selection = {dimension: int(key[dimension]) for dimension in names}
+        matching = tensor.blocks_matching(**selection)
+        if len(matching) == 0:
+            raise ValueError(
+                f"can not broadcast the keys of the inputs to '{fname}': there "
+                f"is no block in {name} for {selection}"
+            )
+        blocks.append(tensor.block(matching[0]).copy())
+
+    return TensorMap(reference.keys, blocks)
@@ -23,6 +23,26 @@ def _check_all_same_type(arrays, expected_type):
             )
 
 
+def promote(A, B):
+    """
+    Convert ``A`` and ``B`` to their common dtype, following the numpy/torch
+    type promotion rules (e.g. ``float32`` and ``float64`` give ``float64``).
+
+    numpy functions already promote their inputs, but most torch linear algebra
+    functions require all their inputs to have the same dtype.
+    """
+    if isinstance(A, np.ndarray):
+        _check_all_same_type([B], np.ndarray)
+        dtype = np.result_type(A, B)
+        return A.astype(dtype, copy=False), B.astype(dtype, copy=False)
+    elif isinstance(A, TorchTensor):
+        _check_all_same_type([B], TorchTensor)
+        dtype = torch.promote_types(A.dtype, B.dtype)
+        return A.to(dtype=dtype), B.to(dtype=dtype)
+    else:
+        raise TypeError(UNKNOWN_ARRAY_TYPE)
+
+
 def all(a, axis=None):
     """Test whether all array elements along a given axis evaluate to True.
 
@@ -48,6 +68,7 @@ def allclose(a, b, rtol, atol, equal_nan=False):
         return np.allclose(a=a, b=b, rtol=rtol, atol=atol, equal_nan=equal_nan)
     elif isinstance(a, TorchTensor):
         _check_all_same_type([b], TorchTensor)
+        a, b = promote(a, b)
         return torch.allclose(
             input=a, other=b, rtol=rtol, atol=atol, equal_nan=equal_nan
         )
@@ -111,6 +132,7 @@ def dot(A, B):
     elif isinstance(A, TorchTensor):
         _check_all_same_type([B], TorchTensor)
         assert len(B.shape) == 2
+        A, B = promote(A, B)
         return A @ B.T
     else:
         raise TypeError(UNKNOWN_ARRAY_TYPE)
@@ -128,12 +150,84 @@ def solve(X, Y):
         return np.linalg.solve(X, Y)
     elif isinstance(X, TorchTensor):
         _check_all_same_type([Y], TorchTensor)
+        X, Y = promote(X, Y)
         result = torch.linalg.solve(X, Y)
         return result
     else:
         raise TypeError(UNKNOWN_ARRAY_TYPE)
 
 
+def svd(X):
+    """
+    Computes the reduced singular value decomposition of a 2-dimensional array,
+    returning ``U, S, Vh`` such that ``X = U @ diag(S) @ Vh``.
+
+    This function has the same behavior as ``numpy.linalg.svd(X,
+    full_matrices=False)``.
+    """
+    if isinstance(X, np.ndarray):
+        return np.linalg.svd(X, full_matrices=False)
+    elif isinstance(X, TorchTensor):
+        return torch.linalg.svd(X, full_matrices=False)
+    else:
+        raise TypeError(UNKNOWN_ARRAY_TYPE)
+
+
+def qr(X):
+    """
+    Computes the reduced QR decomposition of a 2-dimensional array, returning
+    only the ``Q`` matrix with orthonormal columns.
+
+    This function has the same behavior as ``numpy.linalg.qr(X)[0]``.
+    """
+    if isinstance(X, np.ndarray):
+        return np.linalg.qr(X)[0]
+    elif isinstance(X, TorchTensor):
+        return torch.linalg.qr(X)[0]
+    else:
+        raise TypeError(UNKNOWN_ARRAY_TYPE)
+
+
+def cholesky(X):
+    """
+    Computes the lower triangular Cholesky factor ``L`` of a symmetric
+    positive-definite 2-dimensional array, such that ``X = L @ L.T``.
+
+    This function has the same behavior as ``numpy.linalg.cholesky(X)``.
+    """
+    if isinstance(X, np.ndarray):
+        return np.linalg.cholesky(X)
+    elif isinstance(X, TorchTensor):
+        return torch.linalg.cholesky(X)
+    else:
+        raise TypeError(UNKNOWN_ARRAY_TYPE)
+
+
+def solve_triangular(L, Y, lower=True):
+    """
+    Computes the solution ``W`` of ``L @ W = Y``, where ``L`` is a triangular
+    2-dimensional array.
+
+    This function has the same behavior as
+    ``scipy.linalg.solve_triangular(L, Y, lower=lower)``. If scipy is not
+    installed, numpy arrays fall back to ``numpy.linalg.solve``.
+    """
+    if isinstance(L, np.ndarray):
+        _check_all_same_type([Y], np.ndarray)
+        try:
+            import scipy.linalg
+        except ImportError:
+            return np.linalg.solve(L, Y)
+
+        return scipy.linalg.solve_triangular(L, Y, lower=lower)
+    elif isinstance(L, TorchTensor):
+        _check_all_same_type([Y], TorchTensor)
+        L, Y = promote(L, Y)
+        return torch.linalg.solve_triangular(L, Y, upper=not lower)
+    else:
+        raise TypeError(UNKNOWN_ARRAY_TYPE)
+
+
 def sqrt(array):
     """Compute the square root  of the input array.
 
@@ -180,6 +274,7 @@ def lstsq(X, Y, rcond, driver=None):
         return np.linalg.lstsq(X, Y, rcond=rcond)[0]
     elif isinstance(X, TorchTensor):
         _check_all_same_type([Y], TorchTensor)
+        X, Y = promote(X, Y)
         result = torch.linalg.lstsq(X, Y, rcond=rcond, driver=driver)[0]
         return result
     else:
@@ -348,3 +443,41 @@ def random_uniform(array):
         return torch.rand_like(array)
     else:
         raise TypeError(UNKNOWN_ARRAY_TYPE)
+
+
+def random_normal(array, shape, seed=None):
+    """Creates an array of the given ``shape``, with the same dtype (and device)
+    as ``array``, with all values sampled from the standard normal distribution.
+
+    The values are reproducible for a given integer ``seed``.
+    """
+    if isinstance(array, np.ndarray):
+        rng = np.random.default_rng(seed)
+        return rng.standard_normal(shape).astype(array.dtype)
+    elif isinstance(array, TorchTensor):
+        generator = torch.Generator(device=array.device)
+        if seed is None:
+            generator.seed()
+        else:
+            generator.manual_seed(seed)
+        return torch.randn(
+            shape, generator=generator, dtype=array.dtype, device=array.device
+        )
+    else:
+        raise TypeError(UNKNOWN_ARRAY_TYPE)
+
+
+def where(condition, array, other):
+    """Select elements from ``array`` where ``condition`` is true, and from
+    ``other`` everywhere else. ``other`` can be a scalar.
+
+    This function has the same behavior as ``np.where(condition, array, other)``.
+    """
+    if isinstance(array, np.ndarray):
+        return np.where(condition, array, other)
+    elif isinstance(array, TorchTensor):
+        if not isinstance(other, TorchTensor):
+            other = torch.tensor(other, dtype=array.dtype, device=array.device)
+        return torch.where(condition, array, other)
+    else:
+        raise TypeError(UNKNOWN_ARRAY_TYPE)
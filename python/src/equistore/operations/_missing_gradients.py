@@ -0,0 +1,109 @@
+import ctypes
+from typing import List, Tuple
+
+from .._c_api import eqs_tensormap_t
+from .._c_lib import _get_library
+from ..block import TensorBlock
+from ..tensor import TensorMap
+from . import _dispatch
+from .equal_metadata import _copy_units
+
+
+def _check_missing_gradients(missing_gradients: str, fname: str):
+    """Check that ``missing_gradients`` is a valid policy for missing gradients"""
+    if missing_gradients not in ["error", "zero", "drop"]:
+        raise ValueError(
+            f"invalid value for missing_gradients in '{fname}': expected 'error', "
+            f"'zero' or 'drop', got '{missing_gradients}'"
+        )
+
+
+def _unify_gradients(
+    tensor_maps: List[TensorMap], missing_gradients: str
+) -> List[TensorMap]:
+    """Get new versions of ``tensor_maps`` which all have gradients with respect
+    to the same parameters, handling the gradients only present in some of the
+    ``tensor_maps`` according to ``missing_gradients``."""
+    lib = _get_library()
+
+    tensors_array_t = ctypes.POINTER(eqs_tensormap_t) * len(tensor_maps)
+    tensors = tensors_array_t(*[tensor._ptr for tensor in tensor_maps])
+    outputs = tensors_array_t()
+
+    lib.eqs_tensormap_unify_gradients(
+        tensors,
+        len(tensor_maps),
+        missing_gradients.encode("utf8"),
+        outputs,
+    )
+
+    return [TensorMap._from_ptr(ptr) for ptr in outputs]
+
+
+def _fill_missing_gradients(
+    block_a: TensorBlock, block_b: TensorBlock, missing_gradients: str
+) -> Tuple[TensorBlock, TensorBlock]:
+    """Make ``block_a`` and ``block_b`` carry the same gradients, according to
+    ``missing_gradients``.
+
+    With ``"zero"``, a gradient missing from one of the blocks is created with
+    the same samples and components as in the other block, and filled with
+    zeros. With ``"drop"``, gradients missing from one of the blocks are removed
+    from the other one. With ``"error"``, the blocks are returned unchanged.
+    """
+    parameters_a = block_a.gradients_list()
+    parameters_b = block_b.gradients_list()
+
+    if missing_gradients == "error" or set(parameters_a) == set(parameters_b):
+        return block_a, block_b
+
+    if missing_gradients == "drop":
+        common = [p for p in parameters_a if p in parameters_b]
+        return _copy_block(block_a, common), _copy_block(block_b, common)
+
+    assert missing_gradients == "zero"
+    new_a = _copy_block(block_a, parameters_a)
+    new_b = _copy_block(block_b, parameters_b)
+    for parameter in parameters_b:
+        if parameter not in parameters_a:
+            _add_zero_gradient(new_a, block_b.gradient(parameter), parameter)
+
+    for parameter in parameters_a:
+        if parameter not in parameters_b:
+            _add_zero_gradient(new_b, block_a.gradient(parameter), parameter)
+
+    return new_a, new_b
+
+
+def _copy_block(block: TensorBlock, parameters: List[str]) -> TensorBlock:
+    """Create a new block with the same values as ``block`` and only the
+    gradients with respect to ``parameters``"""
+    result = TensorBlock(
+        values=block.values,
+        samples=block.samples,
+        components=block.components,
+        properties=block.properties,
+    )
+    _copy_units(result, block)
+
+    for parameter in parameters:
+        gradient = block.gradient(parameter)
+        result.add_gradient(
+            parameter=parameter,
+            data=gradient.data,
+            samples=gradient.samples,
+            components=gradient.components,
+        )
+
+    return result
+
+
+def _add_zero_gradient(block: TensorBlock, reference, parameter: str):
+    """Add a gradient with respect to ``parameter`` to ``block``, with the same
+    metadata as the ``reference`` gradient and filled with zeros"""
+    block.add_gradient(
+        parameter=parameter,
+        data=_dispatch.zeros_like(reference.data),
+        samples=reference.samples,
+        components=reference.components,
+    )
@@ -5,6 +5,7 @@ Module to find the absolute values of a :py:class:`TensorMap`, returning a new
 from ..block import TensorBlock
 from ..tensor import TensorMap
 from . import _dispatch
+from .equal_metadata import _copy_units
 
 
 def abs(A: TensorMap) -> TensorMap:
@@ -45,6 +46,7 @@ def _abs_block(block: TensorBlock) -> TensorBlock:
         components=block.components,
         properties=block.properties,
     )
+    _copy_units(result_block, block)
     if len(block.gradients_list()) == 0:
         return result_block
 
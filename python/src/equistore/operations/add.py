@@ -1,15 +1,31 @@
-from typing import Union
+from typing import Optional, Union
 
 from ..block import TensorBlock
 from ..tensor import TensorMap
-from .equal_metadata import _check_blocks, _check_maps, _check_same_gradients
-
-
-def add(A: TensorMap, B: Union[float, TensorMap]) -> TensorMap:
+from ._broadcast import _broadcast_keys, _broadcast_values, _check_broadcast
+from ._missing_gradients import _check_missing_gradients, _fill_missing_gradients
+from .equal_metadata import (
+    _check_blocks,
+    _check_maps,
+    _check_same_gradients,
+    _check_same_units,
+    _copy_units,
+)
+
+
+def add(
+    A: TensorMap,
+    B: Union[float, TensorMap],
+    broadcast: Optional[str] = None,
+    missing_gradients: str = "error",
+    broadcast_keys: bool = False,
+) -> TensorMap:
     r"""Return a new :class:`TensorMap` with the values being the sum of
     ``A`` and ``B``.
 
-    If ``B`` is a :py:class:`TensorMap` it has to have the same metadata as ``A``.
+    If ``B`` is a :py:class:`TensorMap` it has to have the same metadata as ``A``,
+    and blocks in ``A`` and ``B`` must have the same unit and quantity (when
+    both are set).
 
     If gradients are present in ``A``:
 
@@ -23,16 +39,58 @@ def add(A: TensorMap, B: Union[float, TensorMap]) -> TensorMap:
        .. math::
             \nabla(A + B) = \nabla A + \nabla B
 
+    If ``broadcast`` is ``"samples"`` (respectively ``"properties"``), the
+    blocks in ``B`` should instead contain a single sample (respectively a
+    single property), which is added to all the samples (respectively
+    properties) of the corresponding block in ``A``. This can be used for
+    example to add back per-block means computed with
+    :py:func:`equistore.mean_over_samples`. ``B`` can not contain gradients in
+    this case, and the gradients of ``A`` are left unchanged.
+
+    If some gradients are only present in one of ``A`` and ``B``, the
+    ``missing_gradients`` parameter controls what happens. With ``"error"`` (the
+    default), an error is raised. With ``"zero"``, the missing gradients are
+    considered to be zero. With ``"drop"``, these gradients are removed from the
+    result.
+
+    If ``broadcast_keys`` is ``True``, ``A`` and ``B`` can have different key
+    names, as long as the key names of one of them are a subset of the key names
+    of the other. The blocks of the tensor map with fewer key dimensions are then
+    used for all the blocks of the other tensor map with the same values of the
+    common key dimensions, and the result has the keys with more dimensions.
+    This can for example be used to add per-species baselines to a tensor map
+    which also contains ``spherical_harmonics_l`` in its keys.
+
     :param A: First :py:class:`TensorMap` for the addition.
     :param B: Second instance for the addition. Parameter can be a scalar or a
               :py:class:`TensorMap`. In the latter case ``B`` must have the same
               metadata of ``A``.
+    :param broadcast: if set to ``"samples"`` or ``"properties"``, broadcast
+              the blocks of ``B`` over this dimension of the blocks of ``A``.
+    :param missing_gradients: how to handle gradients which are only present
+              in one of ``A`` and ``B``: ``"error"``, ``"zero"`` or ``"drop"``.
+    :param broadcast_keys: if ``True``, broadcast the blocks of the input
+              with fewer key dimensions over the keys of the other input.
 
     :return: New :py:class:`TensorMap` with the same metadata as ``A``.
     """
 
+    _check_broadcast(broadcast, B, "add")
+    _check_missing_gradients(missing_gradients, "add")
+
+    if broadcast_keys:
+        A, B = _broadcast_keys(A, B, "add")
+
     blocks = []
-    if isinstance(B, TensorMap):
+    if broadcast is not None:
+        _check_maps(A, B, "add")
+        for key in A.keys:
+            blockA = A[key]
+            blockB = B[key]
+            values = _broadcast_values(blockA, blockB, broadcast, fname="add")
+            _check_same_units(blockA, blockB, fname="add")
+            blocks.append(_add_block_broadcast(blockA, blockB, values))
+    elif isinstance(B, TensorMap):
         _check_maps(A, B, "add")
         for key in A.keys:
             blockA = A[key]
@@ -43,12 +101,16 @@ def add(A: TensorMap, B: Union[float, TensorMap]) -> TensorMap:
                 props=["samples", "components", "properties"],
                 fname="add",
             )
+            blockA, blockB = _fill_missing_gradients(
+                blockA, blockB, missing_gradients
+            )
             _check_same_gradients(
                 blockA,
                 blockB,
                 props=["samples", "components", "properties"],
                 fname="add",
             )
+            _check_same_units(blockA, blockB, fname="add")
             blocks.append(_add_block_block(block1=blockA, block2=blockB))
     else:
         # check if can be converted in float (so if it is a constant value)
@@ -71,6 +133,7 @@ def _add_block_constant(block: TensorBlock, constant: float) -> TensorBlock:
         components=block.components,
         properties=block.properties,
     )
+    _copy_units(result_block, block)
 
     for parameter, gradient in block.gradients():
         result_block.add_gradient(
@@ -92,6 +155,7 @@ def _add_block_block(block1: TensorBlock, block2: TensorBlock) -> TensorBlock:
         components=block1.components,
         properties=block1.properties,
     )
+    _copy_units(result_block, block1, block2)
 
     for parameter1, gradient1 in block1.gradients():
         result_block.add_gradient(
@@ -102,3 +166,27 @@ def _add_block_block(block1: TensorBlock, block2: TensorBlock) -> TensorBlock:
         )
 
     return result_block
+
+
+def _add_block_broadcast(
+    block1: TensorBlock, block2: TensorBlock, values2
+) -> TensorBlock:
+    values = block1.values + values2
+
+    result_block = TensorBlock(
+        values=values,
+        samples=block1.samples,
+        components=block1.components,
+        properties=block1.properties,
+    )
+    _copy_units(result_block, block1, block2)
+
+    for parameter, gradient in block1.gradients():
+        result_block.add_gradient(
+            parameter=parameter,
+            data=gradient.data,
+            samples=gradient.samples,
+            components=gradient.components,
+        )
+
+    return result_block
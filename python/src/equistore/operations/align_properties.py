@@ -0,0 +1,42 @@
+import ctypes
+from typing import List
+
+from .._c_api import eqs_tensormap_t
+from .._c_lib import _get_library
+from ..tensor import TensorMap
+
+
+def align_properties(tensors: List[TensorMap]) -> List[TensorMap]:
+    """
+    Expand all the blocks in all the ``tensors`` to use the same set of
+    properties, i.e. the union of the properties of all blocks. Entries
+    corresponding to properties missing in a block are filled with zeros.
+
+    The resulting :py:class:`TensorMap` are returned in the same order as
+    ``tensors``, and can be directly summed or joined along samples. The union
+    of properties is sorted in lexicographic order.
+
+    :param tensors: list of :py:class:`TensorMap` to align
+
+    :return: a list of new :py:class:`TensorMap`, where all blocks have the same
+        properties
+    """
+    if not isinstance(tensors, (list, tuple)):
+        raise TypeError("`tensors` must be a list or a tuple of TensorMap")
+
+    for tensor in tensors:
+        if not isinstance(tensor, TensorMap):
+            raise TypeError("`tensors` must be a list or a tuple of TensorMap")
+
+    if len(tensors) == 0:
+        return []
+
+    lib = _get_library()
+
+    tensors_array_t = ctypes.POINTER(eqs_tensormap_t) * len(tensors)
+    tensors_ptr = tensors_array_t(*[tensor._ptr for tensor in tensors])
+    aligned = tensors_array_t()
+
+    lib.eqs_tensormap_align_properties(tensors_ptr, len(tensors), aligned)
+
+    return [TensorMap._from_ptr(ptr) for ptr in aligned]
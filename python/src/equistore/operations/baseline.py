@@ -0,0 +1,176 @@
+"""
+Composition baselines
+=====================
+
+Most properties of atomistic systems (energies in particular) contain a large
+contribution which only depends on the chemical composition of the system. It
+is usually a good idea to remove this contribution before training a model on
+the remaining part of the targets.
+
+These functions fit such a *composition baseline*, by solving a least-squares
+problem for the per-species contributions to the targets; and remove the
+corresponding baseline from a set of targets. The compositions should be given
+as a :py:class:`TensorMap` with the same keys as the targets, where each block
+has the same samples as the corresponding target block, no components, and
+contains the number of atoms of each species in the properties.
+
+Since the composition of a system does not change when moving the atoms, the
+gradients of the baseline with respect to positions or cell are always zero,
+and the gradients of the targets (forces, virial, *etc.*) are kept as-is when
+removing the baseline.
+
+.. autofunction:: equistore.fit_composition_baseline
+
+.. autofunction:: equistore.remove_baseline
+"""
+from ..block import TensorBlock
+from ..tensor import TensorMap
+from .dot import dot
+from .equal_metadata import _check_blocks, _check_maps, _copy_units
+from .lstsq import lstsq
+from .remove_gradients import remove_gradients
+
+
+def fit_composition_baseline(
+    targets: TensorMap, compositions: TensorMap, rcond=1e-10, driver=None
+) -> TensorMap:
+    """Fit a linear baseline to ``targets``, using the number of atoms of
+    each species in ``compositions`` as features.
+
+    The gradients of ``targets`` are ignored when fitting the baseline, since
+    the corresponding gradients of the compositions are always zero.
+
+    >>> import numpy as np
+    >>> import equistore
+    >>> from equistore import Labels, TensorBlock, TensorMap
+    >>> samples = Labels(["structure"], np.array([[0], [1], [2]]))
+    >>> # energies of the structures
+    >>> targets = TensorMap(
+    ...     Labels.single(),
+    ...     [
+    ...         TensorBlock(
+    ...             values=np.array([[-3.0], [-5.0], [-4.0]]),
+    ...             samples=samples,
+    ...             components=[],
+    ...             properties=Labels(["energy"], np.array([[0]])),
+    ...         )
+    ...     ],
+    ... )
+    >>> # number of H and O atoms in the structures
+    >>> compositions = TensorMap(
+    ...     Labels.single(),
+    ...     [
+    ...         TensorBlock(
+    ...             values=np.array([[1.0, 1.0], [1.0, 2.0], [2.0, 1.0]]),
+    ...             samples=samples,
+    ...             components=[],
+    ...             properties=Labels(["species"], np.array([[1], [8]])),
+    ...         )
+    ...     ],
+    ... )
+    >>> baseline = equistore.fit_composition_baseline(targets, compositions)
+    >>> np.round(baseline.block().values, 10)
+    array([[-1., -2.]])
+    >>> residual = equistore.remove_baseline(targets, baseline, compositions)
+    >>> np.allclose(residual.block().values, 0.0)
+    True
+
+    :param targets: :py:class:`TensorMap` containing the targets, without
+        components
+    :param compositions: :py:class:`TensorMap` with the same keys and samples
+        as ``targets``, containing the number of atoms of each species
+    :param rcond: cut-off ratio for small singular values, see
+        :py:func:`equistore.lstsq`
+    :param driver: driver for the least-squares solver, see
+        :py:func:`equistore.lstsq`
+
+    :return: a :py:class:`TensorMap` with the same keys as ``targets``, where
+        each block has the ``properties`` of ``targets`` as ``samples`` and the
+        ``properties`` of ``compositions`` as ``properties``, containing the
+        per-species contribution to each target.
+    """
+    _check_maps(targets, compositions, "fit_composition_baseline")
+
+    for key, block in targets:
+        _check_compositions(
+            block, compositions.block(key), fname="fit_composition_baseline"
+        )
+
+    return lstsq(
+        compositions,
+        remove_gradients(targets),
+        rcond=rcond,
+        driver=driver,
+    )
+
+
+def remove_baseline(
+    targets: TensorMap, baseline: TensorMap, compositions: TensorMap
+) -> TensorMap:
+    """Remove a composition ``baseline`` (typically computed with
+    :py:func:`equistore.fit_composition_baseline`) from ``targets``.
+
+    The baseline for each sample is computed from the ``compositions``, and
+    subtracted from the values of ``targets``. The gradients of ``targets`` are
+    kept unchanged. See :py:func:`equistore.fit_composition_baseline` for an
+    example.
+
+    :param targets: :py:class:`TensorMap` containing the targets, without
+        components
+    :param baseline: per-species contribution to the targets, as returned by
+        :py:func:`equistore.fit_composition_baseline`
+    :param compositions: :py:class:`TensorMap` with the same keys and samples
+        as ``targets``, containing the number of atoms of each species
+
+    :return: a new :py:class:`TensorMap` with the same metadata as ``targets``
+    """
+    _check_maps(targets, compositions, "remove_baseline")
+    _check_maps(targets, baseline, "remove_baseline")
+
+    for key, block in targets:
+        _check_compositions(block, compositions.block(key), fname="remove_baseline")
+
+    predictions = dot(compositions, baseline)
+
+    blocks = []
+    for key, block in targets:
+        prediction = predictions.block(key)
+        _check_blocks(
+            block, prediction, props=["samples", "properties"], fname="remove_baseline"
+        )
+        blocks.append(_remove_baseline_block(block, prediction))
+
+    return TensorMap(targets.keys, blocks)
+
+
+def _check_compositions(target: TensorBlock, composition: TensorBlock, fname: str):
+    if len(target.components) != 0:
+        raise ValueError(f"the targets in '{fname}' should not have components")
+
+    if len(composition.components) != 0:
+        raise ValueError(f"the compositions in '{fname}' should not have components")
+
+    if len(composition.gradients_list()) != 0:
+        raise ValueError(f"the compositions in '{fname}' should not have gradients")
+
+    _check_blocks(target, composition, props=["samples"], fname=fname)
+
+
+def _remove_baseline_block(block: TensorBlock, prediction: TensorBlock) -> TensorBlock:
+    result_block = TensorBlock(
+        values=block.values - prediction.values,
+        samples=block.samples,
+        components=block.components,
+        properties=block.properties,
+    )
+    _copy_units(result_block, block)
+
+    for parameter, gradient in block.gradients():
+        result_block.add_gradient(
+            parameter,
+            gradient.data,
+            gradient.samples,
+            gradient.components,
+        )
+
+    return result_block
@@ -0,0 +1,97 @@
+from typing import Iterator, Optional
+
+import numpy as np
+
+from ..labels import Labels
+from ..tensor import TensorMap
+from .slice import slice
+from .unique_metadata import unique_metadata
+
+
+class Batcher:
+    """Iterate over random minibatches of the samples in a :py:class:`TensorMap`.
+
+    The samples are grouped according to the values of a single sample
+    ``column`` (for example ``"structure"``), and each minibatch contains all
+    the samples (and corresponding gradients) for ``batch_size`` different
+    values of this column, in all the blocks of ``tensor``. The minibatches are
+    created with :py:func:`slice`, so the gradients samples are updated to
+    refer to the samples in the minibatch.
+
+    Iterating multiple times over the same :py:class:`Batcher` (e.g. once per
+    training epoch) gives a different order every time if ``shuffle`` is
+    ``True``, while the whole sequence of minibatches is reproducible for a
+    given ``seed``.
+
+    .. code-block:: python
+
+        batcher = Batcher(tensor, batch_size=16, column="structure", seed=42)
+
+        for epoch in range(n_epochs):
+            for batch in batcher:
+                # batch is a TensorMap with the same keys as tensor, containing
+                # the samples for 16 different structures
+                ...
+
+    :param tensor: the :py:class:`TensorMap` to split in minibatches
+    :param batch_size: number of different values of ``column`` in each
+        minibatch
+    :param column: name of the sample dimension used to group samples together
+    :param shuffle: whether to randomly shuffle the values of ``column`` before
+        creating the minibatches. If ``False``, the minibatches are created in
+        the order of the sorted values of ``column``.
+    :param seed: seed for the random number generator used to shuffle the
+        minibatches
+    :param drop_last: whether to skip the last minibatch if it contains less
+        than ``batch_size`` values of ``column``
+    """
+
+    def __init__(
+        self,
+        tensor: TensorMap,
+        batch_size: int,
+        column: str = "structure",
+        shuffle: bool = True,
+        seed: Optional[int] = None,
+        drop_last: bool = False,
+    ):
+        if not isinstance(tensor, TensorMap):
+            raise TypeError("`tensor` must be an equistore `TensorMap`")
+
+        if batch_size <= 0:
+            raise ValueError(f"batch_size must be positive, got {batch_size}")
+
+        if column not in tensor.sample_names:
+            raise ValueError(
+                f"'{column}' is not part of the samples of this tensor, "
+                f"available names are {list(tensor.sample_names)}"
+            )
+
+        self._tensor = tensor
+        self._batch_size = batch_size
+        self._column = column
+        self._shuffle = shuffle
+        self._drop_last = drop_last
+        self._rng = np.random.default_rng(seed)
+
+        unique = unique_metadata(tensor, axis="samples", names=[column])
+        self._values = np.array(unique[column], dtype=np.int32)
+
+    def __len__(self) -> int:
+        """number of minibatches produced when iterating over this batcher"""
+        if self._drop_last:
+            return len(self._values) // self._batch_size
+        else:
+            return -(-len(self._values) // self._batch_size)
+
+    def __iter__(self) -> Iterator[TensorMap]:
+        values = self._values
+        if self._shuffle:
+            values = self._rng.permutation(values)
+
+        for batch_i in range(len(self)):
+            start = batch_i * self._batch_size
+            batch = values[start : start + self._batch_size]
+
+            samples = Labels(names=[self._column], values=batch.reshape(-1, 1))
+            yield slice(self._tensor, samples=samples)
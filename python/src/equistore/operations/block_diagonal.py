@@ -0,0 +1,111 @@
+from typing import Tuple
+
+import numpy as np
+
+from ..labels import Labels
+from ..tensor import TensorMap
+from . import _dispatch
+
+
+def block_diagonal(tensor: TensorMap, sparse: bool = False) -> Tuple:
+    """Assemble all the blocks of ``tensor`` into a single block-diagonal
+    matrix.
+
+    The block associated with the ``i``-th key is placed on the diagonal of the
+    resulting matrix, after the blocks for all the previous keys. This is
+    useful to set up linear problems coupling the different blocks, which can
+    then be solved globally.
+
+    All blocks must be two-dimensional (i.e. have no components), and have the
+    same sample and property names. Gradients are ignored. The rows and columns
+    of the matrix are described by new :py:class:`Labels`, containing both the
+    key and the sample (respectively property) of the original blocks. This
+    requires the names of the keys to be different from the names of the
+    samples and properties.
+
+    :param tensor: input :py:class:`TensorMap`
+    :param sparse: if ``True``, return a :py:class:`scipy.sparse.csr_matrix`
+        instead of a dense array. This requires ``scipy`` to be installed, and
+        the values to be stored in numpy arrays.
+
+    :return: a tuple ``(matrix, samples, properties)``, containing the
+        block-diagonal matrix, and the labels describing its rows and columns
+    """
+    blocks = [block for _, block in tensor]
+    if len(blocks) == 0:
+        raise ValueError(
+            "can not create a block-diagonal matrix from an empty TensorMap"
+        )
+
+    first = blocks[0]
+    for block in blocks:
+        if len(block.components) != 0:
+            raise ValueError(
+                "all blocks must be two-dimensional to create a block-diagonal "
+                "matrix, but some blocks have components"
+            )
+
+        if block.samples.names != first.samples.names:
+            raise ValueError(
+                "all blocks must have the same sample names to create a "
+                "block-diagonal matrix"
+            )
+
+        if block.properties.names != first.properties.names:
+            raise ValueError(
+                "all blocks must have the same property names to create a "
+                "block-diagonal matrix"
+            )
+
+    samples = _combine_labels(tensor, [block.samples for block in blocks])
+    properties = _combine_labels(tensor, [block.properties for block in blocks])
+
+    if sparse:
+        try:
+            import scipy.sparse
+        except ImportError as e:
+            raise ImportError(
+                "scipy is required to create sparse block-diagonal matrices"
+            ) from e
+
+        matrix = scipy.sparse.block_diag(
+            [np.asarray(block.values) for block in blocks], format="csr"
+        )
+    else:
+        matrix = _dispatch.zeros_like(
+            first.values, shape=(len(samples), len(properties))
+        )
+
+        sample_start = 0
+        property_start = 0
+        for block in blocks:
+            sample_stop = sample_start + len(block.samples)
+            property_stop = property_start + len(block.properties)
+            matrix[sample_start:sample_stop, property_start:property_stop] = (
+                block.values
+            )
+            sample_start = sample_stop
+            property_start = property_stop
+
+    return matrix, samples, properties
+
+
+def _combine_labels(tensor, labels):
+    """
+    Concatenate the given ``labels`` (one for each block in ``tensor``), adding
+    the keys of the corresponding block as new dimensions.
+    """
+    names = tensor.keys.names + labels[0].names
+    for name in tensor.keys.names:
+        if name in labels[0].names:
+            raise ValueError(
+                f"can not create a block-diagonal matrix: '{name}' is used both "
+                "in the keys and in the samples or properties"
+            )
+
+    values = []
+    for key, block_labels in zip(tensor.keys.tolist(), labels):
+        values += [tuple(key) + entry for entry in block_labels.tolist()]
+
+    values = np.array(values, dtype=np.int32).reshape(-1, len(names))
+    return Labels(names=names, values=values)
@@ -0,0 +1,143 @@
+from ..block import TensorBlock
+from ..tensor import TensorMap
+from . import _dispatch
+from .equal_metadata import _check_maps, _labels_equal
+
+
+def cholesky(tensor: TensorMap) -> TensorMap:
+    """Compute the Cholesky decomposition of each block in a
+    :py:class:`TensorMap`.
+
+    All the blocks must contain symmetric positive-definite 2D square arrays,
+    without components or gradients, such as the ones created by
+    :py:func:`equistore.gram` or :py:func:`equistore.linear_kernel`. For each
+    block ``K``, the lower triangular matrix ``L`` such that ``K = L @ L.T`` is
+    computed.
+
+    :param tensor: a :py:class:`TensorMap` containing the matrices to
+        decompose
+
+    :return: a :py:class:`TensorMap` with the same keys as ``tensor``, where
+        each :py:class:`TensorBlock` has the same ``samples`` and
+        ``properties`` as the corresponding input block, and the lower
+        triangular Cholesky factor as values
+    """
+    if not isinstance(tensor, TensorMap):
+        raise TypeError("`tensor` must be an equistore `TensorMap`")
+
+    blocks = []
+    for _, block in tensor:
+        _check_square_block(block, "cholesky")
+        blocks.append(
+            TensorBlock(
+                values=_dispatch.cholesky(block.values),
+                samples=block.samples,
+                components=[],
+                properties=block.properties,
+            )
+        )
+
+    return TensorMap(tensor.keys, blocks)
+
+
+def solve_triangular(
+    L: TensorMap, Y: TensorMap, lower: bool = True, transpose: bool = False
+) -> TensorMap:
+    """Solve a triangular linear system between two :py:class:`TensorMap`.
+
+    For each key, solve ``L @ W = Y`` (or ``L.T @ W = Y`` if ``transpose`` is
+    ``True``) for the unknown ``W``, where ``L`` is a triangular matrix. This
+    can be used together with :py:func:`cholesky` to solve a symmetric
+    positive-definite system ``K @ W = Y``:
+
+    .. code-block:: python
+
+        L = cholesky(K)
+        W = solve_triangular(L, solve_triangular(L, Y), transpose=True)
+
+    ``L`` and ``Y`` must have the same keys. The blocks of ``L`` must contain 2D
+    square arrays, and the blocks of ``Y`` 2D arrays; neither can have
+    components or gradients. The ``samples`` of each block in ``Y`` must be the
+    same as the ``samples`` of the corresponding block in ``L`` (or the
+    ``properties`` if ``transpose`` is ``True``).
+
+    If scipy is installed, it is used to solve the system for numpy arrays.
+    Otherwise the triangular structure is not exploited, and a general solver
+    is used instead.
+
+    :param L: a :py:class:`TensorMap` containing the triangular matrices
+    :param Y: a :py:class:`TensorMap` containing the right-hand side of the
+        equation
+    :param lower: whether the blocks of ``L`` are lower (``True``) or upper
+        (``False``) triangular
+    :param transpose: whether to solve the system with the transposed blocks of
+        ``L``
+
+    :return: a :py:class:`TensorMap` with the same keys as ``L`` and ``Y``,
+        where each :py:class:`TensorBlock` has the ``properties`` (or the
+        ``samples`` if ``transpose`` is ``True``) of ``L`` as ``samples``, and
+        the ``properties`` of ``Y`` as ``properties``
+    """
+    _check_maps(L, Y, "solve_triangular")
+
+    blocks = []
+    for key, L_block in L:
+        Y_block = Y.block(key)
+        blocks.append(_solve_triangular_block(L_block, Y_block, lower, transpose))
+
+    return TensorMap(L.keys, blocks)
+
+
+def _check_square_block(block: TensorBlock, fname: str):
+    shape = block.values.shape
+    if len(shape) != 2 or shape[0] != shape[1]:
+        raise ValueError(
+            f"the values in each block of the input to `{fname}` should be a "
+            "square 2D array"
+        )
+
+    if len(block.gradients_list()) > 0:
+        raise ValueError(
+            f"the blocks in the input to `{fname}` should not have gradients"
+        )
+
+
+def _solve_triangular_block(
+    L: TensorBlock, Y: TensorBlock, lower: bool, transpose: bool
+) -> TensorBlock:
+    _check_square_block(L, "solve_triangular")
+
+    if len(Y.values.shape) != 2:
+        raise ValueError(
+            "the values in each block of Y in `solve_triangular` should be a 2D array"
+        )
+
+    if len(Y.gradients_list()) > 0:
+        raise ValueError("Y blocks in `solve_triangular` should not have gradients")
+
+    if transpose:
+        rows = L.properties
+        columns = L.samples
+        L_values = L.values.T
+        lower = not lower
+    else:
+        rows = L.samples
+        columns = L.properties
+        L_values = L.values
+
+    if not _labels_equal(rows, Y.samples, exact_order=True):
+        if transpose:
+            message = "Y samples should be the same as the properties of L"
+        else:
+            message = "Y samples should be the same as the samples of L"
+
+        raise ValueError(f"{message} in `solve_triangular`, in the same order")
+
+    values = _dispatch.solve_triangular(L_values, Y.values, lower=lower)
+
+    return TensorBlock(
+        values=values,
+        samples=columns,
+        components=[],
+        properties=Y.properties,
+    )
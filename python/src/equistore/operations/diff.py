@@ -0,0 +1,72 @@
+import ctypes
+import json
+
+from .._c_lib import _get_library
+from ..tensor import TensorMap
+from ..utils import _call_with_growing_buffer
+
+
+def diff(
+    tensor_1: TensorMap,
+    tensor_2: TensorMap,
+    compare_data: bool = True,
+    tolerance: float = 0.0,
+) -> dict:
+    """
+    Compare ``tensor_1`` and ``tensor_2``, and report all the differences in
+    metadata, keys, units, labels, shapes and data. Blocks are matched by key,
+    so the order of the blocks does not matter.
+
+    The report is a dictionary with the following entries:
+
+    - ``"metadata"``: list of ``{"key", "first", "second"}`` dictionaries for
+      the metadata entries with different values, missing values are ``None``;
+    - ``"keys_names"``: ``None``, or a ``{"first", "second"}`` dictionary
+      containing the names of the keys if they differ. In this case, no other
+      comparison is made;
+    - ``"only_in_first"`` and ``"only_in_second"``: lists of the keys only
+      present in one of the tensor maps;
+    - ``"blocks"``: list of the differences between blocks present in both
+      tensor maps, as ``{"key", "parameter", "difference"}`` dictionaries.
+      ``"parameter"`` is either ``"values"`` or the gradient parameter, and the
+      ``"kind"`` entry of ``"difference"`` is one of ``"unit"``,
+      ``"quantity"``, ``"missing_gradient"``, ``"samples"``, ``"components"``,
+      ``"properties"``, ``"shape"`` or ``"data"``.
+
+    >>> import numpy as np
+    >>> import equistore
+    >>> from equistore import Labels, TensorBlock, TensorMap
+    >>> block = TensorBlock(
+    ...     values=np.array([[1.0], [2.0]]),
+    ...     samples=Labels(["samples"], np.array([[0], [1]])),
+    ...     components=[],
+    ...     properties=Labels(["properties"], np.array([[0]])),
+    ... )
+    >>> tensor = TensorMap(Labels(["key"], np.array([[0]])), [block])
+    >>> report = equistore.diff(tensor, tensor)
+    >>> report["blocks"]
+    []
+
+    :param tensor_1: first :py:class:`TensorMap`
+    :param tensor_2: second :py:class:`TensorMap`
+    :param compare_data: whether to compare the values inside the arrays
+    :param tolerance: absolute tolerance used when comparing the data,
+        differences smaller than this are not reported
+
+    :return: a dictionary containing the structured report of the differences
+    """
+    if not isinstance(tensor_1, TensorMap) or not isinstance(tensor_2, TensorMap):
+        raise TypeError("both inputs to diff must be TensorMap")
+
+    lib = _get_library()
+    report = _call_with_growing_buffer(
+        lambda buffer, bufflen: lib.eqs_tensormap_diff_json(
+            tensor_1._ptr,
+            tensor_2._ptr,
+            compare_data,
+            ctypes.c_double(tolerance),
+            buffer,
+            bufflen,
+        )
+    )
+    return json.loads(report)
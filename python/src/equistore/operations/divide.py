@@ -1,18 +1,38 @@
-from typing import Union
+from typing import Optional, Union
 
 import numpy as np
 
 from ..block import TensorBlock
 from ..tensor import TensorMap
 from . import _dispatch
-from .equal_metadata import _check_blocks, _check_maps, _check_same_gradients
-
-
-def divide(A: TensorMap, B: Union[float, TensorMap]) -> TensorMap:
+from ._broadcast import (
+    _broadcast_gradient,
+    _broadcast_keys,
+    _broadcast_values,
+    _check_broadcast,
+)
+from ._missing_gradients import _check_missing_gradients, _fill_missing_gradients
+from .equal_metadata import (
+    _check_blocks,
+    _check_maps,
+    _check_same_gradients,
+    _copy_units,
+)
+
+
+def divide(
+    A: TensorMap,
+    B: Union[float, TensorMap],
+    broadcast: Optional[str] = None,
+    missing_gradients: str = "error",
+    broadcast_keys: bool = False,
+) -> TensorMap:
     r"""Return a new :class:`TensorMap` with the values being the element-wise
     division of ``A`` and ``B``.
 
     If ``B`` is a :py:class:`TensorMap` it has to have the same metadata as ``A``.
+    The unit and quantity of ``A`` are kept when ``B`` is a scalar, and removed
+    when ``B`` is a :py:class:`TensorMap`.
 
     If gradients are present in ``A``:
 
@@ -27,16 +47,57 @@ def divide(A: TensorMap, B: Union[float, TensorMap]) -> TensorMap:
        .. math::
             \nabla(A / B) =(B*\nabla A-A*\nabla B)/B^2
 
+    If ``broadcast`` is ``"samples"`` (respectively ``"properties"``), the
+    blocks in ``B`` should instead contain a single sample (respectively a
+    single property), which divides all the samples (respectively properties)
+    of the corresponding block in ``A``. ``B`` can not contain gradients in
+    this case, and is considered constant when computing the gradients:
+
+       .. math::
+            \nabla(A / B) = \nabla A / B
+
+    If some gradients are only present in one of ``A`` and ``B``, the
+    ``missing_gradients`` parameter controls what happens. With ``"error"`` (the
+    default), an error is raised. With ``"zero"``, the missing gradients are
+    considered to be zero. With ``"drop"``, these gradients are removed from the
+    result.
+
+    If ``broadcast_keys`` is ``True``, ``A`` and ``B`` can have different key
+    names, as long as the key names of one of them are a subset of the key names
+    of the other. The blocks of the tensor map with fewer key dimensions are then
+    used for all the blocks of the other tensor map with the same values of the
+    common key dimensions, and the result has the keys with more dimensions.
+    This can for example be used to normalize a tensor map which also contains
+    ``spherical_harmonics_l`` in its keys by per-species factors.
+
     :param A: First :py:class:`TensorMap` for the division.
     :param B: Second instance for the division. Parameter can be a scalar
             or a :py:class:`TensorMap`. In the latter case ``B`` must have the same
             metadata of ``A``.
+    :param broadcast: if set to ``"samples"`` or ``"properties"``, broadcast
+            the blocks of ``B`` over this dimension of the blocks of ``A``.
+    :param missing_gradients: how to handle gradients which are only present
+            in one of ``A`` and ``B``: ``"error"``, ``"zero"`` or ``"drop"``.
+    :param broadcast_keys: if ``True``, broadcast the blocks of the input
+            with fewer key dimensions over the keys of the other input.
 
     :return: New :py:class:`TensorMap` with the same metadata as ``A``.
     """
 
+    _check_broadcast(broadcast, B, "divide")
+    _check_missing_gradients(missing_gradients, "divide")
+
+    if broadcast_keys:
+        A, B = _broadcast_keys(A, B, "divide")
+
     blocks = []
-    if isinstance(B, TensorMap):
+    if broadcast is not None:
+        _check_maps(A, B, "divide")
+        for key in A.keys:
+            blockA = A[key]
+            values = _broadcast_values(blockA, B[key], broadcast, fname="divide")
+            blocks.append(_divide_block_broadcast(blockA, values))
+    elif isinstance(B, TensorMap):
         _check_maps(A, B, "divide")
         for key, blockA in A:
             blockB = B.block(key)
@@ -46,6 +107,9 @@ def divide(A: TensorMap, B: Union[float, TensorMap]) -> TensorMap:
                 props=["samples", "components", "properties"],
                 fname="divide",
             )
+            blockA, blockB = _fill_missing_gradients(
+                blockA, blockB, missing_gradients
+            )
             _check_same_gradients(
                 blockA,
                 blockB,
@@ -74,6 +138,7 @@ def _divide_block_constant(block: TensorBlock, constant: float) -> TensorBlock:
         components=block.components,
         properties=block.properties,
     )
+    _copy_units(result_block, block)
 
     for parameter, gradient in block.gradients():
         result_block.add_gradient(
@@ -118,3 +183,24 @@ def _divide_block_block(block1: TensorBlock, block2: TensorBlock) -> TensorBlock
         )
 
     return result_block
+
+
+def _divide_block_broadcast(block1: TensorBlock, values2) -> TensorBlock:
+    values = block1.values / values2
+
+    result_block = TensorBlock(
+        values=values,
+        samples=block1.samples,
+        components=block1.components,
+        properties=block1.properties,
+    )
+
+    for parameter1, gradient1 in block1.gradients():
+        result_block.add_gradient(
+            parameter1,
+            gradient1.data / _broadcast_gradient(values2, block1.values, gradient1),
+            gradient1.samples,
+            gradient1.components,
+        )
+
+    return result_block
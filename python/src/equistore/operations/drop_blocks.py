@@ -25,6 +25,38 @@ def drop_blocks(tensor: TensorMap, keys: Labels) -> TensorMap:
             "some keys in `keys` are not present in `tensor`."
             f" Non-existent keys: {diff}"
         )
-    new_keys = np.setdiff1d(tensor.keys, keys)
+
+    if len(keys) == 0:
+        return tensor.copy()
+
+    result = tensor
+    for i in range(len(keys)):
+        ptr = tensor._lib.eqs_tensormap_drop_blocks(
+            result._ptr, keys[i : i + 1]._as_eqs_labels_t()
+        )
+        result = TensorMap._from_ptr(ptr)
+
+    return result
+
+
+def prune_empty(tensor: TensorMap) -> TensorMap:
+    """
+    Remove all the blocks without any samples from a TensorMap.
+
+    Empty blocks can for example be created when selecting samples, and are
+    often not needed in the rest of a calculation. The order of the remaining
+    blocks is preserved.
+
+    :param tensor: the TensorMap to prune
+
+    :return: a new :py:class:`TensorMap` containing copies of the non-empty
+        blocks of ``tensor``
+    """
+    keep = np.array([len(block.samples) != 0 for _, block in tensor], dtype=bool)
+    if np.any(keep):
+        new_keys = tensor.keys[keep]
+    else:
+        new_keys = Labels.empty(tensor.keys.names)
+
     new_blocks = [tensor[key].copy() for key in new_keys]
     return TensorMap(keys=new_keys, blocks=new_blocks)
@@ -331,6 +331,35 @@ def _check_parameters_in_gradient_block(
             )
 
 
+def _check_same_units(a: TensorBlock, b: TensorBlock, fname: str):
+    """Check that the unit and quantity of two TensorBlocks are consistent for
+    an operation. Blocks without unit (or quantity) are consistent with any
+    other unit (or quantity).
+
+    :param a: first :py:class:`TensorBlock` for check
+    :param b: second :py:class:`TensorBlock` for check
+    """
+    for attribute in ["unit", "quantity"]:
+        value_a = getattr(a, attribute)
+        value_b = getattr(b, attribute)
+        if value_a is not None and value_b is not None and value_a != value_b:
+            raise ValueError(
+                f"Inputs to '{fname}' should have the same {attribute}, "
+                f"got '{value_a}' and '{value_b}'"
+            )
+
+
+def _copy_units(result: TensorBlock, *blocks: TensorBlock):
+    """Set the unit and quantity of ``result`` to the first unit and quantity
+    defined in ``blocks``."""
+    for attribute in ["unit", "quantity"]:
+        for block in blocks:
+            value = getattr(block, attribute)
+            if value is not None:
+                setattr(result, attribute, value)
+                break
+
+
 def _labels_equal(a: Labels, b: Labels, exact_order: bool):
     """
     For 2 :py:class:`Labels` objects ``a`` and ``b``, returns true if they are
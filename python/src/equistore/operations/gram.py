@@ -0,0 +1,151 @@
+from typing import List, Union
+
+from ..block import TensorBlock
+from ..tensor import TensorMap
+from . import _dispatch
+from .equal_metadata import _check_blocks, _check_maps
+
+
+def gram(tensor: Union[TensorMap, List[TensorMap]]) -> TensorMap:
+    """Compute the Gram matrix :math:`X^T X` of each block in a
+    :py:class:`TensorMap`.
+
+    For each block, :math:`X` is the values array reshaped to a 2-dimensional
+    array with one row for each sample and component, and one column for each
+    property. The Gram matrix has then a size of ``n_properties x
+    n_properties``.
+
+    If a list of :py:class:`TensorMap` is given, the Gram matrices are
+    accumulated across all of them, i.e. the result is the same as the Gram
+    matrix of the samples of all tensors joined together. This allows to compute
+    the Gram matrix of datasets which are too large to fit in memory at once.
+    All the tensors must have the same keys, and the blocks with the same key
+    must have the same properties.
+
+    The gradients are not used in this computation.
+
+    :param tensor: a :py:class:`TensorMap`, or list of :py:class:`TensorMap`
+
+    :return: a :py:class:`TensorMap` with the same keys as the input, where
+        each :py:class:`TensorBlock` has both ``samples`` and ``properties``
+        equal to the ``properties`` of the corresponding input block, and no
+        components.
+    """
+    tensors = _check_tensors(tensor, "gram")
+
+    blocks = []
+    for key, block in tensors[0]:
+        values = _gram_values(block)
+        for other in tensors[1:]:
+            values = values + _gram_values(other.block(key))
+
+        blocks.append(_properties_square_block(values, block))
+
+    return TensorMap(tensors[0].keys, blocks)
+
+
+def covariance(
+    tensor: Union[TensorMap, List[TensorMap]], correction: int = 1
+) -> TensorMap:
+    """Compute the covariance matrix of the properties of each block in a
+    :py:class:`TensorMap`.
+
+    As in :py:func:`gram`, each sample and component of a block is used as an
+    observation of the properties, and the result for each block is a
+    ``n_properties x n_properties`` matrix. The data is centered with the mean
+    over all the observations before computing the covariance.
+
+    A list of :py:class:`TensorMap` can be given to accumulate the covariance
+    across all of them. The data is then centered using the mean of all the
+    tensors together.
+
+    The gradients are not used in this computation.
+
+    :param tensor: a :py:class:`TensorMap`, or list of :py:class:`TensorMap`
+    :param correction: difference between the number of observations and the
+        divisor used to normalize the covariance. The default value of 1 gives
+        the unbiased estimator of the covariance.
+
+    :return: a :py:class:`TensorMap` with the same keys as the input, where
+        each :py:class:`TensorBlock` has both ``samples`` and ``properties``
+        equal to the ``properties`` of the corresponding input block, and no
+        components.
+    """
+    tensors = _check_tensors(tensor, "covariance")
+
+    blocks = []
+    for key, block in tensors[0]:
+        values = _gram_values(block)
+        total = _as_matrix(block.values).sum(0)
+        count = _as_matrix(block.values).shape[0]
+        for other in tensors[1:]:
+            other_block = other.block(key)
+            values = values + _gram_values(other_block)
+            total = total + _as_matrix(other_block.values).sum(0)
+            count += _as_matrix(other_block.values).shape[0]
+
+        if count <= correction:
+            raise ValueError(
+                f"can not compute the covariance with correction={correction} "
+                f"from {count} observations in block {tuple(int(k) for k in key)}"
+            )
+
+        mean = total / count
+        values = values - count * (mean.reshape(-1, 1) * mean.reshape(1, -1))
+        values = values / (count - correction)
+
+        blocks.append(_properties_square_block(values, block))
+
+    return TensorMap(tensors[0].keys, blocks)
+
+
+def _check_tensors(
+    tensor: Union[TensorMap, List[TensorMap]], fname: str
+) -> List[TensorMap]:
+    """
+    Checks the arguments passed to :py:func:`gram` and :py:func:`covariance`,
+    and return them as a list.
+    """
+    if isinstance(tensor, TensorMap):
+        return [tensor]
+
+    if not isinstance(tensor, (list, tuple)) or len(tensor) == 0:
+        raise TypeError(
+            f"`tensor` in {fname} must be a TensorMap or a non-empty list of "
+            "TensorMap"
+        )
+
+    for other in tensor:
+        if not isinstance(other, TensorMap):
+            raise TypeError(
+                f"`tensor` in {fname} must be a TensorMap or a non-empty list of "
+                "TensorMap"
+            )
+
+    first = tensor[0]
+    for other in tensor[1:]:
+        _check_maps(first, other, fname)
+        for key, block in first:
+            _check_blocks(block, other.block(key), props=["properties"], fname=fname)
+
+    return list(tensor)
+
+
+def _as_matrix(values):
+    """Reshape values to a 2-dimensional array of shape (-1, n_properties)"""
+    return values.reshape(-1, values.shape[-1])
+
+
+def _gram_values(block: TensorBlock):
+    matrix = _as_matrix(block.values)
+    # matrix.T @ matrix
+    return _dispatch.dot(matrix.T, matrix.T)
+
+
+def _properties_square_block(values, block: TensorBlock) -> TensorBlock:
+    return TensorBlock(
+        values=values,
+        samples=block.properties,
+        components=[],
+        properties=block.properties,
+    )
@@ -0,0 +1,37 @@
+import ctypes
+from typing import Tuple
+
+from .._c_api import eqs_tensormap_t
+from .._c_lib import _get_library
+from ..tensor import TensorMap
+
+
+def intersect_samples(
+    first: TensorMap, second: TensorMap
+) -> Tuple[TensorMap, TensorMap]:
+    """
+    Restrict both ``first`` and ``second`` to the samples they have in common.
+
+    Blocks are matched by key, and both :py:class:`TensorMap` must contain the
+    same set of keys. For each pair of blocks, only the samples present in both
+    blocks are kept, in the order they appear in the block from ``first``; and
+    gradients are updated accordingly.
+
+    :param first: first :py:class:`TensorMap`
+    :param second: second :py:class:`TensorMap`
+
+    :return: a tuple containing the restricted versions of ``first`` and
+        ``second``
+    """
+    if not isinstance(first, TensorMap) or not isinstance(second, TensorMap):
+        raise TypeError("both inputs to intersect_samples must be TensorMap")
+
+    lib = _get_library()
+
+    first_output = ctypes.POINTER(eqs_tensormap_t)()
+    second_output = ctypes.POINTER(eqs_tensormap_t)()
+    lib.eqs_tensormap_intersect_samples(
+        first._ptr, second._ptr, first_output, second_output, None, 0
+    )
+
+    return TensorMap._from_ptr(first_output), TensorMap._from_ptr(second_output)
@@ -6,10 +6,18 @@ import numpy as np
 
 from ..labels import Labels
 from ..tensor import TensorBlock, TensorMap
-from .equal_metadata import _check_maps
+from . import _dispatch
+from ._missing_gradients import _check_missing_gradients, _unify_gradients
+from .equal_metadata import (
+    _check_blocks,
+    _check_maps,
+    _check_same_gradients,
+    _check_same_units,
+    _copy_units,
+)
 
 
-def join(tensor_maps: List[TensorMap], axis: str):
+def join(tensor_maps: List[TensorMap], axis: str, missing_gradients: str = "error"):
     """Join a sequence of :py:class:`TensorMap` along an axis.
 
     The ``axis`` parameter specifies the type join. For example, if
@@ -26,6 +34,12 @@ def join(tensor_maps: List[TensorMap], axis: str):
     :param axis:
         A string indicating how the tensormaps are stacked. Allowed
         values are ``'properties'`` or ``'samples'``.
+    :param missing_gradients:
+        How to handle gradients which are only present in some of the
+        ``tensor_maps``. With ``'error'`` an error is raised; with ``'zero'``
+        the missing gradients are created without any samples, i.e. filled
+        with zeros; and with ``'drop'`` these gradients are removed from the
+        joined :py:class:`TensorMap`.
 
     :return tensor_joined:
         The stacked :py:class:`TensorMap` with more properties or samples
@@ -46,12 +60,16 @@ def join(tensor_maps: List[TensorMap], axis: str):
             "valid values for the `axis` parameter."
         )
 
+    _check_missing_gradients(missing_gradients, "join")
+
     if len(tensor_maps) == 1:
         return tensor_maps[0]
 
     for ts_to_join in tensor_maps[1:]:
         _check_maps(tensor_maps[0], ts_to_join, "join")
 
+    tensor_maps = _unify_gradients(tensor_maps, missing_gradients)
+
     # Deduce if sample/property names are the same in all tensor_maps.
     # If this is not the case we have to change unify the corresponding labels later.
     if axis == "samples":
@@ -113,6 +131,9 @@ def join(tensor_maps: List[TensorMap], axis: str):
     keys = Labels(names=keys_names, values=np.array(keys_values))
     tensor = TensorMap(keys=keys, blocks=blocks)
 
+    # the components of the blocks to join are checked when merging the blocks
+    # in `keys_to_samples`/`keys_to_properties`, which report the key of the first
+    # block and the component entry that differs from the others
     if axis == "samples":
         tensor_joined = tensor.keys_to_samples("tensor")
     else:
@@ -122,3 +143,159 @@ def join(tensor_maps: List[TensorMap], axis: str):
     # remove the `tensor` label entry after joining.
 
     return tensor_joined
+
+
+def join_components(tensor_maps: List[TensorMap], component: int = 0) -> TensorMap:
+    """Join a sequence of :py:class:`TensorMap` along one of the components.
+
+    This is useful to combine data that was produced separately for the
+    different entries of a component, such as the x/y/z cartesian directions.
+    All the ``tensor_maps`` must have the same keys, and the corresponding
+    blocks must have the same samples, properties and component names. The
+    blocks are then concatenated along the ``component`` axis, and the new
+    component labels contain an additional ``tensor`` dimension, specifying the
+    index of the original :py:class:`TensorMap` in ``tensor_maps``.
+
+    Gradients are joined in the same way, and must have the same samples in all
+    ``tensor_maps``.
+
+    :param tensor_maps: sequence of :py:class:`TensorMap` to join
+    :param component: index of the component axis along which to join the
+        blocks
+
+    :return: a new :py:class:`TensorMap` with the joined blocks
+
+    >>> import numpy as np
+    >>> from equistore import Labels
+    >>> block_x = TensorBlock(
+    ...     values=np.array([[[1.0]], [[2.0]]]),
+    ...     samples=Labels.arange("atom", 2),
+    ...     components=[Labels(["direction"], np.array([[0]]))],
+    ...     properties=Labels.arange("property", 1),
+    ... )
+    >>> block_y = TensorBlock(
+    ...     values=np.array([[[3.0]], [[4.0]]]),
+    ...     samples=Labels.arange("atom", 2),
+    ...     components=[Labels(["direction"], np.array([[1]]))],
+    ...     properties=Labels.arange("property", 1),
+    ... )
+    >>> keys = Labels.single()
+    >>> joined = join_components(
+    ...     [TensorMap(keys, [block_x]), TensorMap(keys, [block_y])]
+    ... )
+    >>> joined.block().values[:, :, 0]
+    array([[1., 3.],
+           [2., 4.]])
+    >>> joined.block().components[0].names
+    ('tensor', 'direction')
+    """
+    if not isinstance(tensor_maps, (list, tuple)):
+        raise TypeError(
+            "the `TensorMap`s to join must be provided as a list or a tuple"
+        )
+
+    if len(tensor_maps) < 1:
+        raise ValueError("provide at least one `TensorMap` for joining")
+
+    for tensor in tensor_maps[1:]:
+        _check_maps(tensor_maps[0], tensor, "join_components")
+
+    blocks = []
+    for key in tensor_maps[0].keys:
+        blocks.append(
+            join_components_block(
+                [tensor.block(key) for tensor in tensor_maps], component
+            )
+        )
+
+    return TensorMap(tensor_maps[0].keys, blocks)
+
+
+def join_components_block(blocks: List[TensorBlock], component: int = 0) -> TensorBlock:
+    """Join a sequence of :py:class:`TensorBlock` along one of the components.
+
+    See :py:func:`join_components` for more information.
+
+    :param blocks: sequence of :py:class:`TensorBlock` to join
+    :param component: index of the component axis along which to join the
+        blocks
+
+    :return: a new :py:class:`TensorBlock` with the joined data
+    """
+    if not isinstance(blocks, (list, tuple)):
+        raise TypeError(
+            "the `TensorBlock`s to join must be provided as a list or a tuple"
+        )
+
+    if len(blocks) < 1:
+        raise ValueError("provide at least one `TensorBlock` for joining")
+
+    first = blocks[0]
+    n_components = len(first.components)
+    if component < 0 or component >= n_components:
+        raise ValueError(
+            f"invalid component index {component} for blocks with "
+            f"{n_components} components"
+        )
+
+    for block in blocks[1:]:
+        _check_blocks(first, block, ["samples", "properties"], "join_components")
+        _check_same_gradients(first, block, ["samples"], "join_components")
+        _check_same_units(first, block, "join_components")
+
+        if len(block.components) != n_components or any(
+            a.names != b.names for a, b in zip(first.components, block.components)
+        ):
+            raise ValueError(
+                "Inputs to 'join_components' should have the same component names"
+            )
+
+    new_component = _join_component_labels(
+        [block.components[component] for block in blocks]
+    )
+
+    components = list(first.components)
+    components[component] = new_component
+
+    values = _dispatch.concatenate(
+        [block.values for block in blocks], axis=1 + component
+    )
+    result = TensorBlock(
+        values=values,
+        samples=first.samples,
+        components=components,
+        properties=first.properties,
+    )
+    _copy_units(result, first)
+
+    for parameter, gradient in first.gradients():
+        # gradients can have additional components before the values components
+        n_extra = len(gradient.components) - n_components
+        gradient_components = list(gradient.components)
+        gradient_components[n_extra + component] = new_component
+
+        data = _dispatch.concatenate(
+            [block.gradient(parameter).data for block in blocks],
+            axis=1 + n_extra + component,
+        )
+        result.add_gradient(
+            parameter,
+            data=data,
+            samples=gradient.samples,
+            components=gradient_components,
+        )
+
+    return result
+
+
+def _join_component_labels(components: List[Labels]) -> Labels:
+    """
+    Concatenate the given component ``Labels``, adding a ``tensor`` dimension
+    containing the index of the original labels in the list.
+    """
+    names = ("tensor",) + components[0].names
+    values = []
+    for i, labels in enumerate(components):
+        values += [(i,) + entry for entry in labels.tolist()]
+
+    return Labels(names=names, values=np.array(values, dtype=np.int32))
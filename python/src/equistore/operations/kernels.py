@@ -0,0 +1,98 @@
+from ..block import TensorBlock
+from ..tensor import TensorMap
+from .dot import _dot_block
+from .equal_metadata import _check_maps
+
+
+def linear_kernel(A: TensorMap, B: TensorMap) -> TensorMap:
+    """Compute the linear kernel between the samples of two
+    :py:class:`TensorMap`.
+
+    For each key, the kernel block is ``K = block_A.values @ block_B.values.T``,
+    i.e. the scalar product between the features of each sample of ``A`` and
+    each sample of ``B``. This is the same as :py:func:`equistore.dot`.
+
+    The two :py:class:`TensorMap` must have the same keys, and the blocks with
+    the same key must have the same properties. The blocks of ``B`` must not
+    have components or gradients. If the blocks of ``A`` have gradients, the
+    corresponding gradients of the kernel are computed as well.
+
+    :param A: first :py:class:`TensorMap`, typically the features of the
+        structures/environments to predict
+    :param B: second :py:class:`TensorMap`, typically the features of the
+        support/training points
+
+    :return: a :py:class:`TensorMap` with the same keys as ``A`` and ``B``,
+        where each :py:class:`TensorBlock` has the ``samples`` and
+        ``components`` of ``A`` and the ``samples`` of ``B`` as ``properties``
+    """
+    return polynomial_kernel(A, B, degree=1)
+
+
+def polynomial_kernel(A: TensorMap, B: TensorMap, degree: int) -> TensorMap:
+    """Compute the polynomial kernel between the samples of two
+    :py:class:`TensorMap`.
+
+    For each key, the kernel block is ``K = (block_A.values @
+    block_B.values.T) ** degree``, where the power is taken element-wise. The
+    requirements on ``A`` and ``B`` are the same as for
+    :py:func:`linear_kernel`.
+
+    If the blocks of ``A`` have gradients, the gradients of the kernel are
+    computed with the chain rule: ``dK/dr = degree * (A @ B.T) ** (degree - 1)
+    * (dA/dr @ B.T)``.
+
+    :param A: first :py:class:`TensorMap`
+    :param B: second :py:class:`TensorMap`
+    :param degree: degree of the polynomial kernel, must be a positive integer
+
+    :return: a :py:class:`TensorMap` with the same keys as ``A`` and ``B``,
+        where each :py:class:`TensorBlock` has the ``samples`` and
+        ``components`` of ``A`` and the ``samples`` of ``B`` as ``properties``
+    """
+    if not isinstance(degree, int) or degree <= 0:
+        raise ValueError(f"degree must be a positive integer, got {degree}")
+
+    _check_maps(A, B, "polynomial_kernel")
+
+    blocks = []
+    for key, block1 in A:
+        block2 = B.block(key)
+        blocks.append(_polynomial_kernel_block(block1, block2, degree))
+
+    return TensorMap(A.keys, blocks)
+
+
+def _polynomial_kernel_block(
+    block1: TensorBlock, block2: TensorBlock, degree: int
+) -> TensorBlock:
+    linear = _dot_block(block1=block1, block2=block2)
+    if degree == 1:
+        return linear
+
+    result_block = TensorBlock(
+        values=linear.values**degree,
+        samples=linear.samples,
+        components=linear.components,
+        properties=linear.properties,
+    )
+
+    for parameter, gradient in linear.gradients():
+        gradient_data = gradient.data
+        # number of gradient components, which are not in the values
+        diff_components = len(gradient_data.shape) - len(linear.values.shape)
+
+        # d(X^n)/dr = n X^(n - 1) dX/dr
+        factor = degree * linear.values[gradient.samples["sample"]] ** (degree - 1)
+        factor = factor.reshape(
+            (factor.shape[0],) + (1,) * diff_components + factor.shape[1:]
+        )
+
+        result_block.add_gradient(
+            parameter,
+            factor * gradient_data,
+            gradient.samples,
+            gradient.components,
+        )
+
+    return result_block
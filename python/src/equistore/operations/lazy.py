@@ -0,0 +1,145 @@
+from .._c_lib import _get_library
+from ..block import TensorBlock
+from ..status import _check_pointer
+from ..tensor import TensorMap
+from ._missing_gradients import _check_missing_gradients
+
+
+class LazyTensorMap:
+    """Chain of element-wise operations on a :py:class:`TensorMap`, which are
+    only evaluated when calling :py:meth:`LazyTensorMap.compute` or
+    :py:meth:`LazyTensorMap.compute_block`.
+
+    All the recorded operations are applied in a single pass over the data,
+    without allocating intermediary arrays. Gradients are propagated through all
+    operations. The operations are implemented in the C API, and only support
+    tensor maps containing numpy arrays.
+
+    .. code-block:: python
+
+        # compute `2 * tensor + tensor` without any intermediary TensorMap
+        lazy = LazyTensorMap(tensor)
+        lazy.multiply_scalar(2.0).add(tensor)
+        result = lazy.compute()
+
+    All the methods recording an operation return the lazy tensor map itself,
+    and can be chained.
+    """
+
+    def __init__(self, tensor: TensorMap):
+        """
+        :param tensor: source of the chain of operations
+        """
+        if not isinstance(tensor, TensorMap):
+            raise TypeError(f"`tensor` must be a TensorMap, not {type(tensor)}")
+
+        self._lib = _get_library()
+        # the lazy tensor map keeps pointers to all the tensor maps used in the
+        # operations, keep them alive as long as this object
+        self._tensors = [tensor]
+
+        self._ptr = self._lib.eqs_lazy_tensormap(tensor._ptr)
+        _check_pointer(self._ptr)
+
+    def __del__(self):
+        if hasattr(self, "_lib") and self._lib is not None and hasattr(self, "_ptr"):
+            self._lib.eqs_lazy_tensormap_free(self._ptr)
+
+    def missing_gradients(self, missing_gradients: str) -> "LazyTensorMap":
+        """
+        Set the policy used by the binary operations recorded after this call
+        when only some of the tensor maps contain gradients with respect to a
+        given parameter. The default is ``"error"``.
+
+        :param missing_gradients: how to handle gradients only present in some
+            of the tensor maps. With ``"error"``, an exception is raised. With
+            ``"zero"``, missing gradients are treated as zero-filled, with the
+            same metadata as in the other tensor maps. With ``"drop"``, only the
+            gradients present in all tensor maps are kept.
+        """
+        _check_missing_gradients(missing_gradients, "LazyTensorMap")
+        self._lib.eqs_lazy_tensormap_missing_gradients(
+            self._ptr, missing_gradients.encode("utf8")
+        )
+        return self
+
+    def broadcast_keys(self, broadcast: bool = True) -> "LazyTensorMap":
+        """
+        Allow the binary operations recorded after this call to use tensor maps
+        whose keys only contain a subset of the keys dimensions of the source
+        tensor map. Each block of the source is then combined with the block of
+        the other tensor map with the same values for the common keys
+        dimensions. By default, both tensor maps must have the same keys.
+
+        :param broadcast: whether to broadcast keys in binary operations
+        """
+        self._lib.eqs_lazy_tensormap_broadcast_keys(self._ptr, bool(broadcast))
+        return self
+
+    def _unary(self, operation: str, value: float = 0.0) -> "LazyTensorMap":
+        self._lib.eqs_lazy_tensormap_unary(
+            self._ptr, operation.encode("utf8"), float(value)
+        )
+        return self
+
+    def add_scalar(self, value: float) -> "LazyTensorMap":
+        """Record the addition of the scalar ``value``"""
+        return self._unary("add_scalar", value)
+
+    def multiply_scalar(self, value: float) -> "LazyTensorMap":
+        """Record the multiplication by the scalar ``value``"""
+        return self._unary("multiply_scalar", value)
+
+    def pow(self, value: float) -> "LazyTensorMap":
+        """Record raising all elements to the power ``value``"""
+        return self._unary("pow", value)
+
+    def abs(self) -> "LazyTensorMap":
+        """Record taking the absolute value of all elements"""
+        return self._unary("abs")
+
+    def sqrt(self) -> "LazyTensorMap":
+        """Record taking the square root of all elements"""
+        return self._unary("sqrt")
+
+    def exp(self) -> "LazyTensorMap":
+        """Record taking the exponential of all elements"""
+        return self._unary("exp")
+
+    def log(self) -> "LazyTensorMap":
+        """Record taking the natural logarithm of all elements"""
+        return self._unary("log")
+
+    def add(self, other: TensorMap) -> "LazyTensorMap":
+        """
+        Record the addition of ``other``, which must have the same metadata as
+        the source tensor map.
+        """
+        if not isinstance(other, TensorMap):
+            raise TypeError(f"`other` must be a TensorMap, not {type(other)}")
+
+        self._lib.eqs_lazy_tensormap_add(self._ptr, other._ptr)
+        self._tensors.append(other)
+        return self
+
+    def multiply(self, other: TensorMap) -> "LazyTensorMap":
+        """
+        Record the element-wise multiplication by ``other``, which must have the
+        same metadata as the source tensor map.
+        """
+        if not isinstance(other, TensorMap):
+            raise TypeError(f"`other` must be a TensorMap, not {type(other)}")
+
+        self._lib.eqs_lazy_tensormap_multiply(self._ptr, other._ptr)
+        self._tensors.append(other)
+        return self
+
+    def compute(self) -> TensorMap:
+        """Evaluate all the recorded operations for all blocks"""
+        ptr = self._lib.eqs_lazy_tensormap_compute(self._ptr)
+        return TensorMap._from_ptr(ptr)
+
+    def compute_block(self, index: int) -> TensorBlock:
+        """Evaluate all the recorded operations for the block at ``index`` only"""
+        ptr = self._lib.eqs_lazy_tensormap_compute_block(self._ptr, index)
+        return TensorBlock._from_ptr(ptr, parent=None)
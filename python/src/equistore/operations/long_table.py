@@ -0,0 +1,187 @@
+"""
+Module for converting TensorMaps to and from long-format tables
+"""
+import re
+
+import numpy as np
+
+from ..block import TensorBlock
+from ..labels import Labels
+from ..tensor import TensorMap
+
+
+_COMPONENT_COLUMN = re.compile(r"^component_(\d+)_(.+)$")
+
+
+def to_long_table(tensor: TensorMap) -> np.ndarray:
+    """
+    Convert the values of a :py:class:`TensorMap` to a long-format table, with
+    one row for each entry in the values arrays.
+
+    The table is returned as a numpy structured array, with one ``int32`` column
+    for each dimension of the keys, samples, components and properties, and a
+    final ``float64`` column named ``"value"``. Columns are prefixed by the
+    corresponding metadata, i.e. ``key_<name>``, ``sample_<name>``,
+    ``component_<i>_<name>`` and ``property_<name>``, to prevent name collisions.
+
+    This table can be used directly with pandas (``pandas.DataFrame(table)``),
+    polars (``polars.from_numpy(table)``) or pyarrow, and from there exported to
+    CSV, Arrow, SQL, *etc.* Use :py:func:`equistore.from_long_table` to convert
+    it back to a :py:class:`TensorMap`.
+
+    Gradients are not included in the table, and blocks without any values
+    (i.e. with no samples, components or properties) can not be represented.
+
+    :param tensor: input :py:class:`TensorMap`
+    :returns: a numpy structured array containing the long-format table
+    """
+    block = tensor.block(0)
+
+    columns = [f"key_{name}" for name in tensor.keys.names]
+    columns += [f"sample_{name}" for name in block.samples.names]
+    for i, component in enumerate(block.components):
+        columns += [f"component_{i}_{name}" for name in component.names]
+    columns += [f"property_{name}" for name in block.properties.names]
+
+    dtype = [(name, np.int32) for name in columns] + [("value", np.float64)]
+
+    tables = []
+    for key, block in tensor:
+        values = np.asarray(block.values)
+        table = np.empty(values.size, dtype=dtype)
+
+        indices = np.unravel_index(np.arange(values.size), values.shape)
+        all_labels = [block.samples] + block.components + [block.properties]
+
+        column = 0
+        for name in tensor.keys.names:
+            table[columns[column]] = key[name]
+            column += 1
+
+        for labels, index in zip(all_labels, indices):
+            labels_values = labels.asarray()[index]
+            for i in range(labels_values.shape[1]):
+                table[columns[column]] = labels_values[:, i]
+                column += 1
+
+        table["value"] = values.reshape(-1)
+        tables.append(table)
+
+    return np.concatenate(tables)
+
+
+def from_long_table(table) -> TensorMap:
+    """
+    Create a :py:class:`TensorMap` from a long-format table, as produced by
+    :py:func:`equistore.to_long_table`.
+
+    The ``table`` can be a numpy structured array, a ``dict`` of arrays, or a
+    pandas/polars ``DataFrame``. The columns should follow the naming
+    conventions of :py:func:`equistore.to_long_table`.
+
+    Keys, samples, components and properties are created in the order in which
+    they first appear in the table. Entries missing from the table are set to
+    zero in the values.
+
+    :param table: long-format table to convert
+    :returns: a new :py:class:`TensorMap` containing the data in the table
+    """
+    names = _column_names(table)
+    if "value" not in names:
+        raise ValueError("the long-format table must contain a 'value' column")
+
+    keys_names = []
+    samples_names = []
+    components_names = {}
+    properties_names = []
+    for name in names:
+        if name == "value":
+            continue
+        elif name.startswith("key_"):
+            keys_names.append((name, name[len("key_") :]))
+        elif name.startswith("sample_"):
+            samples_names.append((name, name[len("sample_") :]))
+        elif name.startswith("property_"):
+            properties_names.append((name, name[len("property_") :]))
+        else:
+            match = _COMPONENT_COLUMN.match(name)
+            if match is None:
+                raise ValueError(f"unexpected column '{name}' in long-format table")
+            components = components_names.setdefault(int(match.group(1)), [])
+            components.append((name, match.group(2)))
+
+    if sorted(components_names.keys()) != list(range(len(components_names))):
+        raise ValueError("component columns in long-format table are not contiguous")
+    components_names = [components_names[i] for i in range(len(components_names))]
+
+    for kind, kind_names in [
+        ("key", keys_names),
+        ("sample", samples_names),
+        ("property", properties_names),
+    ]:
+        if len(kind_names) == 0:
+            raise ValueError(f"the long-format table must contain {kind} columns")
+
+    values = np.asarray(table["value"], dtype=np.float64)
+
+    all_keys, keys_index = _unique_in_order(_columns(table, keys_names))
+    blocks = []
+    for key_i in range(len(all_keys)):
+        mask = keys_index == key_i
+
+        shape = []
+        indices = []
+        all_labels = []
+        for kind_names in [samples_names] + components_names + [properties_names]:
+            unique, index = _unique_in_order(_columns(table, kind_names)[mask])
+            shape.append(len(unique))
+            indices.append(index)
+            all_labels.append(Labels([name for _, name in kind_names], unique))
+
+        block_values = np.zeros(shape, dtype=np.float64)
+        block_values[tuple(indices)] = values[mask]
+
+        blocks.append(
+            TensorBlock(
+                values=block_values,
+                samples=all_labels[0],
+                components=all_labels[1:-1],
+                properties=all_labels[-1],
+            )
+        )
+
+    keys = Labels([name for _, name in keys_names], all_keys)
+    return TensorMap(keys, blocks)
+
+
+def _column_names(table):
+    if isinstance(table, np.ndarray):
+        if table.dtype.names is None:
+            raise TypeError("expected a structured array for the long-format table")
+        return list(table.dtype.names)
+    elif hasattr(table, "columns"):
+        return [str(name) for name in table.columns]
+    else:
+        return list(table.keys())
+
+
+def _columns(table, names):
+    """Get the columns of ``table`` for the given ``(column, name)`` pairs"""
+    columns = [np.asarray(table[column], dtype=np.int32) for column, _ in names]
+    return np.stack(columns, axis=1)
+
+
+def _unique_in_order(values):
+    """
+    Get the unique rows in ``values`` in order of first appearance, and the
+    index of each row of ``values`` in the unique rows.
+    """
+    unique, first, inverse = np.unique(
+        values, axis=0, return_index=True, return_inverse=True
+    )
+    order = np.argsort(first)
+
+    rank = np.empty_like(order)
+    rank[order] = np.arange(len(order))
+
+    return unique[order], rank[inverse.reshape(-1)]
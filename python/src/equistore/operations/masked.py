@@ -0,0 +1,374 @@
+"""
+Masked operations
+=================
+
+These functions allow to ignore some of the entries of a :py:class:`TensorMap`,
+for example when some of the targets are missing (structures without forces,
+partially labeled datasets, *etc.*). The entries to use are given by a *mask*:
+a :py:class:`TensorMap` with the same keys, samples, components and properties
+as the data, containing boolean (or ``uint8``) values. Entries where the mask is
+``True`` (or non-zero) are used, and entries where the mask is ``False`` (or
+zero) are ignored, regardless of the value they contain (including ``NaN``).
+Gradients of the mask are never used.
+
+Applying a mask
+---------------
+
+.. autofunction:: equistore.apply_mask
+
+.. autofunction:: equistore.apply_mask_block
+
+Masked reductions
+-----------------
+
+.. autofunction:: equistore.masked_sum_over_samples
+
+.. autofunction:: equistore.masked_mean_over_samples
+
+.. autofunction:: equistore.masked_sum_over_samples_block
+
+.. autofunction:: equistore.masked_mean_over_samples_block
+
+Masked arithmetic
+-----------------
+
+.. autofunction:: equistore.masked_add
+
+.. autofunction:: equistore.masked_subtract
+
+.. autofunction:: equistore.masked_multiply
+
+.. autofunction:: equistore.masked_divide
+"""
+from typing import List, Union
+
+import numpy as np
+
+from ..block import TensorBlock
+from ..tensor import TensorMap
+from . import _dispatch
+from .add import add
+from .divide import divide
+from .equal_metadata import _check_blocks, _check_maps, _copy_units
+from .multiply import multiply
+from .reduce_over_samples import sum_over_samples_block
+from .subtract import subtract
+
+
+def _mask_values(block: TensorBlock, mask: TensorBlock, fname: str):
+    """Check the metadata of ``mask`` against ``block``, and get the mask
+    values as an array of booleans"""
+    _check_blocks(
+        block,
+        mask,
+        props=["samples", "components", "properties"],
+        fname=fname,
+    )
+    return mask.values != 0
+
+
+def _gradient_mask(values_mask, values, gradient):
+    """Get a mask which can be broadcasted against ``gradient.data``, from the
+    mask on the ``values``"""
+    gradient_mask = values_mask[gradient.samples["sample"]]
+    # gradients can have additional components before the values' components
+    n_extra = len(gradient.data.shape) - len(values.shape)
+    shape = (gradient_mask.shape[0],) + (1,) * n_extra + tuple(values.shape[1:])
+    return gradient_mask.reshape(shape)
+
+
+def apply_mask_block(
+    block: TensorBlock, mask: TensorBlock, fill_value: float = 0.0
+) -> TensorBlock:
+    """Replace the entries of ``block`` where ``mask`` is ``False`` by
+    ``fill_value``.
+
+    The corresponding entries in the gradients are set to zero.
+
+    :param block: input :py:class:`TensorBlock`
+    :param mask: :py:class:`TensorBlock` with the same samples, components and
+        properties as ``block``, containing boolean or ``uint8`` values
+    :param fill_value: value to use for the masked entries
+
+    :returns: a new :py:class:`TensorBlock` with the same metadata as ``block``
+    """
+    values_mask = _mask_values(block, mask, "apply_mask")
+
+    result = TensorBlock(
+        values=_dispatch.where(values_mask, block.values, fill_value),
+        samples=block.samples,
+        components=block.components,
+        properties=block.properties,
+    )
+    _copy_units(result, block)
+
+    for parameter, gradient in block.gradients():
+        gradient_mask = _gradient_mask(values_mask, block.values, gradient)
+        result.add_gradient(
+            parameter,
+            _dispatch.where(gradient_mask, gradient.data, 0.0),
+            gradient.samples,
+            gradient.components,
+        )
+
+    return result
+
+
+def apply_mask(
+    tensor: TensorMap, mask: TensorMap, fill_value: float = 0.0
+) -> TensorMap:
+    """Replace the entries of ``tensor`` where ``mask`` is ``False`` by
+    ``fill_value``, calling :py:func:`apply_mask_block` on all blocks.
+
+    >>> from equistore import Labels
+    >>> block = TensorBlock(
+    ...     values=np.array([[1.0, np.nan], [3.0, 4.0]]),
+    ...     samples=Labels.arange("structure", 2),
+    ...     components=[],
+    ...     properties=Labels.arange("properties", 2),
+    ... )
+    >>> mask = TensorBlock(
+    ...     values=np.array([[True, False], [True, True]]),
+    ...     samples=Labels.arange("structure", 2),
+    ...     components=[],
+    ...     properties=Labels.arange("properties", 2),
+    ... )
+    >>> keys = Labels(names=["key"], values=np.array([[0]]))
+    >>> tensor = apply_mask(TensorMap(keys, [block]), TensorMap(keys, [mask]))
+    >>> print(tensor.block(0).values)
+    [[1. 0.]
+     [3. 4.]]
+
+    :param tensor: input :py:class:`TensorMap`
+    :param mask: :py:class:`TensorMap` with the same metadata as ``tensor``,
+        containing boolean or ``uint8`` values
+    :param fill_value: value to use for the masked entries
+
+    :returns: a new :py:class:`TensorMap` with the same metadata as ``tensor``
+    """
+    _check_maps(tensor, mask, "apply_mask")
+
+    blocks = []
+    for key, block in tensor:
+        blocks.append(apply_mask_block(block, mask.block(key), fill_value))
+
+    return TensorMap(tensor.keys, blocks)
+
+
+def masked_sum_over_samples_block(
+    block: TensorBlock, mask: TensorBlock, sample_names: Union[List[str], str]
+) -> TensorBlock:
+    """Sum a :py:class:`TensorBlock` over ``sample_names``, ignoring the entries
+    where ``mask`` is ``False``.
+
+    See :py:func:`sum_over_samples_block` for more information on the
+    reduction.
+
+    :param block: input :py:class:`TensorBlock`
+    :param mask: :py:class:`TensorBlock` with the same samples, components and
+        properties as ``block``, containing boolean or ``uint8`` values
+    :param sample_names: names of samples to sum over
+
+    :returns: a :py:class:`TensorBlock` containing the reduced values and
+        sample labels
+    """
+    return sum_over_samples_block(
+        apply_mask_block(block, mask, fill_value=0.0), sample_names
+    )
+
+
+def masked_mean_over_samples_block(
+    block: TensorBlock, mask: TensorBlock, sample_names: Union[List[str], str]
+) -> TensorBlock:
+    """Average a :py:class:`TensorBlock` over ``sample_names``, ignoring the
+    entries where ``mask`` is ``False``.
+
+    Each entry of the result is divided by the number of entries which are not
+    masked. If all the entries contributing to an output entry are masked, the
+    output is ``NaN``.
+
+    :param block: input :py:class:`TensorBlock`
+    :param mask: :py:class:`TensorBlock` with the same samples, components and
+        properties as ``block``, containing boolean or ``uint8`` values
+    :param sample_names: names of samples to average over
+
+    :returns: a :py:class:`TensorBlock` containing the reduced values and
+        sample labels
+    """
+    summed = masked_sum_over_samples_block(block, mask, sample_names)
+
+    values_mask = _mask_values(block, mask, "masked_mean_over_samples")
+    counts = TensorBlock(
+        values=_dispatch.where(
+            values_mask, _dispatch.zeros_like(block.values) + 1, 0.0
+        ),
+        samples=block.samples,
+        components=block.components,
+        properties=block.properties,
+    )
+    counts = sum_over_samples_block(counts, sample_names).values
+
+    # entries without any un-masked sample are 0 / 0 = NaN
+    with np.errstate(divide="ignore", invalid="ignore"):
+        result = TensorBlock(
+            values=summed.values / counts,
+            samples=summed.samples,
+            components=summed.components,
+            properties=summed.properties,
+        )
+
+        for parameter, gradient in summed.gradients():
+            result.add_gradient(
+                parameter,
+                gradient.data / _gradient_mask(counts, summed.values, gradient),
+                gradient.samples,
+                gradient.components,
+            )
+
+    return result
+
+
+def _masked_reduce(tensor: TensorMap, mask: TensorMap, sample_names, reduction):
+    _check_maps(tensor, mask, reduction.__name__)
+
+    if isinstance(sample_names, str):
+        sample_names = [sample_names]
+
+    for sample in sample_names:
+        if sample not in tensor.sample_names:
+            raise ValueError(
+                f"one of the requested sample name ({sample}) is not part of "
+                "this TensorMap"
+            )
+
+    blocks = []
+    for key, block in tensor:
+        blocks.append(reduction(block, mask.block(key), sample_names))
+
+    return TensorMap(tensor.keys, blocks)
+
+
+def masked_sum_over_samples(
+    tensor: TensorMap, mask: TensorMap, sample_names: Union[List[str], str]
+) -> TensorMap:
+    """Sum a :py:class:`TensorMap` over ``sample_names``, ignoring the entries
+    where ``mask`` is ``False``.
+
+    This calls :py:func:`masked_sum_over_samples_block` on all blocks.
+
+    :param tensor: input :py:class:`TensorMap`
+    :param mask: :py:class:`TensorMap` with the same metadata as ``tensor``,
+        containing boolean or ``uint8`` values
+    :param sample_names: names of samples to sum over
+
+    :returns: a :py:class:`TensorMap` containing the reduced values and sample
+        labels
+    """
+    return _masked_reduce(tensor, mask, sample_names, masked_sum_over_samples_block)
+
+
+def masked_mean_over_samples(
+    tensor: TensorMap, mask: TensorMap, sample_names: Union[List[str], str]
+) -> TensorMap:
+    """Average a :py:class:`TensorMap` over ``sample_names``, ignoring the
+    entries where ``mask`` is ``False``.
+
+    This calls :py:func:`masked_mean_over_samples_block` on all blocks.
+
+    :param tensor: input :py:class:`TensorMap`
+    :param mask: :py:class:`TensorMap` with the same metadata as ``tensor``,
+        containing boolean or ``uint8`` values
+    :param sample_names: names of samples to average over
+
+    :returns: a :py:class:`TensorMap` containing the reduced values and sample
+        labels
+    """
+    return _masked_reduce(
+        tensor, mask, sample_names, masked_mean_over_samples_block
+    )
+
+
+def _masked_binary(A, B, mask, operation, fill_value, neutral_value):
+    """Apply ``operation`` to ``A`` and ``B`` after replacing masked entries
+    with ``neutral_value``, and mask the result with ``fill_value``"""
+    A = apply_mask(A, mask, fill_value=neutral_value)
+    if isinstance(B, TensorMap):
+        B = apply_mask(B, mask, fill_value=neutral_value)
+
+    return apply_mask(operation(A, B), mask, fill_value=fill_value)
+
+
+def masked_add(
+    A: TensorMap, B: Union[float, TensorMap], mask: TensorMap, fill_value=0.0
+) -> TensorMap:
+    """Add ``A`` and ``B`` (see :py:func:`add`), ignoring the entries where
+    ``mask`` is ``False``. These entries are set to ``fill_value`` in the
+    result, and the corresponding gradients are set to zero.
+
+    :param A: first :py:class:`TensorMap` for the addition
+    :param B: scalar or :py:class:`TensorMap` with the same metadata as ``A``
+    :param mask: :py:class:`TensorMap` with the same metadata as ``A``,
+        containing boolean or ``uint8`` values
+    :param fill_value: value to use for the masked entries
+
+    :return: new :py:class:`TensorMap` with the same metadata as ``A``
+    """
+    return _masked_binary(A, B, mask, add, fill_value, neutral_value=0.0)
+
+
+def masked_subtract(
+    A: TensorMap, B: Union[float, TensorMap], mask: TensorMap, fill_value=0.0
+) -> TensorMap:
+    """Subtract ``B`` from ``A`` (see :py:func:`subtract`), ignoring the entries
+    where ``mask`` is ``False``. These entries are set to ``fill_value`` in the
+    result, and the corresponding gradients are set to zero.
+
+    This is typically used to compute the residuals between predictions and
+    targets when some targets are missing.
+
+    :param A: first :py:class:`TensorMap` for the subtraction
+    :param B: scalar or :py:class:`TensorMap` with the same metadata as ``A``
+    :param mask: :py:class:`TensorMap` with the same metadata as ``A``,
+        containing boolean or ``uint8`` values
+    :param fill_value: value to use for the masked entries
+
+    :return: new :py:class:`TensorMap` with the same metadata as ``A``
+    """
+    return _masked_binary(A, B, mask, subtract, fill_value, neutral_value=0.0)
+
+
+def masked_multiply(
+    A: TensorMap, B: Union[float, TensorMap], mask: TensorMap, fill_value=0.0
+) -> TensorMap:
+    """Multiply ``A`` and ``B`` (see :py:func:`multiply`), ignoring the entries
+    where ``mask`` is ``False``. These entries are set to ``fill_value`` in the
+    result, and the corresponding gradients are set to zero.
+
+    :param A: first :py:class:`TensorMap` for the multiplication
+    :param B: scalar or :py:class:`TensorMap` with the same metadata as ``A``
+    :param mask: :py:class:`TensorMap` with the same metadata as ``A``,
+        containing boolean or ``uint8`` values
+    :param fill_value: value to use for the masked entries
+
+    :return: new :py:class:`TensorMap` with the same metadata as ``A``
+    """
+    return _masked_binary(A, B, mask, multiply, fill_value, neutral_value=0.0)
+
+
+def masked_divide(
+    A: TensorMap, B: Union[float, TensorMap], mask: TensorMap, fill_value=0.0
+) -> TensorMap:
+    """Divide ``A`` by ``B`` (see :py:func:`divide`), ignoring the entries where
+    ``mask`` is ``False``. These entries are set to ``fill_value`` in the
+    result, and the corresponding gradients are set to zero.
+
+    :param A: first :py:class:`TensorMap` for the division
+    :param B: scalar or :py:class:`TensorMap` with the same metadata as ``A``
+    :param mask: :py:class:`TensorMap` with the same metadata as ``A``,
+        containing boolean or ``uint8`` values
+    :param fill_value: value to use for the masked entries
+
+    :return: new :py:class:`TensorMap` with the same metadata as ``A``
+    """
+    # masked entries of B are set to 1 to avoid divisions by zero
+    return _masked_binary(A, B, mask, divide, fill_value, neutral_value=1.0)
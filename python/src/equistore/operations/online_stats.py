@@ -0,0 +1,177 @@
+from typing import Dict, List, Tuple
+
+import numpy as np
+
+from ..block import TensorBlock
+from ..labels import Labels
+from ..tensor import TensorMap
+from . import _dispatch
+from .equal_metadata import _labels_equal
+
+
+class _BlockStats:
+    """Running count, mean and sum of squared deviations for a single block"""
+
+    def __init__(self, block: TensorBlock):
+        self.components = block.components
+        self.properties = block.properties
+
+        self.count = 0
+        shape = block.values.shape[1:]
+        self.mean = _dispatch.zeros_like(block.values, shape=shape)
+        self.m2 = _dispatch.zeros_like(block.values, shape=shape)
+
+    def update(self, values):
+        batch_count = values.shape[0]
+        if batch_count == 0:
+            return
+
+        batch_mean = values.sum(0) / batch_count
+        batch_m2 = ((values - batch_mean) ** 2).sum(0)
+
+        # combine the statistics of the batch with the current ones, following
+        # Chan et al. parallel version of Welford's algorithm
+        count = self.count + batch_count
+        delta = batch_mean - self.mean
+        self.mean = self.mean + delta * (batch_count / count)
+        self.m2 = self.m2 + batch_m2 + delta**2 * (self.count * batch_count / count)
+        self.count = count
+
+
+class OnlineStats:
+    """Accumulate the mean and variance of the values in multiple
+    :py:class:`TensorMap`, one batch at a time.
+
+    This makes it possible to compute the statistics needed to standardize a
+    dataset which does not fit in memory all at once. The statistics are
+    computed separately for each block (identified by its key), over all the
+    samples seen in all batches, for each component and property. The
+    accumulation uses Welford's algorithm, which is numerically stable even for
+    a large number of samples.
+
+    Gradients are not included in the statistics.
+
+    .. code-block:: python
+
+        stats = OnlineStats()
+        for batch in Batcher(tensor, batch_size=16):
+            stats.update(batch)
+
+        mean = stats.mean()
+        std = stats.std()
+
+    The :py:class:`TensorMap` returned by :py:meth:`mean`, :py:meth:`var` and
+    :py:meth:`std` have one block for each key seen in any of the batches, each
+    containing a single sample (with :py:meth:`Labels.single` as samples
+    labels), and the same components and properties as the input blocks.
+    """
+
+    def __init__(self):
+        self._key_names = None
+        self._stats: Dict[Tuple[int, ...], _BlockStats] = {}
+
+    def update(self, tensor: TensorMap):
+        """Add all the samples in ``tensor`` to the accumulated statistics.
+
+        The blocks in ``tensor`` must have the same components and properties
+        as the blocks with the same key in the previous batches.
+
+        :param tensor: new batch of data
+        """
+        if not isinstance(tensor, TensorMap):
+            raise TypeError("`tensor` must be an equistore `TensorMap`")
+
+        if self._key_names is None:
+            self._key_names = tensor.keys.names
+        elif tensor.keys.names != self._key_names:
+            raise ValueError(
+                f"the keys names of this tensor ({tensor.keys.names}) do not match "
+                f"the keys names of the previous batches ({self._key_names})"
+            )
+
+        for key, block in tensor:
+            key = tuple(int(k) for k in key)
+            if key not in self._stats:
+                self._stats[key] = _BlockStats(block)
+
+            stats = self._stats[key]
+            _check_same_metadata(stats, block, key)
+            stats.update(block.values)
+
+    def count(self) -> Dict[Tuple[int, ...], int]:
+        """Get the number of samples accumulated so far for each key"""
+        return {key: stats.count for key, stats in self._stats.items()}
+
+    def mean(self) -> TensorMap:
+        """Get the mean of the values accumulated so far"""
+        return self._reduce(lambda stats: stats.mean)
+
+    def var(self, correction: int = 0) -> TensorMap:
+        """Get the variance of the values accumulated so far.
+
+        :param correction: difference between the number of samples and the
+            divisor used when computing the variance. Use ``correction=1`` to
+            get the unbiased estimator of the variance.
+        """
+        return self._reduce(lambda stats: _variance(stats, correction))
+
+    def std(self, correction: int = 0) -> TensorMap:
+        """Get the standard deviation of the values accumulated so far.
+
+        :param correction: same as for :py:meth:`var`
+        """
+        return self._reduce(lambda stats: _dispatch.sqrt(_variance(stats, correction)))
+
+    def _reduce(self, function) -> TensorMap:
+        if len(self._stats) == 0:
+            raise ValueError("no data was accumulated in this OnlineStats")
+
+        keys: List[Tuple[int, ...]] = []
+        blocks = []
+        for key, stats in self._stats.items():
+            if stats.count == 0:
+                raise ValueError(f"no samples were accumulated for the block {key}")
+
+            values = function(stats)
+            keys.append(key)
+            blocks.append(
+                TensorBlock(
+                    values=values.reshape((1,) + values.shape),
+                    samples=Labels.single(),
+                    components=stats.components,
+                    properties=stats.properties,
+                )
+            )
+
+        keys = Labels(
+            names=self._key_names,
+            values=np.array(keys, dtype=np.int32).reshape(len(keys), -1),
+        )
+        return TensorMap(keys, blocks)
+
+
+def _variance(stats: _BlockStats, correction: int):
+    if stats.count <= correction:
+        raise ValueError(
+            f"can not compute the variance with correction={correction} "
+            f"from {stats.count} samples"
+        )
+    return stats.m2 / (stats.count - correction)
+
+
+def _check_same_metadata(stats: _BlockStats, block: TensorBlock, key):
+    same_components = len(stats.components) == len(block.components) and all(
+        _labels_equal(a, b, exact_order=True)
+        for a, b in zip(stats.components, block.components)
+    )
+    if not same_components:
+        raise ValueError(
+            f"the components of the block {key} do not match the ones from "
+            "previous batches"
+        )
+
+    if not _labels_equal(stats.properties, block.properties, exact_order=True):
+        raise ValueError(
+            f"the properties of the block {key} do not match the ones from "
+            "previous batches"
+        )
@@ -0,0 +1,143 @@
+from typing import Optional, Tuple
+
+import numpy as np
+
+from ..block import TensorBlock
+from ..labels import Labels
+from ..tensor import TensorMap
+from . import _dispatch
+from .dot import dot
+
+
+def pca(
+    tensor: TensorMap,
+    n_components: int,
+    solver: str = "full",
+    seed: Optional[int] = None,
+) -> Tuple[TensorMap, TensorMap]:
+    """Compute the principal components of each block in a :py:class:`TensorMap`,
+    and project the data onto the first ``n_components`` of them.
+
+    For each block, every sample and component is used as an observation of
+    the properties. The data is centered with the mean over all observations,
+    and the principal components are the right singular vectors of the centered
+    data.
+
+    The function returns two :py:class:`TensorMap`, both with the same keys as
+    ``tensor``:
+
+    - the ``projector``, where each block has ``n_components`` samples (with a
+      single ``"pca"`` dimension), no components, and the same properties as the
+      input block. The values of the block are the principal components,
+      sorted by decreasing singular values;
+    - the ``projected`` features, where each block has the same samples and
+      components as the input block, and the ``n_components`` principal
+      components as properties. This is equivalent to ``dot(centered,
+      projector)``, and gradients are projected accordingly.
+
+    .. code-block:: python
+
+        projector, projected = pca(tensor, n_components=4)
+
+    :param tensor: the :py:class:`TensorMap` to decompose
+    :param n_components: number of principal components to keep for each block
+    :param solver: which algorithm to use to compute the singular value
+        decomposition. ``"full"`` uses the LAPACK SVD from numpy/torch, while
+        ``"randomized"`` uses a randomized SVD, which can be much faster when
+        ``n_components`` is small compared to the number of properties.
+    :param seed: seed for the random number generator used by the
+        ``"randomized"`` solver, ignored by the ``"full"`` solver.
+
+    :return: a tuple containing the ``projector`` and the ``projected``
+        :py:class:`TensorMap`.
+    """
+    if not isinstance(tensor, TensorMap):
+        raise TypeError("`tensor` must be an equistore `TensorMap`")
+
+    if solver not in ["full", "randomized"]:
+        raise ValueError(f"invalid solver '{solver}', expected 'full' or 'randomized'")
+
+    if n_components <= 0:
+        raise ValueError(f"n_components must be positive, got {n_components}")
+
+    projectors = []
+    centered = []
+    for key, block in tensor:
+        values = block.values
+        matrix = values.reshape(-1, values.shape[-1])
+        if n_components > min(matrix.shape):
+            raise ValueError(
+                f"can not compute {n_components} principal components for the "
+                f"block {tuple(int(k) for k in key)} with shape {tuple(values.shape)}"
+            )
+
+        mean = matrix.sum(0) / matrix.shape[0]
+        matrix = matrix - mean
+
+        if solver == "full":
+            components = _dispatch.svd(matrix)[2][:n_components]
+        else:
+            components = _randomized_components(matrix, n_components, seed)
+
+        projectors.append(
+            TensorBlock(
+                values=_flip_signs(components),
+                samples=Labels(
+                    names=["pca"],
+                    values=np.arange(n_components, dtype=np.int32).reshape(-1, 1),
+                ),
+                components=[],
+                properties=block.properties,
+            )
+        )
+
+        centered_block = TensorBlock(
+            values=values - mean,
+            samples=block.samples,
+            components=block.components,
+            properties=block.properties,
+        )
+        for parameter, gradient in block.gradients():
+            centered_block.add_gradient(
+                parameter,
+                gradient.data,
+                gradient.samples,
+                gradient.components,
+            )
+        centered.append(centered_block)
+
+    projector = TensorMap(tensor.keys, projectors)
+    projected = dot(TensorMap(tensor.keys, centered), projector)
+
+    return projector, projected
+
+
+def _randomized_components(matrix, n_components: int, seed: Optional[int]):
+    """
+    Get the first ``n_components`` right singular vectors of ``matrix`` using
+    the randomized algorithm from Halko, Martinsson and Tropp (2011).
+    """
+    n_oversamples = 10
+    n_power_iterations = 4
+
+    size = min(n_components + n_oversamples, min(matrix.shape))
+    random = _dispatch.random_normal(matrix, (matrix.shape[1], size), seed)
+
+    # find an orthonormal basis approximating the range of the matrix
+    range_basis = _dispatch.qr(matrix @ random)
+    for _ in range(n_power_iterations):
+        range_basis = _dispatch.qr(matrix @ (matrix.T @ range_basis))
+
+    # compute the SVD of the matrix projected on this basis
+    components = _dispatch.svd(range_basis.T @ matrix)[2]
+    return components[:n_components]
+
+
+def _flip_signs(components):
+    """
+    Make the sign of the singular vectors deterministic, by making the entry
+    with the largest absolute value of each vector positive.
+    """
+    largest = _dispatch.abs(components).argmax(1)
+    signs = _dispatch.sign(components[list(range(components.shape[0])), largest])
+    return components * signs.reshape(-1, 1)
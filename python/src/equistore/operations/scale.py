@@ -0,0 +1,91 @@
+from ..block import TensorBlock
+from ..tensor import TensorMap
+from .equal_metadata import _check_maps, _copy_units, _labels_equal
+
+
+def scale_properties(tensor: TensorMap, weights) -> TensorMap:
+    """Multiply each property of the blocks in ``tensor`` by the corresponding
+    weight, including in the gradients.
+
+    This is useful for feature standardization (for example using the inverse
+    of the standard deviation computed by :py:func:`equistore.std_over_samples`)
+    or kernel normalization.
+
+    ``weights`` can either be a one-dimensional array, which is then used for
+    all blocks and must contain one entry for each property; or a
+    :py:class:`TensorMap` with the same keys as ``tensor``, where each block
+    contains a single sample, no components and the same properties as the
+    corresponding block in ``tensor``.
+
+    The unit and quantity of the blocks in ``tensor`` are kept in the result.
+
+    :param tensor: input :py:class:`TensorMap`
+    :param weights: per-property weights, either as an array or as a
+        :py:class:`TensorMap`
+
+    :return: a new :py:class:`TensorMap` with the same metadata as ``tensor``
+        and scaled values and gradients
+    """
+    blocks = []
+    if isinstance(weights, TensorMap):
+        _check_maps(tensor, weights, "scale_properties")
+        for key, block in tensor:
+            weights_block = weights.block(key)
+            if len(weights_block.samples) != 1 or len(weights_block.components) != 0:
+                raise ValueError(
+                    "blocks in the weights of 'scale_properties' should contain "
+                    "a single sample and no components"
+                )
+
+            if not _labels_equal(
+                weights_block.properties, block.properties, exact_order=True
+            ):
+                raise ValueError(
+                    "Inputs to 'scale_properties' should have the same properties"
+                )
+
+            blocks.append(scale_properties_block(block, weights_block.values[0]))
+    else:
+        for _, block in tensor:
+            blocks.append(scale_properties_block(block, weights))
+
+    return TensorMap(tensor.keys, blocks)
+
+
+def scale_properties_block(block: TensorBlock, weights) -> TensorBlock:
+    """Multiply each property of ``block`` by the corresponding entry in the
+    one-dimensional ``weights`` array, including in the gradients.
+
+    See :py:func:`scale_properties` for more information.
+
+    :param block: input :py:class:`TensorBlock`
+    :param weights: one-dimensional array of weights, with one entry for each
+        property in ``block``
+
+    :return: a new :py:class:`TensorBlock` with the same metadata as ``block``
+        and scaled values and gradients
+    """
+    if len(weights.shape) != 1 or weights.shape[0] != len(block.properties):
+        raise ValueError(
+            "weights for 'scale_properties' should be a one-dimensional array "
+            f"with {len(block.properties)} entries, got an array with shape "
+            f"{tuple(weights.shape)}"
+        )
+
+    result = TensorBlock(
+        values=block.values * weights,
+        samples=block.samples,
+        components=block.components,
+        properties=block.properties,
+    )
+    _copy_units(result, block)
+
+    for parameter, gradient in block.gradients():
+        result.add_gradient(
+            parameter,
+            gradient.data * weights,
+            gradient.samples,
+            gradient.components,
+        )
+
+    return result
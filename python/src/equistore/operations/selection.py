@@ -0,0 +1,240 @@
+"""
+Selection of representative samples or properties
+==================================================
+
+These functions select a subset of the samples or properties of a
+:py:class:`TensorMap` or :py:class:`TensorBlock`, which are as representative as
+possible of the whole set. This is a typical step when building sparse kernel
+models, where the selected samples are used as the active/support points.
+
+The functions return :py:class:`Labels` which can be directly given to
+:py:func:`equistore.slice` or :py:func:`equistore.slice_block`. The
+``*_block`` functions perform the selection for a single block, while the
+:py:class:`TensorMap` functions perform a single global selection, using the
+data from all blocks together.
+
+.. autofunction:: equistore.select_fps
+
+.. autofunction:: equistore.select_fps_block
+
+.. autofunction:: equistore.select_cur
+
+.. autofunction:: equistore.select_cur_block
+"""
+
+from typing import List
+
+import numpy as np
+
+from ..block import TensorBlock
+from ..labels import Labels
+from ..tensor import TensorMap
+from . import _dispatch
+
+
+def select_fps(
+    tensor: TensorMap, n_to_select: int, axis: str = "samples", initial: int = 0
+) -> Labels:
+    """Select ``n_to_select`` samples or properties of ``tensor`` using
+    farthest point sampling (FPS), considering all the blocks together.
+
+    All the blocks in ``tensor`` must have the same labels along ``axis``. When
+    selecting samples, the features of each sample are the values of all the
+    blocks (including components) concatenated together; when selecting
+    properties, each property is described by its values for all the samples and
+    components of all the blocks.
+
+    FPS starts from the ``initial`` entry, and then iteratively selects the
+    entry with the largest (euclidean) distance to all the already selected
+    entries.
+
+    :param tensor: input :py:class:`TensorMap`
+    :param n_to_select: number of entries to select
+    :param axis: either ``"samples"`` or ``"properties"``
+    :param initial: position of the first selected entry along ``axis``
+
+    :return: :py:class:`Labels` containing the selected entries, in the order
+        in which they were selected
+    """
+    labels, matrix = _global_matrix(tensor, axis)
+    selected = _fps(_points(matrix, axis), n_to_select, initial)
+    return _select_labels(labels, selected)
+
+
+def select_fps_block(
+    block: TensorBlock, n_to_select: int, axis: str = "samples", initial: int = 0
+) -> Labels:
+    """Select ``n_to_select`` samples or properties of ``block`` using
+    farthest point sampling (FPS).
+
+    When selecting samples, the features of each sample are the corresponding
+    values for all components and properties; when selecting properties, each
+    property is described by its values for all samples and components. See
+    :py:func:`select_fps` for more information.
+
+    :param block: input :py:class:`TensorBlock`
+    :param n_to_select: number of entries to select
+    :param axis: either ``"samples"`` or ``"properties"``
+    :param initial: position of the first selected entry along ``axis``
+
+    :return: :py:class:`Labels` containing the selected entries, in the order
+        in which they were selected
+    """
+    labels, matrix = _block_matrix(block, axis)
+    selected = _fps(_points(matrix, axis), n_to_select, initial)
+    return _select_labels(labels, selected)
+
+
+def select_cur(tensor: TensorMap, n_to_select: int, axis: str = "samples") -> Labels:
+    """Select ``n_to_select`` samples or properties of ``tensor`` using a CUR
+    decomposition, considering all the blocks together.
+
+    The data from all the blocks is combined as in :py:func:`select_fps`. The
+    selection is iterative: at each step, the entry with the largest leverage
+    score (computed from the first singular vector of the data) is selected,
+    and the data is then orthogonalized with respect to this entry.
+
+    :param tensor: input :py:class:`TensorMap`
+    :param n_to_select: number of entries to select
+    :param axis: either ``"samples"`` or ``"properties"``
+
+    :return: :py:class:`Labels` containing the selected entries, in the order
+        in which they were selected
+    """
+    labels, matrix = _global_matrix(tensor, axis)
+    selected = _cur(_points(matrix, axis).T, n_to_select)
+    return _select_labels(labels, selected)
+
+
+def select_cur_block(
+    block: TensorBlock, n_to_select: int, axis: str = "samples"
+) -> Labels:
+    """Select ``n_to_select`` samples or properties of ``block`` using a CUR
+    decomposition. See :py:func:`select_cur` for more information.
+
+    :param block: input :py:class:`TensorBlock`
+    :param n_to_select: number of entries to select
+    :param axis: either ``"samples"`` or ``"properties"``
+
+    :return: :py:class:`Labels` containing the selected entries, in the order
+        in which they were selected
+    """
+    labels, matrix = _block_matrix(block, axis)
+    selected = _cur(_points(matrix, axis).T, n_to_select)
+    return _select_labels(labels, selected)
+
+
+def _check_axis(axis: str):
+    if axis not in ["samples", "properties"]:
+        raise ValueError("axis must be either 'samples' or 'properties'")
+
+
+def _block_matrix(block: TensorBlock, axis: str):
+    """
+    Get the labels along ``axis`` and the values of ``block`` as a matrix with
+    samples (and components) as rows and properties as columns; or samples as
+    rows and components and properties as columns.
+    """
+    if not isinstance(block, TensorBlock):
+        raise TypeError("`block` must be an equistore `TensorBlock`")
+    _check_axis(axis)
+
+    values = block.values
+    if axis == "samples":
+        return block.samples, values.reshape(values.shape[0], -1)
+    else:
+        return block.properties, values.reshape(-1, values.shape[-1])
+
+
+def _global_matrix(tensor: TensorMap, axis: str):
+    """
+    Same as :py:func:`_block_matrix`, combining all the blocks in ``tensor``
+    """
+    if not isinstance(tensor, TensorMap):
+        raise TypeError("`tensor` must be an equistore `TensorMap`")
+    _check_axis(axis)
+
+    blocks = tensor.blocks()
+    labels, _ = _block_matrix(blocks[0], axis)
+
+    matrices = []
+    for block in blocks:
+        block_labels, matrix = _block_matrix(block, axis)
+        if block_labels.names != labels.names or not np.all(block_labels == labels):
+            raise ValueError(
+                f"all blocks must have the same {axis} to select {axis} "
+                "in the whole tensor"
+            )
+        matrices.append(matrix)
+
+    if axis == "samples":
+        return labels, _dispatch.concatenate(matrices, axis=1)
+    else:
+        return labels, _dispatch.concatenate(matrices, axis=0)
+
+
+def _points(matrix, axis: str):
+    """Get the matrix with one row for each entry along ``axis``"""
+    if axis == "samples":
+        return matrix
+    else:
+        return matrix.T
+
+
+def _check_n_to_select(n_to_select: int, n_entries: int):
+    if n_to_select <= 0 or n_to_select > n_entries:
+        raise ValueError(
+            "n_to_select must be between 1 and the number of entries "
+            f"({n_entries}), got {n_to_select}"
+        )
+
+
+def _fps(points, n_to_select: int, initial: int) -> List[int]:
+    """Farthest point sampling on the rows of ``points``"""
+    n_points = points.shape[0]
+    _check_n_to_select(n_to_select, n_points)
+    if initial < 0 or initial >= n_points:
+        raise ValueError(f"initial must be between 0 and {n_points - 1}, got {initial}")
+
+    selected = [initial]
+    distances = ((points - points[initial]) ** 2).sum(1)
+    # make sure already selected points are never selected again, even if
+    # there are duplicated points
+    distances[initial] = -1.0
+    for _ in range(n_to_select - 1):
+        new = int(distances.argmax())
+        selected.append(new)
+
+        new_distances = ((points - points[new]) ** 2).sum(1)
+        distances = _dispatch.where(new_distances < distances, new_distances, distances)
+        distances[new] = -1.0
+
+    return selected
+
+
+def _cur(matrix, n_to_select: int) -> List[int]:
+    """Iterative CUR selection of the columns of ``matrix``"""
+    _check_n_to_select(n_to_select, matrix.shape[1])
+
+    selected = []
+    for _ in range(n_to_select):
+        # leverage scores from the first right singular vector
+        scores = _dispatch.svd(matrix)[2][0] ** 2
+        scores[selected] = 0.0
+
+        new = int(scores.argmax())
+        selected.append(new)
+
+        # orthogonalize the remaining columns with respect to the selected one
+        column = matrix[:, new]
+        norm2 = (column * column).sum()
+        if norm2 > 0:
+            projection = (column @ matrix) / norm2
+            matrix = matrix - column.reshape(-1, 1) * projection.reshape(1, -1)
+
+    return selected
+
+
+def _select_labels(labels: Labels, selected: List[int]) -> Labels:
+    values = labels.view(dtype=np.int32).reshape(len(labels), -1)
+    return Labels(names=labels.names, values=values[selected])
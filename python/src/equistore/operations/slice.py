@@ -9,8 +9,8 @@ from ..tensor import TensorMap
 
 def slice(
     tensor: TensorMap,
-    samples: Optional[Labels] = None,
-    properties: Optional[Labels] = None,
+    samples: Optional[Union[Labels, np.ndarray]] = None,
+    properties: Optional[Union[Labels, np.ndarray]] = None,
 ) -> TensorMap:
     """Slice a :py:class:`TensorMap` along the samples and/or properties dimension(s).
 
@@ -59,17 +59,25 @@ def slice(
     applies to any Gradient TensorBlocks the input TensorBlock may have
     associated with it.
 
+    Instead of :py:class:`Labels`, ``samples`` and ``properties`` can also be
+    given as 1-dimensional boolean arrays (for example the result of a numpy
+    comparison), with ``True`` for the entries to keep. In this case, the mask
+    is applied to every block, and its length must match the number of
+    samples/properties of all the blocks in the tensor.
+
     See the documentation for the :py:func:`slice_block` function to see how an
     individual :py:class:`TensorBlock` is sliced.
 
     :param tensor: the input :py:class:`TensorMap` to be sliced.
     :param samples: a :py:class:`Labels` object containing the names
         and indices of samples to keep in the each of the sliced
-        :py:class:`TensorBlock` of the output :py:class:`TensorMap`. Default
+        :py:class:`TensorBlock` of the output :py:class:`TensorMap`, or a
+        boolean mask over the samples. Default
         value of None indicates no slicing along the samples dimension should
         occur.
     :param properties: a :py:class:`Labels` object containing the names
-        and indices of properties to keep in each of the sliced
+        and indices of properties, or a boolean mask over the properties, to
+        keep in each of the sliced
         :py:class:`TensorBlock` of the output :py:class:`TensorMap`. Default
         value of None indicates no slicing along the properties dimension should
         occur.
@@ -90,8 +98,8 @@ def slice(
 
 def slice_block(
     block: TensorBlock,
-    samples: Optional[Labels] = None,
-    properties: Optional[Labels] = None,
+    samples: Optional[Union[Labels, np.ndarray]] = None,
+    properties: Optional[Union[Labels, np.ndarray]] = None,
 ) -> TensorBlock:
     """
     Slices an input :py:class:`TensorBlock` along the samples and/or properties
@@ -126,6 +134,15 @@ def slice_block(
             )
         )
 
+    ``samples`` and ``properties`` can also be 1-dimensional boolean arrays,
+    with the same length as the corresponding dimension of the block and
+    ``True`` for the entries to keep:
+
+    .. code-block:: python
+
+        structures = block.samples["structure"]
+        sliced_block = slice_block(block, samples=structures < 10)
+
     For the empty blocks that may be returned, although there will be no actual
     values in its TensorBlock.values tensor, the shape of this tensor will be
     non-zero in the dimensions that haven't been sliced. This is created by
@@ -144,12 +161,14 @@ def slice_block(
     :param block: the input :py:class:`TensorBlock` to be sliced.
     :param samples: a :py:class:`Labels` object containing the names
         and indices of samples to keep in the sliced output
-        :py:class:`TensorBlock`. Default value of None indicates no slicing
-        along the samples dimension should occur.
+        :py:class:`TensorBlock`, or a boolean mask over the samples. Default
+        value of None indicates no slicing along the samples dimension should
+        occur.
     :param properties: a :py:class:`Labels` object containing the names
         and indices of properties to keep in the sliced output
-        :py:class:`TensorBlock`. Default value of None indicates no slicing
-        along the properties dimension should occur.
+        :py:class:`TensorBlock`, or a boolean mask over the properties. Default
+        value of None indicates no slicing along the properties dimension should
+        occur.
 
     :return new_block: a :py:class:`TensorBlock` that corresponds to the sliced
         input.
@@ -168,8 +187,8 @@ def slice_block(
 
 def _slice_block(
     block: TensorBlock,
-    samples: Optional[Labels] = None,
-    properties: Optional[Labels] = None,
+    samples: Optional[Union[Labels, np.ndarray]] = None,
+    properties: Optional[Union[Labels, np.ndarray]] = None,
 ) -> TensorBlock:
     """
     Slices an input :py:class:`TensorBlock` along the samples and/or properties
@@ -201,21 +220,13 @@ def _slice_block(
 
     # Generate arrays of bools indicating which samples indices to keep upon slicing.
     if samples is not None:
-        all_samples = block.samples[list(samples.names)].tolist()
-        set_samples_to_slice = set(samples.tolist())
-        samples_filter = np.array(
-            [sample in set_samples_to_slice for sample in all_samples]
-        )
+        samples_filter = _selection_filter(block.samples, samples)
         new_values = new_values[samples_filter]
         new_samples = new_samples[samples_filter]
 
     # Generate array of bools indicating which properties indices to keep upon slicing.
     if properties is not None:
-        all_properties = block.properties[list(properties.names)].tolist()
-        set_properties_to_slice = set(properties.tolist())
-        properties_filter = np.array(
-            [prop in set_properties_to_slice for prop in all_properties]
-        )
+        properties_filter = _selection_filter(block.properties, properties)
         new_values = new_values[..., properties_filter]
         new_properties = new_properties[properties_filter]
 
@@ -279,37 +290,79 @@ def _slice_block(
     return new_block
 
 
+def _selection_filter(labels: Labels, selection: Union[Labels, np.ndarray]):
+    """
+    Get an array of bools indicating which entries of ``labels`` are part of
+    ``selection``, which is either a :py:class:`Labels` or already a mask.
+    """
+    if not isinstance(selection, Labels):
+        return selection
+
+    all_entries = labels[list(selection.names)].tolist()
+    set_to_slice = set(selection.tolist())
+    return np.array([entry in set_to_slice for entry in all_entries], dtype=bool)
+
+
+def _is_mask(selection) -> bool:
+    return isinstance(selection, np.ndarray) and selection.dtype == np.bool_
+
+
 def _check_args(
-    tensor: Union[TensorMap, TensorMap],
-    samples: Optional[Labels] = None,
-    properties: Optional[Labels] = None,
+    tensor: Union[TensorMap, TensorBlock],
+    samples: Optional[Union[Labels, np.ndarray]] = None,
+    properties: Optional[Union[Labels, np.ndarray]] = None,
 ):
     """
     Checks the arguments passed to :py:func:`slice` and :py:func:`slice_block`.
     """
+    blocks = tensor.blocks() if isinstance(tensor, TensorMap) else [tensor]
     # Get a single block
-    block = tensor.block(0) if isinstance(tensor, TensorMap) else tensor
+    block = blocks[0]
     # Check samples Labels if passed
     if samples is not None:
         # Check type
-        if not isinstance(samples, Labels):
-            raise TypeError("samples must be a `Labels` object")
-        # Check names
-        s_names = block.samples.names
-        for name in samples.names:
-            if name not in s_names:
-                raise ValueError(
-                    f"invalid sample name '{name}' which is not part of the input"
-                )
+        if _is_mask(samples):
+            _check_mask(blocks, samples, "samples")
+        elif not isinstance(samples, Labels):
+            raise TypeError("samples must be a `Labels` object or a boolean mask")
+        else:
+            # Check names
+            s_names = block.samples.names
+            for name in samples.names:
+                if name not in s_names:
+                    raise ValueError(
+                        f"invalid sample name '{name}' which is not part of the input"
+                    )
     # Check properties Labels if passed
     if properties is not None:
         # Check type
-        if not isinstance(properties, Labels):
-            raise TypeError("properties must be a `Labels` object")
-        # Check names
-        p_names = block.properties.names
-        for name in properties.names:
-            if name not in p_names:
-                raise ValueError(
-                    f"invalid property name '{name}' which is not part of the input"
-                )
+        if _is_mask(properties):
+            _check_mask(blocks, properties, "properties")
+        elif not isinstance(properties, Labels):
+            raise TypeError("properties must be a `Labels` object or a boolean mask")
+        else:
+            # Check names
+            p_names = block.properties.names
+            for name in properties.names:
+                if name not in p_names:
+                    raise ValueError(
+                        f"invalid property name '{name}' which is not part of the "
+                        "input"
+                    )
+
+
+def _check_mask(blocks, mask: np.ndarray, axis: str):
+    """
+    Checks that a boolean ``mask`` can be used to slice the ``axis`` ("samples"
+    or "properties") of all the ``blocks``.
+    """
+    if len(mask.shape) != 1:
+        raise ValueError(f"{axis} mask must be a 1-dimensional array")
+
+    for block in blocks:
+        expected = len(getattr(block, axis))
+        if mask.shape[0] != expected:
+            raise ValueError(
+                f"{axis} mask has {mask.shape[0]} entries, but the block "
+                f"has {expected} {axis}"
+            )
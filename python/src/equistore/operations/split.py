@@ -1,9 +1,12 @@
-from typing import List, Union
+from typing import List, Optional, Tuple, Union
+
+import numpy as np
 
 from ..block import TensorBlock
 from ..labels import Labels
 from ..tensor import TensorMap
 from .slice import _slice_block
+from .unique_metadata import unique_metadata
 
 
 def split(
@@ -142,6 +145,147 @@ def split_block(
     return _split_block(block, axis, grouped_idxs)
 
 
+def kfold_split(
+    tensor: TensorMap,
+    column: str,
+    k: int,
+    shuffle: bool = True,
+    seed: Optional[int] = None,
+) -> List[Tuple[TensorMap, TensorMap]]:
+    """Split a :py:class:`TensorMap` into ``k`` (train, test) pairs for k-fold
+    cross-validation.
+
+    The split happens at the level of a single sample ``column`` (for example
+    ``"structure"``): the different values of this column are distributed in
+    ``k`` folds of (almost) equal size, and all the samples sharing the same
+    value end up in the same fold. This prevents leakage between the train and
+    test sets, which would happen when splitting individual samples (e.g.
+    different atoms of the same structure ending up in both sets).
+
+    For each fold, the test set contains the samples of this fold, and the
+    train set the samples of all other folds. The split is done with
+    :py:func:`split`, so the gradients are handled consistently.
+
+    .. code-block:: python
+
+        for train, test in kfold_split(tensor, "structure", k=5, seed=42):
+            model.fit(train)
+            error = model.evaluate(test)
+
+    :param tensor: a :py:class:`TensorMap` to be split
+    :param column: name of the sample dimension used to define the folds
+    :param k: number of folds, between 2 and the number of different values of
+        ``column`` in ``tensor``
+    :param shuffle: whether to randomly shuffle the values of ``column`` before
+        creating the folds. If ``False``, the folds contain consecutive sorted
+        values of ``column``.
+    :param seed: seed for the random number generator used to shuffle the
+        values. The folds are deterministic for a given seed.
+
+    :return: a list of ``k`` tuples, each containing a train and a test
+        :py:class:`TensorMap`.
+    """
+    if not isinstance(tensor, TensorMap):
+        raise TypeError("``tensor`` should be an equistore ``TensorMap``")
+
+    if column not in tensor.sample_names:
+        raise ValueError(
+            f"'{column}' is not part of the samples of this tensor, "
+            f"available names are {list(tensor.sample_names)}"
+        )
+
+    unique = unique_metadata(tensor, axis="samples", names=[column])
+    values = np.array(unique[column], dtype=np.int32)
+
+    if k < 2 or k > len(values):
+        raise ValueError(
+            f"k must be between 2 and the number of different '{column}' "
+            f"({len(values)}), got {k}"
+        )
+
+    if shuffle:
+        values = np.random.default_rng(seed).permutation(values)
+
+    folds = np.array_split(values, k)
+
+    result = []
+    for fold_i, test_values in enumerate(folds):
+        train_values = np.concatenate(
+            [fold for i, fold in enumerate(folds) if i != fold_i]
+        )
+
+        train, test = split(
+            tensor,
+            axis="samples",
+            grouped_idxs=[
+                Labels(names=[column], values=train_values.reshape(-1, 1)),
+                Labels(names=[column], values=test_values.reshape(-1, 1)),
+            ],
+        )
+        result.append((train, test))
+
+    return result
+
+
+def subsample(
+    tensor: TensorMap,
+    fractions: List[float],
+    column: str,
+    seed: Optional[int] = None,
+) -> List[TensorMap]:
+    """Create nested subsets of a :py:class:`TensorMap`, for example to compute
+    learning curves.
+
+    As for :py:func:`kfold_split`, the subsets are created at the level of a
+    single sample ``column`` (for example ``"structure"``): the different values
+    of this column are randomly shuffled, and each subset contains the samples
+    corresponding to the first ``fraction`` of the shuffled values. This
+    guarantees that smaller subsets are always contained in larger ones.
+
+    .. code-block:: python
+
+        subsets = subsample(train, [0.1, 0.2, 0.5, 1.0], "structure", seed=42)
+        for subset in subsets:
+            model.fit(subset)
+            error = model.evaluate(test)
+
+    :param tensor: a :py:class:`TensorMap` to be subsampled
+    :param fractions: fraction of the different values of ``column`` to include
+        in each subset. All fractions must be in the ``(0, 1]`` range, and each
+        subset contains at least one value of ``column``.
+    :param column: name of the sample dimension used to define the subsets
+    :param seed: seed for the random number generator used to shuffle the
+        values. The subsets are deterministic for a given seed.
+
+    :return: a list of :py:class:`TensorMap`, one for each entry in
+        ``fractions``.
+    """
+    if not isinstance(tensor, TensorMap):
+        raise TypeError("``tensor`` should be an equistore ``TensorMap``")
+
+    if column not in tensor.sample_names:
+        raise ValueError(
+            f"'{column}' is not part of the samples of this tensor, "
+            f"available names are {list(tensor.sample_names)}"
+        )
+
+    for fraction in fractions:
+        if not 0.0 < fraction <= 1.0:
+            raise ValueError(f"fractions must be in the (0, 1] range, got {fraction}")
+
+    unique = unique_metadata(tensor, axis="samples", names=[column])
+    values = np.array(unique[column], dtype=np.int32)
+    values = np.random.default_rng(seed).permutation(values)
+
+    grouped_idxs = []
+    for fraction in fractions:
+        count = max(1, int(round(fraction * len(values))))
+        selected = np.sort(values[:count])
+        grouped_idxs.append(Labels(names=[column], values=selected.reshape(-1, 1)))
+
+    return split(tensor, axis="samples", grouped_idxs=grouped_idxs)
+
+
 def _split_block(
     block: TensorBlock,
     axis: str,
@@ -1,16 +1,25 @@
-from typing import Union
+from typing import Optional, Union
 
 from ..tensor import TensorMap
+from ._broadcast import _broadcast_keys, _check_broadcast
 from .add import add
 from .equal_metadata import _check_maps
 from .multiply import multiply
 
 
-def subtract(A: TensorMap, B: Union[float, TensorMap]) -> TensorMap:
+def subtract(
+    A: TensorMap,
+    B: Union[float, TensorMap],
+    broadcast: Optional[str] = None,
+    missing_gradients: str = "error",
+    broadcast_keys: bool = False,
+) -> TensorMap:
     r"""Return a new :class:`TensorMap` with the values being the subtract
     of ``A`` and ``B``.
 
-    If ``B`` is a :py:class:`TensorMap` it has to have the same metadata as ``A``.
+    If ``B`` is a :py:class:`TensorMap` it has to have the same metadata as ``A``,
+    and blocks in ``A`` and ``B`` must have the same unit and quantity (when
+    both are set).
 
     If gradients are present in ``A``:
 
@@ -24,13 +33,62 @@ def subtract(A: TensorMap, B: Union[float, TensorMap]) -> TensorMap:
        .. math::
             \nabla(A - B) = \nabla A - \nabla B
 
+    If ``broadcast`` is ``"samples"`` (respectively ``"properties"``), the
+    blocks in ``B`` should instead contain a single sample (respectively a
+    single property), which is subtracted from all the samples (respectively
+    properties) of the corresponding block in ``A``. This can be used for
+    example to center the data with the per-block means computed with
+    :py:func:`equistore.mean_over_samples`. ``B`` can not contain gradients in
+    this case, and the gradients of ``A`` are left unchanged.
+
+    >>> import numpy as np
+    >>> import equistore
+    >>> from equistore import Labels, TensorBlock, TensorMap
+    >>> block = TensorBlock(
+    ...     values=np.array([[1.0, 2.0], [3.0, 6.0]]),
+    ...     samples=Labels(["structure"], np.array([[0], [1]])),
+    ...     components=[],
+    ...     properties=Labels.arange("properties", 2),
+    ... )
+    >>> tensor = TensorMap(Labels.single(), [block])
+    >>> mean = equistore.mean_over_samples(tensor, sample_names="structure")
+    >>> centered = equistore.subtract(tensor, mean, broadcast="samples")
+    >>> centered.block().values
+    array([[-1., -2.],
+           [ 1.,  2.]])
+
+    If some gradients are only present in one of ``A`` and ``B``, the
+    ``missing_gradients`` parameter controls what happens. With ``"error"`` (the
+    default), an error is raised. With ``"zero"``, the missing gradients are
+    considered to be zero. With ``"drop"``, these gradients are removed from the
+    result.
+
+    If ``broadcast_keys`` is ``True``, ``A`` and ``B`` can have different key
+    names, as long as the key names of one of them are a subset of the key names
+    of the other. The blocks of the tensor map with fewer key dimensions are then
+    used for all the blocks of the other tensor map with the same values of the
+    common key dimensions, and the result has the keys with more dimensions.
+    This can for example be used to remove per-species baselines from a tensor
+    map which also contains ``spherical_harmonics_l`` in its keys.
+
     :param A: First :py:class:`TensorMap` for the subtraction.
     :param B: Second instance for the subtraction. Parameter can be a scalar or a
               :py:class:`TensorMap`. In the latter case ``B`` must have the same
               metadata of ``A``.
+    :param broadcast: if set to ``"samples"`` or ``"properties"``, broadcast
+              the blocks of ``B`` over this dimension of the blocks of ``A``.
+    :param missing_gradients: how to handle gradients which are only present
+              in one of ``A`` and ``B``: ``"error"``, ``"zero"`` or ``"drop"``.
+    :param broadcast_keys: if ``True``, broadcast the blocks of the input
+              with fewer key dimensions over the keys of the other input.
 
     :return: New :py:class:`TensorMap` with the same metadata as ``A``.
     """
+    _check_broadcast(broadcast, B, "subtract")
+
+    if broadcast_keys:
+        A, B = _broadcast_keys(A, B, "subtract")
+
     if isinstance(B, TensorMap):
         _check_maps(A, B, "subtract")
         B = multiply(B, -1)
@@ -40,6 +98,8 @@ def subtract(A: TensorMap, B: Union[float, TensorMap]) -> TensorMap:
             B = -float(B)
         except TypeError as e:
             raise TypeError("B should be a TensorMap or a scalar value. ") from e
-    tensor_result = add(A=A, B=B)
+    tensor_result = add(
+        A=A, B=B, broadcast=broadcast, missing_gradients=missing_gradients
+    )
 
     return tensor_result
@@ -0,0 +1,182 @@
+import numpy as np
+
+from ..block import TensorBlock
+from ..labels import Labels
+from ..tensor import TensorMap
+from . import _dispatch
+
+
+def symmetrize(
+    tensor: TensorMap, first: str, second: str, antisymmetric: bool = False
+) -> TensorMap:
+    """Symmetrize (or antisymmetrize) all the blocks of ``tensor`` with respect
+    to the exchange of the ``first`` and ``second`` sample dimensions.
+
+    See :py:func:`symmetrize_block` for more information.
+
+    :param tensor: input :py:class:`TensorMap`
+    :param first: name of the first sample dimension of the pair
+    :param second: name of the second sample dimension of the pair
+    :param antisymmetric: if ``True``, compute the antisymmetric combination
+        instead of the symmetric one
+
+    :return: a new :py:class:`TensorMap` with the same keys as ``tensor``, and
+        the (anti)symmetrized blocks
+    """
+    if not isinstance(tensor, TensorMap):
+        raise TypeError("`tensor` must be an equistore `TensorMap`")
+
+    blocks = []
+    for _, block in tensor:
+        blocks.append(symmetrize_block(block, first, second, antisymmetric))
+
+    return TensorMap(tensor.keys, blocks)
+
+
+def symmetrize_block(
+    block: TensorBlock, first: str, second: str, antisymmetric: bool = False
+) -> TensorBlock:
+    """Symmetrize (or antisymmetrize) ``block`` with respect to the exchange of
+    the ``first`` and ``second`` sample dimensions.
+
+    This is typically used with pair features, where the samples contain
+    ``first_atom`` and ``second_atom`` dimensions. Each sample ``(..., i, ...,
+    j, ...)`` must have a matching sample ``(..., j, ..., i, ...)`` in the
+    block, where only the values of ``first`` and ``second`` are exchanged. The
+    new block contains the samples where ``first <= second``, with values
+
+    .. math::
+
+        X^\\pm_{ij} = \\frac{1}{2} \\left(X_{ij} \\pm X_{ji}\\right),
+
+    where the ``+`` sign is used for the symmetric combination, and the ``-``
+    sign for the antisymmetric one. Gradients are combined in the same way.
+
+    :param block: input :py:class:`TensorBlock`
+    :param first: name of the first sample dimension of the pair
+    :param second: name of the second sample dimension of the pair
+    :param antisymmetric: if ``True``, compute the antisymmetric combination
+        instead of the symmetric one
+
+    :return: a new :py:class:`TensorBlock` with the reduced samples, and the
+        same components and properties as ``block``
+    """
+    if not isinstance(block, TensorBlock):
+        raise TypeError("`block` must be an equistore `TensorBlock`")
+
+    names = block.samples.names
+    for name in [first, second]:
+        if name not in names:
+            raise ValueError(
+                f"'{name}' is not part of the samples of this block, "
+                f"available names are {list(names)}"
+            )
+
+    if first == second:
+        raise ValueError("`first` and `second` must be different sample names")
+
+    first = names.index(first)
+    second = names.index(second)
+
+    samples = block.samples.view(dtype=np.int32).reshape(len(block.samples), -1)
+    positions = {tuple(sample): i for i, sample in enumerate(samples)}
+
+    # for every sample (i, j) with i <= j, the new sample is at position
+    # `new_position[sample]` and contains (X_ij +/- X_ji) / 2. `direct` maps
+    # X_ij to this new sample and `partner` maps X_ji to it.
+    kept = np.nonzero(samples[:, first] <= samples[:, second])[0]
+    new_position = np.full(len(samples), -1)
+    new_position[kept] = np.arange(len(kept))
+
+    direct = np.full(len(samples), -1)
+    partner = np.full(len(samples), -1)
+    for i, sample in enumerate(samples):
+        exchanged = sample.copy()
+        exchanged[first] = sample[second]
+        exchanged[second] = sample[first]
+        j = positions.get(tuple(exchanged))
+        if j is None:
+            raise ValueError(
+                f"the sample {tuple(int(s) for s in exchanged)} corresponding to "
+                f"{tuple(int(s) for s in sample)} is missing from this block"
+            )
+
+        if sample[first] <= sample[second]:
+            direct[i] = new_position[i]
+        if sample[first] >= sample[second]:
+            partner[i] = new_position[j]
+
+    values = block.values
+    new_values = _combine(values, values, direct, partner, len(kept), antisymmetric)
+
+    result_block = TensorBlock(
+        values=new_values,
+        samples=Labels(names=names, values=samples[kept]),
+        components=block.components,
+        properties=block.properties,
+    )
+
+    for parameter, gradient in block.gradients():
+        gradient_samples = gradient.samples
+        gradient_samples = gradient_samples.view(dtype=np.int32).reshape(
+            len(gradient_samples), -1
+        )
+
+        # each gradient sample contributes to up to two new gradient samples,
+        # with the "sample" dimension updated to point to the new samples
+        direct_entries = np.nonzero(direct[gradient_samples[:, 0]] >= 0)[0]
+        partner_entries = np.nonzero(partner[gradient_samples[:, 0]] >= 0)[0]
+
+        direct_samples = gradient_samples[direct_entries].copy()
+        direct_samples[:, 0] = direct[direct_samples[:, 0]]
+        partner_samples = gradient_samples[partner_entries].copy()
+        partner_samples[:, 0] = partner[partner_samples[:, 0]]
+
+        new_gradient_samples = np.concatenate([direct_samples, partner_samples])
+        if len(new_gradient_samples) == 0:
+            index = np.zeros(0, dtype=np.int64)
+        else:
+            new_gradient_samples, index = np.unique(
+                new_gradient_samples, return_inverse=True, axis=0
+            )
+            index = index.reshape(-1)
+
+        new_gradient_data = _combine(
+            gradient.data[direct_entries],
+            gradient.data[partner_entries],
+            index[: len(direct_entries)],
+            index[len(direct_entries) :],
+            len(new_gradient_samples),
+            antisymmetric,
+        )
+
+        result_block.add_gradient(
+            parameter,
+            new_gradient_data,
+            Labels(names=gradient.samples.names, values=new_gradient_samples),
+            gradient.components,
+        )
+
+    return result_block
+
+
+def _combine(direct_values, partner_values, direct, partner, size, antisymmetric):
+    """
+    Compute ``(direct_values +/- partner_values) / 2``, accumulating the rows of
+    ``direct_values`` and ``partner_values`` into the rows given by ``direct``
+    and ``partner`` respectively. Entries with a negative index are ignored.
+    """
+    output = _dispatch.zeros_like(
+        direct_values, shape=(size,) + direct_values.shape[1:]
+    )
+
+    selected = np.nonzero(direct >= 0)[0]
+    _dispatch.index_add(output, direct_values[selected], direct[selected])
+
+    selected = np.nonzero(partner >= 0)[0]
+    if antisymmetric:
+        _dispatch.index_add(output, -partner_values[selected], partner[selected])
+    else:
+        _dispatch.index_add(output, partner_values[selected], partner[selected])
+
+    return output / 2
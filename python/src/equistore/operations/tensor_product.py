@@ -0,0 +1,31 @@
+from .._c_lib import _get_library
+from ..tensor import TensorMap
+
+
+def tensor_product(first: TensorMap, second: TensorMap) -> TensorMap:
+    """
+    Compute the outer product of the properties of ``first`` and ``second``.
+
+    Both :py:class:`TensorMap` must have the same keys (possibly in a different
+    order), and the blocks with the same key must have the same samples and
+    components. The blocks of the result contain the product of all pairs of
+    properties, for each sample and each entry of the components. The
+    properties of the new blocks are the cartesian product of the properties of
+    the two blocks, with the names prefixed by ``first_`` and ``second_``
+    respectively.
+
+    Gradients are not supported, use :py:func:`equistore.remove_gradients`
+    first if the inputs contain gradients.
+
+    :param first: first :py:class:`TensorMap`
+    :param second: second :py:class:`TensorMap`
+
+    :return: a new :py:class:`TensorMap` with the same keys as ``first``,
+        containing the product of the properties of both inputs
+    """
+    if not isinstance(first, TensorMap) or not isinstance(second, TensorMap):
+        raise TypeError("both inputs to tensor_product must be TensorMap")
+
+    lib = _get_library()
+    ptr = lib.eqs_tensormap_product(first._ptr, second._ptr)
+    return TensorMap._from_ptr(ptr)
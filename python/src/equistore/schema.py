@@ -0,0 +1,184 @@
+"""
+Schemas describe the expected names of the keys, samples, components and
+properties of a :py:class:`TensorMap`, and can be used to validate tensor maps
+before they are given to a model. This allows to catch misnamed dimensions
+(e.g. ``center_species`` instead of ``species_center``) early, with a precise
+error message.
+
+This module is opt-in, and needs to be explicitly imported:
+
+>>> from equistore.schema import TensorMapSchema, SchemaError
+
+A few schemas for commonly used atomistic representations are also provided,
+such as :py:data:`SPHERICAL_EXPANSION` and :py:data:`POWER_SPECTRUM`.
+"""
+from typing import Dict, Optional, Sequence
+
+from .block import TensorBlock
+from .tensor import TensorMap
+
+
+class SchemaError(ValueError):
+    """Exception raised when a :py:class:`TensorMap` does not match a schema"""
+
+    pass
+
+
+class TensorMapSchema:
+    """
+    Description of the expected names for the different labels of a
+    :py:class:`TensorMap`.
+
+    All parameters are optional, and the corresponding labels are not checked
+    when they are set to ``None``.
+
+    :param keys: expected names of the keys
+    :param samples: expected names of the samples in all blocks
+    :param components: expected names of the components in all blocks, as a
+        list containing the names for each component dimension
+    :param properties: expected names of the properties in all blocks
+    :param gradients: dictionary of gradients that must be present in all
+        blocks, associating the gradient parameter to the expected names of
+        the gradient samples (or ``None`` to not check the gradient samples)
+    """
+
+    def __init__(
+        self,
+        keys: Optional[Sequence[str]] = None,
+        samples: Optional[Sequence[str]] = None,
+        components: Optional[Sequence[Sequence[str]]] = None,
+        properties: Optional[Sequence[str]] = None,
+        gradients: Optional[Dict[str, Optional[Sequence[str]]]] = None,
+    ):
+        self.keys = _names_or_none(keys)
+        self.samples = _names_or_none(samples)
+        if components is None:
+            self.components = None
+        else:
+            self.components = [tuple(names) for names in components]
+        self.properties = _names_or_none(properties)
+
+        if gradients is None:
+            self.gradients = {}
+        else:
+            self.gradients = {
+                parameter: _names_or_none(names)
+                for parameter, names in gradients.items()
+            }
+
+    def validate(self, tensor: TensorMap):
+        """
+        Check that the given ``tensor`` matches this schema.
+
+        :param tensor: :py:class:`TensorMap` to validate
+        :raises SchemaError: if the tensor map does not match this schema
+        """
+        if not isinstance(tensor, TensorMap):
+            raise TypeError(f"expected a TensorMap, got {type(tensor)}")
+
+        if self.keys is not None:
+            _check_names("keys", self.keys, tensor.keys.names)
+
+        names = tensor.keys.names
+        for key, block in tensor:
+            try:
+                self.validate_block(block)
+            except SchemaError as e:
+                key = ", ".join(f"{name}={value}" for name, value in zip(names, key))
+                raise SchemaError(f"in block for key ({key}): {e}") from None
+
+    def validate_block(self, block: TensorBlock):
+        """
+        Check that the given ``block`` matches the samples, components,
+        properties and gradients of this schema.
+
+        :param block: :py:class:`TensorBlock` to validate
+        :raises SchemaError: if the block does not match this schema
+        """
+        if self.samples is not None:
+            _check_names("samples", self.samples, block.samples.names)
+
+        if self.components is not None:
+            components = [component.names for component in block.components]
+            if len(components) != len(self.components):
+                raise SchemaError(
+                    f"expected {len(self.components)} components, "
+                    f"got {len(components)}"
+                )
+
+            for i, (expected, actual) in enumerate(zip(self.components, components)):
+                _check_names(f"components[{i}]", expected, actual)
+
+        if self.properties is not None:
+            _check_names("properties", self.properties, block.properties.names)
+
+        for parameter, samples in self.gradients.items():
+            if not block.has_gradient(parameter):
+                raise SchemaError(f"missing gradient with respect to '{parameter}'")
+
+            if samples is not None:
+                _check_names(
+                    f"samples of the '{parameter}' gradient",
+                    samples,
+                    block.gradient(parameter).samples.names,
+                )
+
+
+def _names_or_none(names: Optional[Sequence[str]]):
+    if names is None:
+        return None
+    elif isinstance(names, str):
+        return (names,)
+    else:
+        return tuple(names)
+
+
+def _check_names(context: str, expected: Sequence[str], actual: Sequence[str]):
+    expected = tuple(expected)
+    actual = tuple(actual)
+    if expected == actual:
+        return
+
+    message = f"invalid names for {context}: expected {expected}, got {actual}"
+
+    missing = [name for name in expected if name not in actual]
+    unexpected = [name for name in actual if name not in expected]
+    details = []
+    if len(missing) != 0:
+        details.append("missing " + ", ".join(f"'{name}'" for name in missing))
+    if len(unexpected) != 0:
+        details.append("unexpected " + ", ".join(f"'{name}'" for name in unexpected))
+
+    if len(details) == 0:
+        details.append("the names are not in the expected order")
+
+    raise SchemaError(message + " (" + "; ".join(details) + ")")
+
+
+SPHERICAL_EXPANSION = TensorMapSchema(
+    keys=["spherical_harmonics_l", "species_center", "species_neighbor"],
+    samples=["structure", "center"],
+    components=[["spherical_harmonics_m"]],
+    properties=["n"],
+)
+"""
+Schema for a spherical expansion of the neighbor density, with one block for
+each angular channel and pair of central/neighbor species.
+"""
+
+POWER_SPECTRUM = TensorMapSchema(
+    keys=["species_center"],
+    samples=["structure", "center"],
+    components=[],
+    properties=["species_neighbor_1", "species_neighbor_2", "l", "n1", "n2"],
+)
+"""
+Schema for the SOAP power spectrum, with one block for each central species.
+"""
+
+__all__ = [
+    "SchemaError",
+    "TensorMapSchema",
+    "SPHERICAL_EXPANSION",
+    "POWER_SPECTRUM",
+]
@@ -1,12 +1,19 @@
 import copy
 import ctypes
-from typing import List, Union
+from typing import Dict, List, Optional, Tuple, Union
 
 import numpy as np
 
-from ._c_api import c_uintptr_t, eqs_block_t, eqs_labels_t
+from ._c_api import (
+    c_uintptr_t,
+    eqs_array_t,
+    eqs_block_t,
+    eqs_labels_t,
+    eqs_memory_usage_t,
+)
 from ._c_lib import _get_library
 from .block import TensorBlock
+from .data import Array, ArrayWrapper
 from .labels import Labels, _is_namedtuple, _print_labels
 from .status import _check_pointer
 
@@ -81,6 +88,96 @@ class TensorMap:
         new_ptr = self._lib.eqs_tensormap_copy(self._ptr)
         return TensorMap._from_ptr(new_ptr)
 
+    def __reduce__(self):
+        # pickle tensor maps using the same format as `equistore.save`. The
+        # data is loaded back in numpy arrays.
+        from .io import load_buffer, save_buffer
+
+        return (load_buffer, (save_buffer(self),))
+
+    @staticmethod
+    def from_dense(keys: Labels, samples: Labels, data) -> "TensorMap":
+        """
+        Create a new :py:class:`TensorMap` from a dense 3-dimensional array,
+        for the common case where all blocks share the same samples and
+        properties.
+
+        ``data`` must have a shape of ``(len(keys), len(samples),
+        n_properties)``. The block associated with the ``i``-th key will
+        contain ``data[i]`` as values, the given ``samples``, no components,
+        and properties named ``"property"`` containing the range ``0 ..
+        n_properties``.
+
+        :param keys: keys of the new tensor map
+        :param samples: samples shared by all blocks
+        :param data: dense numpy array or torch tensor containing the data
+        """
+        if len(data.shape) != 3:
+            raise ValueError(
+                f"dense data must be a 3-dimensional array, got {len(data.shape)} "
+                "dimensions instead"
+            )
+
+        if data.shape[0] != len(keys) or data.shape[1] != len(samples):
+            raise ValueError(
+                f"invalid shape for dense data: expected ({len(keys)}, "
+                f"{len(samples)}, n_properties) from the keys and samples, "
+                f"got {tuple(data.shape)}"
+            )
+
+        properties = Labels.arange("property", data.shape[2])
+        blocks = []
+        for values in data:
+            blocks.append(
+                TensorBlock(
+                    values=values,
+                    samples=samples,
+                    components=[],
+                    properties=properties,
+                )
+            )
+
+        return TensorMap(keys, blocks)
+
+    def to_dense(self):
+        """
+        Convert this :py:class:`TensorMap` to a dense 3-dimensional array, with
+        a shape of ``(len(self.keys), n_samples, n_properties)``.
+
+        This is the inverse of :py:func:`TensorMap.from_dense`, and is only
+        possible if all blocks have the same samples and properties, no
+        components and no gradients. Empty tensor maps can not be converted,
+        since they do not define the number of samples and properties.
+        """
+        from equistore.operations import _dispatch
+        from equistore.operations.equal_metadata import _labels_equal
+
+        blocks = [block for _, block in self]
+        if len(blocks) == 0:
+            raise ValueError("can not convert an empty TensorMap to a dense array")
+
+        first = blocks[0]
+        arrays = []
+        for block_i, block in enumerate(blocks):
+            if len(block.components) != 0 or len(block.gradients_list()) != 0:
+                raise ValueError(
+                    "can not convert this TensorMap to a dense array: "
+                    f"block {block_i} contains components or gradients"
+                )
+
+            same_samples = _labels_equal(block.samples, first.samples, True)
+            same_properties = _labels_equal(block.properties, first.properties, True)
+            if not (same_samples and same_properties):
+                raise ValueError(
+                    "can not convert this TensorMap to a dense array: "
+                    f"block {block_i} has different samples or properties than "
+                    "the first block"
+                )
+
+            arrays.append(block.values[None])
+
+        return _dispatch.concatenate(arrays, axis=0)
+
     def copy(self) -> "TensorMap":
         """
         Get a deep copy of this TensorMap, including all the (potentially
@@ -88,6 +185,26 @@ class TensorMap:
         """
         return copy.deepcopy(self)
 
+    def with_values(self, values: List[Array]) -> "TensorMap":
+        """
+        Create a new :py:class:`TensorMap` with the same keys, labels and
+        gradients as this one, using ``values`` as the values of the blocks.
+
+        The metadata is shared with this tensor map and not copied, which makes
+        this function useful to re-create the same tensor map structure around
+        new data.
+
+        :param values: arrays containing the new values, one for each block in
+            the same order as the blocks. Each array must have the same shape as
+            the corresponding block values.
+        """
+        arrays = ctypes.ARRAY(eqs_array_t, len(values))()
+        for i, array in enumerate(values):
+            arrays[i] = ArrayWrapper(array).into_eqs_array()
+
+        ptr = self._lib.eqs_tensormap_with_values(self._ptr, arrays, len(arrays))
+        return TensorMap._from_ptr(ptr)
+
     def __iter__(self):
         keys = self.keys
         for i, key in enumerate(keys):
@@ -173,6 +290,43 @@ class TensorMap:
         self._lib.eqs_tensormap_keys(self._ptr, result)
         return Labels._from_eqs_labels_t(result)
 
+    @property
+    def metadata(self) -> Dict[str, str]:
+        """
+        Free-form metadata attached to this tensor map (provenance,
+        hyper-parameters, code version, ...), as a dictionary from string keys
+        to string values.
+
+        The metadata is preserved when copying and serializing the tensor map.
+        The returned dictionary is a copy, use :py:func:`TensorMap.set_metadata`
+        to modify the metadata.
+        """
+        keys = ctypes.POINTER(ctypes.c_char_p)()
+        count = c_uintptr_t()
+        self._lib.eqs_tensormap_metadata_keys(self._ptr, keys, count)
+
+        result = {}
+        for i in range(count.value):
+            key = keys[i]
+            value = ctypes.c_char_p()
+            self._lib.eqs_tensormap_metadata(self._ptr, key, value)
+            result[key.decode("utf8")] = value.value.decode("utf8")
+
+        return result
+
+    def set_metadata(self, key: str, value: Optional[str]):
+        """
+        Set the metadata associated with ``key`` to ``value``, overwriting any
+        existing value.
+
+        :param key: metadata key, which can not be empty
+        :param value: new value for this metadata, or ``None`` to remove the
+            metadata associated with ``key``
+        """
+        if value is not None:
+            value = value.encode("utf8")
+        self._lib.eqs_tensormap_set_metadata(self._ptr, key.encode("utf8"), value)
+
     def block(self, *args, **kwargs) -> TensorBlock:
         """
         Get the block in this tensor map matching the selection made with
@@ -340,6 +494,64 @@ class TensorMap:
         else:
             return result
 
+    def compress_blocks(self, selection: Labels):
+        """
+        Compress in memory the data of all blocks matching the ``selection``,
+        following the same rules as in :py:func:`TensorMap.blocks_matching`.
+
+        The data of compressed blocks is stored with zstd, and can not be
+        accessed from Python until :py:func:`TensorMap.decompress_blocks` is
+        called. This can be used to reduce the memory used by blocks which are
+        not needed for some time.
+
+        :param selection: labels with a single entry describing which blocks
+            should be compressed
+        """
+        self._lib.eqs_tensormap_compress_blocks(
+            self._ptr, selection._as_eqs_labels_t()
+        )
+
+    def decompress_blocks(self):
+        """
+        Convert the data of all blocks compressed with
+        :py:func:`TensorMap.compress_blocks` back to the original array type.
+        """
+        self._lib.eqs_tensormap_decompress_blocks(self._ptr)
+
+    def view(self, selection: Labels) -> Tuple[Labels, List[TensorBlock]]:
+        """
+        Get the keys and blocks in this tensor map matching the ``selection``,
+        without copying the blocks.
+
+        The ``selection`` follows the same rules as in
+        :py:func:`TensorMap.blocks_matching`. The returned keys contain all the
+        dimensions of ``self.keys``, and only the entries for the selected
+        blocks. The blocks are views inside this tensor map, and can not be
+        used to build a new :py:class:`TensorMap` without copying them first.
+
+        :param selection: labels with a single entry describing which blocks
+            are requested
+
+        :return: a tuple containing the keys and the list of selected blocks
+        """
+        keys = eqs_labels_t()
+        blocks = ctypes.ARRAY(ctypes.POINTER(eqs_block_t), len(self.keys))()
+        count = c_uintptr_t(blocks._length_)
+
+        self._lib.eqs_tensormap_view(
+            self._ptr,
+            selection._as_eqs_labels_t(),
+            keys,
+            blocks,
+            count,
+        )
+
+        result = []
+        for i in range(count.value):
+            result.append(TensorBlock._from_ptr(blocks[i], parent=self))
+
+        return Labels._from_eqs_labels_t(keys), result
+
     def _get_block_by_id(self, id) -> TensorBlock:
         block = ctypes.POINTER(eqs_block_t)()
         self._lib.eqs_tensormap_block_by_id(self._ptr, block, id)
@@ -398,6 +610,38 @@ class TensorMap:
         )
         return TensorMap._from_ptr(ptr)
 
+    def sort_keys(
+        self, names: Union[str, List[str]], descending: bool = False
+    ) -> Tuple["TensorMap", List[int]]:
+        """
+        Get a new :py:class:`TensorMap` where the blocks are sorted according to
+        the values of the key dimensions in ``names``.
+
+        The first dimension in ``names`` has the highest priority, and later
+        dimensions are only used to break ties. Blocks with the same values for
+        all the dimensions in ``names`` keep their relative order.
+
+        :param names: names of the key dimensions to use for sorting
+        :param descending: whether to sort the blocks in descending order
+
+        :return: a tuple containing the new :py:class:`TensorMap` and the
+            permutation applied to the blocks: the block at index ``i`` in the
+            new tensor map corresponds to the block at index ``permutation[i]``
+            in this tensor map.
+        """
+        c_names = _list_or_str_to_array_c_char(names)
+        permutation = ctypes.ARRAY(c_uintptr_t, len(self.keys))()
+
+        ptr = self._lib.eqs_tensormap_sort_keys(
+            self._ptr,
+            c_names,
+            c_names._length_,
+            descending,
+            permutation,
+            permutation._length_,
+        )
+        return TensorMap._from_ptr(ptr), list(permutation)
+
     def keys_to_properties(
         self,
         keys_to_move: Union[str, List[str], Labels],
@@ -442,6 +686,87 @@ class TensorMap:
         )
         return TensorMap._from_ptr(ptr)
 
+    def canonicalize(self) -> "TensorMap":
+        """
+        Get a canonical version of this :py:class:`TensorMap`, where the keys
+        are sorted in lexicographic order, and the samples and properties of
+        each block (and the samples of each gradient) are sorted as well. The
+        data arrays are re-ordered accordingly, and the components are left
+        unchanged.
+
+        Two tensor maps containing the same data in a different order will be
+        identical after canonicalization, and will be saved to the same bytes
+        by :py:func:`equistore.save`.
+        """
+        ptr = self._lib.eqs_tensormap_canonicalize(self._ptr)
+        return TensorMap._from_ptr(ptr)
+
+    def fingerprint(self, include_data: bool = True) -> int:
+        """
+        Compute a stable 64-bit fingerprint of this :py:class:`TensorMap`.
+
+        The fingerprint covers all the metadata (keys, samples, components,
+        properties, gradients, units, quantities and tensor map metadata) and
+        the shape of all arrays. If ``include_data`` is ``True``, the values
+        stored in the arrays are also part of the fingerprint.
+
+        The fingerprint does not depend on the platform, the process or the
+        version of equistore, and can be used as a cache key. It does depend on
+        the order of entries in the labels, use :py:meth:`canonicalize` first to
+        ignore it.
+
+        :param include_data: whether to include the values of the arrays in
+            the fingerprint
+        """
+        fingerprint = ctypes.c_uint64()
+        self._lib.eqs_tensormap_fingerprint(self._ptr, include_data, fingerprint)
+        return fingerprint.value
+
+    def memory_usage(self) -> Dict:
+        """
+        Estimate the memory used by this :py:class:`TensorMap`, in bytes.
+
+        The result is a dictionary with ``"keys"``, ``"blocks"`` and ``"total"``
+        entries, containing the memory used by the keys, by each block (in the
+        same order as the blocks), and by the whole tensor map respectively.
+        Each of these is a dictionary with the memory used by the ``"data"``
+        arrays, the ``"labels_values"``, the ``"labels_positions"`` hash maps and
+        the ``"gradients"``. Labels shared between multiple blocks are only
+        counted once in ``"total"``.
+
+        The size of data arrays is estimated from their shape, assuming they
+        contain 64-bit floating point values. The memory used by arrays storing
+        other types (e.g. ``float32``) is over- or under-estimated accordingly.
+        """
+        keys = eqs_memory_usage_t()
+        blocks = (eqs_memory_usage_t * len(self.keys))()
+        total = eqs_memory_usage_t()
+
+        self._lib.eqs_tensormap_memory_usage(
+            self._ptr, keys, blocks, len(self.keys), total
+        )
+
+        def as_dict(usage):
+            return {name: getattr(usage, name) for name, _ in usage._fields_}
+
+        return {
+            "keys": as_dict(keys),
+            "blocks": [as_dict(block) for block in blocks],
+            "total": as_dict(total),
+        }
+
+    def compact_labels(self):
+        """
+        Move the values of all the labels in this tensor map (keys, and
+        samples, components and properties of all blocks and gradients) to a
+        single buffer, sharing identical labels between blocks.
+
+        This reduces the number of allocations and the memory fragmentation for
+        tensor maps containing many small blocks, and does not change the
+        labels or the data in this tensor map.
+        """
+        self._lib.eqs_tensormap_compact_labels(self._ptr)
+
     @property
     def sample_names(self) -> List[str]:
         """Names of the sample labels for all blocks in this tensor map"""
@@ -457,6 +782,27 @@ class TensorMap:
         """Names of the property labels for all blocks in this tensor map"""
         return self.block(0).properties.names
 
+    @property
+    def dtype(self):
+        """
+        Type of the values of all the blocks in this tensor map, or ``None`` if
+        the tensor map does not contain any block.
+
+        Different blocks in the same tensor map can contain values with
+        different dtypes, in which case this is the string ``"mixed"``.
+        Operations combining blocks with different dtypes follow the numpy/torch
+        type promotion rules, e.g. adding ``float32`` and ``float64`` blocks
+        gives a ``float64`` block.
+        """
+        dtype = None
+        for block in self.blocks():
+            if dtype is None:
+                dtype = block.dtype
+            elif block.dtype != dtype:
+                return "mixed"
+
+        return dtype
+
 
 def _normalize_keys_to_move(keys_to_move: Union[str, List[str], Labels]):
     if isinstance(keys_to_move, str):
@@ -0,0 +1,174 @@
+"""
+Utilities to help testing code producing equistore data.
+
+This module is opt-in, and needs to be explicitly imported:
+
+>>> from equistore.testing import check_finite_differences
+"""
+from typing import Callable, List
+
+import numpy as np
+
+from .tensor import TensorMap
+
+
+class BlockReport:
+    """
+    Result of :py:func:`check_finite_differences` for a single block.
+
+    :param key: key of the block, as a dictionary from key names to values
+    :param max_absolute_error: maximal absolute difference between the
+        gradients and the finite differences
+    :param max_relative_error: maximal relative difference between the
+        gradients and the finite differences
+    :param passed: whether the gradients and the finite differences agree within
+        the requested tolerance
+    """
+
+    def __init__(self, key, max_absolute_error, max_relative_error, passed):
+        self.key = key
+        self.max_absolute_error = max_absolute_error
+        self.max_relative_error = max_relative_error
+        self.passed = passed
+
+    def __repr__(self) -> str:
+        status = "passed" if self.passed else "FAILED"
+        return (
+            f"BlockReport(key={self.key}, {status}, "
+            f"max_absolute_error={self.max_absolute_error:.3e}, "
+            f"max_relative_error={self.max_relative_error:.3e})"
+        )
+
+
+def check_finite_differences(
+    f: Callable[[np.ndarray], TensorMap],
+    tensor: np.ndarray,
+    parameter: str,
+    epsilon: float = 1e-6,
+    rtol: float = 1e-5,
+    atol: float = 1e-8,
+) -> List[BlockReport]:
+    """
+    Check the gradients with respect to ``parameter`` of the
+    :py:class:`TensorMap` produced by ``f`` against finite differences.
+
+    ``f`` is called with ``tensor`` (the values of the parameter, as a numpy
+    array), and each entry of ``tensor`` is then perturbed by ``± epsilon`` to
+    compute the derivatives with centered finite differences. ``f`` must always
+    return a :py:class:`TensorMap` with the same metadata.
+
+    The gradient samples and gradient-specific components are used to find the
+    entry of ``tensor`` corresponding to each gradient row: the values of the
+    gradient samples (except for the first ``"sample"`` dimension) index the
+    first dimensions of ``tensor``, and the positions along the
+    gradient-specific components index the remaining dimensions. For example,
+    gradients with respect to ``"positions"`` with ``("sample", "structure",
+    "atom")`` samples and a single ``xyz`` component correspond to a ``tensor``
+    of shape ``(n_structures, n_atoms, 3)``. Entries missing from the gradients
+    are considered to be zero.
+
+    This function evaluates ``f`` twice for each entry in ``tensor``, and is
+    intended to be used in tests with small inputs.
+
+    :param f: function computing a :py:class:`TensorMap` with gradients from
+        the parameter values
+    :param tensor: values of the parameter at which the gradients are checked
+    :param parameter: name of the gradients to check
+    :param epsilon: displacement used for the finite differences
+    :param rtol: relative tolerance used to compare gradients and finite
+        differences
+    :param atol: absolute tolerance used to compare gradients and finite
+        differences
+
+    :return: a list of :py:class:`BlockReport`, one for each block. The
+        relative error is computed as ``|gradient - fd| / (|fd| + atol)``.
+    """
+    tensor = np.array(tensor, dtype=np.float64)
+    reference = f(tensor.copy())
+
+    analytical = []
+    for key, block in reference:
+        if not block.has_gradient(parameter):
+            raise ValueError(
+                f"missing gradient with respect to '{parameter}' in the block "
+                f"for key {_key_to_dict(reference, key)}"
+            )
+
+        analytical.append(_dense_gradient(block, parameter, tensor.shape))
+
+    numerical = [np.zeros_like(array) for array in analytical]
+    for i in range(tensor.size):
+        displaced = tensor.copy()
+        displaced.flat[i] += epsilon
+        positive = f(displaced)
+
+        displaced.flat[i] -= 2 * epsilon
+        negative = f(displaced)
+
+        blocks_p = [block for _, block in positive]
+        blocks_m = [block for _, block in negative]
+        for block_i, (output, block_p, block_m) in enumerate(
+            zip(numerical, blocks_p, blocks_m)
+        ):
+            if block_p.values.shape != output.shape[1:]:
+                raise ValueError(
+                    f"the shape of block {block_i} changed when displacing the "
+                    "parameter, the function must always return the same metadata"
+                )
+            output[i] = (block_p.values - block_m.values) / (2 * epsilon)
+
+    reports = []
+    for (key, _), gradient, fd in zip(reference, analytical, numerical):
+        error = np.abs(gradient - fd)
+        if error.size == 0:
+            max_absolute_error = 0.0
+            max_relative_error = 0.0
+        else:
+            max_absolute_error = float(np.max(error))
+            max_relative_error = float(np.max(error / (np.abs(fd) + atol)))
+
+        reports.append(
+            BlockReport(
+                key=_key_to_dict(reference, key),
+                max_absolute_error=max_absolute_error,
+                max_relative_error=max_relative_error,
+                passed=bool(np.allclose(gradient, fd, rtol=rtol, atol=atol)),
+            )
+        )
+
+    return reports
+
+
+def _dense_gradient(block, parameter, shape):
+    """
+    Get the gradient of ``block`` with respect to ``parameter`` as a dense
+    array, with one row for each entry of a parameter with the given ``shape``.
+    """
+    values = block.values
+    gradient = block.gradient(parameter)
+    data = np.asarray(gradient.data)
+
+    samples_names = gradient.samples.names[1:]
+    n_gradient_components = len(gradient.components) - len(block.components)
+    if len(samples_names) + n_gradient_components != len(shape):
+        raise ValueError(
+            f"can not match the gradient with respect to '{parameter}' to the "
+            f"parameter values: the gradient has {len(samples_names)} additional "
+            f"sample dimensions and {n_gradient_components} gradient-specific "
+            f"components, but the parameter has {len(shape)} dimensions"
+        )
+
+    components_shape = data.shape[1 : 1 + n_gradient_components]
+    dense = np.zeros((int(np.prod(shape)),) + values.shape)
+    for row, sample in enumerate(gradient.samples):
+        values_sample = sample["sample"]
+        indexes = tuple(int(sample[name]) for name in samples_names)
+        for component in np.ndindex(*components_shape):
+            i = np.ravel_multi_index(indexes + component, shape)
+            dense[i, values_sample] += data[(row,) + component]
+
+    return dense
+
+
+def _key_to_dict(tensor, key):
+    return {name: int(value) for name, value in zip(tensor.keys.names, key)}
@@ -34,6 +34,32 @@ class TestBlocks:
                 components=[],
             )
 
+    def test_register_gradient_parameter(self, block):
+        xyz = Labels(["xyz"], np.array([[0], [1], [2]]))
+        equistore.register_gradient_parameter("python-test-strain", [xyz])
+
+        msg = (
+            "invalid parameter: invalid gradient components for "
+            "'python-test-strain': this parameter was registered with the "
+            r"components \[xyz \(3 entries\)\] before the values components, "
+            r"but got \[\]"
+        )
+        with pytest.raises(equistore.status.EquistoreError, match=msg):
+            block.add_gradient(
+                "python-test-strain",
+                data=np.zeros((1, 2)),
+                samples=Labels(["sample"], np.array([[0]])),
+                components=[],
+            )
+
+        block.add_gradient(
+            "python-test-strain",
+            data=np.zeros((1, 3, 2)),
+            samples=Labels(["sample"], np.array([[0]])),
+            components=[xyz],
+        )
+        assert block.gradients_list() == ["python-test-strain"]
+
     def test_repr(self, block):
         expected = """TensorBlock
     samples (3): ['samples']
@@ -219,6 +245,75 @@ properties (2): ['properties']"""
         assert id(other_clone.values) != block_values_id
         assert_equal(other_clone.values, np.full((3, 3, 2), 2.0))
 
+    def test_units(self, block):
+        assert block.unit is None
+        assert block.quantity is None
+
+        block.unit = "eV"
+        block.quantity = "energy"
+        assert block.unit == "eV"
+        assert block.quantity == "energy"
+
+        clone = block.copy()
+        assert clone.unit == "eV"
+        assert clone.quantity == "energy"
+
+        block.unit = None
+        assert block.unit is None
+        assert clone.unit == "eV"
+
+        tensor = equistore.TensorMap(Labels.single(), [clone])
+        assert tensor.block(0).unit == "eV"
+
+        msg = "can not set the unit of this block since it is a view inside a TensorMap"
+        with pytest.raises(ValueError, match=msg):
+            tensor.block(0).unit = "Hartree"
+
+    def test_transpose(self):
+        block = TensorBlock(
+            values=np.arange(12, dtype=np.float64).reshape(2, 3, 2),
+            samples=Labels(["samples"], np.array([[0], [2]])),
+            components=[Labels(["component"], np.array([[-1], [0], [1]]))],
+            properties=Labels(["properties"], np.array([[5], [3]])),
+        )
+        values = block.values.copy()
+
+        block.transpose()
+        assert block.samples.names == ("properties",)
+        assert block.properties.names == ("samples",)
+        assert block.components[0].names == ("component",)
+        assert_equal(block.values, values.swapaxes(0, 2))
+
+        block.add_gradient(
+            parameter="parameter",
+            data=np.zeros((1, 3, 2)),
+            samples=Labels(["sample", "parameter"], np.array([[0, 1]])),
+            components=[Labels(["component"], np.array([[-1], [0], [1]]))],
+        )
+        msg = "can not transpose a block with gradients, remove the gradients first"
+        with pytest.raises(equistore.status.EquistoreError, match=msg):
+            block.transpose()
+
+        tensor = equistore.TensorMap(Labels.single(), [block])
+        msg = "can not transpose this block since it is a view inside a TensorMap"
+        with pytest.raises(ValueError, match=msg):
+            tensor.block(0).transpose()
+
+    def test_modify_values(self, block):
+        block.fill(2.0)
+        assert_equal(block.values, np.full((3, 2), 2.0))
+
+        block.assign_rows([2, 0], np.array([[1.0, 2.0], [3.0, 4.0]]))
+        assert_equal(block.values, np.array([[3.0, 4.0], [2.0, 2.0], [1.0, 2.0]]))
+
+        with pytest.raises(equistore.status.EquistoreError):
+            block.assign_rows([0], np.array([1.0]))
+
+        other = block.copy()
+        other.fill(0.0)
+        other.copy_from(block)
+        assert_equal(other.values, block.values)
+
     def test_shallow_copy_error(self, block):
         msg = "shallow copies of TensorBlock are not possible, use a deepcopy instead"
         with pytest.raises(ValueError, match=msg):
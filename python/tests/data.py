@@ -65,6 +65,19 @@ class ArrayWrapperMixin:
 
         free_eqs_array(eqs_array)
 
+    def test_strides(self):
+        array = self.create_array((2, 3, 4))
+        wrapper = data.ArrayWrapper(array)
+        eqs_array = wrapper.into_eqs_array()
+
+        assert _get_strides(eqs_array) == [12, 4, 1]
+
+        eqs_array.swap_axes(eqs_array.ptr, 0, 2)
+        assert _get_shape(eqs_array, self) == [4, 3, 2]
+        assert _get_strides(eqs_array) == [1, 4, 12]
+
+        free_eqs_array(eqs_array)
+
     def test_create(self):
         array = self.create_array((2, 3))
         array_ref = weakref.ref(array)
@@ -194,6 +207,20 @@ def _get_shape(eqs_array, test):
     return shape
 
 
+def _get_strides(eqs_array):
+    strides_ptr = ctypes.POINTER(c_uintptr_t)()
+    strides_count = c_uintptr_t()
+    status = eqs_array.strides(eqs_array.ptr, strides_ptr, strides_count)
+
+    assert status == EQS_SUCCESS
+
+    strides = []
+    for i in range(strides_count.value):
+        strides.append(strides_ptr[i])
+
+    return strides
+
+
 TEST_ORIGIN = equistore.data.array._register_origin("python.test-origin")
 equistore.data.register_external_data_wrapper(
     "python.test-origin",
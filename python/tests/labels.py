@@ -97,6 +97,26 @@ class TestLabels:
         )
         assert_equal(labels.asarray(), expected)
 
+    def test_native_labels_dtype(self):
+        tensor = tensor_map()
+        labels = tensor.keys
+
+        # the dtype comes from eqs_labels_structured_dtype, and should match the
+        # one of labels created in Python
+        python_labels = Labels(labels.names, labels.asarray())
+        assert labels.dtype == python_labels.dtype
+        assert labels.dtype == np.dtype([("key_1", np.int32), ("key_2", np.int32)])
+        assert np.dtype(labels.dtype.descr) == labels.dtype
+
+        # round-trip through the structured dtype
+        values = labels.view(np.int32).reshape(len(labels), -1)
+        assert_equal(values, labels.asarray())
+        assert_equal(Labels(labels.names, values), python_labels)
+
+        # the values are a view inside the Rust labels
+        assert not labels.flags.writeable
+        assert not labels.flags.owndata
+
     def test_position(self):
         tensor = tensor_map()
         labels = tensor.keys
@@ -105,6 +125,26 @@ class TestLabels:
         assert labels.position((2, 3)) == 3
         assert labels.position((2, -1)) is None
 
+    def test_positions(self):
+        tensor = tensor_map()
+        labels = tensor.keys
+
+        positions = labels.positions([(2, 3), (2, -1), (0, 0)])
+        assert positions.tolist() == [3, -1, 0]
+
+        positions = labels.positions(labels)
+        assert positions.tolist() == list(range(len(labels)))
+
+        positions = labels.positions(np.zeros((0, 2), dtype=np.int32))
+        assert positions.shape == (0,)
+
+        message = (
+            "expected a 2D array with 2 columns in Labels.positions, got an "
+            r"array with shape \(1, 3\)"
+        )
+        with pytest.raises(ValueError, match=message):
+            labels.positions([(0, 0, 0)])
+
     def test_contains(self):
         tensor = tensor_map()
         labels = tensor.keys
@@ -159,6 +199,11 @@ class TestLabels:
         assert label.names == ("_",)
         assert label.shape == (1,)
 
+    def test_labels_scalar(self):
+        label = Labels.scalar()
+        assert label.names == []
+        assert len(label) == 1
+
     def test_labels_empty(self):
         names = (
             "foo",
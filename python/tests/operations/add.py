@@ -217,5 +217,90 @@ class TestAdd:
         with pytest.raises(TypeError, match=msg):
             equistore.add(tensor_A, np.ones((3, 4)))
 
+    def test_units(self, tensor_A, tensor_B):
+        def with_unit(tensor, unit):
+            blocks = []
+            for _, block in tensor:
+                block = block.copy()
+                block.unit = unit
+                blocks.append(block)
+            return TensorMap(tensor.keys, blocks)
+
+        tensor_A = with_unit(tensor_A, "eV")
+
+        result = equistore.add(tensor_A, with_unit(tensor_B, "eV"))
+        assert result.block(0).unit == "eV"
+
+        # blocks without unit can be combined with any unit
+        result = equistore.add(tensor_B, tensor_A)
+        assert result.block(0).unit == "eV"
+
+        result = equistore.subtract(tensor_A, 3.0)
+        assert result.block(0).unit == "eV"
+
+        msg = "Inputs to 'add' should have the same unit, got 'eV' and 'Hartree'"
+        with pytest.raises(ValueError, match=msg):
+            equistore.add(tensor_A, with_unit(tensor_B, "Hartree"))
+
+        with pytest.raises(ValueError, match=msg):
+            equistore.subtract(tensor_A, with_unit(tensor_B, "Hartree"))
+
+
+    def test_missing_gradients(self, tensor_A):
+        no_gradients = equistore.remove_gradients(tensor_A)
+
+        msg = "Inputs to add should have the same gradient parameters."
+        with pytest.raises(ValueError, match=msg):
+            equistore.add(tensor_A, no_gradients)
+
+        result = equistore.add(tensor_A, no_gradients, missing_gradients="zero")
+        for key, block in tensor_A:
+            np.testing.assert_equal(result[key].values, 2 * block.values)
+            gradient = result[key].gradient("parameter")
+            np.testing.assert_equal(gradient.data, block.gradient("parameter").data)
+            assert np.all(gradient.samples == block.gradient("parameter").samples)
+
+        # the missing gradients can also be in A
+        result = equistore.subtract(no_gradients, tensor_A, missing_gradients="zero")
+        for key, block in tensor_A:
+            gradient = result[key].gradient("parameter")
+            np.testing.assert_equal(gradient.data, -block.gradient("parameter").data)
+
+        result = equistore.add(tensor_A, no_gradients, missing_gradients="drop")
+        for _, block in result:
+            assert block.gradients_list() == []
+
+        msg = "invalid value for missing_gradients in 'add'"
+        with pytest.raises(ValueError, match=msg):
+            equistore.add(tensor_A, no_gradients, missing_gradients="other")
+
+    def test_broadcast_keys(self, tensor_A):
+        blocks = []
+        for _, block in tensor_A:
+            blocks.append(
+                TensorBlock(
+                    values=np.full_like(block.values, 10),
+                    samples=block.samples,
+                    components=block.components,
+                    properties=block.properties,
+                )
+            )
+        baseline = TensorMap(Labels(["key_1"], np.array([[0], [1]])), blocks)
+
+        result = equistore.add(
+            tensor_A, baseline, broadcast_keys=True, missing_gradients="zero"
+        )
+        assert np.all(result.keys == tensor_A.keys)
+        for key, block in tensor_A:
+            np.testing.assert_equal(result[key].values, block.values + 10)
+
+        baseline = TensorMap(Labels(["key_1"], np.array([[0]])), [baseline[0].copy()])
+        msg = "there is no block in B for {'key_1': 1}"
+        with pytest.raises(ValueError, match=msg):
+            equistore.add(tensor_A, baseline, broadcast_keys=True)
+
+        msg = "broadcast_keys can only be used in 'add' with a TensorMap"
+        with pytest.raises(ValueError, match=msg):
+            equistore.add(tensor_A, 3.0, broadcast_keys=True)
 
 # TODO: add tests with torch & torch scripting/tracing
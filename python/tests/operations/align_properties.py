@@ -0,0 +1,72 @@
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+class TestAlignProperties(unittest.TestCase):
+    def test_align_properties(self):
+        first = TensorMap(
+            keys=Labels(["key"], np.array([[0], [1]])),
+            blocks=[
+                TensorBlock(
+                    values=np.array([[1.0, 2.0], [3.0, 4.0]]),
+                    samples=Labels(["samples"], np.array([[0], [1]])),
+                    components=[],
+                    properties=Labels(["n"], np.array([[0], [2]])),
+                ),
+                TensorBlock(
+                    values=np.array([[5.0]]),
+                    samples=Labels(["samples"], np.array([[0]])),
+                    components=[],
+                    properties=Labels(["n"], np.array([[1]])),
+                ),
+            ],
+        )
+        second = TensorMap(
+            keys=Labels(["key"], np.array([[0]])),
+            blocks=[
+                TensorBlock(
+                    values=np.array([[6.0, 7.0]]),
+                    samples=Labels(["samples"], np.array([[3]])),
+                    components=[],
+                    properties=Labels(["n"], np.array([[1], [2]])),
+                ),
+            ],
+        )
+
+        aligned = equistore.align_properties([first, second])
+        self.assertEqual(len(aligned), 2)
+
+        expected = Labels(["n"], np.array([[0], [1], [2]]))
+        for tensor in aligned:
+            for _, block in tensor:
+                self.assertTrue(np.all(block.properties == expected))
+
+        # missing properties are filled with zeros
+        np.testing.assert_equal(
+            aligned[0].block(0).values,
+            np.array([[1.0, 0.0, 2.0], [3.0, 0.0, 4.0]]),
+        )
+        np.testing.assert_equal(
+            aligned[0].block(1).values,
+            np.array([[0.0, 5.0, 0.0]]),
+        )
+        np.testing.assert_equal(
+            aligned[1].block(0).values,
+            np.array([[0.0, 6.0, 7.0]]),
+        )
+
+        # the inputs are not modified
+        self.assertEqual(first.block(0).properties.names, ("n",))
+        self.assertEqual(len(first.block(0).properties), 2)
+
+    def test_errors(self):
+        with self.assertRaises(TypeError):
+            equistore.align_properties(3)
+
+
+if __name__ == "__main__":
+    unittest.main()
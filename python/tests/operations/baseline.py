@@ -0,0 +1,86 @@
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+def _targets_and_compositions():
+    samples = Labels(["structure"], np.array([[0], [1], [2], [3]]))
+    compositions = np.array([[1.0, 1.0], [1.0, 2.0], [2.0, 1.0], [3.0, 0.0]])
+    weights = np.array([[-1.0, 0.5], [-2.0, 1.5]])
+
+    targets = TensorBlock(
+        values=compositions @ weights,
+        samples=samples,
+        components=[],
+        properties=Labels.arange("properties", 2),
+    )
+    targets.add_gradient(
+        "positions",
+        data=np.arange(24, dtype=np.float64).reshape(4, 3, 2),
+        samples=Labels(
+            ["sample", "structure", "atom"],
+            np.array([[0, 0, 0], [1, 1, 0], [2, 2, 0], [3, 3, 0]]),
+        ),
+        components=[Labels.arange("direction", 3)],
+    )
+
+    compositions = TensorBlock(
+        values=compositions,
+        samples=samples,
+        components=[],
+        properties=Labels(["species"], np.array([[1], [8]])),
+    )
+
+    keys = Labels(["key"], np.array([[0]]))
+    return TensorMap(keys, [targets]), TensorMap(keys, [compositions]), weights
+
+
+class TestCompositionBaseline(unittest.TestCase):
+    def test_fit_and_remove(self):
+        targets, compositions, weights = _targets_and_compositions()
+
+        baseline = equistore.fit_composition_baseline(targets, compositions)
+        block = baseline.block()
+        self.assertTrue(np.all(block.samples == targets.block().properties))
+        self.assertTrue(np.all(block.properties == compositions.block().properties))
+        self.assertEqual(len(block.gradients_list()), 0)
+
+        self.assertTrue(np.allclose(block.values, weights.T))
+
+        residual = equistore.remove_baseline(targets, baseline, compositions)
+        self.assertTrue(np.allclose(residual.block().values, 0.0))
+
+        # gradients are not modified
+        gradient = residual.block().gradient("positions")
+        reference = targets.block().gradient("positions")
+        self.assertTrue(np.all(gradient.samples == reference.samples))
+        self.assertTrue(np.all(gradient.data == reference.data))
+
+    def test_errors(self):
+        targets, compositions, _ = _targets_and_compositions()
+
+        compositions = TensorMap(
+            compositions.keys,
+            [
+                TensorBlock(
+                    values=compositions.block().values[:3],
+                    samples=Labels(["structure"], np.array([[0], [1], [2]])),
+                    components=[],
+                    properties=compositions.block().properties,
+                )
+            ],
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.fit_composition_baseline(targets, compositions)
+        self.assertIn(
+            "Inputs to 'fit_composition_baseline' should have the same samples",
+            str(cm.exception),
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
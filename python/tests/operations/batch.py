@@ -0,0 +1,104 @@
+import os
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels
+
+
+DATA_ROOT = os.path.join(os.path.dirname(__file__), "..", "data")
+TEST_FILE = "qm7-spherical-expansion.npz"
+
+
+def _batch_structures(batch):
+    structures = set()
+    for _, block in batch:
+        structures.update(block.samples["structure"].tolist())
+    return structures
+
+
+class TestBatcher(unittest.TestCase):
+    def setUp(self):
+        # this file contains 10 different structures
+        self.tensor = equistore.load(
+            os.path.join(DATA_ROOT, TEST_FILE),
+            use_numpy=True,
+        )
+
+    def test_len(self):
+        batcher = equistore.Batcher(self.tensor, batch_size=3)
+        self.assertEqual(len(batcher), 4)
+        self.assertEqual(len(list(batcher)), 4)
+
+        batcher = equistore.Batcher(self.tensor, batch_size=3, drop_last=True)
+        self.assertEqual(len(batcher), 3)
+        self.assertEqual(len(list(batcher)), 3)
+
+        batcher = equistore.Batcher(self.tensor, batch_size=20)
+        self.assertEqual(len(batcher), 1)
+
+    def test_no_shuffle(self):
+        batcher = equistore.Batcher(self.tensor, batch_size=4, shuffle=False)
+
+        batches = [_batch_structures(batch) for batch in batcher]
+        self.assertEqual(batches, [{0, 1, 2, 3}, {4, 5, 6, 7}, {8, 9}])
+
+        for batch in batcher:
+            self.assertTrue(np.all(batch.keys == self.tensor.keys))
+
+    def test_shuffle(self):
+        batcher = equistore.Batcher(self.tensor, batch_size=3, seed=12)
+
+        first_epoch = [_batch_structures(batch) for batch in batcher]
+        seen = set()
+        for structures in first_epoch:
+            self.assertEqual(len(structures & seen), 0)
+            seen.update(structures)
+        self.assertEqual(seen, set(range(10)))
+
+        # the same seed gives the same sequence of minibatches
+        other = equistore.Batcher(self.tensor, batch_size=3, seed=12)
+        self.assertEqual(first_epoch, [_batch_structures(batch) for batch in other])
+
+    def test_gradients(self):
+        batcher = equistore.Batcher(self.tensor, batch_size=2, seed=3)
+
+        for batch in batcher:
+            structures = _batch_structures(batch)
+            samples = Labels(
+                names=["structure"],
+                values=np.array(sorted(structures)).reshape(-1, 1),
+            )
+            expected = equistore.slice(self.tensor, samples=samples)
+            self.assertTrue(equistore.equal(batch, expected))
+
+            for _, block in batch:
+                for _, gradient in block.gradients():
+                    gradient_samples = gradient.samples["sample"]
+                    if len(gradient_samples) != 0:
+                        self.assertLess(np.max(gradient_samples), len(block.samples))
+
+    def test_errors(self):
+        with self.assertRaises(TypeError) as cm:
+            equistore.Batcher(self.tensor.block(0), batch_size=3)
+
+        self.assertEqual(str(cm.exception), "`tensor` must be an equistore `TensorMap`")
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.Batcher(self.tensor, batch_size=0)
+
+        self.assertEqual(str(cm.exception), "batch_size must be positive, got 0")
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.Batcher(self.tensor, batch_size=3, column="frame")
+
+        self.assertEqual(
+            str(cm.exception),
+            "'frame' is not part of the samples of this tensor, "
+            "available names are ['structure', 'center']",
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,70 @@
+import numpy as np
+import pytest
+from numpy.testing import assert_equal
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+@pytest.fixture
+def tensor():
+    block_1 = TensorBlock(
+        values=np.array([[1.0, 2.0], [3.0, 4.0]]),
+        samples=Labels(["structure"], np.array([[0], [1]])),
+        components=[],
+        properties=Labels(["n"], np.array([[0], [1]])),
+    )
+    block_2 = TensorBlock(
+        values=np.array([[5.0, 6.0, 7.0]]),
+        samples=Labels(["structure"], np.array([[1]])),
+        components=[],
+        properties=Labels(["n"], np.array([[0], [1], [2]])),
+    )
+    keys = Labels(["species"], np.array([[1], [8]]))
+    return TensorMap(keys, [block_1, block_2])
+
+
+def test_dense(tensor):
+    matrix, samples, properties = equistore.block_diagonal(tensor)
+
+    expected = np.array(
+        [
+            [1.0, 2.0, 0.0, 0.0, 0.0],
+            [3.0, 4.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 5.0, 6.0, 7.0],
+        ]
+    )
+    assert_equal(matrix, expected)
+
+    assert samples.names == ("species", "structure")
+    assert_equal(samples.tolist(), [(1, 0), (1, 1), (8, 1)])
+
+    assert properties.names == ("species", "n")
+    assert_equal(properties.tolist(), [(1, 0), (1, 1), (8, 0), (8, 1), (8, 2)])
+
+
+def test_sparse(tensor):
+    pytest.importorskip("scipy")
+
+    matrix, _, _ = equistore.block_diagonal(tensor, sparse=True)
+    dense, _, _ = equistore.block_diagonal(tensor)
+    assert matrix.shape == (3, 5)
+    assert matrix.nnz == 7
+    assert_equal(matrix.toarray(), dense)
+
+
+def test_errors(tensor):
+    block = TensorBlock(
+        values=np.zeros((1, 1, 1)),
+        samples=Labels(["structure"], np.array([[0]])),
+        components=[Labels(["m"], np.array([[0]]))],
+        properties=Labels(["n"], np.array([[0]])),
+    )
+    with_components = TensorMap(Labels.single(), [block])
+    with pytest.raises(ValueError, match="some blocks have components"):
+        equistore.block_diagonal(with_components)
+
+    keys = Labels(["structure"], np.array([[0], [1]]))
+    same_names = TensorMap(keys, [tensor.block(0).copy(), tensor.block(1).copy()])
+    with pytest.raises(ValueError, match="'structure' is used both in the keys"):
+        equistore.block_diagonal(same_names)
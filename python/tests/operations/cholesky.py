@@ -0,0 +1,148 @@
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+def _spd_tensor():
+    rng = np.random.default_rng(7)
+
+    blocks = []
+    for size in [4, 6]:
+        X = rng.normal(size=(2 * size, size))
+        properties = Labels(names=["n"], values=np.arange(size).reshape(-1, 1))
+        blocks.append(
+            TensorBlock(
+                values=X.T @ X + np.eye(size),
+                samples=properties,
+                components=[],
+                properties=properties,
+            )
+        )
+
+    return TensorMap(Labels(names=["key"], values=np.array([[0], [1]])), blocks)
+
+
+def _rhs_tensor(K):
+    rng = np.random.default_rng(8)
+
+    blocks = []
+    for _, block in K:
+        blocks.append(
+            TensorBlock(
+                values=rng.normal(size=(len(block.samples), 2)),
+                samples=block.samples,
+                components=[],
+                properties=Labels(names=["target"], values=np.array([[0], [1]])),
+            )
+        )
+
+    return TensorMap(K.keys, blocks)
+
+
+class TestCholesky(unittest.TestCase):
+    def setUp(self):
+        self.K = _spd_tensor()
+        self.Y = _rhs_tensor(self.K)
+
+    def test_cholesky(self):
+        L = equistore.cholesky(self.K)
+        self.assertTrue(np.all(L.keys == self.K.keys))
+
+        for key, block in self.K:
+            L_block = L.block(key)
+            self.assertTrue(np.all(L_block.samples == block.samples))
+            self.assertTrue(np.all(L_block.properties == block.properties))
+
+            L_values = L_block.values
+            self.assertTrue(np.allclose(L_values, np.tril(L_values)))
+            self.assertTrue(np.allclose(L_values @ L_values.T, block.values))
+
+    def test_solve_triangular(self):
+        L = equistore.cholesky(self.K)
+
+        Z = equistore.solve_triangular(L, self.Y)
+        for key, block in Z:
+            L_block = L.block(key)
+            Y_block = self.Y.block(key)
+            self.assertTrue(np.all(block.samples == L_block.properties))
+            self.assertTrue(np.all(block.properties == Y_block.properties))
+            self.assertTrue(np.allclose(L_block.values @ block.values, Y_block.values))
+
+        # solve the full system K W = Y
+        W = equistore.solve_triangular(L, Z, transpose=True)
+        for key, block in W:
+            K_values = self.K.block(key).values
+            expected = np.linalg.solve(K_values, self.Y.block(key).values)
+            self.assertTrue(np.allclose(block.values, expected))
+
+    def test_solve_triangular_upper(self):
+        L = equistore.cholesky(self.K)
+        U = TensorMap(
+            L.keys,
+            [
+                TensorBlock(
+                    values=block.values.T.copy(),
+                    samples=block.properties,
+                    components=[],
+                    properties=block.samples,
+                )
+                for _, block in L
+            ],
+        )
+
+        W = equistore.solve_triangular(U, self.Y, lower=False)
+        for key, block in W:
+            U_values = U.block(key).values
+            Y_values = self.Y.block(key).values
+            self.assertTrue(np.allclose(U_values @ block.values, Y_values))
+
+    def test_errors(self):
+        rng = np.random.default_rng(0)
+        block = TensorBlock(
+            values=rng.normal(size=(3, 2)),
+            samples=Labels(names=["s"], values=np.arange(3).reshape(-1, 1)),
+            components=[],
+            properties=Labels(names=["p"], values=np.arange(2).reshape(-1, 1)),
+        )
+        tensor = TensorMap(Labels(names=["key"], values=np.array([[0]])), [block])
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.cholesky(tensor)
+
+        self.assertEqual(
+            str(cm.exception),
+            "the values in each block of the input to `cholesky` should be a "
+            "square 2D array",
+        )
+
+        Y = TensorMap(
+            self.Y.keys,
+            [
+                TensorBlock(
+                    values=block.values,
+                    samples=Labels(
+                        names=["other"],
+                        values=np.arange(len(block.samples)).reshape(-1, 1),
+                    ),
+                    components=[],
+                    properties=block.properties,
+                )
+                for _, block in self.Y
+            ],
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.solve_triangular(equistore.cholesky(self.K), Y)
+
+        self.assertEqual(
+            str(cm.exception),
+            "Y samples should be the same as the samples of L in "
+            "`solve_triangular`, in the same order",
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,77 @@
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+def tensor(keys, values):
+    blocks = [
+        TensorBlock(
+            values=np.array(values).reshape(-1, 1),
+            samples=Labels(["samples"], np.array([[0], [1]])),
+            components=[],
+            properties=Labels(["properties"], np.array([[0]])),
+        )
+        for _ in keys
+    ]
+    return TensorMap(Labels(["key"], np.array(keys)), blocks)
+
+
+class TestDiff(unittest.TestCase):
+    def test_no_differences(self):
+        first = tensor([[0], [1]], [1.0, 2.0])
+        second = tensor([[1], [0]], [1.0, 2.0])
+
+        report = equistore.diff(first, second)
+        self.assertEqual(
+            report,
+            {
+                "metadata": [],
+                "keys_names": None,
+                "only_in_first": [],
+                "only_in_second": [],
+                "blocks": [],
+            },
+        )
+
+    def test_differences(self):
+        first = tensor([[0], [1]], [1.0, 2.0])
+        second = tensor([[1], [2]], [1.0, 2.5])
+
+        report = equistore.diff(first, second)
+        self.assertEqual(report["only_in_first"], [[0]])
+        self.assertEqual(report["only_in_second"], [[2]])
+        self.assertEqual(
+            report["blocks"],
+            [
+                {
+                    "key": [1],
+                    "parameter": "values",
+                    "difference": {"kind": "data", "max_difference": 0.5},
+                }
+            ],
+        )
+
+        report = equistore.diff(first, second, tolerance=1.0)
+        self.assertEqual(report["blocks"], [])
+
+        report = equistore.diff(first, second, compare_data=False)
+        self.assertEqual(report["blocks"], [])
+
+    def test_keys_names(self):
+        first = tensor([[0]], [1.0, 2.0])
+        second = TensorMap(Labels(["other"], np.array([[0]])), [first.block(0).copy()])
+
+        report = equistore.diff(first, second)
+        self.assertEqual(report["keys_names"], {"first": ["key"], "second": ["other"]})
+
+    def test_errors(self):
+        first = tensor([[0]], [1.0, 2.0])
+        with self.assertRaises(TypeError):
+            equistore.diff(first, first.block(0))
+
+
+if __name__ == "__main__":
+    unittest.main()
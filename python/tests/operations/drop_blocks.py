@@ -5,7 +5,7 @@ import numpy as np
 import pytest
 
 import equistore
-from equistore import Labels, TensorMap
+from equistore import Labels, TensorBlock, TensorMap
 
 
 DATA_ROOT = os.path.join(os.path.dirname(__file__), "..", "data")
@@ -27,9 +27,7 @@ class TestDropBlock:
         keys = test_tensor_map.keys
         tensor = equistore.drop_blocks(test_tensor_map, keys)
         empty_tensor = TensorMap(keys=Labels.empty(keys.names), blocks=[])
-        assert not (
-            equistore.equal(tensor, empty_tensor)
-        )  # TODO: reverse this once #175 is fixed
+        assert equistore.equal(tensor, empty_tensor)
 
     def test_drop_first(self, test_tensor_map):
         # test the behavior when the first key is dropped
@@ -110,6 +108,42 @@ class TestDropBlock:
             equistore.drop_blocks(test_tensor_map, non_existent_key)
 
 
+class TestPruneEmpty:
+    def test_prune_empty(self):
+        def block(n_samples):
+            return TensorBlock(
+                values=np.full((n_samples, 2), float(n_samples)),
+                samples=Labels(["sample"], np.arange(n_samples).reshape(-1, 1)),
+                components=[],
+                properties=Labels(["property"], np.array([[0], [1]])),
+            )
+
+        keys = Labels(["key"], np.array([[0], [1], [2], [3]]))
+        tensor = TensorMap(keys, [block(2), block(0), block(3), block(0)])
+
+        pruned = equistore.prune_empty(tensor)
+        assert pruned.keys.names == ("key",)
+        assert pruned.keys.asarray().tolist() == [[0], [2]]
+        assert pruned.block(0).values.shape == (2, 2)
+        assert pruned.block(1).values.shape == (3, 2)
+
+        # nothing to prune
+        assert equistore.equal(equistore.prune_empty(pruned), pruned)
+
+    def test_prune_all(self):
+        block = TensorBlock(
+            values=np.zeros((0, 2)),
+            samples=Labels.empty(["sample"]),
+            components=[],
+            properties=Labels(["property"], np.array([[0], [1]])),
+        )
+        tensor = TensorMap(Labels(["key"], np.array([[0]])), [block])
+
+        pruned = equistore.prune_empty(tensor)
+        assert pruned.keys.names == ("key",)
+        assert len(pruned.keys) == 0
+
+
 def check_consistency(tensor1: TensorMap, tensor2: TensorMap):
     # check if two TensorMaps contain the same information
     if tensor1.keys.names != tensor2.keys.names:
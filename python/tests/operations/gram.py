@@ -0,0 +1,99 @@
+import os
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+DATA_ROOT = os.path.join(os.path.dirname(__file__), "..", "data")
+TEST_FILE = "qm7-spherical-expansion.npz"
+
+
+class TestGram(unittest.TestCase):
+    def setUp(self):
+        self.tensor = equistore.load(
+            os.path.join(DATA_ROOT, TEST_FILE),
+            use_numpy=True,
+        )
+
+    def test_gram(self):
+        result = equistore.gram(self.tensor)
+        self.assertTrue(np.all(result.keys == self.tensor.keys))
+
+        for key, block in self.tensor:
+            gram_block = result.block(key)
+            self.assertTrue(np.all(gram_block.samples == block.properties))
+            self.assertTrue(np.all(gram_block.properties == block.properties))
+            self.assertEqual(len(gram_block.components), 0)
+            self.assertEqual(len(gram_block.gradients_list()), 0)
+
+            X = block.values.reshape(-1, block.values.shape[-1])
+            self.assertTrue(np.allclose(gram_block.values, X.T @ X))
+
+    def test_covariance(self):
+        result = equistore.covariance(self.tensor)
+
+        for key, block in self.tensor:
+            X = block.values.reshape(-1, block.values.shape[-1])
+            expected = np.cov(X, rowvar=False, ddof=1)
+            self.assertTrue(np.allclose(result.block(key).values, expected))
+
+        result = equistore.covariance(self.tensor, correction=0)
+        for key, block in self.tensor:
+            X = block.values.reshape(-1, block.values.shape[-1])
+            expected = np.cov(X, rowvar=False, ddof=0)
+            self.assertTrue(np.allclose(result.block(key).values, expected))
+
+    def test_accumulation(self):
+        structures = np.arange(10).reshape(-1, 1)
+        parts = equistore.split(
+            self.tensor,
+            axis="samples",
+            grouped_idxs=[
+                Labels(names=["structure"], values=structures[:3]),
+                Labels(names=["structure"], values=structures[3:]),
+            ],
+        )
+
+        # accumulation changes the order of floating point operations
+        gram = equistore.gram(parts)
+        expected = equistore.gram(self.tensor)
+        self.assertTrue(equistore.allclose(gram, expected, rtol=1e-10, atol=1e-10))
+
+        covariance = equistore.covariance(parts)
+        expected = equistore.covariance(self.tensor)
+        self.assertTrue(
+            equistore.allclose(covariance, expected, rtol=1e-10, atol=1e-10)
+        )
+
+    def test_errors(self):
+        with self.assertRaises(TypeError) as cm:
+            equistore.gram([])
+
+        self.assertEqual(
+            str(cm.exception),
+            "`tensor` in gram must be a TensorMap or a non-empty list of TensorMap",
+        )
+
+        block = TensorBlock(
+            values=np.ones((1, 2)),
+            samples=Labels(names=["s"], values=np.array([[0]])),
+            components=[],
+            properties=Labels(names=["p"], values=np.array([[0], [1]])),
+        )
+        tensor = TensorMap(Labels(names=["key"], values=np.array([[0]])), [block])
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.covariance(tensor)
+
+        self.assertEqual(
+            str(cm.exception),
+            "can not compute the covariance with correction=1 from 1 observations "
+            "in block (0,)",
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
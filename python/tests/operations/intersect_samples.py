@@ -0,0 +1,49 @@
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+class TestIntersectSamples(unittest.TestCase):
+    def test_intersect_samples(self):
+        first = TensorMap(
+            keys=Labels(["key"], np.array([[0]])),
+            blocks=[
+                TensorBlock(
+                    values=np.array([[1.0], [2.0], [3.0]]),
+                    samples=Labels(["samples"], np.array([[0], [1], [2]])),
+                    components=[],
+                    properties=Labels(["n"], np.array([[0]])),
+                ),
+            ],
+        )
+        second = TensorMap(
+            keys=Labels(["key"], np.array([[0]])),
+            blocks=[
+                TensorBlock(
+                    values=np.array([[4.0], [5.0]]),
+                    samples=Labels(["samples"], np.array([[2], [0]])),
+                    components=[],
+                    properties=Labels(["n"], np.array([[0]])),
+                ),
+            ],
+        )
+
+        first, second = equistore.intersect_samples(first, second)
+
+        expected = Labels(["samples"], np.array([[0], [2]]))
+        self.assertTrue(np.all(first.block(0).samples == expected))
+        self.assertTrue(np.all(second.block(0).samples == expected))
+
+        np.testing.assert_equal(first.block(0).values, np.array([[1.0], [3.0]]))
+        np.testing.assert_equal(second.block(0).values, np.array([[5.0], [4.0]]))
+
+    def test_errors(self):
+        with self.assertRaises(TypeError):
+            equistore.intersect_samples(3, 4)
+
+
+if __name__ == "__main__":
+    unittest.main()
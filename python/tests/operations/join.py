@@ -223,3 +223,122 @@ class TestJoinTensorMap:
 
         with pytest.raises(ValueError, match="Sample names are not the same!"):
             equistore.join([tensor_map_a, tensor_map_b], axis="samples")
+
+    @pytest.mark.parametrize("axis", ["samples", "properties"])
+    def test_join_different_components(self, axis):
+        """Test join function raises an error with different components."""
+        keys = Labels.arange("frame_a", 1)
+
+        def tensor(component):
+            block = TensorBlock(
+                values=np.zeros([1, 2, 1]),
+                samples=Labels.arange("samp", 1),
+                components=[Labels(["c"], np.array(component))],
+                properties=Labels.arange("idx", 1),
+            )
+            return TensorMap(keys=keys, blocks=[block])
+
+        tensor_map_a = tensor([[0], [1]])
+        tensor_map_b = tensor([[0], [2]])
+
+        message = (
+            r"components mismatch for the block with key \(1, 0\): entry 1 of "
+            r"component \[c\] is \[2\] while it is \[1\]"
+        )
+        with pytest.raises(equistore.status.EquistoreError, match=message):
+            equistore.join([tensor_map_a, tensor_map_b], axis=axis)
+
+    @pytest.mark.parametrize("axis", ["samples", "properties"])
+    def test_join_missing_gradients(self, tensor, axis):
+        """Test join with gradients only present in some of the tensor maps."""
+        no_gradients = equistore.remove_gradients(tensor)
+
+        message = "tensor map 1 does not have gradients with respect to"
+        with pytest.raises(equistore.status.EquistoreError, match=message):
+            equistore.join([tensor, no_gradients], axis=axis)
+
+        joined = equistore.join(
+            [tensor, no_gradients], axis=axis, missing_gradients="zero"
+        )
+        assert joined[0].gradients_list() == tensor[0].gradients_list()
+        for parameter, gradient in tensor[0].gradients():
+            joined_gradient = joined[0].gradient(parameter)
+            # the zero gradients do not contain any samples
+            assert len(joined_gradient.samples) == len(gradient.samples)
+
+        joined = equistore.join(
+            [tensor, no_gradients], axis=axis, missing_gradients="drop"
+        )
+        assert joined[0].gradients_list() == []
+
+        message = "invalid value for missing_gradients in 'join'"
+        with pytest.raises(ValueError, match=message):
+            equistore.join([tensor, tensor], axis=axis, missing_gradients="other")
+
+class TestJoinComponents:
+    def _tensor(self, direction, gradient_samples=None):
+        block = TensorBlock(
+            values=np.full((2, 1, 3), float(direction)),
+            samples=Labels.arange("atom", 2),
+            components=[Labels(["direction"], np.array([[direction]]))],
+            properties=Labels.arange("property", 3),
+        )
+        block.unit = "eV/A"
+
+        if gradient_samples is None:
+            gradient_samples = np.array([[0, 0], [1, 1]])
+
+        block.add_gradient(
+            "positions",
+            data=np.full((len(gradient_samples), 3, 1, 3), 10.0 * direction),
+            samples=Labels(["sample", "atom"], gradient_samples),
+            components=[
+                Labels(["xyz"], np.array([[0], [1], [2]])),
+                Labels(["direction"], np.array([[direction]])),
+            ],
+        )
+
+        return TensorMap(Labels.single(), [block])
+
+    def test_join_components(self):
+        tensors = [self._tensor(direction) for direction in range(3)]
+        joined = equistore.join_components(tensors)
+
+        block = joined.block()
+        assert block.unit == "eV/A"
+        assert block.values.shape == (2, 3, 3)
+        assert_equal(block.values[0, :, 0], [0.0, 1.0, 2.0])
+
+        component = block.components[0]
+        assert component.names == ("tensor", "direction")
+        assert_equal(component.tolist(), [(0, 0), (1, 1), (2, 2)])
+
+        gradient = block.gradient("positions")
+        assert gradient.data.shape == (2, 3, 3, 3)
+        assert_equal(gradient.data[0, 0, :, 0], [0.0, 10.0, 20.0])
+        assert gradient.components[0].names == ("xyz",)
+        assert_equal(gradient.components[1].tolist(), component.tolist())
+
+        # joining a single tensor map still adds the `tensor` dimension
+        joined = equistore.join_components([tensors[0]])
+        assert joined.block().components[0].names == ("tensor", "direction")
+
+    def test_different_samples(self):
+        first = self._tensor(0)
+        second = self._tensor(1)
+        second = TensorMap(
+            second.keys,
+            [equistore.slice_block(second.block(), samples=first.block().samples[:1])],
+        )
+
+        with pytest.raises(ValueError, match="should have the same samples"):
+            equistore.join_components([first, second])
+
+        second = self._tensor(1, gradient_samples=np.array([[0, 0]]))
+        with pytest.raises(ValueError, match="should have the same gradient"):
+            equistore.join_components([first, second])
+
+    def test_wrong_component(self):
+        tensor = self._tensor(0)
+        with pytest.raises(ValueError, match="invalid component index 1"):
+            equistore.join_components([tensor, tensor], component=1)
@@ -0,0 +1,102 @@
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+def _tensor(n_samples, gradients, seed):
+    rng = np.random.default_rng(seed)
+
+    block = TensorBlock(
+        values=rng.normal(size=(n_samples, 4)),
+        samples=Labels(
+            names=["structure"],
+            values=np.arange(n_samples).reshape(-1, 1),
+        ),
+        components=[],
+        properties=Labels(names=["n"], values=np.arange(4).reshape(-1, 1)),
+    )
+    if gradients:
+        block.add_gradient(
+            "positions",
+            data=rng.normal(size=(3, 3, 4)),
+            samples=Labels(
+                names=["sample", "structure", "atom"],
+                values=np.array([[0, 0, 0], [0, 0, 1], [2, 2, 0]]),
+            ),
+            components=[
+                Labels(names=["direction"], values=np.array([[0], [1], [2]])),
+            ],
+        )
+
+    return TensorMap(Labels(names=["key"], values=np.array([[0]])), [block])
+
+
+class TestKernels(unittest.TestCase):
+    def setUp(self):
+        self.A = _tensor(5, gradients=True, seed=1)
+        self.B = _tensor(3, gradients=False, seed=2)
+
+    def test_linear_kernel(self):
+        kernel = equistore.linear_kernel(self.A, self.B)
+        self.assertTrue(equistore.equal(kernel, equistore.dot(self.A, self.B)))
+
+        block = kernel.block(0)
+        self.assertTrue(np.all(block.samples == self.A.block(0).samples))
+        self.assertTrue(np.all(block.properties == self.B.block(0).samples))
+
+    def test_polynomial_kernel(self):
+        kernel = equistore.polynomial_kernel(self.A, self.B, degree=3)
+
+        A = self.A.block(0)
+        B = self.B.block(0)
+        block = kernel.block(0)
+        self.assertTrue(np.all(block.samples == A.samples))
+        self.assertTrue(np.all(block.properties == B.samples))
+
+        linear = A.values @ B.values.T
+        self.assertTrue(np.allclose(block.values, linear**3))
+
+        gradient = block.gradient("positions")
+        A_gradient = A.gradient("positions")
+        self.assertTrue(np.all(gradient.samples == A_gradient.samples))
+
+        for i, sample in enumerate(A_gradient.samples["sample"]):
+            expected = 3 * linear[sample] ** 2 * (A_gradient.data[i] @ B.values.T)
+            self.assertTrue(np.allclose(gradient.data[i], expected))
+
+    def test_polynomial_kernel_finite_differences(self):
+        A = self.A.block(0)
+        B = self.B.block(0)
+        A_gradient = A.gradient("positions")
+
+        delta = 1e-6
+        kernel = equistore.polynomial_kernel(self.A, self.B, degree=2)
+        gradient = kernel.block(0).gradient("positions")
+
+        # move the features of sample 2 along the first gradient direction
+        values = A.values.copy()
+        values[2] += delta * A_gradient.data[2, 0]
+        displaced = (values @ B.values.T) ** 2
+        finite_difference = (displaced[2] - kernel.block(0).values[2]) / delta
+
+        self.assertTrue(np.allclose(gradient.data[2, 0], finite_difference, atol=1e-4))
+
+    def test_errors(self):
+        with self.assertRaises(ValueError) as cm:
+            equistore.polynomial_kernel(self.A, self.B, degree=0)
+
+        self.assertEqual(str(cm.exception), "degree must be a positive integer, got 0")
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.linear_kernel(self.B, self.A)
+
+        self.assertEqual(
+            str(cm.exception), "the second TensorMap in `dot` should not have gradients"
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,116 @@
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+def tensor(values, gradient=True):
+    block = TensorBlock(
+        values=np.array(values).reshape(-1, 1),
+        samples=Labels(["samples"], np.array([[0], [1]])),
+        components=[],
+        properties=Labels(["properties"], np.array([[0]])),
+    )
+    if gradient:
+        block.add_gradient(
+            "parameter",
+            data=np.ones((2, 1)),
+            samples=Labels(["sample"], np.array([[0], [1]])),
+            components=[],
+        )
+    return TensorMap(Labels(["key"], np.array([[0]])), [block])
+
+
+class TestLazyTensorMap(unittest.TestCase):
+    def test_lazy(self):
+        first = tensor([1.0, 4.0])
+        second = tensor([3.0, 2.0])
+
+        lazy = equistore.LazyTensorMap(first).sqrt().multiply(second).add_scalar(1.0)
+        result = lazy.compute()
+
+        expected = np.array([[4.0], [5.0]])
+        np.testing.assert_allclose(result.block(0).values, expected)
+
+        # d/dx (sqrt(x) * y + 1) = dx / (2 sqrt(x)) * y + sqrt(x) * dy
+        expected = np.array([[1.0 / 2.0 * 3.0 + 1.0], [1.0 / 4.0 * 2.0 + 2.0]])
+        gradient = result.block(0).gradient("parameter")
+        np.testing.assert_allclose(gradient.data, expected)
+
+        block = lazy.compute_block(0)
+        np.testing.assert_allclose(block.values, np.array([[4.0], [5.0]]))
+
+        # the inputs are not modified
+        np.testing.assert_equal(first.block(0).values, np.array([[1.0], [4.0]]))
+
+    def test_keeps_inputs_alive(self):
+        lazy = equistore.LazyTensorMap(tensor([1.0, 2.0]))
+        lazy.add(tensor([3.0, 4.0]))
+
+        result = lazy.compute()
+        np.testing.assert_equal(result.block(0).values, np.array([[4.0], [6.0]]))
+
+    def test_missing_gradients(self):
+        first = tensor([1.0, 2.0], gradient=False)
+        second = tensor([3.0, 4.0])
+
+        with self.assertRaises(equistore.status.EquistoreError):
+            equistore.LazyTensorMap(first).add(second)
+
+        lazy = equistore.LazyTensorMap(first).missing_gradients("zero").add(second)
+        gradient = lazy.compute().block(0).gradient("parameter")
+        np.testing.assert_equal(gradient.data, np.ones((2, 1)))
+
+        lazy = equistore.LazyTensorMap(first).missing_gradients("drop").add(second)
+        self.assertEqual(lazy.compute().block(0).gradients_list(), [])
+
+        with self.assertRaises(ValueError):
+            equistore.LazyTensorMap(first).missing_gradients("nothing")
+
+    def test_broadcast_keys(self):
+        blocks = [
+            TensorBlock(
+                values=np.array([[value]]),
+                samples=Labels(["samples"], np.array([[0]])),
+                components=[],
+                properties=Labels(["properties"], np.array([[0]])),
+            )
+            for value in [1.0, 2.0, 3.0]
+        ]
+        keys = Labels(["key", "l"], np.array([[0, 0], [1, 0], [0, 1]]))
+        first = TensorMap(keys, blocks)
+
+        blocks = [
+            TensorBlock(
+                values=np.array([[value]]),
+                samples=Labels(["samples"], np.array([[0]])),
+                components=[],
+                properties=Labels(["properties"], np.array([[0]])),
+            )
+            for value in [10.0, 2.0]
+        ]
+        weights = TensorMap(Labels(["key"], np.array([[1], [0]])), blocks)
+
+        with self.assertRaises(equistore.status.EquistoreError):
+            equistore.LazyTensorMap(first).multiply(weights)
+
+        lazy = equistore.LazyTensorMap(first).broadcast_keys().multiply(weights)
+        result = lazy.compute()
+
+        np.testing.assert_equal(result.block(0).values, np.array([[2.0]]))
+        np.testing.assert_equal(result.block(1).values, np.array([[20.0]]))
+        np.testing.assert_equal(result.block(2).values, np.array([[6.0]]))
+
+    def test_errors(self):
+        with self.assertRaises(TypeError):
+            equistore.LazyTensorMap(3)
+
+        lazy = equistore.LazyTensorMap(tensor([1.0, 2.0]))
+        with self.assertRaises(equistore.status.EquistoreError):
+            lazy.compute_block(3)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,105 @@
+import os
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+DATA_ROOT = os.path.join(os.path.dirname(__file__), "..", "data")
+
+
+def _tensor():
+    block_1 = TensorBlock(
+        values=np.array([[[1.0, 2.0]], [[3.0, 4.0]]]),
+        samples=Labels(["structure", "center"], np.array([[0, 0], [0, 1]])),
+        components=[Labels(["m"], np.array([[-1]]))],
+        properties=Labels(["n"], np.array([[0], [1]])),
+    )
+
+    block_2 = TensorBlock(
+        values=np.array([[[5.0]]]),
+        samples=Labels(["structure", "center"], np.array([[1, 3]])),
+        components=[Labels(["m"], np.array([[-1]]))],
+        properties=Labels(["n"], np.array([[2]])),
+    )
+
+    keys = Labels(["center"], np.array([[6], [1]]))
+    return TensorMap(keys, [block_1, block_2])
+
+
+class TestLongTable(unittest.TestCase):
+    def test_to_long_table(self):
+        table = equistore.to_long_table(_tensor())
+
+        self.assertEqual(
+            table.dtype.names,
+            (
+                "key_center",
+                "sample_structure",
+                "sample_center",
+                "component_0_m",
+                "property_n",
+                "value",
+            ),
+        )
+        self.assertEqual(len(table), 5)
+
+        np.testing.assert_equal(table["key_center"], [6, 6, 6, 6, 1])
+        np.testing.assert_equal(table["sample_structure"], [0, 0, 0, 0, 1])
+        np.testing.assert_equal(table["sample_center"], [0, 0, 1, 1, 3])
+        np.testing.assert_equal(table["component_0_m"], [-1, -1, -1, -1, -1])
+        np.testing.assert_equal(table["property_n"], [0, 1, 0, 1, 2])
+        np.testing.assert_equal(table["value"], [1.0, 2.0, 3.0, 4.0, 5.0])
+
+    def test_roundtrip(self):
+        tensor = _tensor()
+        table = equistore.to_long_table(tensor)
+        self.assertTrue(equistore.equal(equistore.from_long_table(table), tensor))
+
+        # dict of columns
+        columns = {name: table[name] for name in table.dtype.names}
+        self.assertTrue(equistore.equal(equistore.from_long_table(columns), tensor))
+
+        tensor = equistore.load(
+            os.path.join(DATA_ROOT, "qm7-power-spectrum.npz"),
+            # the npz is using DEFLATE compression, equistore only supports STORED
+            use_numpy=True,
+        )
+        tensor = equistore.remove_gradients(tensor)
+
+        table = equistore.to_long_table(tensor)
+        self.assertTrue(equistore.equal(equistore.from_long_table(table), tensor))
+
+    def test_missing_entries(self):
+        table = equistore.to_long_table(_tensor())
+        # remove the entry with value 2.0
+        table = table[table["value"] != 2.0]
+
+        tensor = equistore.from_long_table(table)
+        np.testing.assert_equal(
+            tensor.block(0).values, np.array([[[1.0, 0.0]], [[3.0, 4.0]]])
+        )
+
+    def test_errors(self):
+        table = equistore.to_long_table(_tensor())
+
+        columns = {name: table[name] for name in table.dtype.names}
+        del columns["value"]
+        with self.assertRaisesRegex(ValueError, "must contain a 'value' column"):
+            equistore.from_long_table(columns)
+
+        columns = {name: table[name] for name in table.dtype.names}
+        columns["other"] = columns["value"]
+        with self.assertRaisesRegex(ValueError, "unexpected column 'other'"):
+            equistore.from_long_table(columns)
+
+        columns = {name: table[name] for name in table.dtype.names}
+        del columns["property_n"]
+        with self.assertRaisesRegex(ValueError, "must contain property columns"):
+            equistore.from_long_table(columns)
+
+
+if __name__ == "__main__":
+    unittest.main()
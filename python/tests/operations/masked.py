@@ -0,0 +1,143 @@
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+def _tensor(values, gradient=None):
+    block = TensorBlock(
+        values=values,
+        samples=Labels(
+            ["structure", "center"],
+            np.array([[0, 0], [0, 1], [1, 0], [1, 1]]),
+        ),
+        components=[],
+        properties=Labels.arange("properties", 2),
+    )
+    if gradient is not None:
+        block.add_gradient(
+            "parameter",
+            data=gradient,
+            samples=Labels(["sample", "positions"], np.array([[0, 0], [2, 1]])),
+            components=[],
+        )
+    keys = Labels(names=["key"], values=np.array([[0]]))
+    return TensorMap(keys, [block])
+
+
+MASK = np.array([[True, False], [True, True], [False, False], [False, True]])
+
+
+class TestMasked(unittest.TestCase):
+    def test_apply_mask(self):
+        tensor = _tensor(
+            np.array([[1.0, np.nan], [3.0, 4.0], [np.nan, np.nan], [7.0, 8.0]]),
+            gradient=np.array([[1.0, 2.0], [3.0, 4.0]]),
+        )
+        mask = _tensor(MASK)
+
+        result = equistore.apply_mask(tensor, mask, fill_value=-1.0)
+        block = result.block(0)
+        self.assertTrue(
+            np.all(
+                block.values
+                == np.array([[1.0, -1.0], [3.0, 4.0], [-1.0, -1.0], [-1.0, 8.0]])
+            )
+        )
+        gradient = block.gradient("parameter")
+        self.assertTrue(np.all(gradient.data == np.array([[1.0, 0.0], [0.0, 0.0]])))
+
+        # uint8 masks are also supported
+        result = equistore.apply_mask(tensor, _tensor(MASK.astype(np.uint8)))
+        self.assertFalse(np.any(np.isnan(result.block(0).values)))
+
+    def test_mask_metadata(self):
+        tensor = _tensor(np.zeros((4, 2)))
+        mask = TensorBlock(
+            values=np.ones((4, 1), dtype=bool),
+            samples=tensor.block(0).samples,
+            components=[],
+            properties=Labels.arange("properties", 1),
+        )
+        mask = TensorMap(tensor.keys, [mask])
+
+        with self.assertRaises(ValueError):
+            equistore.apply_mask(tensor, mask)
+
+    def test_masked_reductions(self):
+        tensor = _tensor(
+            np.array([[1.0, np.nan], [3.0, 4.0], [np.nan, np.nan], [7.0, 8.0]]),
+            gradient=np.array([[1.0, 2.0], [3.0, 4.0]]),
+        )
+        mask = _tensor(MASK)
+
+        result = equistore.masked_sum_over_samples(tensor, mask, "center")
+        block = result.block(0)
+        self.assertTrue(np.all(block.values == np.array([[4.0, 4.0], [0.0, 8.0]])))
+
+        # entries without any un-masked sample are NaN
+        result = equistore.masked_mean_over_samples(tensor, mask, "center")
+        block = result.block(0)
+        self.assertTrue(
+            np.allclose(
+                block.values, np.array([[2.0, 4.0], [np.nan, 8.0]]), equal_nan=True
+            )
+        )
+
+        gradient = block.gradient("parameter")
+        self.assertTrue(
+            np.allclose(
+                gradient.data, np.array([[0.5, 0.0], [np.nan, 0.0]]), equal_nan=True
+            )
+        )
+
+        # the block version gives the same result
+        block = equistore.masked_mean_over_samples_block(
+            tensor.block(0), mask.block(0), ["center"]
+        )
+        self.assertTrue(
+            np.allclose(block.values, result.block(0).values, equal_nan=True)
+        )
+
+    def test_masked_arithmetic(self):
+        prediction = _tensor(np.array([[1.0, 2.0], [3.0, 4.0], [5.0, 6.0], [7.0, 8.0]]))
+        target = _tensor(
+            np.array([[2.0, np.nan], [1.0, 1.0], [np.nan, np.nan], [np.nan, 2.0]])
+        )
+        mask = _tensor(MASK)
+
+        residuals = equistore.masked_subtract(prediction, target, mask)
+        self.assertTrue(
+            np.all(
+                residuals.block(0).values
+                == np.array([[-1.0, 0.0], [2.0, 3.0], [0.0, 0.0], [0.0, 6.0]])
+            )
+        )
+
+        loss = equistore.masked_sum_over_samples(
+            equistore.masked_multiply(residuals, residuals, mask),
+            mask,
+            ["structure", "center"],
+        )
+        self.assertTrue(np.all(loss.block(0).values == np.array([[5.0, 45.0]])))
+
+        result = equistore.masked_add(prediction, 1.0, mask)
+        self.assertTrue(
+            np.all(
+                result.block(0).values
+                == np.array([[2.0, 0.0], [4.0, 5.0], [0.0, 0.0], [0.0, 9.0]])
+            )
+        )
+
+        result = equistore.masked_divide(prediction, target, mask, fill_value=np.nan)
+        values = result.block(0).values
+        self.assertTrue(np.all(np.isnan(values) == ~MASK))
+        self.assertEqual(values[0, 0], 0.5)
+        self.assertEqual(values[1, 0], 3.0)
+        self.assertEqual(values[3, 1], 4.0)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -292,6 +292,141 @@ class TestMultiply(unittest.TestCase):
         )
 
 
+    def test_multiply_broadcast_properties(self):
+        block_1 = TensorBlock(
+            values=np.array([[1, 2], [3, 5]]),
+            samples=Labels(["samples"], np.array([[0], [2]])),
+            components=[],
+            properties=Labels.arange("properties", 2),
+        )
+        block_1.add_gradient(
+            "parameter",
+            data=np.array([[[6, 1], [7, 2]], [[8, 3], [9, 4]]]),
+            samples=Labels(["sample", "positions"], np.array([[0, 1], [1, 1]])),
+            components=[
+                Labels.arange("components", 2),
+            ],
+        )
+        block_2 = TensorBlock(
+            values=np.array([[2], [3]]),
+            samples=Labels(["samples"], np.array([[0], [2]])),
+            components=[],
+            properties=Labels.arange("scale", 1),
+        )
+
+        block_res = TensorBlock(
+            values=np.array([[2, 4], [9, 15]]),
+            samples=Labels(["samples"], np.array([[0], [2]])),
+            components=[],
+            properties=Labels.arange("properties", 2),
+        )
+        block_res.add_gradient(
+            "parameter",
+            data=np.array([[[12, 2], [14, 4]], [[24, 9], [27, 12]]]),
+            samples=Labels(["sample", "positions"], np.array([[0, 1], [1, 1]])),
+            components=[
+                Labels.arange("components", 2),
+            ],
+        )
+
+        keys = Labels(names=["key_1", "key_2"], values=np.array([[0, 0]]))
+        A = TensorMap(keys, [block_1])
+        B = TensorMap(keys, [block_2])
+
+        result = equistore.multiply(A, B, broadcast="properties")
+        self.assertTrue(equistore.allclose(result, TensorMap(keys, [block_res])))
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.multiply(A, B, broadcast="samples")
+        self.assertIn(
+            "Inputs to 'multiply' should have the same properties", str(cm.exception)
+        )
+
+
+    def test_multiply_missing_gradients(self):
+        block_1 = TensorBlock(
+            values=np.array([[1, 2], [3, 5]]),
+            samples=Labels(["samples"], np.array([[0], [2]])),
+            components=[],
+            properties=Labels.arange("properties", 2),
+        )
+        block_1.add_gradient(
+            "parameter",
+            data=np.array([[[6, 1], [7, 2]], [[8, 3], [9, 4]]]),
+            samples=Labels(["sample", "positions"], np.array([[0, 1], [1, 1]])),
+            components=[
+                Labels.arange("components", 2),
+            ],
+        )
+        block_2 = TensorBlock(
+            values=np.array([[2, 3], [4, 5]]),
+            samples=Labels(["samples"], np.array([[0], [2]])),
+            components=[],
+            properties=Labels.arange("properties", 2),
+        )
+
+        keys = Labels(names=["key_1", "key_2"], values=np.array([[0, 0]]))
+        A = TensorMap(keys, [block_1])
+        B = TensorMap(keys, [block_2])
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.multiply(A, B)
+        self.assertEqual(
+            str(cm.exception),
+            "Inputs to multiply should have the same gradient parameters.",
+        )
+
+        result = equistore.multiply(A, B, missing_gradients="zero")
+        np.testing.assert_equal(result.block(0).values, [[2, 6], [12, 25]])
+        np.testing.assert_equal(
+            result.block(0).gradient("parameter").data,
+            [[[12, 3], [14, 6]], [[32, 15], [36, 20]]],
+        )
+
+        result = equistore.multiply(A, B, missing_gradients="drop")
+        self.assertEqual(result.block(0).gradients_list(), [])
+
+    def test_multiply_broadcast_keys(self):
+        def block(value):
+            return TensorBlock(
+                values=np.full((2, 2), value),
+                samples=Labels(["samples"], np.array([[0], [1]])),
+                components=[],
+                properties=Labels.arange("properties", 2),
+            )
+
+        keys = Labels(["species", "l"], np.array([[1, 0], [1, 1], [6, 0]]))
+        A = TensorMap(keys, [block(1.0), block(2.0), block(3.0)])
+        weights = TensorMap(
+            Labels(["species"], np.array([[1], [6]])), [block(2.0), block(3.0)]
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.multiply(A, weights)
+        self.assertIn("Inputs to multiply should have the same keys", str(cm.exception))
+
+        for result in [
+            equistore.multiply(A, weights, broadcast_keys=True),
+            equistore.multiply(weights, A, broadcast_keys=True),
+        ]:
+            self.assertEqual(result.keys.tolist(), keys.tolist())
+            np.testing.assert_equal(result.block(0).values, np.full((2, 2), 2.0))
+            np.testing.assert_equal(result.block(1).values, np.full((2, 2), 4.0))
+            np.testing.assert_equal(result.block(2).values, np.full((2, 2), 9.0))
+
+        missing = TensorMap(Labels(["species"], np.array([[1]])), [block(2.0)])
+        with self.assertRaises(ValueError) as cm:
+            equistore.multiply(A, missing, broadcast_keys=True)
+        self.assertIn("there is no block in B for {'species': 6}", str(cm.exception))
+
+        other = TensorMap(Labels(["other"], np.array([[1]])), [block(2.0)])
+        with self.assertRaises(ValueError) as cm:
+            equistore.multiply(A, other, broadcast_keys=True)
+        self.assertIn(
+            "the key names of one input must be a subset of the other",
+            str(cm.exception),
+        )
+
 # TODO: multiply tests with torch & torch scripting/tracing
 
 if __name__ == "__main__":
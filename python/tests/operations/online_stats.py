@@ -0,0 +1,112 @@
+import os
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+DATA_ROOT = os.path.join(os.path.dirname(__file__), "..", "data")
+TEST_FILE = "qm7-spherical-expansion.npz"
+
+
+def _tensor(values, key=0):
+    return TensorMap(
+        keys=Labels(names=["key"], values=np.array([[key]])),
+        blocks=[
+            TensorBlock(
+                values=values,
+                samples=Labels(
+                    names=["sample"],
+                    values=np.arange(values.shape[0]).reshape(-1, 1),
+                ),
+                components=[],
+                properties=Labels(
+                    names=["property"],
+                    values=np.arange(values.shape[1]).reshape(-1, 1),
+                ),
+            )
+        ],
+    )
+
+
+class TestOnlineStats(unittest.TestCase):
+    def test_batches(self):
+        rng = np.random.default_rng(0)
+        all_values = rng.normal(loc=3.0, scale=2.0, size=(50, 4))
+
+        stats = equistore.OnlineStats()
+        for start, stop in [(0, 7), (7, 8), (8, 30), (30, 30), (30, 50)]:
+            stats.update(_tensor(all_values[start:stop]))
+
+        self.assertEqual(stats.count(), {(0,): 50})
+
+        mean = stats.mean()
+        self.assertEqual(mean.keys.names, ("key",))
+        self.assertEqual(mean.block(0).values.shape, (1, 4))
+        self.assertTrue(
+            np.allclose(mean.block(0).values[0], np.mean(all_values, axis=0))
+        )
+
+        var = stats.var()
+        self.assertTrue(np.allclose(var.block(0).values[0], np.var(all_values, axis=0)))
+
+        std = stats.std(correction=1)
+        self.assertTrue(
+            np.allclose(std.block(0).values[0], np.std(all_values, axis=0, ddof=1))
+        )
+
+    def test_multiple_blocks(self):
+        tensor = equistore.load(
+            os.path.join(DATA_ROOT, TEST_FILE),
+            use_numpy=True,
+        )
+
+        stats = equistore.OnlineStats()
+        for batch in equistore.Batcher(tensor, batch_size=3, seed=1):
+            stats.update(batch)
+
+        mean = stats.mean()
+        std = stats.std()
+        for key, block in tensor:
+            self.assertEqual(len(mean.block(key).samples), 1)
+            self.assertTrue(np.all(mean.block(key).properties == block.properties))
+            self.assertTrue(
+                np.allclose(mean.block(key).values[0], np.mean(block.values, axis=0))
+            )
+            self.assertTrue(
+                np.allclose(std.block(key).values[0], np.std(block.values, axis=0))
+            )
+
+    def test_errors(self):
+        stats = equistore.OnlineStats()
+
+        with self.assertRaises(ValueError) as cm:
+            stats.mean()
+
+        self.assertEqual(
+            str(cm.exception), "no data was accumulated in this OnlineStats"
+        )
+
+        stats.update(_tensor(np.ones((3, 2))))
+        with self.assertRaises(ValueError) as cm:
+            stats.update(_tensor(np.ones((3, 4))))
+
+        self.assertEqual(
+            str(cm.exception),
+            "the properties of the block (0,) do not match the ones from "
+            "previous batches",
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            stats.var(correction=3)
+
+        self.assertEqual(
+            str(cm.exception),
+            "can not compute the variance with correction=3 from 3 samples",
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,113 @@
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+def _tensor():
+    rng = np.random.default_rng(4)
+
+    blocks = []
+    for n_samples in [20, 12]:
+        block = TensorBlock(
+            values=rng.normal(size=(n_samples, 3, 5)),
+            samples=Labels(
+                names=["structure"],
+                values=np.arange(n_samples).reshape(-1, 1),
+            ),
+            components=[Labels(names=["m"], values=np.array([[-1], [0], [1]]))],
+            properties=Labels(names=["n"], values=np.arange(5).reshape(-1, 1)),
+        )
+        block.add_gradient(
+            "positions",
+            data=rng.normal(size=(2, 3, 3, 5)),
+            samples=Labels(
+                names=["sample", "structure", "atom"],
+                values=np.array([[0, 0, 0], [3, 3, 1]]),
+            ),
+            components=[
+                Labels(names=["direction"], values=np.array([[0], [1], [2]])),
+                block.components[0],
+            ],
+        )
+        blocks.append(block)
+
+    return TensorMap(Labels(names=["key"], values=np.array([[0], [1]])), blocks)
+
+
+class TestPCA(unittest.TestCase):
+    def setUp(self):
+        self.tensor = _tensor()
+
+    def test_pca(self):
+        projector, projected = equistore.pca(self.tensor, n_components=2)
+
+        self.assertTrue(np.all(projector.keys == self.tensor.keys))
+        self.assertTrue(np.all(projected.keys == self.tensor.keys))
+
+        for key, block in self.tensor:
+            X = block.values.reshape(-1, 5)
+            X = X - np.mean(X, axis=0)
+            reference = np.linalg.svd(X, full_matrices=False)[2][:2]
+
+            projector_block = projector.block(key)
+            self.assertEqual(projector_block.samples.names, ("pca",))
+            self.assertEqual(len(projector_block.samples), 2)
+            self.assertTrue(np.all(projector_block.properties == block.properties))
+
+            P = projector_block.values
+            self.assertTrue(np.allclose(P @ P.T, np.eye(2)))
+            # principal components are defined up to a sign
+            self.assertTrue(np.allclose(np.abs(P), np.abs(reference)))
+
+            projected_block = projected.block(key)
+            self.assertTrue(np.all(projected_block.samples == block.samples))
+            self.assertEqual(len(projected_block.components), 1)
+            self.assertTrue(
+                np.all(projected_block.properties == projector_block.samples)
+            )
+            self.assertTrue(
+                np.allclose(projected_block.values, (X @ P.T).reshape(-1, 3, 2))
+            )
+
+            gradient = block.gradient("positions")
+            projected_gradient = projected_block.gradient("positions")
+            self.assertTrue(np.all(projected_gradient.samples == gradient.samples))
+            self.assertTrue(np.allclose(projected_gradient.data, gradient.data @ P.T))
+
+    def test_randomized(self):
+        projector, projected = equistore.pca(self.tensor, n_components=2)
+        randomized_projector, randomized_projected = equistore.pca(
+            self.tensor, n_components=2, solver="randomized", seed=0
+        )
+
+        self.assertTrue(
+            equistore.allclose(projector, randomized_projector, rtol=1e-8, atol=1e-8)
+        )
+        self.assertTrue(
+            equistore.allclose(projected, randomized_projected, rtol=1e-8, atol=1e-8)
+        )
+
+    def test_errors(self):
+        with self.assertRaises(ValueError) as cm:
+            equistore.pca(self.tensor, n_components=2, solver="lapack")
+
+        self.assertEqual(
+            str(cm.exception),
+            "invalid solver 'lapack', expected 'full' or 'randomized'",
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.pca(self.tensor, n_components=6)
+
+        self.assertEqual(
+            str(cm.exception),
+            "can not compute 6 principal components for the block (0,) with "
+            "shape (20, 3, 5)",
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,95 @@
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+def _block(values, properties):
+    block = TensorBlock(
+        values=values,
+        samples=Labels.arange("samples", values.shape[0]),
+        components=[],
+        properties=properties,
+    )
+    block.add_gradient(
+        "parameter",
+        data=np.ones((1, 3, values.shape[1])),
+        samples=Labels(["sample", "atom"], np.array([[0, 1]])),
+        components=[Labels.arange("xyz", 3)],
+    )
+    return block
+
+
+class TestScaleProperties(unittest.TestCase):
+    def setUp(self):
+        keys = Labels(["key"], np.array([[0], [1]]))
+        self.tensor = TensorMap(
+            keys,
+            [
+                _block(np.ones((2, 3)), Labels.arange("properties", 3)),
+                _block(np.full((3, 3), 2.0), Labels.arange("properties", 3)),
+            ],
+        )
+
+    def test_array_weights(self):
+        weights = np.array([1.0, 2.0, 3.0])
+        result = equistore.scale_properties(self.tensor, weights)
+
+        block = result.block(0)
+        self.assertTrue(np.all(block.values == np.array([[1.0, 2.0, 3.0]] * 2)))
+        gradient = block.gradient("parameter")
+        self.assertTrue(np.all(gradient.data[0, 1] == weights))
+
+        block = result.block(1)
+        self.assertTrue(np.all(block.values == np.array([[2.0, 4.0, 6.0]] * 3)))
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.scale_properties(self.tensor, np.array([1.0, 2.0]))
+        self.assertIn("with 3 entries, got an array with shape (2,)", str(cm.exception))
+
+    def test_tensor_weights(self):
+        weights = TensorMap(
+            self.tensor.keys,
+            [
+                TensorBlock(
+                    values=np.array([[1.0, 0.0, -1.0]]),
+                    samples=Labels.single(),
+                    components=[],
+                    properties=Labels.arange("properties", 3),
+                ),
+                TensorBlock(
+                    values=np.array([[0.5, 0.5, 0.5]]),
+                    samples=Labels.single(),
+                    components=[],
+                    properties=Labels.arange("properties", 3),
+                ),
+            ],
+        )
+
+        result = equistore.scale_properties(self.tensor, weights)
+        self.assertTrue(np.all(result.block(0).values[0] == [1.0, 0.0, -1.0]))
+        self.assertTrue(np.all(result.block(1).values == 1.0))
+        gradient = result.block(1).gradient("parameter")
+        self.assertTrue(np.all(gradient.data == 0.5))
+
+        weights = TensorMap(
+            self.tensor.keys,
+            [
+                TensorBlock(
+                    values=np.ones((1, 3)),
+                    samples=Labels.single(),
+                    components=[],
+                    properties=Labels.arange("other", 3),
+                )
+                for _ in range(2)
+            ],
+        )
+        with self.assertRaises(ValueError) as cm:
+            equistore.scale_properties(self.tensor, weights)
+        self.assertIn("should have the same properties", str(cm.exception))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,158 @@
+import os
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+DATA_ROOT = os.path.join(os.path.dirname(__file__), "..", "data")
+TEST_FILE = "qm7-spherical-expansion.npz"
+
+
+def _block(values):
+    values = np.asarray(values, dtype=np.float64)
+    return TensorBlock(
+        values=values,
+        samples=Labels(
+            names=["sample"],
+            values=np.arange(values.shape[0], dtype=np.int32).reshape(-1, 1),
+        ),
+        components=[],
+        properties=Labels(
+            names=["property"],
+            values=np.arange(values.shape[1], dtype=np.int32).reshape(-1, 1),
+        ),
+    )
+
+
+class TestSelectFPS(unittest.TestCase):
+    def setUp(self):
+        self.tensor = equistore.load(
+            os.path.join(DATA_ROOT, TEST_FILE),
+            use_numpy=True,
+        )
+
+    def test_fps_block(self):
+        block = _block([[0.0, 0.0], [1.0, 0.0], [10.0, 0.0], [0.0, 5.0]])
+
+        selected = equistore.select_fps_block(block, n_to_select=4)
+        self.assertEqual(selected.names, ("sample",))
+        self.assertEqual(selected["sample"].tolist(), [0, 2, 3, 1])
+
+        selected = equistore.select_fps_block(block, n_to_select=2, initial=1)
+        self.assertEqual(selected["sample"].tolist(), [1, 2])
+
+        selected = equistore.select_fps_block(block.copy(), 1, axis="properties")
+        self.assertEqual(selected.names, ("property",))
+        self.assertEqual(selected["property"].tolist(), [0])
+
+    def test_fps_duplicated_points(self):
+        block = _block([[0.0, 0.0], [0.0, 0.0], [0.0, 0.0]])
+
+        selected = equistore.select_fps_block(block, n_to_select=3)
+        self.assertEqual(sorted(selected["sample"].tolist()), [0, 1, 2])
+
+    def test_fps_tensor(self):
+        selected = equistore.select_fps(self.tensor, n_to_select=3, axis="properties")
+        self.assertEqual(len(selected), 3)
+        self.assertEqual(selected.names, self.tensor.block(0).properties.names)
+        self.assertEqual(len(set(selected["n"].tolist())), 3)
+
+        # all blocks have the same properties, the selection can be used to
+        # slice the whole tensor
+        sliced = equistore.slice(self.tensor, properties=selected)
+        for _, block in sliced:
+            self.assertEqual(len(block.properties), 3)
+
+    def test_errors(self):
+        block = _block([[0.0, 0.0], [1.0, 0.0]])
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.select_fps_block(block, n_to_select=3)
+
+        self.assertEqual(
+            str(cm.exception),
+            "n_to_select must be between 1 and the number of entries (2), got 3",
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.select_fps_block(block, n_to_select=1, initial=2)
+
+        self.assertEqual(str(cm.exception), "initial must be between 0 and 1, got 2")
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.select_fps_block(block, n_to_select=1, axis="components")
+
+        self.assertEqual(
+            str(cm.exception), "axis must be either 'samples' or 'properties'"
+        )
+
+        with self.assertRaises(TypeError) as cm:
+            equistore.select_fps_block(self.tensor, n_to_select=1)
+
+        self.assertEqual(
+            str(cm.exception), "`block` must be an equistore `TensorBlock`"
+        )
+
+        with self.assertRaises(TypeError) as cm:
+            equistore.select_fps(block, n_to_select=1)
+
+        self.assertEqual(str(cm.exception), "`tensor` must be an equistore `TensorMap`")
+
+        # blocks have different samples
+        with self.assertRaises(ValueError) as cm:
+            equistore.select_fps(self.tensor, n_to_select=1)
+
+        self.assertEqual(
+            str(cm.exception),
+            "all blocks must have the same samples to select samples "
+            "in the whole tensor",
+        )
+
+
+class TestSelectCUR(unittest.TestCase):
+    def setUp(self):
+        self.tensor = equistore.load(
+            os.path.join(DATA_ROOT, TEST_FILE),
+            use_numpy=True,
+        )
+
+    def test_cur_block(self):
+        # the second property is twice the first one, and the third one is
+        # independent from both
+        block = _block([[1.0, 2.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0, 0.0]])
+
+        selected = equistore.select_cur_block(block, 2, axis="properties")
+        self.assertEqual(selected["property"].tolist(), [1, 2])
+
+        selected = equistore.select_cur_block(block, 2, axis="samples")
+        self.assertEqual(selected["sample"].tolist(), [0, 1])
+
+    def test_cur_tensor(self):
+        selected = equistore.select_cur(self.tensor, n_to_select=4, axis="properties")
+        self.assertEqual(sorted(selected["n"].tolist()), [0, 1, 2, 3])
+
+        keys = Labels(names=["single"], values=np.array([[0]], dtype=np.int32))
+        block = self.tensor.block(0)
+        single = TensorMap(keys, [block.copy()])
+
+        selected = equistore.select_cur(single, n_to_select=2, axis="properties")
+        expected = equistore.select_cur_block(block, n_to_select=2, axis="properties")
+        self.assertEqual(selected.tolist(), expected.tolist())
+
+    def test_errors(self):
+        block = _block([[0.0, 0.0], [1.0, 0.0]])
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.select_cur_block(block, n_to_select=0)
+
+        self.assertEqual(
+            str(cm.exception),
+            "n_to_select must be between 1 and the number of entries (2), got 0",
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -385,6 +385,69 @@ class TestSliceBoth(unittest.TestCase):
         )
 
 
+class TestSliceMask(unittest.TestCase):
+    """Slicing TensorMap and TensorBlock with boolean masks"""
+
+    def setUp(self):
+        self.tensor = equistore.load(
+            os.path.join(DATA_ROOT, TEST_FILE),
+            use_numpy=True,
+        )
+
+    def test_slice_block(self):
+        block = self.tensor.block(5)
+
+        samples_mask = block.samples["center"] % 2 == 1
+        properties_mask = block.properties["n"] < 3
+
+        sliced_block = equistore.slice_block(
+            block,
+            samples=samples_mask,
+            properties=properties_mask,
+        )
+
+        expected = equistore.slice_block(
+            block,
+            samples=Labels(
+                names=["center"],
+                values=np.unique(block.samples["center"][samples_mask]).reshape(-1, 1),
+            ),
+            properties=Labels(
+                names=["n"],
+                values=np.unique(block.properties["n"][properties_mask]).reshape(-1, 1),
+            ),
+        )
+
+        self.assertTrue(equistore.equal_block(sliced_block, expected))
+        self.assertEqual(len(sliced_block.samples), np.sum(samples_mask))
+        self.assertEqual(len(sliced_block.properties), np.sum(properties_mask))
+
+    def test_slice(self):
+        n_properties = len(self.tensor.block(0).properties)
+        for _, block in self.tensor:
+            # the mask is applied to all blocks, so they need the same properties
+            self.assertEqual(len(block.properties), n_properties)
+
+        properties_mask = np.zeros(n_properties, dtype=bool)
+        properties_mask[::2] = True
+
+        sliced_tensor = equistore.slice(self.tensor, properties=properties_mask)
+
+        for key, block in self.tensor:
+            sliced_block = sliced_tensor.block(key)
+            self.assertTrue(
+                np.all(sliced_block.properties == block.properties[properties_mask])
+            )
+            self.assertTrue(
+                np.all(sliced_block.values == block.values[..., properties_mask])
+            )
+            for parameter, gradient in block.gradients():
+                sliced_gradient = sliced_block.gradient(parameter)
+                self.assertTrue(
+                    np.all(sliced_gradient.data == gradient.data[..., properties_mask])
+                )
+
+
 class TestSliceErrors(unittest.TestCase):
     def setUp(self):
         self.tensor = equistore.load(
@@ -416,7 +479,7 @@ class TestSliceErrors(unittest.TestCase):
 
         self.assertEqual(
             str(cm.exception),
-            "samples must be a `Labels` object",
+            "samples must be a `Labels` object or a boolean mask",
         )
 
         # passing properties=np.array raises TypeError
@@ -428,7 +491,7 @@ class TestSliceErrors(unittest.TestCase):
 
         self.assertEqual(
             str(cm.exception),
-            "properties must be a `Labels` object",
+            "properties must be a `Labels` object or a boolean mask",
         )
 
     def test_slice_block_errors(self):
@@ -455,7 +518,7 @@ class TestSliceErrors(unittest.TestCase):
 
         self.assertEqual(
             str(cm.exception),
-            "samples must be a `Labels` object",
+            "samples must be a `Labels` object or a boolean mask",
         )
 
         # passing properties=np.array raises TypeError
@@ -467,9 +530,47 @@ class TestSliceErrors(unittest.TestCase):
 
         self.assertEqual(
             str(cm.exception),
-            "properties must be a `Labels` object",
+            "properties must be a `Labels` object or a boolean mask",
+        )
+
+    def test_slice_mask_errors(self):
+        block = self.tensor.block(0)
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.slice_block(
+                block,
+                samples=np.ones((len(block.samples), 1), dtype=bool),
+            )
+
+        self.assertEqual(
+            str(cm.exception), "samples mask must be a 1-dimensional array"
         )
 
+        n_samples = len(block.samples)
+        with self.assertRaises(ValueError) as cm:
+            equistore.slice_block(
+                block,
+                samples=np.ones(n_samples + 1, dtype=bool),
+            )
+
+        self.assertEqual(
+            str(cm.exception),
+            f"samples mask has {n_samples + 1} entries, but the block "
+            f"has {n_samples} samples",
+        )
+
+        n_properties = len(block.properties)
+        with self.assertRaises(ValueError) as cm:
+            equistore.slice(
+                self.tensor,
+                properties=np.ones(n_properties - 1, dtype=bool),
+            )
+
+        self.assertEqual(
+            str(cm.exception),
+            f"properties mask has {n_properties - 1} entries, but the block "
+            f"has {n_properties} properties",
+        )
 
 if __name__ == "__main__":
     unittest.main()
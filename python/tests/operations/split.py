@@ -500,6 +500,147 @@ class TestSplitErrors(unittest.TestCase):
         )
 
 
+class TestKFoldSplit(unittest.TestCase):
+    def setUp(self):
+        # this file contains 10 different structures
+        self.tensor = equistore.load(
+            os.path.join(DATA_ROOT, TEST_FILE_1),
+            use_numpy=True,
+        )
+
+    def _structures(self, tensor):
+        structures = set()
+        for _, block in tensor:
+            structures.update(block.samples["structure"].tolist())
+        return structures
+
+    def test_kfold_split(self):
+        folds = equistore.kfold_split(self.tensor, "structure", k=3, seed=0)
+        self.assertEqual(len(folds), 3)
+
+        all_test = set()
+        for train, test in folds:
+            train_structures = self._structures(train)
+            test_structures = self._structures(test)
+
+            # no leakage between train and test sets
+            self.assertEqual(len(train_structures & test_structures), 0)
+            self.assertEqual(train_structures | test_structures, set(range(10)))
+            self.assertIn(len(test_structures), [3, 4])
+
+            self.assertTrue(np.all(train.keys == self.tensor.keys))
+            self.assertTrue(np.all(test.keys == self.tensor.keys))
+
+            # all samples end up in either train or test
+            for key, block in self.tensor:
+                self.assertEqual(
+                    len(train.block(key).samples) + len(test.block(key).samples),
+                    len(block.samples),
+                )
+
+            # each structure is in exactly one test set
+            self.assertEqual(len(all_test & test_structures), 0)
+            all_test.update(test_structures)
+
+        self.assertEqual(all_test, set(range(10)))
+
+    def test_deterministic(self):
+        first = equistore.kfold_split(self.tensor, "structure", k=4, seed=7)
+        second = equistore.kfold_split(self.tensor, "structure", k=4, seed=7)
+
+        for (train_1, test_1), (train_2, test_2) in zip(first, second):
+            self.assertTrue(equistore.equal(train_1, train_2))
+            self.assertTrue(equistore.equal(test_1, test_2))
+
+    def test_no_shuffle(self):
+        folds = equistore.kfold_split(self.tensor, "structure", k=3, shuffle=False)
+
+        tests = [self._structures(test) for _, test in folds]
+        self.assertEqual(tests, [{0, 1, 2, 3}, {4, 5, 6}, {7, 8, 9}])
+
+    def test_errors(self):
+        with self.assertRaises(TypeError) as cm:
+            equistore.kfold_split(self.tensor.block(0), "structure", k=3)
+
+        self.assertEqual(
+            str(cm.exception), "``tensor`` should be an equistore ``TensorMap``"
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.kfold_split(self.tensor, "frame", k=3)
+
+        self.assertEqual(
+            str(cm.exception),
+            "'frame' is not part of the samples of this tensor, "
+            "available names are ['structure', 'center']",
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.kfold_split(self.tensor, "structure", k=11)
+
+        self.assertEqual(
+            str(cm.exception),
+            "k must be between 2 and the number of different 'structure' (10), "
+            "got 11",
+        )
+
+
+class TestSubsample(unittest.TestCase):
+    def setUp(self):
+        # this file contains 10 different structures
+        self.tensor = equistore.load(
+            os.path.join(DATA_ROOT, TEST_FILE_1),
+            use_numpy=True,
+        )
+
+    def _structures(self, tensor):
+        structures = set()
+        for _, block in tensor:
+            structures.update(block.samples["structure"].tolist())
+        return structures
+
+    def test_subsample(self):
+        subsets = equistore.subsample(
+            self.tensor, [0.2, 0.5, 1.0, 0.01], "structure", seed=3
+        )
+        self.assertEqual(len(subsets), 4)
+
+        structures = [self._structures(subset) for subset in subsets]
+        self.assertEqual([len(s) for s in structures], [2, 5, 10, 1])
+
+        # smaller subsets are contained in larger ones
+        self.assertTrue(structures[0] <= structures[1])
+        self.assertTrue(structures[1] <= structures[2])
+        self.assertTrue(structures[3] <= structures[0])
+        self.assertEqual(structures[2], set(range(10)))
+
+        for subset in subsets:
+            self.assertTrue(np.all(subset.keys == self.tensor.keys))
+
+        again = equistore.subsample(
+            self.tensor, [0.2, 0.5, 1.0, 0.01], "structure", seed=3
+        )
+        for subset_1, subset_2 in zip(subsets, again):
+            self.assertTrue(equistore.equal(subset_1, subset_2))
+
+    def test_errors(self):
+        with self.assertRaises(ValueError) as cm:
+            equistore.subsample(self.tensor, [0.5], "frame")
+
+        self.assertEqual(
+            str(cm.exception),
+            "'frame' is not part of the samples of this tensor, "
+            "available names are ['structure', 'center']",
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.subsample(self.tensor, [0.5, 1.5], "structure")
+
+        self.assertEqual(
+            str(cm.exception), "fractions must be in the (0, 1] range, got 1.5"
+        )
+
+
 def _unique_indices(
     tensor: Union[TensorMap, TensorBlock],
     axis: str,
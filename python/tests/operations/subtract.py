@@ -286,6 +286,77 @@ class TestSubtract(unittest.TestCase):
         )
 
 
+    def test_subtract_broadcast_samples(self):
+        block_1 = TensorBlock(
+            values=np.array([[1.0, 2.0], [3.0, 6.0]]),
+            samples=Labels(["samples"], np.array([[0], [2]])),
+            components=[],
+            properties=Labels.arange("properties", 2),
+        )
+        block_1.add_gradient(
+            "parameter",
+            data=np.array([[[6.0, 1.0]], [[8.0, 3.0]]]),
+            samples=Labels(["sample", "positions"], np.array([[0, 1], [1, 1]])),
+            components=[Labels.arange("components", 1)],
+        )
+        keys = Labels(names=["key_1"], values=np.array([[0]]))
+        A = TensorMap(keys, [block_1])
+        mean = equistore.mean_over_samples(A, sample_names="samples")
+        mean = equistore.remove_gradients(mean)
+
+        result = equistore.subtract(A, mean, broadcast="samples")
+        block = result.block()
+        self.assertTrue(np.allclose(block.values, np.array([[-1, -2], [1, 2]])))
+        self.assertTrue(
+            np.allclose(
+                block.gradient("parameter").data, block_1.gradient("parameter").data
+            )
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.subtract(A, A, broadcast="samples")
+        self.assertEqual(
+            str(cm.exception),
+            "Inputs to 'add' with broadcast='samples' should have a single "
+            "sample in the blocks of B, got 2",
+        )
+
+    def test_subtract_broadcast_properties(self):
+        block_1 = TensorBlock(
+            values=np.array([[1.0, 2.0], [3.0, 6.0]]),
+            samples=Labels(["samples"], np.array([[0], [2]])),
+            components=[],
+            properties=Labels.arange("properties", 2),
+        )
+        block_2 = TensorBlock(
+            values=np.array([[1.0], [3.0]]),
+            samples=Labels(["samples"], np.array([[0], [2]])),
+            components=[],
+            properties=Labels.arange("properties", 1),
+        )
+        keys = Labels(names=["key_1"], values=np.array([[0]]))
+        A = TensorMap(keys, [block_1])
+        B = TensorMap(keys, [block_2])
+
+        result = equistore.subtract(A, B, broadcast="properties")
+        self.assertTrue(np.allclose(result.block().values, np.array([[0, 1], [0, 3]])))
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.subtract(A, B, broadcast="components")
+        self.assertEqual(
+            str(cm.exception),
+            "invalid value for broadcast in 'subtract': expected 'samples' or "
+            "'properties', got 'components'",
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.subtract(A, 3.0, broadcast="properties")
+        self.assertEqual(
+            str(cm.exception),
+            "broadcast can only be used in 'subtract' with a TensorMap",
+        )
+
+
 # TODO: subtract tests with torch & torch scripting/tracing
 
 if __name__ == "__main__":
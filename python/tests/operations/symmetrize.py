@@ -0,0 +1,115 @@
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+def _pair_block():
+    rng = np.random.default_rng(3)
+
+    block = TensorBlock(
+        values=rng.normal(size=(4, 2)),
+        samples=Labels(
+            names=["structure", "first_atom", "second_atom"],
+            values=np.array([[0, 0, 0], [0, 0, 1], [0, 1, 0], [0, 1, 1]]),
+        ),
+        components=[],
+        properties=Labels(names=["n"], values=np.array([[0], [1]])),
+    )
+    block.add_gradient(
+        "positions",
+        data=rng.normal(size=(3, 3, 2)),
+        samples=Labels(
+            names=["sample", "structure", "atom"],
+            values=np.array([[1, 0, 0], [2, 0, 0], [2, 0, 1]]),
+        ),
+        components=[Labels(names=["direction"], values=np.array([[0], [1], [2]]))],
+    )
+    return block
+
+
+class TestSymmetrize(unittest.TestCase):
+    def setUp(self):
+        self.block = _pair_block()
+
+    def test_symmetric(self):
+        X = self.block.values
+        result = equistore.symmetrize_block(self.block, "first_atom", "second_atom")
+
+        self.assertEqual(result.samples.names, self.block.samples.names)
+        self.assertEqual(result.samples.tolist(), [(0, 0, 0), (0, 0, 1), (0, 1, 1)])
+        self.assertTrue(np.all(result.properties == self.block.properties))
+
+        expected = np.array([X[0], (X[1] + X[2]) / 2, X[3]])
+        self.assertTrue(np.allclose(result.values, expected))
+
+        gradient = result.gradient("positions")
+        data = self.block.gradient("positions").data
+        self.assertEqual(gradient.samples.tolist(), [(1, 0, 0), (1, 0, 1)])
+        expected = np.array([(data[0] + data[1]) / 2, data[2] / 2])
+        self.assertTrue(np.allclose(gradient.data, expected))
+
+    def test_antisymmetric(self):
+        X = self.block.values
+        result = equistore.symmetrize_block(
+            self.block, "first_atom", "second_atom", antisymmetric=True
+        )
+
+        expected = np.array([np.zeros(2), (X[1] - X[2]) / 2, np.zeros(2)])
+        self.assertTrue(np.allclose(result.values, expected))
+
+        gradient = result.gradient("positions")
+        data = self.block.gradient("positions").data
+        self.assertEqual(gradient.samples.tolist(), [(1, 0, 0), (1, 0, 1)])
+        expected = np.array([(data[0] - data[1]) / 2, -data[2] / 2])
+        self.assertTrue(np.allclose(gradient.data, expected))
+
+    def test_tensor(self):
+        keys = Labels(names=["key"], values=np.array([[0]]))
+        tensor = TensorMap(keys, [self.block.copy()])
+
+        result = equistore.symmetrize(tensor, "first_atom", "second_atom")
+        expected = equistore.symmetrize_block(self.block, "first_atom", "second_atom")
+        self.assertTrue(np.all(result.keys == tensor.keys))
+        self.assertTrue(equistore.equal_block(result.block(0), expected))
+
+    def test_errors(self):
+        with self.assertRaises(ValueError) as cm:
+            equistore.symmetrize_block(self.block, "first_atom", "third_atom")
+
+        self.assertEqual(
+            str(cm.exception),
+            "'third_atom' is not part of the samples of this block, available "
+            "names are ['structure', 'first_atom', 'second_atom']",
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.symmetrize_block(self.block, "first_atom", "first_atom")
+
+        self.assertEqual(
+            str(cm.exception), "`first` and `second` must be different sample names"
+        )
+
+        block = TensorBlock(
+            values=np.zeros((2, 1)),
+            samples=Labels(
+                names=["first_atom", "second_atom"],
+                values=np.array([[0, 0], [0, 1]]),
+            ),
+            components=[],
+            properties=Labels(names=["n"], values=np.array([[0]])),
+        )
+
+        with self.assertRaises(ValueError) as cm:
+            equistore.symmetrize_block(block, "first_atom", "second_atom")
+
+        self.assertEqual(
+            str(cm.exception),
+            "the sample (1, 0) corresponding to (0, 1) is missing from this block",
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
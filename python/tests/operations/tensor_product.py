@@ -0,0 +1,86 @@
+import unittest
+
+import numpy as np
+
+import equistore
+from equistore import Labels, TensorBlock, TensorMap
+
+
+class TestTensorProduct(unittest.TestCase):
+    def test_tensor_product(self):
+        first = TensorMap(
+            keys=Labels(["key"], np.array([[0], [1]])),
+            blocks=[
+                TensorBlock(
+                    values=np.array([[1.0, 2.0], [3.0, 4.0]]),
+                    samples=Labels(["samples"], np.array([[0], [1]])),
+                    components=[],
+                    properties=Labels(["n"], np.array([[0], [1]])),
+                ),
+                TensorBlock(
+                    values=np.array([[5.0], [6.0]]),
+                    samples=Labels(["samples"], np.array([[0], [1]])),
+                    components=[],
+                    properties=Labels(["n"], np.array([[0]])),
+                ),
+            ],
+        )
+        second = TensorMap(
+            keys=Labels(["key"], np.array([[1], [0]])),
+            blocks=[
+                TensorBlock(
+                    values=np.array([[1.0, -1.0], [2.0, -2.0]]),
+                    samples=Labels(["samples"], np.array([[0], [1]])),
+                    components=[],
+                    properties=Labels(["m"], np.array([[0], [1]])),
+                ),
+                TensorBlock(
+                    values=np.array([[1.0, 10.0, 100.0], [-1.0, -2.0, -3.0]]),
+                    samples=Labels(["samples"], np.array([[0], [1]])),
+                    components=[],
+                    properties=Labels(["m"], np.array([[0], [1], [2]])),
+                ),
+            ],
+        )
+
+        product = equistore.tensor_product(first, second)
+        self.assertTrue(np.all(product.keys == first.keys))
+
+        block = product.block(key=0)
+        self.assertTrue(np.all(block.samples == first.block(key=0).samples))
+        self.assertEqual(block.properties.names, ("first_n", "second_m"))
+        self.assertEqual(len(block.properties), 6)
+        expected = np.array(
+            [
+                [1.0, 10.0, 100.0, 2.0, 20.0, 200.0],
+                [-3.0, -6.0, -9.0, -4.0, -8.0, -12.0],
+            ]
+        )
+        np.testing.assert_equal(block.values, expected)
+
+        block = product.block(key=1)
+        self.assertEqual(block.properties.names, ("first_n", "second_m"))
+        np.testing.assert_equal(block.values, [[5.0, -5.0], [12.0, -12.0]])
+
+    def test_gradients_error(self):
+        block = TensorBlock(
+            values=np.array([[1.0], [2.0]]),
+            samples=Labels(["samples"], np.array([[0], [1]])),
+            components=[],
+            properties=Labels(["n"], np.array([[0]])),
+        )
+        block.add_gradient(
+            parameter="g",
+            data=np.array([[1.0]]),
+            samples=Labels(["sample"], np.array([[0]])),
+            components=[],
+        )
+        tensor = TensorMap(Labels.single(), [block])
+
+        message = "tensor_product does not support blocks with gradients"
+        with self.assertRaisesRegex(equistore.status.EquistoreError, message):
+            equistore.tensor_product(tensor, tensor)
+
+
+if __name__ == "__main__":
+    unittest.main()
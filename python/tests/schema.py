@@ -0,0 +1,78 @@
+import os
+
+import pytest
+from utils import tensor_map
+
+import equistore
+from equistore.schema import SPHERICAL_EXPANSION, SchemaError, TensorMapSchema
+
+
+ROOT = os.path.dirname(__file__)
+
+
+class TestSchema:
+    @pytest.fixture
+    def tensor(self):
+        return tensor_map()
+
+    def test_valid(self, tensor):
+        schema = TensorMapSchema(
+            keys=["key_1", "key_2"],
+            samples=["samples"],
+            components=[["components"]],
+            properties=["properties"],
+            gradients={"parameter": ["sample", "parameter"]},
+        )
+        schema.validate(tensor)
+
+        # unset parts of the schema are not checked
+        TensorMapSchema().validate(tensor)
+        TensorMapSchema(samples="samples").validate(tensor)
+
+    def test_keys(self, tensor):
+        schema = TensorMapSchema(keys=["key_1", "key_3"])
+        message = (
+            r"invalid names for keys: expected \('key_1', 'key_3'\), got "
+            r"\('key_1', 'key_2'\) \(missing 'key_3'; unexpected 'key_2'\)"
+        )
+        with pytest.raises(SchemaError, match=message):
+            schema.validate(tensor)
+
+        schema = TensorMapSchema(keys=["key_2", "key_1"])
+        with pytest.raises(SchemaError, match="not in the expected order"):
+            schema.validate(tensor)
+
+    def test_blocks(self, tensor):
+        schema = TensorMapSchema(components=[])
+        message = r"in block for key \(key_1=0, key_2=0\): expected 0 components, got 1"
+        with pytest.raises(SchemaError, match=message):
+            schema.validate(tensor)
+
+        schema = TensorMapSchema(properties=["n"])
+        message = "invalid names for properties: expected"
+        with pytest.raises(SchemaError, match=message):
+            schema.validate(tensor)
+
+        schema = TensorMapSchema(gradients={"positions": None})
+        message = "missing gradient with respect to 'positions'"
+        with pytest.raises(SchemaError, match=message):
+            schema.validate(tensor)
+
+        schema = TensorMapSchema(gradients={"parameter": ["sample", "atom"]})
+        message = "invalid names for samples of the 'parameter' gradient"
+        with pytest.raises(SchemaError, match=message):
+            schema.validate(tensor)
+
+        # SchemaError can be caught as a ValueError
+        with pytest.raises(ValueError):
+            schema.validate_block(tensor.block(0))
+
+    def test_spherical_expansion(self):
+        tensor = equistore.load(
+            os.path.join(ROOT, "..", "..", "equistore-core", "tests", "data.npz"),
+        )
+
+        # this file uses an older naming scheme for the keys
+        message = "missing 'species_center', 'species_neighbor'"
+        with pytest.raises(SchemaError, match=message):
+            SPHERICAL_EXPANSION.validate(tensor)
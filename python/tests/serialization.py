@@ -1,4 +1,5 @@
 import os
+import pickle
 
 import numpy as np
 import pytest
@@ -6,7 +7,7 @@ from numpy.testing import assert_equal
 from utils import tensor_map
 
 import equistore
-from equistore import TensorMap
+from equistore import Labels, TensorBlock, TensorMap
 
 
 ROOT = os.path.dirname(__file__)
@@ -64,3 +65,189 @@ class TestIo:
                 assert_equal(data[f"{prefix}/data"], gradient.data)
                 assert_equal(data[f"{prefix}/samples"], gradient.samples)
                 assert_equal(data[f"{prefix}/components/0"], gradient.components[0])
+
+    @pytest.mark.parametrize("use_numpy", (True, False))
+    def test_units(self, use_numpy, tmpdir):
+        block = TensorBlock(
+            values=np.full((3, 2), 1.0),
+            samples=Labels(["samples"], np.array([[0], [2], [4]])),
+            components=[],
+            properties=Labels(["properties"], np.array([[5], [3]])),
+        )
+        block.unit = "eV"
+        block.quantity = "energy"
+        tensor = TensorMap(Labels.single(), [block])
+
+        with tmpdir.as_cwd():
+            equistore.save("units.npz", tensor, use_numpy=use_numpy)
+            data = np.load("units.npz")
+            assert data["blocks/0/values/unit"] == "eV"
+            assert data["blocks/0/values/quantity"] == "energy"
+
+            for load_with_numpy in (True, False):
+                loaded = equistore.load("units.npz", use_numpy=load_with_numpy)
+                assert loaded.block(0).unit == "eV"
+                assert loaded.block(0).quantity == "energy"
+
+    @pytest.mark.parametrize("use_numpy", (True, False))
+    def test_metadata(self, use_numpy, tmpdir):
+        tensor = tensor_map()
+        tensor.set_metadata("version", "1.2.3")
+        tensor.set_metadata("cutoff", "4.5")
+
+        with tmpdir.as_cwd():
+            equistore.save("metadata.npz", tensor, use_numpy=use_numpy)
+            data = np.load("metadata.npz")
+            assert_equal(data["metadata/keys"], ["cutoff", "version"])
+            assert_equal(data["metadata/values"], ["4.5", "1.2.3"])
+
+            for load_with_numpy in (True, False):
+                loaded = equistore.load("metadata.npz", use_numpy=load_with_numpy)
+                assert loaded.metadata == {"cutoff": "4.5", "version": "1.2.3"}
+
+    @pytest.mark.parametrize("use_numpy", (True, False))
+    def test_little_endian(self, use_numpy, tmpdir):
+        tensor = tensor_map()
+        tensor.set_metadata("version", "1.2.3")
+
+        with tmpdir.as_cwd():
+            equistore.save("little-endian.npz", tensor, use_numpy=use_numpy)
+            data = np.load("little-endian.npz")
+
+            for name in data.keys():
+                for _, dtype in data[name].dtype.descr:
+                    assert dtype[0] in "<|", f"'{name}' is not little-endian"
+
+    @pytest.mark.parametrize("use_numpy", (True, False))
+    def test_load_big_endian(self, use_numpy, tmpdir):
+        # emulate a file created on a big-endian platform
+        tensor = tensor_map()
+
+        with tmpdir.as_cwd():
+            equistore.save("native.npz", tensor)
+            data = np.load("native.npz")
+            big_endian = {}
+            for name in data.keys():
+                array = data[name]
+                big_endian[name] = array.astype(array.dtype.newbyteorder(">"))
+            np.savez("big-endian.npz", **big_endian)
+
+            loaded = equistore.load("big-endian.npz", use_numpy=use_numpy)
+
+        assert equistore.equal(loaded, tensor)
+
+    @pytest.mark.parametrize("use_numpy", (True, False))
+    def test_sharded(self, use_numpy, tmpdir):
+        tensor = tensor_map()
+        tensor.set_metadata("version", "1.2.3")
+        n_blocks = len(tensor.keys)
+
+        with tmpdir.as_cwd():
+            # every block is larger than the limit, and goes in its own shard
+            equistore.save_sharded("small", tensor, 1, use_numpy=use_numpy)
+            shards = {f"shard-{i}.npz" for i in range(n_blocks)}
+            assert set(os.listdir("small")) == {"manifest.json"} | shards
+
+            loaded = equistore.load_sharded("small", use_numpy=use_numpy)
+            assert equistore.equal(loaded, tensor)
+            assert loaded.metadata == {"version": "1.2.3"}
+
+            # the first two blocks fit in the first shard
+            max_shard_bytes = sum(
+                equistore.io._block_nbytes(tensor.block(i)) for i in range(2)
+            )
+            equistore.save_sharded("grouped", tensor, max_shard_bytes)
+            shards = {f"shard-{i}.npz" for i in range(n_blocks - 1)}
+            assert set(os.listdir("grouped")) == {"manifest.json"} | shards
+
+            shard = equistore.load(os.path.join("grouped", "shard-0.npz"))
+            assert len(shard.keys) == 2
+
+            loaded = equistore.load_sharded("grouped", use_numpy=use_numpy)
+            assert equistore.equal(loaded, tensor)
+
+    def test_sharded_errors(self, tmpdir):
+        tensor = tensor_map()
+
+        message = "max_shard_bytes must be a positive integer, got 0"
+        with pytest.raises(ValueError, match=message):
+            equistore.save_sharded(str(tmpdir), tensor, 0)
+
+        with open(os.path.join(tmpdir, "manifest.json"), "w") as fd:
+            fd.write('{"format": "something-else"}')
+
+        message = "does not contain a sharded equistore TensorMap"
+        with pytest.raises(ValueError, match=message):
+            equistore.load_sharded(str(tmpdir))
+
+    def test_labels_dictionary(self, tmpdir):
+        tensor = tensor_map()
+
+        with tmpdir.as_cwd():
+            equistore.save("compressed.npz", tensor, labels_dictionary=True)
+            data = np.load("compressed.npz")
+            assert "keys.npy.zst" in data.files
+            assert "keys" not in data.files
+            assert "blocks/0/values/data" in data.files
+
+            loaded = equistore.load("compressed.npz")
+            assert equistore.equal(loaded, tensor)
+
+            message = "contains zstd-compressed labels"
+            with pytest.raises(ValueError, match=message):
+                equistore.load("compressed.npz", use_numpy=True)
+
+            message = "labels_dictionary=True is not supported with use_numpy=True"
+            with pytest.raises(ValueError, match=message):
+                equistore.save(
+                    "numpy.npz", tensor, use_numpy=True, labels_dictionary=True
+                )
+
+    def test_mantissa_bits(self, tmpdir):
+        tensor = tensor_map()
+
+        with tmpdir.as_cwd():
+            equistore.save("rounded.npz", tensor, mantissa_bits=10)
+            data = np.load("rounded.npz")
+            assert "blocks/0/values/data.npy.zst" in data.files
+            assert "blocks/0/values/data" not in data.files
+
+            loaded = equistore.load("rounded.npz")
+            assert equistore.allclose(loaded, tensor, rtol=2**-11)
+
+            message = "mantissa_bits must be between 1 and 52, got 60"
+            with pytest.raises(ValueError, match=message):
+                equistore.save("rounded.npz", tensor, mantissa_bits=60)
+
+            message = "mantissa_bits is not supported with use_numpy=True"
+            with pytest.raises(ValueError, match=message):
+                equistore.save("numpy.npz", tensor, use_numpy=True, mantissa_bits=10)
+
+    def test_buffer(self):
+        tensor = tensor_map()
+        tensor.set_metadata("version", "1.2.3")
+
+        buffer = equistore.save_buffer(tensor)
+        assert isinstance(buffer, bytes)
+
+        loaded = equistore.load_buffer(buffer)
+        assert equistore.equal(loaded, tensor)
+        assert loaded.metadata == {"version": "1.2.3"}
+
+        loaded = equistore.load_buffer(bytearray(buffer))
+        assert equistore.equal(loaded, tensor)
+
+    def test_buffer_same_as_file(self, tmpdir):
+        tensor = tensor_map()
+
+        with tmpdir.as_cwd():
+            equistore.save("file.npz", tensor)
+            with open("file.npz", "rb") as fd:
+                data = fd.read()
+
+        assert equistore.save_buffer(tensor) == data
+
+    def test_pickle(self):
+        tensor = tensor_map()
+        loaded = pickle.loads(pickle.dumps(tensor))
+        assert equistore.equal(loaded, tensor)
@@ -6,6 +6,7 @@ from numpy.testing import assert_equal
 from utils import large_tensor_map, tensor_map
 
 import equistore
+from equistore import Labels
 
 
 class TestTensorMap:
@@ -155,6 +156,23 @@ keys: ['key_1' 'key_2']
         assert_equal(blocks[0].values, np.full((3, 1, 1), 1.0))
         assert_equal(blocks[1].values, np.full((3, 1, 3), 2.0))
 
+    def test_view(self, tensor):
+        keys, blocks = tensor.view(Labels(["key_2"], np.array([[0]])))
+        assert keys.names == ("key_1", "key_2")
+        assert keys.tolist() == [(0, 0), (1, 0)]
+        assert len(blocks) == 2
+
+        assert_equal(blocks[0].values, np.full((3, 1, 1), 1.0))
+        assert_equal(blocks[1].values, np.full((3, 1, 3), 2.0))
+
+        # the blocks are views inside the tensor map
+        with pytest.raises(ValueError, match="from another tensor map"):
+            equistore.TensorMap(keys, blocks)
+
+        keys, blocks = tensor.view(Labels(["key_1"], np.array([[5]])))
+        assert len(keys) == 0
+        assert blocks == []
+
     def test_iter(self, tensor):
         expected = [
             ((0, 0), np.full((3, 1, 1), 1.0)),
@@ -343,6 +361,178 @@ keys: ['key_1' 'key_2']
         assert tuple(block.properties[1]) == (1, 0)
         assert tuple(block.properties[2]) == (2, 0)
 
+    def test_dense(self, tensor):
+        keys = equistore.Labels(["key"], np.array([[1], [3]], dtype=np.int32))
+        samples = equistore.Labels(
+            ["structure"], np.array([[0], [1], [2]], dtype=np.int32)
+        )
+        data = np.arange(2 * 3 * 4, dtype=np.float64).reshape(2, 3, 4)
+
+        dense = equistore.TensorMap.from_dense(keys, samples, data)
+        assert len(dense.keys) == 2
+        block = dense.block(key=3)
+        assert block.samples.names == ("structure",)
+        assert block.properties.names == ("property",)
+        assert len(block.properties) == 4
+        assert_equal(block.values, data[1])
+
+        assert_equal(dense.to_dense(), data)
+
+        message = (
+            r"invalid shape for dense data: expected \(2, 4, n_properties\) from "
+            r"the keys and samples, got \(2, 3, 4\)"
+        )
+        with pytest.raises(ValueError, match=message):
+            samples = equistore.Labels.arange("structure", 4)
+            equistore.TensorMap.from_dense(keys, samples, data)
+
+        # the test tensor has blocks with different samples and components
+        message = "can not convert this TensorMap to a dense array"
+        with pytest.raises(ValueError, match=message):
+            tensor.to_dense()
+
+        empty = equistore.TensorMap(equistore.Labels.empty(["key"]), [])
+        message = "can not convert an empty TensorMap to a dense array"
+        with pytest.raises(ValueError, match=message):
+            empty.to_dense()
+
+    def test_sort_keys(self, tensor):
+        # keys are (0, 0), (1, 0), (2, 2), (2, 3)
+        result, permutation = tensor.sort_keys(["key_2", "key_1"], descending=True)
+        assert permutation == [3, 2, 1, 0]
+        assert result.keys.tolist() == [(2, 3), (2, 2), (1, 0), (0, 0)]
+        assert_equal(result.block(0).values, tensor.block(3).values)
+
+        # blocks with the same key_2 keep their relative order
+        _, permutation = tensor.sort_keys("key_2")
+        assert permutation == [0, 1, 2, 3]
+
+        with pytest.raises(
+            equistore.status.EquistoreError,
+            match="'foo' is not part of the keys for this tensor map",
+        ):
+            tensor.sort_keys("foo")
+
+    def test_canonicalize(self):
+        block_1 = equistore.TensorBlock(
+            values=np.array([[1.0, 2.0], [3.0, 4.0]]),
+            samples=Labels(["samples"], np.array([[2], [0]])),
+            components=[],
+            properties=Labels(["properties"], np.array([[1], [-1]])),
+        )
+        block_2 = equistore.TensorBlock(
+            values=np.array([[5.0]]),
+            samples=Labels(["samples"], np.array([[1]])),
+            components=[],
+            properties=Labels(["properties"], np.array([[0]])),
+        )
+        tensor = equistore.TensorMap(
+            Labels(["key"], np.array([[3], [1]])), [block_1, block_2]
+        )
+
+        canonical = tensor.canonicalize()
+        assert canonical.keys.tolist() == [(1,), (3,)]
+
+        block = canonical.block(key=3)
+        assert block.samples.tolist() == [(0,), (2,)]
+        assert block.properties.tolist() == [(-1,), (1,)]
+        assert_equal(block.values, [[4.0, 3.0], [2.0, 1.0]])
+
+    def test_fingerprint(self, tensor):
+        with_data = tensor.fingerprint()
+        without_data = tensor.fingerprint(include_data=False)
+        assert with_data != without_data
+        assert tensor.copy().fingerprint() == with_data
+
+        tensor.block(0).values[0, 0, 0] = 42.0
+        assert tensor.fingerprint() != with_data
+        assert tensor.fingerprint(include_data=False) == without_data
+
+    def test_with_values(self, tensor):
+        values = [np.full(block.values.shape, 3.0) for _, block in tensor]
+        new_tensor = tensor.with_values(values)
+
+        assert_equal(new_tensor.keys, tensor.keys)
+        for i, (_, block) in enumerate(new_tensor):
+            assert_equal(block.samples, tensor.block(i).samples)
+            assert block.gradients_list() == tensor.block(i).gradients_list()
+            assert_equal(block.values, values[i])
+
+        with pytest.raises(equistore.status.EquistoreError):
+            tensor.with_values(values[:-1])
+
+    def test_memory_usage(self, tensor):
+        usage = tensor.memory_usage()
+        assert len(usage["blocks"]) == len(tensor.keys)
+
+        block = tensor.block(0)
+        assert usage["blocks"][0]["data"] == block.values.size * 8
+        assert usage["blocks"][0]["gradients"] > 0
+        assert usage["keys"]["data"] == 0
+        assert usage["keys"]["labels_values"] > 0
+
+        data = sum(block.values.size * 8 for _, block in tensor)
+        assert usage["total"]["data"] == data
+
+    def test_compress_blocks(self):
+        blocks = [
+            equistore.TensorBlock(
+                values=np.zeros((2, 100)),
+                samples=Labels(["samples"], np.array([[0], [1]])),
+                components=[],
+                properties=Labels(["properties"], np.arange(100).reshape(-1, 1)),
+            )
+            for _ in range(2)
+        ]
+        tensor = equistore.TensorMap(Labels(["key"], np.array([[0], [1]])), blocks)
+        usage = tensor.memory_usage()
+
+        tensor.compress_blocks(Labels(["key"], np.array([[1]])))
+        assert tensor.memory_usage()["blocks"][0] == usage["blocks"][0]
+        assert tensor.memory_usage()["blocks"][1]["data"] < usage["blocks"][1]["data"]
+
+        tensor.decompress_blocks()
+        assert tensor.memory_usage()["blocks"][1] == usage["blocks"][1]
+        assert_equal(tensor.block(1).values, np.zeros((2, 100)))
+
+    def test_compact_labels(self, tensor):
+        reference = tensor.copy()
+        tensor.compact_labels()
+
+        assert_equal(tensor.keys, reference.keys)
+        for key, block in tensor:
+            reference_block = reference.block(key)
+            assert_equal(block.samples, reference_block.samples)
+            assert_equal(block.properties, reference_block.properties)
+            assert_equal(block.values, reference_block.values)
+
+            for parameter, gradient in block.gradients():
+                reference_gradient = reference_block.gradient(parameter)
+                assert_equal(gradient.samples, reference_gradient.samples)
+                assert_equal(gradient.data, reference_gradient.data)
+
+    def test_metadata(self, tensor):
+        assert tensor.metadata == {}
+
+        tensor.set_metadata("version", "1.0")
+        tensor.set_metadata("author", "someone")
+        tensor.set_metadata("version", "2.0")
+        assert tensor.metadata == {"author": "someone", "version": "2.0"}
+
+        # the returned dictionary is a copy
+        tensor.metadata["other"] = "value"
+        assert "other" not in tensor.metadata
+
+        clone = tensor.copy()
+        assert clone.metadata == {"author": "someone", "version": "2.0"}
+
+        tensor.set_metadata("author", None)
+        assert tensor.metadata == {"version": "2.0"}
+        assert clone.metadata == {"author": "someone", "version": "2.0"}
+
+        with pytest.raises(equistore.status.EquistoreError, match="can not be empty"):
+            tensor.set_metadata("", "value")
+
     def test_eq(self, tensor):
         assert equistore.equal(tensor, tensor) == (tensor == tensor)
 
@@ -369,3 +559,31 @@ keys: ['key_1' 'key_2']
 
     def test_pow(self, tensor):
         assert equistore.pow(tensor, 2) == (tensor**2)
+
+    def test_dtype(self, tensor):
+        assert tensor.dtype == np.float64
+        assert tensor.block(0).dtype == np.float64
+
+        def create_tensor(dtypes):
+            keys = equistore.Labels(names=["key"], values=np.array([[0], [1]]))
+            blocks = []
+            for dtype in dtypes:
+                blocks.append(
+                    equistore.TensorBlock(
+                        values=np.ones((2, 3), dtype=dtype),
+                        samples=equistore.Labels.arange("sample", 2),
+                        components=[],
+                        properties=equistore.Labels.arange("property", 3),
+                    )
+                )
+            return equistore.TensorMap(keys, blocks)
+
+        mixed = create_tensor([np.float32, np.float64])
+        assert mixed.dtype == "mixed"
+        assert mixed.block(0).dtype == np.float32
+        assert mixed.block(1).dtype == np.float64
+
+        # float32 + float64 => float64
+        result = equistore.add(mixed, create_tensor([np.float64, np.float64]))
+        assert result.dtype == np.float64
+        assert_equal(result.block(0).values, np.full((2, 3), 2.0))
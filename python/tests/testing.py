@@ -0,0 +1,67 @@
+import numpy as np
+import pytest
+
+from equistore import Labels, TensorBlock, TensorMap
+from equistore.testing import check_finite_differences
+
+
+def squared_norm(positions, factor=2.0):
+    """
+    Compute the squared norm of each atom position, with gradients with respect
+    to positions. ``factor`` should be 2 to get the right gradients.
+    """
+    n_atoms = positions.shape[1]
+    block = TensorBlock(
+        values=np.sum(positions[0] ** 2, axis=1).reshape(-1, 1),
+        samples=Labels(
+            ["structure", "atom"],
+            np.array([[0, i] for i in range(n_atoms)], dtype=np.int32),
+        ),
+        components=[],
+        properties=Labels.arange("property", 1),
+    )
+
+    block.add_gradient(
+        "positions",
+        data=factor * positions[0].reshape(n_atoms, 3, 1),
+        samples=Labels(
+            ["sample", "structure", "atom"],
+            np.array([[i, 0, i] for i in range(n_atoms)], dtype=np.int32),
+        ),
+        components=[Labels.arange("xyz", 3)],
+    )
+
+    return TensorMap(Labels.single(), [block])
+
+
+class TestFiniteDifferences:
+    def test_correct_gradients(self):
+        positions = np.random.rand(1, 4, 3)
+        reports = check_finite_differences(squared_norm, positions, "positions")
+
+        assert len(reports) == 1
+        assert reports[0].passed
+        assert reports[0].key == {"_": 0}
+        assert reports[0].max_absolute_error < 1e-6
+
+    def test_wrong_gradients(self):
+        positions = np.random.rand(1, 4, 3) + 0.5
+        reports = check_finite_differences(
+            lambda x: squared_norm(x, factor=3.0), positions, "positions"
+        )
+
+        assert not reports[0].passed
+        assert reports[0].max_absolute_error > 0.1
+        assert "FAILED" in repr(reports[0])
+
+    def test_errors(self):
+        positions = np.random.rand(1, 4, 3)
+        with pytest.raises(ValueError, match="missing gradient with respect to 'cell'"):
+            check_finite_differences(squared_norm, positions, "cell")
+
+        with pytest.raises(ValueError, match="can not match the gradient"):
+            check_finite_differences(
+                lambda x: squared_norm(x.reshape(1, 4, 3)),
+                positions.reshape(-1),
+                "positions",
+            )